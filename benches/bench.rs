@@ -1,5 +1,8 @@
 mod lp;
+mod vector_ops;
 
 fn main() {
-    divan::main();
+    if !lp::profile::run() {
+        divan::main();
+    }
 }
@@ -1 +1,3 @@
-mod netlib;
+pub mod netlib;
+pub mod profile;
+pub mod transpose;
@@ -1,71 +1,96 @@
-use copters::linalg::cholesky::SimplicialSparseCholesky;
-use copters::linalg::cholesky::SupernodalSparseCholesky;
-use copters::lp;
-use copters::lp::{LPSolver, mpc::MehrotraPredictorCorrector};
+use copters::{
+    SolverState,
+    lp::{LPSolverType, LinearProgram},
+};
 
-trait SolverBuilder {
-    type Solver: LPSolver<'static>;
+use super::profile::{self, ProfileRecord};
+
+pub trait SolverBuilder {
+    const NAME: &'static str;
+    const SOLVER_TYPE: LPSolverType;
 }
 
 macro_rules! solver_builder {
-    ($name:ident, $solver_type:ty) => {
-        struct $name;
+    ($name:ident, $solver_type:expr) => {
+        pub struct $name;
 
         impl SolverBuilder for $name {
-            type Solver = $solver_type;
+            const NAME: &'static str = stringify!($name);
+            const SOLVER_TYPE: LPSolverType = $solver_type;
         }
     };
 }
 
 // Add any additional solvers to benchmark here
-solver_builder!(
-    MPCSimplicial,
-    MehrotraPredictorCorrector<
-        'static,
-        SimplicialSparseCholesky,
-        lp::mpc::augmented_system::SlackReducedSystem<'static, SimplicialSparseCholesky>,
-        lp::mpc::mu_update::AdaptiveMuUpdate<'static>,
-    >
-);
-solver_builder!(
-    MPCSupernodal,
-    MehrotraPredictorCorrector<
-        'static,
-        SupernodalSparseCholesky,
-        lp::mpc::augmented_system::SlackReducedSystem<'static, SupernodalSparseCholesky>,
-        lp::mpc::mu_update::AdaptiveMuUpdate<'static>,
-    >
-);
+solver_builder!(MPCSimplicial, LPSolverType::MpcSimplicialCholesky);
+solver_builder!(MPCSupernodal, LPSolverType::MpcSupernodalCholesky);
+
+/// Solvers included in the profile run, in the same order as the bench `types`.
+pub const SOLVERS: &[(&str, LPSolverType)] = &[
+    (MPCSimplicial::NAME, MPCSimplicial::SOLVER_TYPE),
+    (MPCSupernodal::NAME, MPCSupernodal::SOLVER_TYPE),
+];
+
+fn bench_case<S: SolverBuilder>(bencher: divan::Bencher, name: &str) {
+    let lp = profile::load_netlib_case(name).unwrap();
+    bencher
+        .with_inputs(|| {
+            SolverState::new_interior(
+                lp.get_lower_bounds(),
+                lp.get_upper_bounds(),
+                lp.get_n_cons(),
+            )
+        })
+        .bench_local_values(|mut state| profile::solve(&lp, S::SOLVER_TYPE, &mut state));
+}
+
+/// Solves a single case once and records the outcome for the profile.
+pub fn profile_case(name: &str, solver: &str, solver_type: LPSolverType) -> ProfileRecord {
+    let lp: LinearProgram = match profile::load_netlib_case(name) {
+        Ok(lp) => lp,
+        Err(e) => return ProfileRecord::failed(name, solver, &format!("{e}")),
+    };
+    let mut state = SolverState::new_interior(
+        lp.get_lower_bounds(),
+        lp.get_upper_bounds(),
+        lp.get_n_cons(),
+    );
+
+    let start = std::time::Instant::now();
+    let status = profile::solve(&lp, solver_type, &mut state);
+    let elapsed = start.elapsed();
+
+    match status {
+        Ok(status) => ProfileRecord::new(name, solver, status, state.get_nit() + 1, elapsed),
+        Err(e) => ProfileRecord::failed(name, solver, &format!("{e}")),
+    }
+}
 
 macro_rules! netlib_benches {
-    (@bench $case:ident = $name:literal) => {
-        #[divan::bench(
-            types = [
-                MPCSimplicial,
-                MPCSupernodal,
-            ]
-        )]
-        fn $case<S: SolverBuilder>() -> Result<(), String> {
-            let _lp = loaders::netlib::get_case($name)?;
-            Ok(())
-        }
+    (@name $case:ident = $name:literal) => {
+        $name
     };
-    (@bench $case:ident) => {
+    (@name $case:ident) => {
+        stringify!($case)
+    };
+    (@bench $case:ident $(= $name:literal)?) => {
         #[divan::bench(
             types = [
                 MPCSimplicial,
                 MPCSupernodal,
             ]
         )]
-        fn $case<S: SolverBuilder>() -> Result<(), String> {
-            let _lp = loaders::netlib::get_case(stringify!($case))?;
-            Ok(())
+        fn $case<S: SolverBuilder>(bencher: divan::Bencher) {
+            bench_case::<S>(bencher, netlib_benches!(@name $case $(= $name)?));
         }
     };
     ($($case:ident $(= $name:literal)?),* $(,)?) => {
         $(
             netlib_benches!(@bench $case $(= $name)?);
         )*
+
+        /// Netlib cases covered by the benches.
+        pub const CASES: &[&str] = &[$(netlib_benches!(@name $case $(= $name)?)),*];
     };
 }
 
@@ -103,7 +128,7 @@ netlib_benches!(
     forplan,
     fv47_25 = "25fv47",
     ganges,
-    gfrd_pnc,
+    gfrd_pnc = "gfrd-pnc",
     greenbea,
     greenbeb,
     grow15,
@@ -112,7 +137,7 @@ netlib_benches!(
     israel,
     kb2,
     lotfi,
-    maros_r7,
+    maros_r7 = "maros-r7",
     maros,
     modszk1,
     nesm,
@@ -159,7 +184,7 @@ netlib_benches!(
     stocfor1,
     stocfor2,
     tuff,
-    vtp_base,
+    vtp_base = "vtp.base",
     wood1p,
     woodw,
 );
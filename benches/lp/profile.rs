@@ -0,0 +1,234 @@
+//! Performance profile of the LP solvers on the Netlib cases.
+//!
+//! Instead of the divan timings, a profile run solves every case once per
+//! solver and records the status, iteration count, and wall time into a JSON
+//! artifact. The run is controlled through environment variables, since divan
+//! owns the command line:
+//!
+//! - `COPTERS_PROFILE_OUTPUT`: path of the JSON artifact to write. Setting it
+//!   selects the profile run.
+//! - `COPTERS_PROFILE_BASELINE`: path of a previously written artifact. Every
+//!   regression against it is reported and the process exits with an error.
+//! - `COPTERS_PROFILE_TIME_TOLERANCE`: allowed relative slowdown before a case
+//!   is flagged (default `0.25`).
+//! - `COPTERS_PROFILE_CASES`: comma-separated subset of cases to run.
+
+use std::{collections::HashMap, path::Path, time::Duration};
+
+use copters::{
+    SolverHooks, SolverOptions, SolverState, Status,
+    callback::NoOpCallback,
+    data_loaders,
+    lp::{LPSolverType, LinearProgram},
+    terminators::ConvergenceTerminator,
+};
+use problemo::Problem;
+use serde::{Deserialize, Serialize};
+
+use super::netlib;
+
+const DEFAULT_TIME_TOLERANCE: f64 = 0.25;
+
+/// Outcome of solving a single case with a single solver.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileRecord {
+    case: String,
+    solver: String,
+    status: String,
+    iterations: Option<usize>,
+    time_secs: Option<f64>,
+    error: Option<String>,
+}
+
+impl ProfileRecord {
+    pub fn new(
+        case: &str,
+        solver: &str,
+        status: Status,
+        iterations: usize,
+        elapsed: Duration,
+    ) -> Self {
+        Self {
+            case: case.to_string(),
+            solver: solver.to_string(),
+            status: format!("{status:?}"),
+            iterations: Some(iterations),
+            time_secs: Some(elapsed.as_secs_f64()),
+            error: None,
+        }
+    }
+
+    pub fn failed(case: &str, solver: &str, error: &str) -> Self {
+        Self {
+            case: case.to_string(),
+            solver: solver.to_string(),
+            status: "Error".to_string(),
+            iterations: None,
+            time_secs: None,
+            error: Some(error.to_string()),
+        }
+    }
+
+    fn key(&self) -> (&str, &str) {
+        (&self.case, &self.solver)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Profile {
+    records: Vec<ProfileRecord>,
+}
+
+impl Profile {
+    fn read(path: &Path) -> Result<Self, String> {
+        let data = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read profile '{}': {e}", path.display()))?;
+        serde_json::from_str(&data)
+            .map_err(|e| format!("Failed to parse profile '{}': {e}", path.display()))
+    }
+
+    fn write(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create '{}': {e}", parent.display()))?;
+        }
+        let data = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, data)
+            .map_err(|e| format!("Failed to write profile '{}': {e}", path.display()))
+    }
+
+    /// Lists every case that got worse relative to `baseline`.
+    ///
+    /// A case regresses if it no longer solves to optimality, needs more
+    /// iterations, or is slower by more than `time_tolerance` (relative).
+    fn compare(&self, baseline: &Profile, time_tolerance: f64) -> Vec<String> {
+        let baseline = baseline
+            .records
+            .iter()
+            .map(|record| (record.key(), record))
+            .collect::<HashMap<_, _>>();
+
+        let mut regressions = Vec::new();
+        for current in &self.records {
+            let Some(base) = baseline.get(&current.key()) else {
+                continue;
+            };
+            let (case, solver) = current.key();
+
+            if base.status != current.status {
+                regressions.push(format!(
+                    "{case} [{solver}]: status changed from {} to {}",
+                    base.status, current.status
+                ));
+                continue;
+            }
+
+            if let (Some(base_nit), Some(nit)) = (base.iterations, current.iterations)
+                && nit > base_nit
+            {
+                regressions.push(format!(
+                    "{case} [{solver}]: iterations increased from {base_nit} to {nit}"
+                ));
+            }
+
+            if let (Some(base_time), Some(time)) = (base.time_secs, current.time_secs)
+                && time > base_time * (1. + time_tolerance)
+            {
+                regressions.push(format!(
+                    "{case} [{solver}]: time increased from {base_time:.3}s to {time:.3}s"
+                ));
+            }
+        }
+        regressions
+    }
+}
+
+pub fn load_netlib_case(name: &str) -> Result<LinearProgram, Problem> {
+    data_loaders::sif::netlib::read_case(name)
+}
+
+pub fn solve(
+    lp: &LinearProgram,
+    solver_type: LPSolverType,
+    state: &mut SolverState,
+) -> Result<Status, Problem> {
+    let options = SolverOptions::new();
+    let mut hooks = SolverHooks::new(
+        Box::new(NoOpCallback::new()),
+        Box::new(ConvergenceTerminator::new(&options)),
+    );
+
+    let mut solver = lp
+        .solver_builder()
+        .with_solver(solver_type)
+        .with_options(options)
+        .build()?;
+    solver.solve(state, &mut hooks)
+}
+
+/// Runs the profile if `COPTERS_PROFILE_OUTPUT` is set. Returns `false` otherwise.
+pub fn run() -> bool {
+    let Ok(output) = std::env::var("COPTERS_PROFILE_OUTPUT") else {
+        return false;
+    };
+
+    let selected = std::env::var("COPTERS_PROFILE_CASES")
+        .ok()
+        .map(|cases| cases.split(',').map(str::to_string).collect::<Vec<_>>());
+
+    let mut profile = Profile::default();
+    for case in netlib::CASES {
+        if let Some(selected) = &selected
+            && !selected.iter().any(|s| s.eq_ignore_ascii_case(case))
+        {
+            continue;
+        }
+        for (solver, solver_type) in netlib::SOLVERS {
+            let record = netlib::profile_case(case, solver, *solver_type);
+            println!(
+                "{case:<12} {solver:<16} {:<16} {:>6} {:>10}",
+                record.status,
+                record
+                    .iterations
+                    .map_or("-".to_string(), |nit| nit.to_string()),
+                record
+                    .time_secs
+                    .map_or("-".to_string(), |t| format!("{t:.4}s")),
+            );
+            profile.records.push(record);
+        }
+    }
+
+    if let Err(e) = profile.write(Path::new(&output)) {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+    println!("Profile written to {output}");
+
+    if let Ok(baseline) = std::env::var("COPTERS_PROFILE_BASELINE") {
+        let tolerance = std::env::var("COPTERS_PROFILE_TIME_TOLERANCE")
+            .ok()
+            .and_then(|t| t.parse().ok())
+            .unwrap_or(DEFAULT_TIME_TOLERANCE);
+
+        let baseline = match Profile::read(Path::new(&baseline)) {
+            Ok(baseline) => baseline,
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        };
+
+        let regressions = profile.compare(&baseline, tolerance);
+        if !regressions.is_empty() {
+            eprintln!("Found {} regression(s):", regressions.len());
+            for regression in &regressions {
+                eprintln!("  {regression}");
+            }
+            std::process::exit(1);
+        }
+        println!("No regressions against baseline");
+    }
+
+    true
+}
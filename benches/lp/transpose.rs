@@ -0,0 +1,27 @@
+//! Row-major copies of the constraint matrix, built on every use against the
+//! copy cached in `LinearProgram`.
+
+use copters::lp::LinearProgram;
+use divan::{Bencher, black_box};
+
+use super::profile;
+
+const CASES: &[&str] = &["grow22", "maros-r7"];
+
+#[divan::bench(args = CASES)]
+fn to_row_major(bencher: Bencher, name: &str) {
+    let lp: LinearProgram = profile::load_netlib_case(name).unwrap();
+    bencher.bench_local(|| {
+        black_box(&lp)
+            .get_constraint_matrix()
+            .to_row_major()
+            .unwrap()
+    });
+}
+
+#[divan::bench(args = CASES)]
+fn cached_row_major(bencher: Bencher, name: &str) {
+    let lp: LinearProgram = profile::load_netlib_case(name).unwrap();
+    lp.get_constraint_matrix_csr();
+    bencher.bench_local(|| black_box(&lp).get_constraint_matrix_csr().compute_nnz());
+}
@@ -0,0 +1,86 @@
+//! Fused kernels of `linalg::vector_ops` against the expressions they replace
+//! in the interior-point iterations.
+
+use copters::linalg::vector_ops;
+use divan::{Bencher, black_box};
+use faer::Col;
+
+const SIZES: &[usize] = &[1_000, 100_000];
+
+/// Iterate with a tenth of the lower bounds infinite.
+fn inputs(n: usize) -> (Col<f64>, Col<f64>, Col<f64>, Col<f64>) {
+    let x = Col::from_fn(n, |i| 1. + (i % 7) as f64);
+    let dx = Col::from_fn(n, |i| (i % 5) as f64 - 2.);
+    let l = Col::from_fn(n, |i| if i % 10 == 0 { f64::NEG_INFINITY } else { 0. });
+    let z = Col::from_fn(n, |i| 1. / (1. + i as f64));
+    (x, dx, l, z)
+}
+
+#[divan::bench(args = SIZES)]
+fn axpy_naive(bencher: Bencher, n: usize) {
+    let (x, dx, ..) = inputs(n);
+    bencher
+        .with_inputs(|| x.clone())
+        .bench_local_values(|mut x| {
+            x += black_box(0.5) * &dx;
+            x
+        });
+}
+
+#[divan::bench(args = SIZES)]
+fn axpy_fused(bencher: Bencher, n: usize) {
+    let (x, dx, ..) = inputs(n);
+    bencher
+        .with_inputs(|| x.clone())
+        .bench_local_values(|mut x| {
+            vector_ops::axpy(black_box(0.5), dx.as_ref(), x.as_mut());
+            x
+        });
+}
+
+#[divan::bench(args = SIZES)]
+fn min_ratio_naive(bencher: Bencher, n: usize) {
+    let (x, dx, ..) = inputs(n);
+    bencher.bench_local(|| {
+        let mut alpha = f64::INFINITY;
+        for (&x, &dx) in x.iter().zip(dx.iter()) {
+            if dx < 0. && x.is_finite() {
+                alpha = alpha.min(-x / dx);
+            }
+        }
+        alpha
+    });
+}
+
+#[divan::bench(args = SIZES)]
+fn min_ratio_fused(bencher: Bencher, n: usize) {
+    let (x, dx, ..) = inputs(n);
+    bencher.bench_local(|| vector_ops::min_ratio(x.as_ref(), dx.as_ref()));
+}
+
+#[divan::bench(args = SIZES)]
+fn complementarity_naive(bencher: Bencher, n: usize) {
+    let (x, _, l, z) = inputs(n);
+    bencher.bench_local(|| {
+        let xl = &x - &l;
+        Col::from_fn(n, |i| if xl[i].is_finite() { z[i] * xl[i] } else { 0. }).sum()
+    });
+}
+
+#[divan::bench(args = SIZES)]
+fn complementarity_fused(bencher: Bencher, n: usize) {
+    let (x, _, l, z) = inputs(n);
+    bencher.bench_local(|| vector_ops::complementarity(x.as_ref(), l.as_ref(), z.as_ref()));
+}
+
+#[divan::bench(args = SIZES)]
+fn norm_masked_naive(bencher: Bencher, n: usize) {
+    let (x, _, l, _) = inputs(n);
+    bencher.bench_local(|| Col::from_fn(n, |i| if l[i].is_finite() { x[i] } else { 0. }).norm_l2());
+}
+
+#[divan::bench(args = SIZES)]
+fn norm_masked_fused(bencher: Bencher, n: usize) {
+    let (x, _, l, _) = inputs(n);
+    bencher.bench_local(|| vector_ops::norm_l2_masked(x.as_ref(), l.as_ref()));
+}
@@ -0,0 +1,82 @@
+//! Mean-variance portfolio optimization.
+//!
+//! ```text
+//! min  x^T S x
+//! s.t. sum_k x_k = 1
+//!      mu^T x >= r
+//!      0 <= x_k <= 0.5
+//! ```
+//!
+//! for the covariance `S` and expected returns `mu` of a few assets. The
+//! efficient frontier is traced by raising the target return `r`, warm starting
+//! each solve from the previous portfolio.
+//!
+//! Run with `cargo run --example portfolio`.
+
+use copters::{E, SolverHooks, model::ModelBuilder};
+use problemo::Problem;
+
+fn main() -> Result<(), Problem> {
+    let assets = ["bonds", "equities", "real estate", "commodities"];
+    let mu = [0.03, 0.08, 0.06, 0.05];
+    let covariance = [
+        [0.0016, 0.0002, 0.0004, 0.0001],
+        [0.0002, 0.0400, 0.0120, 0.0060],
+        [0.0004, 0.0120, 0.0225, 0.0030],
+        [0.0001, 0.0060, 0.0030, 0.0300],
+    ];
+
+    let mut builder = ModelBuilder::new();
+    let weights: Vec<_> = assets
+        .iter()
+        .map(|&asset| builder.add_variable(asset, 0., 0.5))
+        .collect();
+    for (i, &x_i) in weights.iter().enumerate() {
+        for (j, &x_j) in weights.iter().enumerate().skip(i) {
+            // x^T S x counts the off-diagonal covariances twice
+            let scale = if i == j { 1. } else { 2. };
+            builder.add_quadratic_term(x_i, x_j, scale * covariance[i][j]);
+        }
+    }
+    let budget: Vec<_> = weights.iter().map(|&x| (x, 1.)).collect();
+    builder.add_constraint("budget", &budget, 1., 1.);
+    let returns: Vec<_> = weights.iter().zip(mu).map(|(&x, mu)| (x, mu)).collect();
+    let target = builder.add_constraint("return", &returns, 0.04, E::INFINITY);
+
+    println!(
+        "{:>8} {:>10} {:>12}  weights ({})",
+        "return",
+        "risk",
+        "dual",
+        assets.join(", ")
+    );
+    let mut previous = None;
+    for k in 0..=6 {
+        let r = 0.04 + 0.005 * k as E;
+        builder.set_constraint_bounds(target, r, E::INFINITY);
+        let model = builder.build()?;
+
+        let mut hooks = SolverHooks::silent();
+        let solution = match &previous {
+            Some(previous) => model.solve_from(previous, &mut hooks)?,
+            None => model.solve(&mut hooks)?,
+        };
+        if !solution.is_optimal() {
+            println!("{r:>8.3} {:?}", solution.get_status());
+            break;
+        }
+
+        let weights: Vec<_> = weights
+            .iter()
+            .map(|&x| format!("{:.3}", solution.get_value(x)))
+            .collect();
+        println!(
+            "{r:>8.3} {:>10.5} {:>12.5}  {}",
+            solution.get_objective_value().sqrt(),
+            solution.get_dual(target),
+            weights.join(" ")
+        );
+        previous = Some(solution);
+    }
+    Ok(())
+}
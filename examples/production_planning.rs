@@ -0,0 +1,127 @@
+//! Production planning with a warm restart after a change of capacity.
+//!
+//! A workshop makes tables, chairs and desks from wood and labor:
+//!
+//! ```text
+//! max  sum_k profit_k x_k
+//! s.t. sum_k usage_rk x_k <= capacity_r    for every resource r
+//!      0 <= x_k <= demand_k
+//! ```
+//!
+//! After the first plan, overtime raises the labor capacity. The changed model
+//! has the same variables and rows, so it is solved from the previous solution
+//! instead of from scratch. A custom callback records the objective at every
+//! iteration of both solves.
+//!
+//! Run with `cargo run --example production_planning`.
+
+use std::sync::{Arc, Mutex};
+
+use copters::{
+    E, SolverHooks, SolverOptions, SolverState, StateView,
+    callback::Callback,
+    model::{Model, ModelBuilder, Sense, Solution},
+    terminators::ConvergenceTerminator,
+};
+use problemo::Problem;
+
+/// Records the objective value of every iteration. Clones share the record, so
+/// it can be read after the hooks took ownership of the callback.
+#[derive(Debug, Clone, Default)]
+struct ObjectiveTrace {
+    objectives: Arc<Mutex<Vec<E>>>,
+}
+
+impl Callback for ObjectiveTrace {
+    fn init(&mut self, _state: &SolverState) {
+        self.objectives.lock().unwrap().clear();
+    }
+
+    fn call(&mut self, state: &SolverState) {
+        if let Some(objective) = StateView::get_objective(state) {
+            self.objectives.lock().unwrap().push(objective);
+        }
+    }
+}
+
+fn report(title: &str, model: &Model, solution: &Solution, trace: &ObjectiveTrace) {
+    println!("{title}: {:?}", solution.get_status());
+    // The solver minimizes the negated profit
+    let objectives = trace.objectives.lock().unwrap();
+    println!(
+        "  {} iterations, objective per iteration: {}",
+        objectives.len(),
+        objectives
+            .iter()
+            .map(|objective| format!("{:.1}", -objective))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    println!("  profit {:.2}", solution.get_objective_value());
+    for (name, value) in model
+        .get_variable_names()
+        .iter()
+        .zip(solution.get_values().iter())
+    {
+        println!("  {name:<8} {value:>8.2}");
+    }
+}
+
+fn main() -> Result<(), Problem> {
+    let products = ["tables", "chairs", "desks"];
+    let profit = [70., 30., 100.];
+    let demand = [40., 120., 25.];
+    let resources = [
+        ("wood", [30., 10., 50.], 2400.),
+        ("labor", [5., 3., 6.], 420.),
+    ];
+
+    let options = SolverOptions::new();
+    let mut builder = ModelBuilder::new()
+        .with_sense(Sense::Maximize)
+        .with_options(options.clone());
+    let x: Vec<_> = products
+        .iter()
+        .zip(demand)
+        .map(|(&name, limit)| builder.add_variable(name, 0., limit))
+        .collect();
+    for (&x_k, profit_k) in x.iter().zip(profit) {
+        builder.set_cost(x_k, profit_k);
+    }
+    let rows: Vec<_> = resources
+        .iter()
+        .map(|&(name, usage, capacity)| {
+            let terms: Vec<_> = x.iter().copied().zip(usage).collect();
+            builder.add_constraint(name, &terms, -E::INFINITY, capacity)
+        })
+        .collect();
+
+    let trace = ObjectiveTrace::default();
+    let mut hooks = SolverHooks::new(
+        Box::new(trace.clone()),
+        Box::new(ConvergenceTerminator::new(&options)),
+    );
+
+    let model = builder.build()?;
+    let plan = model.solve(&mut hooks)?;
+    report("Initial plan", &model, &plan, &trace);
+    for (&row, (name, _, capacity)) in rows.iter().zip(resources) {
+        println!(
+            "  {name:<8} uses {:>7.1} of {capacity:>7.1}, shadow price {:.3}",
+            plan.get_activity(row),
+            plan.get_dual(row)
+        );
+    }
+
+    // Overtime adds 60 hours of labor
+    let labor = rows[1];
+    builder.set_constraint_bounds(labor, -E::INFINITY, resources[1].2 + 60.);
+    let model = builder.build()?;
+    let replan = model.solve_from(&plan, &mut hooks)?;
+    report("\nPlan with overtime", &model, &replan, &trace);
+    println!(
+        "  overtime is worth {:.2}",
+        replan.get_objective_value() - plan.get_objective_value()
+    );
+    Ok(())
+}
@@ -0,0 +1,91 @@
+//! Dual of a soft-margin support vector machine with a linear kernel.
+//!
+//! ```text
+//! max  sum_i a_i - 1/2 sum_ij a_i a_j y_i y_j <p_i, p_j>
+//! s.t. sum_i y_i a_i = 0
+//!      0 <= a_i <= C
+//! ```
+//!
+//! for points `p_i` with labels `y_i = +-1`. The support vectors are the points
+//! with `a_i > 0`, and they determine the separating hyperplane `<w, p> + b = 0`
+//! with `w = sum_i a_i y_i p_i`. The offset `b` is the dual of the equality
+//! constraint.
+//!
+//! Run with `cargo run --example svm_dual`.
+
+use copters::{
+    E, SolverHooks, SolverOptions,
+    model::{ModelBuilder, Sense},
+};
+use problemo::{Problem, common::IntoCommonProblem};
+
+fn main() -> Result<(), Problem> {
+    let points = [
+        ([1.0, 2.0], 1.),
+        ([2.0, 3.0], 1.),
+        ([3.0, 3.5], 1.),
+        ([1.5, 3.5], 1.),
+        ([2.5, 0.5], -1.),
+        ([3.5, 1.5], -1.),
+        ([4.0, 0.0], -1.),
+        ([2.0, 1.8], -1.),
+    ];
+    let penalty = 10.;
+
+    let mut options = SolverOptions::new();
+    options
+        .set_option("tolerance", 1e-9)
+        .map_err(|e| e.gloss())?;
+    let mut builder = ModelBuilder::new()
+        .with_sense(Sense::Maximize)
+        .with_options(options.clone());
+
+    let alpha: Vec<_> = (0..points.len())
+        .map(|i| builder.add_variable(format!("a{i}"), 0., penalty))
+        .collect();
+    for (i, &(p_i, y_i)) in points.iter().enumerate() {
+        builder.set_cost(alpha[i], 1.);
+        for (j, &(p_j, y_j)) in points.iter().enumerate().skip(i) {
+            let kernel = p_i[0] * p_j[0] + p_i[1] * p_j[1];
+            // The pairs (i, j) and (j, i) of the double sum contribute once each
+            let scale = if i == j { -0.5 } else { -1. };
+            builder.add_quadratic_term(alpha[i], alpha[j], scale * y_i * y_j * kernel);
+        }
+    }
+    let labels: Vec<_> = alpha
+        .iter()
+        .zip(points)
+        .map(|(&a, (_, y))| (a, y))
+        .collect();
+    let balance = builder.add_constraint("balance", &labels, 0., 0.);
+
+    let model = builder.build()?;
+    let mut hooks = SolverHooks::default_for(&options)?;
+    let solution = model.solve(&mut hooks)?;
+    println!("Status: {:?}", solution.get_status());
+    println!("Dual objective: {:.6}", solution.get_objective_value());
+
+    let mut w = [0.; 2];
+    println!("\nSupport vectors:");
+    for (&a, &(p, y)) in alpha.iter().zip(&points) {
+        let a_value = solution.get_value(a);
+        if a_value > 1e-6 * penalty {
+            println!(
+                "  ({:>4.1}, {:>4.1})  y = {y:>2}  a = {a_value:.4}",
+                p[0], p[1]
+            );
+            w[0] += a_value * y * p[0];
+            w[1] += a_value * y * p[1];
+        }
+    }
+
+    // The offset b is the multiplier of sum_i y_i a_i = 0, since stationarity
+    // at 0 < a_i < C reads y_i (<w, p_i> + b) = 1
+    let b: E = solution.get_dual(balance);
+    println!("\nHyperplane: {:.4} x {:+.4} y {:+.4} = 0", w[0], w[1], b);
+    for (p, y) in points {
+        let margin = y * (w[0] * p[0] + w[1] * p[1] + b);
+        println!("  ({:>4.1}, {:>4.1})  margin {margin:>7.4}", p[0], p[1]);
+    }
+    Ok(())
+}
@@ -0,0 +1,76 @@
+//! Transportation problem: ship goods from plants to markets at minimum cost.
+//!
+//! ```text
+//! min  sum_ij c_ij x_ij
+//! s.t. sum_j x_ij <= supply_i    for every plant i
+//!      sum_i x_ij >= demand_j    for every market j
+//!      x_ij >= 0
+//! ```
+//!
+//! The duals of the demand rows are the marginal costs of serving one more unit
+//! at each market.
+//!
+//! Run with `cargo run --example transportation`.
+
+use copters::{
+    E, SolverHooks,
+    model::{ModelBuilder, Sense},
+};
+use problemo::Problem;
+
+fn main() -> Result<(), Problem> {
+    let plants = [("Seattle", 350.), ("San Diego", 600.)];
+    let markets = [("New York", 325.), ("Chicago", 300.), ("Topeka", 275.)];
+    // Freight cost per unit from each plant to each market
+    let cost = [[2.5, 1.7, 1.8], [2.5, 1.8, 1.4]];
+
+    let mut builder = ModelBuilder::new().with_sense(Sense::Minimize);
+    let mut shipments = Vec::new();
+    for (i, (plant, _)) in plants.iter().enumerate() {
+        for (j, (market, _)) in markets.iter().enumerate() {
+            let x = builder.add_variable(format!("{plant} -> {market}"), 0., E::INFINITY);
+            builder.set_cost(x, cost[i][j]);
+            shipments.push(x);
+        }
+    }
+
+    let n_markets = markets.len();
+    for (i, &(plant, supply)) in plants.iter().enumerate() {
+        let terms: Vec<_> = (0..n_markets)
+            .map(|j| (shipments[i * n_markets + j], 1.))
+            .collect();
+        builder.add_constraint(format!("supply {plant}"), &terms, -E::INFINITY, supply);
+    }
+    let demand_rows: Vec<_> = markets
+        .iter()
+        .enumerate()
+        .map(|(j, &(market, demand))| {
+            let terms: Vec<_> = (0..plants.len())
+                .map(|i| (shipments[i * n_markets + j], 1.))
+                .collect();
+            builder.add_constraint(format!("demand {market}"), &terms, demand, E::INFINITY)
+        })
+        .collect();
+
+    let model = builder.build()?;
+    let solution = model.solve(&mut SolverHooks::silent())?;
+    println!("Status: {:?}", solution.get_status());
+    println!("Total cost: {:.2}", solution.get_objective_value());
+
+    println!("\nShipments:");
+    for &x in &shipments {
+        let amount = solution.get_value(x);
+        if amount > 1e-3 {
+            println!(
+                "  {:<24} {amount:>8.1}",
+                model.get_variable_names()[x.get_index()]
+            );
+        }
+    }
+
+    println!("\nMarginal cost of demand:");
+    for (&row, (market, _)) in demand_rows.iter().zip(markets) {
+        println!("  {market:<24} {:>8.3}", solution.get_dual(row));
+    }
+    Ok(())
+}
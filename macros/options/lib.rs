@@ -1,4 +1,3 @@
-#![feature(map_try_insert)]
 #![allow(static_mut_refs)]
 
 use std::sync::Mutex;
@@ -8,11 +7,12 @@ use lazy_static::lazy_static;
 use proc_macro::TokenStream;
 use quote::{ToTokens, quote};
 use syn::punctuated::Punctuated;
-use syn::{Expr, Ident, LitStr, MetaNameValue, Token, TraitBound, Type, TypeTuple};
+use syn::{Expr, Ident, Lifetime, LitStr, MetaNameValue, Token, TraitBound, Type, TypeTuple};
 
 #[derive(deluxe::ParseMetaItem)]
 struct ExplicitOptionsInput {
     name: Ident,
+    scope: Option<LitStr>,
 }
 
 /// ## `explicit_options` Attribute Proc Macro
@@ -47,6 +47,13 @@ struct ExplicitOptionsInput {
 /// This will register the `tolerance` field as a solver option and generate an internal options
 /// struct for type-safe access.
 ///
+/// ### Scopes
+///
+/// With `scope = "lp.mpc"`, the options are read with `get_scoped_option`, so that a value set for
+/// `lp.mpc.tolerance` or `lp.tolerance` applies to this struct only and takes precedence over the
+/// value of `tolerance`. Scopes are dot-separated snake case names, and are listed next to the
+/// options they read in the registry.
+///
 /// ### Why use an attribute macro?
 ///
 /// Using an attribute macro allows you to explicitly control which fields are registered as
@@ -61,8 +68,21 @@ pub fn explicit_options(attr: TokenStream, item: TokenStream) -> TokenStream {
     let generics = &item_struct.generics;
     let struct_token = &item_struct.struct_token;
 
-    let ExplicitOptionsInput { name } =
+    let ExplicitOptionsInput { name, scope } =
         deluxe::parse::<ExplicitOptionsInput>(attr).expect("Failed to parse ExplicitOptionsInput");
+    if let Some(scope) = &scope
+        && !scope.value().split('.').all(is_option_name)
+    {
+        return syn::Error::new(
+            scope.span(),
+            format!(
+                "Scope '{}' must be dot-separated snake case names",
+                scope.value()
+            ),
+        )
+        .to_compile_error()
+        .into();
+    }
 
     let item_attr = struct_attrs
         .iter()
@@ -126,11 +146,26 @@ pub fn explicit_options(attr: TokenStream, item: TokenStream) -> TokenStream {
         })
         .collect::<Vec<_>>();
 
+    if let Some(scope) = &scope {
+        let mut scopes = OptionScopes.lock().unwrap();
+        for (name, _) in &option_set {
+            scopes
+                .entry(name.value())
+                .or_default()
+                .insert(scope.value());
+        }
+    }
+
     let option_def = option_set.iter().map(|(name, type_)| {
         let name_expr = syn::parse_str::<Expr>(&format!("{}", name.value()))
             .expect("Failed to parse name as Expr");
-        quote! {
-            #name_expr: options.get_option::<#type_>(#name).expect("Option not found").clone()
+        match &scope {
+            Some(scope) => quote! {
+                #name_expr: options.get_scoped_option::<#type_>(#scope, #name).expect("Option not found").clone()
+            },
+            None => quote! {
+                #name_expr: options.get_option::<#type_>(#name).expect("Option not found").clone()
+            },
         }
     });
 
@@ -185,9 +220,110 @@ struct OptionInput {
     description: Option<LitStr>,
 }
 
+/// Metadata of a registered option, merged over all its declarations.
+struct OptionEntry {
+    type_: String,
+    default: Option<String>,
+    description: Option<String>,
+    /// Locations of the declarations, as `file:line`.
+    locations: Vec<String>,
+    /// Structs declaring the option.
+    owners: Vec<String>,
+}
+
 lazy_static! {
-    static ref OptionMap: Mutex<Option<std::collections::HashMap<String, Box<(String, Option<String>, Option<String>)>>>> =
-        Mutex::new(Some(Default::default()));
+    static ref OptionMap: Mutex<std::collections::HashMap<String, OptionEntry>> =
+        Mutex::new(Default::default());
+    /// Scopes of the structs reading each option, see `explicit_options`.
+    static ref OptionScopes: Mutex<std::collections::HashMap<String, std::collections::BTreeSet<String>>> =
+        Mutex::new(Default::default());
+}
+
+/// Whether `name` is a valid option or scope name, i.e. nonempty snake case.
+fn is_option_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Records a declaration of the option `name` by the struct `owner` at `location`. Fails if the name is not snake case,
+/// differs from a registered name only in case or underscores, or if the option was declared with
+/// another type or default.
+fn register_option(
+    name: &str,
+    type_: String,
+    default: Option<String>,
+    description: Option<String>,
+    location: String,
+    owner: String,
+) -> Result<(), String> {
+    if !is_option_name(name) {
+        return Err(format!(
+            "Option name '{}' must be snake case without dots, e.g. '{}'",
+            name,
+            name.replace('.', "_").to_case(Case::Snake)
+        ));
+    }
+
+    let mut option_map = OptionMap.lock().unwrap();
+    let normalize = |name: &str| name.replace('_', "");
+    if let Some((other, entry)) = option_map
+        .iter()
+        .find(|(other, _)| other.as_str() != name && normalize(other) == normalize(name))
+    {
+        return Err(format!(
+            "Option '{}' collides with option '{}' declared at {}",
+            name,
+            other,
+            entry.locations.join(", ")
+        ));
+    }
+
+    let Some(entry) = option_map.get_mut(name) else {
+        option_map.insert(
+            name.to_string(),
+            OptionEntry {
+                type_,
+                default: default.clone(),
+                description: default.as_ref().map(|_| description.unwrap_or_default()),
+                locations: vec![location],
+                owners: vec![owner],
+            },
+        );
+        return Ok(());
+    };
+
+    if entry.type_ != type_ {
+        return Err(format!(
+            "Option '{}' is declared with type `{}` here and with type `{}` at {}",
+            name,
+            type_,
+            entry.type_,
+            entry.locations.join(", ")
+        ));
+    }
+    if let Some(default) = default {
+        if entry.default.as_ref().is_some_and(|d| d != &default) {
+            return Err(format!(
+                "Option '{}' is declared with default {} here and with default {} at {}",
+                name,
+                default,
+                entry.default.as_ref().unwrap(),
+                entry.locations.join(", ")
+            ));
+        }
+        entry.default = Some(default);
+        entry.description = Some(description.unwrap_or_default());
+    }
+    if !entry.locations.contains(&location) {
+        entry.locations.push(location);
+    }
+    if !entry.owners.contains(&owner) {
+        entry.owners.push(owner);
+    }
+    Ok(())
 }
 
 /// ## `use_option` Attribute Proc Macro
@@ -205,6 +341,10 @@ lazy_static! {
 ///   in documentation and code generation.
 /// - **Integration with Other Macros:**   Enables dynamic documentation and registry generation by
 ///   macros like `build_options!`, `gen_option_struct!`, and `explicit_options`.
+/// - **Collision Diagnostics:**   Option names are global, so every declaration of a name must
+///   agree on its type and default. A conflicting declaration, a name that is not snake case, or
+///   one that differs from a registered name only in underscores is a compile error that points
+///   at the declaration and names the locations of the earlier ones.
 ///
 /// ### Example
 ///
@@ -234,80 +374,34 @@ pub fn use_option(attr: TokenStream, item: TokenStream) -> TokenStream {
         description,
     } = deluxe::parse::<OptionInput>(attr).expect("Failed to parse OptionInput");
 
-    let type_ident = &type_;
-    let _default_lit = &default;
-
-    if default.is_some() {
-        OptionMap
-            .lock()
-            .unwrap()
-            .as_mut()
-            .unwrap()
-            .try_insert(
-                name.value(),
-                Box::new((
-                    type_ident.to_token_stream().to_string(),
-                    Some(default.to_token_stream().to_string()),
-                    Some(description.as_ref().map_or("".to_string(), |d| d.value())),
-                )),
-            )
-            .map_err(|mut err| {
-                if err.value.0 != err.entry.get().0 {
-                    panic!(
-                        "Option '{}' is already defined with a different type",
-                        name.value()
-                    );
-                }
-
-                if err.entry.get().1.is_some()
-                    && default.is_some()
-                    && err.entry.get().1.as_ref().unwrap() != &default.to_token_stream().to_string()
-                {
-                    panic!(
-                        "Option '{}' is already defined with a different default value",
-                        name.value()
-                    );
-                }
-
-                *err.entry.get_mut() = Box::new((
-                    err.entry.get().0.clone(),
-                    Some(default.to_token_stream().to_string()),
-                    Some(description.as_ref().map_or("".to_string(), |d| d.value())),
-                ));
-            })
-            .ok();
-    } else {
-        OptionMap
-            .lock()
-            .unwrap()
-            .as_mut()
-            .unwrap()
-            .try_insert(
-                name.value(),
-                Box::new((type_ident.to_token_stream().to_string(), None, None)),
-            )
-            .map_err(|err| {
-                if err.value.0 != err.entry.get().0 {
-                    panic!(
-                        "Option '{}' is already defined with a different type",
-                        name.value()
-                    );
-                }
-            })
-            .ok();
-    }
-
     let item_struct = syn::parse_macro_input!(item as syn::ItemStruct);
 
+    let span = proc_macro::Span::call_site();
+    let registered = register_option(
+        &name.value(),
+        type_.to_token_stream().to_string(),
+        default.as_ref().map(|d| d.to_token_stream().to_string()),
+        description.as_ref().map(|d| d.value()),
+        format!("{}:{}", span.file(), span.line()),
+        item_struct.ident.to_string(),
+    );
+
     // Generate code to register the option at runtime before the function body
     // let struct_block = &item_struct.block;
     // let struct_attrs = &item_struct.attrs;
     // let struct_vis = &item_struct.vis;
     // let struct_sig = &item_struct.sig;
 
-    quote! (
-        #item_struct
-    )
+    match registered {
+        Ok(()) => quote! { #item_struct },
+        Err(message) => {
+            let error = syn::Error::new(name.span(), message).to_compile_error();
+            quote! {
+                #error
+                #item_struct
+            }
+        }
+    }
     .into()
 }
 
@@ -331,6 +425,11 @@ struct OptionBuilder {
 ///   actual options available in your code.
 /// - **Dynamic Doc Generation:**   As a proc macro, it inspects the registered options and
 ///   generates up-to-date documentation at compile time.
+/// - **Scoped Options:**   The generated options accept scoped names such as
+///   `lp.mpc.max_iterations`, which apply to the structs declared with that scope in
+///   `explicit_options`, and list the registered options with `describe()`.
+/// - **Runtime Introspection:**   `iter()` and `get_info()` return the name, type, default,
+///   description, scopes and declaring structs of the registered options as `OptionInfo`.
 ///
 /// ### Example
 ///
@@ -353,15 +452,18 @@ pub fn build_options(tokens: TokenStream) -> TokenStream {
         registry_name,
     } = deluxe::parse::<OptionBuilder>(tokens).expect("Failed to parse OptionBuilder");
 
-    let option_map_guard = OptionMap.lock().unwrap();
-    let option_map = option_map_guard.as_ref().unwrap();
+    let option_map = OptionMap.lock().unwrap();
+    let option_scopes = OptionScopes.lock().unwrap();
+    let mut option_names: Vec<&String> = option_map.keys().collect();
+    option_names.sort();
 
-    let options_fields: Vec<_> = option_map
+    let options_fields: Vec<_> = option_names
         .iter()
-        .map(|(key, value)| {
-            let (type_str, default, _description) = value.as_ref();
-            let type_ident: Type = syn::parse_str(type_str).expect("Failed to parse type");
-            let default = default
+        .map(|key| {
+            let entry = &option_map[*key];
+            let type_ident: Type = syn::parse_str(&entry.type_).expect("Failed to parse type");
+            let default = entry
+                .default
                 .as_ref()
                 .unwrap_or(&"Default::default()".to_string())
                 .to_string()
@@ -372,34 +474,63 @@ pub fn build_options(tokens: TokenStream) -> TokenStream {
         })
         .collect();
 
-    let docs_fields: Vec<_> = option_map
+    let scopes_of = |key: &str| -> Vec<String> {
+        option_scopes
+            .get(key)
+            .map(|scopes| scopes.iter().cloned().collect())
+            .unwrap_or_default()
+    };
+    let info_fields: Vec<_> = option_names
+        .iter()
+        .map(|key| {
+            let entry = &option_map[*key];
+            let type_str = entry.type_.replace(" :: ", "::");
+            let default = match &entry.default {
+                Some(default) => {
+                    let default = default.replace("\"", "");
+                    quote! { Some(#default) }
+                }
+                None => quote! { None },
+            };
+            let description = entry.description.as_deref().unwrap_or("");
+            let scopes = scopes_of(key);
+            let owners = &entry.owners;
+            quote! {
+                crate::OptionInfo::new(#key, #type_str, #default, #description, &[#(#scopes),*], &[#(#owners),*])
+            }
+        })
+        .collect();
+
+    let docs_fields: Vec<_> = option_names
         .iter()
-        .map(|(key, value)| {
-            let (type_str, default, description) = value.as_ref();
-            let _type_: Type = syn::parse_str(type_str).expect("Failed to parse type");
-            let default = default
+        .map(|key| {
+            let entry = &option_map[*key];
+            let default = entry
+                .default
                 .as_ref()
                 .unwrap_or(&"Default::default()".to_string())
                 .to_string()
                 .replace("\"", "");
-            let description = description
+            let description = entry
+                .description
                 .as_ref()
                 .unwrap_or(&"".to_string())
                 .to_string()
                 .replace("\"", "");
             format!(
-                "| {} | [`{}`] | {} | {} |",
+                "| {} | [`{}`] | {} | {} | {} |",
                 key,
-                type_str.replace(" :: ", "::"),
+                entry.type_.replace(" :: ", "::"),
                 default,
+                scopes_of(key).join(", "),
                 description
             )
         })
         .collect();
 
     let mut doc_string = String::from(
-        "Option registry for Options.\n\n| Option Name      | Type   | Default | Description                \
-         |\n|------------------|--------|---------|----------------------------|\n",
+        "Option registry for Options.\n\n| Option Name      | Type   | Default | Scopes | Description                \
+         |\n|------------------|--------|---------|--------|----------------------------|\n",
     );
 
     for field in docs_fields {
@@ -420,38 +551,186 @@ pub fn build_options(tokens: TokenStream) -> TokenStream {
             map
         });
 
+        impl #name {
+            /// Metadata of the registered options, ordered by name.
+            const INFOS: &[crate::OptionInfo] = &[#(#info_fields),*];
+        }
+
         #[doc = #doc_string]
+        ///
+        /// The options read through [`get_option`](Self::get_option) are
+        /// recorded, by the options and all their clones, so that
+        /// [`get_effective_options`](Self::get_effective_options) reports the
+        /// options consumed by the components built from them.
         #[derive(Clone)]
         pub struct #name {
             map: std::collections::HashMap<String, Box<dyn crate::OptionTrait>>,
+            /// Options set through `set_option`.
+            user: std::collections::HashSet<String>,
+            /// Options read through `get_option`, shared by the clones.
+            used: std::sync::Arc<std::sync::Mutex<std::collections::BTreeSet<String>>>,
         }
 
         impl #name {
             pub fn new() -> Self {
                 let map = #registry_ident.clone();
-                Self { map }
+                Self {
+                    map,
+                    user: std::collections::HashSet::new(),
+                    used: std::sync::Arc::default(),
+                }
+            }
+
+            /// Value of the option `name` for the components in `scope`: the value
+            /// set for `{scope}.{name}`, else for the nearest enclosing scope, e.g.
+            /// `lp.{name}` for the scope `lp.mpc`, else the value of `name`.
+            pub fn get_scoped_option<T: OptionTrait>(&self, scope: &str, name: &str) -> Option<T>
+            where
+                T: Clone,
+            {
+                let mut scope = Some(scope);
+                while let Some(current) = scope {
+                    let key = format!("{current}.{name}");
+                    if self.map.contains_key(&key) {
+                        return self.get_option(&key);
+                    }
+                    scope = current.rsplit_once('.').map(|(parent, _)| parent);
+                }
+                self.get_option(name)
+            }
+
+            /// Metadata of the registered options, ordered by name, e.g. to
+            /// render a reference of the options.
+            pub fn iter() -> impl Iterator<Item = &'static crate::OptionInfo> {
+                Self::INFOS.iter()
+            }
+
+            /// Metadata of the registered option `name`.
+            pub fn get_info(name: &str) -> Option<&'static crate::OptionInfo> {
+                Self::INFOS
+                    .binary_search_by(|info| info.get_name().cmp(name))
+                    .ok()
+                    .map(|k| &Self::INFOS[k])
+            }
+
+            /// Lists the registered options, one per line with their type,
+            /// default, the scopes of the solvers reading them and their
+            /// description.
+            pub fn describe() -> String {
+                let header = ("Option", "Type", "Default", "Scopes", "Description");
+                let rows: Vec<_> = Self::iter()
+                    .map(|info| {
+                        let scopes = if info.get_scopes().is_empty() {
+                            "-".to_string()
+                        } else {
+                            info.get_scopes().join(", ")
+                        };
+                        (
+                            info.get_name(),
+                            info.get_type_name(),
+                            info.get_default().unwrap_or("-"),
+                            scopes,
+                            info.get_description(),
+                        )
+                    })
+                    .collect();
+                let width = |column: fn(&(&str, &str, &str, String, &str)) -> usize, title: &str| {
+                    rows.iter().map(column).max().unwrap_or(0).max(title.len())
+                };
+                let widths = (
+                    width(|row| row.0.len(), header.0),
+                    width(|row| row.1.len(), header.1),
+                    width(|row| row.2.len(), header.2),
+                    width(|row| row.3.len(), header.3),
+                );
+                let mut listing = format!(
+                    "{:<w0$}  {:<w1$}  {:<w2$}  {:<w3$}  {}\n",
+                    header.0, header.1, header.2, header.3, header.4,
+                    w0 = widths.0, w1 = widths.1, w2 = widths.2, w3 = widths.3,
+                );
+                for (name, type_, default, scopes, description) in &rows {
+                    listing.push_str(&format!(
+                        "{:<w0$}  {:<w1$}  {:<w2$}  {:<w3$}  {}\n",
+                        name, type_, default, scopes, description,
+                        w0 = widths.0, w1 = widths.1, w2 = widths.2, w3 = widths.3,
+                    ));
+                }
+                listing
+            }
+
+            /// Checks that the option `name` is read by a solver within `scope`.
+            fn check_scope(scope: &str, name: &str) -> Result<(), String> {
+                let scopes = Self::get_info(name).map_or(&[][..], |info| info.get_scopes());
+                let prefix = format!("{scope}.");
+                if scopes.iter().any(|s| *s == scope || s.starts_with(&prefix)) {
+                    Ok(())
+                } else if scopes.is_empty() {
+                    Err(format!("Option '{}' is not read by any scoped solver.", name))
+                } else {
+                    Err(format!(
+                        "Option '{}' is not read in scope '{}', only in {}.",
+                        name,
+                        scope,
+                        scopes.join(", ")
+                    ))
+                }
             }
 
             pub fn get_option<T: OptionTrait>(&self, name: &str) -> Option<T>
             where
                 T: Clone,
             {
-                self.map
+                let value = self.map
                     .get(name)
                     .and_then(|v| {
                         // Downcast to the concrete type
                         (v.as_ref() as &dyn Any).downcast_ref::<T>()
                     })
-                    .cloned()
+                    .cloned();
+                if value.is_some() {
+                    self.used.lock().unwrap().insert(name.to_string());
+                }
+                value
             }
 
+            /// Options read so far by the components built from these options
+            /// or their clones, ordered by name, with their values here and
+            /// whether they were set or left at their defaults.
+            pub fn get_effective_options(&self) -> crate::EffectiveOptions {
+                let used = self.used.lock().unwrap();
+                crate::EffectiveOptions::new(
+                    used.iter()
+                        .filter_map(|name| {
+                            let value = self.map.get(name)?;
+                            let source = if self.user.contains(name) {
+                                crate::OptionSource::User
+                            } else {
+                                crate::OptionSource::Default
+                            };
+                            Some(crate::EffectiveOption::new(name, format!("{:?}", value), source))
+                        })
+                        .collect(),
+                )
+            }
+
+            /// Sets the option `name`, or with a scoped name such as
+            /// `lp.mpc.max_iterations`, its value for the solvers in that scope
+            /// only, see [`get_scoped_option`](Self::get_scoped_option).
             pub fn set_option<T: OptionTrait>(&mut self, name: &str, value: T) -> Result<(), String> {
-                if !self.map.contains_key(name) {
-                    return Err(format!("Option '{}' is not registered.", name));
+                let (scope, base) = match name.rsplit_once('.') {
+                    Some((scope, base)) => (Some(scope), base),
+                    None => (None, name),
+                };
+                if !self.map.contains_key(base) {
+                    return Err(format!("Option '{}' is not registered.", base));
+                }
+                if let Some(scope) = scope {
+                    Self::check_scope(scope, base)?;
                 }
 
-                if let Some(_) = (self.map.get(name).unwrap().as_ref() as &dyn Any).downcast_ref::<T>() {
+                if let Some(_) = (self.map.get(base).unwrap().as_ref() as &dyn Any).downcast_ref::<T>() {
                     self.map.insert(name.to_string(), Box::new(value));
+                    self.user.insert(name.to_string());
                     Ok(())
                 } else {
                     Err(format!(
@@ -473,10 +752,33 @@ struct EnumTraitInput {
     trait_: TraitBound,
     name: LitStr,
     variants: TypeTuple,
+    aliases: Option<Vec<Vec<LitStr>>>,
     new_arguments: TypeTuple,
     doc_header: Option<LitStr>,
 }
 
+/// Lifetimes named in `tokens`, in order of appearance and without duplicates or `'static`.
+fn collect_lifetimes(tokens: proc_macro2::TokenStream, lifetimes: &mut Vec<Lifetime>) {
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        match token {
+            proc_macro2::TokenTree::Punct(punct)
+                if punct.as_char() == '\'' && punct.spacing() == proc_macro2::Spacing::Joint =>
+            {
+                if let Some(proc_macro2::TokenTree::Ident(ident)) = tokens.peek() {
+                    let lifetime = Lifetime::new(&format!("'{ident}"), ident.span());
+                    if lifetime.ident != "static" && !lifetimes.contains(&lifetime) {
+                        lifetimes.push(lifetime);
+                    }
+                    tokens.next();
+                }
+            }
+            proc_macro2::TokenTree::Group(group) => collect_lifetimes(group.stream(), lifetimes),
+            _ => (),
+        }
+    }
+}
+
 /// ## `build_option_enum!` Proc Macro
 ///
 /// The `build_option_enum!` macro generates an enum type that acts as a registry for all available
@@ -484,29 +786,34 @@ struct EnumTraitInput {
 ///
 /// ### Features
 ///
-/// - **Enum Generation:**   Automatically creates an enum (e.g., `Initializers`) with variants for
-///   each registered implementation.
+/// - **Enum Generation:**   Automatically creates an enum (e.g., `Initializers`) with one variant
+///   per registered implementation, named after its type. The first variant is the default.
+/// - **Option Parsing:**   The enum implements `FromStr` and `OptionTrait`, so it can be used as
+///   the type of an option. A variant is parsed from the snake case name of its type, e.g.
+///   `simple_initializer`, or from any of its `aliases`, ignoring case.
 /// - **Auto-Generated Documentation:**   Dynamically generates documentation for the enum,
-///   including a Markdown table listing each variant, its type, default value, and description.
-/// - **Dynamic Doc Generation:**   As a proc macro, it inspects the registered implementations and
-///   generates up-to-date documentation at compile time.
-/// - **Variant Construction:**   The generated enum includes methods for constructing trait objects
-///   from enum variants and for listing all available variants.
+///   including a Markdown table listing each variant and the names it is parsed from.
+/// - **Variant Construction:**   `into_variant` constructs the trait object of a variant by calling
+///   the `new` function of the trait with `new_arguments`. Lifetimes in the trait bound, including
+///   those bound with `for<'a>`, and in the arguments become lifetime parameters of
+///   `into_variant`, and the trait object is bounded by them.
 ///
 /// ### Example
 ///
 /// ```rust
 /// build_option_enum!(
-///     trait_ = Initializer,
+///     trait_ = for<'a> Initializer<'a>,
 ///     name = "Initializers",
 ///     variants = (SimpleInitializer, AdvancedInitializer),
-///     new_arguments = (),
+///     aliases = [["simple"], ["advanced", "adv"]],
+///     new_arguments = (&'a Problem, &SolverOptions),
 ///     doc_header = "Initializer registry"
 /// );
 /// ```
 ///
 /// This will generate an `Initializers` enum with documentation that includes a table of all
-/// available variants and methods for trait object construction.
+/// available variants, and an `Initializers::into_variant` method that returns a
+/// `Box<dyn Initializer<'a> + 'a>`.
 ///
 /// ### Why use a proc macro?
 ///
@@ -519,20 +826,65 @@ pub fn build_option_enum(token: TokenStream) -> TokenStream {
         trait_,
         name,
         variants,
+        aliases,
         new_arguments,
         doc_header,
     } = deluxe::parse::<EnumTraitInput>(token).expect("Failed to parse EnumTraitInput");
 
     let enum_name = syn::Ident::new(&name.value(), name.span());
 
-    let variant_types: Vec<_> = variants.elems.iter().map(|v| v.to_token_stream()).collect();
-    let variant_names: Vec<_> = variants
-        .elems
+    let variant_types: Vec<_> = variants.elems.iter().collect();
+    let variant_idents: Vec<Ident> = variant_types
+        .iter()
+        .map(|v| match v {
+            Type::Path(path) => path
+                .path
+                .segments
+                .last()
+                .expect("Expected a variant type")
+                .ident
+                .clone(),
+            _ => panic!("Variants must be type paths"),
+        })
+        .collect();
+    let variant_names: Vec<String> = variant_idents
         .iter()
-        .map(|v| {
-            syn::parse_str::<Ident>(&v.to_token_stream().to_string().to_case(Case::Snake)).unwrap()
+        .map(|v| v.to_string().to_case(Case::Snake))
+        .collect();
+    let aliases = aliases.unwrap_or_default();
+    if !aliases.is_empty() && aliases.len() != variant_types.len() {
+        panic!("Expected one list of aliases per variant");
+    }
+    let variant_aliases: Vec<Vec<String>> = (0..variant_types.len())
+        .map(|k| {
+            aliases
+                .get(k)
+                .map(|a| a.iter().map(|alias| alias.value().to_lowercase()).collect())
+                .unwrap_or_default()
         })
         .collect();
+    let parse_arms = variant_idents
+        .iter()
+        .zip(&variant_names)
+        .zip(&variant_aliases)
+        .map(|((ident, name), aliases)| {
+            quote! {
+                #name #(| #aliases)* => Ok(#enum_name::#ident),
+            }
+        });
+    let name_arms = variant_idents
+        .iter()
+        .zip(&variant_names)
+        .map(|(ident, name)| quote! { #enum_name::#ident => #name, });
+
+    // The trait without its `for<...>` binder, whose lifetimes become parameters of `into_variant`
+    let trait_path = &trait_.path;
+    let mut lifetimes = Vec::new();
+    if let Some(bound) = &trait_.lifetimes {
+        collect_lifetimes(bound.lifetimes.to_token_stream(), &mut lifetimes);
+    }
+    collect_lifetimes(trait_path.to_token_stream(), &mut lifetimes);
+    collect_lifetimes(new_arguments.to_token_stream(), &mut lifetimes);
 
     let argument_types: Vec<_> = new_arguments
         .elems
@@ -546,47 +898,65 @@ pub fn build_option_enum(token: TokenStream) -> TokenStream {
     let argument_idents: Vec<_> = (0..new_arguments.elems.len())
         .map(|i| syn::Ident::new(&format!("arg{}", i), proc_macro2::Span::call_site()))
         .collect();
-    let argument_ident = quote!(
-        #(#argument_idents),*
-    );
-
-    let doc_header = quote!(#doc_header)
-        .to_string()
-        .trim_matches('"')
-        .to_string()
-        + " The ```Default::default``` values for the enum is ```"
-        + &variant_names[0].to_string()
-        + "```.";
+    let call_arguments = quote! { #(#argument_idents),* };
+
+    let mut doc_header = doc_header.map(|d| d.value()).unwrap_or_default();
+    doc_header.push_str(&format!(
+        " The ```Default::default``` value of the enum is ```{}```.\n\n| Variant | Names |\n|---------|-------|\n",
+        variant_idents[0]
+    ));
+    for ((ident, name), aliases) in variant_idents
+        .iter()
+        .zip(&variant_names)
+        .zip(&variant_aliases)
+    {
+        let names = std::iter::once(name)
+            .chain(aliases)
+            .map(|n| format!("`{n}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        doc_header.push_str(&format!("| [`{ident}`] | {names} |\n"));
+    }
 
     let expanded = quote! {
-        use std::str::FromStr;
-
-        #[derive(Clone, Default)]
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
         #[doc = #doc_header]
         pub enum #enum_name {
             #[default]
-            #(#variant_names),*
+            #(#variant_idents),*
         }
 
         impl crate::OptionTrait for #enum_name {}
 
-        impl FromStr for #enum_name {
-            type Err = &'static str;
+        impl std::str::FromStr for #enum_name {
+            type Err = String;
 
             fn from_str(s: &str) -> Result<Self, Self::Err> {
-                match s {
-                    #(#variant_names => Ok(#enum_name::#variant_names),)*
-                    _ => Err("Invalid enum variant"),
+                match s.to_lowercase().as_str() {
+                    #(#parse_arms)*
+                    _ => Err(format!("Invalid {} variant: {}", #name, s)),
                 }
             }
         }
 
         impl #enum_name {
-            pub const variants: &[#enum_name] = &[ #(#enum_name::#variant_names),* ];
+            pub const VARIANTS: &[#enum_name] = &[ #(#enum_name::#variant_idents),* ];
+
+            /// Name the variant is parsed from, the snake case name of its type.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    #(#name_arms)*
+                }
+            }
 
-            pub fn into_variant(type_ : &#enum_name, #(#argument_types),*) -> Box<dyn #trait_> {
+            pub fn into_variant<#(#lifetimes),*>(
+                type_: &#enum_name,
+                #(#argument_types),*
+            ) -> Box<dyn #trait_path #(+ #lifetimes)*> {
                 match type_ {
-                    #(#variant_names => Box::new(#variant_types::new(#argument_ident)),)*
+                    #(#enum_name::#variant_idents => Box::new(
+                        <#variant_types as #trait_path>::new(#call_arguments)
+                    ),)*
                 }
             }
         }
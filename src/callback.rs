@@ -1,19 +1,29 @@
-use std::{collections::HashSet, fmt::Debug};
+use std::{
+    collections::HashSet,
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    time::Instant,
+};
 
 use dyn_clone::DynClone;
-use enum_dispatch::enum_dispatch;
+use macros::{explicit_options, use_option};
 
-use crate::{E, SolverOptions, SolverState};
+use crate::{E, SolveStats, SolverOptions, SolverState, StateView};
 
 /// Hook invoked once per solver iteration for logging, monitoring, or early stopping.
-#[enum_dispatch]
-pub trait Callback: Debug + DynClone {
-    fn init(&mut self, _state: &SolverState) {}
+///
+/// Callbacks observe the iterate through [`StateView`], so other crates can
+/// implement their own for any state that provides it. Callbacks are `Send`
+/// so that [`SolverHooks`](crate::SolverHooks) can be moved to worker threads.
+pub trait Callback<S: StateView + ?Sized = SolverState>: Debug + DynClone + Send {
+    fn init(&mut self, _state: &S) {}
 
     /// Called at the end of each iteration with the current solver state.
-    fn call(&mut self, _state: &SolverState) {}
+    fn call(&mut self, _state: &S) {}
 
-    fn finish(&mut self) {}
+    /// Called once the solve stops with the final iterate and the outcome of
+    /// the solve, e.g. to flush buffered output or report a summary.
+    fn finish(&mut self, _state: &S, _stats: &SolveStats) {}
 }
 
 /// A callback that does nothing. Use when no per-iteration output is needed.
@@ -26,58 +36,372 @@ impl NoOpCallback {
     }
 }
 
-impl Callback for NoOpCallback {}
-/// Prints primal and dual infeasibility to stdout each iteration.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct ConvergenceOutput {}
+impl<S: StateView + ?Sized> Callback<S> for NoOpCallback {}
 
-impl ConvergenceOutput {
-    pub fn new() -> Self {
-        Self {}
+/// Column of the iteration table printed by [`ConvergenceOutput`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LogColumn {
+    /// Iteration index.
+    Iteration,
+    /// Primal objective value.
+    PrimalObjective,
+    /// Dual objective value.
+    DualObjective,
+    /// Duality gap.
+    Gap,
+    /// Primal infeasibility, scaled by the number of variables.
+    PrimalInfeasibility,
+    /// Dual infeasibility, scaled by the number of variables.
+    DualInfeasibility,
+    /// Lower-bound complementarity, scaled by the number of variables.
+    ComplementarityLower,
+    /// Upper-bound complementarity, scaled by the number of variables.
+    ComplementarityUpper,
+    /// Barrier parameter.
+    Mu,
+    /// Primal step size.
+    AlphaPrimal,
+    /// Dual step size.
+    AlphaDual,
+    /// Variable whose bound limited the primal step.
+    BlockingPrimal,
+    /// Variable whose bound multiplier limited the dual step.
+    BlockingDual,
+    /// Seconds since the first iteration.
+    Time,
+}
+
+impl LogColumn {
+    /// Parses the name used in the `log_columns` option.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name.trim().to_lowercase().as_str() {
+            "iter" => LogColumn::Iteration,
+            "pobj" => LogColumn::PrimalObjective,
+            "dobj" => LogColumn::DualObjective,
+            "gap" => LogColumn::Gap,
+            "pinf" => LogColumn::PrimalInfeasibility,
+            "dinf" => LogColumn::DualInfeasibility,
+            "cs_l" => LogColumn::ComplementarityLower,
+            "cs_u" => LogColumn::ComplementarityUpper,
+            "mu" => LogColumn::Mu,
+            "alpha_p" => LogColumn::AlphaPrimal,
+            "alpha_d" => LogColumn::AlphaDual,
+            "block_p" => LogColumn::BlockingPrimal,
+            "block_d" => LogColumn::BlockingDual,
+            "time" => LogColumn::Time,
+            _ => return None,
+        })
+    }
+
+    fn header(&self) -> &'static str {
+        match self {
+            LogColumn::Iteration => "ITER",
+            LogColumn::PrimalObjective => "PRIMAL_OBJ",
+            LogColumn::DualObjective => "DUAL_OBJ",
+            LogColumn::Gap => "GAP",
+            LogColumn::PrimalInfeasibility => "PRI_INF",
+            LogColumn::DualInfeasibility => "DUAL_INF",
+            LogColumn::ComplementarityLower => "CS_L",
+            LogColumn::ComplementarityUpper => "CS_U",
+            LogColumn::Mu => "MU",
+            LogColumn::AlphaPrimal => "ALPHA_P",
+            LogColumn::AlphaDual => "ALPHA_D",
+            LogColumn::BlockingPrimal => "BLOCK_P",
+            LogColumn::BlockingDual => "BLOCK_D",
+            LogColumn::Time => "TIME",
+        }
+    }
+
+    fn width(&self) -> usize {
+        match self {
+            LogColumn::Iteration => 5,
+            LogColumn::PrimalObjective | LogColumn::DualObjective => 15,
+            LogColumn::Time => 8,
+            _ => 9,
+        }
+    }
+
+    fn format<S: StateView + ?Sized>(&self, state: &S, elapsed: E) -> String {
+        let width = self.width();
+        let n = state.get_primal().nrows().max(1) as E;
+        let value = match self {
+            LogColumn::Iteration => return format!("{:>width$}", state.get_nit()),
+            LogColumn::Time => return format!("{elapsed:>width$.2}"),
+            LogColumn::BlockingPrimal | LogColumn::BlockingDual => {
+                let blocking = if *self == LogColumn::BlockingPrimal {
+                    state.get_blocking_primal()
+                } else {
+                    state.get_blocking_dual()
+                };
+                return match blocking {
+                    Some(j) => format!("{j:>width$}"),
+                    None => format!("{:>width$}", "-"),
+                };
+            }
+            LogColumn::PrimalObjective => state.get_objective(),
+            LogColumn::DualObjective => state.get_dual_objective(),
+            LogColumn::Gap => state.get_duality_gap(),
+            LogColumn::PrimalInfeasibility => Some(state.get_primal_feasibility().norm_l2() / n),
+            LogColumn::DualInfeasibility => Some(state.get_dual_feasibility().norm_l2() / n),
+            LogColumn::ComplementarityLower => Some(state.get_cs_lower().norm_l2() / n),
+            LogColumn::ComplementarityUpper => Some(state.get_cs_upper().norm_l2() / n),
+            LogColumn::Mu => state.get_mu(),
+            LogColumn::AlphaPrimal => Some(state.get_alpha_primal()),
+            LogColumn::AlphaDual => Some(state.get_alpha_dual()),
+        };
+        match (self, value) {
+            (_, None) => format!("{:>width$}", "-"),
+            (LogColumn::PrimalObjective | LogColumn::DualObjective, Some(v)) => {
+                format!("{v:>width$.7e}")
+            }
+            (_, Some(v)) => format!("{v:>width$.2e}"),
+        }
     }
 }
 
-impl Callback for ConvergenceOutput {
-    fn init(&mut self, _state: &SolverState) {
-        let header = format!(
-            "| {:5} | {:8} | {:8} | {:8} | {:8} | {:8} | {:8} |",
-            "NIT", "D_PRIMAL", "D_DUAL", "PRI_INF", "DUAL_INF", "CS_L", "CS_U"
-        );
+/// Prints a fixed-width table of the iterates to stdout.
+///
+/// The columns are chosen by the comma-separated `log_columns` option from
+/// `iter`, `pobj`, `dobj`, `gap`, `pinf`, `dinf`, `cs_l`, `cs_u`, `mu`,
+/// `alpha_p`, `alpha_d`, `block_p`, `block_d` and `time`. Values a solver does not provide are
+/// printed as `-`. The header is repeated every `log_header_frequency` rows.
+///
+/// Only every `print_frequency`-th iterate is printed. The last iterate is
+/// printed when the solve finishes, even if it falls between them, followed by
+/// the [`summary`] of the solve if `log_summary` is set and the options
+/// consumed by the solve if `log_options` is set.
+#[explicit_options(name = SolverOptions)]
+#[use_option(name = "log_columns", type_ = String, default = "iter,pobj,dobj,gap,pinf,dinf,mu,alpha_p,alpha_d,time", description = "Comma-separated columns of the iteration log")]
+#[use_option(name = "log_header_frequency", type_ = usize, default = "25", description = "Number of iteration log rows between repeated headers (0 prints the header once)")]
+#[use_option(name = "log_options", type_ = bool, default = "false", description = "Whether the iteration log ends with the options consumed by the solve")]
+#[use_option(name = "log_summary", type_ = bool, default = "true", description = "Whether the iteration log ends with a summary of the solve")]
+#[use_option(name = "print_frequency", type_ = usize, default = "1", description = "Number of iterations between rows of the iteration log (0 prints only the last iterate)")]
+#[derive(Clone)]
+pub struct ConvergenceOutput {
+    columns: Vec<LogColumn>,
+    unknown_columns: Vec<String>,
+    start: Option<Instant>,
+    n_rows: usize,
+    n_calls: usize,
+    /// Row of the last iterate if it was not printed.
+    pending: Option<String>,
+}
+
+impl ConvergenceOutput {
+    /// Creates the callback with the columns and frequencies of `options`.
+    /// Unknown names in `log_columns` are skipped with a warning when the
+    /// solve starts, see [`ConvergenceOutput::get_unknown_columns`].
+    pub fn new(options: &SolverOptions) -> Self {
+        let options: ConvergenceOutputInternalOptions = options.into();
+        let (mut columns, mut unknown_columns) = (Vec::new(), Vec::new());
+        for name in options.log_columns.split(',') {
+            match LogColumn::from_name(name) {
+                Some(column) => columns.push(column),
+                None if !name.trim().is_empty() => unknown_columns.push(name.trim().to_string()),
+                None => {}
+            }
+        }
+        Self {
+            options,
+            columns,
+            unknown_columns,
+            start: None,
+            n_rows: 0,
+            n_calls: 0,
+            pending: None,
+        }
+    }
+
+    pub fn get_columns(&self) -> &[LogColumn] {
+        &self.columns
+    }
+
+    /// Names in `log_columns` that are not a column.
+    pub fn get_unknown_columns(&self) -> &[String] {
+        &self.unknown_columns
+    }
+
+    /// Prints `row`, preceded by the header when it is due.
+    fn print_row(&mut self, row: &str) {
+        let frequency = self.options.log_header_frequency;
+        if frequency > 0 && self.n_rows > 0 && self.n_rows.is_multiple_of(frequency) {
+            self.print_header();
+        }
+        println!("{row}");
+        self.n_rows += 1;
+    }
+
+    fn header(&self) -> String {
+        let cells = self
+            .columns
+            .iter()
+            .map(|c| format!("{:>width$}", c.header(), width = c.width()))
+            .collect::<Vec<_>>();
+        format!("| {} |", cells.join(" | "))
+    }
 
-        let separator = "-".repeat(header.len());
-        println!("");
+    fn print_header(&self) {
+        let header = self.header();
         println!("{header}");
-        println!("{separator}");
+        println!("{}", "-".repeat(header.len()));
     }
 
-    fn call(&mut self, state: &SolverState) {
-        let txt = format!(
-            "| {:5} | {:<8.2e} | {:<8.2e} | {:<8.2e} | {:<8.2e} | {:<8.2e} | {:<8.2e} |",
-            state.nit,
-            state.alpha_primal,
-            state.alpha_dual,
-            state.get_primal_feasibility().norm_l2() / state.x.nrows() as E,
-            state.get_dual_feasibility().norm_l2() / state.x.nrows() as E,
-            state.get_cs_lower().norm_l2() / state.x.nrows() as E,
-            state.get_cs_upper().norm_l2() / state.x.nrows() as E,
-        );
-        println!("{txt}");
+    /// Formats the table row of `state`.
+    pub fn row<S: StateView + ?Sized>(&self, state: &S) -> String {
+        let elapsed = self.start.map_or(0., |t| t.elapsed().as_secs_f64());
+        let cells = self
+            .columns
+            .iter()
+            .map(|c| c.format(state, elapsed))
+            .collect::<Vec<_>>();
+        format!("| {} |", cells.join(" | "))
+    }
+}
+
+impl Debug for ConvergenceOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConvergenceOutput")
+            .field("columns", &self.columns)
+            .field("header_frequency", &self.options.log_header_frequency)
+            .field("print_frequency", &self.options.print_frequency)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for ConvergenceOutput {
+    fn eq(&self, other: &Self) -> bool {
+        self.columns == other.columns
+            && self.options.log_header_frequency == other.options.log_header_frequency
+            && self.options.print_frequency == other.options.print_frequency
+    }
+}
+
+impl Eq for ConvergenceOutput {}
+
+impl Hash for ConvergenceOutput {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.columns.hash(state);
+        self.options.log_header_frequency.hash(state);
+        self.options.print_frequency.hash(state);
+    }
+}
+
+impl<S: StateView + ?Sized> Callback<S> for ConvergenceOutput {
+    fn init(&mut self, _state: &S) {
+        self.start = Some(Instant::now());
+        self.n_rows = 0;
+        self.n_calls = 0;
+        self.pending = None;
+        println!();
+        for name in &self.unknown_columns {
+            println!("Ignoring unknown log column '{name}'");
+        }
+        self.print_header();
+    }
+
+    fn call(&mut self, state: &S) {
+        let frequency = self.options.print_frequency;
+        let row = self.row(state);
+        if frequency > 0 && self.n_calls.is_multiple_of(frequency) {
+            self.print_row(&row);
+            self.pending = None;
+        } else {
+            self.pending = Some(row);
+        }
+        self.n_calls += 1;
     }
 
-    fn finish(&mut self) {
-        println!("");
+    fn finish(&mut self, state: &S, stats: &SolveStats) {
+        if let Some(row) = self.pending.take() {
+            self.print_row(&row);
+        }
+        println!();
+        if self.options.log_summary {
+            println!("{}", summary(state, stats));
+        }
+        if self.options.log_options
+            && let Some(options) = stats.get_effective_options()
+        {
+            print!("{options}");
+        }
     }
 }
 
-#[enum_dispatch(Callback)]
+/// One-block summary of a solve: its status and the criterion that stopped it,
+/// the number of iterations, the final objective, the largest constraint
+/// violation and the total time.
+pub fn summary<S: StateView + ?Sized>(state: &S, stats: &SolveStats) -> String {
+    let status = match stats.get_termination() {
+        Some(termination) => format!("{:?} ({})", stats.get_status(), termination.get_reason()),
+        None => format!("{:?}", stats.get_status()),
+    };
+    let objective = match state.get_objective() {
+        Some(objective) => format!("{objective:.7e}"),
+        None => "-".to_string(),
+    };
+    [
+        ("Status", status),
+        ("Iterations", stats.get_n_iterations().to_string()),
+        ("Objective", objective),
+        (
+            "Max violation",
+            format!("{:.2e}", state.get_primal_feasibility().norm_max()),
+        ),
+        (
+            "Time",
+            format!("{:.3} s", stats.get_solve_time().as_secs_f64()),
+        ),
+    ]
+    .iter()
+    .map(|(label, value)| format!("{label:<15}{value}"))
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Callbacks {
     NoOp(NoOpCallback),
-    ConvergenceOutput(ConvergenceOutput),
+    ConvergenceOutput(Box<ConvergenceOutput>),
+}
+
+impl From<NoOpCallback> for Callbacks {
+    fn from(callback: NoOpCallback) -> Self {
+        Callbacks::NoOp(callback)
+    }
+}
+
+impl From<ConvergenceOutput> for Callbacks {
+    fn from(callback: ConvergenceOutput) -> Self {
+        Callbacks::ConvergenceOutput(Box::new(callback))
+    }
 }
 
+impl<S: StateView + ?Sized> Callback<S> for Callbacks {
+    fn init(&mut self, state: &S) {
+        match self {
+            Callbacks::NoOp(cb) => cb.init(state),
+            Callbacks::ConvergenceOutput(cb) => cb.init(state),
+        }
+    }
+
+    fn call(&mut self, state: &S) {
+        match self {
+            Callbacks::NoOp(cb) => cb.call(state),
+            Callbacks::ConvergenceOutput(cb) => cb.call(state),
+        }
+    }
+
+    fn finish(&mut self, state: &S, stats: &SolveStats) {
+        match self {
+            Callbacks::NoOp(cb) => cb.finish(state, stats),
+            Callbacks::ConvergenceOutput(cb) => cb.finish(state, stats),
+        }
+    }
+}
+
+/// Combines multiple callbacks; each is invoked in order.
 #[derive(Debug, Clone)]
-struct MultiCallback {
+pub struct MultiCallback {
     callbacks: Vec<Callbacks>,
 }
 
@@ -86,39 +410,80 @@ impl MultiCallback {
         Self { callbacks }
     }
 
-    #[allow(unused)]
     pub fn new_empty() -> Self {
         Self {
             callbacks: Vec::new(),
         }
     }
 
-    #[allow(unused)]
     pub fn add_callback(&mut self, callback: Callbacks) {
         self.callbacks.push(callback);
     }
+
+    pub fn get_callbacks(&self) -> &[Callbacks] {
+        &self.callbacks
+    }
 }
 
-impl Callback for MultiCallback {
-    fn init(&mut self, state: &SolverState) {
+impl<S: StateView + ?Sized> Callback<S> for MultiCallback {
+    fn init(&mut self, state: &S) {
         for cb in &mut self.callbacks {
-            <Callbacks as Callback>::init(cb, state);
+            <Callbacks as Callback<S>>::init(cb, state);
         }
     }
 
-    fn call(&mut self, state: &SolverState) {
+    fn call(&mut self, state: &S) {
         for cb in &mut self.callbacks {
-            <Callbacks as Callback>::call(cb, state);
+            <Callbacks as Callback<S>>::call(cb, state);
         }
     }
 
-    fn finish(&mut self) {
+    fn finish(&mut self, state: &S, stats: &SolveStats) {
         for cb in &mut self.callbacks {
-            <Callbacks as Callback>::finish(cb);
+            <Callbacks as Callback<S>>::finish(cb, state, stats);
+        }
+    }
+}
+
+/// Invokes two callbacks in order, see [`SolverHooks::with_callback`](crate::SolverHooks::with_callback).
+#[derive(Debug)]
+pub(crate) struct ChainedCallback {
+    first: Box<dyn Callback>,
+    second: Box<dyn Callback>,
+}
+
+impl ChainedCallback {
+    pub(crate) fn new(first: Box<dyn Callback>, second: Box<dyn Callback>) -> Self {
+        Self { first, second }
+    }
+}
+
+impl Clone for ChainedCallback {
+    fn clone(&self) -> Self {
+        Self {
+            first: dyn_clone::clone_box(&*self.first),
+            second: dyn_clone::clone_box(&*self.second),
         }
     }
 }
 
+impl Callback for ChainedCallback {
+    fn init(&mut self, state: &SolverState) {
+        self.first.init(state);
+        self.second.init(state);
+    }
+
+    fn call(&mut self, state: &SolverState) {
+        self.first.call(state);
+        self.second.call(state);
+    }
+
+    fn finish(&mut self, state: &SolverState, stats: &SolveStats) {
+        self.first.finish(state, stats);
+        self.second.finish(state, stats);
+    }
+}
+
 pub struct Builder {
     callback: HashSet<Callbacks>,
     options: SolverOptions,
@@ -151,3 +516,124 @@ impl Builder {
         Box::new(MultiCallback::new(self.callback.iter().cloned().collect()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use faer::{Col, col};
+    use rstest::rstest;
+
+    use super::*;
+    use crate::{Status, TerminationInfo};
+
+    fn stats() -> SolveStats {
+        SolveStats::new(
+            Status::Optimal,
+            3,
+            std::time::Duration::from_millis(1500),
+            Some(TerminationInfo::new(Status::Optimal, "converged", 1e-9)),
+        )
+    }
+
+    #[rstest]
+    #[case("iter, pinf,DINF", vec![LogColumn::Iteration, LogColumn::PrimalInfeasibility, LogColumn::DualInfeasibility])]
+    #[case("mu,alpha_p,alpha_d,time,", vec![LogColumn::Mu, LogColumn::AlphaPrimal, LogColumn::AlphaDual, LogColumn::Time])]
+    fn test_log_columns(#[case] columns: &str, #[case] expected: Vec<LogColumn>) {
+        let mut options = SolverOptions::new();
+        options
+            .set_option("log_columns", columns.to_string())
+            .unwrap();
+        let output = ConvergenceOutput::new(&options);
+        assert_eq!(output.get_columns(), expected.as_slice());
+    }
+
+    #[test]
+    fn test_unknown_log_column() {
+        let mut options = SolverOptions::new();
+        options
+            .set_option("log_columns", "iter,objective".to_string())
+            .unwrap();
+        let output = ConvergenceOutput::new(&options);
+        assert_eq!(output.get_columns(), &[LogColumn::Iteration]);
+        assert_eq!(output.get_unknown_columns(), &["objective".to_string()]);
+        assert!(crate::SolverHooks::default_for(&options).is_err());
+    }
+
+    #[test]
+    fn test_print_frequency() {
+        let mut options = SolverOptions::new();
+        options.set_option("print_frequency", 3usize).unwrap();
+        let mut output = ConvergenceOutput::new(&options);
+        let state = SolverState::new(col![1.], Col::zeros(0), Col::zeros(1), Col::zeros(1));
+
+        Callback::<SolverState>::init(&mut output, &state);
+        for _ in 0..7 {
+            output.call(&state);
+        }
+        assert_eq!(output.n_rows, 3);
+        assert!(output.pending.is_none());
+
+        // The last iterate is printed on finish
+        output.call(&state);
+        assert_eq!(output.n_rows, 3);
+        output.finish(&state, &stats());
+        assert_eq!(output.n_rows, 4);
+    }
+
+    #[test]
+    fn test_log_row() {
+        let output = ConvergenceOutput::new(&SolverOptions::new());
+        let mut state = SolverState::new(col![1., 2.], Col::zeros(1), Col::zeros(2), Col::zeros(2));
+        state.nit = 12;
+        state.primal_feasibility = col![4.];
+
+        let row = output.row(&state);
+        assert_eq!(row.len(), output.header().len());
+        let cells: Vec<_> = row.trim_matches('|').split('|').map(str::trim).collect();
+        // Objectives and the barrier parameter are not set on this state
+        assert_eq!(cells[..5], ["12", "-", "-", "-", "2.00e0"]);
+        assert_eq!(cells[6], "-");
+    }
+
+    #[test]
+    fn test_blocking_columns() {
+        let mut options = SolverOptions::new();
+        options
+            .set_option("log_columns", "block_p,block_d".to_string())
+            .unwrap();
+        let output = ConvergenceOutput::new(&options);
+        let mut state = SolverState::new(col![1., 2.], Col::zeros(1), Col::zeros(2), Col::zeros(2));
+        state.blocking_primal = Some(1);
+
+        let row = output.row(&state);
+        let cells: Vec<_> = row.trim_matches('|').split('|').map(str::trim).collect();
+        assert_eq!(cells, ["1", "-"]);
+    }
+
+    #[test]
+    fn test_multi_callback() {
+        let mut multi = MultiCallback::new_empty();
+        multi.add_callback(NoOpCallback::new().into());
+        multi.add_callback(ConvergenceOutput::new(&SolverOptions::new()).into());
+        assert_eq!(multi.get_callbacks().len(), 2);
+
+        let state = SolverState::new(col![1.], Col::zeros(0), Col::zeros(1), Col::zeros(1));
+        Callback::<SolverState>::init(&mut multi, &state);
+        multi.call(&state);
+        multi.finish(&state, &stats());
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut state = SolverState::new(col![1., 2.], Col::zeros(1), Col::zeros(2), Col::zeros(2));
+        state.primal_feasibility = col![-4e-7];
+
+        let summary = summary(&state, &stats());
+        let lines: Vec<_> = summary.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], "Status         Optimal (converged)");
+        assert_eq!(lines[1], "Iterations     3");
+        assert_eq!(lines[2], "Objective      -");
+        assert_eq!(lines[3], "Max violation  4.00e-7");
+        assert_eq!(lines[4], "Time           1.500 s");
+    }
+}
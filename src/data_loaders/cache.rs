@@ -0,0 +1,425 @@
+//! On-disk cache of the programs converted from SIF and MPS files.
+//!
+//! Converting a large model dominates the runtime of tests and benchmarks that
+//! load it repeatedly. A [`ModelCache`] stores the converted program and its
+//! [`SifTransformation`] with bincode next to the other cached artifacts, so
+//! that later reads only decode the stored matrices.
+//!
+//! An entry is named after the path of the model. Its key records a hash of
+//! the contents of the file it was converted from, together with everything
+//! else the conversion depends on: the layout of the entries, the version of
+//! the crate, the version of the conversion and the infinity threshold. An
+//! entry whose key differs from that of the file, e.g. after the model was
+//! replaced or the conversion changed, is converted again and overwritten, so
+//! stale entries are never returned. The hash is not guaranteed to be stable
+//! across Rust releases, which at worst converts the models once more.
+
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use faer::{
+    Col,
+    sparse::{SparseColMat, SymbolicSparseColMat},
+    traits::IndexCore,
+};
+use problemo::{Problem, common::IntoCommonProblem};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    DEFAULT_INFINITY_THRESHOLD, E, I,
+    interface::sif::{ReadSIF, SifTransformation, open_model_file},
+    lp::LinearProgram,
+    qp::QuadraticProgram,
+    to_index,
+    utils::io::get_cache_dir,
+};
+
+/// Version of the layout of the entries, bumped whenever it changes.
+const FORMAT_VERSION: u32 = 2;
+
+/// Version of the conversion of models by [`ReadSIF`], bumped whenever a change
+/// alters the programs or transformations it produces. Development builds
+/// share the version of the crate, so it alone does not invalidate entries.
+///
+/// 2. Later `N` rows are free rows.
+/// 3. Fixed columns are removed from the program.
+const CONVERSION_VERSION: u32 = 3;
+
+/// Sparse matrix in compressed column form with portable indices.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedMatrix {
+    nrows: usize,
+    ncols: usize,
+    col_ptr: Vec<usize>,
+    row_idx: Vec<usize>,
+    values: Vec<E>,
+}
+
+impl CachedMatrix {
+    fn new(matrix: &SparseColMat<I, E>) -> Self {
+        let symbolic = matrix.symbolic();
+        Self {
+            nrows: matrix.nrows(),
+            ncols: matrix.ncols(),
+            col_ptr: symbolic.col_ptr().iter().map(|p| p.zx()).collect(),
+            row_idx: symbolic.row_idx().iter().map(|i| i.zx()).collect(),
+            values: matrix.val().to_vec(),
+        }
+    }
+
+    fn into_matrix(self) -> SparseColMat<I, E> {
+        let symbolic = SymbolicSparseColMat::new_checked(
+            self.nrows,
+            self.ncols,
+            self.col_ptr.into_iter().map(to_index).collect(),
+            None,
+            self.row_idx.into_iter().map(to_index).collect(),
+        );
+        SparseColMat::new(symbolic, self.values)
+    }
+}
+
+/// Data of a program as stored in the cache.
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedProgram {
+    Q: Option<CachedMatrix>,
+    c: Vec<E>,
+    A: CachedMatrix,
+    b: Vec<E>,
+    l: Vec<E>,
+    u: Vec<E>,
+}
+
+#[allow(non_snake_case)]
+impl CachedProgram {
+    fn new(
+        Q: Option<&SparseColMat<I, E>>,
+        c: &Col<E>,
+        A: &SparseColMat<I, E>,
+        b: &Col<E>,
+        l: &Col<E>,
+        u: &Col<E>,
+    ) -> Self {
+        let values = |col: &Col<E>| col.iter().copied().collect();
+        Self {
+            Q: Q.map(CachedMatrix::new),
+            c: values(c),
+            A: CachedMatrix::new(A),
+            b: values(b),
+            l: values(l),
+            u: values(u),
+        }
+    }
+}
+
+/// Programs that a [`ModelCache`] can store, as converted by [`ReadSIF`].
+pub trait CacheableProgram: ReadSIF {
+    /// Extension of the entries, which keeps the programs of a model apart.
+    const KIND: &'static str;
+
+    fn to_cached(&self) -> CachedProgram;
+
+    fn from_cached(program: CachedProgram) -> Self;
+}
+
+impl CacheableProgram for LinearProgram {
+    const KIND: &'static str = "lp";
+
+    fn to_cached(&self) -> CachedProgram {
+        CachedProgram::new(
+            None,
+            self.get_objective(),
+            self.get_constraint_matrix(),
+            self.get_rhs(),
+            self.get_lower_bounds(),
+            self.get_upper_bounds(),
+        )
+    }
+
+    fn from_cached(program: CachedProgram) -> Self {
+        Self::new(
+            Col::from_iter(program.c),
+            program.A.into_matrix(),
+            Col::from_iter(program.b),
+            Col::from_iter(program.l),
+            Col::from_iter(program.u),
+        )
+    }
+}
+
+#[allow(non_snake_case)]
+impl CacheableProgram for QuadraticProgram {
+    const KIND: &'static str = "qp";
+
+    fn to_cached(&self) -> CachedProgram {
+        CachedProgram::new(
+            Some(self.get_quadratic_objective()),
+            self.get_linear_objective(),
+            self.get_constraint_matrix(),
+            self.get_rhs(),
+            self.get_lower_bounds(),
+            self.get_upper_bounds(),
+        )
+    }
+
+    fn from_cached(program: CachedProgram) -> Self {
+        let n = program.c.len();
+        let Q = match program.Q {
+            Some(Q) => Q.into_matrix(),
+            None => SparseColMat::try_new_from_triplets(n, n, &[]).unwrap(),
+        };
+        Self::new(
+            Q,
+            Col::from_iter(program.c),
+            program.A.into_matrix(),
+            Col::from_iter(program.b),
+            Col::from_iter(program.l),
+            Col::from_iter(program.u),
+        )
+    }
+}
+
+/// Inputs of the conversion that produced an entry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CacheKey {
+    format_version: u32,
+    crate_version: String,
+    conversion_version: u32,
+    infinity_threshold: E,
+    /// Hash of the contents of the model file.
+    hash: u64,
+}
+
+impl CacheKey {
+    fn new(hash: u64) -> Self {
+        Self {
+            format_version: FORMAT_VERSION,
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            conversion_version: CONVERSION_VERSION,
+            infinity_threshold: DEFAULT_INFINITY_THRESHOLD,
+            hash,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    key: CacheKey,
+    program: CachedProgram,
+    transformation: SifTransformation,
+}
+
+/// Cache of converted programs in a directory. See the
+/// [module documentation](self).
+#[derive(Debug)]
+pub struct ModelCache {
+    dir: PathBuf,
+    n_hits: AtomicUsize,
+    n_misses: AtomicUsize,
+}
+
+impl Default for ModelCache {
+    /// Cache in the `models` directory of the artifacts.
+    fn default() -> Self {
+        Self::new(format!("{}/models", get_cache_dir()))
+    }
+}
+
+impl ModelCache {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self {
+            dir: dir.into(),
+            n_hits: AtomicUsize::new(0),
+            n_misses: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn get_dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Number of reads answered from the cache.
+    pub fn get_n_hits(&self) -> usize {
+        self.n_hits.load(Ordering::Relaxed)
+    }
+
+    /// Number of reads that converted the model.
+    pub fn get_n_misses(&self) -> usize {
+        self.n_misses.load(Ordering::Relaxed)
+    }
+
+    /// Reads the model at `path`, from the cache if it holds an entry for its
+    /// current contents.
+    pub fn read<P: CacheableProgram>(&self, path: impl AsRef<Path>) -> Result<P, Problem> {
+        Ok(self.read_with_transformation(path)?.0)
+    }
+
+    /// Reads the model at `path` and the record of its conversion, from the
+    /// cache if it holds an entry for its current contents. Otherwise, the
+    /// model is converted and stored; failing to store it is not an error.
+    pub fn read_with_transformation<P: CacheableProgram>(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(P, SifTransformation), Problem> {
+        let path = path.as_ref();
+        let key = CacheKey::new(hash_bytes(&std::fs::read(path)?));
+        let entry_path = self.entry_path::<P>(path);
+
+        if let Some(entry) = std::fs::read(&entry_path)
+            .ok()
+            .and_then(|bytes| decode(&bytes))
+            .filter(|entry| entry.key == key)
+        {
+            self.n_hits.fetch_add(1, Ordering::Relaxed);
+            return Ok((P::from_cached(entry.program), entry.transformation));
+        }
+
+        self.n_misses.fetch_add(1, Ordering::Relaxed);
+        let (program, transformation) =
+            P::read_sif_with_threshold(open_model_file(path)?, key.infinity_threshold)?;
+        let entry = CacheEntry {
+            key,
+            program: program.to_cached(),
+            transformation,
+        };
+        let _ = self.store(&entry_path, &entry);
+        Ok((program, entry.transformation))
+    }
+
+    /// Removes all entries.
+    pub fn clear(&self) -> Result<(), Problem> {
+        if self.dir.exists() {
+            std::fs::remove_dir_all(&self.dir)?;
+        }
+        Ok(())
+    }
+
+    /// Entry of the model at `path`, named after its file name and a hash of
+    /// its canonical path, so that models of the same name in different
+    /// directories do not evict each other.
+    fn entry_path<P: CacheableProgram>(&self, path: &Path) -> PathBuf {
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        self.dir.join(format!(
+            "{name}-{:016x}.{}.bin",
+            hash_bytes(canonical.as_os_str().as_encoded_bytes()),
+            P::KIND
+        ))
+    }
+
+    fn store(&self, entry_path: &Path, entry: &CacheEntry) -> Result<(), Problem> {
+        let bytes = bincode::serde::encode_to_vec(entry, bincode::config::standard())
+            .map_err(|e| format!("Failed to encode cache entry: {e}").gloss())?;
+        std::fs::create_dir_all(&self.dir)?;
+        // Written under a temporary name, so that concurrent readers never see
+        // a partial entry
+        let partial = entry_path.with_extension(format!("{}.partial", std::process::id()));
+        std::fs::write(&partial, bytes)?;
+        std::fs::rename(&partial, entry_path)?;
+        Ok(())
+    }
+}
+
+fn decode(bytes: &[u8]) -> Option<CacheEntry> {
+    bincode::serde::decode_from_slice(bytes, bincode::config::standard())
+        .ok()
+        .map(|(entry, _)| entry)
+}
+
+fn hash_bytes(bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::col;
+
+    use super::*;
+
+    /// `min x + 2 y` subject to `x + y = 4` and `x - y <= 1` with `0 <= x, y <= 10`.
+    const MODEL: &str = "\
+NAME          SMALL
+ROWS
+ N  obj
+ E  e1
+ L  l1
+COLUMNS
+    x         obj       1.0          e1        1.0
+    x         l1        1.0
+    y         obj       2.0          e1        1.0
+    y         l1        -1.0
+RHS
+    rhs       e1        4.0          l1        1.0
+BOUNDS
+ UP bnd       x         10.0
+ UP bnd       y         10.0
+ENDATA
+";
+
+    #[test]
+    fn test_model_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let model_path = dir.path().join("small.mps");
+        std::fs::write(&model_path, MODEL).unwrap();
+        let cache = ModelCache::new(dir.path().join("models"));
+
+        let (expected, transformation) =
+            LinearProgram::read_sif_with_transformation(MODEL.as_bytes()).unwrap();
+        for _ in 0..2 {
+            let (lp, cached_transformation) = cache
+                .read_with_transformation::<LinearProgram>(&model_path)
+                .unwrap();
+            assert_eq!(lp.get_objective(), expected.get_objective());
+            assert_eq!(lp.get_rhs(), expected.get_rhs());
+            assert_eq!(lp.get_upper_bounds(), expected.get_upper_bounds());
+            assert_eq!(
+                lp.get_constraint_matrix().to_dense(),
+                expected.get_constraint_matrix().to_dense()
+            );
+            assert_eq!(cached_transformation, transformation);
+        }
+        assert_eq!((cache.get_n_hits(), cache.get_n_misses()), (1, 1));
+
+        // Programs of another type are kept apart
+        let qp: QuadraticProgram = cache.read(&model_path).unwrap();
+        assert_eq!(qp.get_quadratic_objective().compute_nnz(), 0);
+        assert_eq!(cache.get_n_misses(), 2);
+        assert_eq!(
+            cache
+                .read::<QuadraticProgram>(&model_path)
+                .unwrap()
+                .get_n_vars(),
+            3
+        );
+        assert_eq!(cache.get_n_hits(), 2);
+
+        // Changing the model invalidates its entry
+        std::fs::write(&model_path, MODEL.replace("e1        4.0", "e1        6.0")).unwrap();
+        let lp: LinearProgram = cache.read(&model_path).unwrap();
+        assert_eq!(lp.get_rhs(), &col![6., 1.]);
+        assert_eq!(cache.get_n_misses(), 3);
+
+        // So does an entry of another conversion of the same model
+        let entry_path = cache.entry_path::<LinearProgram>(&model_path);
+        let mut entry = decode(&std::fs::read(&entry_path).unwrap()).unwrap();
+        assert_eq!(entry.key.crate_version, env!("CARGO_PKG_VERSION"));
+        entry.key.conversion_version -= 1;
+        cache.store(&entry_path, &entry).unwrap();
+        let _: LinearProgram = cache.read(&model_path).unwrap();
+        assert_eq!(cache.get_n_misses(), 4);
+        let _: LinearProgram = cache.read(&model_path).unwrap();
+        assert_eq!(cache.get_n_hits(), 3);
+
+        cache.clear().unwrap();
+        assert!(!cache.get_dir().exists());
+    }
+}
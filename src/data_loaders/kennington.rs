@@ -0,0 +1,91 @@
+//! Loader for the Kennington LP test set.
+//!
+//! The instances are distributed by Netlib as gzipped EMPS files. They are
+//! downloaded on demand, expanded to MPS in the cache directory, and converted
+//! with the streaming reader of [`ReadSIF`](crate::interface::sif::ReadSIF), since the larger instances have
+//! hundreds of thousands of nonzeros.
+
+use std::{io::Read, path::Path};
+
+use flate2::read::GzDecoder;
+use problemo::{Problem, common::IntoCommonProblem};
+
+use crate::{
+    data_loaders::{
+        cache::{CacheableProgram, ModelCache},
+        mps::decompress_mps,
+        sif::download_http,
+    },
+    utils::io::get_cache_dir,
+};
+
+static KENNINGTON_URL: &str = "https://netlib.org/lp/data/kennington/";
+
+/// Instances of the Kennington test set.
+pub static KENNINGTON_CASES: &[&str] = &[
+    "cre-a", "cre-b", "cre-c", "cre-d", "ken-07", "ken-11", "ken-13", "ken-18", "osa-07", "osa-14",
+    "osa-30", "osa-60", "pds-02", "pds-06", "pds-10", "pds-20",
+];
+
+/// Downloads and expands a Kennington instance, returning the path of its MPS
+/// file. A gzipped `.mps.gz` file in the cache is used as is.
+pub fn download_case(case_name: &str) -> Result<String, Problem> {
+    let case_name = case_name.to_lowercase();
+    if !KENNINGTON_CASES.contains(&case_name.as_str()) {
+        return Err(format!("Unknown Kennington case '{case_name}'").gloss());
+    }
+
+    let cache_dir = format!("{}/kennington", get_cache_dir());
+    let mps_path = format!("{cache_dir}/{case_name}.mps");
+    let gz_path = format!("{mps_path}.gz");
+    for path in [&mps_path, &gz_path] {
+        if Path::new(path).exists() {
+            return Ok(path.clone());
+        }
+    }
+    std::fs::create_dir_all(&cache_dir)?;
+
+    let emps_path = format!("{cache_dir}/{case_name}.emps");
+    if !Path::new(&emps_path).exists() {
+        let gz = download_http(&format!("{KENNINGTON_URL}{case_name}.gz"))?;
+        let mut emps = Vec::new();
+        GzDecoder::new(&gz[..])
+            .read_to_end(&mut emps)
+            .map_err(|e| format!("Failed to unzip Kennington case '{case_name}': {e}").gloss())?;
+        std::fs::write(&emps_path, &emps)?;
+    }
+
+    let mps = decompress_mps(&emps_path)?;
+    std::fs::copy(mps.path(), &mps_path)?;
+    Ok(mps_path)
+}
+
+/// Downloads the instance if needed and converts it to a program, which is
+/// kept in the [`ModelCache`].
+pub fn get_case<P: CacheableProgram>(case_name: &str) -> Result<P, Problem> {
+    let mps_path = download_case(case_name)?;
+    ModelCache::default()
+        .read(&mps_path)
+        .map_err(|e| format!("Failed to read MPS file '{mps_path}': {e}").gloss())
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::lp::LinearProgram;
+
+    #[rstest]
+    #[ignore = "downloads large instances"]
+    fn test_get_kennington_lp(
+        #[values(
+            "cre-a", "cre-b", "cre-c", "cre-d", "ken-07", "ken-11", "ken-13", "ken-18", "osa-07",
+            "osa-14", "osa-30", "osa-60", "pds-02", "pds-06", "pds-10", "pds-20"
+        )]
+        case_name: &str,
+    ) {
+        let lp: LinearProgram = get_case(case_name).expect("Failed to load Kennington case");
+        assert!(lp.get_n_cons() > 0);
+    }
+}
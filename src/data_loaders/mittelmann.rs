@@ -0,0 +1,91 @@
+//! Loader for Hans Mittelmann's LP benchmark instances.
+//!
+//! Instances are addressed by their path relative to the benchmark directory,
+//! e.g. `"pds/pds-40.gz"`. Gzipped and uncompressed MPS files are downloaded
+//! and cached as they are, gzipped files as `<name>.mps.gz`, which is read
+//! without decompressing it on disk. Other compression formats are not
+//! supported, but a decompressed or gzipped copy placed at
+//! `<cache>/mittelmann/<name>.mps` or `<name>.mps.gz` is picked up without
+//! downloading.
+
+use std::path::Path;
+
+use problemo::{Problem, common::IntoCommonProblem};
+
+use crate::{
+    data_loaders::{
+        cache::{CacheableProgram, ModelCache},
+        sif::download_http,
+    },
+    utils::io::get_cache_dir,
+};
+
+static MITTELMANN_LP_URL: &str = "https://plato.asu.edu/ftp/lptestset/";
+
+/// Name under which an instance is cached: the file name without directory or
+/// compression extension.
+fn cache_name(case_path: &str) -> &str {
+    let file_name = case_path.rsplit('/').next().unwrap_or(case_path);
+    [".gz", ".bz2", ".mps"]
+        .iter()
+        .find_map(|ext| file_name.strip_suffix(ext))
+        .unwrap_or(file_name)
+}
+
+/// Downloads an instance if it is not cached, returning the path of its MPS
+/// file. Gzipped instances are cached as downloaded, as `.mps.gz` files.
+pub fn download_case(case_path: &str) -> Result<String, Problem> {
+    let cache_dir = format!("{}/mittelmann", get_cache_dir());
+    let mps_path = format!("{cache_dir}/{}.mps", cache_name(case_path));
+    let gz_path = format!("{mps_path}.gz");
+    for path in [&mps_path, &gz_path] {
+        if Path::new(path).exists() {
+            return Ok(path.clone());
+        }
+    }
+
+    let (path, data) = if case_path.ends_with(".gz") {
+        (
+            gz_path,
+            download_http(&format!("{MITTELMANN_LP_URL}{case_path}"))?,
+        )
+    } else if case_path.ends_with(".mps") || !case_path.contains('.') {
+        (
+            mps_path,
+            download_http(&format!("{MITTELMANN_LP_URL}{case_path}"))?,
+        )
+    } else {
+        return Err(format!(
+            "Unsupported compression for Mittelmann case '{case_path}', place a decompressed or gzipped copy at '{mps_path}'"
+        )
+        .gloss());
+    };
+
+    std::fs::create_dir_all(&cache_dir)?;
+    std::fs::write(&path, &data)?;
+    Ok(path)
+}
+
+/// Downloads the instance if needed and converts it to a program, which is
+/// kept in the [`ModelCache`].
+pub fn get_case<P: CacheableProgram>(case_path: &str) -> Result<P, Problem> {
+    let mps_path = download_case(case_path)?;
+    ModelCache::default()
+        .read(&mps_path)
+        .map_err(|e| format!("Failed to read MPS file '{mps_path}': {e}").gloss())
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("pds/pds-40.gz", "pds-40")]
+    #[case("rail4284.mps", "rail4284")]
+    #[case("nug/nug15.bz2", "nug15")]
+    fn test_cache_name(#[case] case_path: &str, #[case] expected: &str) {
+        assert_eq!(cache_name(case_path), expected);
+    }
+}
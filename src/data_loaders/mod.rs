@@ -1,5 +1,8 @@
 #![feature(fn_traits)]
 
+pub mod cache;
+pub mod kennington;
+pub mod mittelmann;
 pub mod mps;
 pub mod mtx;
 pub mod netlib;
@@ -47,6 +47,58 @@ pub fn decompress_mps(emps_path: &str) -> Result<NamedTempFile, Problem> {
     Ok(tmpfile)
 }
 
+/// Netlib LP instances in the EMPS format of netlib.org, an independent source
+/// of the models of the SIF collection.
+pub mod netlib {
+    use crate::{
+        data_loaders::cache::{CacheableProgram, ModelCache},
+        data_loaders::sif::download_http,
+        interface::sif::SifTransformation,
+        utils::io::get_cache_dir,
+    };
+
+    use super::*;
+
+    static NETLIB_EMPS_URL: &str = "https://netlib.org/lp/data/";
+
+    /// Downloads and expands a netlib instance, returning the path of its MPS
+    /// file. A gzipped `.mps.gz` file in the cache is used as is.
+    pub fn download_case(case_name: &str) -> Result<String, Problem> {
+        let case_name = case_name.to_lowercase();
+        let cache_dir = format!("{}/emps", get_cache_dir());
+        let mps_path = format!("{cache_dir}/{case_name}.mps");
+        if Path::new(&mps_path).exists() {
+            return Ok(mps_path);
+        }
+        let gz_path = format!("{mps_path}.gz");
+        if Path::new(&gz_path).exists() {
+            return Ok(gz_path);
+        }
+        std::fs::create_dir_all(&cache_dir)?;
+
+        let emps_path = format!("{cache_dir}/{case_name}.emps");
+        if !Path::new(&emps_path).exists() {
+            let emps = download_http(&format!("{NETLIB_EMPS_URL}{case_name}"))?;
+            std::fs::write(&emps_path, &emps)?;
+        }
+
+        let mps = decompress_mps(&emps_path)?;
+        std::fs::copy(mps.path(), &mps_path)?;
+        Ok(mps_path)
+    }
+
+    /// Downloads the instance if needed, converts it and returns the record of
+    /// the conversion. Conversions are kept in the [`ModelCache`].
+    pub fn read_case_with_transformation<P: CacheableProgram>(
+        case_name: &str,
+    ) -> Result<(P, SifTransformation), Problem> {
+        let mps_path = download_case(case_name)?;
+        ModelCache::default()
+            .read_with_transformation(&mps_path)
+            .map_err(|e| format!("Failed to read MPS file '{mps_path}': {e}").gloss())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use rstest::rstest;
@@ -1,16 +1,20 @@
-use faer::Index;
-use faer::sparse::{SparseColMat, Triplet};
+use faer::sparse::{SparseColMat, SparseColMatRef, Triplet};
 use faer::traits::ComplexField;
+use faer::{ColRef, Index};
 use flate2::bufread::GzDecoder;
 use matrix_market_rs::MtxData;
+use problemo::{Problem, common::IntoCommonProblem};
 use std::collections::HashMap;
 use std::fs::{File, OpenOptions};
-use std::io::{BufReader, Read, Write, copy};
+use std::io::{BufReader, BufWriter, Read, Write, copy};
+use std::ops::RangeBounds;
 use std::path::Path;
 use std::sync::LazyLock;
 use tempfile::NamedTempFile;
 
+use crate::data_loaders::sif::download_http;
 use crate::utils::io::get_cache_dir;
+use crate::{E, I};
 
 pub static MATRICES_URL_MAP: LazyLock<HashMap<&'static str, &'static str>> = LazyLock::new(|| {
     HashMap::from([
@@ -57,39 +61,117 @@ pub static MATRICES_URL_MAP: LazyLock<HashMap<&'static str, &'static str>> = Laz
     ])
 });
 
-pub fn get_matrix_by_name<I: Index + std::convert::From<usize>, E: ComplexField>(
-    name: &str,
-    sym: bool,
-) -> SparseColMat<I, E> {
+/// Base URL of the MatrixMarket archives of the SuiteSparse collection.
+static SUITESPARSE_MM_URL: &str = "https://suitesparse-collection-website.herokuapp.com/MM";
+
+/// Index of the SuiteSparse collection with the statistics of every matrix.
+static SUITESPARSE_INDEX_URL: &str = "https://sparse.tamu.edu/files/ssstats.csv";
+
+/// Loads a sparse matrix, downloading it to the cache directory if needed.
+///
+/// `name` is resolved in order as
+/// - a local `.mtx` file, or a `.tar.gz`/`.tgz` archive containing one,
+/// - an `http(s)` URL of such an archive,
+/// - one of the names in [`MATRICES_URL_MAP`],
+/// - a SuiteSparse `Group/Name` path, as returned by [`MatrixInfo::get_path`].
+pub fn get_matrix_by_name<I: Index, E: ComplexField>(name: &str, sym: bool) -> SparseColMat<I, E> {
+    let local = Path::new(name);
+    if local.is_file() {
+        return if is_archive(name) {
+            read_archive(local, archive_stem(name), sym)
+        } else {
+            read_matrix(local, sym).expect("Failed to parse Matrix Market data")
+        };
+    }
+
+    let (url, archive_name) = if name.starts_with("http://") || name.starts_with("https://") {
+        let file_name = name.rsplit(['/', '\\']).next().unwrap_or_default();
+        (name.to_string(), file_name.to_string())
+    } else if let Some(url) = MATRICES_URL_MAP.get(name) {
+        (url.to_string(), format!("{name}.tar.gz"))
+    } else if let Some((group, matrix)) = name.split_once('/') {
+        (
+            format!("{SUITESPARSE_MM_URL}/{group}/{matrix}.tar.gz"),
+            format!("{group}_{matrix}.tar.gz"),
+        )
+    } else {
+        panic!("Unknown matrix {name}");
+    };
+    assert!(
+        is_archive(&archive_name),
+        "Unsupported matrix archive {url}"
+    );
+
     let cache_dir = format!("{}/mtx", get_cache_dir());
     std::fs::create_dir_all(&cache_dir).expect("Failed to create cache directory");
 
-    let file_name = format!("{}/{}.tar.gz", cache_dir, name);
+    let file_name = format!("{}/{}", cache_dir, archive_name);
     if !Path::new(&file_name).exists() {
         println!("Downloading file {}", name);
-        let url = MATRICES_URL_MAP.get(name).expect("Unknown matrix");
-        let response = reqwest::blocking::get(*url).expect("Failed to download matrix");
-        let mut file = OpenOptions::new()
-            .write(true)
-            .create_new(true)
-            .open(&file_name)
-            .expect("Failed to create file");
-        copy(&mut response.take(usize::MAX as u64), &mut file).expect("Unable to copy file.");
-        file.sync_all().expect("Failed to sync file");
-    }
-
-    let file = File::open(&file_name).expect("Failed to read file");
+        download(&url, &file_name);
+    }
+
+    read_archive(Path::new(&file_name), archive_stem(&url), sym)
+}
+
+fn is_archive(name: &str) -> bool {
+    name.ends_with(".tar.gz") || name.ends_with(".tgz")
+}
+
+fn download(url: &str, file_name: &str) {
+    let response = reqwest::blocking::get(url).expect("Failed to download matrix");
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(file_name)
+        .expect("Failed to create file");
+    copy(&mut response.take(usize::MAX as u64), &mut file).expect("Unable to copy file.");
+    file.sync_all().expect("Failed to sync file");
+}
+
+/// Strips the directory and archive extension from a path or URL.
+fn archive_stem(name: &str) -> &str {
+    let file_name = name.rsplit(['/', '\\']).next().unwrap_or_default();
+    file_name
+        .strip_suffix(".tar.gz")
+        .or_else(|| file_name.strip_suffix(".tgz"))
+        .unwrap_or(file_name)
+}
+
+/// Reads the matrix of a SuiteSparse archive. Archives may also hold
+/// right-hand sides and coordinates, so the `.mtx` entry named `stem` is
+/// preferred over the first one.
+fn read_archive<I: Index, E: ComplexField>(
+    path: &Path,
+    stem: &str,
+    sym: bool,
+) -> SparseColMat<I, E> {
+    let file = File::open(path).expect("Failed to read file");
     let buf_reader = BufReader::new(file);
     let gz = GzDecoder::new(buf_reader);
     let mut archive = tar::Archive::new(gz);
-    let mut entries = archive.entries().unwrap();
 
-    let mut entry = entries.next().unwrap().unwrap();
-
-    let mut mtx_bytes = Vec::new();
-    entry
-        .read_to_end(&mut mtx_bytes)
-        .expect("Failed to read matrix file");
+    let mut mtx_bytes: Option<Vec<u8>> = None;
+    for entry in archive.entries().expect("Failed to read archive") {
+        let mut entry = entry.expect("Failed to read archive entry");
+        let entry_path = entry.path().expect("Invalid archive entry").into_owned();
+        if entry_path.extension().and_then(|s| s.to_str()) != Some("mtx") {
+            continue;
+        }
+        let preferred = entry_path.file_stem().and_then(|s| s.to_str()) == Some(stem);
+        if mtx_bytes.is_some() && !preferred {
+            continue;
+        }
+        let mut bytes = Vec::new();
+        entry
+            .read_to_end(&mut bytes)
+            .expect("Failed to read matrix file");
+        mtx_bytes = Some(bytes);
+        if preferred {
+            break;
+        }
+    }
+    let mtx_bytes = mtx_bytes.expect("No matrix file in archive");
 
     // Create a named temporary file
     let mut tmpfile = NamedTempFile::new().expect("Failed to create temp file");
@@ -97,13 +179,21 @@ pub fn get_matrix_by_name<I: Index + std::convert::From<usize>, E: ComplexField>
         .write_all(&mtx_bytes)
         .expect("Failed to write matrix data");
 
-    // Use the file path with your loader
-    let mtx =
-        MtxData::<f64, 2>::from_file(tmpfile.path()).expect("Failed to parse Matrix Market data");
+    read_matrix(tmpfile.path(), sym).expect("Failed to parse Matrix Market data")
+}
 
-    // Now you can destructure mtx as needed, for example:
+/// Reads a sparse matrix from a MatrixMarket file.
+///
+/// If `sym` is set the file is expected to hold one triangle of a symmetric
+/// matrix, which is mirrored into the full matrix.
+pub fn read_matrix<I: Index, E: ComplexField, P: AsRef<Path>>(
+    path: P,
+    sym: bool,
+) -> Result<SparseColMat<I, E>, Problem> {
+    let mtx = MtxData::<f64, 2>::from_file(path.as_ref())
+        .map_err(|e| format!("Failed to parse {}: {e:?}", path.as_ref().display()).gloss())?;
     let MtxData::Sparse([nrows, ncols], coord, val, _) = mtx else {
-        panic!("Matrix is not in sparse format");
+        return Err(format!("{} is not in sparse format", path.as_ref().display()).gloss());
     };
 
     SparseColMat::try_new_from_triplets(
@@ -116,8 +206,8 @@ pub fn get_matrix_by_name<I: Index + std::convert::From<usize>, E: ComplexField>
                 .flat_map(|(&[row, col], &val)| {
                     let val = if row == col { val / 2.0 } else { val };
                     [
-                        Triplet::new(row.into(), col.into(), E::from_f64_impl(val)),
-                        Triplet::new(col.into(), row.into(), E::from_f64_impl(val)),
+                        Triplet::new(I::truncate(row), I::truncate(col), E::from_f64_impl(val)),
+                        Triplet::new(I::truncate(col), I::truncate(row), E::from_f64_impl(val)),
                     ]
                 })
                 .collect::<Vec<_>>()
@@ -126,12 +216,215 @@ pub fn get_matrix_by_name<I: Index + std::convert::From<usize>, E: ComplexField>
                 .iter()
                 .zip(&val)
                 .map(|(&[row, col], &val)| {
-                    Triplet::new(row.into(), col.into(), E::from_f64_impl(val))
+                    Triplet::new(I::truncate(row), I::truncate(col), E::from_f64_impl(val))
                 })
                 .collect::<Vec<_>>()
         },
     )
-    .unwrap()
+    .map_err(|e| format!("Invalid matrix in {}: {e:?}", path.as_ref().display()).gloss())
+}
+
+/// Writes a sparse matrix in MatrixMarket coordinate format.
+///
+/// If `symmetric` is set the matrix is assumed to be symmetric and only its
+/// lower triangle is written, so that [`read_matrix`] with `sym` recovers it.
+pub fn write_matrix<P: AsRef<Path>>(
+    path: P,
+    mat: &SparseColMat<I, E>,
+    symmetric: bool,
+) -> Result<(), Problem> {
+    write_sparse(path, mat.as_ref(), symmetric)
+}
+
+/// Writes a sparse matrix view in MatrixMarket coordinate format with 1-based
+/// indices, keeping only the lower triangle if `symmetric`.
+pub(crate) fn write_sparse<P: AsRef<Path>>(
+    path: P,
+    mat: SparseColMatRef<'_, I, E>,
+    symmetric: bool,
+) -> Result<(), Problem> {
+    let entries = |j: usize| {
+        mat.row_idx_of_col(j)
+            .zip(mat.val_of_col(j))
+            .filter(move |(i, _)| !symmetric || *i >= j)
+    };
+    let nnz: usize = (0..mat.ncols()).map(|j| entries(j).count()).sum();
+
+    let mut writer = create_writer(path.as_ref())?;
+    let result = (|| {
+        writeln!(
+            writer,
+            "%%MatrixMarket matrix coordinate real {}",
+            if symmetric { "symmetric" } else { "general" }
+        )?;
+        writeln!(writer, "{} {} {}", mat.nrows(), mat.ncols(), nnz)?;
+        for j in 0..mat.ncols() {
+            for (i, value) in entries(j) {
+                writeln!(writer, "{} {} {:e}", i + 1, j + 1, value)?;
+            }
+        }
+        writer.flush()
+    })();
+    result.map_err(|e| format!("Failed to write {}: {e}", path.as_ref().display()).gloss())
+}
+
+/// Writes a column vector in MatrixMarket array format.
+pub(crate) fn write_dense<P: AsRef<Path>>(path: P, col: ColRef<'_, E>) -> Result<(), Problem> {
+    let mut writer = create_writer(path.as_ref())?;
+    let result = (|| {
+        writeln!(writer, "%%MatrixMarket matrix array real general")?;
+        writeln!(writer, "{} 1", col.nrows())?;
+        for value in col.iter() {
+            writeln!(writer, "{value:e}")?;
+        }
+        writer.flush()
+    })();
+    result.map_err(|e| format!("Failed to write {}: {e}", path.as_ref().display()).gloss())
+}
+
+fn create_writer(path: &Path) -> Result<BufWriter<File>, Problem> {
+    File::create(path)
+        .map(BufWriter::new)
+        .map_err(|e| format!("Failed to create {}: {e}", path.display()).gloss())
+}
+
+/// Entry of the SuiteSparse collection index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatrixInfo {
+    group: String,
+    name: String,
+    rows: usize,
+    columns: usize,
+    nonzeros: usize,
+    real: bool,
+    positive_definite: bool,
+    numerical_symmetry: f64,
+    kind: String,
+}
+
+impl MatrixInfo {
+    pub fn get_group(&self) -> &str {
+        &self.group
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// `Group/Name` path accepted by [`get_matrix_by_name`].
+    pub fn get_path(&self) -> String {
+        format!("{}/{}", self.group, self.name)
+    }
+
+    pub fn get_rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn get_columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn get_nonzeros(&self) -> usize {
+        self.nonzeros
+    }
+
+    pub fn is_real(&self) -> bool {
+        self.real
+    }
+
+    pub fn is_positive_definite(&self) -> bool {
+        self.positive_definite
+    }
+
+    /// Fraction of off-diagonal nonzeros matched by an equal transposed entry.
+    pub fn get_numerical_symmetry(&self) -> f64 {
+        self.numerical_symmetry
+    }
+
+    pub fn is_symmetric(&self) -> bool {
+        self.numerical_symmetry == 1.
+    }
+
+    /// Problem domain, e.g. `structural problem` or `circuit simulation problem`.
+    pub fn get_kind(&self) -> &str {
+        &self.kind
+    }
+}
+
+/// Downloads the SuiteSparse index to the cache directory if needed and
+/// returns its entries.
+pub fn get_suitesparse_index() -> Result<Vec<MatrixInfo>, Problem> {
+    let cache_dir = format!("{}/mtx", get_cache_dir());
+    let index_path = format!("{cache_dir}/ssstats.csv");
+    if !Path::new(&index_path).exists() {
+        std::fs::create_dir_all(&cache_dir)?;
+        let csv = download_http(SUITESPARSE_INDEX_URL)?;
+        std::fs::write(&index_path, &csv)?;
+    }
+    let file =
+        File::open(&index_path).map_err(|e| format!("Failed to open {index_path}: {e}").gloss())?;
+    parse_index(BufReader::new(file))
+}
+
+/// Lists the SuiteSparse matrices with a number of nonzeros in `nonzeros`
+/// and, if given, a kind containing `kind`, ordered by number of nonzeros.
+pub fn list_matrices<R: RangeBounds<usize>>(
+    nonzeros: R,
+    kind: Option<&str>,
+) -> Result<Vec<MatrixInfo>, Problem> {
+    let kind = kind.map(str::to_lowercase);
+    let mut matrices: Vec<_> = get_suitesparse_index()?
+        .into_iter()
+        .filter(|m| nonzeros.contains(&m.nonzeros))
+        .filter(|m| {
+            kind.as_ref()
+                .is_none_or(|k| m.kind.to_lowercase().contains(k))
+        })
+        .collect();
+    matrices.sort_by_key(|m| m.nonzeros);
+    Ok(matrices)
+}
+
+/// Parses `ssstats.csv`: a line with the number of matrices and one with the
+/// date of the index, followed by one line per matrix.
+fn parse_index<R: Read>(reader: R) -> Result<Vec<MatrixInfo>, Problem> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(reader);
+
+    let mut matrices = Vec::new();
+    for (line, record) in rdr.records().enumerate() {
+        let record = record.map_err(|e| format!("Invalid SuiteSparse index: {e}").gloss())?;
+        if line < 2 {
+            continue;
+        }
+        if record.len() < 12 {
+            return Err(format!("Invalid SuiteSparse index entry on line {}", line + 1).gloss());
+        }
+        let parse = |k: usize| {
+            record[k].trim().parse::<f64>().map_err(|_| {
+                format!(
+                    "Invalid value '{}' on line {} of SuiteSparse index",
+                    &record[k],
+                    line + 1
+                )
+                .gloss()
+            })
+        };
+        matrices.push(MatrixInfo {
+            group: record[0].to_string(),
+            name: record[1].to_string(),
+            rows: parse(2)? as usize,
+            columns: parse(3)? as usize,
+            nonzeros: parse(4)? as usize,
+            real: parse(5)? != 0.,
+            positive_definite: parse(8)? != 0.,
+            numerical_symmetry: parse(10)?,
+            kind: record[11].to_string(),
+        });
+    }
+    Ok(matrices)
 }
 
 #[cfg(test)]
@@ -150,4 +443,135 @@ mod tests {
         println!("Error = {:e}", error);
         assert!(error < 1e-12, "Matrix not symmetric");
     }
+
+    #[rstest]
+    fn test_write_round_trip(#[values(false, true)] symmetric: bool) {
+        let mat = SparseColMat::<I, E>::try_new_from_triplets(
+            3,
+            3,
+            &[
+                Triplet::new(0, 0, 4.),
+                Triplet::new(1, 0, -1.25),
+                Triplet::new(0, 1, -1.25),
+                Triplet::new(1, 1, 1e-300),
+                Triplet::new(2, 1, 1. / 3.),
+                Triplet::new(1, 2, 1. / 3.),
+                Triplet::new(2, 2, 7.5e12),
+            ],
+        )
+        .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mat.mtx");
+        write_matrix(&path, &mat, symmetric).unwrap();
+
+        let header = std::fs::read_to_string(&path).unwrap();
+        let nnz = if symmetric { "3 3 5" } else { "3 3 7" };
+        assert_eq!(header.lines().nth(1), Some(nnz));
+
+        let read = read_matrix::<I, E, _>(&path, symmetric).unwrap();
+        assert_eq!(read.to_dense(), mat.to_dense());
+    }
+
+    #[test]
+    fn test_write_rectangular() {
+        let mat = SparseColMat::<I, E>::try_new_from_triplets(
+            2,
+            4,
+            &[Triplet::new(1, 0, 2.), Triplet::new(0, 3, -0.5)],
+        )
+        .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mat.mtx");
+        write_matrix(&path, &mat, false).unwrap();
+        let read = read_matrix::<I, E, _>(&path, false).unwrap();
+        assert_eq!((read.nrows(), read.ncols()), (2, 4));
+        assert_eq!(read.to_dense(), mat.to_dense());
+    }
+
+    fn sample_matrix() -> SparseColMat<I, E> {
+        SparseColMat::try_new_from_triplets(
+            2,
+            2,
+            &[
+                Triplet::new(0, 0, 2.),
+                Triplet::new(1, 0, -1.),
+                Triplet::new(0, 1, -1.),
+                Triplet::new(1, 1, 3.),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[rstest]
+    #[case("https://host/MM/Group/Name.tar.gz", "Name")]
+    #[case("/tmp/bcsstk01.tgz", "bcsstk01")]
+    #[case("local.mtx", "local.mtx")]
+    fn test_archive_stem(#[case] name: &str, #[case] stem: &str) {
+        assert_eq!(archive_stem(name), stem);
+    }
+
+    #[rstest]
+    fn test_local_sources(#[values(false, true)] archived: bool) {
+        let mat = sample_matrix();
+        let dir = tempfile::tempdir().unwrap();
+        let mtx_path = dir.path().join("sample.mtx");
+        write_matrix(&mtx_path, &mat, true).unwrap();
+
+        let path = if archived {
+            // Decoy entry ahead of the matrix, as for right-hand sides in SuiteSparse
+            let rhs_path = dir.path().join("sample_b.mtx");
+            write_sparse(&rhs_path, mat.as_ref(), false).unwrap();
+
+            let path = dir.path().join("sample.tar.gz");
+            let gz = flate2::write::GzEncoder::new(
+                File::create(&path).unwrap(),
+                flate2::Compression::default(),
+            );
+            let mut builder = tar::Builder::new(gz);
+            builder
+                .append_path_with_name(&rhs_path, "sample/sample_b.mtx")
+                .unwrap();
+            builder
+                .append_path_with_name(&mtx_path, "sample/sample.mtx")
+                .unwrap();
+            builder.into_inner().unwrap().finish().unwrap();
+            path
+        } else {
+            mtx_path
+        };
+
+        let read = get_matrix_by_name::<I, E>(path.to_str().unwrap(), true);
+        assert_eq!(read.to_dense(), mat.to_dense());
+    }
+
+    #[test]
+    fn test_parse_index() {
+        let csv = "2\n\
+                   31-Oct-2023 18:12:37\n\
+                   HB,bcsstk01,48,48,400,1,0,1,1,1,1,structural problem,224\n\
+                   Bai,olm100,100,100,396,1,0,0,0,1,0.98,computational fluid dynamics problem,396\n";
+        let index = parse_index(csv.as_bytes()).unwrap();
+        assert_eq!(index.len(), 2);
+
+        assert_eq!(index[0].get_path(), "HB/bcsstk01");
+        assert_eq!(
+            (
+                index[0].get_rows(),
+                index[0].get_columns(),
+                index[0].get_nonzeros()
+            ),
+            (48, 48, 400)
+        );
+        assert!(index[0].is_real() && index[0].is_positive_definite() && index[0].is_symmetric());
+        assert_eq!(index[0].get_kind(), "structural problem");
+
+        assert_eq!(index[1].get_group(), "Bai");
+        assert_eq!(index[1].get_name(), "olm100");
+        assert!(!index[1].is_positive_definite() && !index[1].is_symmetric());
+        assert_eq!(index[1].get_numerical_symmetry(), 0.98);
+
+        assert!(parse_index("1\ndate\nHB,bcsstk01,48\n".as_bytes()).is_err());
+    }
 }
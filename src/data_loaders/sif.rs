@@ -1,4 +1,8 @@
-use crate::utils::io::get_cache_dir;
+use crate::{
+    data_loaders::cache::{CacheableProgram, ModelCache},
+    interface::sif::{SifTransformation, open_model_file},
+    utils::io::get_cache_dir,
+};
 use problemo::{Problem, ProblemResult, common::IntoCommonProblem};
 use sif_rs::SIF;
 use std::{io::Read, path::Path, sync::LazyLock};
@@ -7,7 +11,7 @@ static MAROS_MEZAROS_QP_TAR_URL: &str =
     "https://bitbucket.org/optrove/maros-meszaros/get/v0.1.tar.gz";
 static NETLIB_LP_TAR_URL: &str = "https://bitbucket.org/optrove/netlib-lp/get/v0.1.tar.gz";
 
-fn download_http(url: &str) -> Result<Vec<u8>, Problem> {
+pub(crate) fn download_http(url: &str) -> Result<Vec<u8>, Problem> {
     let response =
         reqwest::blocking::get(url).map_err(|e| format!("HTTP request failed: {e}").gloss())?;
     let total = response.content_length().unwrap_or(0);
@@ -120,54 +124,106 @@ pub fn download_netlib_lp() -> Result<(), Problem> {
     Ok(())
 }
 
+/// Returns the path of a cached SIF case, downloading the dataset if it is
+/// missing. A gzipped `.SIF.gz` file in the cache is used as is.
+fn case_path(
+    dataset: &str,
+    case_name: &str,
+    download: fn() -> Result<(), Problem>,
+) -> Result<String, Problem> {
+    let file_path = format!(
+        "{}/{}/{}.SIF",
+        get_cache_dir(),
+        dataset,
+        case_name.to_uppercase()
+    );
+    let gz_path = format!("{file_path}.gz");
+    if Path::new(&gz_path).exists() && !Path::new(&file_path).exists() {
+        return Ok(gz_path);
+    }
+    if !Path::new(&file_path).exists() {
+        download()?;
+    }
+    if !Path::new(&file_path).exists() {
+        return Err(format!(
+            "SIF file for case '{}' not found at '{}'",
+            case_name, file_path
+        )
+        .gloss());
+    }
+    Ok(file_path)
+}
+
 pub mod netlib {
     use super::*;
 
+    /// Parses the case into [`SIF`] maps, which drop its `OBJSENSE` and
+    /// `RANGES` sections. Programs are converted with [`read_case`], which
+    /// honors both.
     pub fn get_case(case_name: &str) -> Result<SIF, Problem> {
-        let file_path = format!(
-            "{}/netlib/{}.SIF",
-            get_cache_dir(),
-            case_name.to_uppercase()
-        );
-        if !Path::new(&file_path).exists() {
-            download_netlib_lp()?;
-        }
-        if !Path::new(&file_path).exists() {
-            return Err(format!(
-                "SIF file for case '{}' not found at '{}'",
-                case_name, file_path
-            )
-            .gloss());
-        }
-        let sif_data = std::fs::read_to_string(&file_path)
+        let file_path = case_path("netlib", case_name, download_netlib_lp)?;
+        let mut sif_data = String::new();
+        open_model_file(&file_path)?
+            .read_to_string(&mut sif_data)
             .map_err(|e| format!("Failed to read SIF file '{}': {e}", file_path).gloss())?;
         sif_rs::parse_sif(&sif_data).map_err(|_| "Unable to parse SIF file".gloss())
     }
+
+    /// Converts the case while streaming it from disk, without building the [`SIF`] maps.
+    /// Conversions are kept in the [`ModelCache`].
+    pub fn read_case<P: CacheableProgram>(case_name: &str) -> Result<P, Problem> {
+        let file_path = case_path("netlib", case_name, download_netlib_lp)?;
+        ModelCache::default()
+            .read(&file_path)
+            .map_err(|e| format!("Failed to read SIF file '{}': {e}", file_path).gloss())
+    }
+
+    /// Converts the case while streaming it from disk and returns the record of
+    /// the conversion. Conversions are kept in the [`ModelCache`].
+    pub fn read_case_with_transformation<P: CacheableProgram>(
+        case_name: &str,
+    ) -> Result<(P, SifTransformation), Problem> {
+        let file_path = case_path("netlib", case_name, download_netlib_lp)?;
+        ModelCache::default()
+            .read_with_transformation(&file_path)
+            .map_err(|e| format!("Failed to read SIF file '{}': {e}", file_path).gloss())
+    }
 }
 
 pub mod maros_mezaros {
     use super::*;
 
+    /// Parses the case into [`SIF`] maps, which drop its `OBJSENSE` and
+    /// `RANGES` sections. Programs are converted with [`read_case`], which
+    /// honors both.
     pub fn get_case(case_name: &str) -> Result<SIF, Problem> {
-        let file_path = format!(
-            "{}/maros_mezaros/{}.SIF",
-            get_cache_dir(),
-            case_name.to_uppercase()
-        );
-        if !Path::new(&file_path).exists() {
-            download_maros_mezaros_qp()?;
-        }
-        if !Path::new(&file_path).exists() {
-            return Err(format!(
-                "SIF file for case '{}' not found at '{}'",
-                case_name, file_path
-            )
-            .gloss());
-        }
-        let sif_data = std::fs::read_to_string(&file_path)
+        let file_path = case_path("maros_mezaros", case_name, download_maros_mezaros_qp)?;
+        let mut sif_data = String::new();
+        open_model_file(&file_path)?
+            .read_to_string(&mut sif_data)
             .map_err(|e| format!("Failed to read SIF file '{}': {e}", file_path).gloss())?;
         sif_rs::parse_sif(&sif_data).map_err(|_| "Unable to parse SIF file".gloss())
     }
+
+    /// Converts the case while streaming it from disk, without building the [`SIF`] maps.
+    /// Conversions are kept in the [`ModelCache`].
+    pub fn read_case<P: CacheableProgram>(case_name: &str) -> Result<P, Problem> {
+        let file_path = case_path("maros_mezaros", case_name, download_maros_mezaros_qp)?;
+        ModelCache::default()
+            .read(&file_path)
+            .map_err(|e| format!("Failed to read SIF file '{}': {e}", file_path).gloss())
+    }
+
+    /// Converts the case while streaming it from disk and returns the record of
+    /// the conversion. Conversions are kept in the [`ModelCache`].
+    pub fn read_case_with_transformation<P: CacheableProgram>(
+        case_name: &str,
+    ) -> Result<(P, SifTransformation), Problem> {
+        let file_path = case_path("maros_mezaros", case_name, download_maros_mezaros_qp)?;
+        ModelCache::default()
+            .read_with_transformation(&file_path)
+            .map_err(|e| format!("Failed to read SIF file '{}': {e}", file_path).gloss())
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,286 @@
+//! Trajectories of the iterates of a solve, e.g. to plot their convergence.
+//!
+//! A [`HistoryRecorder`] added to the hooks of a solve with
+//! [`SolverHooks::with_callback`](crate::SolverHooks::with_callback) stores an
+//! [`Iterate`] per recorded iteration: the primal variables, the objective,
+//! the largest residuals and the barrier parameter. The iterates kept depend on
+//! the `history_mode` option:
+//!
+//! - `Full`: every iterate.
+//! - `Subsampled`: every `history_stride`-th iterate, and the last one.
+//! - `RingBuffer`: the last `history_capacity` iterates, which bounds the
+//!   memory of long solves.
+//!
+//! [`HistoryRecorder::get_history`] returns the trajectory once the solve
+//! returns. Histories are serializable to hand them to plotting tools.
+
+use std::{
+    collections::VecDeque,
+    fmt::Debug,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use faer::ColRef;
+use macros::{explicit_options, use_option};
+use serde::{Deserialize, Serialize};
+
+use crate::{E, OptionTrait, SolveStats, SolverOptions, StateView, callback::Callback};
+
+/// Iterates kept by a [`HistoryRecorder`]. See the
+/// [module documentation](self).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum HistoryMode {
+    #[default]
+    Full,
+    Subsampled,
+    RingBuffer,
+}
+
+impl OptionTrait for HistoryMode {}
+
+impl FromStr for HistoryMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "full" => Ok(HistoryMode::Full),
+            "subsampled" => Ok(HistoryMode::Subsampled),
+            "ring_buffer" | "ring" => Ok(HistoryMode::RingBuffer),
+            _ => Err(format!("Invalid history mode: {}", s)),
+        }
+    }
+}
+
+/// One recorded iterate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Iterate {
+    nit: usize,
+    x: Vec<E>,
+    objective: Option<E>,
+    primal_residual: E,
+    dual_residual: E,
+    mu: Option<E>,
+}
+
+impl Iterate {
+    pub fn new<S: StateView + ?Sized>(state: &S) -> Self {
+        Self {
+            nit: state.get_nit(),
+            x: state.get_primal().iter().copied().collect(),
+            objective: state.get_objective(),
+            primal_residual: state.get_primal_feasibility().norm_max(),
+            dual_residual: state.get_dual_feasibility().norm_max(),
+            mu: state.get_mu(),
+        }
+    }
+
+    pub fn get_nit(&self) -> usize {
+        self.nit
+    }
+
+    pub fn get_primal(&self) -> ColRef<'_, E> {
+        ColRef::from_slice(&self.x)
+    }
+
+    pub fn get_objective(&self) -> Option<E> {
+        self.objective
+    }
+
+    pub fn get_primal_residual(&self) -> E {
+        self.primal_residual
+    }
+
+    pub fn get_dual_residual(&self) -> E {
+        self.dual_residual
+    }
+
+    pub fn get_mu(&self) -> Option<E> {
+        self.mu
+    }
+}
+
+/// Recorded iterates of one solve, in the order of the iterations.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct History {
+    iterates: Vec<Iterate>,
+}
+
+impl History {
+    pub fn get_iterates(&self) -> &[Iterate] {
+        &self.iterates
+    }
+
+    pub fn len(&self) -> usize {
+        self.iterates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.iterates.is_empty()
+    }
+
+    /// Objective values of the recorded iterates.
+    pub fn get_objectives(&self) -> Vec<Option<E>> {
+        self.iterates.iter().map(Iterate::get_objective).collect()
+    }
+}
+
+/// Records the trajectory of a solve. See the [module documentation](self).
+///
+/// Clones share the trajectory, so that the recorder kept by the caller sees
+/// the iterations of the copy moved into the hooks. Every solve restarts the
+/// trajectory.
+#[explicit_options(name = SolverOptions)]
+#[use_option(name = "history_mode", type_ = crate::history::HistoryMode, default = "full", description = "Iterates kept by a history recorder (full, subsampled or ring_buffer).")]
+#[use_option(name = "history_stride", type_ = usize, default = "10", description = "Number of iterations between the iterates kept by a subsampled history.")]
+#[use_option(name = "history_capacity", type_ = usize, default = "1000", description = "Number of most recent iterates kept by a ring-buffer history.")]
+#[derive(Clone)]
+pub struct HistoryRecorder {
+    iterates: Arc<Mutex<VecDeque<Iterate>>>,
+}
+
+impl HistoryRecorder {
+    pub fn new(options: &SolverOptions) -> Self {
+        Self {
+            iterates: Arc::default(),
+            options: options.into(),
+        }
+    }
+
+    /// Iterates recorded since the start of the last solve.
+    pub fn get_history(&self) -> History {
+        History {
+            iterates: self.iterates.lock().unwrap().iter().cloned().collect(),
+        }
+    }
+
+    fn record<S: StateView + ?Sized>(&self, state: &S) {
+        let mut iterates = self.iterates.lock().unwrap();
+        if self.options.history_mode == HistoryMode::RingBuffer {
+            if self.options.history_capacity == 0 {
+                return;
+            }
+            while iterates.len() >= self.options.history_capacity {
+                iterates.pop_front();
+            }
+        }
+        iterates.push_back(Iterate::new(state));
+    }
+}
+
+impl Debug for HistoryRecorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HistoryRecorder")
+            .field("mode", &self.options.history_mode)
+            .field("n_iterates", &self.iterates.lock().unwrap().len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S: StateView + ?Sized> Callback<S> for HistoryRecorder {
+    fn init(&mut self, _state: &S) {
+        self.iterates.lock().unwrap().clear();
+    }
+
+    fn call(&mut self, state: &S) {
+        let stride = self.options.history_stride.max(1);
+        if self.options.history_mode != HistoryMode::Subsampled
+            || state.get_nit().is_multiple_of(stride)
+        {
+            self.record(state);
+        }
+    }
+
+    fn finish(&mut self, state: &S, _stats: &SolveStats) {
+        let last = self.iterates.lock().unwrap().back().map(Iterate::get_nit);
+        if self.options.history_mode == HistoryMode::Subsampled && last != Some(state.get_nit()) {
+            self.record(state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::{
+        col,
+        sparse::{SparseColMat, Triplet},
+    };
+    use rstest::rstest;
+
+    use super::*;
+    use crate::{
+        SolverHooks, SolverState, Status, callback::NoOpCallback, lp::LinearProgram,
+        terminators::ComplementarityTerminator,
+    };
+
+    /// Records the solve of `min x_0 + 2 x_1` subject to `x_0 + x_1 = 2`,
+    /// `x_0 - x_1 = 0` and `0 <= x <= 10` with the history `options`, and
+    /// returns the history and the number of iterations.
+    fn record(options: SolverOptions) -> (History, usize) {
+        let triplets = [
+            Triplet::new(0, 0, 1.),
+            Triplet::new(0, 1, 1.),
+            Triplet::new(1, 0, 1.),
+            Triplet::new(1, 1, -1.),
+        ];
+        let lp = LinearProgram::new(
+            col![1., 2.],
+            SparseColMat::try_new_from_triplets(2, 2, &triplets).unwrap(),
+            col![2., 0.],
+            col![0., 0.],
+            col![10., 10.],
+        );
+
+        let recorder = HistoryRecorder::new(&options);
+        let mut hooks = SolverHooks::new(
+            Box::new(NoOpCallback::new()),
+            Box::new(ComplementarityTerminator::new(&options)),
+        )
+        .with_callback(Box::new(recorder.clone()));
+        let mut state = SolverState::new_interior(lp.get_lower_bounds(), lp.get_upper_bounds(), 2);
+        let status = lp
+            .solver_builder()
+            .with_options(options)
+            .build()
+            .unwrap()
+            .solve(&mut state, &mut hooks)
+            .unwrap();
+        assert_eq!(status, Status::Optimal);
+        (recorder.get_history(), state.get_nit())
+    }
+
+    #[rstest]
+    #[case("full", 1, 1000)]
+    #[case("subsampled", 4, 1000)]
+    #[case("ring_buffer", 1, 4)]
+    fn test_history(#[case] mode: &str, #[case] stride: usize, #[case] capacity: usize) {
+        let mut options = SolverOptions::new();
+        options
+            .set_option("history_mode", HistoryMode::from_str(mode).unwrap())
+            .unwrap();
+        options.set_option("history_stride", stride).unwrap();
+        options.set_option("history_capacity", capacity).unwrap();
+        let (history, nit) = record(options);
+
+        let nits: Vec<_> = history
+            .get_iterates()
+            .iter()
+            .map(Iterate::get_nit)
+            .collect();
+        let expected: Vec<_> = match mode {
+            "full" => (0..=nit).collect(),
+            "subsampled" => (0..=nit).filter(|k| k % 4 == 0 || *k == nit).collect(),
+            _ => (nit.saturating_sub(3)..=nit).collect(),
+        };
+        assert_eq!(nits, expected);
+
+        let last = history.get_iterates().last().unwrap();
+        assert!((last.get_primal()[0] - 1.).abs() < 1e-6);
+        assert!((last.get_objective().unwrap() - 3.).abs() < 1e-6);
+        assert!(last.get_primal_residual() < 1e-6);
+
+        let json = serde_json::to_string(&history).unwrap();
+        let restored: History = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.len(), history.len());
+    }
+}
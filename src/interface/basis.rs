@@ -0,0 +1,269 @@
+//! Reading and writing of MPS basis (`.bas`) files.
+//!
+//! The format follows the convention used by CPLEX, HiGHS, and most other
+//! simplex codes. Each record names a column and optionally a row:
+//!
+//! ```text
+//! NAME          example
+//!  XU x1        r1
+//!  XL x2        r2
+//!  UL x3
+//!  LL x4
+//! ENDATA
+//! ```
+//!
+//! `XU`/`XL` make the column basic and the row nonbasic at its upper/lower
+//! bound, `UL`/`LL` place a nonbasic column at its upper/lower bound. Columns
+//! not mentioned are nonbasic at their lower bound and rows not mentioned are
+//! basic.
+
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Write},
+    path::Path,
+};
+
+use problemo::{Problem, common::IntoCommonProblem};
+
+/// Status of a single column or row within a simplex basis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BasisStatus {
+    /// The variable is basic.
+    Basic,
+    /// The variable is nonbasic at its lower bound.
+    #[default]
+    AtLower,
+    /// The variable is nonbasic at its upper bound.
+    AtUpper,
+}
+
+/// A simplex basis given by the status of every column and row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Basis {
+    col_status: Vec<BasisStatus>,
+    row_status: Vec<BasisStatus>,
+}
+
+impl Basis {
+    /// Creates the slack basis: all columns at their lower bound and all rows basic.
+    pub fn new(ncols: usize, nrows: usize) -> Self {
+        Self {
+            col_status: vec![BasisStatus::AtLower; ncols],
+            row_status: vec![BasisStatus::Basic; nrows],
+        }
+    }
+
+    pub fn from_status(col_status: Vec<BasisStatus>, row_status: Vec<BasisStatus>) -> Self {
+        Self {
+            col_status,
+            row_status,
+        }
+    }
+
+    pub fn get_col_status(&self) -> &[BasisStatus] {
+        &self.col_status
+    }
+
+    pub fn get_row_status(&self) -> &[BasisStatus] {
+        &self.row_status
+    }
+
+    pub fn set_col_status(&mut self, j: usize, status: BasisStatus) {
+        self.col_status[j] = status;
+    }
+
+    pub fn set_row_status(&mut self, i: usize, status: BasisStatus) {
+        self.row_status[i] = status;
+    }
+
+    /// Number of basic columns and rows. A valid basis has exactly `nrows` of them.
+    pub fn num_basic(&self) -> usize {
+        self.col_status
+            .iter()
+            .chain(self.row_status.iter())
+            .filter(|s| **s == BasisStatus::Basic)
+            .count()
+    }
+
+    /// Reads a basis in MPS `.bas` format.
+    ///
+    /// `col_names` and `row_names` give the names of the columns and rows in
+    /// index order; every name in the file must appear in the corresponding list.
+    pub fn read_bas<R: std::io::Read>(
+        reader: R,
+        col_names: &[String],
+        row_names: &[String],
+    ) -> Result<Self, Problem> {
+        let col_idx: HashMap<&str, usize> = col_names
+            .iter()
+            .enumerate()
+            .map(|(j, name)| (name.as_str(), j))
+            .collect();
+        let row_idx: HashMap<&str, usize> = row_names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.as_str(), i))
+            .collect();
+
+        let lookup = |map: &HashMap<&str, usize>, name: &str, kind: &str| {
+            map.get(name)
+                .copied()
+                .ok_or_else(|| format!("Basis file references unknown {kind}: {name}").gloss())
+        };
+
+        let mut basis = Self::new(col_names.len(), row_names.len());
+
+        for line in BufReader::new(reader).lines() {
+            let line = line?;
+
+            // Section headers start in the first column, records are indented
+            if line.starts_with("NAME") || line.starts_with('*') || line.trim().is_empty() {
+                continue;
+            }
+            if line.starts_with("ENDATA") {
+                break;
+            }
+
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            match fields.as_slice() {
+                ["XU", col, row] | ["XL", col, row] => {
+                    let j = lookup(&col_idx, col, "column")?;
+                    let i = lookup(&row_idx, row, "row")?;
+                    basis.col_status[j] = BasisStatus::Basic;
+                    basis.row_status[i] = if fields[0] == "XU" {
+                        BasisStatus::AtUpper
+                    } else {
+                        BasisStatus::AtLower
+                    };
+                }
+                ["UL", col, ..] => {
+                    let j = lookup(&col_idx, col, "column")?;
+                    basis.col_status[j] = BasisStatus::AtUpper;
+                }
+                ["LL", col, ..] => {
+                    let j = lookup(&col_idx, col, "column")?;
+                    basis.col_status[j] = BasisStatus::AtLower;
+                }
+                _ => return Err(format!("Invalid basis record: {}", line.trim()).gloss()),
+            }
+        }
+
+        Ok(basis)
+    }
+
+    /// Writes the basis in MPS `.bas` format.
+    ///
+    /// Basic columns are paired with nonbasic rows, which requires the two
+    /// counts to be equal; this holds for any basis with `nrows` basic entries.
+    pub fn write_bas<W: Write>(
+        &self,
+        mut writer: W,
+        name: &str,
+        col_names: &[String],
+        row_names: &[String],
+    ) -> Result<(), Problem> {
+        if col_names.len() != self.col_status.len() || row_names.len() != self.row_status.len() {
+            return Err("Basis dimensions do not match the provided names".gloss());
+        }
+
+        let basic_cols = (0..self.col_status.len())
+            .filter(|&j| self.col_status[j] == BasisStatus::Basic)
+            .collect::<Vec<_>>();
+        let nonbasic_rows = (0..self.row_status.len())
+            .filter(|&i| self.row_status[i] != BasisStatus::Basic)
+            .collect::<Vec<_>>();
+
+        if basic_cols.len() != nonbasic_rows.len() {
+            return Err(format!(
+                "Cannot write basis with {} basic columns and {} nonbasic rows",
+                basic_cols.len(),
+                nonbasic_rows.len()
+            )
+            .gloss());
+        }
+
+        writeln!(writer, "NAME          {name}")?;
+        for (&j, &i) in basic_cols.iter().zip(nonbasic_rows.iter()) {
+            let indicator = match self.row_status[i] {
+                BasisStatus::AtUpper => "XU",
+                _ => "XL",
+            };
+            writeln!(writer, " {indicator} {:<8} {}", col_names[j], row_names[i])?;
+        }
+        // Columns at their lower bound are the default and need not be listed
+        for (j, status) in self.col_status.iter().enumerate() {
+            if *status == BasisStatus::AtUpper {
+                writeln!(writer, " UL {}", col_names[j])?;
+            }
+        }
+        writeln!(writer, "ENDATA")?;
+
+        Ok(())
+    }
+
+    pub fn read_bas_file<P: AsRef<Path>>(
+        path: P,
+        col_names: &[String],
+        row_names: &[String],
+    ) -> Result<Self, Problem> {
+        let file = std::fs::File::open(path)?;
+        Self::read_bas(file, col_names, row_names)
+    }
+
+    pub fn write_bas_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        name: &str,
+        col_names: &[String],
+        row_names: &[String],
+    ) -> Result<(), Problem> {
+        let file = std::fs::File::create(path)?;
+        self.write_bas(std::io::BufWriter::new(file), name, col_names, row_names)
+    }
+}
+
+/// Generates the names `{prefix}0, {prefix}1, ...` for models without named columns or rows.
+pub fn default_names(prefix: &str, n: usize) -> Vec<String> {
+    (0..n).map(|i| format!("{prefix}{i}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_basis_roundtrip() {
+        let col_names = default_names("C", 4);
+        let row_names = default_names("R", 2);
+
+        let basis = Basis::from_status(
+            vec![
+                BasisStatus::Basic,
+                BasisStatus::AtUpper,
+                BasisStatus::AtLower,
+                BasisStatus::Basic,
+            ],
+            vec![BasisStatus::AtUpper, BasisStatus::AtLower],
+        );
+
+        let mut buf = Vec::new();
+        basis
+            .write_bas(&mut buf, "TEST", &col_names, &row_names)
+            .unwrap();
+        let read = Basis::read_bas(buf.as_slice(), &col_names, &row_names).unwrap();
+
+        assert_eq!(basis, read);
+        assert_eq!(read.num_basic(), row_names.len());
+    }
+
+    #[test]
+    fn test_basis_unknown_name() {
+        let input = "NAME          TEST\n XU C0       R9\nENDATA\n";
+        let result = Basis::read_bas(
+            input.as_bytes(),
+            &default_names("C", 2),
+            &default_names("R", 2),
+        );
+        assert!(result.is_err());
+    }
+}
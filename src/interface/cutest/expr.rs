@@ -0,0 +1,366 @@
+//! Fortran arithmetic expressions appearing in SIF element and group functions.
+//!
+//! Expressions are compiled once against a symbol table, so that evaluation only
+//! reads values from a slice of slots.
+
+use problemo::{Problem, common::IntoCommonProblem};
+
+use crate::E;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Intrinsic {
+    Sin,
+    Cos,
+    Tan,
+    Asin,
+    Acos,
+    Atan,
+    Sinh,
+    Cosh,
+    Tanh,
+    Exp,
+    Log,
+    Log10,
+    Sqrt,
+    Abs,
+    Identity,
+    Min,
+    Max,
+}
+
+impl Intrinsic {
+    fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_uppercase();
+        // Double precision variants carry a `D` prefix, e.g. `DSIN`
+        let base = match name.as_str() {
+            "DBLE" | "FLOAT" | "REAL" => return Some(Self::Identity),
+            "DMIN1" | "AMIN1" => return Some(Self::Min),
+            "DMAX1" | "AMAX1" => return Some(Self::Max),
+            _ => name.strip_prefix('D').unwrap_or(&name),
+        };
+        match base {
+            "SIN" => Some(Self::Sin),
+            "COS" => Some(Self::Cos),
+            "TAN" => Some(Self::Tan),
+            "ASIN" => Some(Self::Asin),
+            "ACOS" => Some(Self::Acos),
+            "ATAN" => Some(Self::Atan),
+            "SINH" => Some(Self::Sinh),
+            "COSH" => Some(Self::Cosh),
+            "TANH" => Some(Self::Tanh),
+            "EXP" => Some(Self::Exp),
+            "LOG" => Some(Self::Log),
+            "LOG10" => Some(Self::Log10),
+            "SQRT" => Some(Self::Sqrt),
+            "ABS" => Some(Self::Abs),
+            "MIN" => Some(Self::Min),
+            "MAX" => Some(Self::Max),
+            _ => None,
+        }
+    }
+
+    fn apply(self, args: &[E]) -> E {
+        let x = args[0];
+        match self {
+            Self::Sin => x.sin(),
+            Self::Cos => x.cos(),
+            Self::Tan => x.tan(),
+            Self::Asin => x.asin(),
+            Self::Acos => x.acos(),
+            Self::Atan => x.atan(),
+            Self::Sinh => x.sinh(),
+            Self::Cosh => x.cosh(),
+            Self::Tanh => x.tanh(),
+            Self::Exp => x.exp(),
+            Self::Log => x.ln(),
+            Self::Log10 => x.log10(),
+            Self::Sqrt => x.sqrt(),
+            Self::Abs => x.abs(),
+            Self::Identity => x,
+            Self::Min => args[1..].iter().fold(x, |acc, &a| acc.min(a)),
+            Self::Max => args[1..].iter().fold(x, |acc, &a| acc.max(a)),
+        }
+    }
+}
+
+/// A compiled expression whose variables refer to slots.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Expr {
+    Const(E),
+    Slot(usize),
+    Neg(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(Intrinsic, Vec<Expr>),
+}
+
+impl Expr {
+    /// Compiles `text`, resolving names with `lookup`.
+    pub(crate) fn parse(
+        text: &str,
+        lookup: &dyn Fn(&str) -> Option<Expr>,
+    ) -> Result<Self, Problem> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            pos: 0,
+            lookup,
+        };
+        let expr = parser.expr()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(token) => Err(format!("Unexpected '{token:?}' in expression '{text}'").gloss()),
+        }
+    }
+
+    pub(crate) fn eval(&self, slots: &[E]) -> E {
+        match self {
+            Expr::Const(value) => *value,
+            Expr::Slot(k) => slots[*k],
+            Expr::Neg(a) => -a.eval(slots),
+            Expr::Binary(op, a, b) => {
+                let (a, b) = (a.eval(slots), b.eval(slots));
+                match op {
+                    BinaryOp::Add => a + b,
+                    BinaryOp::Sub => a - b,
+                    BinaryOp::Mul => a * b,
+                    BinaryOp::Div => a / b,
+                    BinaryOp::Pow if b.fract() == 0. && b.abs() <= i32::MAX as E => {
+                        a.powi(b as i32)
+                    }
+                    BinaryOp::Pow => a.powf(b),
+                }
+            }
+            Expr::Call(f, args) => {
+                let args = args.iter().map(|a| a.eval(slots)).collect::<Vec<_>>();
+                f.apply(&args)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(E),
+    Name(String),
+    Op(BinaryOp),
+    Open,
+    Close,
+    Comma,
+}
+
+/// Parses a Fortran real literal, which may use `D` as the exponent marker.
+pub(crate) fn parse_number(text: &str) -> Option<E> {
+    text.replace(['D', 'd'], "E").parse::<E>().ok()
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, Problem> {
+    let chars = text.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let ch = chars[i];
+        match ch {
+            ' ' | '\t' => i += 1,
+            '(' | ')' | ',' | '+' | '-' | '/' => {
+                tokens.push(match ch {
+                    '(' => Token::Open,
+                    ')' => Token::Close,
+                    ',' => Token::Comma,
+                    '+' => Token::Op(BinaryOp::Add),
+                    '-' => Token::Op(BinaryOp::Sub),
+                    _ => Token::Op(BinaryOp::Div),
+                });
+                i += 1;
+            }
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                tokens.push(Token::Op(BinaryOp::Pow));
+                i += 2;
+            }
+            '*' => {
+                tokens.push(Token::Op(BinaryOp::Mul));
+                i += 1;
+            }
+            _ if ch.is_ascii_digit() || ch == '.' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                // Exponent, e.g. `1.0D-3`
+                if i < chars.len() && matches!(chars[i], 'E' | 'e' | 'D' | 'd') {
+                    let mut j = i + 1;
+                    if j < chars.len() && matches!(chars[j], '+' | '-') {
+                        j += 1;
+                    }
+                    if j < chars.len() && chars[j].is_ascii_digit() {
+                        i = j;
+                        while i < chars.len() && chars[i].is_ascii_digit() {
+                            i += 1;
+                        }
+                    }
+                }
+                let literal = chars[start..i].iter().collect::<String>();
+                let value = parse_number(&literal)
+                    .ok_or_else(|| format!("Invalid number '{literal}' in '{text}'").gloss())?;
+                tokens.push(Token::Number(value));
+            }
+            _ if ch.is_alphanumeric() || ch == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                tokens.push(Token::Name(chars[start..i].iter().collect()));
+            }
+            _ => return Err(format!("Unexpected character '{ch}' in '{text}'").gloss()),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive-descent parser following Fortran precedence: `**` binds tighter
+/// than unary minus and is right-associative.
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    lookup: &'a dyn Fn(&str) -> Option<Expr>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), Problem> {
+        match self.next() {
+            Some(t) if t == token => Ok(()),
+            other => Err(format!("Expected '{token:?}', found '{other:?}'").gloss()),
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, Problem> {
+        let mut lhs = self.unary()?;
+        while let Some(Token::Op(op @ (BinaryOp::Add | BinaryOp::Sub))) = self.peek().cloned() {
+            self.pos += 1;
+            let rhs = self.term()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    /// A leading sign applies to the first term only, e.g. `-A*B + C`.
+    fn unary(&mut self) -> Result<Expr, Problem> {
+        match self.peek() {
+            Some(Token::Op(BinaryOp::Sub)) => {
+                self.pos += 1;
+                Ok(Expr::Neg(Box::new(self.term()?)))
+            }
+            Some(Token::Op(BinaryOp::Add)) => {
+                self.pos += 1;
+                self.term()
+            }
+            _ => self.term(),
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, Problem> {
+        let mut lhs = self.power()?;
+        while let Some(Token::Op(op @ (BinaryOp::Mul | BinaryOp::Div))) = self.peek().cloned() {
+            self.pos += 1;
+            let rhs = self.power()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn power(&mut self) -> Result<Expr, Problem> {
+        let base = self.atom()?;
+        if let Some(Token::Op(BinaryOp::Pow)) = self.peek() {
+            self.pos += 1;
+            // The exponent may carry its own sign, e.g. `X**-2`
+            let exponent = match self.peek() {
+                Some(Token::Op(BinaryOp::Sub)) => {
+                    self.pos += 1;
+                    Expr::Neg(Box::new(self.power()?))
+                }
+                _ => self.power()?,
+            };
+            return Ok(Expr::Binary(
+                BinaryOp::Pow,
+                Box::new(base),
+                Box::new(exponent),
+            ));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Expr, Problem> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(Expr::Const(value)),
+            Some(Token::Open) => {
+                let expr = self.expr()?;
+                self.expect(Token::Close)?;
+                Ok(expr)
+            }
+            Some(Token::Name(name)) if self.peek() == Some(&Token::Open) => {
+                let f = Intrinsic::from_name(&name)
+                    .ok_or_else(|| format!("Unsupported function '{name}'").gloss())?;
+                self.pos += 1;
+                let mut args = vec![self.expr()?];
+                while self.peek() == Some(&Token::Comma) {
+                    self.pos += 1;
+                    args.push(self.expr()?);
+                }
+                self.expect(Token::Close)?;
+                Ok(Expr::Call(f, args))
+            }
+            Some(Token::Name(name)) => {
+                (self.lookup)(&name).ok_or_else(|| format!("Unknown name '{name}'").gloss())
+            }
+            other => Err(format!("Unexpected '{other:?}' in expression").gloss()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("X * X", 9.)]
+    #[case("-X**2 + 1.0D0", -8.)]
+    #[case("2.0 * (X - Y) / 4", 1.)]
+    #[case("X ** -1", 1. / 3.)]
+    #[case("DSQRT(X + 1.0) * LOG(EXP(Y))", 2.)]
+    #[case("MAX(X, Y, 5.0E0)", 5.)]
+    #[case("1.5d+1 - 2**3**0", 13.)]
+    fn test_eval(#[case] text: &str, #[case] expected: E) {
+        let lookup = |name: &str| match name {
+            "X" => Some(Expr::Slot(0)),
+            "Y" => Some(Expr::Slot(1)),
+            _ => None,
+        };
+        let expr = Expr::parse(text, &lookup).unwrap();
+        assert!((expr.eval(&[3., 1.]) - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_unknown_name() {
+        assert!(Expr::parse("X + Z", &|_| None).is_err());
+    }
+}
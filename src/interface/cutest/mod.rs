@@ -0,0 +1,1828 @@
+//! Conversion of nonlinear SIF models, as used by CUTEst, into [`NonlinearProgram`]s.
+//!
+//! A SIF model is a sum of *groups*. Each group applies a univariate group
+//! function to a linear combination of the variables plus a weighted sum of
+//! nonlinear *elements*:
+//!
+//! ```text
+//!   g_i( a_i^T x + sum_e w_e f_e(x) - b_i ) / s_i
+//! ```
+//!
+//! `N` groups add up to the objective, while `E`, `L` and `G` groups are
+//! constraints `= 0`, `<= 0` and `>= 0`. Inequality groups receive a
+//! nonnegative slack, as in [`crate::interface::sif`], so every constraint of
+//! the resulting program is an equality.
+//!
+//! The element and group functions, together with their first and second
+//! derivatives, are Fortran expressions given in the `ELEMENTS` and `GROUPS`
+//! parts of the file. They are compiled once and evaluated by the closures of
+//! the program.
+//!
+//! Integer and real parameters and `DO` loops of the data part are expanded
+//! while the file is read: the records of a loop are replayed for every value
+//! of its index, and indexed names such as `X(I,J)` become `X1,2`. Kinds
+//! starting with `Z` take their value from a real parameter.
+//!
+//! Only a subset of SIF is supported: internal element variables, ranges and
+//! conditional expressions are rejected with an error. Names are read as
+//! whitespace-separated tokens, so they must not contain spaces.
+
+mod expr;
+
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read},
+    path::Path,
+    sync::Arc,
+};
+
+use faer::{
+    Col,
+    sparse::{SparseColMat, Triplet},
+};
+use problemo::{Problem, common::IntoCommonProblem};
+
+use crate::{E, I, nlp::NonlinearProgram, to_index};
+use expr::{Expr, parse_number};
+
+/// A nonlinear SIF model together with its starting point.
+pub struct CUTEstProblem {
+    name: String,
+    var_names: Vec<String>,
+    con_names: Vec<String>,
+    x0: Col<E>,
+    l: Col<E>,
+    u: Col<E>,
+    model: Arc<Model>,
+}
+
+impl CUTEstProblem {
+    /// Reads a SIF model, including its element and group functions.
+    pub fn read<R: Read>(reader: R) -> Result<Self, Problem> {
+        SifReader::default().read(reader)
+    }
+
+    pub fn read_file<P: AsRef<Path>>(path: P) -> Result<Self, Problem> {
+        let file = std::fs::File::open(path)?;
+        Self::read(file)
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Number of variables of the program, including the slacks of inequality groups.
+    pub fn get_n_vars(&self) -> usize {
+        self.var_names.len() + self.model.n_slack
+    }
+
+    /// Number of constraint groups.
+    pub fn get_n_cons(&self) -> usize {
+        self.con_names.len()
+    }
+
+    /// Names of the structural variables; the slacks follow them in the program.
+    pub fn get_var_names(&self) -> &[String] {
+        &self.var_names
+    }
+
+    pub fn get_con_names(&self) -> &[String] {
+        &self.con_names
+    }
+
+    /// The starting point of the model, with each slack set to the violation it absorbs.
+    pub fn get_start_point(&self) -> Col<E> {
+        let n_x = self.var_names.len();
+        let mut x = Col::zeros(self.get_n_vars());
+        x.subrows_mut(0, n_x).copy_from(&self.x0);
+
+        let values = self.model.constraint_values(&x);
+        for (i, &(_, slack)) in self.model.constraints.iter().enumerate() {
+            if let Some((k, sign)) = slack {
+                x[n_x + k] = (-sign * values[i]).max(E::from(0.));
+            }
+        }
+        x
+    }
+
+    /// Lower bounds of the program, with slacks bounded below by zero.
+    pub fn get_lower_bounds(&self) -> Col<E> {
+        let mut l = Col::zeros(self.get_n_vars());
+        l.subrows_mut(0, self.var_names.len()).copy_from(&self.l);
+        l
+    }
+
+    /// Upper bounds of the program, with slacks unbounded above.
+    pub fn get_upper_bounds(&self) -> Col<E> {
+        let mut u = E::INFINITY * Col::<E>::ones(self.get_n_vars());
+        u.subrows_mut(0, self.var_names.len()).copy_from(&self.u);
+        u
+    }
+}
+
+impl From<&CUTEstProblem> for NonlinearProgram {
+    /// Builds the program from closures sharing the compiled model.
+    ///
+    /// The Hessian is the full symmetric matrix `∇²f(x) + sum_i y_i ∇²g_i(x)`.
+    fn from(problem: &CUTEstProblem) -> Self {
+        let (n, m) = (problem.get_n_vars(), problem.get_n_cons());
+        let (m_f, m_g, m_df) = (
+            problem.model.clone(),
+            problem.model.clone(),
+            problem.model.clone(),
+        );
+        let (m_dg, m_h) = (problem.model.clone(), problem.model.clone());
+
+        let f = Box::new(move |x: &Col<E>| m_f.objective(x));
+        let g = Box::new(move |x: &Col<E>| m_g.constraint_values(x));
+        let df = Box::new(move |x: &Col<E>| m_df.objective_gradient(x));
+        let dg = Box::new(move |x: &Col<E>| m_dg.jacobian(x));
+        let h = Box::new(move |x: &Col<E>, y: &Col<E>| m_h.hessian(x, y));
+
+        NonlinearProgram::new_boxed(
+            n,
+            m,
+            f,
+            g,
+            df,
+            dg,
+            Some(h),
+            Some(problem.get_lower_bounds()),
+            Some(problem.get_upper_bounds()),
+        )
+    }
+}
+
+impl From<CUTEstProblem> for NonlinearProgram {
+    fn from(problem: CUTEstProblem) -> Self {
+        Self::from(&problem)
+    }
+}
+
+/// An element or group function with its derivatives.
+struct Function {
+    n_args: usize,
+    n_params: usize,
+    /// Arguments, parameters and temporaries, in slot order.
+    names: Vec<String>,
+    /// Temporaries defined by `A` lines, occupying the slots after the parameters.
+    temps: Vec<Expr>,
+    f: Option<Expr>,
+    g: Vec<Expr>,
+    /// Lower triangle of the Hessian, row by row.
+    h: Vec<Expr>,
+}
+
+/// Value, gradient and lower-triangular Hessian of a [`Function`].
+struct FunctionEval {
+    f: E,
+    g: Vec<E>,
+    h: Vec<E>,
+}
+
+impl Function {
+    fn new(args: Vec<String>, params: Vec<String>) -> Self {
+        let n_args = args.len();
+        Self {
+            n_args,
+            n_params: params.len(),
+            names: args.into_iter().chain(params).collect(),
+            temps: Vec::new(),
+            f: None,
+            g: vec![Expr::Const(E::from(0.)); n_args],
+            h: vec![Expr::Const(E::from(0.)); n_args * (n_args + 1) / 2],
+        }
+    }
+
+    fn slot(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|n| n == name)
+    }
+
+    fn compile(&self, text: &str, globals: &HashMap<String, E>) -> Result<Expr, Problem> {
+        let lookup = |name: &str| {
+            self.slot(name)
+                .map(Expr::Slot)
+                .or_else(|| globals.get(name).map(|&v| Expr::Const(v)))
+        };
+        Expr::parse(text, &lookup)
+    }
+
+    fn hessian_index(&self, i: usize, j: usize) -> usize {
+        let (i, j) = if i >= j { (i, j) } else { (j, i) };
+        i * (i + 1) / 2 + j
+    }
+
+    fn eval(&self, args: &[E], params: &[E], order: usize) -> FunctionEval {
+        let mut slots = Vec::with_capacity(self.names.len());
+        slots.extend_from_slice(args);
+        slots.extend_from_slice(params);
+        for temp in self.temps.iter() {
+            let value = temp.eval(&slots);
+            slots.push(value);
+        }
+
+        FunctionEval {
+            f: self.f.as_ref().map_or(E::from(0.), |f| f.eval(&slots)),
+            g: match order {
+                0 => Vec::new(),
+                _ => self.g.iter().map(|g| g.eval(&slots)).collect(),
+            },
+            h: match order {
+                0 | 1 => Vec::new(),
+                _ => self.h.iter().map(|h| h.eval(&slots)).collect(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum GroupKind {
+    N,
+    E,
+    L,
+    G,
+}
+
+struct ElementUse {
+    etype: Option<usize>,
+    vars: Vec<Option<usize>>,
+    params: Vec<Option<E>>,
+}
+
+/// An element use whose type, variables and parameters are all assigned.
+struct Element {
+    etype: usize,
+    vars: Vec<usize>,
+    params: Vec<E>,
+}
+
+struct Group {
+    kind: GroupKind,
+    linear: Vec<(usize, E)>,
+    constant: E,
+    scale: E,
+    gtype: Option<usize>,
+    params: Vec<Option<E>>,
+    elements: Vec<(usize, E)>,
+}
+
+/// Value, gradient and full Hessian (as triplets) of a group.
+struct GroupEval {
+    value: E,
+    grad: Vec<(usize, E)>,
+    hess: Vec<(usize, usize, E)>,
+}
+
+/// A compiled SIF model over the structural variables and slacks.
+struct Model {
+    n_x: usize,
+    n_slack: usize,
+    element_types: Vec<Function>,
+    group_types: Vec<Function>,
+    elements: Vec<Element>,
+    /// Groups whose parameters are all assigned, see [`SifReader::finish`].
+    groups: Vec<Group>,
+    objective: Vec<usize>,
+    /// Constraint groups with the index and sign of their slack.
+    constraints: Vec<(usize, Option<(usize, E)>)>,
+}
+
+impl Model {
+    fn eval_group(&self, group: &Group, x: &Col<E>, order: usize) -> GroupEval {
+        let mut r = -group.constant;
+        let mut grad_r = Vec::new();
+        let mut hess_r = Vec::new();
+
+        for &(j, a) in group.linear.iter() {
+            r += a * x[j];
+            grad_r.push((j, a));
+        }
+
+        for &(e, w) in group.elements.iter() {
+            let element = &self.elements[e];
+            let function = &self.element_types[element.etype];
+            let vars = &element.vars;
+            let args = vars.iter().map(|&j| x[j]).collect::<Vec<_>>();
+
+            let eval = function.eval(&args, &element.params, order);
+            r += w * eval.f;
+            if order >= 1 {
+                grad_r.extend(vars.iter().zip(eval.g.iter()).map(|(&j, &g)| (j, w * g)));
+            }
+            if order >= 2 {
+                for k in 0..vars.len() {
+                    for l in 0..=k {
+                        let h = w * eval.h[function.hessian_index(k, l)];
+                        hess_r.push((vars[k], vars[l], h));
+                        if k != l {
+                            hess_r.push((vars[l], vars[k], h));
+                        }
+                    }
+                }
+            }
+        }
+
+        let (g0, g1, g2) = match group.gtype {
+            None => (r, E::from(1.), E::from(0.)),
+            Some(t) => {
+                let params = group.params.iter().flatten().copied().collect::<Vec<_>>();
+                let eval = self.group_types[t].eval(&[r], &params, order);
+                let g1 = eval.g.first().copied().unwrap_or(E::from(0.));
+                let g2 = eval.h.first().copied().unwrap_or(E::from(0.));
+                (eval.f, g1, g2)
+            }
+        };
+        let scale = E::from(1.) / group.scale;
+
+        let mut result = GroupEval {
+            value: scale * g0,
+            grad: Vec::new(),
+            hess: Vec::new(),
+        };
+        if order >= 1 {
+            // Merge repeated variables so that the outer product below is exact
+            grad_r.sort_by_key(|&(j, _)| j);
+            grad_r.dedup_by(|next, prev| {
+                let repeated = next.0 == prev.0;
+                if repeated {
+                    prev.1 += next.1;
+                }
+                repeated
+            });
+            result.grad = grad_r.iter().map(|&(j, a)| (j, scale * g1 * a)).collect();
+        }
+        if order >= 2 {
+            result.hess = hess_r
+                .into_iter()
+                .map(|(i, j, h)| (i, j, scale * g1 * h))
+                .collect();
+            if g2 != E::from(0.) {
+                for &(i, a) in grad_r.iter() {
+                    for &(j, b) in grad_r.iter() {
+                        result.hess.push((i, j, scale * g2 * a * b));
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    fn objective(&self, x: &Col<E>) -> E {
+        self.objective
+            .iter()
+            .map(|&i| self.eval_group(&self.groups[i], x, 0).value)
+            .sum()
+    }
+
+    fn objective_gradient(&self, x: &Col<E>) -> Col<E> {
+        let mut df = Col::zeros(x.nrows());
+        for &i in self.objective.iter() {
+            for (j, a) in self.eval_group(&self.groups[i], x, 1).grad {
+                df[j] += a;
+            }
+        }
+        df
+    }
+
+    fn constraint_values(&self, x: &Col<E>) -> Col<E> {
+        Col::from_fn(self.constraints.len(), |i| {
+            let (group, slack) = self.constraints[i];
+            let value = self.eval_group(&self.groups[group], x, 0).value;
+            match slack {
+                Some((k, sign)) => value + sign * x[self.n_x + k],
+                None => value,
+            }
+        })
+    }
+
+    fn jacobian(&self, x: &Col<E>) -> SparseColMat<I, E> {
+        let mut triplets = Vec::new();
+        for (i, &(group, slack)) in self.constraints.iter().enumerate() {
+            let eval = self.eval_group(&self.groups[group], x, 1);
+            triplets.extend(
+                eval.grad
+                    .into_iter()
+                    .map(|(j, a)| Triplet::new(to_index(i), to_index(j), a)),
+            );
+            if let Some((k, sign)) = slack {
+                triplets.push(Triplet::new(to_index(i), to_index(self.n_x + k), sign));
+            }
+        }
+        // The triplets index variables and groups of the model, and duplicates are summed
+        SparseColMat::try_new_from_triplets(self.constraints.len(), x.nrows(), &triplets)
+            .expect("Jacobian entries lie within the dimensions of the program")
+    }
+
+    fn hessian(&self, x: &Col<E>, y: &Col<E>) -> SparseColMat<I, E> {
+        let weighted = self.objective.iter().map(|&i| (i, E::from(1.))).chain(
+            self.constraints
+                .iter()
+                .enumerate()
+                .map(|(k, &(i, _))| (i, y[k])),
+        );
+
+        let mut triplets = Vec::new();
+        for (i, weight) in weighted {
+            if weight == E::from(0.) {
+                continue;
+            }
+            let eval = self.eval_group(&self.groups[i], x, 2);
+            triplets.extend(
+                eval.hess
+                    .into_iter()
+                    .map(|(i, j, h)| Triplet::new(to_index(i), to_index(j), weight * h)),
+            );
+        }
+        SparseColMat::try_new_from_triplets(x.nrows(), x.nrows(), &triplets)
+            .expect("Hessian entries lie within the dimensions of the program")
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Section {
+    Preamble,
+    Groups,
+    Variables,
+    Constants,
+    Bounds,
+    StartPoint,
+    ElementType,
+    ElementUses,
+    GroupType,
+    GroupUses,
+    Ignored,
+    Temporaries,
+    Globals,
+    Individuals,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Part {
+    Data,
+    Elements,
+    GroupFunctions,
+}
+
+/// An `A`, `F`, `G` or `H` line, held until its continuation lines are read.
+struct PendingExpr {
+    kind: char,
+    names: Vec<String>,
+    text: String,
+    /// Whether the expression defines a global rather than part of the current function.
+    global: bool,
+    line_number: usize,
+}
+
+/// A `DO` loop of the data part, recorded until its end and then replayed for
+/// every value of its index.
+struct Loop {
+    index: String,
+    first: i64,
+    last: i64,
+    /// Nesting depth of the records being read, with the loop itself at 1.
+    depth: usize,
+    records: Vec<(usize, Vec<String>)>,
+}
+
+#[derive(Default)]
+struct SifReader {
+    name: String,
+    var_index: HashMap<String, usize>,
+    var_names: Vec<String>,
+    group_index: HashMap<String, usize>,
+    group_names: Vec<String>,
+    groups: Vec<Group>,
+    l: Vec<E>,
+    u: Vec<E>,
+    x0: Vec<E>,
+
+    element_type_index: HashMap<String, usize>,
+    element_types: Vec<Function>,
+    element_index: HashMap<String, usize>,
+    elements: Vec<ElementUse>,
+    default_element_type: Option<usize>,
+
+    group_type_index: HashMap<String, usize>,
+    group_types: Vec<Function>,
+    default_group_type: Option<usize>,
+
+    globals: HashMap<String, E>,
+    current_function: Option<usize>,
+    pending: Option<PendingExpr>,
+
+    /// Integer and real parameters of the data part.
+    params: HashMap<String, E>,
+    loop_record: Option<Loop>,
+}
+
+fn error(line_number: usize, message: impl std::fmt::Display) -> Problem {
+    format!("Line {line_number}: {message}").gloss()
+}
+
+fn parse_value(token: Option<&&str>, line_number: usize) -> Result<E, Problem> {
+    let token = token.ok_or_else(|| error(line_number, "missing value"))?;
+    parse_number(token).ok_or_else(|| error(line_number, format!("invalid value '{token}'")))
+}
+
+/// Strips the `X` prefix that marks a line without loop indices, e.g. `XN` or `XT`.
+fn strip_x(kind: &str) -> &str {
+    match kind.len() {
+        2 => kind.strip_prefix('X').unwrap_or(kind),
+        _ => kind,
+    }
+}
+
+impl SifReader {
+    fn read<R: Read>(mut self, reader: R) -> Result<CUTEstProblem, Problem> {
+        let mut reader = BufReader::new(reader);
+        let mut line = String::new();
+        let mut line_number = 0;
+        let mut part = Part::Data;
+        let mut section = Section::Preamble;
+        // The `GROUPS` header starts the group functions once the data has ended
+        let mut data_done = false;
+
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                break;
+            }
+            line_number += 1;
+
+            if line.trim().is_empty() || line.starts_with('*') {
+                continue;
+            }
+
+            let tokens = line.split_whitespace().collect::<Vec<_>>();
+            if !line.starts_with(char::is_whitespace) {
+                if let Some(record) = &self.loop_record {
+                    return Err(error(
+                        line_number,
+                        format!("loop over '{}' is not closed", record.index),
+                    ));
+                }
+                self.flush_pending(part)?;
+                let header = line.trim_end();
+                section = if header.starts_with("ENDATA") {
+                    data_done = true;
+                    Section::Ignored
+                } else if header.starts_with("ELEMENTS") {
+                    part = Part::Elements;
+                    self.current_function = None;
+                    Section::Individuals
+                } else if data_done && header.starts_with("GROUPS") {
+                    part = Part::GroupFunctions;
+                    self.current_function = None;
+                    Section::Individuals
+                } else if part == Part::Data {
+                    self.data_section(header, line_number)?
+                } else if header.starts_with("TEMPORARIES") {
+                    Section::Temporaries
+                } else if header.starts_with("GLOBALS") {
+                    Section::Globals
+                } else if header.starts_with("INDIVIDUALS") {
+                    Section::Individuals
+                } else {
+                    return Err(error(line_number, format!("unknown section '{header}'")));
+                };
+                continue;
+            }
+
+            match section {
+                Section::Globals | Section::Individuals => {
+                    self.read_function_line(part, section, &tokens, line_number)?
+                }
+                Section::Ignored | Section::Temporaries => {}
+                _ => {
+                    let tokens = tokens.iter().map(|t| t.to_string()).collect();
+                    self.read_data_record(section, tokens, line_number)?
+                }
+            }
+        }
+        self.flush_pending(part)?;
+
+        self.finish()
+    }
+
+    /// Reads a record of the data part, defining parameters, recording loops and
+    /// expanding the other records.
+    fn read_data_record(
+        &mut self,
+        section: Section,
+        tokens: Vec<String>,
+        line_number: usize,
+    ) -> Result<(), Problem> {
+        if let Some(record) = self.loop_record.as_mut() {
+            let nested = record.depth > 1;
+            match tokens[0].as_str() {
+                "DO" => record.depth += 1,
+                "OD" => record.depth -= 1,
+                "ND" => record.depth = 0,
+                _ => {}
+            }
+            // `ND` also ends the loops nested in the recorded one
+            if record.depth > 0 || (nested && tokens[0] == "ND") {
+                record.records.push((line_number, tokens));
+            }
+            if record.depth == 0
+                && let Some(record) = self.loop_record.take()
+            {
+                self.replay_loop(section, record)?;
+            }
+            return Ok(());
+        }
+
+        match tokens[0].as_str() {
+            "DO" => {
+                let [_, index, first, last, ..] = tokens.as_slice() else {
+                    return Err(error(line_number, "invalid DO record"));
+                };
+                self.loop_record = Some(Loop {
+                    index: index.clone(),
+                    first: self.integer(first, line_number)?,
+                    last: self.integer(last, line_number)?,
+                    depth: 1,
+                    records: Vec::new(),
+                });
+                Ok(())
+            }
+            "DI" | "OD" | "ND" => Err(error(line_number, "loop record outside of a loop")),
+            kind if is_parameter_kind(kind) => self.define_parameter(&tokens, line_number),
+            _ => {
+                let tokens = self.expand(section, tokens, line_number)?;
+                let tokens = tokens.iter().map(String::as_str).collect::<Vec<_>>();
+                self.read_record(section, &tokens, line_number)
+            }
+        }
+    }
+
+    fn read_record(
+        &mut self,
+        section: Section,
+        tokens: &[&str],
+        line_number: usize,
+    ) -> Result<(), Problem> {
+        match section {
+            Section::Groups => self.read_group(tokens, line_number),
+            Section::Variables => self.read_variable(tokens, line_number),
+            Section::Constants => self.read_constant(tokens, line_number),
+            Section::Bounds => self.read_bound(tokens, line_number),
+            Section::StartPoint => self.read_start_point(tokens, line_number),
+            Section::ElementType => self.read_element_type(tokens, line_number),
+            Section::ElementUses => self.read_element_use(tokens, line_number),
+            Section::GroupType => self.read_group_type(tokens, line_number),
+            Section::GroupUses => self.read_group_use(tokens, line_number),
+            _ => Ok(()),
+        }
+    }
+
+    /// Replays the records of `record` for every value of its index. A `DI`
+    /// record in front of the others sets the increment.
+    fn replay_loop(&mut self, section: Section, record: Loop) -> Result<(), Problem> {
+        let mut records = record.records.as_slice();
+        let mut increment = 1;
+        if let Some(((line_number, tokens), rest)) = records.split_first()
+            && tokens[0] == "DI"
+        {
+            increment = self.integer(tokens.get(2).map_or("", String::as_str), *line_number)?;
+            if increment == 0 {
+                return Err(error(*line_number, "loop increment must not be zero"));
+            }
+            records = rest;
+        }
+
+        let mut value = record.first;
+        while (increment > 0 && value <= record.last) || (increment < 0 && value >= record.last) {
+            self.params.insert(record.index.clone(), value as E);
+            for (line_number, tokens) in records {
+                self.read_data_record(section, tokens.clone(), *line_number)?;
+            }
+            value += increment;
+        }
+        Ok(())
+    }
+
+    /// Value of a parameter or a literal number.
+    fn value(&self, token: &str, line_number: usize) -> Result<E, Problem> {
+        self.params
+            .get(token)
+            .copied()
+            .or_else(|| parse_number(token))
+            .ok_or_else(|| error(line_number, format!("unknown parameter '{token}'")))
+    }
+
+    fn integer(&self, token: &str, line_number: usize) -> Result<i64, Problem> {
+        Ok(self.value(token, line_number)?.trunc() as i64)
+    }
+
+    /// Defines the parameter of an `I`, `R` or `A` record. Integer parameters are
+    /// held as reals with integral values and array elements as parameters of
+    /// the expanded name.
+    ///
+    /// | kind | value       | kind | value         |
+    /// |------|-------------|------|---------------|
+    /// | `E`  | `v`         | `=`  | `p`           |
+    /// | `R`  | `int(p)`    | `+`  | `p + q`       |
+    /// | `I`  | `real(p)`   | `-`  | `p - q`       |
+    /// | `A`  | `p + v`     | `*`  | `p * q`       |
+    /// | `S`  | `v - p`     | `/`  | `p / q`       |
+    /// | `M`  | `p * v`     | `F`  | `f(v)`        |
+    /// | `D`  | `v / p`     | `(`  | `f(p)`        |
+    fn define_parameter(&mut self, tokens: &[String], line_number: usize) -> Result<(), Problem> {
+        let tokens = tokens
+            .iter()
+            .map(|t| self.expand_name(t, line_number))
+            .collect::<Result<Vec<_>, _>>()?;
+        let field = |k: usize| {
+            tokens
+                .get(k)
+                .map(String::as_str)
+                .ok_or_else(|| error(line_number, format!("invalid '{}' record", tokens[0])))
+        };
+        let (name, integer) = (field(1)?.to_string(), tokens[0].starts_with('I'));
+        let value = |k: usize| self.value(field(k)?, line_number);
+
+        let mut result = match &tokens[0][1..] {
+            "E" => value(2)?,
+            "R" => value(2)?.trunc(),
+            "I" | "=" => value(2)?,
+            "A" => value(2)? + value(3)?,
+            "S" => value(3)? - value(2)?,
+            "M" => value(2)? * value(3)?,
+            "D" => value(3)? / value(2)?,
+            "+" => value(2)? + value(3)?,
+            "-" => value(2)? - value(3)?,
+            "*" => value(2)? * value(3)?,
+            "/" => value(2)? / value(3)?,
+            "F" | "(" => apply_function(field(2)?, value(3)?, line_number)?,
+            _ => {
+                return Err(error(
+                    line_number,
+                    format!("unknown parameter record '{}'", tokens[0]),
+                ));
+            }
+        };
+        if integer {
+            result = result.trunc();
+        }
+        self.params.insert(name, result);
+        Ok(())
+    }
+
+    /// Replaces the indices of `name`, e.g. `X(I,J)`, by the values of the
+    /// integer parameters or literals they name.
+    fn expand_name(&self, name: &str, line_number: usize) -> Result<String, Problem> {
+        let Some((prefix, indices)) = name.strip_suffix(')').and_then(|name| name.split_once('('))
+        else {
+            return Ok(name.to_string());
+        };
+        let indices = indices
+            .split(',')
+            .map(|index| Ok(self.integer(index.trim(), line_number)?.to_string()))
+            .collect::<Result<Vec<_>, Problem>>()?;
+        Ok(format!("{prefix}{}", indices.join(",")))
+    }
+
+    /// Expands the indexed names of a record. The value of a `Z` kind is read
+    /// from the real parameter named in its last field, except for `ZV` element
+    /// uses, whose last field is an indexed variable.
+    fn expand(
+        &self,
+        section: Section,
+        tokens: Vec<String>,
+        line_number: usize,
+    ) -> Result<Vec<String>, Problem> {
+        let mut tokens = tokens
+            .iter()
+            .map(|t| self.expand_name(t, line_number))
+            .collect::<Result<Vec<_>, _>>()?;
+        let kind = tokens[0].clone();
+        if section == Section::Variables || !kind.starts_with('Z') || kind.len() > 2 {
+            return Ok(tokens);
+        }
+
+        tokens[0] = format!("X{}", &kind[1..]);
+        if !(section == Section::ElementUses && kind == "ZV")
+            && let Some(last) = tokens.last_mut()
+        {
+            *last = self.value(last, line_number)?.to_string();
+        }
+        Ok(tokens)
+    }
+
+    fn data_section(&mut self, header: &str, line_number: usize) -> Result<Section, Problem> {
+        let keyword = header.split_whitespace().next().unwrap_or_default();
+        Ok(match keyword {
+            "NAME" => {
+                self.name = header.split_whitespace().nth(1).unwrap_or("").to_string();
+                Section::Preamble
+            }
+            "GROUPS" | "ROWS" | "CONSTRAINTS" => Section::Groups,
+            "VARIABLES" | "COLUMNS" => Section::Variables,
+            "CONSTANTS" | "RHS" | "RHS'" => Section::Constants,
+            "BOUNDS" => Section::Bounds,
+            "START" => Section::StartPoint,
+            "ELEMENT" if header.contains("TYPE") => Section::ElementType,
+            "ELEMENT" => Section::ElementUses,
+            "GROUP" if header.contains("TYPE") => Section::GroupType,
+            "GROUP" => Section::GroupUses,
+            "OBJECT" => Section::Ignored,
+            "RANGES" | "QUADRATIC" | "HESSIAN" | "QUADS" | "QUADOBJ" | "QSECTION" => {
+                return Err(error(line_number, format!("{keyword} are not supported")));
+            }
+            _ => return Err(error(line_number, format!("unknown section '{header}'"))),
+        })
+    }
+
+    fn var(&mut self, name: &str) -> usize {
+        if let Some(&j) = self.var_index.get(name) {
+            return j;
+        }
+        let j = self.var_names.len();
+        self.var_index.insert(name.to_string(), j);
+        self.var_names.push(name.to_string());
+        self.l.push(E::from(0.));
+        self.u.push(E::INFINITY);
+        self.x0.push(E::from(0.));
+        j
+    }
+
+    fn group(&mut self, name: &str, kind: GroupKind) -> usize {
+        if let Some(&i) = self.group_index.get(name) {
+            return i;
+        }
+        let i = self.groups.len();
+        self.group_index.insert(name.to_string(), i);
+        self.group_names.push(name.to_string());
+        self.groups.push(Group {
+            kind,
+            linear: Vec::new(),
+            constant: E::from(0.),
+            scale: E::from(1.),
+            gtype: None,
+            params: Vec::new(),
+            elements: Vec::new(),
+        });
+        i
+    }
+
+    fn existing_var(&self, name: &str, line_number: usize) -> Result<usize, Problem> {
+        self.var_index
+            .get(name)
+            .copied()
+            .ok_or_else(|| error(line_number, format!("unknown variable '{name}'")))
+    }
+
+    fn existing_group(&self, name: &str, line_number: usize) -> Result<usize, Problem> {
+        self.group_index
+            .get(name)
+            .copied()
+            .ok_or_else(|| error(line_number, format!("unknown group '{name}'")))
+    }
+
+    fn read_group(&mut self, tokens: &[&str], line_number: usize) -> Result<(), Problem> {
+        let kind = match strip_x(tokens[0]) {
+            "N" => GroupKind::N,
+            "E" => GroupKind::E,
+            "L" => GroupKind::L,
+            "G" => GroupKind::G,
+            kind => return Err(error(line_number, format!("unknown group type '{kind}'"))),
+        };
+        let name = tokens
+            .get(1)
+            .ok_or_else(|| error(line_number, "missing group name"))?;
+        let i = self.group(name, kind);
+
+        for pair in tokens[2..].chunks(2) {
+            let value = parse_value(pair.get(1), line_number)?;
+            match pair[0] {
+                "'SCALE'" => self.groups[i].scale = value,
+                var => {
+                    let j = self.existing_var(var, line_number)?;
+                    self.groups[i].linear.push((j, value));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn read_variable(&mut self, tokens: &[&str], line_number: usize) -> Result<(), Problem> {
+        if tokens.contains(&"'MARKER'") {
+            return Ok(());
+        }
+        // A name followed by pairs, behind an optional `X` kind
+        let tokens = match tokens {
+            ["X", rest @ ..] if tokens.len().is_multiple_of(2) => rest,
+            _ => tokens,
+        };
+        let j = self.var(tokens[0]);
+        for pair in tokens[1..].chunks(2) {
+            let value = parse_value(pair.get(1), line_number)?;
+            // Variable scaling only affects the numerics of SIF-based solvers
+            if pair[0] == "'SCALE'" {
+                continue;
+            }
+            let i = self.existing_group(pair[0], line_number)?;
+            self.groups[i].linear.push((j, value));
+        }
+        Ok(())
+    }
+
+    /// Drops the optional set name in front of `name value` pairs.
+    fn strip_set_name<'a, 'b>(tokens: &'a [&'b str]) -> &'a [&'b str] {
+        if tokens.len() % 2 == 1 {
+            &tokens[1..]
+        } else {
+            tokens
+        }
+    }
+
+    fn read_constant(&mut self, tokens: &[&str], line_number: usize) -> Result<(), Problem> {
+        let tokens = match tokens[0] {
+            "X" | "Z" => &tokens[1..],
+            _ => tokens,
+        };
+        for pair in Self::strip_set_name(tokens).chunks(2) {
+            let value = parse_value(pair.get(1), line_number)?;
+            if pair[0] == "'DEFAULT'" {
+                self.groups.iter_mut().for_each(|g| g.constant = value);
+            } else {
+                let i = self.existing_group(pair[0], line_number)?;
+                self.groups[i].constant = value;
+            }
+        }
+        Ok(())
+    }
+
+    fn read_bound(&mut self, tokens: &[&str], line_number: usize) -> Result<(), Problem> {
+        let bound_type = match tokens[0] {
+            "XL" | "LO" => "LO",
+            "XU" | "UP" => "UP",
+            "XX" | "FX" => "FX",
+            "XR" | "FR" => "FR",
+            "XM" | "MI" => "MI",
+            "XP" | "PL" => "PL",
+            kind => return Err(error(line_number, format!("unknown bound type '{kind}'"))),
+        };
+        let has_value = matches!(bound_type, "LO" | "UP" | "FX");
+        // The name of the bound vector is optional
+        let (name, value) = match (has_value, tokens.len()) {
+            (true, 4) => (tokens[2], Some(parse_value(tokens.get(3), line_number)?)),
+            (true, _) => (tokens[1], Some(parse_value(tokens.get(2), line_number)?)),
+            (false, 3) => (tokens[2], None),
+            (false, _) => (tokens[1], None),
+        };
+
+        let vars = match name {
+            "'DEFAULT'" => (0..self.var_names.len()).collect::<Vec<_>>(),
+            name => vec![self.existing_var(name, line_number)?],
+        };
+        for j in vars {
+            let (l, u) = (&mut self.l[j], &mut self.u[j]);
+            match (bound_type, value) {
+                ("LO", Some(v)) => *l = v,
+                ("UP", Some(v)) => *u = v,
+                ("FX", Some(v)) => (*l, *u) = (v, v),
+                ("FR", _) => (*l, *u) = (-E::INFINITY, E::INFINITY),
+                ("MI", _) => *l = -E::INFINITY,
+                _ => *u = E::INFINITY,
+            }
+        }
+        Ok(())
+    }
+
+    fn read_start_point(&mut self, tokens: &[&str], line_number: usize) -> Result<(), Problem> {
+        let tokens = match tokens[0] {
+            "V" | "X" | "XV" => &tokens[1..],
+            // Starting values of the multipliers are not used
+            "M" | "XM" => return Ok(()),
+            _ => tokens,
+        };
+        for pair in Self::strip_set_name(tokens).chunks(2) {
+            let value = parse_value(pair.get(1), line_number)?;
+            if pair[0] == "'DEFAULT'" {
+                self.x0.iter_mut().for_each(|x| *x = value);
+            } else if let Some(&j) = self.var_index.get(pair[0]) {
+                self.x0[j] = value;
+            } else if !self.group_index.contains_key(pair[0]) {
+                return Err(error(
+                    line_number,
+                    format!("unknown variable '{}'", pair[0]),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn read_element_type(&mut self, tokens: &[&str], line_number: usize) -> Result<(), Problem> {
+        let [kind, type_name, names @ ..] = tokens else {
+            return Err(error(line_number, "invalid element type record"));
+        };
+        let t = match self.element_type_index.get(*type_name) {
+            Some(&t) => t,
+            None => {
+                let t = self.element_types.len();
+                self.element_type_index.insert(type_name.to_string(), t);
+                self.element_types
+                    .push(Function::new(Vec::new(), Vec::new()));
+                t
+            }
+        };
+
+        let function = &mut self.element_types[t];
+        let (mut args, mut params) = (
+            function.names[..function.n_args].to_vec(),
+            function.names[function.n_args..].to_vec(),
+        );
+        match *kind {
+            "EV" => args.extend(names.iter().map(|n| n.to_string())),
+            "EP" => params.extend(names.iter().map(|n| n.to_string())),
+            "IV" => {
+                return Err(error(
+                    line_number,
+                    "internal element variables are not supported",
+                ));
+            }
+            kind => {
+                return Err(error(
+                    line_number,
+                    format!("unknown element type record '{kind}'"),
+                ));
+            }
+        }
+        *function = Function::new(args, params);
+        Ok(())
+    }
+
+    fn read_element_use(&mut self, tokens: &[&str], line_number: usize) -> Result<(), Problem> {
+        let kind = strip_x(tokens[0]);
+        let name = tokens
+            .get(1)
+            .ok_or_else(|| error(line_number, "missing element name"))?;
+
+        if kind == "T" {
+            let t = *self
+                .element_type_index
+                .get(*tokens.get(2).unwrap_or(&""))
+                .ok_or_else(|| error(line_number, "unknown element type"))?;
+            if *name == "'DEFAULT'" {
+                self.default_element_type = Some(t);
+                return Ok(());
+            }
+            let e = self.element(name);
+            self.elements[e].etype = Some(t);
+            self.size_element(e);
+            return Ok(());
+        }
+
+        let e = self.element(name);
+        if self.elements[e].etype.is_none() {
+            self.elements[e].etype = self.default_element_type;
+            self.size_element(e);
+        }
+        let t = self.elements[e]
+            .etype
+            .ok_or_else(|| error(line_number, format!("element '{name}' has no type")))?;
+        let function = &self.element_types[t];
+
+        let slot = tokens
+            .get(2)
+            .and_then(|n| function.slot(n))
+            .ok_or_else(|| error(line_number, "unknown elemental variable or parameter"))?;
+        match kind {
+            "V" if slot < function.n_args => {
+                let var = tokens
+                    .get(3)
+                    .ok_or_else(|| error(line_number, "missing variable"))?;
+                // Variables may first appear in an element
+                let j = self.var(var);
+                self.elements[e].vars[slot] = Some(j);
+            }
+            "P" if slot >= function.n_args => {
+                let value = parse_value(tokens.get(3), line_number)?;
+                self.elements[e].params[slot - function.n_args] = Some(value);
+            }
+            _ => {
+                return Err(error(
+                    line_number,
+                    format!("invalid element use record '{}'", tokens[0]),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn element(&mut self, name: &str) -> usize {
+        if let Some(&e) = self.element_index.get(name) {
+            return e;
+        }
+        let e = self.elements.len();
+        self.element_index.insert(name.to_string(), e);
+        self.elements.push(ElementUse {
+            etype: None,
+            vars: Vec::new(),
+            params: Vec::new(),
+        });
+        e
+    }
+
+    fn size_element(&mut self, e: usize) {
+        let element = &mut self.elements[e];
+        if let Some(t) = element.etype {
+            let function = &self.element_types[t];
+            element.vars.resize(function.n_args, None);
+            element.params.resize(function.n_params, None);
+        }
+    }
+
+    fn read_group_type(&mut self, tokens: &[&str], line_number: usize) -> Result<(), Problem> {
+        let [kind, type_name, name, ..] = tokens else {
+            return Err(error(line_number, "invalid group type record"));
+        };
+        let t = match self.group_type_index.get(*type_name) {
+            Some(&t) => t,
+            None => {
+                let t = self.group_types.len();
+                self.group_type_index.insert(type_name.to_string(), t);
+                self.group_types.push(Function::new(Vec::new(), Vec::new()));
+                t
+            }
+        };
+
+        let function = &mut self.group_types[t];
+        let (mut args, mut params) = (
+            function.names[..function.n_args].to_vec(),
+            function.names[function.n_args..].to_vec(),
+        );
+        match *kind {
+            "GV" => args = vec![name.to_string()],
+            "GP" => params.extend(tokens[2..].iter().map(|n| n.to_string())),
+            kind => {
+                return Err(error(
+                    line_number,
+                    format!("unknown group type record '{kind}'"),
+                ));
+            }
+        }
+        *function = Function::new(args, params);
+        Ok(())
+    }
+
+    fn read_group_use(&mut self, tokens: &[&str], line_number: usize) -> Result<(), Problem> {
+        let kind = strip_x(tokens[0]);
+        let name = tokens
+            .get(1)
+            .ok_or_else(|| error(line_number, "missing group name"))?;
+
+        match kind {
+            "T" => {
+                let t = *self
+                    .group_type_index
+                    .get(*tokens.get(2).unwrap_or(&""))
+                    .ok_or_else(|| error(line_number, "unknown group type"))?;
+                let n_params = self.group_types[t].n_params;
+                let groups = match *name {
+                    "'DEFAULT'" => {
+                        self.default_group_type = Some(t);
+                        return Ok(());
+                    }
+                    name => vec![self.existing_group(name, line_number)?],
+                };
+                for i in groups {
+                    self.groups[i].gtype = Some(t);
+                    self.groups[i].params = vec![None; n_params];
+                }
+            }
+            "E" => {
+                let i = self.existing_group(name, line_number)?;
+                let mut k = 2;
+                while k < tokens.len() {
+                    let e = *self.element_index.get(tokens[k]).ok_or_else(|| {
+                        error(line_number, format!("unknown element '{}'", tokens[k]))
+                    })?;
+                    let weight = tokens.get(k + 1).and_then(|t| parse_number(t));
+                    self.groups[i]
+                        .elements
+                        .push((e, weight.unwrap_or(E::from(1.))));
+                    k += if weight.is_some() { 2 } else { 1 };
+                }
+            }
+            "P" => {
+                let i = self.existing_group(name, line_number)?;
+                let t = self.groups[i]
+                    .gtype
+                    .or(self.default_group_type)
+                    .ok_or_else(|| error(line_number, format!("group '{name}' has no type")))?;
+                let function = &self.group_types[t];
+                let slot = tokens
+                    .get(2)
+                    .and_then(|n| function.slot(n))
+                    .filter(|&s| s >= function.n_args)
+                    .ok_or_else(|| error(line_number, "unknown group parameter"))?;
+                let n_params = function.n_params;
+                let value = parse_value(tokens.get(3), line_number)?;
+
+                let group = &mut self.groups[i];
+                group.gtype = Some(t);
+                group.params.resize(n_params, None);
+                group.params[slot - 1] = Some(value);
+            }
+            kind => {
+                return Err(error(
+                    line_number,
+                    format!("invalid group use record '{kind}'"),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn read_function_line(
+        &mut self,
+        part: Part,
+        section: Section,
+        tokens: &[&str],
+        line_number: usize,
+    ) -> Result<(), Problem> {
+        // Continuation lines extend the pending expression
+        if let Some(kind) = tokens[0].strip_suffix('+') {
+            let pending = self
+                .pending
+                .as_mut()
+                .filter(|p| kind.starts_with(p.kind))
+                .ok_or_else(|| error(line_number, "continuation without an expression"))?;
+            let skip = 1 + pending.names.len();
+            pending.text.push(' ');
+            pending
+                .text
+                .push_str(&tokens[skip.min(tokens.len())..].join(" "));
+            return Ok(());
+        }
+        self.flush_pending(part)?;
+
+        let kind = tokens[0];
+        if section == Section::Globals {
+            if kind != "A" {
+                return Err(error(
+                    line_number,
+                    format!("unsupported global record '{kind}'"),
+                ));
+            }
+        } else if kind == "T" {
+            let name = tokens.get(1).copied().unwrap_or_default();
+            let index = match part {
+                Part::Elements => &self.element_type_index,
+                _ => &self.group_type_index,
+            };
+            self.current_function = Some(
+                *index
+                    .get(name)
+                    .ok_or_else(|| error(line_number, format!("unknown type '{name}'")))?,
+            );
+            return Ok(());
+        }
+
+        let n_names = match (kind, part) {
+            ("A", _) => 1,
+            ("F", _) => 0,
+            ("G", Part::Elements) => 1,
+            ("H", Part::Elements) => 2,
+            ("G" | "H", _) => 0,
+            ("R", Part::Elements) => {
+                return Err(error(
+                    line_number,
+                    "internal element variables are not supported",
+                ));
+            }
+            _ => {
+                return Err(error(
+                    line_number,
+                    format!("unsupported function record '{kind}'"),
+                ));
+            }
+        };
+        if tokens.len() < 1 + n_names {
+            return Err(error(line_number, format!("invalid '{kind}' record")));
+        }
+
+        self.pending = Some(PendingExpr {
+            kind: kind.chars().next().unwrap(),
+            names: tokens[1..1 + n_names]
+                .iter()
+                .map(|n| n.to_string())
+                .collect(),
+            text: tokens[1 + n_names..].join(" "),
+            global: section == Section::Globals,
+            line_number,
+        });
+        Ok(())
+    }
+
+    /// Compiles the pending expression into the current function or the globals.
+    fn flush_pending(&mut self, part: Part) -> Result<(), Problem> {
+        let Some(pending) = self.pending.take() else {
+            return Ok(());
+        };
+        let line_number = pending.line_number;
+
+        if pending.global {
+            // Globals may only depend on constants and earlier globals
+            let empty = Function::new(Vec::new(), Vec::new());
+            let value = empty
+                .compile(&pending.text, &self.globals)
+                .map_err(|e| error(line_number, e))?
+                .eval(&[]);
+            self.globals.insert(pending.names[0].clone(), value);
+            return Ok(());
+        }
+
+        let t = self
+            .current_function
+            .ok_or_else(|| error(line_number, "expression outside of a function"))?;
+
+        let function = match part {
+            Part::Elements => &mut self.element_types[t],
+            _ => &mut self.group_types[t],
+        };
+        let expr = function
+            .compile(&pending.text, &self.globals)
+            .map_err(|e| error(line_number, e))?;
+
+        let arg = |name: &String| {
+            function.names[..function.n_args]
+                .iter()
+                .position(|n| n == name)
+                .ok_or_else(|| error(line_number, format!("unknown variable '{name}'")))
+        };
+        match pending.kind {
+            'A' => {
+                function.names.push(pending.names[0].clone());
+                function.temps.push(expr);
+            }
+            'F' => function.f = Some(expr),
+            'G' if part == Part::Elements => {
+                let k = arg(&pending.names[0])?;
+                function.g[k] = expr;
+            }
+            'H' if part == Part::Elements => {
+                let (k, l) = (arg(&pending.names[0])?, arg(&pending.names[1])?);
+                let index = function.hessian_index(k, l);
+                function.h[index] = expr;
+            }
+            'G' => function.g[0] = expr,
+            _ => function.h[0] = expr,
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<CUTEstProblem, Problem> {
+        if let Some(record) = &self.loop_record {
+            return Err(format!("Loop over '{}' is not closed", record.index).gloss());
+        }
+
+        let mut elements = Vec::with_capacity(self.elements.len());
+        for (e, name) in sorted_names(&self.element_index) {
+            let element = &self.elements[e];
+            let t = element
+                .etype
+                .ok_or_else(|| format!("Element '{name}' has no type").gloss())?;
+            let (Some(vars), Some(params)) = (
+                element.vars.iter().copied().collect::<Option<Vec<_>>>(),
+                element.params.iter().copied().collect::<Option<Vec<_>>>(),
+            ) else {
+                return Err(
+                    format!("Element '{name}' has unassigned variables or parameters").gloss(),
+                );
+            };
+            if self.element_types[t].f.is_none() {
+                return Err(format!("Element '{name}' has an undefined element function").gloss());
+            }
+            elements.push(Element {
+                etype: t,
+                vars,
+                params,
+            });
+        }
+
+        for (i, group) in self.groups.iter_mut().enumerate() {
+            if group.gtype.is_none() {
+                group.gtype = self.default_group_type;
+            }
+            if let Some(t) = group.gtype {
+                let function = &self.group_types[t];
+                group.params.resize(function.n_params, None);
+                if function.f.is_none() || group.params.iter().any(Option::is_none) {
+                    return Err(format!(
+                        "Group '{}' has an undefined group function or parameter",
+                        self.group_names[i]
+                    )
+                    .gloss());
+                }
+            }
+        }
+
+        let mut objective = Vec::new();
+        let mut constraints = Vec::new();
+        let mut con_names = Vec::new();
+        let mut n_slack = 0;
+        for (i, group) in self.groups.iter().enumerate() {
+            let slack_sign = match group.kind {
+                GroupKind::N => {
+                    objective.push(i);
+                    continue;
+                }
+                GroupKind::E => None,
+                GroupKind::L => Some(E::from(1.)),
+                GroupKind::G => Some(E::from(-1.)),
+            };
+            let slack = slack_sign.map(|sign| {
+                n_slack += 1;
+                (n_slack - 1, sign)
+            });
+            constraints.push((i, slack));
+            con_names.push(self.group_names[i].clone());
+        }
+
+        let model = Model {
+            n_x: self.var_names.len(),
+            n_slack,
+            element_types: self.element_types,
+            group_types: self.group_types,
+            elements,
+            groups: self.groups,
+            objective,
+            constraints,
+        };
+
+        Ok(CUTEstProblem {
+            name: self.name,
+            x0: Col::from_fn(self.x0.len(), |j| self.x0[j]),
+            l: Col::from_fn(self.l.len(), |j| self.l[j]),
+            u: Col::from_fn(self.u.len(), |j| self.u[j]),
+            var_names: self.var_names,
+            con_names,
+            model: Arc::new(model),
+        })
+    }
+}
+
+fn is_parameter_kind(kind: &str) -> bool {
+    kind.len() == 2 && matches!(kind.as_bytes()[0], b'I' | b'R' | b'A')
+}
+
+/// Applies the function of an `RF` record, e.g. `SQRT` or `ARCTAN`.
+fn apply_function(name: &str, value: E, line_number: usize) -> Result<E, Problem> {
+    let name = match name {
+        "ARCSIN" => "ASIN",
+        "ARCCOS" => "ACOS",
+        "ARCTAN" => "ATAN",
+        "HYPSIN" => "SINH",
+        "HYPCOS" => "COSH",
+        "HYPTAN" => "TANH",
+        name => name,
+    };
+    let expr = Expr::parse(&format!("{name}(X)"), &|arg| {
+        (arg == "X").then_some(Expr::Slot(0))
+    })
+    .map_err(|_| error(line_number, format!("unknown function '{name}'")))?;
+    Ok(expr.eval(&[value]))
+}
+
+fn sorted_names(index: &HashMap<String, usize>) -> Vec<(usize, &str)> {
+    let mut names = index
+        .iter()
+        .map(|(n, &i)| (i, n.as_str()))
+        .collect::<Vec<_>>();
+    names.sort_unstable();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use faer::col;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::{
+        IterativeSolver, SolverHooks, SolverOptions, SolverState, Status,
+        callback::{ConvergenceOutput, NoOpCallback},
+        nlp::{NLPSolver, gd::GradientDescent, gd::stepsize::ConstantStepSize},
+        terminators::SlowProgressTerminator,
+    };
+
+    static ROSENBR: &str = "
+NAME          ROSENBR
+VARIABLES
+    X1
+    X2
+GROUPS
+ N  G1        X2        1.0
+ N  G1        'SCALE'   0.01
+ N  G2        X1        1.0
+CONSTANTS
+    ROSENBR   G2        1.0
+BOUNDS
+ FR ROSENBR   'DEFAULT'
+START POINT
+    ROSENBR   X1        -1.2           X2        1.0
+ELEMENT TYPE
+ EV SQ        V1
+ELEMENT USES
+ T  E1        SQ
+ V  E1        V1                       X1
+GROUP TYPE
+ GV L2        GVAR
+GROUP USES
+ T  'DEFAULT' L2
+ E  G1        E1        -1.0
+OBJECT BOUND
+ LO ROSENBR             0.0
+ENDATA
+ELEMENTS      ROSENBR
+INDIVIDUALS
+ T  SQ
+ F                      V1 * V1
+ G  V1                  V1 + V1
+ H  V1        V1        2.0
+ENDATA
+GROUPS        ROSENBR
+INDIVIDUALS
+ T  L2
+ F                      GVAR * GVAR
+ G                      GVAR + GVAR
+ H                      2.0
+ENDATA
+";
+
+    /// `min (x1 - 1)^2 + (x2 - 2)^2` s.t. `x1 + x2 = 3` and `x1 x2 <= 2.5`
+    static QUADCON: &str = "
+NAME          QUADCON
+VARIABLES
+    X1
+    X2
+GROUPS
+ XN OBJ1      X1        1.0
+ XN OBJ2      X2        1.0
+ XE CON       X1        1.0            X2        1.0
+ XL PROD
+CONSTANTS
+    QUADCON   OBJ1      1.0            OBJ2      2.0
+    QUADCON   CON       3.0            PROD      2.5
+BOUNDS
+ XR QUADCON   'DEFAULT'
+START POINT
+ XV QUADCON   X1        0.5            X2        0.5
+ELEMENT TYPE
+ EV 2PR       V1                       V2
+ELEMENT USES
+ XT E1        2PR
+ ZV E1        V1                       X1
+ XV E1        V2                       X2
+GROUP TYPE
+ GV L2        GVAR
+GROUP USES
+ XT OBJ1      L2
+ XT OBJ2      L2
+ XE PROD      E1
+ENDATA
+ELEMENTS      QUADCON
+TEMPORARIES
+ R  PROD
+GLOBALS
+ A  ONE                 1.0D+0
+INDIVIDUALS
+ T  2PR
+ A  PROD                V1 * V2
+ F                      PROD
+ G  V1                  V2
+ G  V2                  V1
+ H  V1        V2        ONE
+ENDATA
+GROUPS        QUADCON
+INDIVIDUALS
+ T  L2
+ F                      GVAR**2
+ G                      2.0 * GVAR
+ H                      2.0
+ENDATA
+";
+
+    fn central_difference(f: impl Fn(&Col<E>) -> Col<E>, x: &Col<E>, j: usize) -> Col<E> {
+        let h = 1e-6;
+        let (mut x_plus, mut x_minus) = (x.clone(), x.clone());
+        x_plus[j] += h;
+        x_minus[j] -= h;
+        (f(&x_plus) - f(&x_minus)) / (2. * h)
+    }
+
+    #[test]
+    fn test_rosenbrock() {
+        let problem = CUTEstProblem::read(ROSENBR.as_bytes()).unwrap();
+        let nlp = NonlinearProgram::from(&problem);
+        let x0 = problem.get_start_point();
+
+        assert_eq!(problem.get_name(), "ROSENBR");
+        assert_eq!(x0, col![-1.2, 1.]);
+        assert!((nlp.f(&x0) - 24.2).abs() < 1e-12);
+        assert!((nlp.df(&x0) - col![-215.6, -88.]).norm_l2() < 1e-10);
+        assert_eq!(nlp.l().unwrap(), &col![-E::INFINITY, -E::INFINITY]);
+
+        // The minimizer is (1, 1)
+        let x = col![1., 1.];
+        assert_eq!(nlp.f(&x), 0.);
+        let h = nlp.h(&x, &Col::zeros(0)).unwrap().to_dense();
+        assert!((h[(0, 0)] - 802.).abs() < 1e-10 && (h[(0, 1)] + 400.).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_derivatives() {
+        let x = col![0.3, -0.7, 1.1];
+        let y = col![0.5, -2.];
+        let problem = CUTEstProblem::read(QUADCON.replace("ZV", "XV").as_bytes()).unwrap();
+        let nlp = NonlinearProgram::from(&problem);
+
+        assert_eq!((problem.get_n_vars(), problem.get_n_cons()), (3, 2));
+        assert_eq!(problem.get_con_names(), &["CON", "PROD"]);
+        assert!((nlp.g(&x) - col![0.3 - 0.7 - 3., 0.3 * -0.7 - 2.5 + 1.1]).norm_l2() < 1e-12);
+
+        // Slack of the inequality absorbs its violation at the starting point
+        assert_eq!(problem.get_start_point(), col![0.5, 0.5, 2.25]);
+
+        let df = nlp.df(&x);
+        let dg = nlp.dg(&x).to_dense();
+        let h = nlp.h(&x, &y).unwrap().to_dense();
+        let grad_lagrangian = |x: &Col<E>| nlp.df(x) + nlp.dg(x).transpose() * &y;
+        for j in 0..3 {
+            let f = |x: &Col<E>| col![nlp.f(x)];
+            assert!((central_difference(f, &x, j)[0] - df[j]).abs() < 1e-6);
+            assert!((central_difference(|x| nlp.g(x), &x, j) - dg.col(j)).norm_l2() < 1e-6);
+            assert!((central_difference(grad_lagrangian, &x, j) - h.col(j)).norm_l2() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_parameters_and_loops() {
+        // `ZV` names the variable of an element like `V`
+        let problem = CUTEstProblem::read(QUADCON.as_bytes()).unwrap();
+        assert_eq!(problem.get_start_point(), col![0.5, 0.5, 2.25]);
+
+        // Nested loops ended by a single `ND`, with a decreasing inner index
+        let with_loops = ROSENBR.replace(
+            "VARIABLES\n    X1\n    X2\n",
+            "
+ IE 1                   1
+ IE N                   2
+ IA N+1       N         1
+ RI RN        N
+ RD H         RN        1.0
+ RM MH        H         -1.0
+VARIABLES
+ DO I         1                        N
+ DO J         N+1                      N
+ DI J         -1
+ X  Y(I,J)
+ ND
+ DO I         1                        N
+ X  X(I)
+ OD I
+",
+        );
+        let problem = CUTEstProblem::read(with_loops.as_bytes()).unwrap();
+        assert_eq!(
+            problem.get_var_names(),
+            ["Y1,3", "Y1,2", "Y2,3", "Y2,2", "X1", "X2"]
+        );
+
+        // The weight of the element is read from a parameter
+        let scaled = with_loops.replace(
+            " E  G1        E1        -1.0",
+            " ZE G1        E1                       MH",
+        );
+        let problem = CUTEstProblem::read(scaled.as_bytes()).unwrap();
+        let nlp = NonlinearProgram::from(&problem);
+        let x = col![0., 0., 0., 0., 2., 1.];
+        // 100 (x2 - x1^2 / 2)^2 + (x1 - 1)^2, with X1 and X2 after the Y variables
+        assert!((nlp.f(&x) - 101.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let unclosed = ROSENBR.replace(
+            "VARIABLES\n",
+            "VARIABLES\n DO I         1                        2\n",
+        );
+        let error = CUTEstProblem::read(unclosed.as_bytes()).err().unwrap();
+        assert!(error.to_string().contains("not closed"), "{error}");
+
+        let unknown = ROSENBR.replace("    X2\n", " X  X(N)\n");
+        let error = CUTEstProblem::read(unknown.as_bytes()).err().unwrap();
+        assert!(
+            error.to_string().contains("unknown parameter 'N'"),
+            "{error}"
+        );
+
+        let ranges = ROSENBR.replace("BOUNDS\n", "RANGES\n    ROSENBR   G2        1.0\nBOUNDS\n");
+        assert!(CUTEstProblem::read(ranges.as_bytes()).is_err());
+    }
+
+    /// Reads an instance of `data/cutest`.
+    fn read_instance(name: &str) -> CUTEstProblem {
+        let path = format!("{}/data/cutest/{name}.SIF", env!("CARGO_MANIFEST_DIR"));
+        CUTEstProblem::read_file(path).unwrap()
+    }
+
+    #[rstest]
+    #[case("HS5", 2, 0, 1., col![0.5 - PI / 3., -0.5 - PI / 3.], -(3f64.sqrt()) / 2. - PI / 3.)]
+    #[case("HS21", 3, 1, -98.99, col![2., 0., 10.], -99.96)]
+    #[case("HS35", 4, 1, 2.25, col![4. / 3., 7. / 9., 4. / 9., 0.], 1. / 9.)]
+    #[case("ARWHEAD", 10, 0, 27., Col::from_fn(10, |j| if j < 9 { 1. } else { 0. }), 0.)]
+    fn test_instances(
+        #[case] name: &str,
+        #[case] n_vars: usize,
+        #[case] n_cons: usize,
+        #[case] f_start: E,
+        #[case] solution: Col<E>,
+        #[case] f_solution: E,
+    ) {
+        let problem = read_instance(name);
+        let nlp = NonlinearProgram::from(&problem);
+        assert_eq!(problem.get_name(), name);
+        assert_eq!(
+            (problem.get_n_vars(), problem.get_n_cons()),
+            (n_vars, n_cons)
+        );
+
+        let x0 = problem.get_start_point();
+        assert!((nlp.f(&x0) - f_start).abs() < 1e-12);
+        assert!((nlp.f(&solution) - f_solution).abs() < 1e-12);
+        assert!(nlp.g(&solution).norm_max() < 1e-12);
+        let (l, u) = (problem.get_lower_bounds(), problem.get_upper_bounds());
+        assert!((0..n_vars).all(|j| l[j] <= solution[j] && solution[j] <= u[j]));
+
+        let y = Col::<E>::ones(n_cons);
+        let df = nlp.df(&x0);
+        let dg = nlp.dg(&x0).to_dense();
+        let h = nlp.h(&x0, &y).unwrap().to_dense();
+        let grad_lagrangian = |x: &Col<E>| nlp.df(x) + nlp.dg(x).transpose() * &y;
+        for j in 0..n_vars {
+            let f = |x: &Col<E>| col![nlp.f(x)];
+            assert!((central_difference(f, &x0, j)[0] - df[j]).abs() < 1e-6);
+            assert!((central_difference(|x| nlp.g(x), &x0, j) - dg.col(j)).norm_l2() < 1e-6);
+            assert!((central_difference(grad_lagrangian, &x0, j) - h.col(j)).norm_l2() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_hs5() {
+        let problem = read_instance("HS5");
+        let nlp = NonlinearProgram::from(&problem);
+
+        let mut state = SolverState::new(
+            problem.get_start_point(),
+            Col::zeros(0),
+            Col::zeros(2),
+            Col::zeros(2),
+        );
+        let options = SolverOptions::new();
+        let mut hooks = SolverHooks {
+            callback: Box::new(NoOpCallback::new()),
+            terminator: Box::new(SlowProgressTerminator::new(&options)),
+        };
+        let mut solver = GradientDescent::<ConstantStepSize>::new(&nlp, &options);
+        let status = solver.solve(&mut state, &mut hooks).unwrap();
+        assert_eq!(status, Status::Optimal);
+        assert!((state.x - col![0.5 - PI / 3., -0.5 - PI / 3.]).norm_max() < 1e-3);
+    }
+
+    #[test]
+    fn test_gradient_descent() {
+        // Drop the product inequality, which is inactive at the solution
+        let text = QUADCON
+            .replace("ZV", "XV")
+            .replace(" XL PROD\n", "")
+            .replace("PROD      2.5", "")
+            .replace(" XE PROD      E1\n", "");
+        let problem = CUTEstProblem::read(text.as_bytes()).unwrap();
+        let nlp = NonlinearProgram::from(&problem);
+
+        let mut state = SolverState::new(
+            problem.get_start_point(),
+            Col::zeros(problem.get_n_cons()),
+            Col::zeros(problem.get_n_vars()),
+            Col::zeros(problem.get_n_vars()),
+        );
+        let options = SolverOptions::new();
+        let mut hooks = SolverHooks {
+            callback: Box::new(ConvergenceOutput::new(&options)),
+            terminator: Box::new(SlowProgressTerminator::new(&options)),
+        };
+
+        let mut solver = GradientDescent::<ConstantStepSize>::new(&nlp, &options);
+        let status = solver.solve(&mut state, &mut hooks).unwrap();
+        assert_eq!(status, Status::Optimal);
+        assert!((state.x[0] - 1.).abs() < 1e-3);
+        assert!((state.x[1] - 2.).abs() < 1e-3);
+    }
+}
@@ -1 +1,6 @@
+pub mod basis;
+pub mod cutest;
+pub mod robust;
 pub mod sif;
+pub mod solution;
+pub mod warm_start;
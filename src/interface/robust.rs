@@ -0,0 +1,409 @@
+//! Robust counterparts of linear programs with row-wise uncertainty.
+//!
+//! An uncertain row is an inequality of the standard form, `a^T x + s = b` with
+//! a slack `s >= 0` (or `a^T x - s = b` for a `>=` row), whose coefficients
+//! vary independently within `a_j +/- d_j`. The robust counterpart requires the
+//! inequality to hold for every realization in the uncertainty set:
+//!
+//! - **Box**: every coefficient may deviate at once, so the row becomes
+//!   `a^T x + sum_j d_j |x_j| <= b`.
+//! - **Budget** (Bertsimas–Sim): at most `gamma` coefficients deviate. By
+//!   duality of the inner maximization, the row becomes
+//!   `a^T x + gamma z + sum_j p_j <= b` with `z + p_j >= d_j |x_j|` and
+//!   `z, p >= 0`.
+//!
+//! The absolute value `|x_j|` is replaced by `x_j` or `-x_j` when the bounds of
+//! the column fix its sign, and by an auxiliary variable `t_j >= |x_j|` otherwise.
+//! All auxiliary variables and the slacks of the added rows follow the columns of
+//! the original program.
+
+use faer::{
+    Col,
+    sparse::{SparseColMat, Triplet},
+};
+use problemo::{Problem, common::IntoCommonProblem};
+
+use crate::{E, I, lp::LinearProgram, to_index};
+
+/// Set of coefficient realizations of an uncertain row.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UncertaintySet {
+    /// All coefficients may deviate simultaneously.
+    Box,
+    /// At most `gamma` coefficients deviate, one of them possibly by a fraction.
+    Budget(E),
+}
+
+/// Deviations of the coefficients of a row and the set they range over.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowUncertainty {
+    /// Columns and their maximal deviations `d_j >= 0`.
+    deviations: Vec<(usize, E)>,
+    set: UncertaintySet,
+}
+
+impl RowUncertainty {
+    pub fn new_box(deviations: Vec<(usize, E)>) -> Self {
+        Self {
+            deviations,
+            set: UncertaintySet::Box,
+        }
+    }
+
+    pub fn new_budget(deviations: Vec<(usize, E)>, gamma: E) -> Self {
+        Self {
+            deviations,
+            set: UncertaintySet::Budget(gamma),
+        }
+    }
+
+    pub fn get_deviations(&self) -> &[(usize, E)] {
+        &self.deviations
+    }
+
+    pub fn get_set(&self) -> UncertaintySet {
+        self.set
+    }
+
+    /// Largest increase of `a^T x` over the uncertainty set.
+    pub fn get_protection(&self, x: &Col<E>) -> E {
+        let mut terms = self
+            .deviations
+            .iter()
+            .map(|&(j, d)| d * x[j].abs())
+            .collect::<Vec<_>>();
+        match self.set {
+            UncertaintySet::Box => terms.iter().sum(),
+            UncertaintySet::Budget(gamma) => {
+                terms.sort_by(|a, b| b.total_cmp(a));
+                let full = (gamma.floor().max(E::from(0.)) as usize).min(terms.len());
+                let fraction = terms
+                    .get(full)
+                    .map_or(E::from(0.), |&t| (gamma - full as E) * t);
+                terms[..full].iter().sum::<E>() + fraction
+            }
+        }
+    }
+}
+
+/// Builds the robust counterpart of a linear program.
+pub struct RobustCounterpartBuilder<'a> {
+    lp: &'a LinearProgram,
+    rows: Vec<(usize, RowUncertainty)>,
+}
+
+/// A robust counterpart, whose first columns are those of the nominal program.
+#[derive(Debug, Clone)]
+pub struct RobustCounterpart {
+    lp: LinearProgram,
+    n_vars: usize,
+}
+
+impl RobustCounterpart {
+    pub fn get_program(&self) -> &LinearProgram {
+        &self.lp
+    }
+
+    pub fn into_program(self) -> LinearProgram {
+        self.lp
+    }
+
+    /// Variables of the nominal program within a solution of the counterpart.
+    pub fn get_original(&self, x: &Col<E>) -> Col<E> {
+        x.subrows(0, self.n_vars).to_owned()
+    }
+}
+
+/// Columns and rows appended to the nominal program.
+struct Extension {
+    c: Vec<E>,
+    l: Vec<E>,
+    u: Vec<E>,
+    b: Vec<E>,
+    triplets: Vec<Triplet<I, I, E>>,
+    n_vars: usize,
+    n_cons: usize,
+}
+
+impl Extension {
+    fn add_column(&mut self, lower: E, upper: E) -> usize {
+        self.c.push(E::from(0.));
+        self.l.push(lower);
+        self.u.push(upper);
+        self.n_vars + self.c.len() - 1
+    }
+
+    /// Adds the row `terms <= rhs` with its slack.
+    fn add_inequality(&mut self, terms: &[(usize, E)], rhs: E) {
+        let row = self.n_cons + self.b.len();
+        self.b.push(rhs);
+        for &(j, a) in terms {
+            self.triplets
+                .push(Triplet::new(to_index(row), to_index(j), a));
+        }
+        let slack = self.add_column(E::from(0.), E::INFINITY);
+        self.triplets
+            .push(Triplet::new(to_index(row), to_index(slack), E::from(1.)));
+    }
+}
+
+impl<'a> RobustCounterpartBuilder<'a> {
+    pub fn new(lp: &'a LinearProgram) -> Self {
+        Self {
+            lp,
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn with_row_uncertainty(mut self, row: usize, uncertainty: RowUncertainty) -> Self {
+        self.rows.push((row, uncertainty));
+        self
+    }
+
+    #[allow(non_snake_case)]
+    pub fn build(self) -> Result<RobustCounterpart, Problem> {
+        let lp = self.lp;
+        let (n, m) = lp.get_dims();
+        let (l, u) = (lp.get_lower_bounds(), lp.get_upper_bounds());
+
+        let A = lp.get_constraint_matrix();
+
+        let mut triplets = Vec::new();
+        let mut slack_signs = vec![None; m];
+        for j in 0..n {
+            let is_slack = A.row_idx_of_col_raw(j).len() == 1
+                && l[j] == E::from(0.)
+                && u[j] == E::INFINITY
+                && lp.get_objective()[j] == E::from(0.);
+            for (i, &a) in A.row_idx_of_col(j).zip(A.val_of_col(j)) {
+                triplets.push(Triplet::new(to_index(i), to_index(j), a));
+                if is_slack && slack_signs[i].is_none() {
+                    slack_signs[i] = Some((j, a.signum()));
+                }
+            }
+        }
+
+        let mut extension = Extension {
+            c: Vec::new(),
+            l: Vec::new(),
+            u: Vec::new(),
+            b: Vec::new(),
+            triplets: Vec::new(),
+            n_vars: n,
+            n_cons: m,
+        };
+        // Auxiliary t_j >= |x_j| for columns of either sign, shared by all rows
+        let mut magnitudes = vec![None; n];
+
+        for (row, uncertainty) in self.rows.iter() {
+            let row = *row;
+            if row >= m {
+                return Err(format!("Row {row} does not exist").gloss());
+            }
+            let Some((slack, sign)) = slack_signs[row] else {
+                return Err(format!("Row {row} is an equality and cannot be uncertain").gloss());
+            };
+
+            // Each deviation term d_j |x_j| as a linear combination of columns
+            let mut deviation_terms = Vec::new();
+            for &(j, d) in uncertainty.deviations.iter() {
+                if j >= n || j == slack || d < E::from(0.) {
+                    return Err(format!("Invalid deviation {d} of column {j} in row {row}").gloss());
+                }
+                if d == E::from(0.) {
+                    continue;
+                }
+                let term = if l[j] >= E::from(0.) {
+                    (j, d)
+                } else if u[j] <= E::from(0.) {
+                    (j, -d)
+                } else {
+                    let t = *magnitudes[j].get_or_insert_with(|| {
+                        let t = extension.add_column(E::from(0.), E::INFINITY);
+                        extension
+                            .add_inequality(&[(j, E::from(1.)), (t, E::from(-1.))], E::from(0.));
+                        extension
+                            .add_inequality(&[(j, E::from(-1.)), (t, E::from(-1.))], E::from(0.));
+                        t
+                    });
+                    (t, d)
+                };
+                deviation_terms.push(term);
+            }
+
+            // The protection enters a `>=` row with the opposite sign
+            match uncertainty.set {
+                UncertaintySet::Box => {
+                    for (j, a) in deviation_terms {
+                        triplets.push(Triplet::new(to_index(row), to_index(j), sign * a));
+                    }
+                }
+                UncertaintySet::Budget(gamma) => {
+                    if gamma < E::from(0.) {
+                        return Err(format!("Negative budget {gamma} in row {row}").gloss());
+                    }
+                    let z = extension.add_column(E::from(0.), E::INFINITY);
+                    triplets.push(Triplet::new(to_index(row), to_index(z), sign * gamma));
+                    for (j, a) in deviation_terms {
+                        let p = extension.add_column(E::from(0.), E::INFINITY);
+                        triplets.push(Triplet::new(to_index(row), to_index(p), sign));
+                        // d_j |x_j| - z - p_j <= 0
+                        extension.add_inequality(
+                            &[(j, a), (z, E::from(-1.)), (p, E::from(-1.))],
+                            E::from(0.),
+                        );
+                    }
+                }
+            }
+        }
+
+        let (n_added, m_added) = (extension.c.len(), extension.b.len());
+        let mut c = Col::zeros(n + n_added);
+        let mut b = Col::zeros(m + m_added);
+        let mut l_robust = Col::zeros(n + n_added);
+        let mut u_robust = Col::zeros(n + n_added);
+        c.subrows_mut(0, n).copy_from(lp.get_objective());
+        b.subrows_mut(0, m).copy_from(lp.get_rhs());
+        l_robust.subrows_mut(0, n).copy_from(l);
+        u_robust.subrows_mut(0, n).copy_from(u);
+        for k in 0..n_added {
+            c[n + k] = extension.c[k];
+            l_robust[n + k] = extension.l[k];
+            u_robust[n + k] = extension.u[k];
+        }
+        for k in 0..m_added {
+            b[m + k] = extension.b[k];
+        }
+        triplets.extend(extension.triplets);
+
+        let A = SparseColMat::try_new_from_triplets(m + m_added, n + n_added, &triplets)
+            .map_err(|e| format!("Failed to assemble robust counterpart: {e:?}").gloss())?;
+        Ok(RobustCounterpart {
+            lp: LinearProgram::new(c, A, b, l_robust, u_robust),
+            n_vars: n,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::col;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::lp::test::solve_silent;
+
+    /// `max x_0 + x_1` subject to `x_0 + x_1 <= 4` and `x_0 - x_1 <= 1`,
+    /// with `x_1 <= 3` and `x_0` free within `[-5, 5]`.
+    fn build_lp() -> LinearProgram {
+        let a = SparseColMat::try_new_from_triplets(
+            2,
+            4,
+            &[
+                Triplet::new(0, 0, 1.),
+                Triplet::new(0, 1, 1.),
+                Triplet::new(0, 2, 1.),
+                Triplet::new(1, 0, 1.),
+                Triplet::new(1, 1, -1.),
+                Triplet::new(1, 3, 1.),
+            ],
+        )
+        .unwrap();
+        LinearProgram::new(
+            col![-1., -1., 0., 0.],
+            a,
+            col![4., 1.],
+            col![-5., 0., 0., 0.],
+            col![5., 3., E::INFINITY, E::INFINITY],
+        )
+    }
+
+    #[rstest]
+    #[case(UncertaintySet::Box, 1.5, 0.75)]
+    #[case(UncertaintySet::Budget(1.), 1., 0.5)]
+    #[case(UncertaintySet::Budget(1.5), 1.25, 0.625)]
+    #[case(UncertaintySet::Budget(0.), 0., 0.)]
+    fn test_protection(#[case] set: UncertaintySet, #[case] expected: E, #[case] at_x: E) {
+        let deviations = vec![(0, 0.5), (1, 0.25)];
+        let uncertainty = RowUncertainty { deviations, set };
+        assert!((uncertainty.get_protection(&col![-2., 2.]) - expected).abs() < 1e-12);
+        assert!((uncertainty.get_protection(&col![1., 1.]) - at_x).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_box() {
+        let lp = build_lp();
+        let robust = RobustCounterpartBuilder::new(&lp)
+            .with_row_uncertainty(0, RowUncertainty::new_box(vec![(0, 0.5), (1, 0.5)]))
+            .build()
+            .unwrap();
+
+        // x_0 of either sign needs t_0 >= |x_0| and two rows
+        assert_eq!(robust.get_program().get_dims(), (7, 4));
+
+        let x = robust.get_original(&solve_silent(robust.get_program()));
+        let nominal = solve_silent(&lp);
+        assert!((nominal[0] + nominal[1] - 4.).abs() < 1e-6);
+
+        // The robust row x_0 + x_1 + 0.5 (|x_0| + x_1) <= 4 is binding at the optimum
+        let uncertainty = RowUncertainty::new_box(vec![(0, 0.5), (1, 0.5)]);
+        assert!((x[0] + x[1] + uncertainty.get_protection(&x) - 4.).abs() < 1e-6);
+        assert!((x[0] + x[1] - 8. / 3.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_budget() {
+        let lp = build_lp();
+        let uncertainty = RowUncertainty::new_budget(vec![(0, 1.), (1, 0.5)], 1.);
+        let robust = RobustCounterpartBuilder::new(&lp)
+            .with_row_uncertainty(0, uncertainty.clone())
+            .build()
+            .unwrap();
+        let x = robust.get_original(&solve_silent(robust.get_program()));
+
+        // Only the larger deviation counts: x_0 + x_1 + max(|x_0|, 0.5 x_1) <= 4
+        assert!(x[0] + x[1] + uncertainty.get_protection(&x) <= 4. + 1e-6);
+        assert!((x[0] + x[1] - 3.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_greater_equal_row() {
+        // x_1 - s = 1, i.e. x_1 >= 1, with the coefficient of x_1 in [0.5, 1.5]
+        let a = SparseColMat::try_new_from_triplets(
+            1,
+            2,
+            &[Triplet::new(0, 0, 1.), Triplet::new(0, 1, -1.)],
+        )
+        .unwrap();
+        let lp = LinearProgram::new(
+            col![1., 0.],
+            a,
+            col![1.],
+            col![0., 0.],
+            col![10., E::INFINITY],
+        );
+        let robust = RobustCounterpartBuilder::new(&lp)
+            .with_row_uncertainty(0, RowUncertainty::new_box(vec![(0, 0.5)]))
+            .build()
+            .unwrap();
+
+        let row = robust.get_program().get_constraint_matrix().to_dense();
+        assert_eq!(row[(0, 0)], 0.5);
+        let x = robust.get_original(&solve_silent(robust.get_program()));
+        assert!((x[0] - 2.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let a = SparseColMat::try_new_from_triplets(1, 1, &[Triplet::new(0, 0, 1.)]).unwrap();
+        let lp = LinearProgram::new(col![1.], a, col![1.], col![-1.], col![1.]);
+        let builder = RobustCounterpartBuilder::new(&lp)
+            .with_row_uncertainty(0, RowUncertainty::new_box(vec![(0, 0.5)]));
+        assert!(builder.build().is_err());
+
+        let lp = build_lp();
+        let builder = RobustCounterpartBuilder::new(&lp)
+            .with_row_uncertainty(0, RowUncertainty::new_box(vec![(2, 0.5)]));
+        assert!(builder.build().is_err());
+    }
+}
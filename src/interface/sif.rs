@@ -1,37 +1,470 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read},
+    path::Path,
+};
+
 use faer::{
     Col,
-    sparse::{SparseColMat, Triplet},
+    sparse::{SparseColMat, SymbolicSparseColMat, Triplet},
+    traits::IndexCore,
+};
+use problemo::{Problem, common::IntoCommonProblem};
+use rayon::iter::{Either, IntoParallelRefIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
+use sif_rs::{
+    SIF,
+    types::{BoundType, RowType},
 };
-use problemo::Problem;
-use sif_rs::SIF;
 
-use crate::{E, I, lp::LinearProgram, qp::QuadraticProgram};
+use crate::{
+    DEFAULT_INFINITY_THRESHOLD, E, I, SolverState, apply_infinity_threshold, lp::LinearProgram,
+    qp::QuadraticProgram, to_index,
+};
 
+/// Conversion of parsed SIF models.
+///
+/// The maps of [`SIF`] keep neither the `OBJSENSE` nor the `RANGES` section, so
+/// models converted through this trait are always minimized and their rows
+/// are not ranged. [`ReadSIF`] honors both sections. Nor do they keep the order
+/// of the rows, which identifies the objective among several `N` rows, so such
+/// models are rejected with an error.
+///
+/// Bounds beyond [`DEFAULT_INFINITY_THRESHOLD`] in magnitude, which SIF and MPS
+/// files commonly use for a missing bound, are converted to infinities.
 pub trait TryFromSIF {
     type Output;
-    fn try_from_sif(sif: &SIF) -> Result<Self::Output, Problem>;
+
+    fn try_from_sif(sif: &SIF) -> Result<Self::Output, Problem> {
+        Ok(Self::try_from_sif_with_transformation(sif)?.0)
+    }
+
+    /// Converts the model and returns the record of the conversion, which maps
+    /// solutions back to the rows and columns of the model.
+    fn try_from_sif_with_transformation(
+        sif: &SIF,
+    ) -> Result<(Self::Output, SifTransformation), Problem> {
+        Self::try_from_sif_with_threshold(sif, DEFAULT_INFINITY_THRESHOLD)
+    }
+
+    /// Converts the model with the bounds beyond `infinity_threshold` in
+    /// magnitude as infinities. A threshold above the default only applies to
+    /// programs that do not treat such bounds as infinite on construction,
+    /// unlike [`LinearProgram::new`].
+    fn try_from_sif_with_threshold(
+        sif: &SIF,
+        infinity_threshold: E,
+    ) -> Result<(Self::Output, SifTransformation), Problem>;
 }
 
 impl TryFromSIF for LinearProgram {
     type Output = Self;
 
-    fn try_from_sif(sif: &SIF) -> Result<Self::Output, Problem> {
-        let data = parse_sif(sif)?;
-        Ok(Self::new(data.c, data.A, data.b, data.l, data.u))
+    fn try_from_sif_with_threshold(
+        sif: &SIF,
+        infinity_threshold: E,
+    ) -> Result<(Self, SifTransformation), Problem> {
+        let data = parse_sif(sif, infinity_threshold)?;
+        Ok((
+            Self::new(data.c, data.A, data.b, data.l, data.u),
+            data.transformation,
+        ))
+    }
+}
+
+/// Streaming conversion of SIF files.
+///
+/// Unlike [`TryFromSIF`], the model is converted while the input is read, so the
+/// file contents and the intermediate [`SIF`] maps are never held in memory.
+/// Names are interned once and the matrices are built from index triplets.
+///
+/// The variables and constraints are ordered by name, so the result matches
+/// [`TryFromSIF`]. Bound records are applied in the order they appear, so a
+/// variable may receive both a lower and an upper bound. Fixed variables (`FX`)
+/// are removed from the program, see [`SifTransformation`]. The bounds of
+/// integer and semi-continuous variables (`BV`, `LI`, `UI` and `SC`) and
+/// integer markers in the `COLUMNS` section are rejected with an error.
+///
+/// Maximization problems (`OBJSENSE MAX`) are converted into minimization
+/// problems by negating the objective, and `RANGES` turn rows into ranged rows:
+///
+/// | row | range `r` | row bounds                  |
+/// |-----|-----------|-----------------------------|
+/// | `E` | `r > 0`   | `rhs <= a^T x <= rhs + r`   |
+/// | `E` | `r < 0`   | `rhs + r <= a^T x <= rhs`   |
+/// | `L` | any       | `rhs - |r| <= a^T x <= rhs` |
+/// | `G` | any       | `rhs <= a^T x <= rhs + |r|` |
+///
+/// The first `N` row is the objective. Any later `N` row is a free row, which
+/// keeps its activity `a^T x` in the solution but does not constrain it; its
+/// range is ignored.
+///
+/// As for [`TryFromSIF`], bounds beyond [`DEFAULT_INFINITY_THRESHOLD`] in
+/// magnitude are converted to infinities.
+pub trait ReadSIF: Sized {
+    fn read_sif<R: Read>(reader: R) -> Result<Self, Problem> {
+        Ok(Self::read_sif_with_transformation(reader)?.0)
+    }
+
+    /// Converts the model and returns the record of the conversion, which maps
+    /// solutions back to the rows and columns of the model.
+    fn read_sif_with_transformation<R: Read>(
+        reader: R,
+    ) -> Result<(Self, SifTransformation), Problem> {
+        Self::read_sif_with_threshold(reader, DEFAULT_INFINITY_THRESHOLD)
+    }
+
+    /// Converts the model with the bounds beyond `infinity_threshold` in
+    /// magnitude as infinities, see [`TryFromSIF::try_from_sif_with_threshold`].
+    fn read_sif_with_threshold<R: Read>(
+        reader: R,
+        infinity_threshold: E,
+    ) -> Result<(Self, SifTransformation), Problem>;
+
+    /// Reads the model from the file at `path`, decompressing it if it is
+    /// gzipped, see [`open_model_file`].
+    fn read_sif_file<P: AsRef<Path>>(path: P) -> Result<Self, Problem> {
+        Self::read_sif(open_model_file(path)?)
+    }
+}
+
+/// Opens the SIF or MPS file at `path` for reading, decompressing it on the
+/// fly if it is gzipped, e.g. `afiro.mps.gz`. Compression is detected from the
+/// contents rather than the extension. Files compressed with bzip2 are
+/// rejected.
+pub fn open_model_file<P: AsRef<Path>>(path: P) -> Result<Box<dyn Read>, Problem> {
+    let path = path.as_ref();
+    let mut reader = BufReader::new(std::fs::File::open(path)?);
+    let magic = reader.fill_buf()?;
+    if magic.starts_with(&[0x1f, 0x8b]) {
+        Ok(Box::new(flate2::bufread::GzDecoder::new(reader)))
+    } else if magic.starts_with(b"BZh") {
+        Err(format!(
+            "Model file '{}' is compressed with bzip2, which is not supported; recompress it with gzip",
+            path.display()
+        )
+        .gloss())
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+impl ReadSIF for LinearProgram {
+    fn read_sif_with_threshold<R: Read>(
+        reader: R,
+        infinity_threshold: E,
+    ) -> Result<(Self, SifTransformation), Problem> {
+        let data = stream_sif(reader, infinity_threshold)?;
+        Ok((
+            Self::new(data.c, data.A, data.b, data.l, data.u),
+            data.transformation,
+        ))
+    }
+}
+
+impl ReadSIF for QuadraticProgram {
+    fn read_sif_with_threshold<R: Read>(
+        reader: R,
+        infinity_threshold: E,
+    ) -> Result<(Self, SifTransformation), Problem> {
+        let data = stream_sif(reader, infinity_threshold)?;
+
+        #[allow(non_snake_case)]
+        let Q = data.Q.unwrap_or(
+            SparseColMat::try_new_from_triplets(data.c.nrows(), data.c.nrows(), &[]).unwrap(),
+        );
+        Ok((
+            Self::new(Q, data.c, data.A, data.b, data.l, data.u),
+            data.transformation,
+        ))
     }
 }
 
 impl TryFromSIF for QuadraticProgram {
     type Output = Self;
 
-    fn try_from_sif(sif: &SIF) -> Result<Self::Output, Problem> {
-        let data = parse_sif(sif)?;
+    fn try_from_sif_with_threshold(
+        sif: &SIF,
+        infinity_threshold: E,
+    ) -> Result<(Self, SifTransformation), Problem> {
+        let data = parse_sif(sif, infinity_threshold)?;
 
         #[allow(non_snake_case)]
         let Q = data.Q.unwrap_or(
             SparseColMat::try_new_from_triplets(data.c.nrows(), data.c.nrows(), &[]).unwrap(),
         ); // Return an error if Q is not provided, since it's required for a QP
-        Ok(Self::new(Q, data.c, data.A, data.b, data.l, data.u))
+        Ok((
+            Self::new(Q, data.c, data.A, data.b, data.l, data.u),
+            data.transformation,
+        ))
+    }
+}
+
+/// Direction of the objective of a model.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ObjectiveSense {
+    #[default]
+    Minimize,
+    Maximize,
+}
+
+impl ObjectiveSense {
+    /// Sign of the objective of the model relative to the minimized program.
+    fn sign(&self) -> E {
+        match self {
+            ObjectiveSense::Minimize => E::from(1.),
+            ObjectiveSense::Maximize => E::from(-1.),
+        }
+    }
+}
+
+/// Slack column `s` of the program that turns a row `a^T x` of the model into
+/// the equality `a^T x + coefficient * s = rhs`, with `lower <= s <= upper`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SlackColumn {
+    row: usize,
+    coefficient: E,
+    lower: E,
+    upper: E,
+}
+
+impl SlackColumn {
+    /// Slack of the constraint row `row` with the given type and range, or
+    /// `None` if the row is an equality. A zero range makes an inequality an
+    /// equality, which avoids slacks with equal bounds. The slack of a free
+    /// (`N`) row is free and ignores the range.
+    fn new(row: usize, row_type: RowType, range: Option<E>) -> Option<Self> {
+        if row_type == RowType::N {
+            return Some(Self {
+                row,
+                coefficient: E::from(1.),
+                lower: -E::INFINITY,
+                upper: E::INFINITY,
+            });
+        }
+        let coefficient = match (row_type, range) {
+            (_, Some(r)) if r == E::from(0.) => return None,
+            (RowType::L, _) => E::from(1.),
+            (RowType::G, _) => E::from(-1.),
+            (RowType::E, Some(r)) if r < E::from(0.) => E::from(1.),
+            (RowType::E, Some(_)) => E::from(-1.),
+            _ => return None,
+        };
+        Some(Self {
+            row,
+            coefficient,
+            lower: E::from(0.),
+            upper: range.map(E::abs).unwrap_or(E::INFINITY),
+        })
+    }
+
+    pub fn get_row(&self) -> usize {
+        self.row
+    }
+
+    pub fn get_coefficient(&self) -> E {
+        self.coefficient
+    }
+
+    pub fn get_lower_bound(&self) -> E {
+        self.lower
+    }
+
+    pub fn get_upper_bound(&self) -> E {
+        self.upper
+    }
+}
+
+/// Column of the model with equal finite bounds, such as an `FX` variable,
+/// which the conversion substitutes into the program.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FixedColumn {
+    name: String,
+    value: E,
+    /// Objective coefficient in the program, including the coupling with the
+    /// other fixed columns through the Hessian.
+    cost: E,
+    /// Entries of the column in the rows of the program.
+    entries: Vec<(usize, E)>,
+    /// Coupling with the columns of the program through the Hessian.
+    hessian: Vec<(usize, E)>,
+}
+
+impl FixedColumn {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_value(&self) -> E {
+        self.value
+    }
+
+    /// Rows of the program and coefficients of the column in them.
+    pub fn get_entries(&self) -> &[(usize, E)] {
+        &self.entries
+    }
+}
+
+/// Record of the conversion of a SIF model into a program in standard form.
+///
+/// The columns of the program are the variables of the model with distinct
+/// bounds, ordered by name, followed by one [`SlackColumn`] per `L`, `G`,
+/// ranged `E` or free row. The slack `s_i >= 0` of row `i` enters it with
+/// coefficient `1` for `L` rows and `-1` for `G` rows, which turns the row into
+/// an equality; the range of a row bounds its slack from above. The slack of a
+/// free row, an `N` row other than the objective, is free, so that its
+/// multiplier vanishes. The rows of the program are the constraint and free
+/// rows of the model, ordered by name.
+///
+/// A variable with equal bounds would leave the interior point solvers no
+/// interior, so it is recorded as a [`FixedColumn`] instead: its column times
+/// its value moves into the right-hand side, and its objective terms into the
+/// objective and its offset.
+///
+/// The program minimizes `sign * (f(x) - offset)` for the objective `f` of the
+/// model, where `sign` is `-1` for maximization problems and `offset` is the
+/// constant of the objective (the negated right-hand side of the objective
+/// row).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SifTransformation {
+    col_names: Vec<String>,
+    row_names: Vec<String>,
+    /// Slack columns, in column order.
+    slacks: Vec<SlackColumn>,
+    fixed: Vec<FixedColumn>,
+    sense: ObjectiveSense,
+    objective_offset: E,
+}
+
+impl SifTransformation {
+    /// Names of the variables of the model that are columns of the program, in
+    /// column order.
+    pub fn get_col_names(&self) -> &[String] {
+        &self.col_names
+    }
+
+    /// Names of the constraint rows of the model, in row order.
+    pub fn get_row_names(&self) -> &[String] {
+        &self.row_names
+    }
+
+    /// Slack columns, which follow the variables of the model in column order.
+    pub fn get_slacks(&self) -> &[SlackColumn] {
+        &self.slacks
+    }
+
+    /// Variables of the model removed from the program, ordered by name.
+    pub fn get_fixed_columns(&self) -> &[FixedColumn] {
+        &self.fixed
+    }
+
+    pub fn get_sense(&self) -> ObjectiveSense {
+        self.sense
+    }
+
+    /// Constant term of the objective of the model.
+    pub fn get_objective_offset(&self) -> E {
+        self.objective_offset
+    }
+
+    /// Objective value of the model for the objective value `value` of the
+    /// converted program.
+    pub fn objective_value(&self, value: E) -> E {
+        self.sense.sign() * value + self.objective_offset
+    }
+
+    /// Maps an iterate of the converted program with constraint matrix `a` to
+    /// the variables and rows of the model.
+    ///
+    /// The slack of a row only turns it into an equality, so the multiplier
+    /// of the equality is the dual value of the row and the bound multipliers
+    /// of the slack carry no further information. The duals of maximization
+    /// problems are negated, so that they refer to the objective of the model.
+    ///
+    /// Fixed columns regain their values and their share of the activities;
+    /// their reduced costs follow from the row duals.
+    pub fn recover(&self, a: &SparseColMat<I, E>, state: &SolverState) -> Solution {
+        let n = self.col_names.len();
+        let mut activities = a.as_ref() * &state.x;
+        for (k, slack) in self.slacks.iter().enumerate() {
+            activities[slack.row] -= slack.coefficient * state.x[n + k];
+        }
+        for fixed in &self.fixed {
+            for &(i, val) in &fixed.entries {
+                activities[i] += val * fixed.value;
+            }
+        }
+
+        let sign = self.sense.sign();
+        let fixed_reduced_costs = Col::from_fn(self.fixed.len(), |k| {
+            let fixed = &self.fixed[k];
+            let gradient = fixed.cost
+                + fixed
+                    .hessian
+                    .iter()
+                    .map(|&(j, val)| val * state.x[j])
+                    .sum::<E>();
+            let dual = fixed
+                .entries
+                .iter()
+                .map(|&(i, val)| val * state.y[i])
+                .sum::<E>();
+            sign * (gradient - dual)
+        });
+        Solution {
+            x: state.x.subrows(0, n).to_owned(),
+            activities,
+            row_duals: sign * &state.y,
+            reduced_costs: sign * (state.z_l.subrows(0, n) + state.z_u.subrows(0, n)),
+            fixed_x: Col::from_fn(self.fixed.len(), |k| self.fixed[k].value),
+            fixed_reduced_costs,
+        }
+    }
+}
+
+/// Solution of a SIF model, recovered from the converted program by
+/// [`SifTransformation::recover`].
+///
+/// Dual values follow the sign convention of the solvers: at an optimum of a
+/// minimization, `c = A^T y + d` for the row duals `y` and reduced costs `d`,
+/// `y_i <= 0` for `L` rows, `y_i >= 0` for `G` rows, and `d_j` is nonnegative
+/// at a lower bound and nonpositive at an upper bound.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution {
+    x: Col<E>,
+    activities: Col<E>,
+    row_duals: Col<E>,
+    reduced_costs: Col<E>,
+    fixed_x: Col<E>,
+    fixed_reduced_costs: Col<E>,
+}
+
+impl Solution {
+    /// Values of the variables of the model that are columns of the program.
+    pub fn get_primal(&self) -> &Col<E> {
+        &self.x
+    }
+
+    /// Values of the constraint rows of the model, without slacks.
+    pub fn get_row_activities(&self) -> &Col<E> {
+        &self.activities
+    }
+
+    pub fn get_row_duals(&self) -> &Col<E> {
+        &self.row_duals
+    }
+
+    pub fn get_reduced_costs(&self) -> &Col<E> {
+        &self.reduced_costs
+    }
+
+    /// Values of the fixed columns, see [`SifTransformation::get_fixed_columns`].
+    pub fn get_fixed_primal(&self) -> &Col<E> {
+        &self.fixed_x
+    }
+
+    pub fn get_fixed_reduced_costs(&self) -> &Col<E> {
+        &self.fixed_reduced_costs
     }
 }
 
@@ -43,155 +476,242 @@ struct SifData {
     l: Col<E>,
     u: Col<E>,
     Q: Option<SparseColMat<I, E>>,
+    transformation: SifTransformation,
 }
 
-fn parse_sif(sif: &SIF) -> Result<SifData, Problem> {
-    // Map variable and constraint names to their respective internal indices
-    // Use BTreeSet/BTreeMap for deterministic ordering of indices
-    let map_var_idx: std::collections::BTreeMap<_, _> = sif
-        .get_cols()
-        .into_iter()
-        .map(|(var_name, _)| var_name.clone())
-        .collect::<std::collections::BTreeSet<_>>()
-        .into_iter()
-        .enumerate()
-        .map(|(i, var_name)| (var_name, i))
-        .collect();
-    let map_con_idx: std::collections::BTreeMap<_, _> = sif
+impl SifData {
+    /// Removes the variables with equal finite bounds from the program and
+    /// records them as [`FixedColumn`]s. The objective `c^T x + x^T Q x / 2`
+    /// keeps the terms of the fixed columns that are linear in the others, and
+    /// its constant moves into the objective offset.
+    fn eliminate_fixed_columns(self) -> Self {
+        let n_var = self.transformation.col_names.len();
+        let is_fixed = |j: usize| j < n_var && self.l[j] == self.u[j] && self.l[j].is_finite();
+        let n = self.c.nrows();
+        if !(0..n).any(is_fixed) {
+            return self;
+        }
+
+        // Position of each column among the kept (left) or fixed (right) columns
+        let (mut n_kept, mut n_fixed) = (0, 0);
+        let position = (0..n)
+            .map(|j| {
+                if is_fixed(j) {
+                    n_fixed += 1;
+                    Either::Right(n_fixed - 1)
+                } else {
+                    n_kept += 1;
+                    Either::Left(n_kept - 1)
+                }
+            })
+            .collect::<Vec<_>>();
+        let value = |j: usize| if is_fixed(j) { self.l[j] } else { E::from(0.) };
+
+        // The gradient of the quadratic term at the fixed values, and its
+        // dependence on the kept columns, through the symmetric part of `Q`
+        let mut gradient = Col::<E>::zeros(n);
+        let mut hessian = vec![Vec::new(); n_fixed];
+        let mut q_triplets = Vec::new();
+        if let Some(q) = &self.Q {
+            for j in 0..n {
+                for (i, &val) in q.row_idx_of_col(j).zip(q.val_of_col(j)) {
+                    let half = E::from(0.5) * val;
+                    for (row, col) in [(i, j), (j, i)] {
+                        gradient[row] += half * value(col);
+                        if let (Either::Right(f), Either::Left(k)) = (position[row], position[col])
+                        {
+                            hessian[f].push((k, half));
+                        }
+                    }
+                    if let (Either::Left(i), Either::Left(j)) = (position[i], position[j]) {
+                        q_triplets.push(Triplet::new(to_index(i), to_index(j), val));
+                    }
+                }
+            }
+        }
+
+        let mut b = self.b;
+        let mut entries = vec![Vec::new(); n_fixed];
+        let mut a_triplets = Vec::new();
+        for (j, &position) in position.iter().enumerate() {
+            for (i, &val) in self.A.row_idx_of_col(j).zip(self.A.val_of_col(j)) {
+                match position {
+                    Either::Left(k) => a_triplets.push(Triplet::new(to_index(i), to_index(k), val)),
+                    Either::Right(f) => {
+                        b[i] -= val * value(j);
+                        entries[f].push((i, val));
+                    }
+                }
+            }
+        }
+
+        let kept = |v: &Col<E>| Col::from_iter((0..n).filter(|&j| !is_fixed(j)).map(|j| v[j]));
+        let c = kept(&(&self.c + &gradient));
+
+        // The constant `c_f^T x_f + x_f^T Q_ff x_f / 2` of the objective, where
+        // the gradient already holds `Q_ff x_f`
+        let mut constant = E::from(0.);
+        let mut fixed = Vec::with_capacity(n_fixed);
+        let mut col_names = Vec::with_capacity(n_kept);
+        let mut columns = entries.into_iter().zip(hessian);
+        for (j, name) in self.transformation.col_names.iter().enumerate() {
+            if !is_fixed(j) {
+                col_names.push(name.clone());
+                continue;
+            }
+            let (entries, hessian) = columns.next().unwrap();
+            constant += (self.c[j] + E::from(0.5) * gradient[j]) * value(j);
+            fixed.push(FixedColumn {
+                name: name.clone(),
+                value: value(j),
+                cost: self.c[j] + gradient[j],
+                entries,
+                hessian,
+            });
+        }
+        let sign = self.transformation.sense.sign();
+
+        Self {
+            c,
+            A: assemble_col_major(b.nrows(), n_kept, &a_triplets),
+            b,
+            l: kept(&self.l),
+            u: kept(&self.u),
+            Q: Some(assemble_col_major(n_kept, n_kept, &q_triplets))
+                .filter(|q| q.compute_nnz() > 0),
+            transformation: SifTransformation {
+                col_names,
+                fixed,
+                objective_offset: self.transformation.objective_offset + sign * constant,
+                ..self.transformation
+            },
+        }
+    }
+}
+
+fn parse_sif(sif: &SIF, infinity_threshold: E) -> Result<SifData, Problem> {
+    if sif
         .get_rows()
-        .into_iter()
-        .filter(|(_, rhs_type)| rhs_type != &&sif_rs::types::RowType::N)
-        .map(|(con_name, _)| con_name.clone())
-        .collect::<std::collections::BTreeSet<_>>()
-        .into_iter()
-        .enumerate()
-        .map(|(i, con_name)| (con_name, i))
-        .collect();
+        .values()
+        .filter(|t| **t == RowType::N)
+        .count()
+        > 1
+    {
+        return Err(
+            "The model has several N rows, whose order is lost in the SIF maps; read it with ReadSIF to keep the later ones as free rows"
+                .gloss(),
+        );
+    }
 
-    let (n_var, n_con) = (map_var_idx.len(), map_con_idx.len());
+    // Map variable and constraint names to their respective internal indices.
+    // The maps of `SIF` iterate in sorted order, which makes the indices
+    // deterministic; objective rows map to `None`.
+    let (map_var_idx, map_con_idx) = rayon::join(
+        || {
+            sif.get_cols()
+                .keys()
+                .enumerate()
+                .map(|(j, var_name)| (var_name.as_str(), j))
+                .collect::<HashMap<_, _>>()
+        },
+        || {
+            let mut n_con = 0;
+            sif.get_rows()
+                .iter()
+                .map(|(con_name, row_type)| {
+                    let i = (*row_type != RowType::N).then(|| {
+                        n_con += 1;
+                        n_con - 1
+                    });
+                    (con_name.as_str(), i)
+                })
+                .collect::<HashMap<_, _>>()
+        },
+    );
 
-    // Get number of slack variables
-    let n_slack = sif
+    let n_var = map_var_idx.len();
+    let n_con = map_con_idx.values().filter(|i| i.is_some()).count();
+
+    // The maps of `SIF` hold no ranges, so every slack is nonnegative
+    let slacks = sif
         .get_rows()
         .iter()
-        .filter(|(_, rhs_type)| {
-            **rhs_type == sif_rs::types::RowType::L || **rhs_type == sif_rs::types::RowType::G
+        .filter_map(|(con_name, row_type)| {
+            SlackColumn::new(map_con_idx[con_name.as_str()]?, *row_type, None)
         })
-        .count();
+        .collect::<Vec<_>>();
+    let n_slack = slacks.len();
+
+    // Split the entries into objective coefficients and constraint triplets
+    let (objective, a_triplets): (Vec<_>, Vec<_>) = sif
+        .get_entries()
+        .par_iter()
+        .filter(|(_, val)| **val != 0.)
+        .partition_map(|((con, var), &val)| {
+            let j = map_var_idx[var.as_str()];
+            match map_con_idx[con.as_str()] {
+                Some(i) => Either::Right(Triplet::new(to_index(i), to_index(j), E::from(val))),
+                None => Either::Left((j, E::from(val))),
+            }
+        });
 
     // Construct the objective function
     let mut c = Col::zeros(n_var + n_slack);
-    sif.get_entries()
-        .iter()
-        .filter(|((con, _var), _)|
-            // Filter out non-objective function coefficients
-            sif.get_rows().get(con) == Some(&&sif_rs::types::RowType::N))
-        .for_each(|((_con, var), &val)| {
-            let j = map_var_idx[var];
-            c[j] = E::from(val);
-        });
+    for (j, val) in objective {
+        c[j] = val;
+    }
 
     // Construct the right-hand side vector
-    let b = sif
-        .get_rhs()
-        .into_iter()
-        .filter(|(con, _val)| sif.get_rows().get(*con) != Some(&&sif_rs::types::RowType::N))
-        .map(|(con, val)| {
-            let i = map_con_idx[con];
-            (i, val)
-        })
-        .fold(Col::zeros(n_con), |mut b, (i, val)| {
+    let mut b = Col::zeros(n_con);
+    for (con, val) in sif.get_rhs() {
+        if let Some(i) = map_con_idx[con.as_str()] {
             b[i] = E::from(*val);
-            b
-        });
-
-    let a_triplets = sif
-        .get_entries()
-        .iter()
-        .filter(|((con, _var), val)| {
-            // Filter out zero coefficients and objective function coefficients
-            if **val == 0. {
-                return false;
-            }
-
-            if sif.get_rows().get(con) == Some(&&sif_rs::types::RowType::N) {
-                return false;
-            }
-
-            true
-        })
-        .map(|(i, &val)| {
-            let (i, j) = (map_con_idx[&i.0], map_var_idx[&i.1]);
-            Triplet::new(I::from(i), I::from(j), E::from(val))
-        })
-        .collect::<Vec<_>>();
+        }
+    }
 
     // Construct bounds
     let mut l = Col::<E>::zeros(n_var + n_slack);
     let mut u = E::INFINITY * Col::<E>::ones(n_var + n_slack);
-    sif.get_bounds()
-        .into_iter()
-        .for_each(|(var_name, (bound_type, val))| {
-            let j = map_var_idx[var_name];
-
-            match bound_type {
-                sif_rs::types::BoundType::Lo => {
-                    l[j] = E::from(*val);
-                }
-                sif_rs::types::BoundType::Up => {
-                    u[j] = E::from(*val);
-                }
-                sif_rs::types::BoundType::Fr => {
-                    l[j] = -E::INFINITY;
-                    u[j] = E::INFINITY;
-                }
-                sif_rs::types::BoundType::Mi => {
-                    l[j] = -E::INFINITY;
-                    u[j] = E::from(0.);
-                }
-                sif_rs::types::BoundType::Pl => {
-                    l[j] = E::from(0.);
-                    u[j] = E::INFINITY;
-                }
-                sif_rs::types::BoundType::Fx => {
-                    // TODO: cannot currently handle fixed variables properly because we need to ensure the initial iterate is strictly feasible. For now, we just add a small tolerance around the fixed value.
-                    l[j] = E::from(*val - 0.01);
-                    u[j] = E::from(*val + 0.01);
-                }
-                // sif_rs::types::BoundType::Bv => {
-                //     l[j] = E::from(0.);
-                //     u[j] = E::from(1.);
-                // }
-                // sif_rs::types::BoundType::Li => {
-                //     l[j] = E::from(*val);
-                //     u[j] = E::INFINITY;
-                // }
-                // sif_rs::types::BoundType::Ui => {
-                //     l[j] = -E::INFINITY;
-                //     u[j] = E::from(*val);
-                // }
-                // sif_rs::types::BoundType::Sc => {
-                //     // Special case for semi-continuous variables: either 0 or above a threshold
-                //     l[j] = E::from(*val);
-                //     u[j] = E::INFINITY;
-                // }
-                _ => panic!("Unsupported bound type: {:?}", bound_type),
-            }
-        });
+    for (var_name, (bound_type, val)) in sif.get_bounds() {
+        apply_bound(
+            &mut l,
+            &mut u,
+            map_var_idx[var_name.as_str()],
+            *bound_type,
+            *val,
+        );
+    }
+    apply_infinity_threshold(&mut l, &mut u, infinity_threshold);
+    for (k, slack) in slacks.iter().enumerate() {
+        l[n_var + k] = slack.lower;
+        u[n_var + k] = slack.upper;
+    }
 
     // Add slack variable coefficients to the constraint matrix
-    let slack_triplets = map_con_idx
+    let slack_triplets = slacks
         .iter()
-        .map(|(con_name, &i)| (sif.get_rows()[con_name], i))
-        .filter(|(con_type, _)| {
-            *con_type == sif_rs::types::RowType::L || *con_type == sif_rs::types::RowType::G
-        })
         .enumerate()
-        .map(|(i, (con_type, j))| match con_type {
-            sif_rs::types::RowType::L => Triplet::new(I::from(j), I::from(n_var + i), E::from(1.)),
-            sif_rs::types::RowType::G => Triplet::new(I::from(j), I::from(n_var + i), E::from(-1.)),
-            _ => unreachable!(),
-        });
+        .map(|(k, slack)| Triplet::new(to_index(slack.row), to_index(n_var + k), slack.coefficient))
+        .collect::<Vec<_>>();
+
+    let transformation = SifTransformation {
+        col_names: sif.get_cols().keys().cloned().collect(),
+        row_names: sif
+            .get_rows()
+            .iter()
+            .filter(|(_, row_type)| **row_type != RowType::N)
+            .map(|(con_name, _)| con_name.clone())
+            .collect(),
+        slacks,
+        fixed: Vec::new(),
+        sense: ObjectiveSense::Minimize,
+        objective_offset: -sif
+            .get_rhs()
+            .iter()
+            .filter(|(con, _)| map_con_idx[con.as_str()].is_none())
+            .map(|(_, val)| E::from(*val))
+            .sum::<E>(),
+    };
 
     let a_triplets = a_triplets
         .into_iter()
@@ -199,23 +719,327 @@ fn parse_sif(sif: &SIF) -> Result<SifData, Problem> {
         .collect::<Vec<_>>();
 
     #[allow(non_snake_case)]
-    let A =
-        SparseColMat::try_new_from_triplets(n_con, n_var + n_slack, a_triplets.as_slice()).unwrap();
+    let A = assemble_col_major(n_con, n_var + n_slack, &a_triplets);
 
     #[allow(non_snake_case)]
     let Q = {
         let q_triplet = sif
             .get_quadratic()
-            .into_iter()
+            .par_iter()
             .map(|((var1, var2), coeff)| {
-                let j1 = map_var_idx[var1];
-                let j2 = map_var_idx[var2];
-                Triplet::new(I::from(j1), I::from(j2), E::from(*coeff))
+                let j1 = map_var_idx[var1.as_str()];
+                let j2 = map_var_idx[var2.as_str()];
+                Triplet::new(to_index(j1), to_index(j2), E::from(*coeff))
             })
             .collect::<Vec<_>>();
-        SparseColMat::try_new_from_triplets(n_var + n_slack, n_var + n_slack, &q_triplet)
+        assemble_col_major(n_var + n_slack, n_var + n_slack, &q_triplet)
+    };
+
+    Ok(SifData {
+        c,
+        A,
+        b,
+        l,
+        u,
+        Q: if Q.compute_nnz() > 0 { Some(Q) } else { None },
+        transformation,
+    }
+    .eliminate_fixed_columns())
+}
+
+/// Names interned in order of first appearance.
+#[derive(Default)]
+struct Interner {
+    index: HashMap<Box<str>, usize>,
+}
+
+impl Interner {
+    fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    fn get(&self, name: &str) -> Option<usize> {
+        self.index.get(name).copied()
+    }
+
+    fn get_or_insert(&mut self, name: &str) -> usize {
+        if let Some(id) = self.get(name) {
+            return id;
+        }
+        let id = self.index.len();
+        self.index.insert(name.into(), id);
+        id
+    }
+
+    /// Interned names and their ids in lexicographic order.
+    fn sorted(&self) -> Vec<(&str, usize)> {
+        let mut names = self
+            .index
+            .iter()
+            .map(|(name, &id)| (&**name, id))
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SifSection {
+    Preamble,
+    Rows,
+    Columns,
+    Rhs,
+    Bounds,
+    Quadratic,
+    Ranges,
+    ObjSense,
+    Ignored,
+}
+
+fn parse_value(token: Option<&str>, line_number: usize) -> Result<E, Problem> {
+    let token = token.ok_or_else(|| format!("Line {line_number}: missing value").gloss())?;
+    token
+        .parse::<E>()
+        .map_err(|e| format!("Line {line_number}: invalid value '{token}': {e}").gloss())
+}
+
+fn parse_sense(token: &str, line_number: usize) -> Result<ObjectiveSense, Problem> {
+    match token {
+        "MIN" | "MINIMIZE" => Ok(ObjectiveSense::Minimize),
+        "MAX" | "MAXIMIZE" => Ok(ObjectiveSense::Maximize),
+        _ => Err(format!("Line {line_number}: unknown objective sense '{token}'").gloss()),
+    }
+}
+
+/// Converts a SIF model while reading it line by line.
+fn stream_sif<R: Read>(reader: R, infinity_threshold: E) -> Result<SifData, Problem> {
+    let mut reader = BufReader::new(reader);
+    let mut line = String::new();
+    let mut line_number = 0;
+    let mut section = SifSection::Preamble;
+
+    let (mut rows, mut cols) = (Interner::default(), Interner::default());
+    let mut row_types = Vec::new();
+    let mut entries = Vec::new();
+    let mut rhs = Vec::new();
+    let mut ranges = Vec::new();
+    let mut bounds = Vec::new();
+    let mut quadratic = Vec::new();
+    let mut sense = ObjectiveSense::Minimize;
+
+    let col_id = |cols: &Interner, name: &str, line_number: usize| {
+        cols.get(name)
+            .ok_or_else(|| format!("Line {line_number}: unknown column '{name}'").gloss())
+    };
+    let row_id = |rows: &Interner, name: &str, line_number: usize| {
+        rows.get(name)
+            .ok_or_else(|| format!("Line {line_number}: unknown row '{name}'").gloss())
+    };
+
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        line_number += 1;
+
+        if line.trim().is_empty() || line.starts_with('*') {
+            continue;
+        }
+
+        // Section headers start in the first column, records are indented
+        if !line.starts_with(char::is_whitespace) {
+            let mut tokens = line.split_whitespace();
+            let header = tokens.next().unwrap_or_default();
+            section = match header {
+                "NAME" => SifSection::Preamble,
+                "ROWS" | "GROUPS" | "CONSTRAINTS" => SifSection::Rows,
+                "COLUMNS" | "VARIABLES" => SifSection::Columns,
+                "RHS" | "RHS'" | "CONSTANTS" => SifSection::Rhs,
+                "BOUNDS" => SifSection::Bounds,
+                "QUADRATIC" | "HESSIAN" | "QUADS" | "QUADOBJ" | "QSECTION" => SifSection::Quadratic,
+                "RANGES" => SifSection::Ranges,
+                "OBJSENSE" => {
+                    // The sense may follow the header on the same line
+                    if let Some(token) = tokens.next() {
+                        sense = parse_sense(token, line_number)?;
+                    }
+                    SifSection::ObjSense
+                }
+                "ENDATA" => break,
+                _ => SifSection::Ignored,
+            };
+            continue;
+        }
+
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        match section {
+            SifSection::Rows => {
+                let [row_type, name] = tokens[..] else {
+                    return Err(format!("Line {line_number}: invalid row record").gloss());
+                };
+                let row_type = row_type.parse::<RowType>().map_err(|_| {
+                    format!("Line {line_number}: unknown row type '{row_type}'").gloss()
+                })?;
+                if rows.get_or_insert(name) == row_types.len() {
+                    row_types.push(row_type);
+                }
+            }
+            SifSection::Columns => {
+                // Dropping the markers would silently relax integer columns
+                if tokens.contains(&"'MARKER'") {
+                    return Err(format!(
+                        "Line {line_number}: integer columns ('MARKER' with 'INTORG' and 'INTEND') are not supported"
+                    )
+                    .gloss());
+                }
+                let j = cols.get_or_insert(tokens[0]);
+                for pair in tokens[1..].chunks(2) {
+                    let i = row_id(&rows, pair[0], line_number)?;
+                    entries.push((i, j, parse_value(pair.get(1).copied(), line_number)?));
+                }
+            }
+            SifSection::Rhs | SifSection::Ranges => {
+                // The name of the right-hand side or range vector is optional
+                let records = if tokens.len() % 2 == 1 {
+                    &tokens[1..]
+                } else {
+                    &tokens[..]
+                };
+                let values = if section == SifSection::Rhs {
+                    &mut rhs
+                } else {
+                    &mut ranges
+                };
+                for pair in records.chunks(2) {
+                    let i = row_id(&rows, pair[0], line_number)?;
+                    values.push((i, parse_value(pair.get(1).copied(), line_number)?));
+                }
+            }
+            SifSection::ObjSense => {
+                sense = parse_sense(tokens[0], line_number)?;
+            }
+            SifSection::Bounds => {
+                let bound_type = tokens[0].parse::<BoundType>().map_err(|_| match tokens[0] {
+                    // Relaxing these would silently solve a different problem
+                    "BV" | "LI" | "UI" | "SC" => format!(
+                        "Line {line_number}: bound type '{}' of integer or semi-continuous variables is not supported",
+                        tokens[0]
+                    )
+                    .gloss(),
+                    _ => format!("Line {line_number}: unknown bound type '{}'", tokens[0]).gloss(),
+                })?;
+                let has_value = matches!(bound_type, BoundType::Lo | BoundType::Up | BoundType::Fx);
+                // The name of the bound vector is optional
+                let (name, value) = match (has_value, tokens.len()) {
+                    (true, 4) => (tokens[2], tokens.get(3).copied()),
+                    (true, _) => (tokens[1], tokens.get(2).copied()),
+                    (false, 3) => (tokens[2], None),
+                    (false, _) => (tokens[1], None),
+                };
+                let value = if has_value {
+                    parse_value(value, line_number)?
+                } else {
+                    E::from(0.)
+                };
+                bounds.push((col_id(&cols, name, line_number)?, bound_type, value));
+            }
+            SifSection::Quadratic => {
+                let [var1, var2, value] = tokens[..] else {
+                    return Err(format!("Line {line_number}: invalid quadratic record").gloss());
+                };
+                quadratic.push((
+                    col_id(&cols, var1, line_number)?,
+                    col_id(&cols, var2, line_number)?,
+                    parse_value(Some(value), line_number)?,
+                ));
+            }
+            SifSection::Preamble | SifSection::Ignored => {}
+        }
+    }
+
+    // Order variables and constraints by name, as in `parse_sif`
+    let n_var = cols.len();
+    let mut var_idx = vec![0; n_var];
+    let mut col_names = Vec::with_capacity(n_var);
+    for (j, (name, id)) in cols.sorted().into_iter().enumerate() {
+        var_idx[id] = j;
+        col_names.push(name.to_string());
+    }
+
+    let mut row_range = vec![None; rows.len()];
+    for (i, val) in ranges {
+        row_range[i] = Some(val);
     }
-    .unwrap();
+
+    // The first N row is the objective, any other is a free row
+    let objective = row_types.iter().position(|t| *t == RowType::N);
+    let mut con_idx = vec![None; rows.len()];
+    let mut row_names = Vec::new();
+    let mut slacks = Vec::new();
+    let mut n_con = 0;
+    for (name, id) in rows.sorted() {
+        if Some(id) != objective {
+            con_idx[id] = Some(n_con);
+            row_names.push(name.to_string());
+            slacks.extend(SlackColumn::new(n_con, row_types[id], row_range[id]));
+            n_con += 1;
+        }
+    }
+    let n_slack = slacks.len();
+
+    let mut c = Col::zeros(n_var + n_slack);
+    let mut a_triplets = Vec::with_capacity(entries.len() + n_slack);
+    for (i, j, val) in entries {
+        match con_idx[i] {
+            Some(i) => a_triplets.push(Triplet::new(to_index(i), to_index(var_idx[j]), val)),
+            None => c[var_idx[j]] = val,
+        }
+    }
+    for (k, slack) in slacks.iter().enumerate() {
+        a_triplets.push(Triplet::new(
+            to_index(slack.row),
+            to_index(n_var + k),
+            slack.coefficient,
+        ));
+    }
+
+    // The right-hand side of an objective row is the negated objective constant
+    let mut b = Col::zeros(n_con);
+    let mut objective_offset = E::from(0.);
+    for (i, val) in rhs {
+        match con_idx[i] {
+            Some(i) => b[i] = val,
+            None => objective_offset -= val,
+        }
+    }
+
+    let mut l = Col::<E>::zeros(n_var + n_slack);
+    let mut u = E::INFINITY * Col::<E>::ones(n_var + n_slack);
+    for (j, bound_type, val) in bounds {
+        apply_bound(&mut l, &mut u, var_idx[j], bound_type, val);
+    }
+    apply_infinity_threshold(&mut l, &mut u, infinity_threshold);
+    for (k, slack) in slacks.iter().enumerate() {
+        l[n_var + k] = slack.lower;
+        u[n_var + k] = slack.upper;
+    }
+
+    let sign = sense.sign();
+    c *= sign;
+    let q_triplets = quadratic
+        .into_iter()
+        .map(|(j1, j2, val)| Triplet::new(to_index(var_idx[j1]), to_index(var_idx[j2]), sign * val))
+        .collect();
+
+    #[allow(non_snake_case)]
+    let A = assemble_col_major(n_con, n_var + n_slack, &sort_and_deduplicate(a_triplets));
+    #[allow(non_snake_case)]
+    let Q = assemble_col_major(
+        n_var + n_slack,
+        n_var + n_slack,
+        &sort_and_deduplicate(q_triplets),
+    );
 
     Ok(SifData {
         c,
@@ -224,5 +1048,420 @@ fn parse_sif(sif: &SIF) -> Result<SifData, Problem> {
         l,
         u,
         Q: if Q.compute_nnz() > 0 { Some(Q) } else { None },
-    })
+        transformation: SifTransformation {
+            col_names,
+            row_names,
+            slacks,
+            fixed: Vec::new(),
+            sense,
+            objective_offset,
+        },
+    }
+    .eliminate_fixed_columns())
+}
+
+/// Sorts triplets by column and row, keeping the last of any repeated entry and
+/// dropping explicit zeros.
+fn sort_and_deduplicate(mut triplets: Vec<Triplet<I, I, E>>) -> Vec<Triplet<I, I, E>> {
+    // The sort is stable, so repeated entries remain in input order
+    triplets.sort_by_key(|t| (t.col, t.row));
+    triplets.dedup_by(|next, prev| {
+        let repeated = next.row == prev.row && next.col == prev.col;
+        if repeated {
+            prev.val = next.val;
+        }
+        repeated
+    });
+    triplets.retain(|t| t.val != E::from(0.));
+    triplets
+}
+
+/// Applies a bound record to variable `j`. Fixed variables get `l = u` and are
+/// later removed by [`SifData::eliminate_fixed_columns`].
+fn apply_bound(l: &mut Col<E>, u: &mut Col<E>, j: usize, bound_type: BoundType, val: f64) {
+    match bound_type {
+        BoundType::Lo => {
+            l[j] = E::from(val);
+        }
+        BoundType::Up => {
+            u[j] = E::from(val);
+        }
+        BoundType::Fr => {
+            l[j] = -E::INFINITY;
+            u[j] = E::INFINITY;
+        }
+        BoundType::Mi => {
+            l[j] = -E::INFINITY;
+            u[j] = E::from(0.);
+        }
+        BoundType::Pl => {
+            l[j] = E::from(0.);
+            u[j] = E::INFINITY;
+        }
+        BoundType::Fx => {
+            l[j] = E::from(val);
+            u[j] = E::from(val);
+        }
+    }
+}
+
+/// Assembles a sparse matrix from triplets with a counting sort over the columns.
+///
+/// The triplets must be free of duplicates and list the rows of each column in
+/// increasing order. Triplets generated from the sorted maps of `SIF` satisfy
+/// this, which avoids the general sort in `SparseColMat::try_new_from_triplets`.
+fn assemble_col_major(
+    nrows: usize,
+    ncols: usize,
+    triplets: &[Triplet<I, I, E>],
+) -> SparseColMat<I, E> {
+    let mut col_ptr = vec![0; ncols + 1];
+    for t in triplets {
+        col_ptr[t.col.zx() + 1] += 1;
+    }
+    for j in 0..ncols {
+        col_ptr[j + 1] += col_ptr[j];
+    }
+
+    let mut next = col_ptr[..ncols].to_vec();
+    let mut row_idx = vec![to_index(0); triplets.len()];
+    let mut values = vec![E::from(0.); triplets.len()];
+    for t in triplets {
+        let k = next[t.col.zx()];
+        row_idx[k] = t.row;
+        values[k] = t.val;
+        next[t.col.zx()] += 1;
+    }
+
+    let col_ptr = col_ptr.into_iter().map(to_index).collect();
+    let symbolic = SymbolicSparseColMat::new_checked(nrows, ncols, col_ptr, None, row_idx);
+    SparseColMat::new(symbolic, values)
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::col;
+
+    use super::*;
+    use crate::{
+        SolverHooks, SolverOptions, Status, callback::NoOpCallback,
+        terminators::ComplementarityTerminator,
+    };
+
+    /// `max x + 2 y + 5` subject to `4 <= x + y <= 6`, `-1 <= x - y <= 0`,
+    /// `1 <= x <= 3`, `2 <= x <= 3` and `0 <= x, y <= 10`.
+    const RANGED: &str = "\
+NAME          RANGED
+OBJSENSE
+    MAX
+ROWS
+ N  obj
+ E  e1
+ L  l1
+ G  g1
+ E  e2
+COLUMNS
+    x         obj       1.0          e1        1.0
+    x         l1        1.0          g1        1.0
+    x         e2        1.0
+    y         obj       2.0          e1        1.0
+    y         e2        -1.0
+RHS
+    rhs       obj       -5.0         e1        4.0
+    rhs       l1        3.0          g1        1.0
+RANGES
+    rng       e1        2.0          l1        1.0
+    rng       g1        -2.0         e2        -1.0
+BOUNDS
+ UP bnd       x         10.0
+ UP bnd       y         10.0
+ENDATA
+";
+
+    #[test]
+    fn test_ranges_and_sense() {
+        let (lp, transformation) =
+            LinearProgram::read_sif_with_transformation(RANGED.as_bytes()).unwrap();
+        assert_eq!(transformation.get_row_names(), &["e1", "e2", "g1", "l1"]);
+        let slacks = transformation
+            .get_slacks()
+            .iter()
+            .map(|s| (s.get_row(), s.get_coefficient(), s.get_upper_bound()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            slacks,
+            [(0, -1., 2.), (1, 1., 1.), (2, -1., 2.), (3, 1., 1.)]
+        );
+        assert_eq!(transformation.get_sense(), ObjectiveSense::Maximize);
+        assert_eq!(transformation.get_objective_offset(), 5.);
+        assert_eq!(lp.get_objective(), &col![-1., -2., 0., 0., 0., 0.]);
+        assert_eq!(lp.get_rhs(), &col![4., 0., 1., 3.]);
+        assert_eq!(lp.get_upper_bounds(), &col![10., 10., 2., 1., 2., 1.]);
+
+        let mut state = SolverState::new_interior(lp.get_lower_bounds(), lp.get_upper_bounds(), 4);
+        let options = SolverOptions::new();
+        let mut hooks = SolverHooks {
+            callback: Box::new(NoOpCallback::new()),
+            terminator: Box::new(ComplementarityTerminator::new(&options)),
+        };
+        let status = lp
+            .solver_builder()
+            .with_solver(crate::lp::LPSolverType::MpcSimplicialCholesky)
+            .build()
+            .unwrap()
+            .solve(&mut state, &mut hooks)
+            .unwrap();
+        assert_eq!(status, Status::Optimal);
+
+        // x + y = 6 and x - y = -1 are binding at x = (2.5, 3.5)
+        let value = transformation.objective_value(lp.get_objective_value(state.get_primal()));
+        assert!((value - 14.5).abs() < 1e-6);
+        let solution = transformation.recover(lp.get_constraint_matrix(), &state);
+        assert!((solution.get_primal() - col![2.5, 3.5]).norm_l2() < 1e-6);
+        assert!((solution.get_row_activities() - col![6., -1., 2.5, 2.5]).norm_l2() < 1e-6);
+        // Both binding rows limit the increase of the objective
+        assert!(solution.get_row_duals()[0] > 1e-3 && solution.get_row_duals()[1] < -1e-3);
+        // and hold their slacks at the upper bound, given by the range
+        for k in [2, 3] {
+            assert!((state.x[k] - lp.get_upper_bounds()[k]).abs() < 1e-6);
+            assert!(state.z_u[k] < -1e-3 && state.z_l[k].abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_free_rows() {
+        // The first N row is the objective even though `aux` sorts before it
+        let model = RANGED
+            .replace(" N  obj\n", " N  obj\n N  aux\n")
+            .replace(
+                "    y         e2        -1.0\n",
+                "    y         e2        -1.0         aux       -3.0\n    x         aux       1.0\n",
+            )
+            .replace("    rhs       l1        3.0", "    rhs       aux       7.0\n    rhs       l1        3.0")
+            .replace("    rng       e1        2.0", "    rng       aux       4.0\n    rng       e1        2.0");
+        let (lp, transformation) =
+            LinearProgram::read_sif_with_transformation(model.as_bytes()).unwrap();
+        assert_eq!(
+            transformation.get_row_names(),
+            &["aux", "e1", "e2", "g1", "l1"]
+        );
+        let free = transformation.get_slacks()[0];
+        assert_eq!((free.get_row(), free.get_coefficient()), (0, 1.));
+        assert_eq!(
+            (free.get_lower_bound(), free.get_upper_bound()),
+            (-E::INFINITY, E::INFINITY)
+        );
+        assert_eq!(transformation.get_objective_offset(), 5.);
+        assert_eq!(lp.get_objective(), &col![-1., -2., 0., 0., 0., 0., 0.]);
+        assert_eq!(lp.get_rhs(), &col![7., 4., 0., 1., 3.]);
+
+        let mut state = SolverState::new_interior(lp.get_lower_bounds(), lp.get_upper_bounds(), 5);
+        let options = SolverOptions::new();
+        let mut hooks = SolverHooks {
+            callback: Box::new(NoOpCallback::new()),
+            terminator: Box::new(ComplementarityTerminator::new(&options)),
+        };
+        let status = lp
+            .solver_builder()
+            .with_solver(crate::lp::LPSolverType::MpcSimplicialCholesky)
+            .build()
+            .unwrap()
+            .solve(&mut state, &mut hooks)
+            .unwrap();
+        assert_eq!(status, Status::Optimal);
+
+        // The free row leaves the solution unchanged and has no dual
+        let value = transformation.objective_value(lp.get_objective_value(state.get_primal()));
+        assert!((value - 14.5).abs() < 1e-6);
+        let solution = transformation.recover(lp.get_constraint_matrix(), &state);
+        assert!((solution.get_primal() - col![2.5, 3.5]).norm_l2() < 1e-6);
+        assert!((solution.get_row_activities()[0] + 8.).abs() < 1e-6);
+        assert!(solution.get_row_duals()[0].abs() < 1e-6);
+        assert_eq!((state.z_l[2], state.z_u[2]), (0., 0.));
+
+        // The SIF maps lose the order of the N rows
+        let model = model.replace("OBJSENSE\n    MAX\n", "").replace(
+            "RANGES\n    rng       aux       4.0\n    rng       e1        2.0          l1        1.0\n    rng       g1        -2.0         e2        -1.0\n",
+            "",
+        );
+        let sif = sif_rs::parse_sif(&model).unwrap();
+        let error = LinearProgram::try_from_sif(&sif).unwrap_err();
+        assert!(error.to_string().contains("ReadSIF"), "{error}");
+    }
+
+    #[test]
+    fn test_zero_range() {
+        let model = RANGED.replace("l1        1.0", "l1        0.0");
+        let (lp, transformation) =
+            LinearProgram::read_sif_with_transformation(model.as_bytes()).unwrap();
+        let rows = transformation
+            .get_slacks()
+            .iter()
+            .map(SlackColumn::get_row)
+            .collect::<Vec<_>>();
+        assert_eq!(rows, [0, 1, 2]);
+        assert_eq!(lp.get_dims(), (5, 4));
+    }
+
+    #[test]
+    fn test_gzipped_file() {
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+        let plain = dir.path().join("ranged.mps");
+        std::fs::write(&plain, RANGED).unwrap();
+        let gzipped = dir.path().join("ranged.mps.gz");
+        let mut encoder = flate2::write::GzEncoder::new(
+            std::fs::File::create(&gzipped).unwrap(),
+            flate2::Compression::default(),
+        );
+        encoder.write_all(RANGED.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+
+        let expected = LinearProgram::read_sif_file(&plain).unwrap();
+        let lp = LinearProgram::read_sif_file(&gzipped).unwrap();
+        assert_eq!(lp.get_objective(), expected.get_objective());
+        assert_eq!(lp.get_rhs(), expected.get_rhs());
+        assert_eq!(lp.get_upper_bounds(), expected.get_upper_bounds());
+
+        let bzipped = dir.path().join("ranged.mps.bz2");
+        std::fs::write(&bzipped, b"BZh91AY&SY").unwrap();
+        assert!(LinearProgram::read_sif_file(&bzipped).is_err());
+    }
+
+    #[test]
+    fn test_invalid_sense() {
+        let model = RANGED.replace("    MAX", "    UP");
+        assert!(LinearProgram::read_sif(model.as_bytes()).is_err());
+        let model = RANGED.replace("OBJSENSE\n    MAX", "OBJSENSE    MIN");
+        let (_, transformation) =
+            LinearProgram::read_sif_with_transformation(model.as_bytes()).unwrap();
+        assert_eq!(transformation.get_sense(), ObjectiveSense::Minimize);
+    }
+
+    #[test]
+    fn test_infinity_threshold() {
+        let model = RANGED
+            .replace("UP bnd       x         10.0", "UP bnd       x         1e30")
+            .replace(
+                "UP bnd       y         10.0",
+                "MI bnd       y\n LO bnd       y         -1e20",
+            );
+        let lp = LinearProgram::read_sif(model.as_bytes()).unwrap();
+        assert_eq!(lp.get_upper_bounds()[0], E::INFINITY);
+        assert_eq!(lp.get_lower_bounds()[1], -E::INFINITY);
+
+        // A lower threshold also applies to the quadratic program
+        let model = RANGED.replace("UP bnd       y         10.0", "UP bnd       y         1e8");
+        let (qp, _) = QuadraticProgram::read_sif_with_threshold(model.as_bytes(), 1e6).unwrap();
+        assert_eq!(qp.get_upper_bounds()[0], 10.);
+        assert_eq!(qp.get_upper_bounds()[1], E::INFINITY);
+        let (qp, _) = QuadraticProgram::read_sif_with_transformation(model.as_bytes()).unwrap();
+        assert_eq!(qp.get_upper_bounds()[1], 1e8);
+
+        // The SIF parser knows neither OBJSENSE nor RANGES
+        let model = model.replace("OBJSENSE\n    MAX\n", "").replace(
+            "RANGES\n    rng       e1        2.0          l1        1.0\n    rng       g1        -2.0         e2        -1.0\n",
+            "",
+        );
+        let sif = sif_rs::parse_sif(&model).unwrap();
+        let lp = LinearProgram::try_from_sif_with_threshold(&sif, 1e6)
+            .unwrap()
+            .0;
+        assert_eq!(lp.get_upper_bounds()[1], E::INFINITY);
+    }
+
+    #[test]
+    fn test_bound_types() {
+        let model = RANGED.replace("UP bnd       y         10.0", "FX bnd       y         2.5");
+        let (lp, transformation) =
+            LinearProgram::read_sif_with_transformation(model.as_bytes()).unwrap();
+        assert_eq!(transformation.get_col_names(), &["x"]);
+        let fixed = &transformation.get_fixed_columns()[0];
+        assert_eq!((fixed.get_name(), fixed.get_value()), ("y", 2.5));
+        assert_eq!(lp.get_dims(), (5, 4));
+
+        for bound in [
+            "BV bnd       y",
+            "LI bnd       y         1.0",
+            "SC bnd       y         5.0",
+        ] {
+            let model = RANGED.replace("UP bnd       y         10.0", bound);
+            let error = LinearProgram::read_sif(model.as_bytes()).unwrap_err();
+            assert!(error.to_string().contains("not supported"), "{error}");
+        }
+
+        // Integer columns are delimited by markers rather than bounds
+        let model = RANGED.replace(
+            "    y         obj       2.0",
+            "    MARKER    'MARKER'     'INTORG'\n    y         obj       2.0",
+        );
+        let error = LinearProgram::read_sif(model.as_bytes()).unwrap_err();
+        assert!(error.to_string().contains("not supported"), "{error}");
+    }
+
+    #[test]
+    fn test_fixed_columns() {
+        // With y = 2.5, the rows leave 2 <= x <= 2.5
+        let model = RANGED.replace("UP bnd       y         10.0", "FX bnd       y         2.5");
+        let (lp, transformation) =
+            LinearProgram::read_sif_with_transformation(model.as_bytes()).unwrap();
+        assert_eq!(lp.get_objective(), &col![-1., 0., 0., 0., 0.]);
+        assert_eq!(lp.get_rhs(), &col![1.5, 2.5, 1., 3.]);
+        assert_eq!(transformation.get_objective_offset(), 10.);
+
+        let options = SolverOptions::new();
+        let mut hooks = SolverHooks {
+            callback: Box::new(NoOpCallback::new()),
+            terminator: Box::new(ComplementarityTerminator::new(&options)),
+        };
+        let mut state = SolverState::new_interior(lp.get_lower_bounds(), lp.get_upper_bounds(), 4);
+        let status = lp
+            .solver_builder()
+            .with_solver(crate::lp::LPSolverType::MpcSimplicialCholesky)
+            .build()
+            .unwrap()
+            .solve(&mut state, &mut hooks)
+            .unwrap();
+        assert_eq!(status, Status::Optimal);
+
+        let value = transformation.objective_value(lp.get_objective_value(state.get_primal()));
+        assert!((value - 12.5).abs() < 1e-6);
+        let solution = transformation.recover(lp.get_constraint_matrix(), &state);
+        assert!((solution.get_primal()[0] - 2.5).abs() < 1e-6);
+        assert_eq!(solution.get_fixed_primal(), &col![2.5]);
+        assert!((solution.get_row_activities() - col![5., 0., 2.5, 2.5]).norm_l2() < 1e-6);
+        // `x - y <= 0` holds back x, whose objective coefficient is its dual
+        assert!((solution.get_row_duals()[1] - 1.).abs() < 1e-6);
+        assert!((solution.get_fixed_reduced_costs()[0] - 3.).abs() < 1e-6);
+
+        // `min x + 2 y + x^2 + x y + y^2 + 5`, whose coupling moves into the
+        // objective of x
+        let model = model.replace("    MAX", "    MIN").replace(
+            "BOUNDS",
+            "QUADOBJ\n    x         x         2.0\n    x         y         1.0\n    y         x         1.0\n    y         y         2.0\nBOUNDS",
+        );
+        let (qp, transformation) =
+            QuadraticProgram::read_sif_with_transformation(model.as_bytes()).unwrap();
+        assert_eq!(qp.get_linear_objective(), &col![3.5, 0., 0., 0., 0.]);
+        assert_eq!(qp.get_quadratic_objective().compute_nnz(), 1);
+        assert_eq!(transformation.get_objective_offset(), 16.25);
+
+        let mut state = SolverState::new_interior(qp.get_lower_bounds(), qp.get_upper_bounds(), 4);
+        let status = qp
+            .solver_builder()
+            .with_solver(crate::qp::QPSolverType::MpcSimplicialCholesky)
+            .build()
+            .unwrap()
+            .solve(&mut state, &mut hooks)
+            .unwrap();
+        assert_eq!(status, Status::Optimal);
+
+        let objective = crate::verify::VerifiableProgram::objective(&qp, state.get_primal());
+        assert!((transformation.objective_value(objective) - 27.25).abs() < 1e-6);
+        let solution = transformation.recover(qp.get_constraint_matrix(), &state);
+        assert!((solution.get_primal()[0] - 2.).abs() < 1e-6);
+        // No row holds y, so its reduced cost is the gradient `2 + x + 2 y`
+        assert!((solution.get_fixed_reduced_costs()[0] - 9.).abs() < 1e-6);
+    }
 }
@@ -0,0 +1,516 @@
+//! Reading of solution files written by other solvers, for cross-checking.
+//!
+//! Three formats are recognized from their content:
+//!
+//! - HiGHS (`writeSolution`): sections `# Primal solution values` and
+//!   `# Dual solution values`, each with `# Columns n` and `# Rows m`
+//!   followed by one `name value` record per line.
+//! - CPLEX: XML with a `header` element holding `objectiveValue` and one
+//!   `variable` (`name`, `value`, `reducedCost`) or `constraint` (`name`,
+//!   `dual`) element per column or row.
+//! - Gurobi: one `name value` record per line, with the objective in the
+//!   comment `# Objective value = ...`.
+//!
+//! [`compare_solution`] evaluates a solution read this way on the program of
+//! a model, converted by [`SifTransformation`], and compares it with a
+//! solution of this crate. Dual values are kept as written by the solver.
+
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use faer::Col;
+use problemo::{Problem, common::IntoCommonProblem};
+use serde::Serialize;
+
+use crate::{
+    E, SolverState,
+    interface::sif::SifTransformation,
+    lp::LinearProgram,
+    verify::{CertificateCheck, check_solution},
+};
+
+/// Solver that wrote a solution file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolutionFormat {
+    Highs,
+    Cplex,
+    Gurobi,
+}
+
+/// Values of the columns and rows of a model, read from a solution file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolutionFile {
+    format: SolutionFormat,
+    status: Option<String>,
+    objective: Option<E>,
+    primal: HashMap<String, E>,
+    reduced_costs: HashMap<String, E>,
+    row_duals: HashMap<String, E>,
+}
+
+impl SolutionFile {
+    fn new(format: SolutionFormat) -> Self {
+        Self {
+            format,
+            status: None,
+            objective: None,
+            primal: HashMap::new(),
+            reduced_costs: HashMap::new(),
+            row_duals: HashMap::new(),
+        }
+    }
+
+    /// Reads a solution file in any of the recognized formats.
+    pub fn read<R: std::io::Read>(reader: R) -> Result<Self, Problem> {
+        let lines = BufReader::new(reader)
+            .lines()
+            .collect::<Result<Vec<_>, _>>()?;
+        let first = lines
+            .iter()
+            .map(|line| line.trim())
+            .find(|line| !line.is_empty());
+        match first {
+            Some(line) if line.starts_with('<') => Self::read_cplex(&lines),
+            Some("Model status") => Self::read_highs(&lines),
+            _ => Self::read_gurobi(&lines),
+        }
+    }
+
+    pub fn read_file<P: AsRef<Path>>(path: P) -> Result<Self, Problem> {
+        Self::read(std::fs::File::open(path)?)
+    }
+
+    pub fn get_format(&self) -> SolutionFormat {
+        self.format
+    }
+
+    /// Status reported by the solver, if the format has one.
+    pub fn get_status(&self) -> Option<&str> {
+        self.status.as_deref()
+    }
+
+    /// Objective value reported by the solver.
+    pub fn get_objective(&self) -> Option<E> {
+        self.objective
+    }
+
+    /// Value of column `name`, `None` if the file does not list it.
+    pub fn get_value(&self, name: &str) -> Option<E> {
+        self.primal.get(name).copied()
+    }
+
+    /// Reduced cost of column `name`, `None` if the file does not list it.
+    pub fn get_reduced_cost(&self, name: &str) -> Option<E> {
+        self.reduced_costs.get(name).copied()
+    }
+
+    /// Dual value of row `name`, `None` if the file does not list it.
+    pub fn get_row_dual(&self, name: &str) -> Option<E> {
+        self.row_duals.get(name).copied()
+    }
+
+    /// Values of the columns `col_names`, in their order. Columns that the
+    /// file does not list, as some solvers omit zeros, are zero.
+    pub fn get_primal(&self, col_names: &[String]) -> Col<E> {
+        Col::from_fn(col_names.len(), |j| {
+            self.get_value(&col_names[j]).unwrap_or(E::from(0.))
+        })
+    }
+
+    fn read_highs(lines: &[String]) -> Result<Self, Problem> {
+        let mut solution = Self::new(SolutionFormat::Highs);
+        let mut dual = false;
+        let mut lines = lines.iter().map(|line| line.trim());
+        while let Some(line) = lines.next() {
+            if line == "Model status" {
+                solution.status = lines.next().map(str::to_string);
+            } else if line.starts_with("# Primal solution values") {
+                dual = false;
+            } else if line.starts_with("# Dual solution values") {
+                dual = true;
+            } else if line.starts_with("# Basis") {
+                break;
+            } else if let Some(value) = line.strip_prefix("Objective ") {
+                solution.objective = Some(parse_value(value)?);
+            } else if let Some(count) = line.strip_prefix("# Columns ") {
+                let values = if dual {
+                    &mut solution.reduced_costs
+                } else {
+                    &mut solution.primal
+                };
+                read_records(&mut lines, count, values)?;
+            } else if let Some(count) = line.strip_prefix("# Rows ") {
+                // The primal row values are activities, which follow from the columns
+                let mut activities = HashMap::new();
+                let values = if dual {
+                    &mut solution.row_duals
+                } else {
+                    &mut activities
+                };
+                read_records(&mut lines, count, values)?;
+            }
+        }
+        Ok(solution)
+    }
+
+    fn read_cplex(lines: &[String]) -> Result<Self, Problem> {
+        let mut solution = Self::new(SolutionFormat::Cplex);
+        let text = lines.join("\n");
+        for element in text.split('<').skip(1) {
+            let (tag, attributes) = element
+                .split_once(char::is_whitespace)
+                .unwrap_or((element, ""));
+            let attributes = xml_attributes(attributes);
+            let attribute = |key: &str| attributes.get(key).map(|value| parse_value(value));
+            match tag {
+                "header" => {
+                    solution.objective = attribute("objectiveValue").transpose()?;
+                    solution.status = attributes.get("solutionStatusString").cloned();
+                }
+                "variable" | "constraint" => {
+                    let name = attributes
+                        .get("name")
+                        .ok_or_else(|| format!("Missing name in CPLEX {tag}").gloss())?
+                        .clone();
+                    if tag == "variable" {
+                        if let Some(value) = attribute("value").transpose()? {
+                            solution.primal.insert(name.clone(), value);
+                        }
+                        if let Some(value) = attribute("reducedCost").transpose()? {
+                            solution.reduced_costs.insert(name, value);
+                        }
+                    } else if let Some(value) = attribute("dual").transpose()? {
+                        solution.row_duals.insert(name, value);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(solution)
+    }
+
+    fn read_gurobi(lines: &[String]) -> Result<Self, Problem> {
+        let mut solution = Self::new(SolutionFormat::Gurobi);
+        for line in lines.iter().map(|line| line.trim()) {
+            if let Some(comment) = line.strip_prefix('#') {
+                if let Some((_, value)) = comment.split_once("Objective value =") {
+                    solution.objective = Some(parse_value(value)?);
+                }
+                continue;
+            }
+            if line.is_empty() {
+                continue;
+            }
+            let (name, value) = parse_record(line)?;
+            solution.primal.insert(name, value);
+        }
+        Ok(solution)
+    }
+}
+
+fn parse_value(value: &str) -> Result<E, Problem> {
+    let value = value.trim();
+    match value.to_lowercase().as_str() {
+        "inf" | "+inf" | "infinity" => Ok(E::INFINITY),
+        "-inf" | "-infinity" => Ok(-E::INFINITY),
+        _ => value
+            .parse()
+            .map_err(|_| format!("Invalid value in solution file: {value}").gloss()),
+    }
+}
+
+/// Splits a `name value` record.
+fn parse_record(line: &str) -> Result<(String, E), Problem> {
+    let fields = line.split_whitespace().collect::<Vec<_>>();
+    match fields.as_slice() {
+        [name, value, ..] => Ok((name.to_string(), parse_value(value)?)),
+        _ => Err(format!("Invalid record in solution file: {line}").gloss()),
+    }
+}
+
+/// Reads the `count` records following a section header into `values`.
+fn read_records<'a>(
+    lines: &mut impl Iterator<Item = &'a str>,
+    count: &str,
+    values: &mut HashMap<String, E>,
+) -> Result<(), Problem> {
+    let count = count
+        .trim()
+        .parse::<usize>()
+        .map_err(|_| format!("Invalid record count in solution file: {count}").gloss())?;
+    for _ in 0..count {
+        let line = lines
+            .next()
+            .ok_or_else(|| "Solution file ends within a section".gloss())?;
+        let (name, value) = parse_record(line)?;
+        values.insert(name, value);
+    }
+    Ok(())
+}
+
+/// Attributes `key="value"` of an XML element.
+fn xml_attributes(element: &str) -> HashMap<String, String> {
+    let mut attributes = HashMap::new();
+    let mut rest = element;
+    while let Some((key, value)) = rest.split_once('=') {
+        let Some(value) = value.trim_start().strip_prefix('"') else {
+            break;
+        };
+        let Some((value, tail)) = value.split_once('"') else {
+            break;
+        };
+        attributes.insert(key.trim().to_string(), value.to_string());
+        rest = tail;
+    }
+    attributes
+}
+
+/// Evaluation of a solution of another solver on a program, and its
+/// differences to a solution of this crate.
+///
+/// Objective values refer to the model, see
+/// [`SifTransformation::objective_value`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SolutionComparison {
+    pub tolerance: E,
+    /// Objective value reported in the solution file.
+    pub reported_objective: Option<E>,
+    /// Objective value of the solution, evaluated on the program.
+    pub objective: E,
+    /// Objective value of our solution.
+    pub our_objective: E,
+    /// Difference of the two objective values, scaled by the larger one.
+    pub objective_difference: CertificateCheck,
+    /// `A x = b` at the solution, with the slacks of the inequality rows
+    /// computed from the columns.
+    pub primal_feasibility: CertificateCheck,
+    /// `l <= x <= u` at the solution, including the slacks, which bounds the
+    /// inequality rows.
+    pub bound_feasibility: CertificateCheck,
+    /// Largest difference of a column to our solution.
+    pub max_primal_difference: E,
+    /// Column of the largest difference.
+    pub max_difference_column: Option<String>,
+    /// Columns of the model that the file does not list, taken as zero.
+    pub missing_columns: Vec<String>,
+}
+
+impl SolutionComparison {
+    /// Returns `true` if the solution is feasible and its objective value
+    /// agrees with ours.
+    pub fn is_consistent(&self) -> bool {
+        self.primal_feasibility.passed
+            && self.bound_feasibility.passed
+            && self.objective_difference.passed
+    }
+}
+
+/// Evaluates `solution` on `lp`, converted from a model by `transformation`,
+/// and compares it with `ours`, an iterate of `lp`.
+pub fn compare_solution(
+    lp: &LinearProgram,
+    transformation: &SifTransformation,
+    solution: &SolutionFile,
+    ours: &SolverState,
+    tol: E,
+) -> SolutionComparison {
+    let col_names = transformation.get_col_names();
+    let n = col_names.len();
+    let a = lp.get_constraint_matrix();
+
+    // The slack of each inequality row takes up the difference to the right-hand side
+    let mut x = Col::zeros(lp.get_n_vars());
+    x.subrows_mut(0, n)
+        .copy_from(solution.get_primal(col_names));
+    let activities = a * &x;
+    for (k, slack) in transformation.get_slacks().iter().enumerate() {
+        let i = slack.get_row();
+        x[n + k] = (lp.get_rhs()[i] - activities[i]) / slack.get_coefficient();
+    }
+
+    let m = lp.get_n_cons();
+    let point = SolverState::new(
+        x.clone(),
+        Col::zeros(m),
+        Col::zeros(x.nrows()),
+        Col::zeros(x.nrows()),
+    );
+    let report = check_solution(lp, &point, tol);
+
+    let objective = transformation.objective_value(report.primal_objective);
+    let our_objective = transformation.objective_value(lp.get_objective_value(&ours.x));
+    let (max_primal_difference, max_difference_column) = (0..n)
+        .map(|j| ((x[j] - ours.x[j]).abs(), j))
+        .fold((E::from(0.), None), |(max, column), (difference, j)| {
+            if difference > max {
+                (difference, Some(col_names[j].clone()))
+            } else {
+                (max, column)
+            }
+        });
+
+    SolutionComparison {
+        tolerance: tol,
+        reported_objective: solution.get_objective(),
+        objective,
+        our_objective,
+        objective_difference: CertificateCheck::new(
+            (objective - our_objective).abs(),
+            objective.abs().max(our_objective.abs()),
+            tol,
+        ),
+        primal_feasibility: report.primal_feasibility,
+        bound_feasibility: report.bound_feasibility,
+        max_primal_difference,
+        max_difference_column,
+        missing_columns: col_names
+            .iter()
+            .filter(|name| solution.get_value(name).is_none())
+            .cloned()
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::col;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::{
+        SolverHooks, SolverOptions, Status, callback::NoOpCallback, interface::sif::ReadSIF,
+        terminators::ComplementarityTerminator,
+    };
+
+    /// `min -x - 2 y` subject to `x + y <= 4` and `0 <= x, y <= 3`, with
+    /// optimum `(1, 3)`.
+    const MODEL: &str = "\
+NAME          SMALL
+ROWS
+ N  obj
+ L  cap
+COLUMNS
+    x         obj       -1.0         cap       1.0
+    y         obj       -2.0         cap       1.0
+RHS
+    rhs       cap       4.0
+BOUNDS
+ UP bnd       x         3.0
+ UP bnd       y         3.0
+ENDATA
+";
+
+    const HIGHS: &str = "\
+Model status
+Optimal
+
+# Primal solution values
+Feasible
+Objective -7
+# Columns 2
+x 1
+y 3
+# Rows 1
+cap 4
+
+# Dual solution values
+Feasible
+# Columns 2
+x 0
+y -1
+# Rows 1
+cap -1
+
+# Basis
+HiGHS v1
+Valid
+";
+
+    const CPLEX: &str = r#"<?xml version = "1.0" encoding="UTF-8" standalone="yes"?>
+<CPLEXSolution version="1.2">
+ <header
+   problemName="small.mps"
+   objectiveValue="-7"
+   solutionStatusString="optimal"/>
+ <linearConstraints>
+  <constraint name="cap" index="0" slack="0" dual="-1"/>
+ </linearConstraints>
+ <variables>
+  <variable name="x" index="0" value="1" reducedCost="0"/>
+  <variable name="y" index="1" value="3" reducedCost="-1"/>
+ </variables>
+</CPLEXSolution>
+"#;
+
+    const GUROBI: &str = "\
+# Solution for model SMALL
+# Objective value = -7
+x 1
+y 3
+";
+
+    #[rstest]
+    #[case(HIGHS, SolutionFormat::Highs, Some("Optimal"))]
+    #[case(CPLEX, SolutionFormat::Cplex, Some("optimal"))]
+    #[case(GUROBI, SolutionFormat::Gurobi, None)]
+    fn test_read(#[case] text: &str, #[case] format: SolutionFormat, #[case] status: Option<&str>) {
+        let solution = SolutionFile::read(text.as_bytes()).unwrap();
+        assert_eq!(solution.get_format(), format);
+        assert_eq!(solution.get_status(), status);
+        assert_eq!(solution.get_objective(), Some(-7.));
+        let names = ["x".to_string(), "y".to_string(), "z".to_string()];
+        assert_eq!(solution.get_primal(&names), col![1., 3., 0.]);
+        if format != SolutionFormat::Gurobi {
+            assert_eq!(solution.get_row_dual("cap"), Some(-1.));
+            assert_eq!(solution.get_reduced_cost("y"), Some(-1.));
+        }
+    }
+
+    #[test]
+    fn test_read_invalid() {
+        assert!(SolutionFile::read("x one".as_bytes()).is_err());
+        assert!(
+            SolutionFile::read(HIGHS.replace("# Columns 2", "# Columns 3").as_bytes()).is_err()
+        );
+    }
+
+    #[rstest]
+    #[case(GUROBI, true, None)]
+    // Feasible, but worse by 2
+    #[case("x 1\ny 2\n", false, Some("y"))]
+    // Violates x + y <= 4
+    #[case("x 3\ny 3\n", false, Some("x"))]
+    fn test_compare(#[case] text: &str, #[case] consistent: bool, #[case] column: Option<&str>) {
+        let (lp, transformation) =
+            LinearProgram::read_sif_with_transformation(MODEL.as_bytes()).unwrap();
+        let mut state = SolverState::new_interior(lp.get_lower_bounds(), lp.get_upper_bounds(), 1);
+        let options = SolverOptions::new();
+        let mut hooks = SolverHooks::new(
+            Box::new(NoOpCallback::new()),
+            Box::new(ComplementarityTerminator::new(&options)),
+        );
+        let status = lp
+            .solver_builder()
+            .build()
+            .unwrap()
+            .solve(&mut state, &mut hooks)
+            .unwrap();
+        assert_eq!(status, Status::Optimal);
+
+        let solution = SolutionFile::read(text.as_bytes()).unwrap();
+        let comparison = compare_solution(&lp, &transformation, &solution, &state, 1e-6);
+        assert_eq!(comparison.is_consistent(), consistent);
+        assert!((comparison.our_objective + 7.).abs() < 1e-6);
+        assert_eq!(comparison.max_difference_column.as_deref(), column);
+        assert!(comparison.missing_columns.is_empty());
+        assert!(comparison.primal_feasibility.passed);
+        if text.starts_with("x 3") {
+            assert!(!comparison.bound_feasibility.passed);
+        } else {
+            assert!(comparison.bound_feasibility.passed);
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! Reading and writing of primal-dual starting points.
+//!
+//! The file consists of one section per vector. Each section starts with a
+//! header giving the vector name and length, followed by one value per line:
+//!
+//! ```text
+//! PRIMAL 2
+//! 1.5
+//! 0
+//! DUAL 1
+//! -3.25
+//! LOWER_REDUCED_COST 2
+//! ...
+//! UPPER_REDUCED_COST 2
+//! ...
+//! ```
+//!
+//! Sections may appear in any order. Missing dual sections are filled with zeros.
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    path::Path,
+};
+
+use faer::Col;
+use problemo::{Problem, common::IntoCommonProblem};
+
+use crate::{E, SolverState};
+
+const PRIMAL: &str = "PRIMAL";
+const DUAL: &str = "DUAL";
+const LOWER_REDUCED_COST: &str = "LOWER_REDUCED_COST";
+const UPPER_REDUCED_COST: &str = "UPPER_REDUCED_COST";
+
+/// A primal-dual point that can be used to warm start a solver.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WarmStart {
+    x: Col<E>,
+    y: Col<E>,
+    z_l: Col<E>,
+    z_u: Col<E>,
+}
+
+impl WarmStart {
+    pub fn new(x: Col<E>, y: Col<E>, z_l: Col<E>, z_u: Col<E>) -> Self {
+        Self { x, y, z_l, z_u }
+    }
+
+    pub fn from_state(state: &SolverState) -> Self {
+        Self {
+            x: state.x.clone(),
+            y: state.y.clone(),
+            z_l: state.z_l.clone(),
+            z_u: state.z_u.clone(),
+        }
+    }
+
+    /// Converts the point into a fresh solver state.
+    pub fn into_state(self) -> SolverState {
+        SolverState::new(self.x, self.y, self.z_l, self.z_u)
+    }
+
+    pub fn get_primal(&self) -> &Col<E> {
+        &self.x
+    }
+
+    pub fn get_dual(&self) -> &Col<E> {
+        &self.y
+    }
+
+    pub fn get_lower_reduced_cost(&self) -> &Col<E> {
+        &self.z_l
+    }
+
+    pub fn get_upper_reduced_cost(&self) -> &Col<E> {
+        &self.z_u
+    }
+
+    pub fn read<R: std::io::Read>(reader: R) -> Result<Self, Problem> {
+        let mut sections: [Option<Col<E>>; 4] = [None, None, None, None];
+        let mut lines = BufReader::new(reader).lines();
+
+        while let Some(line) = lines.next() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (name, len) = line
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| format!("Invalid section header: {line}").gloss())?;
+            let len = len
+                .trim()
+                .parse::<usize>()
+                .map_err(|e| format!("Invalid length for section {name}: {e}").gloss())?;
+
+            let slot = match name {
+                PRIMAL => 0,
+                DUAL => 1,
+                LOWER_REDUCED_COST => 2,
+                UPPER_REDUCED_COST => 3,
+                _ => return Err(format!("Unknown section: {name}").gloss()),
+            };
+
+            let mut values = Col::<E>::zeros(len);
+            for i in 0..len {
+                let value = lines.next().ok_or_else(|| {
+                    format!("Section {name} ended after {i} of {len} values").gloss()
+                })??;
+                values[i] = value
+                    .trim()
+                    .parse::<E>()
+                    .map_err(|e| format!("Invalid value in section {name}: {e}").gloss())?;
+            }
+            sections[slot] = Some(values);
+        }
+
+        let [x, y, z_l, z_u] = sections;
+        let x = x.ok_or_else(|| format!("Missing {PRIMAL} section").gloss())?;
+        let n = x.nrows();
+        let z_l = z_l.unwrap_or_else(|| Col::zeros(n));
+        let z_u = z_u.unwrap_or_else(|| Col::zeros(n));
+        if z_l.nrows() != n || z_u.nrows() != n {
+            return Err("Reduced cost length does not match the primal length".gloss());
+        }
+
+        Ok(Self {
+            x,
+            y: y.unwrap_or_else(|| Col::zeros(0)),
+            z_l,
+            z_u,
+        })
+    }
+
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<(), Problem> {
+        for (name, values) in [
+            (PRIMAL, &self.x),
+            (DUAL, &self.y),
+            (LOWER_REDUCED_COST, &self.z_l),
+            (UPPER_REDUCED_COST, &self.z_u),
+        ] {
+            writeln!(writer, "{name} {}", values.nrows())?;
+            // Display for f64 prints the shortest representation that round-trips exactly
+            for value in values.iter() {
+                writeln!(writer, "{value}")?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn read_file<P: AsRef<Path>>(path: P) -> Result<Self, Problem> {
+        let file = std::fs::File::open(path)?;
+        Self::read(file)
+    }
+
+    pub fn write_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Problem> {
+        let file = std::fs::File::create(path)?;
+        self.write(std::io::BufWriter::new(file))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::col;
+
+    use super::*;
+
+    #[test]
+    fn test_warm_start_roundtrip() {
+        let warm_start = WarmStart::new(
+            col![1.5, 0., 1. / 3.],
+            col![-3.25],
+            col![0.1, 1e-12, 0.],
+            col![0., 0., E::INFINITY],
+        );
+
+        let mut buf = Vec::new();
+        warm_start.write(&mut buf).unwrap();
+        let read = WarmStart::read(buf.as_slice()).unwrap();
+
+        assert_eq!(warm_start, read);
+    }
+
+    #[test]
+    fn test_warm_start_primal_only() {
+        let input = "PRIMAL 2\n1\n2\n";
+        let read = WarmStart::read(input.as_bytes()).unwrap();
+
+        assert_eq!(read.get_primal(), &col![1., 2.]);
+        assert_eq!(read.get_dual().nrows(), 0);
+        assert_eq!(read.get_lower_reduced_cost(), &Col::<E>::zeros(2));
+    }
+}
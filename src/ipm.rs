@@ -1,9 +1,484 @@
-use faer::Col;
+use std::{collections::BTreeSet, str::FromStr, time::Duration};
 
-use crate::{E, SolverState};
+use faer::{
+    Col, ColRef,
+    sparse::{SparseColMat, SparseColMatRef, SymbolicSparseColMat},
+    traits::IndexCore,
+};
+
+use macros::{explicit_options, use_option};
+use problemo::{Problem, common::IntoCommonProblem};
+
+#[cfg(feature = "data-loaders")]
+use crate::data_loaders::mtx;
+use crate::{
+    E, I, OptionTrait, SearchDirection, SolverOptions, SolverState, Status, StepLength,
+    TerminationInfo,
+    linalg::{
+        solver::{LinearSolver, MemoryEstimate},
+        vector_ops::max_step_to_boundary,
+    },
+    to_index,
+};
 
 pub(crate) const DEFAULT_MAX_ITERATIONS: usize = 1000;
 
+/// Relative regularization added to the diagonal of the normal equations so that
+/// rank-deficient constraint matrices still yield a nonsingular system.
+const NORMAL_EQUATIONS_REGULARIZATION: E = 1e-12;
+
+/// Formulation of the Newton system solved at every interior-point iteration.
+///
+/// - `Standard`: the full augmented system `[Q + D, -A^T; -A, 0]`.
+/// - `SlackReduced`: the augmented system with the bound multipliers eliminated
+///   into a diagonal `(1,1)` block. Requires a diagonal (or zero) Hessian; for
+///   linear programs it coincides with `Standard`.
+/// - `NormalEquations`: the positive definite system `A D^{-1} A^T` in the dual
+///   variables only. Requires a diagonal Hessian and every variable to have a
+///   finite bound or positive curvature.
+/// - `SchurComplement`: the slack-reduced system with the columns of `A` that
+///   have at most one nonzero and a finite bound eliminated, which shrinks the
+///   system by the number of such columns. Only available for linear programs.
+/// - `Auto`: picks one of the above based on the structure of the problem.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum AugmentedSystemType {
+    #[default]
+    Auto,
+    Standard,
+    SlackReduced,
+    NormalEquations,
+    SchurComplement,
+}
+
+impl OptionTrait for AugmentedSystemType {}
+
+impl FromStr for AugmentedSystemType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(AugmentedSystemType::Auto),
+            "standard" => Ok(AugmentedSystemType::Standard),
+            "slack_reduced" => Ok(AugmentedSystemType::SlackReduced),
+            "normal_equations" | "ne" => Ok(AugmentedSystemType::NormalEquations),
+            "schur_complement" | "schur" => Ok(AugmentedSystemType::SchurComplement),
+            _ => Err(format!("Invalid augmented system type: {}", s)),
+        }
+    }
+}
+
+/// Number of Gondzio centrality correctors computed after the Mehrotra
+/// corrector of an interior-point iteration.
+///
+/// - `Fixed(k)`: up to `k` correctors per iteration; `0` disables them.
+/// - `Auto`: up to a number chosen every iteration from the measured ratio of
+///   the factorization time to the solve time, see [`SolveCosts`]. Correctors
+///   reuse the factorization, so they pay off when factorizing is expensive.
+///
+/// Each corrector costs one solve and is kept only if it lengthens the step.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CorrectorCount {
+    Fixed(usize),
+    Auto,
+}
+
+impl Default for CorrectorCount {
+    fn default() -> Self {
+        CorrectorCount::Fixed(0)
+    }
+}
+
+impl OptionTrait for CorrectorCount {}
+
+impl FromStr for CorrectorCount {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(CorrectorCount::Auto),
+            count => count
+                .parse()
+                .map(CorrectorCount::Fixed)
+                .map_err(|_| format!("Invalid corrector count: {}", s)),
+        }
+    }
+}
+
+/// Largest number of correctors of an iteration in `Auto` mode.
+const MAX_AUTO_CORRECTORS: usize = 6;
+
+/// Complementarity products of a trial point are corrected into
+/// `[CORRECTOR_BETA_MIN, CORRECTOR_BETA_MAX]` times the target `sigma * mu`.
+const CORRECTOR_BETA_MIN: E = 0.1;
+const CORRECTOR_BETA_MAX: E = 10.;
+
+/// Increase of the step length aimed at by a corrector.
+pub(crate) const CORRECTOR_STEP_INCREASE: E = 0.1;
+
+/// Fraction of `CORRECTOR_STEP_INCREASE` by which a corrector must lengthen
+/// the step to be kept.
+pub(crate) const CORRECTOR_MIN_GAIN: E = 0.1;
+
+impl CorrectorCount {
+    /// Largest number of correctors of the next iteration given the costs
+    /// measured so far. `Auto` makes one corrector until both costs are known.
+    ///
+    /// As in Gondzio (1996), `Auto` makes one corrector while a factorization
+    /// costs at most 10 solves and one more for every further 20 solves.
+    pub fn get_max_correctors(&self, costs: &SolveCosts) -> usize {
+        match self {
+            CorrectorCount::Fixed(count) => *count,
+            CorrectorCount::Auto => costs.get_ratio().map_or(1, |ratio| {
+                if ratio <= E::from(10.) {
+                    1
+                } else {
+                    MAX_AUTO_CORRECTORS.min(2 + ((ratio - E::from(10.)) / E::from(20.)) as usize)
+                }
+            }),
+        }
+    }
+}
+
+/// Running times of the factorizations and of the solves reusing them during
+/// an interior-point solve.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SolveCosts {
+    factor: Duration,
+    solve: Duration,
+    n_factor: u32,
+    n_solve: u32,
+}
+
+impl SolveCosts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_factor(&mut self, elapsed: Duration) {
+        self.factor += elapsed;
+        self.n_factor += 1;
+    }
+
+    pub fn record_solve(&mut self, elapsed: Duration) {
+        self.solve += elapsed;
+        self.n_solve += 1;
+    }
+
+    /// Ratio of the mean factorization time to the mean solve time, `None`
+    /// before both have been recorded or if the solves took no measurable time.
+    pub fn get_ratio(&self) -> Option<E> {
+        if self.n_factor == 0 || self.n_solve == 0 || self.solve.is_zero() {
+            return None;
+        }
+        let factor = self.factor.as_secs_f64() / self.n_factor as E;
+        let solve = self.solve.as_secs_f64() / self.n_solve as E;
+        Some(factor / solve)
+    }
+}
+
+/// Reuse of the factorization of the Newton system across interior-point
+/// iterations whose scaling matrix `D` barely changes.
+///
+/// The system is refactorized only if some entry of `D` differs from its value
+/// at the last factorization by more than `refactorization_threshold` times
+/// that value. Otherwise the matrix is updated but not factorized, and solves
+/// with the stale factorization are corrected by up to
+/// `refactorization_refine_iterations` steps of iterative refinement against
+/// the current matrix. The change is measured from the last factorization, so
+/// that it cannot accumulate over several skipped iterations.
+///
+/// Early iterations of large problems move `D` little and pay mostly for the
+/// factorization, so skipping trades some accuracy of their directions for
+/// speed. A threshold of 0 refactorizes every iteration.
+#[explicit_options(name = SolverOptions)]
+#[use_option(name = "refactorization_threshold", type_ = E, default = "0", description = "Largest relative change of the scaling matrix of an interior-point iteration for which the previous factorization of the Newton system is reused; 0 always refactorizes.")]
+#[use_option(name = "refactorization_refine_iterations", type_ = usize, default = "3", description = "Iterative refinement steps of solves with a reused factorization of the Newton system.")]
+pub(crate) struct FactorizationReuse {
+    /// `D` at the last factorization.
+    factored: Option<Col<E>>,
+    /// Whether the current matrix differs from the factorized one.
+    stale: bool,
+    n_skipped: usize,
+}
+
+impl FactorizationReuse {
+    pub(crate) fn new(options: &SolverOptions) -> Self {
+        Self {
+            factored: None,
+            stale: false,
+            n_skipped: 0,
+            options: options.into(),
+        }
+    }
+
+    /// Whether the matrix with scaling `d` must be factorized. Records `d` as
+    /// the factorized scaling if so, and counts a skipped factorization if not.
+    pub(crate) fn needs_factorization(&mut self, d: &Col<E>) -> bool {
+        let threshold = self.options.refactorization_threshold;
+        let reuse = threshold > E::from(0.)
+            && self.factored.as_ref().is_some_and(|factored| {
+                factored
+                    .iter()
+                    .zip(d.iter())
+                    .all(|(&old, &new)| (new - old).abs() <= threshold * old.abs())
+            });
+        self.stale = reuse;
+        if reuse {
+            self.n_skipped += 1;
+        } else {
+            self.factored = Some(d.clone());
+        }
+        !reuse
+    }
+
+    /// Number of factorizations skipped so far.
+    pub(crate) fn get_n_skipped(&self) -> usize {
+        self.n_skipped
+    }
+
+    /// Solves `mat x = rhs` with the factorization of `solver`, refining the
+    /// solution against `mat` if the factorization is stale.
+    pub(crate) fn solve<Solver: LinearSolver>(
+        &self,
+        solver: &mut Solver,
+        mat: SparseColMatRef<'_, I, E>,
+        rhs: &Col<E>,
+    ) -> Result<Col<E>, Problem> {
+        let mut x = solver.solve(rhs.as_mat())?.col(0).to_owned();
+        if !self.stale {
+            return Ok(x);
+        }
+        let tolerance = E::EPSILON * (E::from(1.) + rhs.norm_max());
+        for _ in 0..self.options.refactorization_refine_iterations {
+            let residual = rhs - mat * &x;
+            if residual.norm_max() <= tolerance {
+                break;
+            }
+            x += solver.solve(residual.as_mat())?.col(0);
+        }
+        Ok(x)
+    }
+}
+
+/// Change of the complementarity products `(x - bound) z` of a trial point
+/// that moves them into `[CORRECTOR_BETA_MIN, CORRECTOR_BETA_MAX] * target`,
+/// with decreases capped at `CORRECTOR_BETA_MAX * target` (Gondzio, 1996).
+/// Added to the complementarity right-hand side, it yields a centrality
+/// corrector. Entries of infinite bounds are zero.
+pub(crate) fn centrality_correction(
+    x: ColRef<'_, E>,
+    bound: ColRef<'_, E>,
+    z: ColRef<'_, E>,
+    target: E,
+) -> Col<E> {
+    let (low, high) = (CORRECTOR_BETA_MIN * target, CORRECTOR_BETA_MAX * target);
+    Col::from_fn(x.nrows(), |j| {
+        if !bound[j].is_finite() {
+            return E::from(0.);
+        }
+        let product = (x[j] - bound[j]) * z[j];
+        if product < low {
+            low - product
+        } else if product > high {
+            (high - product).max(-high)
+        } else {
+            E::from(0.)
+        }
+    })
+}
+
+/// Returns the termination with [`Status::MemoryLimit`], whose detail is the estimate in MiB, if the
+/// estimated memory of a factorization exceeds `max_memory` MiB. A limit of 0 or a missing
+/// estimate never exceeds.
+pub(crate) fn exceeds_memory_limit(
+    estimate: Option<MemoryEstimate>,
+    max_memory: E,
+) -> Option<TerminationInfo> {
+    let mib = estimate?.get_total_bytes() as E / E::from(1024. * 1024.);
+    (max_memory > E::from(0.) && mib > max_memory).then(|| {
+        TerminationInfo::new(
+            Status::MemoryLimit,
+            format!("factorization needs more than max_memory = {max_memory} MiB"),
+            mib,
+        )
+    })
+}
+
+/// Step lengths that keep `l <= x <= u` and the signs `z_l >= 0`, `z_u <= 0` of
+/// the multipliers of the finite bounds, scaled by the safety factor of `state`
+/// and capped at a full step.
+pub(crate) fn max_step_length(
+    l: &Col<E>,
+    u: &Col<E>,
+    state: &SolverState,
+    step: &SearchDirection,
+) -> StepLength {
+    crate::profile!("line_search");
+    let (alpha_primal, blocking_primal) =
+        max_step_to_boundary(state.x.as_ref(), step.dx.as_ref(), l.as_ref(), u.as_ref());
+
+    // The multiplier of a bound is restricted in sign only if the bound is finite
+    let zero_or = |bound: &Col<E>, infinity: E| {
+        Col::from_fn(bound.nrows(), |j| {
+            if bound[j].is_finite() {
+                E::from(0.)
+            } else {
+                infinity
+            }
+        })
+    };
+    let free = Col::from_fn(l.nrows(), |_| E::INFINITY);
+    let (alpha_lower, blocking_lower) = max_step_to_boundary(
+        state.z_l.as_ref(),
+        step.dz_l.as_ref(),
+        zero_or(l, -E::INFINITY).as_ref(),
+        free.as_ref(),
+    );
+    let (alpha_upper, blocking_upper) = max_step_to_boundary(
+        state.z_u.as_ref(),
+        step.dz_u.as_ref(),
+        (-&free).as_ref(),
+        zero_or(u, E::INFINITY).as_ref(),
+    );
+    let (alpha_dual, blocking_dual) = if alpha_upper < alpha_lower {
+        (alpha_upper, blocking_upper)
+    } else {
+        (alpha_lower, blocking_lower)
+    };
+
+    let one = E::from(1.);
+    let safety_factor = state.safety_factor.unwrap_or(one);
+    StepLength {
+        primal: E::min(one, safety_factor * E::min(one, alpha_primal)),
+        dual: E::min(one, safety_factor * E::min(one, alpha_dual)),
+        blocking_primal: blocking_primal.filter(|_| alpha_primal < one),
+        blocking_dual: blocking_dual.filter(|_| alpha_dual < one),
+    }
+}
+
+/// Returns `true` if the normal equations `A D^{-1} A^T` are expected to be sparser
+/// than the `(n_var + n_con)` augmented system for the given constraint matrix.
+///
+/// The fill of the normal equations is estimated by the number of products
+/// `a_ij a_kj` formed in each column, which overestimates the true count when
+/// columns share rows but catches the dense columns that make the normal
+/// equations impractical.
+#[allow(non_snake_case)]
+pub(crate) fn prefer_normal_equations(A: SparseColMatRef<I, E>) -> bool {
+    let normal_nnz: usize = (0..A.ncols())
+        .map(|j| {
+            let nnz = A.row_idx_of_col_raw(j).len();
+            nnz * (nnz + 1) / 2
+        })
+        .sum();
+    let augmented_nnz = A.ncols() + A.compute_nnz();
+
+    normal_nnz <= augmented_nnz
+}
+
+/// The matrix `A diag(d) A^T` with a fixed sparsity pattern.
+///
+/// The pattern is computed once from `A`; every update only recomputes the values.
+/// Both triangles are stored, matching the other augmented systems.
+#[allow(non_snake_case)]
+pub(crate) struct NormalMatrix {
+    mat: SparseColMat<I, E>,
+    /// Position of every product `a_pj a_qj` in `mat`, grouped by the column `j` of `A`.
+    products: Vec<(usize, usize, usize)>,
+    products_ptr: Vec<usize>,
+    diag_idx: Vec<usize>,
+}
+
+#[allow(non_snake_case)]
+impl NormalMatrix {
+    pub(crate) fn new(A: SparseColMatRef<I, E>) -> Self {
+        let m = A.nrows();
+        let a_col_ptr = A.symbolic().col_ptr();
+        let a_row_idx = A.symbolic().row_idx();
+
+        // Collect the sparsity pattern column by column, always including the diagonal
+        let mut pattern = (0..m).map(|i| BTreeSet::from([i])).collect::<Vec<_>>();
+        for j in 0..A.ncols() {
+            for q in A.row_idx_of_col(j) {
+                for p in A.row_idx_of_col(j) {
+                    pattern[q].insert(p);
+                }
+            }
+        }
+
+        let mut col_ptrs = Vec::with_capacity(m + 1);
+        let mut row_indices = Vec::new();
+        col_ptrs.push(0);
+        for rows in &pattern {
+            row_indices.extend(rows.iter().copied());
+            col_ptrs.push(row_indices.len());
+        }
+
+        let position = |p: usize, q: usize| {
+            let start = col_ptrs[q];
+            start
+                + row_indices[start..col_ptrs[q + 1]]
+                    .binary_search(&p)
+                    .unwrap()
+        };
+
+        let diag_idx = (0..m).map(|i| position(i, i)).collect::<Vec<_>>();
+
+        let mut products = Vec::new();
+        let mut products_ptr = Vec::with_capacity(A.ncols() + 1);
+        products_ptr.push(0);
+        for j in 0..A.ncols() {
+            for kq in a_col_ptr[j].zx()..a_col_ptr[j + 1].zx() {
+                for kp in a_col_ptr[j].zx()..a_col_ptr[j + 1].zx() {
+                    products.push((kp, kq, position(a_row_idx[kp].zx(), a_row_idx[kq].zx())));
+                }
+            }
+            products_ptr.push(products.len());
+        }
+
+        let nnz = row_indices.len();
+        let col_ptrs = col_ptrs.into_iter().map(to_index).collect();
+        let row_indices = row_indices.into_iter().map(to_index).collect();
+        let mat = unsafe {
+            let sym = SymbolicSparseColMat::new_unchecked(m, m, col_ptrs, None, row_indices);
+            SparseColMat::<I, E>::new(sym, vec![E::from(0.); nnz])
+        };
+
+        Self {
+            mat,
+            products,
+            products_ptr,
+            diag_idx,
+        }
+    }
+
+    /// Recomputes the values as `A diag(d) A^T` plus a small diagonal regularization.
+    pub(crate) fn update(&mut self, A: SparseColMatRef<I, E>, d: &Col<E>) {
+        let a_values = A.val();
+        let values = self.mat.val_mut();
+        values.fill(E::from(0.));
+
+        for j in 0..A.ncols() {
+            for &(kp, kq, pos) in &self.products[self.products_ptr[j]..self.products_ptr[j + 1]] {
+                values[pos] += a_values[kp] * d[j] * a_values[kq];
+            }
+        }
+
+        let max_diag = self
+            .diag_idx
+            .iter()
+            .fold(E::from(0.), |acc, &k| acc.max(values[k].abs()));
+        let regularization = NORMAL_EQUATIONS_REGULARIZATION * max_diag.max(E::from(1.));
+        for &k in &self.diag_idx {
+            values[k] += regularization;
+        }
+    }
+
+    pub(crate) fn as_ref(&self) -> SparseColMatRef<'_, I, E> {
+        self.mat.as_ref()
+    }
+}
+
 #[allow(unused)]
 #[derive(Debug, Clone, PartialEq)]
 pub struct RHS {
@@ -76,3 +551,82 @@ impl RHS {
         self.r_u = value;
     }
 }
+
+/// Writes the Newton systems of selected interior-point iterations to MatrixMarket
+/// files, configured by the `kkt_dump_directory` and `kkt_dump_iterations` options.
+///
+/// Every solve of iteration `k` writes `iter_<k>_<stage>_kkt.mtx` with the system
+/// matrix, `_r_d`, `_r_c`, `_r_l`, `_r_u` with the right-hand side and `_dx`,
+/// `_dy`, `_dz_l`, `_dz_u` with the resulting step.
+pub(crate) struct KktDump {
+    directory: std::path::PathBuf,
+    nit: usize,
+}
+
+impl KktDump {
+    /// Returns the dump for iteration `nit`, or `None` if the directory is empty
+    /// or `nit` is not among the comma-separated `iterations` (empty selects all).
+    pub(crate) fn new(
+        directory: &str,
+        iterations: &str,
+        nit: usize,
+    ) -> Result<Option<Self>, Problem> {
+        if directory.is_empty() {
+            return Ok(None);
+        }
+        if cfg!(not(feature = "data-loaders")) {
+            return Err("Dumping the KKT system requires the data-loaders feature".gloss());
+        }
+        let selected = iterations.trim().is_empty()
+            || iterations
+                .split(',')
+                .map(|k| {
+                    k.trim().parse::<usize>().map_err(|_| {
+                        format!("Invalid iteration '{k}' in kkt_dump_iterations").gloss()
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?
+                .contains(&nit);
+        if !selected {
+            return Ok(None);
+        }
+
+        std::fs::create_dir_all(directory)
+            .map_err(|e| format!("Failed to create {directory}: {e}").gloss())?;
+        Ok(Some(Self {
+            directory: directory.into(),
+            nit,
+        }))
+    }
+
+    #[cfg_attr(not(feature = "data-loaders"), allow(unused_variables))]
+    pub(crate) fn write(
+        &self,
+        stage: &str,
+        matrix: SparseColMatRef<I, E>,
+        rhs: &RHS,
+        step: &SearchDirection,
+    ) -> Result<(), Problem> {
+        #[cfg(feature = "data-loaders")]
+        {
+            let path = |name: &str| {
+                self.directory
+                    .join(format!("iter_{:04}_{stage}_{name}.mtx", self.nit))
+            };
+            mtx::write_sparse(path("kkt"), matrix, false)?;
+            for (name, col) in [
+                ("r_d", &rhs.r_d),
+                ("r_c", &rhs.r_c),
+                ("r_l", &rhs.r_l),
+                ("r_u", &rhs.r_u),
+                ("dx", &step.dx),
+                ("dy", &step.dy),
+                ("dz_l", &step.dz_l),
+                ("dz_u", &step.dz_u),
+            ] {
+                mtx::write_dense(path(name), col.as_ref())?;
+            }
+        }
+        Ok(())
+    }
+}
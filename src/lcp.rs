@@ -0,0 +1,357 @@
+//! Linear complementarity problems.
+//!
+//! A linear complementarity problem (LCP) asks for `z` such that
+//!
+//! ```text
+//! w = M z + q,    w >= 0,    z >= 0,    w^T z = 0
+//! ```
+//!
+//! [`Lemke`] solves it by complementary pivoting on a dense tableau, which
+//! finds a solution whenever `M` is a P-matrix, or copositive-plus and the
+//! problem is feasible, as in the contact problems of rigid-body dynamics.
+//!
+//! If `M` is positive semidefinite, the LCP is also the optimality condition of
+//! the convex quadratic program
+//!
+//! ```text
+//! min  z^T (M z + q)
+//! s.t. M z - w = -q
+//!      z, w >= 0
+//! ```
+//!
+//! whose optimal value is zero. [`LinearComplementarityProblem::to_quadratic_program`]
+//! builds it for the interior-point solvers of [`crate::qp`].
+
+use faer::{Col, Mat, sparse::SparseColMat, sparse::Triplet};
+use macros::{explicit_options, use_option};
+use problemo::{Problem, common::IntoCommonProblem};
+
+use crate::{E, I, SolverOptions, Status, qp::QuadraticProgram, to_index};
+
+/// Linear complementarity problem `w = M z + q, w, z >= 0, w^T z = 0`.
+#[derive(Debug, Clone)]
+#[allow(non_snake_case)]
+pub struct LinearComplementarityProblem {
+    M: Mat<E>,
+    q: Col<E>,
+}
+
+#[allow(non_snake_case)]
+impl LinearComplementarityProblem {
+    /// Creates the problem, failing if `M` is not square of the size of `q`.
+    pub fn new(M: Mat<E>, q: Col<E>) -> Result<Self, Problem> {
+        if M.nrows() != q.nrows() || M.ncols() != q.nrows() {
+            return Err(format!(
+                "Matrix of size {}x{} does not match a vector of size {}",
+                M.nrows(),
+                M.ncols(),
+                q.nrows()
+            )
+            .gloss());
+        }
+        Ok(Self { M, q })
+    }
+
+    pub fn get_size(&self) -> usize {
+        self.q.nrows()
+    }
+
+    pub fn get_matrix(&self) -> &Mat<E> {
+        &self.M
+    }
+
+    pub fn get_vector(&self) -> &Col<E> {
+        &self.q
+    }
+
+    /// Slack `w = M z + q` of `z`.
+    pub fn get_slack(&self, z: &Col<E>) -> Col<E> {
+        &self.M * z + &self.q
+    }
+
+    /// Largest violation of the nonnegativity and complementarity conditions
+    /// at `z`.
+    pub fn get_violation(&self, z: &Col<E>) -> E {
+        let w = self.get_slack(z);
+        (0..self.get_size())
+            .map(|i| (-z[i]).max(-w[i]).max((z[i] * w[i]).abs()))
+            .fold(E::from(0.), E::max)
+    }
+
+    /// Quadratic program in the variables `(z, w)` whose optimal solutions
+    /// solve the problem, for positive semidefinite `M`.
+    pub fn to_quadratic_program(&self) -> QuadraticProgram {
+        let n = self.get_size();
+        let mut q_triplets = Vec::new();
+        let mut a_triplets = Vec::new();
+        for j in 0..n {
+            for i in 0..n {
+                let symmetric = self.M[(i, j)] + self.M[(j, i)];
+                if symmetric != E::from(0.) {
+                    q_triplets.push(Triplet::new(to_index(i), to_index(j), symmetric));
+                }
+                if self.M[(i, j)] != E::from(0.) {
+                    a_triplets.push(Triplet::new(to_index(i), to_index(j), self.M[(i, j)]));
+                }
+            }
+            a_triplets.push(Triplet::new(to_index(j), to_index(n + j), E::from(-1.)));
+        }
+
+        let mut c = Col::zeros(2 * n);
+        c.subrows_mut(0, n).copy_from(&self.q);
+        QuadraticProgram::new(
+            SparseColMat::<I, E>::try_new_from_triplets(2 * n, 2 * n, &q_triplets).unwrap(),
+            c,
+            SparseColMat::try_new_from_triplets(n, 2 * n, &a_triplets).unwrap(),
+            -&self.q,
+            Col::zeros(2 * n),
+            Col::full(2 * n, E::INFINITY),
+        )
+    }
+}
+
+/// Result of [`Lemke::solve`].
+#[derive(Debug, Clone)]
+pub struct LCPSolution {
+    status: Status,
+    z: Col<E>,
+    w: Col<E>,
+    n_pivots: usize,
+}
+
+impl LCPSolution {
+    /// [`Status::Optimal`] if a solution was found, [`Status::Infeasible`] on
+    /// ray termination and [`Status::IterationLimit`] if `lcp_max_pivots` was
+    /// reached.
+    pub fn get_status(&self) -> Status {
+        self.status
+    }
+
+    pub fn get_z(&self) -> &Col<E> {
+        &self.z
+    }
+
+    pub fn get_w(&self) -> &Col<E> {
+        &self.w
+    }
+
+    pub fn get_n_pivots(&self) -> usize {
+        self.n_pivots
+    }
+}
+
+/// Lemke's complementary pivoting method with covering vector `e = 1`.
+///
+/// Ties in the ratio test are broken lexicographically, which rules out
+/// cycling on degenerate problems. Ray termination proves that the problem has
+/// no solution if `M` is copositive-plus.
+#[explicit_options(name = SolverOptions)]
+#[use_option(name = "lcp_max_pivots", type_ = usize, default = "0", description = "Maximum number of pivots of Lemke's method (0 uses 100 (n + 1)).")]
+#[use_option(name = "lcp_pivot_tolerance", type_ = E, default = "1e-12", description = "Smallest pivot element of Lemke's method.")]
+pub struct Lemke {}
+
+impl Lemke {
+    pub fn new(options: &SolverOptions) -> Self {
+        Self {
+            options: options.into(),
+        }
+    }
+
+    /// Solves `lcp` from the trivial basis `w = q`.
+    pub fn solve(&self, lcp: &LinearComplementarityProblem) -> LCPSolution {
+        let n = lcp.get_size();
+        let tolerance = self.options.lcp_pivot_tolerance;
+        let max_pivots = match self.options.lcp_max_pivots {
+            0 => 100 * (n + 1),
+            max_pivots => max_pivots,
+        };
+
+        // Tableau [I, -M, -e | q] over the columns w, z and z_0
+        let z0 = 2 * n;
+        let mut tableau = Mat::<E>::zeros(n, 2 * n + 2);
+        for i in 0..n {
+            tableau[(i, i)] = E::from(1.);
+            for j in 0..n {
+                tableau[(i, n + j)] = -lcp.M[(i, j)];
+            }
+            tableau[(i, z0)] = E::from(-1.);
+            tableau[(i, z0 + 1)] = lcp.q[i];
+        }
+        let mut basis = (0..n).collect::<Vec<_>>();
+        let solution = |basis: &[usize], tableau: &Mat<E>, status, n_pivots| {
+            let mut values = Col::zeros(2 * n + 1);
+            for (i, &k) in basis.iter().enumerate() {
+                values[k] = tableau[(i, z0 + 1)];
+            }
+            LCPSolution {
+                status,
+                z: values.subrows(n, n).to_owned(),
+                w: values.subrows(0, n).to_owned(),
+                n_pivots,
+            }
+        };
+
+        // z_0 enters at the row of the most negative q_i
+        let Some(row) = (0..n)
+            .filter(|&i| lcp.q[i] < E::from(0.))
+            .min_by(|&i, &k| lcp.q[i].total_cmp(&lcp.q[k]))
+        else {
+            return solution(&basis, &tableau, Status::Optimal, 0);
+        };
+        let mut entering = z0;
+        let mut row = row;
+
+        for n_pivots in 1..=max_pivots {
+            let leaving = basis[row];
+            pivot(&mut tableau, row, entering);
+            basis[row] = entering;
+            if leaving == z0 {
+                return solution(&basis, &tableau, Status::Optimal, n_pivots);
+            }
+
+            // The complement of the leaving variable enters
+            entering = if leaving < n {
+                leaving + n
+            } else {
+                leaving - n
+            };
+            match ratio_test(&tableau, &basis, entering, z0, n, tolerance) {
+                Some(next) => row = next,
+                None => return solution(&basis, &tableau, Status::Infeasible, n_pivots),
+            }
+        }
+        solution(&basis, &tableau, Status::IterationLimit, max_pivots)
+    }
+}
+
+/// Row of the leaving variable when `entering` enters, or `None` if the
+/// column has no positive entry. Prefers `z_0`, then breaks ties
+/// lexicographically on the columns of `w`, which hold the basis inverse.
+fn ratio_test(
+    tableau: &Mat<E>,
+    basis: &[usize],
+    entering: usize,
+    z0: usize,
+    n: usize,
+    tolerance: E,
+) -> Option<usize> {
+    let rhs = z0 + 1;
+    let key = |i: usize, j: usize| tableau[(i, j)] / tableau[(i, entering)];
+
+    let mut best: Option<usize> = None;
+    for i in (0..n).filter(|&i| tableau[(i, entering)] > tolerance) {
+        let Some(k) = best else {
+            best = Some(i);
+            continue;
+        };
+        let (ratio, best_ratio) = (key(i, rhs), key(k, rhs));
+        let better = if (ratio - best_ratio).abs() > tolerance * best_ratio.abs().max(E::from(1.)) {
+            ratio < best_ratio
+        } else if basis[i] == z0 || basis[k] == z0 {
+            basis[i] == z0
+        } else {
+            (0..n)
+                .map(|j| key(i, j) - key(k, j))
+                .find(|d| d.abs() > tolerance)
+                .is_some_and(|d| d < E::from(0.))
+        };
+        if better {
+            best = Some(i);
+        }
+    }
+    best
+}
+
+/// Gauss-Jordan pivot on the entry `(row, col)` of `tableau`.
+fn pivot(tableau: &mut Mat<E>, row: usize, col: usize) {
+    let scale = E::from(1.) / tableau[(row, col)];
+    for j in 0..tableau.ncols() {
+        tableau[(row, j)] *= scale;
+    }
+    for i in (0..tableau.nrows()).filter(|&i| i != row) {
+        let factor = tableau[(i, col)];
+        if factor == E::from(0.) {
+            continue;
+        }
+        for j in 0..tableau.ncols() {
+            let update = factor * tableau[(row, j)];
+            tableau[(i, j)] -= update;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::{col, mat};
+    use rstest::rstest;
+
+    use super::*;
+    use crate::{
+        SolverHooks, SolverState, callback::NoOpCallback, qp::QPSolverType,
+        terminators::ComplementarityTerminator,
+    };
+
+    /// Positive definite problem with solution `z = (2/3, 0, 1/3)`.
+    fn build_lcp() -> LinearComplementarityProblem {
+        LinearComplementarityProblem::new(
+            mat![[2., 1., 0.], [1., 2., 1.], [0., 1., 2.]],
+            col![-4. / 3., 1., -2. / 3.],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_lemke() {
+        let lcp = build_lcp();
+        let solution = Lemke::new(&SolverOptions::new()).solve(&lcp);
+        assert_eq!(solution.get_status(), Status::Optimal);
+        assert!((solution.get_z() - col![2. / 3., 0., 1. / 3.]).norm_max() < 1e-12);
+        assert!((solution.get_w() - lcp.get_slack(solution.get_z())).norm_max() < 1e-12);
+        assert!(lcp.get_violation(solution.get_z()) < 1e-12);
+    }
+
+    #[rstest]
+    // q >= 0 is solved by z = 0 without pivots
+    #[case(mat![[1., 0.], [0., 1.]], col![1., 2.], Status::Optimal)]
+    // w_0 = -z_0 - 1 < 0 for all z_0 >= 0
+    #[case(mat![[-1., 0.], [0., 1.]], col![-1., 1.], Status::Infeasible)]
+    // Degenerate q with ties in the ratio test
+    #[case(mat![[1., 2.], [2., 1.]], col![-1., -1.], Status::Optimal)]
+    fn test_lemke_cases(#[case] m: Mat<E>, #[case] q: Col<E>, #[case] status: Status) {
+        let lcp = LinearComplementarityProblem::new(m, q).unwrap();
+        let solution = Lemke::new(&SolverOptions::new()).solve(&lcp);
+        assert_eq!(solution.get_status(), status);
+        if status == Status::Optimal {
+            assert!(lcp.get_violation(solution.get_z()) < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_quadratic_program() {
+        let lcp = build_lcp();
+        let qp = lcp.to_quadratic_program();
+        let mut state: SolverState =
+            SolverState::new_interior(qp.get_lower_bounds(), qp.get_upper_bounds(), lcp.get_size());
+        let options = SolverOptions::new();
+        let mut hooks = SolverHooks {
+            callback: Box::new(NoOpCallback::new()),
+            terminator: Box::new(ComplementarityTerminator::new(&options)),
+        };
+        let status = qp
+            .solver_builder()
+            .with_solver(QPSolverType::MpcSimplicialCholesky)
+            .build()
+            .unwrap()
+            .solve(&mut state, &mut hooks)
+            .unwrap();
+        assert_eq!(status, Status::Optimal);
+
+        let z = state.get_primal().subrows(0, 3).to_owned();
+        assert!((z - col![2. / 3., 0., 1. / 3.]).norm_max() < 1e-4);
+    }
+
+    #[test]
+    fn test_dimension_mismatch() {
+        assert!(LinearComplementarityProblem::new(Mat::zeros(2, 3), Col::zeros(2)).is_err());
+    }
+}
@@ -9,11 +9,16 @@ use dyn_clone::{DynClone, clone_box};
 use faer::sparse::SparseColMat;
 use faer::traits::ComplexField;
 use faer::traits::num_traits::{Float, PrimInt};
-use faer::{Col, Index};
+use faer::{Col, ColRef, Index};
 use macros::build_options;
-use problemo::Problem;
+use problemo::{Problem, common::IntoCommonProblem};
+use serde::Serialize;
 
-use crate::callback::Callback;
+use crate::callback::{Callback, ChainedCallback, ConvergenceOutput, NoOpCallback};
+use crate::terminators::{
+    CancellationTerminator, CancellationToken, ChainedTerminator, ConvergenceTerminator,
+    DivergenceTerminator, MultiTerminator, TimeOutTerminator,
+};
 
 pub trait ElementType: ComplexField + Float + Div<Output = Self> + PrimInt {}
 impl<T> ElementType for T where T: ComplexField + Float + Div<Output = T> + PrimInt {}
@@ -22,18 +27,52 @@ pub trait IndexType: Copy + PartialEq + Eq + Ord + Index {}
 impl<T> IndexType for T where T: Copy + PartialEq + Eq + Ord + Index {}
 
 pub type E = f64;
+
+/// Index type of the sparse matrices. The `index32` feature halves the memory of the indices
+/// for problems whose matrices and factors have fewer than `2^32` nonzeros.
+#[cfg(not(feature = "index32"))]
 pub type I = usize;
+#[cfg(feature = "index32")]
+pub type I = u32;
+
+/// Converts a position to the index type of the sparse matrices, truncating positions that do
+/// not fit.
+#[inline]
+pub(crate) fn to_index(i: usize) -> I {
+    <I as faer::traits::IndexCore>::truncate(i)
+}
+
+/// Runs `$body` inside a trace-level span named `$name` if the `tracing` feature is enabled,
+/// so that profiles built from the spans attribute the time of the hot paths of the solvers.
+/// Without a body, the span lasts until the end of the enclosing block.
+macro_rules! profile {
+    ($name:literal) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!($name).entered();
+    };
+    ($name:literal, $body:expr) => {{
+        crate::profile!($name);
+        $body
+    }};
+}
+pub(crate) use profile;
 
 pub mod callback;
+pub mod history;
 pub mod interface;
 pub(crate) mod ipm;
+pub mod lcp;
 pub mod linalg;
 pub mod lp;
+pub mod model;
 pub mod nlp;
 pub mod qp;
+pub mod registry;
+pub mod snapshot;
 pub mod stochastic;
 pub mod terminators;
 pub mod utils;
+pub mod verify;
 
 #[cfg(feature = "data-loaders")]
 pub mod data_loaders;
@@ -41,7 +80,7 @@ pub mod data_loaders;
 #[cfg(test)]
 pub mod tests;
 
-pub trait OptionTrait: Any + Sync + Send + DynClone {}
+pub trait OptionTrait: Any + Sync + Send + DynClone + std::fmt::Debug {}
 impl OptionTrait for &'static str {}
 impl OptionTrait for String {}
 impl OptionTrait for bool {}
@@ -63,6 +102,153 @@ impl Clone for Box<dyn OptionTrait> {
     }
 }
 
+/// Metadata of a registered option, as declared with `use_option`. See
+/// [`SolverOptions::iter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct OptionInfo {
+    name: &'static str,
+    type_name: &'static str,
+    default: Option<&'static str>,
+    description: &'static str,
+    scopes: &'static [&'static str],
+    owners: &'static [&'static str],
+}
+
+impl OptionInfo {
+    pub(crate) const fn new(
+        name: &'static str,
+        type_name: &'static str,
+        default: Option<&'static str>,
+        description: &'static str,
+        scopes: &'static [&'static str],
+        owners: &'static [&'static str],
+    ) -> Self {
+        Self {
+            name,
+            type_name,
+            default,
+            description,
+            scopes,
+            owners,
+        }
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Type of the option as written in its declaration, e.g. `usize` or
+    /// `crate::ipm::CorrectorCount`.
+    pub fn get_type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Default value as written in its declaration, parsed with
+    /// [`FromStr`](std::str::FromStr).
+    pub fn get_default(&self) -> Option<&'static str> {
+        self.default
+    }
+
+    pub fn get_description(&self) -> &'static str {
+        self.description
+    }
+
+    /// Scopes of the solvers reading the option, under which it can be set
+    /// for those solvers only, e.g. `lp.mpc`.
+    pub fn get_scopes(&self) -> &'static [&'static str] {
+        self.scopes
+    }
+
+    /// Names of the structs declaring the option, e.g. the solvers and
+    /// terminators reading it.
+    pub fn get_owners(&self) -> &'static [&'static str] {
+        self.owners
+    }
+}
+
+/// Whether an option was set or left at its default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionSource {
+    Default,
+    User,
+}
+
+/// Value of an option consumed by a solve.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EffectiveOption {
+    name: String,
+    value: String,
+    source: OptionSource,
+}
+
+impl EffectiveOption {
+    pub fn new(name: impl Into<String>, value: impl Into<String>, source: OptionSource) -> Self {
+        Self {
+            name: name.into(),
+            value: value.into(),
+            source,
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Value of the option, formatted with [`Debug`](std::fmt::Debug).
+    pub fn get_value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn get_source(&self) -> OptionSource {
+        self.source
+    }
+}
+
+/// Options consumed by the components of a solve, see
+/// [`SolverOptions::get_effective_options`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EffectiveOptions {
+    options: Vec<EffectiveOption>,
+}
+
+impl EffectiveOptions {
+    pub fn new(options: Vec<EffectiveOption>) -> Self {
+        Self { options }
+    }
+
+    pub fn get_options(&self) -> &[EffectiveOption] {
+        &self.options
+    }
+
+    /// Entry of the option `name`, if it was consumed.
+    pub fn get(&self, name: &str) -> Option<&EffectiveOption> {
+        self.options.iter().find(|option| option.name == name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.options.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.options.is_empty()
+    }
+}
+
+impl std::fmt::Display for EffectiveOptions {
+    /// One line per option, with its value and whether it is a default.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let width = self.options.iter().map(|o| o.name.len()).max().unwrap_or(0);
+        for option in &self.options {
+            let source = match option.source {
+                OptionSource::Default => "default",
+                OptionSource::User => "user",
+            };
+            writeln!(f, "{:<width$}  {}  ({source})", option.name, option.value)?;
+        }
+        Ok(())
+    }
+}
+
 /// Status codes for optimization solvers.
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
 pub enum Status {
@@ -83,10 +269,346 @@ pub enum Status {
     IterationLimit,
     /// The solver was interrupted (e.g., by user or signal).
     Interrupted,
+    /// The iterates diverged or became non-finite.
+    Diverged,
+    /// The factorization would exceed the `max_memory` limit.
+    MemoryLimit,
+}
+
+/// Why a solve stopped: its [`Status`], the criterion that stopped it, and the
+/// value that met the criterion, e.g. the final residual on convergence, the
+/// elapsed seconds at a time limit or the growth of the residual on divergence.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerminationInfo {
+    status: Status,
+    reason: String,
+    detail: E,
+}
+
+impl TerminationInfo {
+    pub fn new(status: Status, reason: impl Into<String>, detail: E) -> Self {
+        Self {
+            status,
+            reason: reason.into(),
+            detail,
+        }
+    }
+
+    /// Termination reported by the solver itself, with the largest residual of
+    /// `state` as the detail.
+    fn reported(state: &SolverState) -> Self {
+        let reason = match state.status {
+            Status::Optimal => "solver reported an optimal solution".to_string(),
+            Status::Infeasible => "solver detected infeasibility".to_string(),
+            Status::Unbounded => "solver detected unboundedness".to_string(),
+            Status::Diverged => "solver produced a non-finite iterate".to_string(),
+            status => format!("solver stopped with status {status:?}"),
+        };
+        let reason = match (state.status, state.blocking_primal) {
+            (Status::Optimal, _) | (_, None) => reason,
+            (_, Some(j)) => format!("{reason}, last step blocked by variable {j}"),
+        };
+        Self::new(state.status, reason, Self::residual_of(state))
+    }
+
+    /// Largest primal or dual residual of `state`, the detail of criteria
+    /// without a more specific value.
+    pub fn residual_of<S: StateView + ?Sized>(state: &S) -> E {
+        state
+            .get_primal_feasibility()
+            .norm_max()
+            .max(state.get_dual_feasibility().norm_max())
+    }
+
+    pub fn get_status(&self) -> Status {
+        self.status
+    }
+
+    /// Description of the criterion that stopped the solve.
+    pub fn get_reason(&self) -> &str {
+        &self.reason
+    }
+
+    /// Value that met the criterion, as documented by the terminator or
+    /// solver that reports it.
+    pub fn get_detail(&self) -> E {
+        self.detail
+    }
+}
+
+/// Magnitudes of the data of a program, cached by the program and used to scale
+/// relative stopping tests, e.g. by
+/// [`RelativeConvergenceTerminator`](crate::terminators::RelativeConvergenceTerminator),
+/// independently of the size of the program.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ProblemNorms {
+    constraints: E,
+    rhs: E,
+    objective: E,
+    quadratic: E,
+}
+
+impl ProblemNorms {
+    pub fn new(constraints: E, rhs: E, objective: E, quadratic: E) -> Self {
+        Self {
+            constraints,
+            rhs,
+            objective,
+            quadratic,
+        }
+    }
+
+    /// Largest absolute entry of the constraint matrix.
+    pub fn get_constraint_norm(&self) -> E {
+        self.constraints
+    }
+
+    /// Largest absolute entry of the right-hand side.
+    pub fn get_rhs_norm(&self) -> E {
+        self.rhs
+    }
+
+    /// Largest absolute entry of the linear objective.
+    pub fn get_objective_norm(&self) -> E {
+        self.objective
+    }
+
+    /// Largest absolute entry of the quadratic objective, zero for linear programs.
+    pub fn get_quadratic_norm(&self) -> E {
+        self.quadratic
+    }
+
+    /// Scale `1 + ‖b‖∞` of the primal residual.
+    pub fn get_primal_scale(&self) -> E {
+        E::from(1.) + self.rhs
+    }
+
+    /// Scale `1 + ‖c‖∞` of the dual residual.
+    pub fn get_dual_scale(&self) -> E {
+        E::from(1.) + self.objective
+    }
+
+    /// Ratio of the largest to the smallest nonzero norm, one if all are zero.
+    /// A large spread points to a badly scaled program, whose residuals are
+    /// dominated by a part of its data.
+    pub fn get_spread(&self) -> E {
+        let norms = [self.constraints, self.rhs, self.objective, self.quadratic];
+        let nonzero = norms.iter().copied().filter(|&norm| norm > E::from(0.));
+        let (min, max) = nonzero.fold((E::INFINITY, E::from(0.)), |(min, max), norm| {
+            (min.min(norm), max.max(norm))
+        });
+        if max > E::from(0.) {
+            max / min
+        } else {
+            E::from(1.)
+        }
+    }
+}
+
+/// Magnitude from which bounds are treated as infinite unless configured otherwise. Model files
+/// and modeling tools commonly write `1e20` or `1e30` for a missing bound, which as a finite
+/// bound would enter the barrier terms of the interior-point methods.
+pub const DEFAULT_INFINITY_THRESHOLD: E = 1e20;
+
+/// Replaces the lower bounds at or below `-threshold` and the upper bounds at or above
+/// `threshold` by infinities, and returns the number of bounds replaced.
+pub(crate) fn apply_infinity_threshold(l: &mut Col<E>, u: &mut Col<E>, threshold: E) -> usize {
+    let mut n_replaced = 0;
+    for j in 0..l.nrows() {
+        if l[j].is_finite() && l[j] <= -threshold {
+            l[j] = -E::INFINITY;
+            n_replaced += 1;
+        }
+        if u[j].is_finite() && u[j] >= threshold {
+            u[j] = E::INFINITY;
+            n_replaced += 1;
+        }
+    }
+    n_replaced
+}
+
+/// Largest absolute value of `values`, zero if empty.
+pub(crate) fn max_abs(values: &[E]) -> E {
+    values
+        .iter()
+        .fold(E::from(0.), |norm, value| norm.max(value.abs()))
 }
 
 pub trait OptimizationProgram {
     fn update_residual(&self, state: &mut SolverState);
+
+    /// Norms of the data of the program, `None` if the program does not cache them.
+    fn get_norms(&self) -> Option<ProblemNorms> {
+        None
+    }
+
+    /// Extracts a certificate that the program has no optimal solution from the
+    /// final iterate of a solve, if the program supports it and the iterate
+    /// contains one.
+    fn get_certificate(&self, _state: &SolverState) -> Option<Certificate> {
+        None
+    }
+}
+
+/// Ray that proves that a program has no optimal solution.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Certificate {
+    /// Multipliers `y` of the constraints for which `b^T y` exceeds the maximum
+    /// of `(A^T y)^T x` over the bounds, so that `A x = b` has no solution
+    /// within the bounds.
+    PrimalInfeasible(Col<E>),
+    /// Direction `d` with `A d = 0` that is feasible for the bounds and along
+    /// which the objective decreases.
+    Unbounded(Col<E>),
+}
+
+/// Outcome of [`IterativeSolver::solve_detailed`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolveResult {
+    status: Status,
+    nit: usize,
+    primal_residual: E,
+    dual_residual: E,
+    complementarity: E,
+    duality_gap: Option<E>,
+    solve_time: std::time::Duration,
+    certificate: Option<Certificate>,
+    termination: Option<TerminationInfo>,
+    effective_options: Option<EffectiveOptions>,
+}
+
+impl SolveResult {
+    fn new(
+        status: Status,
+        state: &SolverState,
+        solve_time: std::time::Duration,
+        certificate: Option<Certificate>,
+    ) -> Self {
+        Self {
+            status,
+            nit: state.nit,
+            primal_residual: state.primal_feasibility.norm_max(),
+            dual_residual: state.dual_feasibility.norm_max(),
+            complementarity: state.cs_lower.norm_max().max(state.cs_upper.norm_max()),
+            duality_gap: state.duality_gap,
+            solve_time,
+            certificate,
+            termination: state.termination.clone(),
+            effective_options: None,
+        }
+    }
+
+    pub fn get_status(&self) -> Status {
+        self.status
+    }
+
+    /// Index of the final iteration, as in [`SolverState::get_nit`].
+    pub fn get_nit(&self) -> usize {
+        self.nit
+    }
+
+    /// Largest violation of the constraints at the final iterate.
+    pub fn get_primal_residual(&self) -> E {
+        self.primal_residual
+    }
+
+    /// Largest violation of the stationarity condition at the final iterate.
+    pub fn get_dual_residual(&self) -> E {
+        self.dual_residual
+    }
+
+    /// Largest complementarity product of the bounds at the final iterate.
+    pub fn get_complementarity(&self) -> E {
+        self.complementarity
+    }
+
+    /// Primal minus dual objective value at the final iterate, for solvers
+    /// that evaluate them.
+    pub fn get_duality_gap(&self) -> Option<E> {
+        self.duality_gap
+    }
+
+    /// Wall-clock time of the solve, including the initialization.
+    pub fn get_solve_time(&self) -> std::time::Duration {
+        self.solve_time
+    }
+
+    /// Certificate of infeasibility or unboundedness, found in the final
+    /// iterate of a solve that did not reach optimality.
+    pub fn get_certificate(&self) -> Option<&Certificate> {
+        self.certificate.as_ref()
+    }
+
+    /// Criterion that stopped the solve, as in [`SolverState::get_termination`].
+    pub fn get_termination(&self) -> Option<&TerminationInfo> {
+        self.termination.as_ref()
+    }
+
+    /// Options consumed by the solve, as in [`SolveStats::get_effective_options`].
+    pub fn get_effective_options(&self) -> Option<&EffectiveOptions> {
+        self.effective_options.as_ref()
+    }
+}
+
+/// Outcome of a solve passed to [`Callback::finish`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolveStats {
+    status: Status,
+    n_iterations: usize,
+    solve_time: std::time::Duration,
+    termination: Option<TerminationInfo>,
+    effective_options: Option<EffectiveOptions>,
+}
+
+impl SolveStats {
+    pub fn new(
+        status: Status,
+        n_iterations: usize,
+        solve_time: std::time::Duration,
+        termination: Option<TerminationInfo>,
+    ) -> Self {
+        Self {
+            status,
+            n_iterations,
+            solve_time,
+            termination,
+            effective_options: None,
+        }
+    }
+
+    pub fn with_effective_options(self, effective_options: EffectiveOptions) -> Self {
+        Self {
+            effective_options: Some(effective_options),
+            ..self
+        }
+    }
+
+    /// Status returned by the solve, which a terminator may have set without
+    /// updating the state.
+    pub fn get_status(&self) -> Status {
+        self.status
+    }
+
+    /// Number of iterations performed.
+    pub fn get_n_iterations(&self) -> usize {
+        self.n_iterations
+    }
+
+    /// Wall-clock time of the solve, including the initialization.
+    pub fn get_solve_time(&self) -> std::time::Duration {
+        self.solve_time
+    }
+
+    /// Criterion that stopped the solve.
+    pub fn get_termination(&self) -> Option<&TerminationInfo> {
+        self.termination.as_ref()
+    }
+
+    /// Options consumed by the solve, for solvers that report them through
+    /// [`IterativeSolver::get_options`].
+    pub fn get_effective_options(&self) -> Option<&EffectiveOptions> {
+        self.effective_options.as_ref()
+    }
 }
 
 /// Trait for iterative optimization solvers.
@@ -98,6 +620,12 @@ pub trait IterativeSolver {
 
     fn get_max_iterations(&self) -> usize;
 
+    /// Options the solver was built with, whose consumed entries are reported
+    /// with the outcome of a solve.
+    fn get_options(&self) -> Option<&SolverOptions> {
+        None
+    }
+
     fn initialize(&mut self, state: &mut SolverState) {
         // Default implementation does nothing, but can be overridden by specific solvers
     }
@@ -109,12 +637,15 @@ pub trait IterativeSolver {
         state: &mut SolverState,
         hooks: &mut SolverHooks,
     ) -> Result<Status, Problem> {
+        let start = std::time::Instant::now();
         hooks.callback.init(state);
 
         self.initialize(state);
 
         state.nit = 0;
         state.status = Status::InProgress;
+        state.termination = None;
+        state.norms = self.get_program().get_norms();
 
         let max_iter = {
             let max_iter = self.get_max_iterations();
@@ -125,33 +656,92 @@ pub trait IterativeSolver {
             }
         };
 
+        let finish = |hooks: &mut SolverHooks,
+                      state: &SolverState,
+                      status,
+                      n_iterations,
+                      options: Option<&SolverOptions>| {
+            let mut stats = SolveStats::new(
+                status,
+                n_iterations,
+                start.elapsed(),
+                state.termination.clone(),
+            );
+            if let Some(options) = options {
+                stats = stats.with_effective_options(options.get_effective_options());
+            }
+            hooks.callback.finish(state, &stats);
+        };
         for iter in 0..max_iter {
             state.nit = iter;
-            self.iterate(state)?;
+            if let Err(problem) = profile!("iteration", self.iterate(state)) {
+                finish(hooks, state, Status::Unknown, iter + 1, self.get_options());
+                return Err(problem);
+            }
 
             let status = state.status;
             if status != Status::InProgress {
-                println!(
-                    "Converged in {} iterations with status: {:?}",
-                    iter + 1,
-                    status
-                );
+                if state
+                    .termination
+                    .as_ref()
+                    .is_none_or(|t| t.status != status)
+                {
+                    state.termination = Some(TerminationInfo::reported(state));
+                }
+                finish(hooks, state, status, iter + 1, self.get_options());
                 return Ok(status);
             }
 
             hooks.callback.call(state);
             if let Some(terminator_status) = hooks.terminator.terminate(state) {
-                println!(
-                    "Terminated in {} iterations with status: {:?}",
+                state.termination = Some(hooks.terminator.describe(state, terminator_status));
+                finish(
+                    hooks,
+                    state,
+                    terminator_status,
                     iter + 1,
-                    terminator_status
+                    self.get_options(),
                 );
                 return Ok(terminator_status);
             }
         }
-        println!("Reached maximum iterations without convergence.");
+        state.termination = Some(TerminationInfo::new(
+            Status::IterationLimit,
+            format!("reached the iteration limit of {max_iter}"),
+            TerminationInfo::residual_of(state),
+        ));
+        finish(
+            hooks,
+            state,
+            Status::IterationLimit,
+            max_iter,
+            self.get_options(),
+        );
         Ok(Status::IterationLimit)
     }
+
+    /// Solves like [`IterativeSolver::solve`] and summarizes the final iterate
+    /// in a [`SolveResult`]. If the solve does not reach optimality, the final
+    /// iterate is checked for a [`Certificate`] of infeasibility or
+    /// unboundedness.
+    fn solve_detailed(
+        &mut self,
+        state: &mut SolverState,
+        hooks: &mut SolverHooks,
+    ) -> Result<SolveResult, Problem> {
+        let start = std::time::Instant::now();
+        let status = self.solve(state, hooks)?;
+        let solve_time = start.elapsed();
+
+        let certificate = if status == Status::Optimal {
+            None
+        } else {
+            self.get_program().get_certificate(state)
+        };
+        let mut result = SolveResult::new(status, state, solve_time, certificate);
+        result.effective_options = self.get_options().map(SolverOptions::get_effective_options);
+        Ok(result)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -159,6 +749,8 @@ pub trait IterativeSolver {
 pub struct SolverState {
     status: Status,
     nit: usize,
+    termination: Option<TerminationInfo>,
+    norms: Option<ProblemNorms>,
 
     // Primal-Dual Variables
     x: Col<E>,
@@ -175,6 +767,13 @@ pub struct SolverState {
     // Step size
     alpha_primal: E,
     alpha_dual: E,
+    blocking_primal: Option<usize>,
+    blocking_dual: Option<usize>,
+
+    // Objective values, for solvers that evaluate them
+    primal_obj: Option<E>,
+    dual_obj: Option<E>,
+    duality_gap: Option<E>,
 
     // IPM-specific state
     sigma: Option<E>,
@@ -196,6 +795,8 @@ impl SolverState {
         Self {
             status: Status::InProgress,
             nit: 0,
+            termination: None,
+            norms: None,
 
             x: x.clone(),
             y: y.clone(),
@@ -204,12 +805,18 @@ impl SolverState {
 
             alpha_primal: E::from(1.),
             alpha_dual: E::from(1.),
+            blocking_primal: None,
+            blocking_dual: None,
 
             dual_feasibility: Col::<E>::zeros(x.nrows()),
             primal_feasibility: Col::<E>::zeros(y.nrows()),
             cs_lower: Col::<E>::zeros(z_l.nrows()),
             cs_upper: Col::<E>::zeros(z_u.nrows()),
 
+            primal_obj: None,
+            dual_obj: None,
+            duality_gap: None,
+
             sigma: None,
             mu: None,
             tau: None,
@@ -224,6 +831,51 @@ impl SolverState {
         }
     }
 
+    /// Starting point strictly inside the bounds `l <= x <= u`: the midpoint of
+    /// finite bounds or one away from a single finite bound, zero for free
+    /// variables, zero `y`, and unit multipliers on the finite bounds.
+    ///
+    /// Multipliers of infinite bounds are never updated by the solvers, so they
+    /// start at zero.
+    pub fn new_interior(l: &Col<E>, u: &Col<E>, n_cons: usize) -> Self {
+        let x = Col::from_fn(l.nrows(), |j| match (l[j].is_finite(), u[j].is_finite()) {
+            (true, true) => (l[j] + u[j]) / E::from(2.),
+            (true, false) => l[j] + E::from(1.),
+            (false, true) => u[j] - E::from(1.),
+            (false, false) => E::from(0.),
+        });
+        Self::new(
+            x,
+            Col::zeros(n_cons),
+            Col::from_fn(l.nrows(), |j| if l[j].is_finite() { 1. } else { 0. }),
+            Col::from_fn(u.nrows(), |j| if u[j].is_finite() { -1. } else { 0. }),
+        )
+    }
+
+    /// Moves the iterate the least distance needed to be `margin` inside the
+    /// bounds `l <= x <= u`, or a quarter of the width of bounds closer than
+    /// that, and the multipliers of the finite bounds at least `margin` away
+    /// from zero. Multipliers of infinite bounds are set to zero.
+    ///
+    /// This makes a warm start, e.g. a previous solution with variables at
+    /// their bounds, a valid starting point of the interior-point solvers.
+    pub fn push_to_interior(&mut self, l: &Col<E>, u: &Col<E>, margin: E) {
+        for j in 0..self.x.nrows() {
+            let shift = margin.min((u[j] - l[j]) / E::from(4.));
+            self.x[j] = self.x[j].max(l[j] + shift).min(u[j] - shift);
+            self.z_l[j] = if l[j].is_finite() {
+                self.z_l[j].max(margin)
+            } else {
+                E::from(0.)
+            };
+            self.z_u[j] = if u[j].is_finite() {
+                self.z_u[j].min(-margin)
+            } else {
+                E::from(0.)
+            };
+        }
+    }
+
     pub fn get_status(&self) -> Status {
         self.status
     }
@@ -232,6 +884,17 @@ impl SolverState {
         self.status = status;
     }
 
+    /// Criterion that stopped the last solve: the terminator that fired, the
+    /// iteration limit, or the status the solver reported.
+    pub fn get_termination(&self) -> Option<&TerminationInfo> {
+        self.termination.as_ref()
+    }
+
+    /// Returns the index of the current iteration.
+    pub fn get_nit(&self) -> usize {
+        self.nit
+    }
+
     pub fn get_primal(&self) -> &Col<E> {
         &self.x
     }
@@ -259,6 +922,187 @@ impl SolverState {
     pub fn get_cs_upper(&self) -> &Col<E> {
         &self.cs_upper
     }
+
+    /// Primal objective value of the iterate, if the solver evaluates it.
+    pub fn get_primal_objective(&self) -> Option<E> {
+        self.primal_obj
+    }
+
+    /// Dual objective value of the iterate, if the solver evaluates it.
+    pub fn get_dual_objective(&self) -> Option<E> {
+        self.dual_obj
+    }
+
+    /// Primal minus dual objective value of the iterate.
+    pub fn get_duality_gap(&self) -> Option<E> {
+        self.duality_gap
+    }
+
+    pub(crate) fn set_objective_values(&mut self, primal: E, dual: E) {
+        self.primal_obj = Some(primal);
+        self.dual_obj = Some(dual);
+        self.duality_gap = Some(primal - dual);
+    }
+}
+
+/// Read-only view of an iterate, as seen by terminators and callbacks.
+///
+/// [`SolverState`] implements it for the solvers of this crate. Other crates can
+/// implement [`Terminator`](terminators::Terminator) and [`Callback`] against
+/// this trait without access to the internals of the state.
+pub trait StateView {
+    fn get_status(&self) -> Status;
+
+    /// Returns the index of the current iteration.
+    fn get_nit(&self) -> usize;
+
+    fn get_primal(&self) -> ColRef<'_, E>;
+
+    fn get_dual(&self) -> ColRef<'_, E>;
+
+    /// Multipliers of the lower bounds.
+    fn get_lower_multipliers(&self) -> ColRef<'_, E>;
+
+    /// Multipliers of the upper bounds.
+    fn get_upper_multipliers(&self) -> ColRef<'_, E>;
+
+    fn get_primal_feasibility(&self) -> ColRef<'_, E>;
+
+    fn get_dual_feasibility(&self) -> ColRef<'_, E>;
+
+    fn get_cs_lower(&self) -> ColRef<'_, E>;
+
+    fn get_cs_upper(&self) -> ColRef<'_, E>;
+
+    /// Primal step size of the last iteration.
+    fn get_alpha_primal(&self) -> E;
+
+    /// Dual step size of the last iteration.
+    fn get_alpha_dual(&self) -> E;
+
+    /// Variable whose bound limited the primal step of the last iteration.
+    fn get_blocking_primal(&self) -> Option<usize> {
+        None
+    }
+
+    /// Variable whose bound multiplier limited the dual step of the last
+    /// iteration.
+    fn get_blocking_dual(&self) -> Option<usize> {
+        None
+    }
+
+    /// Barrier parameter, for interior-point methods.
+    fn get_mu(&self) -> Option<E> {
+        None
+    }
+
+    /// Objective value, for solvers that evaluate it.
+    fn get_objective(&self) -> Option<E> {
+        None
+    }
+
+    /// Dual objective value, for solvers that evaluate it.
+    fn get_dual_objective(&self) -> Option<E> {
+        None
+    }
+
+    /// Difference between the primal and dual objective values.
+    fn get_duality_gap(&self) -> Option<E> {
+        Some(self.get_objective()? - self.get_dual_objective()?)
+    }
+
+    /// Norms of the data of the program being solved, see [`ProblemNorms`].
+    fn get_norms(&self) -> Option<ProblemNorms> {
+        None
+    }
+
+    /// Gradient of the Lagrangian with respect to the primal variables at the
+    /// last evaluation, for first-order solvers.
+    fn get_gradient(&self) -> Option<ColRef<'_, E>> {
+        None
+    }
+}
+
+impl StateView for SolverState {
+    fn get_status(&self) -> Status {
+        self.status
+    }
+
+    fn get_nit(&self) -> usize {
+        self.nit
+    }
+
+    fn get_primal(&self) -> ColRef<'_, E> {
+        self.x.as_ref()
+    }
+
+    fn get_dual(&self) -> ColRef<'_, E> {
+        self.y.as_ref()
+    }
+
+    fn get_lower_multipliers(&self) -> ColRef<'_, E> {
+        self.z_l.as_ref()
+    }
+
+    fn get_upper_multipliers(&self) -> ColRef<'_, E> {
+        self.z_u.as_ref()
+    }
+
+    fn get_primal_feasibility(&self) -> ColRef<'_, E> {
+        self.primal_feasibility.as_ref()
+    }
+
+    fn get_dual_feasibility(&self) -> ColRef<'_, E> {
+        self.dual_feasibility.as_ref()
+    }
+
+    fn get_cs_lower(&self) -> ColRef<'_, E> {
+        self.cs_lower.as_ref()
+    }
+
+    fn get_cs_upper(&self) -> ColRef<'_, E> {
+        self.cs_upper.as_ref()
+    }
+
+    fn get_alpha_primal(&self) -> E {
+        self.alpha_primal
+    }
+
+    fn get_alpha_dual(&self) -> E {
+        self.alpha_dual
+    }
+
+    fn get_blocking_primal(&self) -> Option<usize> {
+        self.blocking_primal
+    }
+
+    fn get_blocking_dual(&self) -> Option<usize> {
+        self.blocking_dual
+    }
+
+    fn get_mu(&self) -> Option<E> {
+        self.mu
+    }
+
+    fn get_objective(&self) -> Option<E> {
+        self.primal_obj.or(self.f)
+    }
+
+    fn get_dual_objective(&self) -> Option<E> {
+        self.dual_obj
+    }
+
+    fn get_duality_gap(&self) -> Option<E> {
+        self.duality_gap
+    }
+
+    fn get_norms(&self) -> Option<ProblemNorms> {
+        self.norms
+    }
+
+    fn get_gradient(&self) -> Option<ColRef<'_, E>> {
+        self.dL.as_ref().map(|dl| dl.as_ref())
+    }
 }
 
 pub struct SearchDirection {
@@ -286,11 +1130,128 @@ impl SearchDirection {
     }
 }
 
+/// Primal and dual step lengths of an iteration.
+///
+/// The blocking entries are the variables whose bound, or whose bound
+/// multiplier, limits the step below a full step.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StepLength {
+    primal: E,
+    dual: E,
+    blocking_primal: Option<usize>,
+    blocking_dual: Option<usize>,
+}
+
+impl StepLength {
+    pub fn get_primal(&self) -> E {
+        self.primal
+    }
+
+    pub fn get_dual(&self) -> E {
+        self.dual
+    }
+
+    pub fn get_blocking_primal(&self) -> Option<usize> {
+        self.blocking_primal
+    }
+
+    pub fn get_blocking_dual(&self) -> Option<usize> {
+        self.blocking_dual
+    }
+}
+
+/// Callback and terminator of a solve.
+///
+/// The hooks are `Send`, so a solve, together with its hooks and the program it
+/// borrows, can run on a worker thread or in an async task.
 pub struct SolverHooks {
     callback: Box<dyn Callback>,
     terminator: Box<dyn crate::terminators::Terminator>,
 }
 
+impl SolverHooks {
+    pub fn new(
+        callback: Box<dyn Callback>,
+        terminator: Box<dyn crate::terminators::Terminator>,
+    ) -> Self {
+        Self {
+            callback,
+            terminator,
+        }
+    }
+
+    /// Hooks that print the iteration log configured in `options` and stop on
+    /// convergence, divergence or the `max_time` limit. Fails on an unknown
+    /// column in `log_columns`.
+    pub fn default_for(options: &SolverOptions) -> Result<Self, Problem> {
+        let output = ConvergenceOutput::new(options);
+        if let Some(name) = output.get_unknown_columns().first() {
+            return Err(format!("Unknown log column '{name}'").gloss());
+        }
+        Ok(Self::new(
+            Box::new(output),
+            Box::new(Self::default_terminator(options)),
+        ))
+    }
+
+    /// Default terminators without any output.
+    pub fn silent() -> Self {
+        Self::new(
+            Box::new(NoOpCallback::new()),
+            Box::new(Self::default_terminator(&SolverOptions::new())),
+        )
+    }
+
+    /// Default terminators with the default iteration log.
+    pub fn verbose() -> Self {
+        Self::new(
+            Box::new(ConvergenceOutput::new(&SolverOptions::new())),
+            Box::new(Self::default_terminator(&SolverOptions::new())),
+        )
+    }
+
+    /// Additionally invokes `callback` after the callback of the hooks.
+    pub fn with_callback(self, callback: Box<dyn Callback>) -> Self {
+        Self {
+            callback: Box::new(ChainedCallback::new(self.callback, callback)),
+            terminator: self.terminator,
+        }
+    }
+
+    /// Additionally stops the solver with [`Status::TimeLimit`] after `limit`.
+    pub fn with_time_limit(self, limit: std::time::Duration) -> Self {
+        let options = SolverOptions::new();
+        let time_limit = TimeOutTerminator::new(&options).with_limit(limit);
+        Self {
+            callback: self.callback,
+            terminator: Box::new(ChainedTerminator::new(
+                self.terminator,
+                Box::new(time_limit),
+            )),
+        }
+    }
+
+    /// Additionally stops the solver with [`Status::Interrupted`] once `token`
+    /// is cancelled.
+    pub fn with_cancellation(self, token: CancellationToken) -> Self {
+        Self {
+            callback: self.callback,
+            terminator: Box::new(ChainedTerminator::new(
+                self.terminator,
+                Box::new(CancellationTerminator::new(token)),
+            )),
+        }
+    }
+
+    fn default_terminator(options: &SolverOptions) -> MultiTerminator {
+        MultiTerminator::new(vec![
+            ConvergenceTerminator::new(options).into(),
+            DivergenceTerminator::new(options).into(),
+            TimeOutTerminator::new(options).into(),
+        ])
+    }
+}
+
 impl Clone for SolverHooks {
     fn clone(&self) -> Self {
         Self {
@@ -5,6 +5,9 @@
 //! - The [`SymmetricLinearSolver`] trait for a unified solver interface.
 //! - The [`SimplicialSparseCholesky`] solver for efficient sparse Cholesky factorization.
 //! - Helper functions for matrix permutation and extraction.
+//! - Read access to the factors `P A P^T = L D L^T` of both solvers, through `get_permutation`,
+//!   `get_l`, `get_d` and the faer view `get_ldlt`, for custom refinement, Schur complements or
+//!   inertia tests.
 //! - Unit tests for correctness and accuracy.
 //!
 //! ## Example Usage
@@ -34,12 +37,13 @@
 //! let b = faer::Mat::from_fn(n, 1, |i, _| i as E);
 //! let x = solver.solve(b.as_ref()).unwrap();
 //! ```
-use faer::MatMut;
+use std::sync::Arc;
+
 use faer::dyn_stack::{MemBuffer, MemStack, StackReq};
 use faer::linalg::cholesky::ldlt::factor::LdltRegularization;
 use faer::perm::{Perm, PermRef};
 use faer::prelude::{Reborrow, ReborrowMut};
-use faer::sparse::linalg::amd;
+use faer::sparse::Triplet;
 use faer::sparse::linalg::cholesky::simplicial::{
     self, SimplicialLdltRef, SymbolicSimplicialCholesky,
 };
@@ -47,37 +51,93 @@ use faer::sparse::linalg::cholesky::supernodal::{
     self, SupernodalLdltRef, SymbolicSupernodalCholesky,
 };
 use faer::sparse::{SparseColMat, SparseColMatRef, SymbolicSparseColMat};
-use problemo::{Problem, ProblemResult};
+use faer::traits::{IndexCore, SignedIndex};
+use faer::{Col, MatMut};
+use macros::{explicit_options, use_option};
+use problemo::{Problem, ProblemResult, common::IntoCommonProblem};
+
+use crate::linalg::ordering::{FillReducingOrdering, fill_reducing_permutation};
+use crate::linalg::solver::{
+    LinearSolver, LinearSolverError, MemoryEstimate, Solver, SymbolicAnalysis,
+    SymmetricLinearSolver,
+};
+use crate::{E, I, SolverOptions, to_index};
+
+/// Fill-reducing permutation and symbolic factorization of a matrix, shared between the solvers
+/// of matrices with the same sparsity pattern.
+struct CholeskyAnalysis<S> {
+    perm: Perm<I>,
+    symbolic: S,
+    memory: MemoryEstimate,
+    /// Column pointers of the analyzed pattern, without unused entries.
+    col_ptr: Vec<usize>,
+    /// Row indices of the analyzed pattern.
+    row_idx: Vec<I>,
+}
+
+impl<S> CholeskyAnalysis<S> {
+    fn new(perm: Perm<I>, symbolic: S, memory: MemoryEstimate, mat: SparseColMatRef<I, E>) -> Self {
+        let mut col_ptr = Vec::with_capacity(mat.ncols() + 1);
+        let mut row_idx = Vec::with_capacity(mat.compute_nnz());
+        col_ptr.push(0);
+        for j in 0..mat.ncols() {
+            row_idx.extend_from_slice(mat.row_idx_of_col_raw(j));
+            col_ptr.push(row_idx.len());
+        }
+        Self {
+            perm,
+            symbolic,
+            memory,
+            col_ptr,
+            row_idx,
+        }
+    }
 
-use crate::linalg::solver::{LinearSolver, LinearSolverError, Solver, SymmetricLinearSolver};
-use crate::{E, I};
+    /// Whether `mat` has the analyzed sparsity pattern.
+    fn matches(&self, mat: SparseColMatRef<I, E>) -> bool {
+        self.perm.len() == mat.ncols()
+            && (0..mat.ncols()).all(|j| {
+                mat.row_idx_of_col_raw(j) == &self.row_idx[self.col_ptr[j]..self.col_ptr[j + 1]]
+            })
+    }
+}
 
 /// Sparse Cholesky solver using the simplicial factorization method.
 ///
 /// Stores symbolic analysis, numeric factorization values, permutation, and LDLT factorization
 /// reference. All fields are uninitialized (`None` or empty) until `analyze` and `factorize` are
 /// called.
+#[explicit_options(name = SolverOptions)]
+#[use_option(name = "cholesky_ordering", type_ = crate::linalg::ordering::FillReducingOrdering, default = "amd", description = "Fill-reducing ordering of the sparse Cholesky backends: amd, colamd or natural.")]
 #[allow(non_snake_case)]
 pub struct SimplicialSparseCholesky {
-    /// Symbolic analysis data for the Cholesky factorization (set by `analyze`).
-    symbolic: Option<SymbolicSimplicialCholesky<I>>,
+    /// Fill-reducing permutation, symbolic factorization and memory estimate (set by `analyze`
+    /// or shared by `set_symbolic`).
+    analysis: Option<Arc<CholeskyAnalysis<SymbolicSimplicialCholesky<I>>>>,
     /// Numeric factorization values (set by `factorize`).
     L_values: Vec<E>,
-    /// Permutation used for fill-reducing reordering of the matrix (set by `analyze`).
-    perm: Option<Perm<I>>,
-    /// LDLT factorization reference (set by `factorize`).
-    ldlt: Option<SimplicialLdltRef<'static, I, E>>,
+    /// Permutation used instead of `cholesky_ordering` (set by `with_permutation`).
+    given_perm: Option<Vec<I>>,
+    /// Whether `L_values` holds the factorization for the current `analysis` (set by `factorize`).
+    /// The LDLT view is rebuilt from both on every solve, so the solver never holds a reference
+    /// into its own fields.
+    factorized: bool,
 }
 
 /// Implementation of the `SymmetricLinearSolver` trait for the `SimplicialSparseCholesky` solver.
 /// Provides symbolic analysis, factorization, and solution routines for symmetric sparse matrices.
 impl Solver for SimplicialSparseCholesky {
     fn new() -> Self {
+        Self::new_with_options(&SolverOptions::new())
+    }
+
+    fn new_with_options(options: &SolverOptions) -> Self {
         Self {
-            symbolic: None,
+            analysis: None,
             L_values: Vec::new(),
-            perm: None,
-            ldlt: None,
+            given_perm: None,
+            factorized: false,
+            options: options.into(),
         }
     }
 
@@ -85,45 +145,24 @@ impl Solver for SimplicialSparseCholesky {
     /// and prepares internal state for factorization.
     /// Returns `Ok(())` on success, or an error message on failure.
     fn analyze(&mut self, mat: SparseColMatRef<I, E>) -> Result<(), Problem> {
+        crate::profile!("analyze");
+        // A new analysis invalidates the numeric factorization
+        self.factorized = false;
         let nnz = mat.compute_nnz();
         let dim = mat.ncols();
-        let mat_symbolic = mat.symbolic();
 
         // Fill reducing permutation
-        let (perm_fwd, perm_inv) = {
-            let mut perm = Vec::new();
-            let mut perm_inv = Vec::new();
-            perm.try_reserve_exact(dim)
-                .via(LinearSolverError::MemoryReservation)?;
-            perm_inv
-                .try_reserve_exact(dim)
-                .via(LinearSolverError::MemoryReservation)?;
-            perm.resize(dim, 0usize);
-            perm_inv.resize(dim, 0usize);
-
-            let mut mem = MemBuffer::try_new(amd::order_scratch::<I>(dim, nnz))
-                .via(LinearSolverError::MemoryAllocation)?;
-            amd::order(
-                &mut perm,
-                &mut perm_inv,
-                mat_symbolic,
-                amd::Control::default(),
-                MemStack::new(&mut mem),
-            )
-            .via(LinearSolverError::SymbolicFactorization)?;
-
-            (perm, perm_inv)
-        };
-
-        self.perm = Some(unsafe {
-            Perm::new_unchecked(perm_fwd.into_boxed_slice(), perm_inv.into_boxed_slice())
-        });
+        let perm = fill_reducing_permutation(
+            mat,
+            self.options.cholesky_ordering,
+            self.given_perm.as_deref(),
+        )?;
 
-        let mat_upper = get_mat_upper(mat, self.perm.rb().unwrap().as_ref())?;
+        let mat_upper = get_mat_upper(mat, perm.as_ref())?;
         // let mat_upper = self.get_mat_upper(mat);
 
         // symbolic analysis
-        self.symbolic = Some({
+        let symbolic = {
             let mut mem = MemBuffer::try_new(StackReq::any_of(&[
                 simplicial::prefactorize_symbolic_cholesky_scratch::<I>(dim, nnz),
                 simplicial::factorize_simplicial_symbolic_cholesky_scratch::<I>(dim),
@@ -136,11 +175,11 @@ impl Solver for SimplicialSparseCholesky {
             etree
                 .try_reserve_exact(dim)
                 .via(LinearSolverError::MemoryReservation)?;
-            etree.resize(dim, 0isize);
+            etree.resize(dim, SignedIndex::truncate(0));
             col_counts
                 .try_reserve_exact(dim)
                 .via(LinearSolverError::MemoryReservation)?;
-            col_counts.resize(dim, 0usize);
+            col_counts.resize(dim, to_index(0));
 
             simplicial::prefactorize_symbolic_cholesky(
                 &mut etree,
@@ -157,7 +196,17 @@ impl Solver for SimplicialSparseCholesky {
                 stack,
             )
             .via(LinearSolverError::SymbolicFactorization)?
-        });
+        };
+
+        let len_val = symbolic.len_val();
+        let scratch = simplicial::factorize_simplicial_numeric_ldlt_scratch::<I, E>(dim);
+        let memory = MemoryEstimate::new(
+            nnz,
+            len_val,
+            len_val * size_of::<E>(),
+            matrix_copy_bytes(dim, nnz) + scratch.size_bytes(),
+        );
+        self.analysis = Some(Arc::new(CholeskyAnalysis::new(perm, symbolic, memory, mat)));
 
         // Implementation of analysis
         Ok(())
@@ -166,19 +215,22 @@ impl Solver for SimplicialSparseCholesky {
     /// Performs numeric factorization of the matrix after symbolic analysis.
     /// Returns `Ok(())` on success, or an error message on failure.
     fn factorize(&mut self, mat: SparseColMatRef<I, E>) -> Result<(), Problem> {
-        let symbolic = self
-            .symbolic
+        crate::profile!("factorize");
+        let analysis = self
+            .analysis
             .as_ref()
             .ok_or(LinearSolverError::Uninitialized)?;
+        let symbolic = &analysis.symbolic;
         let dim = mat.ncols();
 
+        self.factorized = false;
         self.L_values = Vec::new();
         self.L_values
             .try_reserve_exact(symbolic.len_val())
             .via(LinearSolverError::MemoryReservation)?;
         self.L_values.resize(symbolic.len_val(), 0.0f64);
 
-        let mat_upper = get_mat_upper(mat, self.perm.rb().unwrap().as_ref())?;
+        let mat_upper = get_mat_upper(mat, analysis.perm.as_ref())?;
         // let mat_upper = self.get_mat_upper(mat);
 
         // numerical factorization
@@ -201,31 +253,50 @@ impl Solver for SimplicialSparseCholesky {
         .via(LinearSolverError::NumericFactorization)?;
         // TODO: consider LdltInfo and LdltErrors
 
-        // SAFETY: We extend the lifetime to 'static because symbolic and L_values are owned by self and
-        // live as long as self.
-        self.ldlt = Some(unsafe {
-            std::mem::transmute::<
-                simplicial::SimplicialLdltRef<'_, I, E>,
-                simplicial::SimplicialLdltRef<'static, I, E>,
-            >(simplicial::SimplicialLdltRef::<'_, I, E>::new(
-                symbolic,
-                &self.L_values,
-            ))
-        });
+        self.factorized = true;
 
         // Implementation of factorization
         Ok(())
     }
 
+    fn estimate_memory(&self) -> Option<MemoryEstimate> {
+        self.analysis.as_ref().map(|analysis| analysis.memory)
+    }
+
+    fn get_symbolic(&self) -> Option<SymbolicAnalysis> {
+        self.analysis.clone().map(SymbolicAnalysis::new)
+    }
+
+    fn set_symbolic(&mut self, mat: SparseColMatRef<I, E>, symbolic: &SymbolicAnalysis) -> bool {
+        match symbolic
+            .downcast()
+            .filter(|analysis: &Arc<CholeskyAnalysis<_>>| analysis.matches(mat))
+        {
+            Some(analysis) => {
+                self.analysis = Some(analysis);
+                self.factorized = false;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn count_negative_pivots(&self) -> Option<usize> {
+        let d = self.get_d()?;
+        Some(d.iter().filter(|&&pivot| pivot < E::from(0.)).count())
+    }
+
     /// Solves the linear system in place for the given right-hand side vector `b`.
     /// Returns `Ok(())` on success, or an error message on failure.
     fn solve_in_place(&mut self, sol: &mut MatMut<E>) -> Result<(), Problem> {
-        let symbolic = self
-            .symbolic
+        crate::profile!("triangular_solve");
+        let ldlt = self.ldlt()?;
+        let symbolic = ldlt.symbolic();
+        let perm = &self
+            .analysis
             .as_ref()
-            .ok_or(LinearSolverError::Uninitialized)?;
-        let perm = self.perm.as_ref().ok_or(LinearSolverError::Uninitialized)?;
-        let ldlt = self.ldlt.as_ref().ok_or(LinearSolverError::Uninitialized)?;
+            .ok_or(LinearSolverError::Uninitialized)?
+            .perm;
 
         let dim = symbolic.ncols();
 
@@ -256,19 +327,74 @@ impl SimplicialSparseCholesky {
     /// # Returns
     ///
     /// A new `SimplicialSparseCholesky` object with:
-    /// - `symbolic`: `None` (symbolic analysis not performed)
-    /// - `perm`: `None` (permutation not set)
+    /// - `analysis`: `None` (symbolic analysis not performed)
+    /// - `given_perm`: `None` (ordered by the `cholesky_ordering` option)
     /// - `L_values`: empty vector (numeric factorization not performed)
-    /// - `ldlt`: `None` (LDLT factorization not performed)
+    /// - `factorized`: `false` (LDLT factorization not performed)
     ///
     /// These fields must be properly initialized by calling `analyze` and `factorize` before use.
     pub fn new() -> Self {
-        Self {
-            symbolic: None,
-            perm: None,
-            L_values: Vec::new(),
-            ldlt: None,
+        <Self as Solver>::new()
+    }
+
+    /// Orders the matrix by `perm`, the columns of the original matrix in their order of
+    /// elimination, instead of the `cholesky_ordering` option.
+    pub fn with_permutation(mut self, perm: Vec<I>) -> Self {
+        self.given_perm = Some(perm);
+        self
+    }
+
+    /// LDLT view of the current factorization, borrowed from `analysis` and `L_values`.
+    fn ldlt(&self) -> Result<SimplicialLdltRef<'_, I, E>, Problem> {
+        let analysis = self
+            .analysis
+            .as_ref()
+            .filter(|_| self.factorized)
+            .ok_or(LinearSolverError::Uninitialized)?;
+        Ok(SimplicialLdltRef::new(&analysis.symbolic, &self.L_values))
+    }
+
+    /// Fill-reducing permutation `P` of the factorization `P A P^T = L D L^T`, where row `i` of
+    /// `P A` is row `P.arrays().0[i]` of `A`. `None` before `analyze`.
+    pub fn get_permutation(&self) -> Option<PermRef<'_, I>> {
+        self.analysis
+            .as_ref()
+            .map(|analysis| analysis.perm.as_ref())
+    }
+
+    /// The faer view of the factorization, whose values hold `D` on the diagonal of `L`. `None`
+    /// before `factorize`.
+    pub fn get_ldlt(&self) -> Option<SimplicialLdltRef<'_, I, E>> {
+        self.ldlt().ok()
+    }
+
+    /// Unit lower triangular factor `L` of `P A P^T = L D L^T`. `None` before `factorize`.
+    pub fn get_l(&self) -> Option<SparseColMat<I, E>> {
+        let ldlt = self.get_ldlt()?;
+        let factor = ldlt.symbolic().factor();
+        let mut triplets = Vec::with_capacity(ldlt.values().len());
+        for j in 0..factor.ncols() {
+            for k in factor.col_range(j) {
+                let i = factor.row_idx()[k].zx();
+                let value = if i == j {
+                    E::from(1.)
+                } else {
+                    ldlt.values()[k]
+                };
+                triplets.push(Triplet::new(to_index(i), to_index(j), value));
+            }
         }
+        SparseColMat::try_new_from_triplets(factor.nrows(), factor.ncols(), &triplets).ok()
+    }
+
+    /// Diagonal factor `D` of `P A P^T = L D L^T`, the pivots of the factorization. `None` before
+    /// `factorize`.
+    pub fn get_d(&self) -> Option<Col<E>> {
+        let ldlt = self.get_ldlt()?;
+        let col_ptr = ldlt.symbolic().col_ptr();
+        Some(Col::from_fn(ldlt.symbolic().ncols(), |j| {
+            ldlt.values()[col_ptr[j].zx()]
+        }))
     }
 }
 
@@ -277,27 +403,37 @@ impl SimplicialSparseCholesky {
 /// Stores symbolic analysis, numeric factorization values, permutation, and LDLT factorization
 /// reference. All fields are uninitialized (`None` or empty) until `analyze` and `factorize` are
 /// called.
+#[explicit_options(name = SolverOptions)]
+#[use_option(name = "cholesky_ordering", type_ = crate::linalg::ordering::FillReducingOrdering, default = "amd", description = "Fill-reducing ordering of the sparse Cholesky backends: amd, colamd or natural.")]
 #[allow(non_snake_case)]
 pub struct SupernodalSparseCholesky {
-    /// Symbolic analysis data for the Cholesky factorization (set by `analyze`).
-    symbolic: Option<SymbolicSupernodalCholesky<I>>,
+    /// Fill-reducing permutation, symbolic factorization and memory estimate (set by `analyze`
+    /// or shared by `set_symbolic`).
+    analysis: Option<Arc<CholeskyAnalysis<SymbolicSupernodalCholesky<I>>>>,
     /// Numeric factorization values (set by `factorize`).
     L_values: Vec<E>,
-    /// Permutation used for fill-reducing reordering of the matrix (set by `analyze`).
-    perm: Option<Perm<I>>,
-    /// LDLT factorization reference (set by `factorize`).
-    ldlt: Option<SupernodalLdltRef<'static, I, E>>,
+    /// Permutation used instead of `cholesky_ordering` (set by `with_permutation`).
+    given_perm: Option<Vec<I>>,
+    /// Whether `L_values` holds the factorization for the current `analysis` (set by `factorize`).
+    /// The LDLT view is rebuilt from both on every solve, so the solver never holds a reference
+    /// into its own fields.
+    factorized: bool,
 }
 
 /// Implementation of the `SymmetricLinearSolver` trait for the `SupernodalSparseCholesky` solver.
 /// Provides symbolic analysis, factorization, and solution routines for symmetric sparse matrices.
 impl Solver for SupernodalSparseCholesky {
     fn new() -> Self {
+        Self::new_with_options(&SolverOptions::new())
+    }
+
+    fn new_with_options(options: &SolverOptions) -> Self {
         Self {
-            symbolic: None,
+            analysis: None,
             L_values: Vec::new(),
-            perm: None,
-            ldlt: None,
+            given_perm: None,
+            factorized: false,
+            options: options.into(),
         }
     }
 
@@ -305,45 +441,24 @@ impl Solver for SupernodalSparseCholesky {
     /// and prepares internal state for factorization.
     /// Returns `Ok(())` on success, or an error message on failure.
     fn analyze(&mut self, mat: SparseColMatRef<I, E>) -> Result<(), Problem> {
+        crate::profile!("analyze");
+        // A new analysis invalidates the numeric factorization
+        self.factorized = false;
         let nnz = mat.compute_nnz();
         let dim = mat.ncols();
-        let mat_symbolic = mat.symbolic();
 
         // Fill reducing permutation
-        let (perm_fwd, perm_inv) = {
-            let mut perm = Vec::new();
-            let mut perm_inv = Vec::new();
-            perm.try_reserve_exact(dim)
-                .via(LinearSolverError::MemoryReservation)?;
-            perm_inv
-                .try_reserve_exact(dim)
-                .via(LinearSolverError::MemoryReservation)?;
-            perm.resize(dim, 0usize);
-            perm_inv.resize(dim, 0usize);
-
-            let mut mem = MemBuffer::try_new(amd::order_scratch::<I>(dim, nnz))
-                .via(LinearSolverError::MemoryAllocation)?;
-            amd::order(
-                &mut perm,
-                &mut perm_inv,
-                mat_symbolic,
-                amd::Control::default(),
-                MemStack::new(&mut mem),
-            )
-            .via(LinearSolverError::SymbolicFactorization)?;
-
-            (perm, perm_inv)
-        };
-
-        self.perm = Some(unsafe {
-            Perm::new_unchecked(perm_fwd.into_boxed_slice(), perm_inv.into_boxed_slice())
-        });
+        let perm = fill_reducing_permutation(
+            mat,
+            self.options.cholesky_ordering,
+            self.given_perm.as_deref(),
+        )?;
 
         // let mat_upper = self.get_mat_upper(mat);
-        let mat_upper = get_mat_upper(mat, self.perm.rb().unwrap().as_ref())?;
+        let mat_upper = get_mat_upper(mat, perm.as_ref())?;
 
         // symbolic analysis
-        self.symbolic = Some({
+        let symbolic = {
             let mut mem = MemBuffer::try_new(StackReq::any_of(&[
                 simplicial::prefactorize_symbolic_cholesky_scratch::<I>(dim, nnz),
                 supernodal::factorize_supernodal_symbolic_cholesky_scratch::<I>(dim),
@@ -356,11 +471,11 @@ impl Solver for SupernodalSparseCholesky {
             etree
                 .try_reserve_exact(dim)
                 .via(LinearSolverError::MemoryReservation)?;
-            etree.resize(dim, 0isize);
+            etree.resize(dim, SignedIndex::truncate(0));
             col_counts
                 .try_reserve_exact(dim)
                 .via(LinearSolverError::MemoryReservation)?;
-            col_counts.resize(dim, 0usize);
+            col_counts.resize(dim, to_index(0));
 
             simplicial::prefactorize_symbolic_cholesky(
                 &mut etree,
@@ -378,7 +493,29 @@ impl Solver for SupernodalSparseCholesky {
                 faer::sparse::linalg::SymbolicSupernodalParams { relax: None },
             )
             .via(LinearSolverError::SymbolicFactorization)?
-        });
+        };
+
+        let len_val = symbolic.len_val();
+        let scratch = supernodal::factorize_supernodal_numeric_ldlt_scratch::<I, E>(
+            &symbolic,
+            faer::Par::Seq,
+            Default::default(),
+        );
+        let max_supernode_size = symbolic
+            .supernode_begin()
+            .iter()
+            .zip(symbolic.supernode_end())
+            .map(|(begin, end)| (*end - *begin).zx())
+            .max()
+            .unwrap_or(0);
+        let memory = MemoryEstimate::new(
+            nnz,
+            len_val,
+            len_val * size_of::<E>(),
+            matrix_copy_bytes(dim, nnz) + scratch.size_bytes(),
+        )
+        .with_supernodes(symbolic.n_supernodes(), max_supernode_size);
+        self.analysis = Some(Arc::new(CholeskyAnalysis::new(perm, symbolic, memory, mat)));
 
         // Implementation of analysis
         Ok(())
@@ -387,19 +524,22 @@ impl Solver for SupernodalSparseCholesky {
     /// Performs numeric factorization of the matrix after symbolic analysis.
     /// Returns `Ok(())` on success, or an error message on failure.
     fn factorize(&mut self, mat: SparseColMatRef<I, E>) -> Result<(), Problem> {
-        let symbolic = self
-            .symbolic
+        crate::profile!("factorize");
+        let analysis = self
+            .analysis
             .as_ref()
             .ok_or(LinearSolverError::Uninitialized)?;
+        let symbolic = &analysis.symbolic;
         let _dim = mat.ncols();
 
+        self.factorized = false;
         self.L_values = Vec::new();
         self.L_values
             .try_reserve_exact(symbolic.len_val())
             .via(LinearSolverError::MemoryReservation)?;
         self.L_values.resize(symbolic.len_val(), 0.0f64);
 
-        let mat_lower = get_mat_lower(mat, self.perm.rb().unwrap().as_ref())?;
+        let mat_lower = get_mat_lower(mat, analysis.perm.as_ref())?;
         // let mat_lower = self.get_mat_lower(mat);
 
         // numerical factorization
@@ -428,17 +568,7 @@ impl Solver for SupernodalSparseCholesky {
         .via(LinearSolverError::NumericFactorization)?;
         // TODO: consider LdltInfo and LdltErrors
 
-        // SAFETY: We extend the lifetime to 'static because symbolic and L_values are owned by self and
-        // live as long as self.
-        self.ldlt = Some(unsafe {
-            std::mem::transmute::<
-                supernodal::SupernodalLdltRef<'_, I, E>,
-                supernodal::SupernodalLdltRef<'static, I, E>,
-            >(supernodal::SupernodalLdltRef::<'_, I, E>::new(
-                symbolic,
-                &self.L_values,
-            ))
-        });
+        self.factorized = true;
 
         // Implementation of factorization
         Ok(())
@@ -451,15 +581,44 @@ impl Solver for SupernodalSparseCholesky {
         self.factorize(mat)
     }
 
+    fn estimate_memory(&self) -> Option<MemoryEstimate> {
+        self.analysis.as_ref().map(|analysis| analysis.memory)
+    }
+
+    fn get_symbolic(&self) -> Option<SymbolicAnalysis> {
+        self.analysis.clone().map(SymbolicAnalysis::new)
+    }
+
+    fn set_symbolic(&mut self, mat: SparseColMatRef<I, E>, symbolic: &SymbolicAnalysis) -> bool {
+        match symbolic
+            .downcast()
+            .filter(|analysis: &Arc<CholeskyAnalysis<_>>| analysis.matches(mat))
+        {
+            Some(analysis) => {
+                self.analysis = Some(analysis);
+                self.factorized = false;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn count_negative_pivots(&self) -> Option<usize> {
+        let d = self.get_d()?;
+        Some(d.iter().filter(|&&pivot| pivot < E::from(0.)).count())
+    }
+
     /// Solves the linear system in place for the given right-hand side vector `b`.
     /// Returns `Ok(())` on success, or an error message on failure.
     fn solve_in_place(&mut self, sol: &mut MatMut<E>) -> Result<(), Problem> {
-        let symbolic = self
-            .symbolic
+        crate::profile!("triangular_solve");
+        let ldlt = self.ldlt()?;
+        let symbolic = ldlt.symbolic();
+        let perm = &self
+            .analysis
             .as_ref()
-            .ok_or(LinearSolverError::Uninitialized)?;
-        let perm = self.perm.as_ref().ok_or(LinearSolverError::Uninitialized)?;
-        let ldlt = self.ldlt.as_ref().ok_or(LinearSolverError::Uninitialized)?;
+            .ok_or(LinearSolverError::Uninitialized)?
+            .perm;
 
         let dim = symbolic.ncols();
 
@@ -494,22 +653,114 @@ impl SupernodalSparseCholesky {
     /// # Returns
     ///
     /// A new `SupernodalSparseCholesky` object with:
-    /// - `symbolic`: `None` (symbolic analysis not performed)
-    /// - `perm`: `None` (permutation not set)
+    /// - `analysis`: `None` (symbolic analysis not performed)
+    /// - `given_perm`: `None` (ordered by the `cholesky_ordering` option)
     /// - `L_values`: empty vector (numeric factorization not performed)
-    /// - `ldlt`: `None` (LDLT factorization not performed)
+    /// - `factorized`: `false` (LDLT factorization not performed)
     ///
     /// These fields must be properly initialized by calling `analyze` and `factorize` before use.
     pub fn new() -> Self {
-        Self {
-            symbolic: None,
-            perm: None,
-            L_values: Vec::new(),
-            ldlt: None,
+        <Self as Solver>::new()
+    }
+
+    /// Orders the matrix by `perm`, the columns of the original matrix in their order of
+    /// elimination, instead of the `cholesky_ordering` option.
+    pub fn with_permutation(mut self, perm: Vec<I>) -> Self {
+        self.given_perm = Some(perm);
+        self
+    }
+
+    /// LDLT view of the current factorization, borrowed from `analysis` and `L_values`.
+    fn ldlt(&self) -> Result<SupernodalLdltRef<'_, I, E>, Problem> {
+        let analysis = self
+            .analysis
+            .as_ref()
+            .filter(|_| self.factorized)
+            .ok_or(LinearSolverError::Uninitialized)?;
+        Ok(SupernodalLdltRef::new(&analysis.symbolic, &self.L_values))
+    }
+
+    /// Fill-reducing permutation `P` of the factorization `P A P^T = L D L^T`, where row `i` of
+    /// `P A` is row `P.arrays().0[i]` of `A`. `None` before `analyze`.
+    pub fn get_permutation(&self) -> Option<PermRef<'_, I>> {
+        self.analysis
+            .as_ref()
+            .map(|analysis| analysis.perm.as_ref())
+    }
+
+    /// The faer view of the factorization, whose supernodes hold `D` on their diagonal. `None`
+    /// before `factorize`.
+    pub fn get_ldlt(&self) -> Option<SupernodalLdltRef<'_, I, E>> {
+        self.ldlt().ok()
+    }
+
+    /// Unit lower triangular factor `L` of `P A P^T = L D L^T`. `None` before `factorize`.
+    pub fn get_l(&self) -> Option<SparseColMat<I, E>> {
+        let ldlt = self.get_ldlt()?;
+        let n = ldlt.symbolic().ncols();
+        let mut triplets = Vec::new();
+        for s in 0..ldlt.symbolic().n_supernodes() {
+            // Dense block of the columns of the supernode, with the rows of its pattern below
+            let supernode = ldlt.supernode(s);
+            let (start, pattern, block) = (supernode.start(), supernode.pattern(), supernode.val());
+            let width = block.ncols();
+            for k in 0..width {
+                let j = to_index(start + k);
+                triplets.push(Triplet::new(j, j, E::from(1.)));
+                for i in k + 1..width {
+                    triplets.push(Triplet::new(to_index(start + i), j, block[(i, k)]));
+                }
+                for (p, &i) in pattern.iter().enumerate() {
+                    triplets.push(Triplet::new(i, j, block[(width + p, k)]));
+                }
+            }
+        }
+        SparseColMat::try_new_from_triplets(n, n, &triplets).ok()
+    }
+
+    /// Diagonal factor `D` of `P A P^T = L D L^T`, the pivots of the factorization. `None` before
+    /// `factorize`.
+    pub fn get_d(&self) -> Option<Col<E>> {
+        let ldlt = self.get_ldlt()?;
+        let mut d = Col::zeros(ldlt.symbolic().ncols());
+        for s in 0..ldlt.symbolic().n_supernodes() {
+            let supernode = ldlt.supernode(s);
+            let block = supernode.val();
+            for k in 0..block.ncols() {
+                d[supernode.start() + k] = block[(k, k)];
+            }
         }
+        Some(d)
     }
 }
 
+/// Memory and fill-in that the symbolic analysis of [`SimplicialSparseCholesky`] predicts for
+/// every [`FillReducingOrdering`] of the symmetric matrix `mat`.
+pub fn compare_orderings(
+    mat: SparseColMatRef<I, E>,
+) -> Result<Vec<(FillReducingOrdering, MemoryEstimate)>, Problem> {
+    FillReducingOrdering::ALL
+        .into_iter()
+        .map(|ordering| {
+            let mut options = SolverOptions::new();
+            options
+                .set_option("cholesky_ordering", ordering)
+                .map_err(|e| e.gloss())?;
+            let mut solver = SimplicialSparseCholesky::new_with_options(&options);
+            solver.analyze(mat)?;
+            let estimate = solver
+                .estimate_memory()
+                .ok_or(LinearSolverError::Uninitialized)?;
+            Ok((ordering, estimate))
+        })
+        .collect()
+}
+
+/// Bytes of the permuted copy of a matrix with `nnz` entries made by every factorization.
+fn matrix_copy_bytes(dim: usize, nnz: usize) -> usize {
+    (dim + 1) * size_of::<I>() + nnz * (size_of::<I>() + size_of::<E>())
+}
+
 fn get_mat_lower(
     mat: SparseColMatRef<I, E>,
     perm: PermRef<I>,
@@ -524,11 +775,11 @@ fn get_mat_lower(
     mat_col_ptrs
         .try_reserve_exact(dim + 1)
         .via(LinearSolverError::MemoryReservation)?;
-    mat_col_ptrs.resize(dim + 1, 0usize);
+    mat_col_ptrs.resize(dim + 1, to_index(0));
     mat_row_indices
         .try_reserve_exact(nnz)
         .via(LinearSolverError::MemoryReservation)?;
-    mat_row_indices.resize(nnz, 0usize);
+    mat_row_indices.resize(nnz, to_index(0));
     mat_values
         .try_reserve_exact(nnz)
         .via(LinearSolverError::MemoryReservation)?;
@@ -569,11 +820,11 @@ fn get_mat_upper(
     mat_col_ptrs
         .try_reserve_exact(dim + 1)
         .via(LinearSolverError::MemoryReservation)?;
-    mat_col_ptrs.resize(dim + 1, 0usize);
+    mat_col_ptrs.resize(dim + 1, to_index(0));
     mat_row_indices
         .try_reserve_exact(nnz)
         .via(LinearSolverError::MemoryReservation)?;
-    mat_row_indices.resize(nnz, 0usize);
+    mat_row_indices.resize(nnz, to_index(0));
     mat_values
         .try_reserve_exact(nnz)
         .via(LinearSolverError::MemoryReservation)?;
@@ -657,10 +908,18 @@ mod tests {
         let n = 3;
         let mut triplets = Vec::new();
         for i in 0..n {
-            triplets.push(faer::sparse::Triplet::new(i, i, 2.0));
+            triplets.push(faer::sparse::Triplet::new(to_index(i), to_index(i), 2.0));
             if i + 1 < n {
-                triplets.push(faer::sparse::Triplet::new(i, i + 1, -1.0));
-                triplets.push(faer::sparse::Triplet::new(i + 1, i, -1.0));
+                triplets.push(faer::sparse::Triplet::new(
+                    to_index(i),
+                    to_index(i + 1),
+                    -1.0,
+                ));
+                triplets.push(faer::sparse::Triplet::new(
+                    to_index(i + 1),
+                    to_index(i),
+                    -1.0,
+                ));
             }
         }
         let mat = faer::sparse::SparseColMat::try_new_from_triplets(n, n, &triplets).unwrap();
@@ -673,4 +932,284 @@ mod tests {
         let mat = mtx::get_matrix_by_name("Trefethen 20b", true);
         test_symmetric_solver(mat, solver_type, 10);
     }
+
+    #[rstest]
+    fn test_factorization_lifetime(
+        #[values(SolverType::SimplicialCholesky, SolverType::SupernodalCholesky)]
+        solver_type: SolverType,
+    ) {
+        let tridiagonal = |n: usize, diagonal: E| {
+            let mut triplets = Vec::new();
+            for i in 0..n {
+                triplets.push(faer::sparse::Triplet::new(
+                    to_index(i),
+                    to_index(i),
+                    diagonal,
+                ));
+                if i + 1 < n {
+                    triplets.push(faer::sparse::Triplet::new(
+                        to_index(i),
+                        to_index(i + 1),
+                        -1.0,
+                    ));
+                    triplets.push(faer::sparse::Triplet::new(
+                        to_index(i + 1),
+                        to_index(i),
+                        -1.0,
+                    ));
+                }
+            }
+            SparseColMat::<I, E>::try_new_from_triplets(n, n, &triplets).unwrap()
+        };
+        let new_solver = || -> Box<dyn SymmetricLinearSolver> {
+            match solver_type {
+                SolverType::SimplicialCholesky => Box::new(SimplicialSparseCholesky::new()),
+                SolverType::SupernodalCholesky => Box::new(SupernodalSparseCholesky::new()),
+            }
+        };
+        let rhs = faer::Mat::<E>::ones(4, 1);
+
+        let mut solver = new_solver();
+        assert!(solver.solve(rhs.as_ref()).is_err());
+
+        // The factorization is rebuilt from owned data, so the solver can be moved
+        let mat = tridiagonal(4, 2.);
+        solver.analyze(mat.as_ref()).unwrap();
+        solver.factorize(mat.as_ref()).unwrap();
+        let mut moved = vec![solver];
+        let solution = moved[0].solve(rhs.as_ref()).unwrap();
+        assert!((&rhs - &mat * &solution).norm_l2() < 1e-10);
+
+        // Refactorizing replaces the factor values in place
+        let mat = tridiagonal(4, 3.);
+        moved[0].factorize(mat.as_ref()).unwrap();
+        let solution = moved[0].solve(rhs.as_ref()).unwrap();
+        assert!((&rhs - &mat * &solution).norm_l2() < 1e-10);
+
+        // A new analysis discards the factorization of the previous one
+        let mut solver = moved.pop().unwrap();
+        let mat = tridiagonal(6, 2.);
+        solver.analyze(mat.as_ref()).unwrap();
+        assert!(solver.solve(faer::Mat::<E>::ones(6, 1).as_ref()).is_err());
+    }
+
+    #[rstest]
+    fn test_orderings(
+        #[values(SolverType::SimplicialCholesky, SolverType::SupernodalCholesky)]
+        solver_type: SolverType,
+    ) {
+        // Arrow matrix with the dense row and column first, which the natural order fills in
+        let n = 6;
+        let mut triplets = vec![faer::sparse::Triplet::new(to_index(0), to_index(0), n as E)];
+        for i in 1..n {
+            triplets.push(faer::sparse::Triplet::new(to_index(i), to_index(i), 2.0));
+            triplets.push(faer::sparse::Triplet::new(to_index(0), to_index(i), 1.0));
+            triplets.push(faer::sparse::Triplet::new(to_index(i), to_index(0), 1.0));
+        }
+        let mat = SparseColMat::<I, E>::try_new_from_triplets(n, n, &triplets).unwrap();
+
+        let statistics = compare_orderings(mat.as_ref()).unwrap();
+        let factor_nnz = |ordering| {
+            statistics
+                .iter()
+                .find(|(o, _)| *o == ordering)
+                .unwrap()
+                .1
+                .get_factor_nnz()
+        };
+        assert_eq!(statistics.len(), FillReducingOrdering::ALL.len());
+        assert!(factor_nnz(FillReducingOrdering::Amd) < factor_nnz(FillReducingOrdering::Natural));
+
+        let rhs = faer::Mat::<E>::ones(n, 1);
+        let reversed = (0..n).rev().map(to_index).collect::<Vec<_>>();
+        for ordering in FillReducingOrdering::ALL {
+            let mut options = SolverOptions::new();
+            options.set_option("cholesky_ordering", ordering).unwrap();
+            let (mut solver, mut given): (
+                Box<dyn SymmetricLinearSolver>,
+                Box<dyn SymmetricLinearSolver>,
+            ) = match solver_type {
+                SolverType::SimplicialCholesky => (
+                    Box::new(SimplicialSparseCholesky::new_with_options(&options)),
+                    Box::new(SimplicialSparseCholesky::new().with_permutation(reversed.clone())),
+                ),
+                SolverType::SupernodalCholesky => (
+                    Box::new(SupernodalSparseCholesky::new_with_options(&options)),
+                    Box::new(SupernodalSparseCholesky::new().with_permutation(reversed.clone())),
+                ),
+            };
+            for solver in [&mut solver, &mut given] {
+                solver.analyze(mat.as_ref()).unwrap();
+                solver.factorize(mat.as_ref()).unwrap();
+                let solution = solver.solve(rhs.as_ref()).unwrap();
+                assert!((&rhs - &mat * &solution).norm_l2() < 1e-10);
+            }
+        }
+    }
+
+    #[rstest]
+    fn test_count_negative_pivots(
+        #[values(SolverType::SimplicialCholesky, SolverType::SupernodalCholesky)]
+        solver_type: SolverType,
+    ) {
+        // Quasi-definite [2, 1, 0; 1, -3, 1; 0, 1, d] with one or two negative eigenvalues
+        let matrix = |d: E| {
+            let triplets = [(0, 0, 2.), (0, 1, 1.), (1, 0, 1.), (1, 1, -3.), (1, 2, 1.)]
+                .into_iter()
+                .chain([(2, 1, 1.), (2, 2, d)])
+                .map(|(i, j, v)| faer::sparse::Triplet::new(to_index(i), to_index(j), v))
+                .collect::<Vec<_>>();
+            SparseColMat::<I, E>::try_new_from_triplets(3, 3, &triplets).unwrap()
+        };
+        let mut solver: Box<dyn SymmetricLinearSolver> = match solver_type {
+            SolverType::SimplicialCholesky => Box::new(SimplicialSparseCholesky::new()),
+            SolverType::SupernodalCholesky => Box::new(SupernodalSparseCholesky::new()),
+        };
+        assert_eq!(solver.count_negative_pivots(), None);
+
+        let mat = matrix(4.);
+        solver.analyze(mat.as_ref()).unwrap();
+        solver.factorize(mat.as_ref()).unwrap();
+        assert_eq!(solver.count_negative_pivots(), Some(1));
+
+        solver.factorize(matrix(-4.).as_ref()).unwrap();
+        assert_eq!(solver.count_negative_pivots(), Some(2));
+    }
+
+    #[rstest]
+    fn test_factor_access(
+        #[values(SolverType::SimplicialCholesky, SolverType::SupernodalCholesky)]
+        solver_type: SolverType,
+    ) {
+        // Arrow matrix with one negative eigenvalue
+        let n = 5;
+        let mut triplets = vec![faer::sparse::Triplet::new(to_index(0), to_index(0), -4.0)];
+        for i in 1..n {
+            triplets.push(faer::sparse::Triplet::new(to_index(i), to_index(i), 3.0));
+            triplets.push(faer::sparse::Triplet::new(to_index(0), to_index(i), 1.0));
+            triplets.push(faer::sparse::Triplet::new(to_index(i), to_index(0), 1.0));
+        }
+        let mat = SparseColMat::<I, E>::try_new_from_triplets(n, n, &triplets).unwrap();
+
+        let (perm, l, d) = match solver_type {
+            SolverType::SimplicialCholesky => {
+                let mut solver = SimplicialSparseCholesky::new();
+                assert!(solver.get_l().is_none() && solver.get_d().is_none());
+                solver.analyze(mat.as_ref()).unwrap();
+                solver.factorize(mat.as_ref()).unwrap();
+                let perm = solver.get_permutation().unwrap().arrays().0.to_vec();
+                (perm, solver.get_l().unwrap(), solver.get_d().unwrap())
+            }
+            SolverType::SupernodalCholesky => {
+                let mut solver = SupernodalSparseCholesky::new();
+                assert!(solver.get_l().is_none() && solver.get_d().is_none());
+                solver.analyze(mat.as_ref()).unwrap();
+                solver.factorize(mat.as_ref()).unwrap();
+                let perm = solver.get_permutation().unwrap().arrays().0.to_vec();
+                (perm, solver.get_l().unwrap(), solver.get_d().unwrap())
+            }
+        };
+
+        // L D L^T reproduces A in the order of the permutation
+        let l = l.to_dense();
+        let ldlt = &l * d.as_diagonal() * l.transpose();
+        let dense = mat.to_dense();
+        for i in 0..n {
+            assert_eq!(l[(i, i)], 1.);
+            for j in 0..n {
+                let expected = dense[(perm[i].zx(), perm[j].zx())];
+                assert!((ldlt[(i, j)] - expected).abs() < 1e-12);
+            }
+        }
+        assert_eq!(d.iter().filter(|&&pivot| pivot < 0.).count(), 1);
+    }
+
+    #[rstest]
+    fn test_set_symbolic(
+        #[values(SolverType::SimplicialCholesky, SolverType::SupernodalCholesky)]
+        solver_type: SolverType,
+    ) {
+        // Diagonal matrices with one off-diagonal pair at (0, 1) or at (0, 2)
+        let build = |k: usize, value: E| {
+            let mut triplets = (0..3)
+                .map(|i| faer::sparse::Triplet::new(to_index(i), to_index(i), 2.0))
+                .collect::<Vec<_>>();
+            triplets.push(faer::sparse::Triplet::new(0, to_index(k), value));
+            triplets.push(faer::sparse::Triplet::new(to_index(k), 0, value));
+            SparseColMat::<I, E>::try_new_from_triplets(3, 3, &triplets).unwrap()
+        };
+        let new_solver = || -> Box<dyn SymmetricLinearSolver> {
+            match solver_type {
+                SolverType::SimplicialCholesky => Box::new(SimplicialSparseCholesky::new()),
+                SolverType::SupernodalCholesky => Box::new(SupernodalSparseCholesky::new()),
+            }
+        };
+        let mut solver = new_solver();
+        solver.analyze(build(1, -1.).as_ref()).unwrap();
+        let symbolic = solver.get_symbolic().unwrap();
+
+        // The same pattern with other values shares the analysis
+        let mut shared = new_solver();
+        assert!(shared.set_symbolic(build(1, 1.).as_ref(), &symbolic));
+
+        // Another pattern with the same dimension and number of entries does not
+        let other = build(2, -1.);
+        let mut fresh = new_solver();
+        assert!(!fresh.set_symbolic(other.as_ref(), &symbolic));
+        fresh
+            .analyze_or_reuse(other.as_ref(), Some(&symbolic))
+            .unwrap();
+        fresh.factorize(other.as_ref()).unwrap();
+        let b = faer::Mat::from_fn(3, 1, |i, _| [1., 2., 3.][i] as E);
+        let x = fresh.solve(b.as_ref()).unwrap();
+        assert!((other.as_ref() * &x - &b).norm_max() < 1e-12);
+    }
+
+    #[rstest]
+    fn test_estimate_memory(
+        #[values(SolverType::SimplicialCholesky, SolverType::SupernodalCholesky)]
+        solver_type: SolverType,
+    ) {
+        let n = 3;
+        let mut triplets = Vec::new();
+        for i in 0..n {
+            triplets.push(faer::sparse::Triplet::new(to_index(i), to_index(i), 2.0));
+            if i + 1 < n {
+                triplets.push(faer::sparse::Triplet::new(
+                    to_index(i),
+                    to_index(i + 1),
+                    -1.0,
+                ));
+                triplets.push(faer::sparse::Triplet::new(
+                    to_index(i + 1),
+                    to_index(i),
+                    -1.0,
+                ));
+            }
+        }
+        let mat = SparseColMat::<I, E>::try_new_from_triplets(n, n, &triplets).unwrap();
+
+        let supernodal = matches!(solver_type, SolverType::SupernodalCholesky);
+        let mut solver: Box<dyn SymmetricLinearSolver> = match solver_type {
+            SolverType::SimplicialCholesky => Box::new(SimplicialSparseCholesky::new()),
+            SolverType::SupernodalCholesky => Box::new(SupernodalSparseCholesky::new()),
+        };
+        assert_eq!(solver.estimate_memory(), None);
+        solver.analyze(mat.as_ref()).unwrap();
+
+        // The factor of a tridiagonal matrix has at least its lower triangle
+        let estimate = solver.estimate_memory().unwrap();
+        assert_eq!(estimate.get_matrix_nnz(), 7);
+        assert!(estimate.get_factor_nnz() >= 5);
+        assert!(estimate.get_fill_ratio() >= 5. / 7.);
+        assert_eq!(
+            estimate.get_factor_bytes(),
+            estimate.get_factor_nnz() * size_of::<E>()
+        );
+        assert!(estimate.get_total_bytes() > estimate.get_factor_bytes());
+        assert_eq!(estimate.get_n_supernodes().is_some(), supernodal);
+        if let Some(size) = estimate.get_max_supernode_size() {
+            assert!((1..=n).contains(&size));
+        }
+    }
 }
@@ -4,6 +4,7 @@
 //! using LU factorization with the Faer library. It includes:
 //! - The [`SimplicialSparseLu`] solver for simplicial LU factorization.
 //! - Support for both symmetric and non-symmetric matrices.
+//! - Read access to the factors `P_r A P_c^T = L U` and both permutations.
 //! - Unit tests for correctness and accuracy.
 //!
 //! ## Example Usage
@@ -35,20 +36,23 @@
 
 use faer::MatMut;
 use faer::dyn_stack::{MemBuffer, MemStack, StackReq};
-use faer::perm::Perm;
+use faer::perm::{Perm, PermRef};
 use faer::prelude::{Reborrow, ReborrowMut};
 use faer::sparse::SparseColMatRef;
-use faer::sparse::linalg::colamd;
 use faer::sparse::linalg::lu::simplicial::{self, SimplicialLu};
+use macros::{explicit_options, use_option};
 use problemo::{Problem, ProblemResult};
 
+use crate::linalg::ordering::fill_reducing_permutation;
 use crate::linalg::solver::{LinearSolver, LinearSolverError, Solver};
-use crate::{E, I};
+use crate::{E, I, SolverOptions, to_index};
 
 /// Sparse LU solver using the simplicial factorization method.
 ///
 /// Stores symbolic analysis, numeric factorization, row and column permutations.
 /// All fields are uninitialized (`None` or empty) until `analyze` and `factorize` are called.
+#[explicit_options(name = SolverOptions)]
+#[use_option(name = "lu_ordering", type_ = crate::linalg::ordering::FillReducingOrdering, default = "colamd", description = "Fill-reducing column ordering of the sparse LU backend: colamd, amd or natural.")]
 #[allow(non_snake_case)]
 pub struct SimplicialSparseLu {
     /// Numeric LU factorization (set by `factorize`).
@@ -57,6 +61,8 @@ pub struct SimplicialSparseLu {
     row_perm: Option<Perm<I>>,
     /// Column permutation for fill reduction (set by `analyze`).
     col_perm: Option<Perm<I>>,
+    /// Column permutation used instead of `lu_ordering` (set by `with_permutation`).
+    given_perm: Option<Vec<I>>,
     /// Matrix dimensions
     nrows: usize,
     ncols: usize,
@@ -64,63 +70,40 @@ pub struct SimplicialSparseLu {
 
 impl Solver for SimplicialSparseLu {
     fn new() -> Self {
+        Self::new_with_options(&SolverOptions::new())
+    }
+
+    fn new_with_options(options: &SolverOptions) -> Self {
         Self {
             lu: None,
             row_perm: None,
             col_perm: None,
+            given_perm: None,
             nrows: 0,
             ncols: 0,
+            options: options.into(),
         }
     }
 
     /// Performs symbolic analysis of the input matrix and computes fill-reducing column permutation.
     fn analyze(&mut self, mat: SparseColMatRef<I, E>) -> Result<(), Problem> {
-        let nrows = mat.nrows();
-        let ncols = mat.ncols();
-        let nnz = mat.compute_nnz();
-
-        self.nrows = nrows;
-        self.ncols = ncols;
-
-        // Fill reducing column permutation using COLAMD
-        let (col_perm_fwd, col_perm_inv) = {
-            let mut perm = Vec::new();
-            let mut perm_inv = Vec::new();
-            perm.try_reserve_exact(ncols)
-                .via(LinearSolverError::MemoryReservation)?;
-            perm_inv
-                .try_reserve_exact(ncols)
-                .via(LinearSolverError::MemoryReservation)?;
-            perm.resize(ncols, 0usize);
-            perm_inv.resize(ncols, 0usize);
-
-            let mut mem = MemBuffer::try_new(colamd::order_scratch::<usize>(nrows, ncols, nnz))
-                .via(LinearSolverError::MemoryAllocation)?;
-
-            colamd::order(
-                &mut perm,
-                &mut perm_inv,
-                mat.symbolic(),
-                colamd::Control::default(),
-                MemStack::new(&mut mem),
-            )
-            .via(LinearSolverError::SymbolicFactorization)?;
-
-            (perm, perm_inv)
-        };
-
-        self.col_perm = Some(unsafe {
-            Perm::new_unchecked(
-                col_perm_fwd.into_boxed_slice(),
-                col_perm_inv.into_boxed_slice(),
-            )
-        });
+        crate::profile!("analyze");
+        self.nrows = mat.nrows();
+        self.ncols = mat.ncols();
+
+        // Fill reducing column permutation
+        self.col_perm = Some(fill_reducing_permutation(
+            mat,
+            self.options.lu_ordering,
+            self.given_perm.as_deref(),
+        )?);
 
         Ok(())
     }
 
     /// Performs numeric LU factorization of the matrix after symbolic analysis.
     fn factorize(&mut self, mat: SparseColMatRef<I, E>) -> Result<(), Problem> {
+        crate::profile!("factorize");
         let col_perm = self
             .col_perm
             .as_ref()
@@ -138,8 +121,8 @@ impl Solver for SimplicialSparseLu {
         row_perm_inv
             .try_reserve_exact(nrows)
             .via(LinearSolverError::MemoryReservation)?;
-        row_perm.resize(nrows, 0usize);
-        row_perm_inv.resize(nrows, 0usize);
+        row_perm.resize(nrows, to_index(0));
+        row_perm_inv.resize(nrows, to_index(0));
 
         // Initialize LU structure
         let mut lu = SimplicialLu::new();
@@ -171,6 +154,7 @@ impl Solver for SimplicialSparseLu {
 
     /// Solves the linear system in place for the given right-hand side vector `b`.
     fn solve_in_place(&mut self, sol: &mut MatMut<E>) -> Result<(), Problem> {
+        crate::profile!("triangular_solve");
         let lu = self.lu.as_ref().ok_or(LinearSolverError::Uninitialized)?;
         let row_perm = self
             .row_perm
@@ -208,13 +192,38 @@ impl Solver for SimplicialSparseLu {
 impl SimplicialSparseLu {
     /// Creates a new instance of `SimplicialSparseLu` with all fields uninitialized.
     pub fn new() -> Self {
-        Self {
-            lu: None,
-            row_perm: None,
-            col_perm: None,
-            nrows: 0,
-            ncols: 0,
-        }
+        <Self as Solver>::new()
+    }
+
+    /// Orders the columns by `perm`, the columns of the original matrix in their order of
+    /// elimination, instead of the `lu_ordering` option.
+    pub fn with_permutation(mut self, perm: Vec<I>) -> Self {
+        self.given_perm = Some(perm);
+        self
+    }
+
+    /// Row permutation `P_r` of the factorization `P_r A P_c^T = L U`, chosen by pivoting, where
+    /// row `i` of `P_r A` is row `P_r.arrays().0[i]` of `A`. `None` before `factorize`.
+    pub fn get_row_permutation(&self) -> Option<PermRef<'_, I>> {
+        self.row_perm.as_ref().map(Perm::as_ref)
+    }
+
+    /// Fill-reducing column permutation `P_c` of the factorization `P_r A P_c^T = L U`. `None`
+    /// before `analyze`.
+    pub fn get_col_permutation(&self) -> Option<PermRef<'_, I>> {
+        self.col_perm.as_ref().map(Perm::as_ref)
+    }
+
+    /// Unit lower triangular factor `L` of `P_r A P_c^T = L U`, with unsorted row indices. `None`
+    /// before `factorize`.
+    pub fn get_l(&self) -> Option<SparseColMatRef<'_, I, E>> {
+        self.lu.as_ref().map(SimplicialLu::l_factor_unsorted)
+    }
+
+    /// Upper triangular factor `U` of `P_r A P_c^T = L U`, with unsorted row indices. `None`
+    /// before `factorize`.
+    pub fn get_u(&self) -> Option<SparseColMatRef<'_, I, E>> {
+        self.lu.as_ref().map(SimplicialLu::u_factor_unsorted)
     }
 }
 
@@ -223,7 +232,7 @@ impl LinearSolver for SimplicialSparseLu {}
 #[cfg(test)]
 mod tests {
     use super::*;
-    use faer::sparse::SparseColMat;
+    use faer::{sparse::SparseColMat, traits::IndexCore};
 
     fn test_lu_solver<T: Solver>(mat: SparseColMat<I, E>, n_count: usize) {
         let mut solver = T::new();
@@ -260,14 +269,103 @@ mod tests {
         let n = 3;
         let mut triplets = Vec::new();
         for i in 0..n {
-            triplets.push(faer::sparse::Triplet::new(i, i, 4.0));
+            triplets.push(faer::sparse::Triplet::new(to_index(i), to_index(i), 4.0));
             if i + 1 < n {
-                triplets.push(faer::sparse::Triplet::new(i, i + 1, -1.0));
-                triplets.push(faer::sparse::Triplet::new(i + 1, i, -1.0));
+                triplets.push(faer::sparse::Triplet::new(
+                    to_index(i),
+                    to_index(i + 1),
+                    -1.0,
+                ));
+                triplets.push(faer::sparse::Triplet::new(
+                    to_index(i + 1),
+                    to_index(i),
+                    -1.0,
+                ));
             }
         }
         let mat = faer::sparse::SparseColMat::try_new_from_triplets(n, n, &triplets).unwrap();
 
         test_lu_solver::<SimplicialSparseLu>(mat, 10);
     }
+
+    #[test]
+    fn test_lu_orderings() {
+        // Nonsymmetric matrix with a dense first column
+        let n = 5;
+        let mut triplets = Vec::new();
+        for i in 0..n {
+            triplets.push(faer::sparse::Triplet::new(to_index(i), to_index(i), 4.0));
+            if i > 0 {
+                triplets.push(faer::sparse::Triplet::new(to_index(i), to_index(0), 1.0));
+                triplets.push(faer::sparse::Triplet::new(
+                    to_index(i - 1),
+                    to_index(i),
+                    -1.0,
+                ));
+            }
+        }
+        let mat = SparseColMat::<I, E>::try_new_from_triplets(n, n, &triplets).unwrap();
+        let rhs = faer::Mat::<E>::ones(n, 1);
+
+        for ordering in ["colamd", "amd", "natural"] {
+            let mut options = SolverOptions::new();
+            options
+                .set_option(
+                    "lu_ordering",
+                    ordering
+                        .parse::<crate::linalg::ordering::FillReducingOrdering>()
+                        .unwrap(),
+                )
+                .unwrap();
+            let mut solver = SimplicialSparseLu::new_with_options(&options);
+            solver.analyze(mat.as_ref()).unwrap();
+            solver.factorize(mat.as_ref()).unwrap();
+            let solution = solver.solve(rhs.as_ref()).unwrap();
+            assert!((&rhs - &mat * &solution).norm_l2() < 1e-10);
+        }
+
+        let mut solver = SimplicialSparseLu::new().with_permutation(vec![4, 3, 2, 1, 0]);
+        solver.analyze(mat.as_ref()).unwrap();
+        solver.factorize(mat.as_ref()).unwrap();
+        let solution = solver.solve(rhs.as_ref()).unwrap();
+        assert!((&rhs - &mat * &solution).norm_l2() < 1e-10);
+
+        let mut solver = SimplicialSparseLu::new().with_permutation(vec![0, 1]);
+        assert!(solver.analyze(mat.as_ref()).is_err());
+    }
+
+    #[test]
+    fn test_factor_access() {
+        let triplets = [
+            (0, 0, 1.),
+            (1, 0, 4.),
+            (2, 0, 2.),
+            (0, 1, 3.),
+            (1, 1, 1.),
+            (2, 2, 5.),
+        ]
+        .map(|(i, j, v)| faer::sparse::Triplet::new(to_index(i), to_index(j), v));
+        let mat = SparseColMat::<I, E>::try_new_from_triplets(3, 3, &triplets).unwrap();
+
+        let mut solver = SimplicialSparseLu::new();
+        assert!(solver.get_l().is_none() && solver.get_col_permutation().is_none());
+        solver.analyze(mat.as_ref()).unwrap();
+        assert!(solver.get_col_permutation().is_some() && solver.get_row_permutation().is_none());
+        solver.factorize(mat.as_ref()).unwrap();
+
+        // L U reproduces the rows and columns of A in the order of the permutations
+        let (rows, cols) = (
+            solver.get_row_permutation().unwrap().arrays().0,
+            solver.get_col_permutation().unwrap().arrays().0,
+        );
+        let (l, u) = (solver.get_l().unwrap(), solver.get_u().unwrap());
+        let lu = l.to_dense() * u.to_dense();
+        let dense = mat.to_dense();
+        for i in 0..3 {
+            for j in 0..3 {
+                let expected = dense[(rows[i].zx(), cols[j].zx())];
+                assert!((lu[(i, j)] - expected).abs() < 1e-12);
+            }
+        }
+    }
 }
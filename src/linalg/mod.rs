@@ -1,5 +1,6 @@
 pub mod cholesky;
 pub mod lu;
+pub mod ordering;
 pub mod solver;
 pub mod vector_ops;
 
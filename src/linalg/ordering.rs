@@ -0,0 +1,184 @@
+//! Fill-reducing orderings of the sparse factorizations.
+//!
+//! The Cholesky backends order the matrix symmetrically by the `cholesky_ordering` option and the
+//! LU backend orders its columns by the `lu_ordering` option:
+//!
+//! - `amd`: approximate minimum degree of the pattern of `A + A^T`, the default for Cholesky.
+//! - `colamd`: column approximate minimum degree, the default for LU.
+//! - `natural`: the identity, which keeps the fill of banded matrices within the band.
+//!
+//! A permutation given to a backend with `with_permutation` takes precedence over the option.
+//! AMD is noticeably suboptimal for some KKT structures, and [`compare_orderings`] reports the
+//! fill-in that the symbolic Cholesky analysis predicts for each ordering.
+//!
+//! [`compare_orderings`]: crate::linalg::cholesky::compare_orderings
+
+use std::str::FromStr;
+
+use faer::dyn_stack::{MemBuffer, MemStack};
+use faer::perm::Perm;
+use faer::sparse::SparseColMatRef;
+use faer::sparse::linalg::{amd, colamd};
+use faer::traits::IndexCore;
+use problemo::{Problem, ProblemResult, common::IntoCommonProblem};
+
+use crate::linalg::solver::LinearSolverError;
+use crate::{E, I, OptionTrait, to_index};
+
+/// Fill-reducing ordering computed during the symbolic analysis.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FillReducingOrdering {
+    /// Approximate minimum degree of the pattern of `A + A^T`.
+    Amd,
+    /// Column approximate minimum degree.
+    Colamd,
+    /// No reordering.
+    Natural,
+}
+
+impl FillReducingOrdering {
+    /// All orderings, in the order of [`compare_orderings`](crate::linalg::cholesky::compare_orderings).
+    pub const ALL: [FillReducingOrdering; 3] = [
+        FillReducingOrdering::Amd,
+        FillReducingOrdering::Colamd,
+        FillReducingOrdering::Natural,
+    ];
+}
+
+impl OptionTrait for FillReducingOrdering {}
+
+impl FromStr for FillReducingOrdering {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "amd" => Ok(FillReducingOrdering::Amd),
+            "colamd" => Ok(FillReducingOrdering::Colamd),
+            "natural" | "none" => Ok(FillReducingOrdering::Natural),
+            _ => Err(format!("Invalid fill-reducing ordering: {}", s)),
+        }
+    }
+}
+
+/// Computes the permutation of the columns of `mat`, taking `given` over `ordering` if present.
+/// Orderings other than `colamd` require a square matrix.
+pub(crate) fn fill_reducing_permutation(
+    mat: SparseColMatRef<I, E>,
+    ordering: FillReducingOrdering,
+    given: Option<&[I]>,
+) -> Result<Perm<I>, Problem> {
+    let (nrows, ncols) = (mat.nrows(), mat.ncols());
+    let nnz = mat.compute_nnz();
+
+    let mut perm = Vec::new();
+    let mut perm_inv = Vec::new();
+    perm.try_reserve_exact(ncols)
+        .via(LinearSolverError::MemoryReservation)?;
+    perm_inv
+        .try_reserve_exact(ncols)
+        .via(LinearSolverError::MemoryReservation)?;
+    perm_inv.resize(ncols, I::MAX);
+
+    match (given, ordering) {
+        (Some(given), _) => {
+            if given.len() != ncols {
+                return Err(format!(
+                    "Permutation has {} entries instead of {}",
+                    given.len(),
+                    ncols
+                )
+                .gloss());
+            }
+            perm.extend_from_slice(given);
+        }
+        (None, FillReducingOrdering::Natural) => perm.extend((0..ncols).map(to_index)),
+        (None, FillReducingOrdering::Amd) => {
+            perm.resize(ncols, to_index(0));
+            let mut mem = MemBuffer::try_new(amd::order_scratch::<I>(ncols, nnz))
+                .via(LinearSolverError::MemoryAllocation)?;
+            amd::order(
+                &mut perm,
+                &mut perm_inv,
+                mat.symbolic(),
+                amd::Control::default(),
+                MemStack::new(&mut mem),
+            )
+            .via(LinearSolverError::SymbolicFactorization)?;
+        }
+        (None, FillReducingOrdering::Colamd) => {
+            perm.resize(ncols, to_index(0));
+            let mut mem = MemBuffer::try_new(colamd::order_scratch::<I>(nrows, ncols, nnz))
+                .via(LinearSolverError::MemoryAllocation)?;
+            colamd::order(
+                &mut perm,
+                &mut perm_inv,
+                mat.symbolic(),
+                colamd::Control::default(),
+                MemStack::new(&mut mem),
+            )
+            .via(LinearSolverError::SymbolicFactorization)?;
+        }
+    }
+
+    // Recompute the inverse, which also checks a given permutation
+    perm_inv.fill(I::MAX);
+    for (new, &old) in perm.iter().enumerate() {
+        if old.zx() >= ncols || perm_inv[old.zx()] != I::MAX {
+            return Err(format!("Invalid permutation entry {} at position {}", old, new).gloss());
+        }
+        perm_inv[old.zx()] = to_index(new);
+    }
+
+    Ok(Perm::new_checked(
+        perm.into_boxed_slice(),
+        perm_inv.into_boxed_slice(),
+        ncols,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::sparse::{SparseColMat, Triplet};
+
+    use super::*;
+
+    #[test]
+    fn test_fill_reducing_permutation() {
+        // Arrow matrix with the dense row and column first
+        let n = 5;
+        let mut triplets = vec![Triplet::new(0, 0, 4.)];
+        for i in 1..n {
+            triplets.push(Triplet::new(to_index(i), to_index(i), 4.));
+            triplets.push(Triplet::new(to_index(0), to_index(i), 1.));
+            triplets.push(Triplet::new(to_index(i), to_index(0), 1.));
+        }
+        let mat = SparseColMat::<I, E>::try_new_from_triplets(n, n, &triplets).unwrap();
+
+        let natural =
+            fill_reducing_permutation(mat.as_ref(), FillReducingOrdering::Natural, None).unwrap();
+        assert_eq!(natural.arrays().0, &[0, 1, 2, 3, 4]);
+
+        // AMD eliminates the dense node last
+        let amd = fill_reducing_permutation(mat.as_ref(), FillReducingOrdering::Amd, None).unwrap();
+        assert_eq!(amd.arrays().0[n - 1], 0);
+
+        let given = [4, 3, 2, 1, 0];
+        let perm = fill_reducing_permutation(mat.as_ref(), FillReducingOrdering::Amd, Some(&given))
+            .unwrap();
+        assert_eq!(perm.arrays().0, &given);
+        assert_eq!(perm.arrays().1, &given);
+
+        assert!(
+            fill_reducing_permutation(
+                mat.as_ref(),
+                FillReducingOrdering::Amd,
+                Some(&[0, 1, 1, 2, 3])
+            )
+            .is_err()
+        );
+        assert!(
+            fill_reducing_permutation(mat.as_ref(), FillReducingOrdering::Amd, Some(&[0])).is_err()
+        );
+        assert_eq!("COLAMD".parse(), Ok(FillReducingOrdering::Colamd));
+    }
+}
@@ -1,68 +1,222 @@
-use std::fmt::Debug;
+//! PARDISO backends (Intel MKL and Panua) for the [`Solver`] interface.
+//!
+//! The matrix is handed to PARDISO in its 1-based CSR format. For the symmetric matrix types only
+//! the upper triangle is passed, with an explicit (possibly zero) entry on every diagonal position
+//! as PARDISO requires. The map from the CSR positions back to the CSC values of the input is
+//! computed once during [`Solver::analyze`], so that numeric factorizations only scatter the new
+//! values and never repeat the symbolic analysis.
+//!
+//! The backend is configured through the following options:
+//!
+//! - `pardiso_matrix_type`: `symmetric_indefinite` (default) for the augmented and normal
+//!   systems of the interior-point methods, `structurally_symmetric` or `nonsymmetric`.
+//! - `pardiso_ordering`: fill-reducing ordering (`iparm[1]`), 0 for minimum degree and 2 for
+//!   METIS.
+//! - `pardiso_refinement_steps`: maximum number of iterative refinement steps (`iparm[7]`).
+//! - `pardiso_pivot_perturbation`: small pivots are perturbed to `10^-k` (`iparm[9]`).
+//! - `pardiso_weighted_matching`: scaling and maximum weighted matching (`iparm[10]` and
+//!   `iparm[12]`), which makes the symmetric indefinite factorization much more robust.
 
-use faer::{Index, Mat, MatRef, sparse::SparseColMatRef};
-use problemo::Problem;
+use std::str::FromStr;
+
+use faer::{MatRef, sparse::SparseColMatRef, traits::IndexCore};
+use macros::{explicit_options, use_option};
+use problemo::{Problem, ProblemResult};
 
 use crate::{
-    E, I,
-    linalg::solver::{LinearSolver, Solver},
+    E, I, OptionTrait, SolverOptions,
+    linalg::solver::{LinearSolver, LinearSolverError, MemoryEstimate, Solver},
 };
 use pardiso_wrapper::{MatrixType, PardisoInterface, Phase};
 
-#[cfg(feature = "mkl")]
+/// Matrix type PARDISO factorizes the input as.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum PardisoMatrixType {
+    /// Real symmetric indefinite matrix, factorized as `LDL^T` with Bunch-Kaufman pivoting.
+    #[default]
+    SymmetricIndefinite,
+    /// Real matrix with a symmetric sparsity pattern, factorized as `LU`.
+    StructurallySymmetric,
+    /// General real matrix, factorized as `LU`.
+    Nonsymmetric,
+}
+
+impl PardisoMatrixType {
+    /// Returns `true` if only the upper triangle of the matrix is passed to PARDISO.
+    pub fn is_symmetric(&self) -> bool {
+        matches!(self, PardisoMatrixType::SymmetricIndefinite)
+    }
+
+    fn as_matrix_type(&self) -> MatrixType {
+        match self {
+            PardisoMatrixType::SymmetricIndefinite => MatrixType::RealSymmetricIndefinite,
+            PardisoMatrixType::StructurallySymmetric => MatrixType::RealStructurallySymmetric,
+            PardisoMatrixType::Nonsymmetric => MatrixType::RealNonsymmetric,
+        }
+    }
+}
+
+impl OptionTrait for PardisoMatrixType {}
+
+impl FromStr for PardisoMatrixType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "symmetric_indefinite" | "symmetric" => Ok(PardisoMatrixType::SymmetricIndefinite),
+            "structurally_symmetric" => Ok(PardisoMatrixType::StructurallySymmetric),
+            "nonsymmetric" => Ok(PardisoMatrixType::Nonsymmetric),
+            _ => Err(format!("Invalid PARDISO matrix type: {}", s)),
+        }
+    }
+}
+
+#[explicit_options(name = SolverOptions)]
+#[use_option(name = "pardiso_matrix_type", type_ = PardisoMatrixType, default = "symmetric_indefinite", description = "Matrix type used by the PARDISO backends.")]
+#[use_option(name = "pardiso_ordering", type_ = i32, default = "2", description = "Fill-reducing ordering of PARDISO (iparm[1]): 0 for minimum degree, 2 for METIS.")]
+#[use_option(name = "pardiso_refinement_steps", type_ = i32, default = "2", description = "Maximum number of iterative refinement steps of PARDISO (iparm[7]).")]
+#[use_option(name = "pardiso_pivot_perturbation", type_ = i32, default = "8", description = "PARDISO perturbs small pivots to 10^-k (iparm[9]).")]
+#[use_option(name = "pardiso_weighted_matching", type_ = bool, default = "true", description = "Enable scaling and maximum weighted matching in PARDISO (iparm[10] and iparm[12]).")]
 pub struct Pardiso<P: PardisoInterface> {
-    col_ptrs: Vec<i32>,
-    row_idx: Vec<i32>,
+    n: usize,
+    row_ptrs: Vec<i32>,
+    col_idx: Vec<i32>,
     values: Vec<E>,
+    /// Position in the CSC values of every CSR entry, `None` for inserted diagonal entries.
+    value_map: Vec<Option<usize>>,
+    /// Number of stored entries of the analyzed CSC matrix.
+    source_nnz: usize,
+    /// Memory reported by the analysis phase.
+    memory: Option<MemoryEstimate>,
     ps: P,
 }
 
+impl<P: PardisoInterface> Pardiso<P> {
+    pub fn get_matrix_type(&self) -> PardisoMatrixType {
+        self.options.pardiso_matrix_type
+    }
+
+    /// Copies the values of `mat` into the CSR values passed to PARDISO.
+    fn scatter_values(&mut self, mat: SparseColMatRef<I, E>) {
+        let val = mat.val();
+        for (value, idx) in self.values.iter_mut().zip(self.value_map.iter()) {
+            *value = idx.map_or(E::from(0.), |idx| val[idx]);
+        }
+    }
+
+    /// Runs the numeric factorization of the current values.
+    fn numeric_factorization(&mut self) -> Result<(), Problem> {
+        self.ps.set_phase(Phase::NumFact);
+        self.ps.pardiso(
+            self.values.as_slice(),
+            self.row_ptrs.as_slice(),
+            self.col_idx.as_slice(),
+            &mut [],
+            &mut [],
+            self.n as i32,
+            1,
+        )?;
+        Ok(())
+    }
+}
+
 impl<P: PardisoInterface> Solver for Pardiso<P> {
     fn new() -> Self
     where
         Self: Sized,
     {
+        Self::new_with_options(&SolverOptions::new())
+    }
+
+    fn new_with_options(options: &SolverOptions) -> Self
+    where
+        Self: Sized,
+    {
+        let options: PardisoInternalOptions = options.into();
+
         let mut ps = P::new().unwrap();
+        // The defaults set by `pardisoinit` depend on the matrix type
+        ps.set_matrix_type(options.pardiso_matrix_type.as_matrix_type());
         ps.pardisoinit();
-        // TODO: set this more dynamically
-        ps.set_matrix_type(MatrixType::RealNonsymmetric);
         ps.set_message_level(pardiso_wrapper::MessageLevel::Off);
+
+        // Use the parameters below instead of the solver defaults
+        let matching = i32::from(options.pardiso_weighted_matching);
+        ps.set_iparm(0, 1);
+        ps.set_iparm(1, options.pardiso_ordering);
+        ps.set_iparm(7, options.pardiso_refinement_steps);
+        ps.set_iparm(9, options.pardiso_pivot_perturbation);
+        ps.set_iparm(10, matching);
+        ps.set_iparm(12, matching);
+        // Report the number of entries of the factor during the analysis
+        ps.set_iparm(17, -1);
+
         Self {
-            col_ptrs: Vec::new(),
-            row_idx: Vec::new(),
+            n: 0,
+            row_ptrs: Vec::new(),
+            col_idx: Vec::new(),
             values: Vec::new(),
+            value_map: Vec::new(),
+            source_nnz: 0,
+            memory: None,
             ps,
+            options,
         }
     }
 
     fn analyze(&mut self, mat: SparseColMatRef<I, E>) -> Result<(), Problem> {
-        (self.col_ptrs, self.row_idx, self.values) = convert_matrix_idx_type(mat);
+        let upper = self.options.pardiso_matrix_type.is_symmetric();
+        (self.row_ptrs, self.col_idx, self.value_map) = csr_structure(mat, upper)?;
+        self.values = vec![E::from(0.); self.value_map.len()];
+        self.scatter_values(mat);
+        self.n = mat.nrows();
+        self.source_nnz = mat.compute_nnz();
+
         self.ps.set_phase(Phase::Analysis);
         self.ps.pardiso(
             self.values.as_slice(),
-            self.col_ptrs.as_slice(),
-            self.row_idx.as_slice(),
+            self.row_ptrs.as_slice(),
+            self.col_idx.as_slice(),
             &mut [],
             &mut [],
-            mat.nrows() as i32,
+            self.n as i32,
             1,
         )?;
+
+        // Peak memory of the analysis (iparm[14]) and memory of the factor (iparm[15] and
+        // iparm[16]), in KiB
+        let kib = |i: usize| self.ps.get_iparm(i).max(0) as usize * 1024;
+        let factor_bytes = kib(15) + kib(16);
+        self.memory = Some(MemoryEstimate::new(
+            self.value_map.len(),
+            self.ps.get_iparm(17).max(0) as usize,
+            factor_bytes,
+            kib(14).saturating_sub(factor_bytes),
+        ));
         Ok(())
     }
 
+    /// Factorizes `mat`, repeating the symbolic analysis only if its dimension or number of
+    /// entries differs from the analyzed matrix.
     fn factorize(&mut self, mat: SparseColMatRef<I, E>) -> Result<(), Problem> {
-        self.values = mat.transpose().val().to_vec(); // Update values for refactorization
-        self.ps.set_phase(Phase::NumFact);
-        self.ps.pardiso(
-            self.values.as_slice(),
-            self.col_ptrs.as_slice(),
-            self.row_idx.as_slice(),
-            &mut [],
-            &mut [],
-            mat.nrows() as i32,
-            1,
-        )?;
-        Ok(())
+        if self.row_ptrs.is_empty() {
+            return Err(LinearSolverError::Uninitialized.into());
+        }
+        if mat.nrows() != self.n || mat.compute_nnz() != self.source_nnz {
+            self.analyze(mat)?;
+        }
+        self.refactorize(mat)
+    }
+
+    /// Factorizes `mat`, which must have the sparsity pattern of the analyzed matrix, without
+    /// repeating the symbolic analysis.
+    fn refactorize(&mut self, mat: SparseColMatRef<I, E>) -> Result<(), Problem> {
+        self.scatter_values(mat);
+        self.numeric_factorization()
+    }
+
+    fn estimate_memory(&self) -> Option<MemoryEstimate> {
+        self.memory
     }
 
     fn solve_in_place(&mut self, b: &mut faer::MatMut<crate::E>) -> Result<(), Problem> {
@@ -74,8 +228,8 @@ impl<P: PardisoInterface> Solver for Pardiso<P> {
 
         self.ps.pardiso(
             self.values.as_slice(),
-            self.col_ptrs.as_slice(),
-            self.row_idx.as_slice(),
+            self.row_ptrs.as_slice(),
+            self.col_idx.as_slice(),
             b_vec.as_mut_slice(),
             x_vec.as_mut_slice(),
             b.nrows() as i32,
@@ -90,49 +244,74 @@ impl<P: PardisoInterface> Solver for Pardiso<P> {
 
 impl<P: PardisoInterface> LinearSolver for Pardiso<P> {}
 
-/// Converts a CSC matrix (faer, 0-based) to CSR format (PARDISO, 1-based).
-fn convert_matrix_idx_type<T>(mat: SparseColMatRef<I, E>) -> (Vec<T>, Vec<T>, Vec<E>)
-where
-    T: TryFrom<usize> + Debug,
-    T::Error: Debug,
-    I: Index,
-{
+/// Computes the 1-based CSR structure of a CSC matrix (faer, 0-based) as used by PARDISO.
+///
+/// Returns the row pointers, the column indices sorted within each row, and the position in the
+/// CSC values of every CSR entry. If `upper` is set, only the upper triangle is kept and missing
+/// diagonal entries are inserted with no source value.
+fn csr_structure(
+    mat: SparseColMatRef<I, E>,
+    upper: bool,
+) -> Result<(Vec<i32>, Vec<i32>, Vec<Option<usize>>), Problem> {
     let n = mat.nrows();
-    let nnz = mat.compute_nnz();
+    let col_ptr = mat.col_ptr();
+    let row_idx = mat.row_idx();
+    let keep = |row: usize, col: usize| !upper || row <= col;
+
+    // Diagonal positions that have to be inserted
+    let mut missing_diag = vec![upper; n];
+    if upper {
+        for col in 0..mat.ncols() {
+            for row in mat.row_idx_of_col(col) {
+                if row == col {
+                    missing_diag[row] = false;
+                }
+            }
+        }
+    }
 
     // Count entries per row to build CSR row pointers
-    let mut row_counts = vec![0usize; n];
-    for &r in mat.row_idx() {
-        row_counts[r] += 1;
+    let mut row_counts: Vec<usize> = missing_diag.iter().map(|&m| usize::from(m)).collect();
+    for col in 0..mat.ncols() {
+        for row in mat.row_idx_of_col(col) {
+            if keep(row, col) {
+                row_counts[row] += 1;
+            }
+        }
     }
 
-    // Build 1-based CSR row pointers (ia)
     let mut ia = vec![0usize; n + 1];
     for i in 0..n {
         ia[i + 1] = ia[i] + row_counts[i];
     }
-    let row_ptrs: Vec<T> = ia.iter().map(|&x| T::try_from(x + 1).unwrap()).collect();
 
-    // Fill CSR column indices (ja) and values, sorted by column within each row
+    // Fill columns in increasing order, so entries are sorted within each row. Inserted diagonal
+    // entries are placed when their column is reached.
+    let nnz = ia[n];
     let mut ja = vec![0usize; nnz];
-    let mut vals = vec![0.0f64; nnz];
-    let mut row_pos = ia[..n].to_vec(); // current fill position per row
+    let mut value_map = vec![None; nnz];
+    let mut row_pos = ia[..n].to_vec();
 
     for col in 0..mat.ncols() {
-        let col_start = mat.col_ptr()[col];
-        let col_end = mat.col_ptr()[col + 1];
-        for idx in col_start..col_end {
-            let row = mat.row_idx()[idx];
-            let pos = row_pos[row];
-            ja[pos] = col + 1; // 1-based column index
-            vals[pos] = mat.val()[idx];
-            row_pos[row] += 1;
+        if col < n && missing_diag[col] {
+            ja[row_pos[col]] = col;
+            row_pos[col] += 1;
+        }
+        for idx in col_ptr[col].zx()..col_ptr[col + 1].zx() {
+            let row = row_idx[idx].zx();
+            if keep(row, col) {
+                ja[row_pos[row]] = col;
+                value_map[row_pos[row]] = Some(idx);
+                row_pos[row] += 1;
+            }
         }
     }
 
-    let col_idx: Vec<T> = ja.iter().map(|&x| T::try_from(x).unwrap()).collect();
+    let to_index = |x: usize| i32::try_from(x + 1).via(LinearSolverError::SymbolicFactorization);
+    let row_ptrs = ia.into_iter().map(to_index).collect::<Result<_, _>>()?;
+    let col_idx = ja.into_iter().map(to_index).collect::<Result<_, _>>()?;
 
-    (row_ptrs, col_idx, vals)
+    Ok((row_ptrs, col_idx, value_map))
 }
 
 #[cfg(feature = "mkl")]
@@ -142,50 +321,91 @@ pub type PanuaPardiso = Pardiso<pardiso_wrapper::PanuaPardisoSolver>;
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use faer::{
+        Mat,
+        sparse::{SparseColMat, Triplet},
+    };
     use rstest::rstest;
 
-    #[cfg(feature = "mkl")]
+    use super::*;
+    use crate::data_loaders::mtx;
+
     #[rstest]
-    fn test_mkl(#[values("Trefethen 20b")] mat_name: &str) {
-        let mat = loaders::mtx::get_matrix_by_name::<I, E>(mat_name, true);
+    fn test_csr_structure(#[values(false, true)] upper: bool) {
+        // [4 1 0; 1 0 2; 0 2 3] with an empty diagonal entry in the middle
+        let mat = SparseColMat::<I, E>::try_new_from_triplets(
+            3,
+            3,
+            &[
+                Triplet::new(0, 0, 4.),
+                Triplet::new(1, 0, 1.),
+                Triplet::new(0, 1, 1.),
+                Triplet::new(2, 1, 2.),
+                Triplet::new(1, 2, 2.),
+                Triplet::new(2, 2, 3.),
+            ],
+        )
+        .unwrap();
+        let (row_ptrs, col_idx, value_map) = csr_structure(mat.as_ref(), upper).unwrap();
+        let values: Vec<E> = value_map
+            .iter()
+            .map(|idx| idx.map_or(0., |idx| mat.val()[idx]))
+            .collect();
 
-        let mut solver = MKLPardiso::new();
+        if upper {
+            assert_eq!(row_ptrs, vec![1, 3, 5, 6]);
+            assert_eq!(col_idx, vec![1, 2, 2, 3, 3]);
+            assert_eq!(values, vec![4., 1., 0., 2., 3.]);
+        } else {
+            assert_eq!(row_ptrs, vec![1, 3, 5, 7]);
+            assert_eq!(col_idx, vec![1, 2, 1, 3, 2, 3]);
+            assert_eq!(values, vec![4., 1., 1., 2., 2., 3.]);
+        }
+    }
+
+    fn test_solver<P: PardisoInterface>(mat_name: &str, matrix_type: &str) {
+        let mat = mtx::get_matrix_by_name::<I, E>(mat_name, true);
+
+        let mut options = SolverOptions::new();
+        options
+            .set_option(
+                "pardiso_matrix_type",
+                PardisoMatrixType::from_str(matrix_type).unwrap(),
+            )
+            .unwrap();
+        let mut solver = Pardiso::<P>::new_with_options(&options);
         solver.analyze(mat.as_ref()).unwrap();
         solver.factorize(mat.as_ref()).unwrap();
 
         let n = mat.ncols();
-        let mut b = Mat::zeros(n, 1);
-        for i in 0..n {
-            b[(i, 0)] = E::from(i as f64 + 1.0); // Example right-hand side
-        }
+        let b = Mat::from_fn(n, 1, |i, _| E::from(i as f64 + 1.0));
+        let x = solver.solve(b.as_ref()).unwrap();
+        assert!((&mat * &x - &b).norm_l2() < 1e-10);
 
+        // Refactorizing with scaled values reuses the analysis
+        let scaled = &mat * faer::Scale(2.);
+        solver.refactorize(scaled.as_ref()).unwrap();
         let x = solver.solve(b.as_ref()).unwrap();
+        assert!((&scaled * &x - &b).norm_l2() < 1e-10);
+    }
 
-        // Here you would typically compare `b` to a known solution or check residuals
-        let err = mat * &x - &b;
-        assert!(err.norm_l2() < 1e-10);
+    #[cfg(feature = "mkl")]
+    #[rstest]
+    fn test_mkl(
+        #[values("Trefethen 20b")] mat_name: &str,
+        #[values("symmetric_indefinite", "structurally_symmetric", "nonsymmetric")]
+        matrix_type: &str,
+    ) {
+        test_solver::<pardiso_wrapper::MKLPardisoSolver>(mat_name, matrix_type);
     }
 
     #[cfg(feature = "panua")]
     #[rstest]
-    fn test_panua(#[values("Trefethen 20b")] mat_name: &str) {
-        let mat = loaders::mtx::get_matrix_by_name::<I, E>(mat_name, true);
-
-        let mut solver = PanuaPardiso::new();
-        solver.analyze(mat.as_ref()).unwrap();
-        solver.factorize(mat.as_ref()).unwrap();
-
-        let n = mat.ncols();
-        let mut b = Mat::zeros(n, 1);
-        for i in 0..n {
-            b[(i, 0)] = E::from(i as f64 + 1.0); // Example right-hand side
-        }
-
-        solver.solve_in_place(&mut b.as_mut()).unwrap();
-
-        // Here you would typically compare `b` to a known solution or check residuals
-        let err = mat * &x - &b;
-        assert!(err.norm_l2() < 1e-10);
+    fn test_panua(
+        #[values("Trefethen 20b")] mat_name: &str,
+        #[values("symmetric_indefinite", "structurally_symmetric", "nonsymmetric")]
+        matrix_type: &str,
+    ) {
+        test_solver::<pardiso_wrapper::PanuaPardisoSolver>(mat_name, matrix_type);
     }
 }
@@ -1,9 +1,13 @@
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+
 use derive_more::{Display, Error};
 use faer::sparse::SparseColMatRef;
 use faer::{Mat, MatMut, MatRef};
 use problemo::Problem;
 
-use crate::{E, I};
+use crate::{E, I, SolverOptions};
 
 #[derive(Debug, Display, Error, PartialEq)]
 pub enum LinearSolverError {
@@ -32,20 +36,155 @@ pub enum LinearSolverError {
     SolveFailed,
 }
 
+/// Memory of a numeric factorization, estimated from the symbolic analysis before the factor is
+/// allocated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryEstimate {
+    /// Number of stored entries of the analyzed matrix.
+    matrix_nnz: usize,
+    /// Number of stored entries of the factor.
+    factor_nnz: usize,
+    /// Bytes of the factor values and the indices allocated with them.
+    factor_bytes: usize,
+    /// Bytes of the scratch space and matrix copies of a factorization.
+    workspace_bytes: usize,
+    /// Number of supernodes and size of the largest one, for supernodal factorizations.
+    supernodes: Option<(usize, usize)>,
+}
+
+impl MemoryEstimate {
+    pub fn new(
+        matrix_nnz: usize,
+        factor_nnz: usize,
+        factor_bytes: usize,
+        workspace_bytes: usize,
+    ) -> Self {
+        Self {
+            matrix_nnz,
+            factor_nnz,
+            factor_bytes,
+            workspace_bytes,
+            supernodes: None,
+        }
+    }
+
+    /// Records `n_supernodes` supernodes, the largest of which has `max_size` columns.
+    pub fn with_supernodes(mut self, n_supernodes: usize, max_size: usize) -> Self {
+        self.supernodes = Some((n_supernodes, max_size));
+        self
+    }
+
+    pub fn get_matrix_nnz(&self) -> usize {
+        self.matrix_nnz
+    }
+
+    pub fn get_factor_nnz(&self) -> usize {
+        self.factor_nnz
+    }
+
+    /// Ratio of the entries of the factor to the entries of the analyzed matrix.
+    pub fn get_fill_ratio(&self) -> E {
+        self.factor_nnz as E / self.matrix_nnz.max(1) as E
+    }
+
+    pub fn get_factor_bytes(&self) -> usize {
+        self.factor_bytes
+    }
+
+    pub fn get_workspace_bytes(&self) -> usize {
+        self.workspace_bytes
+    }
+
+    /// Peak memory of a factorization in bytes.
+    pub fn get_total_bytes(&self) -> usize {
+        self.factor_bytes + self.workspace_bytes
+    }
+
+    pub fn get_n_supernodes(&self) -> Option<usize> {
+        self.supernodes.map(|(n, _)| n)
+    }
+
+    pub fn get_max_supernode_size(&self) -> Option<usize> {
+        self.supernodes.map(|(_, size)| size)
+    }
+}
+
+/// Symbolic analysis of a sparsity pattern, obtained from [`Solver::get_symbolic`] and reused by
+/// [`Solver::set_symbolic`] in other solvers of the same backend.
+///
+/// The analysis is reference counted and immutable, so that cloning it and sharing it between
+/// threads is cheap.
+#[derive(Clone)]
+pub struct SymbolicAnalysis(pub(crate) Arc<dyn Any + Send + Sync>);
+
+impl SymbolicAnalysis {
+    pub(crate) fn new<T: Any + Send + Sync>(analysis: Arc<T>) -> Self {
+        Self(analysis)
+    }
+
+    /// The analysis as the type of the backend that produced it, `None` for another backend.
+    pub(crate) fn downcast<T: Any + Send + Sync>(&self) -> Option<Arc<T>> {
+        self.0.clone().downcast().ok()
+    }
+}
+
+impl fmt::Debug for SymbolicAnalysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SymbolicAnalysis").finish_non_exhaustive()
+    }
+}
+
 /// Trait for symmetric linear solvers supporting matrix analysis, factorization, and solving linear
 /// systems.
 ///
 /// This trait provides a standard interface for working with sparse matrices and right-hand side
-/// vectors. Implementors must call `analyze` and `factorize` before solving systems.
-pub trait Solver {
+/// vectors. Implementors must call `analyze` and `factorize` before solving systems. Solvers are
+/// `Send` so that the interior-point solvers owning them can be moved across threads.
+pub trait Solver: Send {
     fn new() -> Self
     where
         Self: Sized;
 
+    /// Creates a solver configured from `options`. Backends without options of their own use
+    /// the default configuration of `new`.
+    fn new_with_options(options: &SolverOptions) -> Self
+    where
+        Self: Sized,
+    {
+        let _ = options;
+        Self::new()
+    }
+
     /// Performs symbolic analysis of the given sparse matrix and prepares for factorization.
     /// Returns `Ok(())` on success, or an error message on failure.
     fn analyze(&mut self, mat: SparseColMatRef<I, E>) -> Result<(), Problem>;
 
+    /// Symbolic analysis of the last call to `analyze`, to share with other solvers of matrices
+    /// with the same sparsity pattern. `None` before `analyze` or if the backend cannot share it.
+    fn get_symbolic(&self) -> Option<SymbolicAnalysis> {
+        None
+    }
+
+    /// Reuses `symbolic`, the analysis of a matrix with the sparsity pattern of `mat`, instead of
+    /// analyzing `mat`. Returns `false`, leaving the solver unchanged, if the analysis comes from
+    /// another backend or another sparsity pattern than the one of `mat`.
+    fn set_symbolic(&mut self, mat: SparseColMatRef<I, E>, symbolic: &SymbolicAnalysis) -> bool {
+        let _ = (mat, symbolic);
+        false
+    }
+
+    /// Reuses `symbolic` if given and accepted by `set_symbolic`, and analyzes `mat` otherwise.
+    fn analyze_or_reuse(
+        &mut self,
+        mat: SparseColMatRef<I, E>,
+        symbolic: Option<&SymbolicAnalysis>,
+    ) -> Result<(), Problem> {
+        match symbolic {
+            Some(symbolic) if self.set_symbolic(mat, symbolic) => Ok(()),
+            _ => self.analyze(mat),
+        }
+    }
+
     /// Performs numeric factorization of the matrix after symbolic analysis.
     /// Returns `Ok(())` on success, or an error message on failure.
     fn factorize(&mut self, mat: SparseColMatRef<I, E>) -> Result<(), Problem>;
@@ -57,6 +196,19 @@ pub trait Solver {
         self.factorize(mat)
     }
 
+    /// Estimates the memory of `factorize` from the last symbolic analysis. Returns `None` before
+    /// `analyze` or if the backend cannot predict the fill-in.
+    fn estimate_memory(&self) -> Option<MemoryEstimate> {
+        None
+    }
+
+    /// Number of negative pivots of the last factorization, which is the number of negative
+    /// eigenvalues of a symmetric matrix. `None` before `factorize` or if the backend does not
+    /// report its pivots.
+    fn count_negative_pivots(&self) -> Option<usize> {
+        None
+    }
+
     /// Solves the linear system in place for the given right-hand side vector `b`.
     /// Returns `Ok(())` on success, or an error message on failure.
     fn solve_in_place(&mut self, b: &mut MatMut<E>) -> Result<(), Problem>;
@@ -142,14 +294,14 @@ mod tests {
     #[cfg(feature = "mkl")]
     #[rstest]
     fn test_mtx_mkl(#[values("Trefethen 20b")] mat_name: &str) {
-        let solver = pardiso::MKLPardiso::new();
-        test_solver(mat_name, solver);
+        let mut solver = crate::linalg::pardiso::MKLPardiso::new();
+        test_solver(mat_name, &mut solver);
     }
 
     #[cfg(feature = "panua")]
     #[rstest]
     fn test_mtx_panua(#[values("Trefethen 20b")] mat_name: &str) {
-        let solver = pardiso::PanuaSolver::new();
-        test_solver(mat_name, solver);
+        let mut solver = crate::linalg::pardiso::PanuaPardiso::new();
+        test_solver(mat_name, &mut solver);
     }
 }
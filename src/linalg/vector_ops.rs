@@ -1,7 +1,7 @@
 use std::f64::INFINITY;
 use std::ops::{Div, Mul};
 
-use faer::{Col, ColRef, unzip, zip};
+use faer::{Col, ColMut, ColRef, unzip, zip};
 
 use crate::E;
 
@@ -26,7 +26,8 @@ where
 
     zip!(x1, x2, out.as_mut()).for_each(|unzip!(x1, x2, out)| {
         let product = *x1 * *x2;
-        *out = if product == E::INFINITY || product == -E::INFINITY {
+        // An infinite factor is dropped even when the other one is zero
+        *out = if product.is_infinite() || x1.is_infinite() || x2.is_infinite() {
             E::from(0.)
         } else {
             product
@@ -80,8 +81,93 @@ pub(crate) fn is_col_positive<'a>(x: ColRef<'a, E>) -> bool {
     res
 }
 
+/// Computes `y += alpha * x` in place.
+pub fn axpy(alpha: E, x: ColRef<'_, E>, y: ColMut<'_, E>) {
+    zip!(x, y).for_each(|unzip!(x, y)| *y += alpha * *x);
+}
+
+/// Computes `z += x .* y` in place.
+pub fn cwise_multiply_add(x: ColRef<'_, E>, y: ColRef<'_, E>, z: ColMut<'_, E>) {
+    zip!(x, y, z).for_each(|unzip!(x, y, z)| *z += *x * *y);
+}
+
+/// Largest step `alpha` with `x + alpha * dx >= 0`, or infinity if no entry
+/// decreases. Infinite entries of `x` never block.
+pub fn min_ratio(x: ColRef<'_, E>, dx: ColRef<'_, E>) -> E {
+    let mut alpha = E::INFINITY;
+    zip!(x, dx).for_each(|unzip!(x, dx)| {
+        if *dx < E::from(0.) && x.is_finite() {
+            alpha = E::min(alpha, -*x / *dx);
+        }
+    });
+    alpha
+}
+
+/// Largest step `alpha` with `l <= x + alpha * dx <= u` and the index of the
+/// entry that limits it, or infinity and `None` if no entry reaches a bound.
+/// Infinite bounds never block.
+pub fn max_step_to_boundary(
+    x: ColRef<'_, E>,
+    dx: ColRef<'_, E>,
+    l: ColRef<'_, E>,
+    u: ColRef<'_, E>,
+) -> (E, Option<usize>) {
+    let mut alpha = E::INFINITY;
+    let mut blocking = None;
+    zip!(x, dx, l, u).for_each_with_index(|i, unzip!(x, dx, l, u)| {
+        let ratio = if *dx < E::from(0.) && l.is_finite() {
+            (*l - *x) / *dx
+        } else if *dx > E::from(0.) && u.is_finite() {
+            (*u - *x) / *dx
+        } else {
+            return;
+        };
+        if ratio < alpha {
+            alpha = ratio;
+            blocking = Some(i);
+        }
+    });
+    (alpha, blocking)
+}
+
+/// Sum of `z .* (x - bound)` over the entries with a finite bound, i.e. the
+/// complementarity of the multipliers `z` of the bounds.
+pub fn complementarity(x: ColRef<'_, E>, bound: ColRef<'_, E>, z: ColRef<'_, E>) -> E {
+    let mut sum = E::from(0.);
+    zip!(x, bound, z).for_each(|unzip!(x, bound, z)| {
+        if bound.is_finite() {
+            sum += *z * (*x - *bound);
+        }
+    });
+    sum
+}
+
+/// Euclidean norm of the entries of `x` for which `mask` is finite.
+pub fn norm_l2_masked(x: ColRef<'_, E>, mask: ColRef<'_, E>) -> E {
+    let mut sum = E::from(0.);
+    zip!(x, mask).for_each(|unzip!(x, mask)| {
+        if mask.is_finite() {
+            sum += *x * *x;
+        }
+    });
+    sum.sqrt()
+}
+
+/// Maximum absolute value of the entries of `x` for which `mask` is finite.
+pub fn norm_max_masked(x: ColRef<'_, E>, mask: ColRef<'_, E>) -> E {
+    let mut max = E::from(0.);
+    zip!(x, mask).for_each(|unzip!(x, mask)| {
+        if mask.is_finite() {
+            max = E::max(max, x.abs());
+        }
+    });
+    max
+}
+
 #[cfg(test)]
 mod tests {
+    use faer::col;
+
     use super::*;
 
     #[test]
@@ -96,6 +182,18 @@ mod tests {
         assert_eq!(result, expected_col);
     }
 
+    #[test]
+    fn test_cwise_multiply_finite() {
+        let x1_data = [2.0, 0.0, E::INFINITY, -E::INFINITY, 1.0];
+        let x2_data = [3.0, E::INFINITY, 0.0, 2.0, -E::INFINITY];
+        let x1 = Col::from_fn(x1_data.len(), |i| x1_data[i]);
+        let x2 = Col::from_fn(x2_data.len(), |i| x2_data[i]);
+        let result = cwise_multiply_finite(x1.as_ref(), x2.as_ref());
+        let expected = [6.0, 0.0, 0.0, 0.0, 0.0];
+        let expected_col = Col::from_fn(expected.len(), |i| expected[i]);
+        assert_eq!(result, expected_col);
+    }
+
     #[test]
     fn test_col_min() {
         let x1_data = [1.0, 2.0, 3.0];
@@ -115,4 +213,62 @@ mod tests {
         assert!(is_col_positive(x1.as_ref()));
         assert!(!is_col_positive(x2.as_ref()));
     }
+
+    #[test]
+    fn test_fused_kernels() {
+        let x = col![1., -2., 3.];
+        let mut y = col![1., 1., 1.];
+        axpy(2., x.as_ref(), y.as_mut());
+        assert_eq!(y, col![3., -3., 7.]);
+
+        let mut z = col![1., 1., 1.];
+        cwise_multiply_add(x.as_ref(), y.as_ref(), z.as_mut());
+        assert_eq!(z, col![4., 7., 22.]);
+    }
+
+    #[test]
+    fn test_min_ratio() {
+        let x = col![1., 2., E::INFINITY, 0.5];
+        assert_eq!(
+            min_ratio(x.as_ref(), col![-4., -1., -1., 1.].as_ref()),
+            0.25
+        );
+        assert_eq!(
+            min_ratio(x.as_ref(), col![0., 1., -1., 2.].as_ref()),
+            E::INFINITY
+        );
+    }
+
+    #[test]
+    fn test_max_step_to_boundary() {
+        let x = col![1., 2., 0.5, 3.];
+        let l = col![0., -E::INFINITY, 0., 0.];
+        let u = col![E::INFINITY, 4., 1., E::INFINITY];
+        let (alpha, blocking) = max_step_to_boundary(
+            x.as_ref(),
+            col![-1., 1., 1., -2.].as_ref(),
+            l.as_ref(),
+            u.as_ref(),
+        );
+        assert_eq!((alpha, blocking), (0.5, Some(2)));
+
+        // Only infinite bounds in the direction of the step
+        let (alpha, blocking) = max_step_to_boundary(
+            x.as_ref(),
+            col![0., -1., 0., 1.].as_ref(),
+            l.as_ref(),
+            u.as_ref(),
+        );
+        assert_eq!((alpha, blocking), (E::INFINITY, None));
+    }
+
+    #[test]
+    fn test_masked_norms() {
+        let x = col![3., -4., 12.];
+        let l = col![0., 1., -E::INFINITY];
+        let z = col![2., 1., 5.];
+        assert_eq!(complementarity(x.as_ref(), l.as_ref(), z.as_ref()), 6. - 5.);
+        assert_eq!(norm_l2_masked(x.as_ref(), l.as_ref()), 5.);
+        assert_eq!(norm_max_masked(x.as_ref(), l.as_ref()), 4.);
+    }
 }
@@ -0,0 +1,300 @@
+//! Active-set prediction and basis identification from interior-point iterates.
+//!
+//! Near the optimum of a linear program an interior-point iterate separates the
+//! variables by the size of their bound gap compared to their bound multiplier.
+//! For a column `j` with gap `g_j` to its nearest finite bound and multiplier
+//! `z_j` on that bound, the indicator
+//!
+//! ```text
+//! rho_j = g_j / |z_j|
+//! ```
+//!
+//! tends to infinity for columns strictly between their bounds and to zero for
+//! columns at a bound with a nonzero reduced cost. Columns with `rho_j > 1` are
+//! predicted to be basic, the others nonbasic at the bound the gap refers to.
+//!
+//! The predicted basic columns form the support of the solution. For crossover,
+//! [`ActiveSetPrediction::to_basis`] turns the prediction into a simplex
+//! [`Basis`] with exactly one basic variable per row.
+
+use faer::Col;
+
+use crate::{
+    E, SolverState,
+    interface::basis::{Basis, BasisStatus},
+    lp::LinearProgram,
+};
+
+/// Relative norm below which a column counts as linearly dependent on the
+/// columns already in the basis.
+const DEPENDENCE_TOLERANCE: E = 1e-9;
+
+/// Predicted bound status of every column at the optimum.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActiveSetPrediction {
+    status: Vec<BasisStatus>,
+    indicators: Col<E>,
+}
+
+impl ActiveSetPrediction {
+    /// Predicts the active set from the primal-dual iterate `state` of `lp`.
+    pub fn from_state(lp: &LinearProgram, state: &SolverState) -> Self {
+        let (l, u) = (lp.get_lower_bounds(), lp.get_upper_bounds());
+        let n = lp.get_n_vars();
+
+        let mut status = vec![BasisStatus::Basic; n];
+        let indicators = Col::from_fn(n, |j| {
+            if l[j] == u[j] {
+                status[j] = BasisStatus::AtLower;
+                return E::from(0.);
+            }
+
+            let (gap_l, gap_u) = (state.x[j] - l[j], u[j] - state.x[j]);
+            let (gap, multiplier, bound) = if gap_l <= gap_u {
+                (gap_l, state.z_l[j], BasisStatus::AtLower)
+            } else {
+                (gap_u, state.z_u[j], BasisStatus::AtUpper)
+            };
+            // Free columns have infinite gaps and are always basic
+            let indicator = if gap.is_infinite() {
+                E::INFINITY
+            } else {
+                gap.max(E::from(0.)) / multiplier.abs()
+            };
+            if indicator <= E::from(1.) {
+                status[j] = bound;
+            }
+            indicator
+        });
+
+        Self { status, indicators }
+    }
+
+    /// Predicted status of every column.
+    pub fn get_status(&self) -> &[BasisStatus] {
+        &self.status
+    }
+
+    /// Ratio of bound gap to bound multiplier of every column. Fixed columns
+    /// have indicator zero and free columns infinity.
+    pub fn get_indicators(&self) -> &Col<E> {
+        &self.indicators
+    }
+
+    /// Columns predicted to lie strictly between their bounds.
+    pub fn get_support(&self) -> Vec<usize> {
+        (0..self.status.len())
+            .filter(|&j| self.status[j] == BasisStatus::Basic)
+            .collect()
+    }
+
+    /// Columns predicted to be at one of their bounds.
+    pub fn get_nonbasic(&self) -> Vec<usize> {
+        (0..self.status.len())
+            .filter(|&j| self.status[j] != BasisStatus::Basic)
+            .collect()
+    }
+
+    /// Builds a simplex basis of `lp` from the prediction.
+    ///
+    /// Columns are added to the basis in decreasing order of their indicator,
+    /// starting with the predicted support, as long as they are linearly
+    /// independent of the columns already chosen. When the support has fewer
+    /// independent columns than `lp` has rows, the basis is completed with row
+    /// logicals. Nonbasic columns are placed at the bound of their prediction.
+    ///
+    /// The independence test is dense in the number of rows.
+    pub fn to_basis(&self, lp: &LinearProgram) -> Basis {
+        let m = lp.get_n_cons();
+        let a = lp.get_constraint_matrix();
+
+        let mut col_status = self.status.clone();
+        let mut row_status = vec![BasisStatus::AtLower; m];
+        // Support columns left out of the basis are placed at a finite bound
+        for (j, status) in col_status.iter_mut().enumerate() {
+            if *status == BasisStatus::Basic {
+                *status = finite_bound(lp, j);
+            }
+        }
+
+        let mut support = self.get_support();
+        support.sort_by(|&i, &j| self.indicators[j].total_cmp(&self.indicators[i]));
+
+        // Orthonormal basis of the span of the chosen columns
+        let mut span: Vec<Col<E>> = Vec::with_capacity(m);
+        let mut add_independent = |v: Col<E>| -> bool {
+            let norm = v.norm_l2();
+            let mut r = v;
+            // Two passes of Gram-Schmidt keep the projection accurate
+            for _ in 0..2 {
+                for q in span.iter() {
+                    let coefficient = q.transpose() * &r;
+                    r -= coefficient * q;
+                }
+            }
+            let residual = r.norm_l2();
+            if norm == E::from(0.) || residual <= DEPENDENCE_TOLERANCE * norm {
+                return false;
+            }
+            span.push(r / residual);
+            true
+        };
+
+        for j in support {
+            if span_full(m, &col_status, &row_status) {
+                break;
+            }
+            let mut v = Col::zeros(m);
+            for (i, value) in a.row_idx_of_col(j).zip(a.val_of_col(j)) {
+                v[i] += *value;
+            }
+            if add_independent(v) {
+                col_status[j] = BasisStatus::Basic;
+            }
+        }
+        for i in 0..m {
+            if span_full(m, &col_status, &row_status) {
+                break;
+            }
+            let mut v = Col::zeros(m);
+            v[i] = E::from(1.);
+            if add_independent(v) {
+                row_status[i] = BasisStatus::Basic;
+            }
+        }
+
+        Basis::from_status(col_status, row_status)
+    }
+}
+
+/// Nonbasic status of a column without a predicted bound: its lower bound if
+/// finite, else its upper bound.
+fn finite_bound(lp: &LinearProgram, j: usize) -> BasisStatus {
+    if lp.get_lower_bounds()[j].is_finite() || lp.get_upper_bounds()[j].is_infinite() {
+        BasisStatus::AtLower
+    } else {
+        BasisStatus::AtUpper
+    }
+}
+
+fn span_full(m: usize, col_status: &[BasisStatus], row_status: &[BasisStatus]) -> bool {
+    col_status
+        .iter()
+        .chain(row_status.iter())
+        .filter(|s| **s == BasisStatus::Basic)
+        .count()
+        >= m
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::{
+        col,
+        sparse::{SparseColMat, Triplet},
+    };
+    use rstest::rstest;
+
+    use super::*;
+    use crate::{
+        SolverHooks, SolverOptions, Status, callback::NoOpCallback,
+        terminators::ComplementarityTerminator,
+    };
+
+    /// `min -x_0 - 2 x_1` subject to `x_0 + x_1 + s = b`, `x_0 <= 1` and `x_1 <= 1.5`.
+    fn build_lp(b: E) -> LinearProgram {
+        let a = SparseColMat::try_new_from_triplets(
+            1,
+            3,
+            &[
+                Triplet::new(0, 0, 1.),
+                Triplet::new(0, 1, 1.),
+                Triplet::new(0, 2, 1.),
+            ],
+        )
+        .unwrap();
+        LinearProgram::new(
+            col![-1., -2., 0.],
+            a,
+            col![b],
+            col![0., 0., 0.],
+            col![1., 1.5, E::INFINITY],
+        )
+    }
+
+    fn solve(lp: &LinearProgram) -> SolverState {
+        let mut state = SolverState::new(
+            col![0.5, 0.75, 1.],
+            Col::zeros(1),
+            col![1., 1., 1.],
+            col![-1., -1., 0.],
+        );
+        let options = SolverOptions::new();
+        let mut hooks = SolverHooks {
+            callback: Box::new(NoOpCallback::new()),
+            terminator: Box::new(ComplementarityTerminator::new(&options)),
+        };
+        let mut solver = lp.solver_builder().with_options(options).build().unwrap();
+        assert_eq!(
+            solver.solve(&mut state, &mut hooks).unwrap(),
+            Status::Optimal
+        );
+        state
+    }
+
+    #[rstest]
+    #[case(2., [BasisStatus::Basic, BasisStatus::AtUpper, BasisStatus::AtLower])]
+    #[case(1., [BasisStatus::AtLower, BasisStatus::Basic, BasisStatus::AtLower])]
+    #[case(3., [BasisStatus::AtUpper, BasisStatus::AtUpper, BasisStatus::Basic])]
+    fn test_prediction(#[case] b: E, #[case] expected: [BasisStatus; 3]) {
+        let lp = build_lp(b);
+        let prediction = ActiveSetPrediction::from_state(&lp, &solve(&lp));
+        assert_eq!(prediction.get_status(), &expected);
+
+        let support = prediction.get_support();
+        assert_eq!(support.len(), 1);
+        assert!(prediction.get_indicators()[support[0]] > 1.);
+        assert_eq!(prediction.get_nonbasic().len(), 2);
+
+        // The support column is the only basic variable
+        let basis = prediction.to_basis(&lp);
+        assert_eq!(basis.num_basic(), 1);
+        assert_eq!(basis.get_col_status(), &expected);
+        assert_eq!(basis.get_row_status(), &[BasisStatus::AtLower]);
+    }
+
+    #[test]
+    fn test_degenerate_basis() {
+        // At b = 2.5 every column is at a bound, so the row logical becomes basic
+        let lp = build_lp(2.5);
+        let state = SolverState::new(
+            col![1., 1.5, 0.],
+            Col::zeros(1),
+            col![0., 0., 1.],
+            col![-1., -2., 0.],
+        );
+        let prediction = ActiveSetPrediction::from_state(&lp, &state);
+        assert!(prediction.get_support().is_empty());
+
+        let basis = prediction.to_basis(&lp);
+        assert_eq!(basis.num_basic(), 1);
+        assert_eq!(basis.get_row_status(), &[BasisStatus::Basic]);
+    }
+
+    #[test]
+    fn test_dependent_support() {
+        // Both columns are between their bounds but only one fits in the basis
+        let lp = build_lp(2.);
+        let state = SolverState::new(
+            col![0.5, 0.5, 1.],
+            Col::zeros(1),
+            col![1e-3, 1e-3, 1e-3],
+            col![-1e-3, -1e-3, 0.],
+        );
+        let prediction = ActiveSetPrediction::from_state(&lp, &state);
+        assert_eq!(prediction.get_support().len(), 3);
+
+        let basis = prediction.to_basis(&lp);
+        assert_eq!(basis.num_basic(), 1);
+        assert_eq!(basis.get_row_status(), &[BasisStatus::AtLower]);
+    }
+}
@@ -0,0 +1,350 @@
+//! Batch solves of a linear program over many right-hand sides or objectives.
+//!
+//! Simulation and scenario evaluation solve the same [`LinearProgram`] for a
+//! batch of right-hand sides `b_k` or objectives `c_k`, given as the columns of
+//! a matrix. All programs of a batch share the pattern of `A` and the bounds,
+//! so the symbolic analysis of the KKT system is computed once and shared by
+//! every solve through a [`SymbolicCache`].
+//!
+//! Each solve is warm started from the solution of the previous scenario, in
+//! the same way as the solves of [`parametric`](crate::lp::parametric), so
+//! that neighbouring scenarios should be close. A warm start that does not
+//! reach optimality is retried from the default starting point. If
+//! `batch_parallel` is set, the batch is split into one contiguous chunk per
+//! thread, and the chunks are solved in parallel, each warm started
+//! sequentially.
+//!
+//! Scenarios that are not solved to optimality do not fail the batch; their
+//! status is reported with the final iterate of the cold start.
+
+use faer::{Col, Mat, MatRef};
+use macros::{explicit_options, use_option};
+use problemo::{Problem, common::IntoCommonProblem};
+use rayon::prelude::*;
+
+use crate::{
+    E, SolverHooks, SolverOptions, SolverState, Status,
+    callback::NoOpCallback,
+    lp::{LPSolverType, LinearProgram, parametric::warm_start, symbolic_cache::SymbolicCache},
+    terminators::ComplementarityTerminator,
+};
+
+/// Data that changes between the scenarios of a batch, one scenario per column.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BatchData {
+    /// Right-hand sides `b_k`.
+    Rhs(Mat<E>),
+    /// Objectives `c_k`.
+    Objective(Mat<E>),
+}
+
+impl BatchData {
+    fn get_n_scenarios(&self) -> usize {
+        match self {
+            BatchData::Rhs(b) => b.ncols(),
+            BatchData::Objective(c) => c.ncols(),
+        }
+    }
+}
+
+/// Solutions of a batch, one scenario per column.
+#[derive(Debug, Clone)]
+pub struct BatchSolution {
+    x: Mat<E>,
+    y: Mat<E>,
+    objectives: Col<E>,
+    statuses: Vec<Status>,
+    n_warm_starts: usize,
+}
+
+impl BatchSolution {
+    pub fn get_n_scenarios(&self) -> usize {
+        self.statuses.len()
+    }
+
+    /// Primal solutions, an `n_vars x n_scenarios` matrix.
+    pub fn get_primal(&self) -> MatRef<'_, E> {
+        self.x.as_ref()
+    }
+
+    /// Row multipliers, an `n_cons x n_scenarios` matrix.
+    pub fn get_dual(&self) -> MatRef<'_, E> {
+        self.y.as_ref()
+    }
+
+    /// Objective value of every scenario.
+    pub fn get_objective_values(&self) -> &Col<E> {
+        &self.objectives
+    }
+
+    pub fn get_statuses(&self) -> &[Status] {
+        &self.statuses
+    }
+
+    /// Whether every scenario is solved to optimality.
+    pub fn is_optimal(&self) -> bool {
+        self.statuses
+            .iter()
+            .all(|status| *status == Status::Optimal)
+    }
+
+    /// Number of scenarios that reached optimality from a warm start.
+    pub fn get_n_warm_starts(&self) -> usize {
+        self.n_warm_starts
+    }
+}
+
+/// Outcome of the solve of one scenario.
+struct ScenarioSolution {
+    state: SolverState,
+    objective: E,
+    status: Status,
+    warm: bool,
+}
+
+/// Solver for a linear program over a [`BatchData`].
+#[explicit_options(name = SolverOptions)]
+#[use_option(name = "batch_parallel", type_ = bool, default = "false", description = "Whether BatchSolver solves contiguous chunks of its scenarios in parallel.")]
+#[use_option(name = "parametric_warm_start_shift", type_ = E, default = "1e-2", description = "Minimal distance of a warm start to the bounds and minimal magnitude of its bound multipliers.")]
+pub struct BatchSolver<'a> {
+    lp: &'a LinearProgram,
+    data: BatchData,
+    solver_type: Option<LPSolverType>,
+    symbolic_cache: SymbolicCache,
+}
+
+impl<'a> BatchSolver<'a> {
+    pub fn new(
+        lp: &'a LinearProgram,
+        data: BatchData,
+        options: &SolverOptions,
+    ) -> Result<Self, Problem> {
+        let (n_vars, n_cons) = lp.get_dims();
+        match &data {
+            BatchData::Rhs(b) if b.nrows() != n_cons => {
+                return Err(format!(
+                    "Right-hand sides have {} rows but the program has {n_cons} constraints",
+                    b.nrows()
+                )
+                .gloss());
+            }
+            BatchData::Objective(c) if c.nrows() != n_vars => {
+                return Err(format!(
+                    "Objectives have {} rows but the program has {n_vars} variables",
+                    c.nrows()
+                )
+                .gloss());
+            }
+            _ => {}
+        }
+
+        Ok(Self {
+            lp,
+            data,
+            solver_type: None,
+            symbolic_cache: SymbolicCache::new(),
+            options: options.into(),
+        })
+    }
+
+    /// Solves every scenario with `solver_type` instead of the solver selected
+    /// by [`LPSolverBuilder::build`](crate::lp::LPSolverBuilder::build).
+    pub fn with_solver(mut self, solver_type: LPSolverType) -> Self {
+        self.solver_type = Some(solver_type);
+        self
+    }
+
+    /// Shares the symbolic analyses with `cache`, e.g. across batches of
+    /// programs with the same pattern, instead of a cache of the solver.
+    pub fn with_symbolic_cache(mut self, cache: &SymbolicCache) -> Self {
+        self.symbolic_cache = cache.clone();
+        self
+    }
+
+    pub fn get_symbolic_cache(&self) -> &SymbolicCache {
+        &self.symbolic_cache
+    }
+
+    /// The linear program of scenario `k`.
+    #[allow(non_snake_case)]
+    pub fn get_program(&self, k: usize) -> LinearProgram {
+        let lp = self.lp;
+        let A = lp.get_constraint_matrix().clone();
+        let (l, u) = (lp.get_lower_bounds().clone(), lp.get_upper_bounds().clone());
+        match &self.data {
+            BatchData::Rhs(b) => {
+                LinearProgram::new(lp.get_objective().clone(), A, b.col(k).to_owned(), l, u)
+            }
+            BatchData::Objective(c) => {
+                LinearProgram::new(c.col(k).to_owned(), A, lp.get_rhs().clone(), l, u)
+            }
+        }
+    }
+
+    /// Solves every scenario of the batch.
+    pub fn solve(&self) -> Result<BatchSolution, Problem> {
+        let n_scenarios = self.data.get_n_scenarios();
+        let scenarios: Vec<usize> = (0..n_scenarios).collect();
+        let solutions: Vec<ScenarioSolution> = if self.options.batch_parallel {
+            let chunk_size = n_scenarios.div_ceil(rayon::current_num_threads()).max(1);
+            scenarios
+                .par_chunks(chunk_size)
+                .map(|chunk| self.solve_chunk(chunk))
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                .flatten()
+                .collect()
+        } else {
+            self.solve_chunk(&scenarios)?
+        };
+
+        let (n_vars, n_cons) = self.lp.get_dims();
+        let mut batch = BatchSolution {
+            x: Mat::zeros(n_vars, n_scenarios),
+            y: Mat::zeros(n_cons, n_scenarios),
+            objectives: Col::zeros(n_scenarios),
+            statuses: Vec::with_capacity(n_scenarios),
+            n_warm_starts: 0,
+        };
+        for (k, solution) in solutions.into_iter().enumerate() {
+            batch.x.col_mut(k).copy_from(&solution.state.x);
+            batch.y.col_mut(k).copy_from(&solution.state.y);
+            batch.objectives[k] = solution.objective;
+            batch.statuses.push(solution.status);
+            batch.n_warm_starts += solution.warm as usize;
+        }
+        Ok(batch)
+    }
+
+    /// Solves the scenarios of `chunk` in order, each warm started from the
+    /// solution of the previous one.
+    fn solve_chunk(&self, chunk: &[usize]) -> Result<Vec<ScenarioSolution>, Problem> {
+        let mut solutions: Vec<ScenarioSolution> = Vec::with_capacity(chunk.len());
+        for &k in chunk {
+            let lp = self.get_program(k);
+            let (l, u) = (lp.get_lower_bounds(), lp.get_upper_bounds());
+
+            let previous = solutions
+                .last()
+                .filter(|previous| previous.status == Status::Optimal);
+            let mut solution = None;
+            if let Some(previous) = previous {
+                let mut state = warm_start(
+                    &previous.state,
+                    l,
+                    u,
+                    self.options.parametric_warm_start_shift,
+                );
+                if matches!(self.solve_program(&lp, &mut state), Ok(Status::Optimal)) {
+                    solution = Some((state, Status::Optimal, true));
+                }
+            }
+            let (state, status, warm) = match solution {
+                Some(solution) => solution,
+                None => {
+                    let mut state = SolverState::new_interior(l, u, lp.get_n_cons());
+                    let status = self
+                        .solve_program(&lp, &mut state)
+                        .map_err(|e| format!("Batch scenario {k}: {e}").gloss())?;
+                    (state, status, false)
+                }
+            };
+            solutions.push(ScenarioSolution {
+                objective: lp.get_objective_value(&state.x),
+                state,
+                status,
+                warm,
+            });
+        }
+        Ok(solutions)
+    }
+
+    fn solve_program(
+        &self,
+        lp: &LinearProgram,
+        state: &mut SolverState,
+    ) -> Result<Status, Problem> {
+        let mut hooks = SolverHooks {
+            callback: Box::new(NoOpCallback::new()),
+            terminator: Box::new(ComplementarityTerminator::new(&self.options.root)),
+        };
+        let mut builder = lp.solver_builder().with_options(self.options.root.clone());
+        if let Some(solver_type) = self.solver_type {
+            builder = builder.with_solver(solver_type);
+        }
+        self.symbolic_cache.build(builder)?.solve(state, &mut hooks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::mat;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::{ipm::AugmentedSystemType, lp::test::build_budget_lp};
+
+    fn build_options(parallel: bool) -> SolverOptions {
+        let mut options = SolverOptions::new();
+        options.set_option("batch_parallel", parallel).unwrap();
+        options
+            .set_option("augmented_system", AugmentedSystemType::NormalEquations)
+            .unwrap();
+        options
+    }
+
+    #[rstest]
+    fn test_rhs(#[values(false, true)] parallel: bool) {
+        // min -x_0 - 2 x_1 fills x_1 up to b = 1.5, then x_0 up to b = 2.5
+        let lp = build_budget_lp();
+        let b = [0.5, 1., 1.5, 2., 2.5, 3.];
+        let data = BatchData::Rhs(Mat::from_fn(1, b.len(), |_, k| b[k]));
+        // The program is a network, which is solved without a factorization by default
+        let solver = BatchSolver::new(lp, data, &build_options(parallel))
+            .unwrap()
+            .with_solver(LPSolverType::MpcSimplicialCholesky);
+        let batch = solver.solve().unwrap();
+
+        assert_eq!(batch.get_n_scenarios(), b.len());
+        assert!(batch.is_optimal());
+        for (k, b) in b.into_iter().enumerate() {
+            let x_1 = b.min(1.5);
+            let x_0 = (b - x_1).min(1.);
+            assert!((batch.get_primal()[(0, k)] - x_0).abs() < 1e-6);
+            assert!((batch.get_primal()[(1, k)] - x_1).abs() < 1e-6);
+            assert!((batch.get_objective_values()[k] + x_0 + 2. * x_1).abs() < 1e-6);
+        }
+        if !parallel {
+            assert!(batch.get_n_warm_starts() > 0);
+        }
+
+        // Every scenario shares the analysis of the first
+        let cache = solver.get_symbolic_cache();
+        assert_eq!(cache.len(), 1);
+        assert!(cache.get_n_hits() >= b.len() - rayon::current_num_threads().min(b.len()));
+    }
+
+    #[test]
+    fn test_objective() {
+        let lp = build_budget_lp();
+        let data = BatchData::Objective(mat![[-1., -2.], [-2., -1.], [0., 0.]]);
+        let batch = BatchSolver::new(lp, data, &build_options(false))
+            .unwrap()
+            .solve()
+            .unwrap();
+        assert!(batch.is_optimal());
+        assert!((batch.get_primal()[(1, 0)] - 1.5).abs() < 1e-6);
+        assert!((batch.get_primal()[(0, 1)] - 1.).abs() < 1e-6);
+        assert!((batch.get_objective_values()[0] + 3.5).abs() < 1e-6);
+        assert!((batch.get_objective_values()[1] + 3.).abs() < 1e-6);
+        assert_eq!(batch.get_dual().nrows(), 1);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let lp = build_budget_lp();
+        let options = SolverOptions::new();
+        assert!(BatchSolver::new(lp, BatchData::Rhs(Mat::zeros(2, 3)), &options).is_err());
+        assert!(BatchSolver::new(lp, BatchData::Objective(Mat::zeros(1, 3)), &options).is_err());
+    }
+}
@@ -0,0 +1,518 @@
+//! Cutting-plane management for linear programs.
+//!
+//! A [`CutPool`] stores inequalities `a^T x <= rhs` on the variables of a base
+//! linear program. Only the *active* cuts enter the program solved, each as a
+//! row `a^T x + s = rhs` with a slack column `s >= 0` appended after the
+//! variables of the base program. Between solves, [`CutPool::update`]
+//! deactivates cuts that stayed slack for `cut_max_age` rounds and activates
+//! the stored cuts violated by the last solution:
+//!
+//! ```text
+//! let mut lp = pool.build(&base)?;
+//! let mut state = SolverState::new_interior(...);
+//! loop {
+//!     solve(&lp, &mut state);
+//!     if !pool.update(&state).changed() { break; }
+//!     lp = pool.build(&base)?;
+//!     state = pool.warm_start(&lp, &state, shift);
+//! }
+//! ```
+//!
+//! [`LazyConstraintSolver`] runs this loop for constraints that are only
+//! generated on the fly: at every candidate solution, a
+//! [`LazyConstraintCallback`] reports the constraints the candidate violates,
+//! which are added to the pool before the program is solved again from a warm
+//! start. The loop ends at the first candidate for which the callback reports
+//! no violated constraint and the pool is unchanged.
+
+use faer::{
+    Col,
+    sparse::{SparseColMat, Triplet},
+};
+use macros::{explicit_options, use_option};
+use problemo::{Problem, common::IntoCommonProblem};
+
+use crate::{
+    E, OptimizationProgram, SolverHooks, SolverOptions, SolverState, Status,
+    lp::{LPSolverType, LinearProgram, parametric},
+    to_index,
+};
+
+/// A cut `coefficients^T x <= rhs` on the variables of a linear program.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinearCut {
+    coefficients: Col<E>,
+    rhs: E,
+}
+
+impl LinearCut {
+    pub fn new(coefficients: Col<E>, rhs: E) -> Self {
+        Self { coefficients, rhs }
+    }
+
+    pub fn get_coefficients(&self) -> &Col<E> {
+        &self.coefficients
+    }
+
+    pub fn get_rhs(&self) -> E {
+        self.rhs
+    }
+
+    /// Amount `coefficients^T x - rhs` by which `x` violates the cut; negative
+    /// if the cut is slack.
+    pub fn violation(&self, x: &Col<E>) -> E {
+        self.coefficients.transpose() * x - self.rhs
+    }
+}
+
+/// Number of cuts activated and deactivated by [`CutPool::update`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CutRound {
+    activated: usize,
+    deactivated: usize,
+}
+
+impl CutRound {
+    pub fn get_activated(&self) -> usize {
+        self.activated
+    }
+
+    pub fn get_deactivated(&self) -> usize {
+        self.deactivated
+    }
+
+    /// Whether the set of active cuts changed.
+    pub fn changed(&self) -> bool {
+        self.activated + self.deactivated > 0
+    }
+}
+
+/// Pool of cuts on the variables of a base linear program.
+#[explicit_options(name = SolverOptions)]
+#[use_option(name = "cut_violation_tolerance", type_ = E, default = "1e-6", description = "Violation above which an inactive cut of a CutPool is activated.")]
+#[use_option(name = "cut_slack_tolerance", type_ = E, default = "1e-6", description = "Slack above which an active cut of a CutPool counts as not binding.")]
+#[use_option(name = "cut_max_age", type_ = usize, default = "3", description = "Number of consecutive rounds an active cut of a CutPool may be slack before it is deactivated.")]
+pub struct CutPool {
+    n_vars: usize,
+    cuts: Vec<LinearCut>,
+    /// Indices of the active cuts, in the order of their rows.
+    active: Vec<usize>,
+    /// Number of consecutive rounds each cut was slack while active.
+    age: Vec<usize>,
+
+    /// Active cuts of the last two programs returned by `build`.
+    built: Vec<usize>,
+    previous: Vec<usize>,
+}
+
+impl CutPool {
+    /// Creates an empty pool for a base program with `n_vars` variables.
+    pub fn new(n_vars: usize, options: &SolverOptions) -> Self {
+        Self {
+            n_vars,
+            cuts: Vec::new(),
+            active: Vec::new(),
+            age: Vec::new(),
+            built: Vec::new(),
+            previous: Vec::new(),
+            options: options.into(),
+        }
+    }
+
+    /// Stores `cut` as an inactive cut and returns its index.
+    pub fn add_cut(&mut self, cut: LinearCut) -> Result<usize, Problem> {
+        if cut.coefficients.nrows() != self.n_vars {
+            return Err(format!(
+                "Cut has {} coefficients instead of {}",
+                cut.coefficients.nrows(),
+                self.n_vars
+            )
+            .gloss());
+        }
+        self.cuts.push(cut);
+        self.age.push(0);
+        Ok(self.cuts.len() - 1)
+    }
+
+    /// Stores `cut` and activates it.
+    pub fn add_active_cut(&mut self, cut: LinearCut) -> Result<usize, Problem> {
+        let index = self.add_cut(cut)?;
+        self.active.push(index);
+        Ok(index)
+    }
+
+    pub fn get_cuts(&self) -> &[LinearCut] {
+        &self.cuts
+    }
+
+    /// Indices of the active cuts, in the order of their rows.
+    pub fn get_active(&self) -> &[usize] {
+        &self.active
+    }
+
+    pub fn is_active(&self, index: usize) -> bool {
+        self.active.contains(&index)
+    }
+
+    /// Manages the cuts at the solution `state` of the program last returned
+    /// by [`build`](Self::build): deactivates the cuts slack for `cut_max_age`
+    /// consecutive rounds, then activates the inactive cuts violated by more
+    /// than `cut_violation_tolerance`.
+    pub fn update(&mut self, state: &SolverState) -> CutRound {
+        let x = state.get_primal().subrows(0, self.n_vars).to_owned();
+        let mut round = CutRound::default();
+
+        let (max_age, slack_tolerance) =
+            (self.options.cut_max_age, self.options.cut_slack_tolerance);
+        let (cuts, age) = (&self.cuts, &mut self.age);
+        self.active.retain(|&k| {
+            if -cuts[k].violation(&x) > slack_tolerance {
+                age[k] += 1;
+            } else {
+                age[k] = 0;
+            }
+            let keep = age[k] < max_age;
+            if !keep {
+                age[k] = 0;
+                round.deactivated += 1;
+            }
+            keep
+        });
+
+        for (k, cut) in self.cuts.iter().enumerate() {
+            if !self.active.contains(&k) && cut.violation(&x) > self.options.cut_violation_tolerance
+            {
+                self.active.push(k);
+                round.activated += 1;
+            }
+        }
+        round
+    }
+
+    /// Returns `base` with a row `a^T x + s = rhs` and a slack column `s >= 0`
+    /// for every active cut.
+    pub fn build(&mut self, base: &LinearProgram) -> Result<LinearProgram, Problem> {
+        if base.get_n_vars() != self.n_vars {
+            return Err(format!(
+                "Base program has {} variables instead of {}",
+                base.get_n_vars(),
+                self.n_vars
+            )
+            .gloss());
+        }
+
+        let (m, k) = (base.get_n_cons(), self.active.len());
+        let mut lp = base.clone();
+        lp.add_columns(
+            SparseColMat::try_new_from_triplets(m, k, &[])
+                .unwrap()
+                .as_ref(),
+            Col::zeros(k).as_ref(),
+            Col::zeros(k).as_ref(),
+            Col::from_fn(k, |_| E::INFINITY).as_ref(),
+        )?;
+
+        let mut triplets = Vec::new();
+        for (row, &index) in self.active.iter().enumerate() {
+            for (j, &a) in self.cuts[index].coefficients.iter().enumerate() {
+                if a != E::from(0.) {
+                    triplets.push(Triplet::new(to_index(row), to_index(j), a));
+                }
+            }
+            triplets.push(Triplet::new(
+                to_index(row),
+                to_index(self.n_vars + row),
+                E::from(1.),
+            ));
+        }
+        let rows = SparseColMat::try_new_from_triplets(k, self.n_vars + k, &triplets).unwrap();
+        let rhs = Col::from_fn(k, |row| self.cuts[self.active[row]].rhs);
+        lp.add_rows(rows.as_ref(), rhs.as_ref())?;
+
+        self.previous = std::mem::replace(&mut self.built, self.active.clone());
+        Ok(lp)
+    }
+
+    /// Maps the solution `state` of the previously built program to a starting
+    /// point of `lp`, the program returned by the last [`build`](Self::build).
+    ///
+    /// The iterate of the base program and the slacks and multipliers of the
+    /// cuts that stayed active are carried over; newly activated cuts start at
+    /// a unit slack with zero multipliers. The result is moved at least `shift`
+    /// into the interior of the bounds, as for a warm start.
+    pub fn warm_start(&self, lp: &LinearProgram, state: &SolverState, shift: E) -> SolverState {
+        let (n, m) = (self.n_vars, lp.get_n_cons() - self.built.len());
+
+        let mut start = SolverState::new_interior(
+            lp.get_lower_bounds(),
+            lp.get_upper_bounds(),
+            lp.get_n_cons(),
+        );
+        start.x.subrows_mut(0, n).copy_from(state.x.subrows(0, n));
+        start.y.subrows_mut(0, m).copy_from(state.y.subrows(0, m));
+        start
+            .z_l
+            .subrows_mut(0, n)
+            .copy_from(state.z_l.subrows(0, n));
+        start
+            .z_u
+            .subrows_mut(0, n)
+            .copy_from(state.z_u.subrows(0, n));
+        for (row, index) in self.built.iter().enumerate() {
+            if let Some(old) = self.previous.iter().position(|k| k == index) {
+                start.x[n + row] = state.x[n + old];
+                start.z_l[n + row] = state.z_l[n + old];
+                start.y[m + row] = state.y[m + old];
+            }
+        }
+
+        let mut start =
+            parametric::warm_start(&start, lp.get_lower_bounds(), lp.get_upper_bounds(), shift);
+        lp.update_residual(&mut start);
+        start
+    }
+}
+
+/// Separation oracle of a [`LazyConstraintSolver`].
+pub trait LazyConstraintCallback: Send {
+    /// Cuts violated by the candidate solution `x` of the base program; empty
+    /// if `x` is feasible.
+    fn separate(&mut self, x: &Col<E>) -> Vec<LinearCut>;
+}
+
+impl<F: FnMut(&Col<E>) -> Vec<LinearCut> + Send> LazyConstraintCallback for F {
+    fn separate(&mut self, x: &Col<E>) -> Vec<LinearCut> {
+        self(x)
+    }
+}
+
+/// Cutting-plane loop over a base linear program with lazily generated
+/// constraints. See the [module documentation](self).
+#[explicit_options(name = SolverOptions)]
+#[use_option(name = "lazy_max_rounds", type_ = usize, default = "100", description = "Maximum number of solves of a LazyConstraintSolver before it stops with an iteration limit.")]
+#[use_option(name = "parametric_warm_start_shift", type_ = E, default = "1e-2", description = "Minimal distance of a warm start to the bounds and minimal magnitude of its bound multipliers.")]
+pub struct LazyConstraintSolver<'a> {
+    base: &'a LinearProgram,
+    callback: Box<dyn LazyConstraintCallback + 'a>,
+    pool: CutPool,
+    solver_type: Option<LPSolverType>,
+
+    n_rounds: usize,
+}
+
+impl<'a> LazyConstraintSolver<'a> {
+    pub fn new(
+        base: &'a LinearProgram,
+        callback: impl LazyConstraintCallback + 'a,
+        options: &SolverOptions,
+    ) -> Self {
+        Self {
+            base,
+            callback: Box::new(callback),
+            pool: CutPool::new(base.get_n_vars(), options),
+            solver_type: None,
+            n_rounds: 0,
+            options: options.into(),
+        }
+    }
+
+    /// Solves every round with `solver_type` instead of the solver selected
+    /// by [`LPSolverBuilder::build`](crate::lp::LPSolverBuilder::build).
+    pub fn with_solver(mut self, solver_type: LPSolverType) -> Self {
+        self.solver_type = Some(solver_type);
+        self
+    }
+
+    /// Pool of the cuts reported by the callback, e.g. to add known cuts
+    /// before the first solve.
+    pub fn get_pool(&self) -> &CutPool {
+        &self.pool
+    }
+
+    pub fn get_pool_mut(&mut self) -> &mut CutPool {
+        &mut self.pool
+    }
+
+    /// Number of solves of the last call to [`solve`](Self::solve).
+    pub fn get_n_rounds(&self) -> usize {
+        self.n_rounds
+    }
+
+    /// Solves the base program with the cuts of the pool, adds the cuts that
+    /// the callback reports at the solution and solves again, until the
+    /// callback reports none. Every solve uses `hooks`; the first one starts
+    /// from the default starting point.
+    ///
+    /// `state` holds the solution of the last round restricted to the
+    /// variables and constraints of the base program. Returns the status of
+    /// the first round that is not solved to optimality, or
+    /// [`Status::IterationLimit`] after `lazy_max_rounds` rounds.
+    pub fn solve(
+        &mut self,
+        state: &mut SolverState,
+        hooks: &mut SolverHooks,
+    ) -> Result<Status, Problem> {
+        self.n_rounds = 0;
+        let mut lp = self.pool.build(self.base)?;
+        let mut current = SolverState::new_interior(
+            lp.get_lower_bounds(),
+            lp.get_upper_bounds(),
+            lp.get_n_cons(),
+        );
+        let status = loop {
+            if self.n_rounds == self.options.lazy_max_rounds {
+                break Status::IterationLimit;
+            }
+            self.n_rounds += 1;
+            let mut builder = lp.solver_builder().with_options(self.options.root.clone());
+            if let Some(solver_type) = self.solver_type {
+                builder = builder.with_solver(solver_type);
+            }
+            let status = builder.build()?.solve(&mut current, hooks)?;
+            if status != Status::Optimal {
+                break status;
+            }
+
+            let x = current.x.subrows(0, self.base.get_n_vars()).to_owned();
+            let mut changed = false;
+            for cut in self.callback.separate(&x) {
+                self.pool.add_active_cut(cut)?;
+                changed = true;
+            }
+            if !self.pool.update(&current).changed() && !changed {
+                break status;
+            }
+            lp = self.pool.build(self.base)?;
+            current = self
+                .pool
+                .warm_start(&lp, &current, self.options.parametric_warm_start_shift);
+        };
+
+        let (n, m) = self.base.get_dims();
+        *state = SolverState::new(
+            current.x.subrows(0, n).to_owned(),
+            current.y.subrows(0, m).to_owned(),
+            current.z_l.subrows(0, n).to_owned(),
+            current.z_u.subrows(0, n).to_owned(),
+        );
+        state.set_status(status);
+        Ok(status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::col;
+
+    use super::*;
+    use crate::{
+        SolverHooks, Status, callback::NoOpCallback, lp::LPSolverType,
+        terminators::ComplementarityTerminator,
+    };
+
+    /// `min -x_0 - x_1` subject to `x_0 - x_1 = 0`, `0 <= x <= 10`.
+    fn build_base() -> LinearProgram {
+        let a = SparseColMat::try_new_from_triplets(
+            1,
+            2,
+            &[Triplet::new(0, 0, 1.), Triplet::new(0, 1, -1.)],
+        )
+        .unwrap();
+        LinearProgram::new(col![-1., -1.], a, col![0.], col![0., 0.], col![10., 10.])
+    }
+
+    fn solve(lp: &LinearProgram, state: &mut SolverState) -> E {
+        let options = SolverOptions::new();
+        let mut hooks = SolverHooks::new(
+            Box::new(NoOpCallback::new()),
+            Box::new(ComplementarityTerminator::new(&options)),
+        );
+        let status = lp
+            .solver_builder()
+            .with_solver(LPSolverType::MpcSimplicialCholesky)
+            .build()
+            .unwrap()
+            .solve(state, &mut hooks)
+            .unwrap();
+        assert_eq!(status, Status::Optimal);
+        lp.get_objective_value(state.get_primal())
+    }
+
+    #[test]
+    fn test_cutting_plane_loop() {
+        let base = build_base();
+        let mut options = SolverOptions::new();
+        options.set_option("cut_max_age", 1usize).unwrap();
+        let mut pool = CutPool::new(2, &options);
+        let sum = pool.add_cut(LinearCut::new(col![1., 1.], 4.)).unwrap();
+        let first = pool.add_cut(LinearCut::new(col![1., 0.], 3.)).unwrap();
+        pool.add_cut(LinearCut::new(col![0., 1.], 12.)).unwrap();
+        assert!(pool.add_cut(LinearCut::new(col![1.], 0.)).is_err());
+
+        let mut lp = pool.build(&base).unwrap();
+        let mut state = SolverState::new_interior(lp.get_lower_bounds(), lp.get_upper_bounds(), 1);
+        let mut values = vec![solve(&lp, &mut state)];
+        let mut rounds = Vec::new();
+        loop {
+            let round = pool.update(&state);
+            rounds.push(round);
+            if !round.changed() {
+                break;
+            }
+            lp = pool.build(&base).unwrap();
+            state = pool.warm_start(&lp, &state, 1e-2);
+            values.push(solve(&lp, &mut state));
+        }
+
+        // x = (10, 10) violates the first two cuts; the second one is slack at
+        // x = (2, 2) and dropped, which leaves the optimum unchanged
+        assert_eq!(pool.get_active(), &[sum]);
+        assert!(!pool.is_active(first));
+        assert_eq!(rounds[0].get_activated(), 2);
+        assert_eq!(rounds[1].get_deactivated(), 1);
+        assert_eq!(rounds.len(), 3);
+        assert!((values[0] + 20.).abs() < 1e-6);
+        assert!(values[1..].iter().all(|v| (v + 4.).abs() < 1e-6));
+        assert_eq!(lp.get_dims(), (3, 2));
+    }
+
+    #[test]
+    fn test_lazy_constraints() {
+        // The callback separates x_0 + x_1 <= 4 and then x_0 <= 1, one at a time
+        let base = build_base();
+        let callback = |x: &Col<E>| {
+            if x[0] + x[1] > 4. + 1e-6 {
+                vec![LinearCut::new(col![1., 1.], 4.)]
+            } else if x[0] > 1. + 1e-6 {
+                vec![LinearCut::new(col![1., 0.], 1.)]
+            } else {
+                Vec::new()
+            }
+        };
+        let options = SolverOptions::new();
+        let mut solver = LazyConstraintSolver::new(&base, callback, &options)
+            .with_solver(LPSolverType::MpcSimplicialCholesky);
+        let mut hooks = SolverHooks::new(
+            Box::new(NoOpCallback::new()),
+            Box::new(ComplementarityTerminator::new(&options)),
+        );
+        let mut state =
+            SolverState::new(Col::zeros(2), Col::zeros(1), Col::zeros(2), Col::zeros(2));
+        let status = solver.solve(&mut state, &mut hooks).unwrap();
+
+        assert_eq!(status, Status::Optimal);
+        assert_eq!(state.get_status(), Status::Optimal);
+        assert_eq!(solver.get_n_rounds(), 3);
+        assert_eq!(solver.get_pool().get_cuts().len(), 2);
+        assert_eq!(state.get_primal().nrows(), 2);
+        assert!((base.get_objective_value(state.get_primal()) + 2.).abs() < 1e-6);
+
+        // The loop stops at the round limit
+        let mut options = SolverOptions::new();
+        options.set_option("lazy_max_rounds", 2usize).unwrap();
+        let mut solver = LazyConstraintSolver::new(&base, callback, &options)
+            .with_solver(LPSolverType::MpcSimplicialCholesky);
+        let status = solver.solve(&mut state, &mut hooks).unwrap();
+        assert_eq!(status, Status::IterationLimit);
+    }
+}
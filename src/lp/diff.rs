@@ -0,0 +1,289 @@
+//! Comparison of linear programs.
+//!
+//! [`LinearProgram::diff`] lists the entries of the objective, constraint
+//! matrix, right-hand side and bounds in which two programs of equal dimensions
+//! differ. Two values `a` and `b` agree within the tolerance `tol` if they are
+//! equal, which covers infinite bounds, or if
+//!
+//! ```text
+//! |a - b| <= tol * max(1, |a|, |b|)
+//! ```
+//!
+//! Entries missing from the sparsity pattern of a matrix count as zeros, so
+//! explicitly stored zeros do not make two programs differ.
+
+use std::{collections::BTreeMap, fmt};
+
+use faer::Col;
+
+use crate::{E, lp::LinearProgram};
+
+/// Part of a linear program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Component {
+    Objective,
+    ConstraintMatrix,
+    Rhs,
+    LowerBounds,
+    UpperBounds,
+}
+
+impl fmt::Display for Component {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Component::Objective => "c",
+            Component::ConstraintMatrix => "A",
+            Component::Rhs => "b",
+            Component::LowerBounds => "l",
+            Component::UpperBounds => "u",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Entry in which two linear programs differ. Entries of vectors have column 0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntryDiff {
+    component: Component,
+    row: usize,
+    col: usize,
+    left: E,
+    right: E,
+}
+
+impl EntryDiff {
+    pub fn get_component(&self) -> Component {
+        self.component
+    }
+
+    /// Row and column of the entry.
+    pub fn get_index(&self) -> (usize, usize) {
+        (self.row, self.col)
+    }
+
+    /// Values of the entry in the compared programs.
+    pub fn get_values(&self) -> (E, E) {
+        (self.left, self.right)
+    }
+}
+
+impl fmt::Display for EntryDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.component {
+            Component::ConstraintMatrix => write!(f, "A[{}, {}]", self.row, self.col)?,
+            component => write!(f, "{component}[{}]", self.row)?,
+        }
+        write!(f, ": {} != {}", self.left, self.right)
+    }
+}
+
+/// Differences between two linear programs, as returned by
+/// [`LinearProgram::diff`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProgramDiff {
+    left_dims: (usize, usize),
+    right_dims: (usize, usize),
+    entries: Vec<EntryDiff>,
+}
+
+impl ProgramDiff {
+    /// Whether the programs agree in dimensions and all entries.
+    pub fn is_empty(&self) -> bool {
+        self.left_dims == self.right_dims && self.entries.is_empty()
+    }
+
+    /// Dimensions `(n_vars, n_cons)` of the compared programs.
+    pub fn get_dims(&self) -> ((usize, usize), (usize, usize)) {
+        (self.left_dims, self.right_dims)
+    }
+
+    /// Differing entries, ordered by component and index. Empty if the
+    /// dimensions differ, since the entries are then not compared.
+    pub fn get_entries(&self) -> &[EntryDiff] {
+        &self.entries
+    }
+
+    /// Differing entries of `component`.
+    pub fn get_component(&self, component: Component) -> impl Iterator<Item = &EntryDiff> {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.component == component)
+    }
+}
+
+impl fmt::Display for ProgramDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.left_dims != self.right_dims {
+            return writeln!(
+                f,
+                "dimensions (n_vars, n_cons): {:?} != {:?}",
+                self.left_dims, self.right_dims
+            );
+        }
+        for entry in &self.entries {
+            writeln!(f, "{entry}")?;
+        }
+        Ok(())
+    }
+}
+
+fn agrees(a: E, b: E, tol: E) -> bool {
+    a == b
+        || (a.is_finite()
+            && b.is_finite()
+            && (a - b).abs() <= tol * E::max(E::from(1.), E::max(a.abs(), b.abs())))
+}
+
+fn diff_vectors(
+    component: Component,
+    left: &Col<E>,
+    right: &Col<E>,
+    tol: E,
+    entries: &mut Vec<EntryDiff>,
+) {
+    for (row, (&a, &b)) in left.iter().zip(right.iter()).enumerate() {
+        if !agrees(a, b, tol) {
+            entries.push(EntryDiff {
+                component,
+                row,
+                col: 0,
+                left: a,
+                right: b,
+            });
+        }
+    }
+}
+
+impl LinearProgram {
+    /// Entries in which `other` differs from `self` by more than the relative
+    /// tolerance `tol`.
+    #[allow(non_snake_case)]
+    pub fn diff(&self, other: &LinearProgram, tol: E) -> ProgramDiff {
+        let mut entries = Vec::new();
+        if self.get_dims() == other.get_dims() {
+            diff_vectors(Component::Objective, &self.c, &other.c, tol, &mut entries);
+
+            let (A, B) = (&self.A, &other.A);
+            for col in 0..self.get_n_vars() {
+                let mut values = BTreeMap::<usize, (E, E)>::new();
+                for (row, &a) in A.row_idx_of_col(col).zip(A.val_of_col(col)) {
+                    values.entry(row).or_default().0 += a;
+                }
+                for (row, &b) in B.row_idx_of_col(col).zip(B.val_of_col(col)) {
+                    values.entry(row).or_default().1 += b;
+                }
+                entries.extend(
+                    values
+                        .into_iter()
+                        .filter(|(_, (a, b))| !agrees(*a, *b, tol))
+                        .map(|(row, (left, right))| EntryDiff {
+                            component: Component::ConstraintMatrix,
+                            row,
+                            col,
+                            left,
+                            right,
+                        }),
+                );
+            }
+            // Order the matrix entries by row within the component
+            let start = entries.len()
+                - entries
+                    .iter()
+                    .rev()
+                    .take_while(|entry| entry.component == Component::ConstraintMatrix)
+                    .count();
+            entries[start..].sort_by_key(|entry| (entry.row, entry.col));
+
+            diff_vectors(Component::Rhs, &self.b, &other.b, tol, &mut entries);
+            diff_vectors(Component::LowerBounds, &self.l, &other.l, tol, &mut entries);
+            diff_vectors(Component::UpperBounds, &self.u, &other.u, tol, &mut entries);
+        }
+
+        ProgramDiff {
+            left_dims: self.get_dims(),
+            right_dims: other.get_dims(),
+            entries,
+        }
+    }
+
+    /// Whether `other` has the dimensions of `self` and all its entries agree
+    /// within the relative tolerance `tol`.
+    pub fn approx_eq(&self, other: &LinearProgram, tol: E) -> bool {
+        self.diff(other, tol).is_empty()
+    }
+}
+
+/// Exact equality of the entries; explicitly stored zeros of the constraint
+/// matrices are ignored.
+impl PartialEq for LinearProgram {
+    fn eq(&self, other: &Self) -> bool {
+        self.approx_eq(other, E::from(0.))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::{
+        col,
+        sparse::{SparseColMat, Triplet},
+    };
+
+    use super::*;
+    use crate::I;
+
+    fn build_lp(entries: &[Triplet<I, I, E>], upper: E) -> LinearProgram {
+        LinearProgram::new(
+            col![1., -1., 0.],
+            SparseColMat::try_new_from_triplets(2, 3, entries).unwrap(),
+            col![1., 2.],
+            col![0., 0., 0.],
+            col![upper, E::INFINITY, 1.],
+        )
+    }
+
+    #[test]
+    fn test_diff() {
+        let entries = [
+            Triplet::new(0, 0, 1.),
+            Triplet::new(1, 0, 2.),
+            Triplet::new(0, 2, 3.),
+        ];
+        let lp = build_lp(&entries, 4.);
+        assert_eq!(lp, lp.clone());
+
+        // Explicit zeros and small relative changes do not count
+        let mut close = entries.to_vec();
+        close.push(Triplet::new(1, 1, 0.));
+        close[2].val += 1e-10;
+        let other = build_lp(&close, 4. + 1e-10);
+        assert_ne!(lp, other);
+        assert!(lp.approx_eq(&other, 1e-8));
+
+        let mut far = entries.to_vec();
+        far[0].val = 5.;
+        far.push(Triplet::new(1, 2, 1.));
+        let other = build_lp(&far, E::INFINITY);
+        let diff = lp.diff(&other, 1e-8);
+        assert!(!diff.is_empty());
+        let matrix = diff
+            .get_component(Component::ConstraintMatrix)
+            .map(|entry| (entry.get_index(), entry.get_values()))
+            .collect::<Vec<_>>();
+        assert_eq!(matrix, [((0, 0), (1., 5.)), ((1, 2), (0., 1.))]);
+        assert_eq!(
+            diff.to_string(),
+            "A[0, 0]: 1 != 5\nA[1, 2]: 0 != 1\nu[0]: 4 != inf\n"
+        );
+
+        let smaller = LinearProgram::new(
+            col![1.],
+            SparseColMat::try_new_from_triplets(1, 1, &[]).unwrap(),
+            col![1.],
+            col![0.],
+            col![1.],
+        );
+        let diff = lp.diff(&smaller, 1e-8);
+        assert_eq!(diff.get_dims(), ((3, 2), (1, 1)));
+        assert!(!diff.is_empty() && diff.get_entries().is_empty());
+    }
+}
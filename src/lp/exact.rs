@@ -0,0 +1,336 @@
+//! Exact rational verification of linear program solutions.
+//!
+//! A floating-point solution is only optimal to within the tolerance of the
+//! solver. For small programs, the optimal basis it identifies can be verified
+//! in exact arithmetic: the data of the program are converted to rationals
+//! without rounding, and the basic solution
+//!
+//! ```text
+//! B x_B = b - N x_N,    B^T y = c_B,    z_N = c_N - N^T y
+//! ```
+//!
+//! is computed by Gaussian elimination over the rationals. The basis is
+//! certified optimal if `x_B` is within its bounds and every reduced cost `z_N`
+//! has the sign of the bound its column is at. The objective of a certified
+//! basis is then the exact optimal value of the program as stored in `f64`.
+//!
+//! The elimination is dense in the number of rows and the size of the
+//! rationals grows with it, so this is meant for reference values of small
+//! programs.
+
+use num_rational::BigRational;
+use num_traits::{ToPrimitive, Zero};
+use problemo::{Problem, common::IntoCommonProblem};
+
+use crate::{
+    E, SolverState,
+    interface::basis::{Basis, BasisStatus},
+    lp::{LinearProgram, active_set::ActiveSetPrediction},
+};
+
+/// Certified optimal solution of a linear program in exact arithmetic.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExactSolution {
+    x: Vec<BigRational>,
+    y: Vec<BigRational>,
+    z: Vec<BigRational>,
+    objective: BigRational,
+}
+
+impl ExactSolution {
+    /// Exact primal solution.
+    pub fn get_primal(&self) -> &[BigRational] {
+        &self.x
+    }
+
+    /// Exact multipliers of the equality constraints.
+    pub fn get_dual(&self) -> &[BigRational] {
+        &self.y
+    }
+
+    /// Exact reduced costs `c - A^T y`, zero for the basic columns.
+    pub fn get_reduced_cost(&self) -> &[BigRational] {
+        &self.z
+    }
+
+    /// Exact optimal objective value.
+    pub fn get_objective(&self) -> &BigRational {
+        &self.objective
+    }
+
+    /// Optimal objective value rounded to the nearest `f64`.
+    pub fn get_objective_value(&self) -> E {
+        self.objective.to_f64().unwrap()
+    }
+}
+
+impl LinearProgram {
+    /// Certifies the optimal basis predicted from the solver state `state` in
+    /// exact arithmetic. See [`LinearProgram::verify_basis`].
+    pub fn verify_exact(&self, state: &SolverState) -> Result<ExactSolution, Problem> {
+        let basis = ActiveSetPrediction::from_state(self, state).to_basis(self);
+        self.verify_basis(&basis)
+    }
+
+    /// Computes the basic solution of `basis` in exact arithmetic and certifies
+    /// that it is optimal.
+    ///
+    /// Basic rows stand for artificial columns fixed at zero. Nonbasic free
+    /// columns are fixed at zero. Fails if the basis is singular, or if its
+    /// solution is not primal or dual feasible.
+    pub fn verify_basis(&self, basis: &Basis) -> Result<ExactSolution, Problem> {
+        let (m, n) = (self.get_n_cons(), self.get_n_vars());
+        let (col_status, row_status) = (basis.get_col_status(), basis.get_row_status());
+        if col_status.len() != n || row_status.len() != m {
+            return Err(format!(
+                "Basis of {} columns and {} rows does not match a program with {n} variables and {m} constraints",
+                col_status.len(),
+                row_status.len()
+            )
+            .gloss());
+        }
+        if basis.num_basic() != m {
+            return Err(format!(
+                "Basis has {} basic variables for {m} constraints",
+                basis.num_basic()
+            )
+            .gloss());
+        }
+
+        let (l, u) = (&self.l, &self.u);
+        let columns = (0..n)
+            .map(|j| {
+                self.A
+                    .row_idx_of_col(j)
+                    .zip(self.A.val_of_col(j))
+                    .map(|(i, &a)| (i, rational(a)))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        // Values of the nonbasic columns
+        let mut x = vec![BigRational::zero(); n];
+        for j in 0..n {
+            x[j] = match col_status[j] {
+                BasisStatus::Basic => continue,
+                BasisStatus::AtLower if l[j].is_finite() => rational(l[j]),
+                BasisStatus::AtUpper if u[j].is_finite() => rational(u[j]),
+                _ if l[j].is_infinite() && u[j].is_infinite() => BigRational::zero(),
+                _ => return Err(format!("Variable {j} is nonbasic at an infinite bound").gloss()),
+            };
+        }
+
+        // Columns of B: the basic columns, then the basic row logicals
+        let basic_cols = (0..n)
+            .filter(|&j| col_status[j] == BasisStatus::Basic)
+            .collect::<Vec<_>>();
+        let mut b_matrix = vec![vec![BigRational::zero(); m]; m];
+        for (k, &j) in basic_cols.iter().enumerate() {
+            for (i, a) in columns[j].iter() {
+                b_matrix[*i][k] = a.clone();
+            }
+        }
+        for (k, i) in (0..m)
+            .filter(|&i| row_status[i] == BasisStatus::Basic)
+            .enumerate()
+        {
+            b_matrix[i][basic_cols.len() + k] = BigRational::from_integer(1.into());
+        }
+
+        // Primal: B x_B = b - N x_N
+        let mut rhs = (0..m).map(|i| rational(self.b[i])).collect::<Vec<_>>();
+        for j in (0..n).filter(|&j| col_status[j] != BasisStatus::Basic) {
+            for (i, a) in columns[j].iter() {
+                rhs[*i] -= a * &x[j];
+            }
+        }
+        let x_b =
+            solve_dense(b_matrix.clone(), rhs).ok_or_else(|| "Basis matrix is singular".gloss())?;
+        for (k, &j) in basic_cols.iter().enumerate() {
+            x[j] = x_b[k].clone();
+            if (l[j].is_finite() && x[j] < rational(l[j]))
+                || (u[j].is_finite() && x[j] > rational(u[j]))
+            {
+                return Err(format!("Basic variable {j} violates its bounds").gloss());
+            }
+        }
+        if let Some(k) = (basic_cols.len()..m).find(|&k| !x_b[k].is_zero()) {
+            return Err(format!("Artificial variable {k} of the basis is nonzero").gloss());
+        }
+
+        // Dual: B^T y = c_B, with zero cost for the row logicals
+        let transpose = (0..m)
+            .map(|k| (0..m).map(|i| b_matrix[i][k].clone()).collect())
+            .collect();
+        let mut c_b = vec![BigRational::zero(); m];
+        for (k, &j) in basic_cols.iter().enumerate() {
+            c_b[k] = rational(self.c[j]);
+        }
+        let y = solve_dense(transpose, c_b).ok_or_else(|| "Basis matrix is singular".gloss())?;
+
+        // Reduced costs of the nonbasic columns
+        let mut z = vec![BigRational::zero(); n];
+        for j in (0..n).filter(|&j| col_status[j] != BasisStatus::Basic) {
+            z[j] = rational(self.c[j]);
+            for (i, a) in columns[j].iter() {
+                z[j] -= a * &y[*i];
+            }
+            let fixed = l[j] == u[j];
+            let free = l[j].is_infinite() && u[j].is_infinite();
+            let infeasible = match col_status[j] {
+                _ if fixed => false,
+                _ if free => !z[j].is_zero(),
+                BasisStatus::AtLower => z[j] < BigRational::zero(),
+                _ => z[j] > BigRational::zero(),
+            };
+            if infeasible {
+                return Err(format!("Reduced cost of variable {j} has the wrong sign").gloss());
+            }
+        }
+
+        let objective = (0..n)
+            .map(|j| rational(self.c[j]) * &x[j])
+            .fold(BigRational::zero(), |sum, term| sum + term);
+        Ok(ExactSolution { x, y, z, objective })
+    }
+}
+
+/// Exact value of a finite `f64`.
+fn rational(value: E) -> BigRational {
+    BigRational::from_float(value).unwrap()
+}
+
+/// Solves the square system `a x = rhs` by Gaussian elimination. Returns `None`
+/// if `a` is singular.
+fn solve_dense(
+    mut a: Vec<Vec<BigRational>>,
+    mut rhs: Vec<BigRational>,
+) -> Option<Vec<BigRational>> {
+    let m = rhs.len();
+    for k in 0..m {
+        let pivot = (k..m).find(|&i| !a[i][k].is_zero())?;
+        a.swap(k, pivot);
+        rhs.swap(k, pivot);
+        let (top, bottom) = a.split_at_mut(k + 1);
+        let pivot_row = &top[k];
+        for (row, i) in bottom.iter_mut().zip(k + 1..) {
+            if row[k].is_zero() {
+                continue;
+            }
+            let factor = &row[k] / &pivot_row[k];
+            for (a_ij, a_kj) in row[k..].iter_mut().zip(&pivot_row[k..]) {
+                *a_ij -= &factor * a_kj;
+            }
+            let update = &factor * &rhs[k];
+            rhs[i] -= update;
+        }
+    }
+
+    let mut x = vec![BigRational::zero(); m];
+    for k in (0..m).rev() {
+        let mut value = rhs[k].clone();
+        for (a_kj, x_j) in a[k][k + 1..].iter().zip(&x[k + 1..]) {
+            value -= a_kj * x_j;
+        }
+        x[k] = value / &a[k][k];
+    }
+    Some(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::{Col, col};
+
+    use super::*;
+    use crate::{
+        SolverHooks, SolverOptions, Status, callback::NoOpCallback, lp::test::build_budget_lp,
+        terminators::ComplementarityTerminator,
+    };
+
+    /// The program of [`build_budget_lp`] with the right-hand side 2.1, which is
+    /// not a binary fraction.
+    fn build_lp() -> LinearProgram {
+        let mut lp = build_budget_lp().clone();
+        lp.b = col![2.1];
+        lp
+    }
+
+    #[test]
+    fn test_verify_exact() {
+        let lp = build_lp();
+        let mut state = SolverState::new(
+            col![0.5, 0.75, 1.],
+            Col::zeros(1),
+            col![1., 1., 1.],
+            col![-1., -1., 0.],
+        );
+        let options = SolverOptions::new();
+        let mut hooks = SolverHooks {
+            callback: Box::new(NoOpCallback::new()),
+            terminator: Box::new(ComplementarityTerminator::new(&options)),
+        };
+        let mut solver = lp.solver_builder().with_options(options).build().unwrap();
+        assert_eq!(
+            solver.solve(&mut state, &mut hooks).unwrap(),
+            Status::Optimal
+        );
+
+        // x_0 = 2.1 - 1.5 in the exact value of the stored right-hand side
+        let solution = lp.verify_exact(&state).unwrap();
+        let x_0 = rational(2.1) - rational(1.5);
+        assert_eq!(solution.get_primal()[0], x_0);
+        assert_eq!(solution.get_objective(), &(-x_0 - rational(3.)));
+        assert_eq!(solution.get_dual(), &[rational(-1.)]);
+        assert_eq!(solution.get_reduced_cost()[1], rational(-1.));
+        assert!((solution.get_objective_value() + 3.6).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_reject_basis() {
+        let lp = build_lp();
+        let status = |col_status: [BasisStatus; 3]| {
+            lp.verify_basis(&Basis::from_status(
+                col_status.to_vec(),
+                vec![BasisStatus::AtLower],
+            ))
+        };
+
+        assert!(
+            status([
+                BasisStatus::Basic,
+                BasisStatus::AtUpper,
+                BasisStatus::AtLower
+            ])
+            .is_ok()
+        );
+        // Primal infeasible: x_0 = 2.1 at x_1 = 0
+        assert!(
+            status([
+                BasisStatus::Basic,
+                BasisStatus::AtLower,
+                BasisStatus::AtLower
+            ])
+            .is_err()
+        );
+        // Dual infeasible: y = -2, so x_0 has a positive reduced cost at its upper bound
+        assert!(
+            status([
+                BasisStatus::AtUpper,
+                BasisStatus::Basic,
+                BasisStatus::AtLower
+            ])
+            .is_err()
+        );
+        // Dual infeasible: y = 0, so x_0 has a negative reduced cost at its lower bound
+        assert!(
+            status([
+                BasisStatus::AtLower,
+                BasisStatus::AtLower,
+                BasisStatus::Basic
+            ])
+            .is_err()
+        );
+        // Too many basic variables
+        assert!(status([BasisStatus::Basic, BasisStatus::Basic, BasisStatus::AtLower]).is_err());
+    }
+}
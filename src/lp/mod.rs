@@ -1,17 +1,47 @@
-use faer::{Col, sparse::SparseColMat};
+use std::sync::OnceLock;
+
+use faer::{
+    Col, ColRef,
+    sparse::{SparseColMat, SparseColMatRef, SparseRowMat, SymbolicSparseColMat},
+};
 use problemo::Problem;
 use problemo::common::IntoCommonProblem;
 
-use crate::OptimizationProgram;
+use macros::use_option;
+
+use crate::linalg::solver::{LinearSolver, MemoryEstimate, SymbolicAnalysis};
 use crate::linalg::vector_ops::cwise_multiply_finite;
 use crate::nlp::NonlinearProgram;
 use crate::qp::QuadraticProgram;
+use crate::registry;
+use crate::{
+    Certificate, DEFAULT_INFINITY_THRESHOLD, OptimizationProgram, ProblemNorms,
+    apply_infinity_threshold, max_abs,
+};
 use crate::{
-    E, I, IterativeSolver, SolverOptions,
+    E, I, IterativeSolver, SolverOptions, SolverState,
     linalg::cholesky::{SimplicialSparseCholesky, SupernodalSparseCholesky},
+    to_index,
 };
 
+pub mod active_set;
+pub mod batch;
+pub mod cuts;
+pub mod diff;
+#[cfg(feature = "exact")]
+pub mod exact;
 pub mod mpc;
+pub mod multiobjective;
+pub mod network;
+pub mod parametric;
+pub mod perturbation;
+pub mod phase1;
+pub mod polish;
+pub mod presolve;
+pub mod symbolic_cache;
+pub mod validation;
+
+pub use crate::ipm::{AugmentedSystemType, CorrectorCount, SolveCosts};
 
 /// A linear program in standard form:
 ///
@@ -20,6 +50,13 @@ pub mod mpc;
 /// s.t. A x = b
 ///      l <= x <= u
 /// ```
+///
+/// Every row is an equality, so the row multipliers `y` of a [`SolverState`]
+/// are free and only the bound multipliers `z_l >= 0` and `z_u <= 0` carry sign
+/// restrictions and complementarity terms. Inequality and ranged rows are
+/// represented by slack columns, see
+/// [`SifTransformation`](crate::interface::sif::SifTransformation), whose bound
+/// multipliers are the signed multipliers of the rows.
 #[allow(non_snake_case)]
 #[derive(Clone, Debug)]
 pub struct LinearProgram {
@@ -33,13 +70,29 @@ pub struct LinearProgram {
     l: Col<E>,
     /// Upper bounds on the variables.
     u: Col<E>,
+    /// Row-major copy of `A`, built on first use.
+    A_csr: OnceLock<SparseRowMat<I, E>>,
+    /// Norms of `A`, `b` and `c`, computed on first use.
+    norms: OnceLock<ProblemNorms>,
 }
 
 #[allow(non_snake_case)]
 impl LinearProgram {
     /// Creates a new linear program from the objective, constraints, and bounds.
-    pub fn new(c: Col<E>, A: SparseColMat<I, E>, b: Col<E>, l: Col<E>, u: Col<E>) -> Self {
-        Self { c, A, b, l, u }
+    ///
+    /// Bounds beyond [`DEFAULT_INFINITY_THRESHOLD`](crate::DEFAULT_INFINITY_THRESHOLD) in
+    /// magnitude, e.g. `1e30` for a missing bound, are replaced by infinities.
+    pub fn new(c: Col<E>, A: SparseColMat<I, E>, b: Col<E>, mut l: Col<E>, mut u: Col<E>) -> Self {
+        apply_infinity_threshold(&mut l, &mut u, DEFAULT_INFINITY_THRESHOLD);
+        Self {
+            c,
+            A,
+            b,
+            l,
+            u,
+            A_csr: OnceLock::new(),
+            norms: OnceLock::new(),
+        }
     }
 
     /// Returns the number of variables (columns of `A`).
@@ -65,6 +118,12 @@ impl LinearProgram {
         &self.A
     }
 
+    /// Row-major copy of the constraint matrix, i.e. the column-major storage
+    /// of `A^T`, built on first use and kept until `A` changes.
+    pub fn get_constraint_matrix_csr(&self) -> &SparseRowMat<I, E> {
+        self.A_csr.get_or_init(|| self.A.to_row_major().unwrap())
+    }
+
     pub fn get_rhs(&self) -> &Col<E> {
         &self.b
     }
@@ -88,6 +147,156 @@ impl LinearProgram {
     pub fn get_constraint_values(&self, x: &Col<E>) -> Col<E> {
         self.A.as_ref() * x - &self.b
     }
+
+    /// Appends the columns `cols` with objective coefficients `costs` and
+    /// bounds `lower <= x <= upper` to the program.
+    ///
+    /// The entries of the new columns are appended to the storage of `A`
+    /// without touching the existing columns. Solvers borrow the program, so
+    /// they are built again after the change; states of the previous program
+    /// are carried over with [`extend_state`](Self::extend_state).
+    pub fn add_columns(
+        &mut self,
+        cols: SparseColMatRef<I, E>,
+        costs: ColRef<E>,
+        lower: ColRef<E>,
+        upper: ColRef<E>,
+    ) -> Result<(), Problem> {
+        let k = cols.ncols();
+        if cols.nrows() != self.get_n_cons() {
+            return Err(format!(
+                "Added columns have {} rows instead of {}",
+                cols.nrows(),
+                self.get_n_cons()
+            )
+            .gloss());
+        }
+        if costs.nrows() != k || lower.nrows() != k || upper.nrows() != k {
+            return Err(
+                format!("Costs and bounds of {k} added columns must have {k} entries").gloss(),
+            );
+        }
+
+        let empty = SparseColMat::try_new_from_triplets(0, 0, &[]).unwrap();
+        let (m, n, mut col_ptr, mut row_idx, mut values) =
+            into_compressed_parts(std::mem::replace(&mut self.A, empty));
+        row_idx.reserve(cols.compute_nnz());
+        values.reserve(cols.compute_nnz());
+        for j in 0..k {
+            row_idx.extend_from_slice(cols.row_idx_of_col_raw(j));
+            values.extend_from_slice(cols.val_of_col(j));
+            col_ptr.push(to_index(row_idx.len()));
+        }
+        self.A = from_compressed_parts(m, n + k, col_ptr, row_idx, values);
+        self.A_csr = OnceLock::new();
+        self.norms = OnceLock::new();
+
+        self.c = concat(self.c.as_ref(), costs);
+        self.l = concat(self.l.as_ref(), lower);
+        self.u = concat(self.u.as_ref(), upper);
+        Ok(())
+    }
+
+    /// Appends the constraints `rows x = rhs` to the program, where `rows` has
+    /// one column per variable.
+    ///
+    /// Appending rows rebuilds the column-major storage of `A` once. States of
+    /// the previous program are carried over with
+    /// [`extend_state`](Self::extend_state).
+    pub fn add_rows(&mut self, rows: SparseColMatRef<I, E>, rhs: ColRef<E>) -> Result<(), Problem> {
+        let (m, n) = (self.get_n_cons(), self.get_n_vars());
+        if rows.ncols() != n {
+            return Err(format!("Added rows have {} columns instead of {n}", rows.ncols()).gloss());
+        }
+        if rhs.nrows() != rows.nrows() {
+            return Err(format!(
+                "Right-hand side of {} added rows has {} entries",
+                rows.nrows(),
+                rhs.nrows()
+            )
+            .gloss());
+        }
+
+        let nnz = self.A.compute_nnz() + rows.compute_nnz();
+        let mut col_ptr = Vec::with_capacity(n + 1);
+        let mut row_idx = Vec::with_capacity(nnz);
+        let mut values = Vec::with_capacity(nnz);
+        col_ptr.push(to_index(0));
+        for j in 0..n {
+            row_idx.extend_from_slice(self.A.row_idx_of_col_raw(j));
+            values.extend_from_slice(self.A.val_of_col(j));
+            row_idx.extend(rows.row_idx_of_col(j).map(|i| to_index(i + m)));
+            values.extend_from_slice(rows.val_of_col(j));
+            col_ptr.push(to_index(row_idx.len()));
+        }
+        self.A = from_compressed_parts(m + rows.nrows(), n, col_ptr, row_idx, values);
+        self.A_csr = OnceLock::new();
+        self.norms = OnceLock::new();
+        self.b = concat(self.b.as_ref(), rhs);
+        Ok(())
+    }
+
+    /// Extends a state of the program before columns or rows were added into a
+    /// starting point of the current program.
+    ///
+    /// The previous iterate is moved at least `shift` into the interior of the
+    /// bounds, as for a warm start. New variables start in the interior of
+    /// their bounds, with unit multipliers on their finite bounds, and new
+    /// constraints with zero multipliers.
+    pub fn extend_state(&self, state: &SolverState, shift: E) -> SolverState {
+        let (n, m) = (state.x.nrows(), state.y.nrows());
+        let mut extended = SolverState::new_interior(&self.l, &self.u, self.get_n_cons());
+        extended.x.subrows_mut(0, n).copy_from(&state.x);
+        extended.y.subrows_mut(0, m).copy_from(&state.y);
+        extended.z_l.subrows_mut(0, n).copy_from(&state.z_l);
+        extended.z_u.subrows_mut(0, n).copy_from(&state.z_u);
+
+        let mut extended = parametric::warm_start(&extended, &self.l, &self.u, shift);
+        self.update_residual(&mut extended);
+        extended
+    }
+}
+
+/// Splits `A` into compressed column pointers, row indices and values,
+/// reusing its storage unless it has unused entries between columns.
+#[allow(non_snake_case)]
+fn into_compressed_parts(A: SparseColMat<I, E>) -> (usize, usize, Vec<I>, Vec<I>, Vec<E>) {
+    if A.symbolic().col_nnz().is_none() {
+        let (symbolic, values) = A.into_parts();
+        let (m, n, col_ptr, _, row_idx) = symbolic.into_parts();
+        return (m, n, col_ptr, row_idx, values);
+    }
+
+    let (m, n) = (A.nrows(), A.ncols());
+    let mut col_ptr = Vec::with_capacity(n + 1);
+    let mut row_idx = Vec::with_capacity(A.compute_nnz());
+    let mut values = Vec::with_capacity(A.compute_nnz());
+    col_ptr.push(to_index(0));
+    for j in 0..n {
+        row_idx.extend_from_slice(A.row_idx_of_col_raw(j));
+        values.extend_from_slice(A.val_of_col(j));
+        col_ptr.push(to_index(row_idx.len()));
+    }
+    (m, n, col_ptr, row_idx, values)
+}
+
+fn from_compressed_parts(
+    m: usize,
+    n: usize,
+    col_ptr: Vec<I>,
+    row_idx: Vec<I>,
+    values: Vec<E>,
+) -> SparseColMat<I, E> {
+    let symbolic = SymbolicSparseColMat::new_unsorted_checked(m, n, col_ptr, None, row_idx);
+    SparseColMat::new(symbolic, values)
+}
+
+fn concat(head: ColRef<E>, tail: ColRef<E>) -> Col<E> {
+    let n = head.nrows();
+    Col::from_fn(
+        n + tail.nrows(),
+        |i| if i < n { head[i] } else { tail[i - n] },
+    )
 }
 
 #[allow(unused, non_snake_case)]
@@ -168,23 +377,143 @@ impl From<&LinearProgram> for NonlinearProgram {
 }
 
 impl OptimizationProgram for LinearProgram {
+    fn get_norms(&self) -> Option<ProblemNorms> {
+        Some(*self.norms.get_or_init(|| {
+            ProblemNorms::new(
+                max_abs(self.A.val()),
+                self.b.norm_max(),
+                self.c.norm_max(),
+                E::from(0.),
+            )
+        }))
+    }
+
     fn update_residual(&self, state: &mut crate::SolverState) {
+        crate::profile!("residual");
         state.dual_feasibility = -&self.c + self.A.transpose() * &state.y + &state.z_l + &state.z_u;
         state.primal_feasibility = self.A.as_ref() * &state.x - &self.b;
         state.cs_lower = -cwise_multiply_finite(state.z_l.as_ref(), (&state.x - &self.l).as_ref());
         state.cs_upper = -cwise_multiply_finite(state.z_u.as_ref(), (&state.x - &self.u).as_ref());
+
+        let primal_obj = self.c.transpose() * &state.x;
+        let dual_obj = self.b.transpose() * &state.y
+            + bound_objective(&self.l, &self.u, &state.z_l, &state.z_u);
+        state.set_objective_values(primal_obj, dual_obj);
     }
+
+    fn get_certificate(&self, state: &SolverState) -> Option<Certificate> {
+        farkas_certificate(&self.A, &self.b, &self.l, &self.u, &state.y)
+            .or_else(|| unbounded_certificate(&self.A, &self.c, &self.l, &self.u, &state.x))
+    }
+}
+
+/// Relative tolerance of the conditions of a [`Certificate`].
+pub(crate) const CERTIFICATE_TOLERANCE: E = 1e-6;
+
+/// Checks whether `y` or `-y`, scaled to unit max norm, is a Farkas ray of
+/// `A x = b, l <= x <= u`.
+pub(crate) fn farkas_certificate(
+    a: &SparseColMat<I, E>,
+    b: &Col<E>,
+    l: &Col<E>,
+    u: &Col<E>,
+    y: &Col<E>,
+) -> Option<Certificate> {
+    let norm = y.norm_max();
+    if !norm.is_finite() || norm == E::from(0.) {
+        return None;
+    }
+    let tolerance = CERTIFICATE_TOLERANCE * b.norm_max().max(E::from(1.));
+
+    [E::from(1.), E::from(-1.)].into_iter().find_map(|sign| {
+        let y = (sign / norm) * y;
+        let s = a.transpose() * &y;
+        // Maximum of s^T x over the bounds, where entries of s below the
+        // tolerance may face an infinite bound
+        let mut maximum = E::from(0.);
+        for j in 0..s.nrows() {
+            let bound = if s[j] >= E::from(0.) { u[j] } else { l[j] };
+            if bound.is_finite() {
+                maximum += s[j] * bound;
+            } else if s[j].abs() > CERTIFICATE_TOLERANCE {
+                return None;
+            }
+        }
+        (b.transpose() * &y > maximum + tolerance).then_some(Certificate::PrimalInfeasible(y))
+    })
+}
+
+/// Checks whether `x`, scaled to unit max norm, is a feasible direction of
+/// `A x = b, l <= x <= u` along which `c^T x` decreases.
+pub(crate) fn unbounded_certificate(
+    a: &SparseColMat<I, E>,
+    c: &Col<E>,
+    l: &Col<E>,
+    u: &Col<E>,
+    x: &Col<E>,
+) -> Option<Certificate> {
+    let norm = x.norm_max();
+    if !norm.is_finite() || norm == E::from(0.) {
+        return None;
+    }
+    let d = x / norm;
+
+    let feasible = (a * &d).norm_max() <= CERTIFICATE_TOLERANCE
+        && (0..d.nrows()).all(|j| {
+            (l[j].is_infinite() || d[j] >= -CERTIFICATE_TOLERANCE)
+                && (u[j].is_infinite() || d[j] <= CERTIFICATE_TOLERANCE)
+        });
+    let descent = c.transpose() * &d < -CERTIFICATE_TOLERANCE * c.norm_max().max(E::from(1.));
+    (feasible && descent).then_some(Certificate::Unbounded(d))
+}
+
+/// Contribution `l'z_l + u'z_u` of the finite bounds to the dual objective.
+pub(crate) fn bound_objective(l: &Col<E>, u: &Col<E>, z_l: &Col<E>, z_u: &Col<E>) -> E {
+    cwise_multiply_finite(l.as_ref(), z_l.as_ref()).sum()
+        + cwise_multiply_finite(u.as_ref(), z_u.as_ref()).sum()
 }
 
 /// Trait for solvers that operate on a [`LinearProgram`].
-pub trait LPSolver<'a>: IterativeSolver {
+///
+/// Solvers are `Send`, and [`LinearProgram`] is `Send + Sync`, so that built
+/// solvers can be moved to worker threads.
+pub trait LPSolver<'a>: IterativeSolver + Send {
     /// Creates a new solver instance for the given linear program and options.
     fn new(lp: &'a LinearProgram, options: &SolverOptions) -> Self
     where
         Self: Sized;
+
+    /// Creates a new solver reusing `symbolic`, the analysis returned by
+    /// [`LPSolver::get_symbolic`] of a solver of the same type for a program
+    /// with the sparsity pattern of `lp`. Solvers without a symbolic analysis
+    /// ignore it.
+    fn new_with_symbolic(
+        lp: &'a LinearProgram,
+        options: &SolverOptions,
+        symbolic: Option<&SymbolicAnalysis>,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        let _ = symbolic;
+        Self::new(lp, options)
+    }
+
+    /// Memory of the factorizations of the solver, estimated before the first
+    /// iteration. `None` if the solver does not factorize or cannot predict it.
+    fn estimate_memory(&self) -> Option<MemoryEstimate> {
+        None
+    }
+
+    /// Symbolic analysis of the KKT system of the solver, to share with
+    /// solvers of programs with the same sparsity pattern. `None` if the solver
+    /// does not factorize or its linear solver cannot share the analysis.
+    fn get_symbolic(&self) -> Option<SymbolicAnalysis> {
+        None
+    }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 pub enum LPSolverType {
     MpcSimplicialCholesky,
     MpcSupernodalCholesky,
@@ -193,11 +522,20 @@ pub enum LPSolverType {
     MpcMKL,
     #[cfg(feature = "panua")]
     MpcPanua,
+    /// Primal network simplex; requires a network constraint matrix (see [`network`]).
+    NetworkSimplex,
 }
 
+#[use_option(name = "augmented_system", type_ = crate::ipm::AugmentedSystemType, default = "auto", description = "Formulation of the Newton system in interior-point methods.")]
+#[use_option(name = "lp_mu_update", type_ = crate::lp::mpc::mu_update::MuUpdates, default = "adaptive", description = "Barrier parameter update of the interior-point method for linear programs (adaptive or constant).")]
+#[use_option(name = "lp_line_search", type_ = crate::lp::mpc::line_search::LineSearches, default = "max_step", description = "Step length rule of the interior-point method for linear programs (max_step).")]
+#[use_option(name = "lp_solver", type_ = String, default = "", description = "Name of a registered linear program solver to build when no solver type is given; empty selects a built-in solver.")]
 pub struct LPSolverBuilder<'a> {
     lp: Option<&'a LinearProgram>,
     solver_type: Option<LPSolverType>,
+    solver_name: Option<String>,
+    system_type: Option<AugmentedSystemType>,
+    symbolic: Option<SymbolicAnalysis>,
     options: SolverOptions,
 }
 
@@ -206,6 +544,9 @@ impl<'a> LPSolverBuilder<'a> {
         Self {
             lp: None,
             solver_type: None,
+            solver_name: None,
+            system_type: None,
+            symbolic: None,
             options: SolverOptions::new(),
         }
     }
@@ -220,67 +561,190 @@ impl<'a> LPSolverBuilder<'a> {
         self
     }
 
+    /// Selects a solver registered with [`crate::registry::register_lp_solver`].
+    pub fn with_solver_name(mut self, name: &str) -> Self {
+        self.solver_name = Some(name.to_string());
+        self
+    }
+
+    pub fn with_system(mut self, system_type: AugmentedSystemType) -> Self {
+        self.system_type = Some(system_type);
+        self
+    }
+
     pub fn with_options(mut self, options: SolverOptions) -> Self {
         self.options = options;
         self
     }
 
+    /// Reuses the symbolic analysis of the KKT system returned by
+    /// [`LPSolverBuilder::analyze`] or [`LPSolver::get_symbolic`], instead of
+    /// analyzing it again. An analysis of another linear solver or of a KKT
+    /// system with another sparsity pattern, e.g. of a program with another
+    /// pattern or another system type, is ignored and the system is analyzed
+    /// anew. A [`SymbolicCache`](symbolic_cache::SymbolicCache) keeps the
+    /// analyses of several patterns and configurations.
+    pub fn with_symbolic(mut self, symbolic: SymbolicAnalysis) -> Self {
+        self.symbolic = Some(symbolic);
+        self
+    }
+
+    /// Builds the solver and returns the symbolic analysis of its KKT system,
+    /// to pass to [`LPSolverBuilder::with_symbolic`] of later builds, e.g. of
+    /// the subproblems of a decomposition that only change `c`, `b` and the
+    /// bounds. Fails if the solver cannot share its analysis.
+    pub fn analyze(self) -> Result<SymbolicAnalysis, Problem> {
+        self.build()?
+            .get_symbolic()
+            .ok_or_else(|| "The solver cannot share its symbolic analysis".gloss())
+    }
+
+    /// Builds the solver.
+    ///
+    /// If no solver type is given, the registered solver named with
+    /// [`LPSolverBuilder::with_solver_name`] or `lp_solver` is built. Otherwise
+    /// network LPs are dispatched to [`LPSolverType::NetworkSimplex`] and all
+    /// others to [`LPSolverType::MpcSimplicialCholesky`].
     pub fn build(self) -> Result<Box<dyn LPSolver<'a> + 'a>, Problem> {
-        let lp = self
-            .lp
-            .ok_or_else(|| "Linear program must be provided".gloss())?;
-        let solver_type = self
-            .solver_type
-            .ok_or_else(|| "Solver type must be specified".gloss())?;
+        let lp = self.get_lp()?;
+        if let Some(name) = self.get_registered_name() {
+            return registry::build_lp_solver(&name, lp, &self.options);
+        }
+        let solver_type = self.get_solver_type(lp);
+        let system_type = self.get_system_type(lp)?;
+        let symbolic = self.symbolic.as_ref();
 
         match solver_type {
-            LPSolverType::MpcSimplicialCholesky => {
-                Ok(Box::new(mpc::MehrotraPredictorCorrector::<
-                    'a,
-                    SimplicialSparseCholesky,
-                    mpc::augmented_system::SlackReducedSystem<'a, SimplicialSparseCholesky>,
-                    mpc::mu_update::AdaptiveMuUpdate<'a>,
-                >::new(lp.into(), &self.options)))
-            }
-            LPSolverType::MpcSupernodalCholesky => {
-                Ok(Box::new(mpc::MehrotraPredictorCorrector::<
-                    'a,
-                    SupernodalSparseCholesky,
-                    mpc::augmented_system::SlackReducedSystem<'a, SupernodalSparseCholesky>,
-                    mpc::mu_update::AdaptiveMuUpdate<'a>,
-                >::new(lp.into(), &self.options)))
+            LPSolverType::MpcSimplicialCholesky => Ok(build_mpc::<SimplicialSparseCholesky>(
+                lp,
+                system_type,
+                &self.options,
+                symbolic,
+            )),
+            LPSolverType::MpcSupernodalCholesky => Ok(build_mpc::<SupernodalSparseCholesky>(
+                lp,
+                system_type,
+                &self.options,
+                symbolic,
+            )),
+            LPSolverType::MpcSimplicialLu => Ok(build_mpc::<SimplicialSparseCholesky>(
+                lp,
+                system_type,
+                &self.options,
+                symbolic,
+            )),
+            #[cfg(feature = "mkl")]
+            LPSolverType::MpcMKL => Ok(build_mpc::<crate::linalg::pardiso::MKLPardiso>(
+                lp,
+                system_type,
+                &self.options,
+                symbolic,
+            )),
+            #[cfg(feature = "panua")]
+            LPSolverType::MpcPanua => Ok(build_mpc::<crate::linalg::pardiso::PanuaPardiso>(
+                lp,
+                system_type,
+                &self.options,
+                symbolic,
+            )),
+            LPSolverType::NetworkSimplex if !network::is_network_lp(lp) => Err(
+                "Network simplex requires a network matrix and a finite bound on every variable"
+                    .gloss(),
+            ),
+            LPSolverType::NetworkSimplex => Ok(Box::new(network::simplex::NetworkSimplex::new(
+                lp,
+                &self.options,
+            )) as Box<dyn LPSolver<'a> + 'a>),
+        }
+    }
+
+    fn get_lp(&self) -> Result<&'a LinearProgram, Problem> {
+        self.lp
+            .ok_or_else(|| "Linear program must be provided".gloss())
+    }
+
+    /// Name of the registered solver to build, if no solver type is given.
+    fn get_registered_name(&self) -> Option<String> {
+        match self.solver_type {
+            Some(_) => None,
+            None => registry::selected_name(self.solver_name.clone(), &self.options, "lp_solver"),
+        }
+    }
+
+    /// Solver type built for `lp` if no registered solver is selected.
+    fn get_solver_type(&self, lp: &LinearProgram) -> LPSolverType {
+        self.solver_type.unwrap_or_else(|| {
+            if network::is_network_lp(lp) {
+                LPSolverType::NetworkSimplex
+            } else {
+                LPSolverType::MpcSimplicialCholesky
             }
-            LPSolverType::MpcSimplicialLu => {
-                Ok(Box::new(mpc::MehrotraPredictorCorrector::<
-                    'a,
-                    SimplicialSparseCholesky,
-                    mpc::augmented_system::SlackReducedSystem<'a, SimplicialSparseCholesky>,
-                    mpc::mu_update::AdaptiveMuUpdate<'a>,
-                >::new(lp.into(), &self.options)))
+        })
+    }
+
+    /// Augmented system built for `lp`, from the builder or else the options.
+    fn get_system_type(&self, lp: &LinearProgram) -> Result<AugmentedSystemType, Problem> {
+        match self
+            .system_type
+            .or(self
+                .options
+                .get_option::<AugmentedSystemType>("augmented_system"))
+            .unwrap_or_default()
+        {
+            AugmentedSystemType::Auto => Ok(mpc::augmented_system::select_system(lp)),
+            AugmentedSystemType::NormalEquations
+                if !mpc::augmented_system::has_bounded_variables(lp) =>
+            {
+                Err("Normal equations require every variable to have a finite bound".gloss())
             }
-            #[cfg(feature = "mkl")]
-            LPSolverType::MpcMKL => Ok(Box::new(mpc::MehrotraPredictorCorrector::<
+            system_type => Ok(system_type),
+        }
+    }
+}
+
+/// Instantiates the MPC solver with the augmented system given by `system_type`.
+fn build_mpc<'a, LinSolve: LinearSolver + 'a>(
+    lp: &'a LinearProgram,
+    system_type: AugmentedSystemType,
+    options: &SolverOptions,
+    symbolic: Option<&SymbolicAnalysis>,
+) -> Box<dyn LPSolver<'a> + 'a> {
+    match system_type {
+        // Without a Hessian the standard and slack-reduced systems coincide
+        AugmentedSystemType::Auto
+        | AugmentedSystemType::Standard
+        | AugmentedSystemType::SlackReduced => {
+            Box::new(mpc::MehrotraPredictorCorrector::<
                 'a,
-                crate::linalg::pardiso::MKLPardiso,
-                mpc::augmented_system::SlackReducedSystem<'a, crate::linalg::pardiso::MKLPardiso>,
-                mpc::mu_update::AdaptiveMuUpdate<'a>,
-            >::new(lp.into(), &self.options))),
-            #[cfg(feature = "panua")]
-            LPSolverType::MpcPanua => Ok(Box::new(mpc::MehrotraPredictorCorrector::<
+                LinSolve,
+                mpc::augmented_system::SlackReducedSystem<'a, LinSolve>,
+                Box<dyn mpc::mu_update::MuUpdate<'a> + 'a>,
+            >::new_with_symbolic(lp, options, symbolic))
+        }
+        AugmentedSystemType::NormalEquations => {
+            Box::new(mpc::MehrotraPredictorCorrector::<
                 'a,
-                crate::linalg::pardiso::PanuaSolver,
-                mpc::augmented_system::SlackReducedSystem<'a, crate::linalg::pardiso::PanuaSolver>,
-                mpc::mu_update::AdaptiveMuUpdate<'a>,
-            >::new(lp.into(), &self.options))),
+                LinSolve,
+                mpc::augmented_system::NormalEquationsSystem<'a, LinSolve>,
+                Box<dyn mpc::mu_update::MuUpdate<'a> + 'a>,
+            >::new_with_symbolic(lp, options, symbolic))
+        }
+        AugmentedSystemType::SchurComplement => {
+            Box::new(mpc::MehrotraPredictorCorrector::<
+                'a,
+                LinSolve,
+                mpc::augmented_system::SchurComplementSystem<'a, LinSolve>,
+                Box<dyn mpc::mu_update::MuUpdate<'a> + 'a>,
+            >::new_with_symbolic(lp, options, symbolic))
         }
     }
 }
 
 #[cfg(test)]
-mod test {
+pub(crate) mod test {
     use super::*;
 
-    use std::sync::OnceLock;
+    use std::{sync::OnceLock, time::Duration};
 
     use faer::{
         Col,
@@ -290,8 +754,12 @@ mod test {
     use rstest_reuse::{apply, template};
 
     use crate::{
-        E, I, SolverHooks, SolverOptions, SolverState, callback::ConvergenceOutput,
-        lp::LinearProgram, terminators::ConvergenceTerminator,
+        E, I, SolverHooks, SolverOptions, SolverState, StateView, Status,
+        callback::{ConvergenceOutput, NoOpCallback},
+        lp::LinearProgram,
+        terminators::{
+            ComplementarityTerminator, ConvergenceTerminator, RelativeConvergenceTerminator,
+        },
     };
 
     #[template]
@@ -341,6 +809,45 @@ mod test {
         })
     }
 
+    /// `min -x_0 - 2 x_1` subject to `x_0 + x_1 + s = 2`, `x_0 <= 1` and
+    /// `x_1 <= 1.5`, with the optimum `x = (0.5, 1.5, 0)` and `y = -1`.
+    #[fixture]
+    pub(crate) fn build_budget_lp() -> &'static LinearProgram {
+        static LP: OnceLock<LinearProgram> = OnceLock::new();
+        LP.get_or_init(|| {
+            let a_triplets: [Triplet<I, I, E>; 3] = [
+                Triplet::new(0, 0, 1.),
+                Triplet::new(0, 1, 1.),
+                Triplet::new(0, 2, 1.),
+            ];
+            let a = SparseColMat::try_new_from_triplets(1, 3, a_triplets.as_slice()).unwrap();
+
+            LinearProgram::new(
+                Col::from_fn(3, |j| [-1., -2., 0.][j]),
+                a,
+                Col::full(1, 2.),
+                Col::zeros(3),
+                Col::from_fn(3, |j| [1., 1.5, E::INFINITY][j]),
+            )
+        })
+    }
+
+    /// Solves `lp` to a tolerance of 1e-10 from [`SolverState::new_interior`]
+    /// without output and returns the optimal primal solution.
+    pub(crate) fn solve_silent(lp: &LinearProgram) -> Col<E> {
+        let mut state = SolverState::new_interior(&lp.l, &lp.u, lp.get_n_cons());
+        let mut options = SolverOptions::new();
+        options.set_option("tolerance", 1e-10).unwrap();
+        let mut hooks = SolverHooks::silent();
+        hooks.terminator = Box::new(ConvergenceTerminator::new(&options));
+        let mut solver = lp.solver_builder().with_options(options).build().unwrap();
+        assert_eq!(
+            solver.solve(&mut state, &mut hooks).unwrap(),
+            Status::Optimal
+        );
+        state.x
+    }
+
     #[fixture]
     fn build_options() -> &'static SolverOptions {
         static OPTIONS: OnceLock<SolverOptions> = OnceLock::new();
@@ -367,7 +874,7 @@ mod test {
         let options = SolverOptions::new();
 
         let mut properties = SolverHooks {
-            callback: Box::new(ConvergenceOutput::new()),
+            callback: Box::new(ConvergenceOutput::new(&options)),
             terminator: Box::new(ConvergenceTerminator::new(&options)),
         };
 
@@ -380,4 +887,1034 @@ mod test {
 
         assert_eq!(status.unwrap(), crate::Status::Optimal);
     }
+
+    #[rstest]
+    fn test_system_types(
+        #[values(build_simple_lp())] lp: &'static LinearProgram,
+        #[values(
+            AugmentedSystemType::Auto,
+            AugmentedSystemType::Standard,
+            AugmentedSystemType::SlackReduced,
+            AugmentedSystemType::NormalEquations,
+            AugmentedSystemType::SchurComplement
+        )]
+        system_type: AugmentedSystemType,
+    ) {
+        // Bound the free variable so that the normal equations are applicable
+        let lp = LinearProgram::new(
+            lp.c.clone(),
+            lp.A.clone(),
+            lp.b.clone(),
+            Col::from_fn(5, |i| [-10., 0., 0., 0., 0.][i]),
+            lp.u.clone(),
+        );
+
+        let mut state = SolverState::new(
+            Col::ones(lp.c.nrows()),
+            Col::ones(lp.b.nrows()),
+            Col::ones(lp.c.nrows()),
+            -Col::<E>::ones(lp.c.nrows()),
+        );
+
+        let options = SolverOptions::new();
+
+        let mut properties = SolverHooks {
+            callback: Box::new(ConvergenceOutput::new(&options)),
+            terminator: Box::new(ConvergenceTerminator::new(&options)),
+        };
+
+        let mut solver = LinearProgram::solver_builder(&lp)
+            .with_solver(LPSolverType::MpcSimplicialCholesky)
+            .with_system(system_type)
+            .with_options(options.clone())
+            .build()
+            .unwrap();
+        let status = solver.solve(&mut state, &mut properties);
+
+        assert_eq!(status.unwrap(), crate::Status::Optimal);
+    }
+
+    #[rstest]
+    fn test_centrality_correctors(
+        #[values(build_simple_lp())] lp: &'static LinearProgram,
+        #[values(
+            AugmentedSystemType::SlackReduced,
+            AugmentedSystemType::NormalEquations,
+            AugmentedSystemType::SchurComplement
+        )]
+        system_type: AugmentedSystemType,
+        #[values("0", "3", "auto")] correctors: &str,
+    ) {
+        let lp = LinearProgram::new(
+            lp.c.clone(),
+            lp.A.clone(),
+            lp.b.clone(),
+            Col::from_fn(5, |i| [-10., 0., 0., 0., 0.][i]),
+            lp.u.clone(),
+        );
+        let solve = |correctors: &str| {
+            let mut options = SolverOptions::new();
+            options
+                .set_option(
+                    "centrality_correctors",
+                    correctors.parse::<CorrectorCount>().unwrap(),
+                )
+                .unwrap();
+            let mut state = SolverState::new_interior(&lp.l, &lp.u, 3);
+            let mut solver = LinearProgram::solver_builder(&lp)
+                .with_solver(LPSolverType::MpcSimplicialCholesky)
+                .with_system(system_type)
+                .with_options(options.clone())
+                .build()
+                .unwrap();
+            let mut hooks = SolverHooks::new(
+                Box::new(NoOpCallback::new()),
+                Box::new(ComplementarityTerminator::new(&options)),
+            );
+            let status = solver.solve(&mut state, &mut hooks);
+            assert_eq!(status.unwrap(), crate::Status::Optimal);
+            lp.get_objective_value(state.get_primal())
+        };
+        // x = (0.5, 1.5, 0, 0, 6.5)
+        assert!((solve(correctors) - 2.5).abs() < 1e-6);
+    }
+
+    /// Solves `lp` with `System` and returns the objective and the number of
+    /// skipped factorizations.
+    fn solve_skipping<'a, System>(lp: &'a LinearProgram, threshold: E) -> (E, usize)
+    where
+        System: mpc::augmented_system::AugmentedSystem<'a, SimplicialSparseCholesky>,
+    {
+        let mut options = SolverOptions::new();
+        options
+            .set_option("refactorization_threshold", threshold)
+            .unwrap();
+        let mut solver = mpc::MehrotraPredictorCorrector::<
+            SimplicialSparseCholesky,
+            System,
+            mpc::mu_update::AdaptiveMuUpdate,
+        >::new(lp, &options);
+        let mut hooks = SolverHooks::new(
+            Box::new(NoOpCallback::new()),
+            Box::new(ComplementarityTerminator::new(&options)),
+        );
+        let mut state = SolverState::new_interior(&lp.l, &lp.u, lp.get_n_cons());
+        let status = solver.solve(&mut state, &mut hooks);
+        assert_eq!(status.unwrap(), crate::Status::Optimal);
+        (
+            lp.get_objective_value(state.get_primal()),
+            solver.get_n_skipped_factorizations(),
+        )
+    }
+
+    #[rstest]
+    fn test_refactorization_skipping(
+        #[values(build_simple_lp())] lp: &'static LinearProgram,
+        #[values(0., 2.)] threshold: E,
+    ) {
+        let lp = LinearProgram::new(
+            lp.c.clone(),
+            lp.A.clone(),
+            lp.b.clone(),
+            Col::from_fn(5, |i| [-10., 0., 0., 0., 0.][i]),
+            lp.u.clone(),
+        );
+        for (objective, skipped) in [
+            solve_skipping::<mpc::augmented_system::SlackReducedSystem<_>>(&lp, threshold),
+            solve_skipping::<mpc::augmented_system::NormalEquationsSystem<_>>(&lp, threshold),
+            solve_skipping::<mpc::augmented_system::SchurComplementSystem<_>>(&lp, threshold),
+        ] {
+            // x = (0.5, 1.5, 0, 0, 6.5)
+            assert!((objective - 2.5).abs() < 1e-6, "objective {objective}");
+            assert_eq!(skipped > 0, threshold > 0., "skipped {skipped}");
+        }
+    }
+
+    #[test]
+    fn test_corrector_count() {
+        assert_eq!("auto".parse(), Ok(CorrectorCount::Auto));
+        assert_eq!("2".parse(), Ok(CorrectorCount::Fixed(2)));
+        assert!("many".parse::<CorrectorCount>().is_err());
+
+        let mut costs = SolveCosts::new();
+        assert_eq!(CorrectorCount::Fixed(2).get_max_correctors(&costs), 2);
+        assert_eq!(CorrectorCount::Auto.get_max_correctors(&costs), 1);
+
+        // Factorizations costing 5, 35 and 1000 solves
+        costs.record_solve(Duration::from_millis(2));
+        for (factor, correctors) in [(10, 1), (70, 3), (2000, 6)] {
+            let mut costs = costs;
+            costs.record_factor(Duration::from_millis(factor));
+            assert_eq!(CorrectorCount::Auto.get_max_correctors(&costs), correctors);
+        }
+    }
+
+    #[rstest]
+    fn test_kkt_dump(#[values(build_simple_lp())] lp: &'static LinearProgram) {
+        let directory = tempfile::tempdir().unwrap();
+        let mut state = SolverState::new(
+            Col::ones(lp.c.nrows()),
+            Col::ones(lp.b.nrows()),
+            Col::ones(lp.c.nrows()),
+            -Col::<E>::ones(lp.c.nrows()),
+        );
+
+        let mut options = SolverOptions::new();
+        options
+            .set_option(
+                "kkt_dump_directory",
+                directory.path().to_str().unwrap().to_string(),
+            )
+            .unwrap();
+        options
+            .set_option("kkt_dump_iterations", "0, 2".to_string())
+            .unwrap();
+
+        let mut properties = SolverHooks {
+            callback: Box::new(ConvergenceOutput::new(&options)),
+            terminator: Box::new(ConvergenceTerminator::new(&options)),
+        };
+        let mut solver = LinearProgram::solver_builder(lp)
+            .with_solver(LPSolverType::MpcSimplicialCholesky)
+            .with_system(AugmentedSystemType::SlackReduced)
+            .with_options(options)
+            .build()
+            .unwrap();
+        assert_eq!(
+            solver.solve(&mut state, &mut properties).unwrap(),
+            crate::Status::Optimal
+        );
+
+        for stage in ["affine", "corrector"] {
+            for name in [
+                "kkt", "r_d", "r_c", "r_l", "r_u", "dx", "dy", "dz_l", "dz_u",
+            ] {
+                for nit in [0, 2] {
+                    let path = directory
+                        .path()
+                        .join(format!("iter_{nit:04}_{stage}_{name}.mtx"));
+                    assert!(path.exists(), "{} is missing", path.display());
+                }
+            }
+        }
+        assert!(!directory.path().join("iter_0001_affine_kkt.mtx").exists());
+
+        // The matrix of the slack-reduced system has n_var + n_con rows
+        let kkt =
+            std::fs::read_to_string(directory.path().join("iter_0000_affine_kkt.mtx")).unwrap();
+        assert!(kkt.lines().nth(1).unwrap().starts_with("8 8 "));
+    }
+
+    #[rstest]
+    fn test_normal_equations_free_variable(
+        #[values(build_simple_lp())] lp: &'static LinearProgram,
+    ) {
+        let solver = LinearProgram::solver_builder(lp)
+            .with_solver(LPSolverType::MpcSimplicialCholesky)
+            .with_system(AugmentedSystemType::NormalEquations)
+            .build();
+
+        assert!(solver.is_err());
+    }
+
+    #[rstest]
+    fn test_schur_complement_direction(#[values(build_simple_lp())] lp: &'static LinearProgram) {
+        use mpc::augmented_system::{AugmentedSystem, SchurComplementSystem, SlackReducedSystem};
+
+        let mut state = SolverState::new(
+            Col::ones(lp.c.nrows()),
+            Col::ones(lp.b.nrows()),
+            Col::ones(lp.c.nrows()),
+            Col::zeros(lp.c.nrows()),
+        );
+        state.sigma = Some(0.1);
+        state.mu = Some(1.);
+        lp.update_residual(&mut state);
+        let rhs = crate::ipm::RHS::from(&state);
+
+        let options = SolverOptions::new();
+        let mut full = SlackReducedSystem::<SimplicialSparseCholesky>::new(lp, &options);
+        let mut reduced = SchurComplementSystem::<SimplicialSparseCholesky>::new(lp, &options);
+
+        // The singleton slack columns 2, 3 and 4 are eliminated
+        assert_eq!(reduced.get_n_eliminated(), 3);
+        assert_eq!(reduced.get_matrix().nrows(), 5);
+
+        let expected = full.solve(&state, &rhs).unwrap();
+        let direction = reduced.solve(&state, &rhs).unwrap();
+        for (a, b) in [
+            (&expected.dx, &direction.dx),
+            (&expected.dy, &direction.dy),
+            (&expected.dz_l, &direction.dz_l),
+            (&expected.dz_u, &direction.dz_u),
+        ] {
+            assert!((a - b).norm_max() < 1e-10);
+        }
+    }
+
+    #[fixture]
+    fn build_transportation_lp() -> &'static LinearProgram {
+        static LP: OnceLock<LinearProgram> = OnceLock::new();
+        LP.get_or_init(|| {
+            // Two sources with supplies (3, 4) and three sinks with demands (2, 3, 2).
+            // Arc j = 3 s + t ships from source s to sink t
+            let a_triplets = (0..6)
+                .flat_map(|j| [Triplet::new(j / 3, j, 1.), Triplet::new(2 + j % 3, j, -1.)])
+                .collect::<Vec<Triplet<I, I, E>>>();
+            let a = SparseColMat::try_new_from_triplets(5, 6, a_triplets.as_slice()).unwrap();
+
+            LinearProgram::new(
+                Col::from_fn(6, |j| [4., 6., 9., 5., 3., 8.][j]),
+                a,
+                Col::from_fn(5, |i| [3., 4., -2., -3., -2.][i]),
+                Col::zeros(6),
+                Col::from_fn(6, |j| if j == 4 { 2. } else { E::INFINITY }),
+            )
+        })
+    }
+
+    /// `min -x_0 - 2 x_1` subject to the ranged row `1 <= x_0 + x_1 <= 3` and
+    /// the free row `x_0 - x_1`, with `x_0 <= 2`. The rows are equalities with
+    /// the slack `0 <= s_0 <= 2` and the free slack `s_1`, whose multiplier
+    /// vanishes. The optimum is `(x, s) = (0, 3, 2, 3)` with `y = (-2, 0)`,
+    /// `z_l = (1, 0, 0, 0)` and `z_u = (0, 0, -2, 0)`.
+    #[fixture]
+    pub(crate) fn build_ranged_lp() -> &'static LinearProgram {
+        static LP: OnceLock<LinearProgram> = OnceLock::new();
+        LP.get_or_init(|| {
+            let a_triplets: [Triplet<I, I, E>; 6] = [
+                Triplet::new(0, 0, 1.),
+                Triplet::new(1, 0, 1.),
+                Triplet::new(0, 1, 1.),
+                Triplet::new(1, 1, -1.),
+                Triplet::new(0, 2, -1.),
+                Triplet::new(1, 3, 1.),
+            ];
+            let a = SparseColMat::try_new_from_triplets(2, 4, a_triplets.as_slice()).unwrap();
+
+            LinearProgram::new(
+                Col::from_fn(4, |j| [-1., -2., 0., 0.][j]),
+                a,
+                Col::from_fn(2, |i| [1., 0.][i]),
+                Col::from_fn(4, |j| [0., 0., 0., -E::INFINITY][j]),
+                Col::from_fn(4, |j| [2., E::INFINITY, 2., E::INFINITY][j]),
+            )
+        })
+    }
+
+    #[apply(solver_types)]
+    fn test_ranged_and_free_rows(
+        #[values(build_ranged_lp())] lp: &'static LinearProgram,
+        solver_type: LPSolverType,
+        #[values(
+            AugmentedSystemType::SlackReduced,
+            AugmentedSystemType::SchurComplement
+        )]
+        system_type: AugmentedSystemType,
+    ) {
+        let mut state = SolverState::new_interior(&lp.l, &lp.u, lp.get_n_cons());
+        let status = lp
+            .solver_builder()
+            .with_solver(solver_type)
+            .with_system(system_type)
+            .build()
+            .unwrap()
+            .solve(&mut state, &mut SolverHooks::silent())
+            .unwrap();
+        assert_eq!(status, Status::Optimal);
+
+        assert!((&state.x - Col::from_fn(4, |j| [0., 3., 2., 3.][j])).norm_max() < 1e-6);
+        assert!((&state.y - Col::from_fn(2, |i| [-2., 0.][i])).norm_max() < 1e-6);
+        assert!((&state.z_l - Col::from_fn(4, |j| [1., 0., 0., 0.][j])).norm_max() < 1e-6);
+        assert!((&state.z_u - Col::from_fn(4, |j| [0., 0., -2., 0.][j])).norm_max() < 1e-6);
+
+        // Infinite bounds keep zero multipliers and add no complementarity
+        assert_eq!((state.z_l[3], state.z_u[1], state.z_u[3]), (0., 0., 0.));
+        assert_eq!(
+            (state.cs_lower[3], state.cs_upper[1], state.cs_upper[3]),
+            (0., 0., 0.)
+        );
+        assert!(state.get_dual_feasibility().norm_max() < 1e-6);
+        assert!(state.get_primal_feasibility().norm_max() < 1e-6);
+        assert!(state.get_cs_lower().norm_max() < 1e-6 && state.get_cs_upper().norm_max() < 1e-6);
+    }
+
+    #[test]
+    fn test_free_row_step_length() {
+        // The free slack and the multipliers of infinite bounds never block a step
+        let lp = build_ranged_lp();
+        let state = SolverState::new_interior(&lp.l, &lp.u, lp.get_n_cons());
+        let step = crate::SearchDirection {
+            dx: Col::from_fn(4, |j| [0., 0., 0., -1e6][j]),
+            dy: Col::from_fn(2, |i| [0., 1e6][i]),
+            dz_l: Col::from_fn(4, |j| [0., 0., 0., -1e6][j]),
+            dz_u: Col::from_fn(4, |j| [0., 1e6, 0., 1e6][j]),
+        };
+        let options = SolverOptions::new();
+        let length = mpc::line_search::compute_max_step_length(lp, &options, &state, &step);
+        assert_eq!((length.get_primal(), length.get_dual()), (1., 1.));
+
+        // The finite bounds of the ranged slack do
+        let step = crate::SearchDirection {
+            dx: Col::from_fn(4, |j| [0., 0., 2., 0.][j]),
+            ..step
+        };
+        let length = mpc::line_search::compute_max_step_length(lp, &options, &state, &step);
+        assert!((length.get_primal() - 0.5).abs() < 1e-12);
+        assert_eq!(length.get_blocking_primal(), Some(2));
+    }
+
+    #[fixture]
+    fn build_circulation_lp() -> &'static LinearProgram {
+        static LP: OnceLock<LinearProgram> = OnceLock::new();
+        LP.get_or_init(|| {
+            // Zero supplies, so the origin is feasible but a negative-cost cycle
+            // through the ground node carries flow at the optimum
+            let a_triplets = [
+                Triplet::new(0, 0, -1.),
+                Triplet::new(1, 0, -1.),
+                Triplet::new(0, 1, 1.),
+                Triplet::new(0, 2, 1.),
+                Triplet::new(1, 3, 1.),
+                Triplet::new(1, 4, 1.),
+            ];
+            let a = SparseColMat::try_new_from_triplets(2, 5, &a_triplets).unwrap();
+
+            LinearProgram::new(
+                Col::from_fn(5, |j| [1., -1.5, 0., -1.5, 0.][j]),
+                a,
+                Col::zeros(2),
+                Col::zeros(5),
+                Col::from_fn(5, |j| [10., 1., E::INFINITY, 3., E::INFINITY][j]),
+            )
+        })
+    }
+
+    #[rstest]
+    #[case(build_transportation_lp(), 36.)]
+    #[case(build_circulation_lp(), -3.)]
+    fn test_network_simplex(#[case] lp: &'static LinearProgram, #[case] objective: E) {
+        assert!(network::is_network_lp(lp));
+
+        let mut state = SolverState::new(
+            Col::zeros(lp.c.nrows()),
+            Col::zeros(lp.b.nrows()),
+            Col::zeros(lp.c.nrows()),
+            Col::zeros(lp.c.nrows()),
+        );
+
+        let options = SolverOptions::new();
+
+        let mut properties = SolverHooks {
+            callback: Box::new(ConvergenceOutput::new(&options)),
+            terminator: Box::new(ConvergenceTerminator::new(&options)),
+        };
+
+        // No solver type: the network structure is detected automatically
+        let mut solver = LinearProgram::solver_builder(lp)
+            .with_options(options.clone())
+            .build()
+            .unwrap();
+        let status = solver.solve(&mut state, &mut properties);
+
+        assert_eq!(status.unwrap(), crate::Status::Optimal);
+        assert_eq!(lp.get_objective_value(&state.x), objective);
+        assert!(crate::verify::check_solution(lp, &state, 1e-9).is_optimal());
+    }
+
+    #[rstest]
+    fn test_network_simplex_non_network(#[values(build_simple_lp())] lp: &'static LinearProgram) {
+        let solver = LinearProgram::solver_builder(lp)
+            .with_solver(LPSolverType::NetworkSimplex)
+            .build();
+
+        assert!(solver.is_err());
+    }
+
+    #[rstest]
+    fn test_objective_values(#[values(build_simple_lp())] lp: &'static LinearProgram) {
+        // Multipliers only on finite bounds, so that the dual objective is finite
+        let mut state = SolverState::new_interior(&lp.l, &lp.u, lp.b.nrows());
+        assert_eq!(state.get_primal_objective(), None);
+
+        let options = SolverOptions::new();
+        let mut properties = SolverHooks {
+            callback: Box::new(NoOpCallback::new()),
+            terminator: Box::new(ComplementarityTerminator::new(&options)),
+        };
+        let mut solver = LinearProgram::solver_builder(lp)
+            .with_solver(LPSolverType::MpcSimplicialCholesky)
+            .with_options(options.clone())
+            .build()
+            .unwrap();
+        let status = solver.solve(&mut state, &mut properties);
+        assert_eq!(status.unwrap(), crate::Status::Optimal);
+
+        let primal = state.get_primal_objective().unwrap();
+        let dual = state.get_dual_objective().unwrap();
+        assert!((primal - lp.get_objective_value(state.get_primal())).abs() < 1e-12);
+        assert!((primal - dual).abs() < 1e-5, "primal {primal}, dual {dual}");
+        assert_eq!(state.get_duality_gap(), Some(primal - dual));
+    }
+
+    #[rstest]
+    fn test_hooks_presets(#[values(build_simple_lp())] lp: &'static LinearProgram) {
+        let options = SolverOptions::new();
+        let solve = |hooks: &mut SolverHooks| {
+            let mut state = SolverState::new_interior(&lp.l, &lp.u, lp.b.nrows());
+            LinearProgram::solver_builder(lp)
+                .with_solver(LPSolverType::MpcSimplicialCholesky)
+                .with_options(options.clone())
+                .build()
+                .unwrap()
+                .solve(&mut state, hooks)
+                .unwrap()
+        };
+
+        assert_eq!(solve(&mut SolverHooks::silent()), crate::Status::Optimal);
+        assert_eq!(solve(&mut SolverHooks::verbose()), crate::Status::Optimal);
+        assert_eq!(
+            solve(&mut SolverHooks::default_for(&options).unwrap()),
+            crate::Status::Optimal
+        );
+        assert_eq!(
+            solve(&mut SolverHooks::silent().with_time_limit(std::time::Duration::ZERO)),
+            crate::Status::TimeLimit
+        );
+
+        let token = crate::terminators::CancellationToken::new();
+        token.cancel();
+        assert_eq!(
+            solve(&mut SolverHooks::silent().with_cancellation(token)),
+            crate::Status::Interrupted
+        );
+    }
+
+    #[rstest]
+    fn test_solve_on_worker_threads(#[values(build_simple_lp())] lp: &'static LinearProgram) {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<LinearProgram>();
+        assert_send_sync::<SolverOptions>();
+
+        let statuses: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = [
+                LPSolverType::MpcSimplicialCholesky,
+                LPSolverType::MpcSupernodalCholesky,
+            ]
+            .into_iter()
+            .map(|solver_type| {
+                let mut solver = LinearProgram::solver_builder(lp)
+                    .with_solver(solver_type)
+                    .build()
+                    .unwrap();
+                let mut hooks = SolverHooks::silent();
+                scope.spawn(move || {
+                    let mut state = SolverState::new_interior(&lp.l, &lp.u, lp.b.nrows());
+                    solver.solve(&mut state, &mut hooks).unwrap()
+                })
+            })
+            .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        assert!(statuses.iter().all(|s| *s == crate::Status::Optimal));
+    }
+
+    #[rstest]
+    fn test_memory_limit(#[values(build_simple_lp())] lp: &'static LinearProgram) {
+        let solve = |max_memory: E| {
+            let mut options = SolverOptions::new();
+            options.set_option("max_memory", max_memory).unwrap();
+            let mut solver = LinearProgram::solver_builder(lp)
+                .with_solver(LPSolverType::MpcSupernodalCholesky)
+                .with_options(options)
+                .build()
+                .unwrap();
+            let estimate = solver.estimate_memory().unwrap();
+            assert!(estimate.get_total_bytes() > 0);
+
+            let mut state = SolverState::new_interior(&lp.l, &lp.u, lp.b.nrows());
+            let status = solver
+                .solve(&mut state, &mut SolverHooks::silent())
+                .unwrap();
+            (status, state.get_nit())
+        };
+
+        // A limit of one byte stops before the first factorization
+        assert_eq!(solve(1. / (1024. * 1024.)), (crate::Status::MemoryLimit, 0));
+        assert_eq!(solve(0.).0, crate::Status::Optimal);
+        assert_eq!(solve(1.).0, crate::Status::Optimal);
+    }
+
+    #[rstest]
+    fn test_shared_symbolic(
+        #[values(build_simple_lp())] lp: &'static LinearProgram,
+        #[values(
+            LPSolverType::MpcSimplicialCholesky,
+            LPSolverType::MpcSupernodalCholesky
+        )]
+        solver_type: LPSolverType,
+    ) {
+        let symbolic = lp
+            .solver_builder()
+            .with_solver(solver_type)
+            .with_system(AugmentedSystemType::SlackReduced)
+            .analyze()
+            .unwrap();
+
+        // Same sparsity pattern, another right-hand side
+        let mut shifted = lp.clone();
+        shifted.b = Col::from_fn(3, |i| [-1., 5., 2.][i]);
+        let solve = |symbolic: Option<&SymbolicAnalysis>, system_type| {
+            let mut builder = shifted
+                .solver_builder()
+                .with_solver(solver_type)
+                .with_system(system_type);
+            if let Some(symbolic) = symbolic {
+                builder = builder.with_symbolic(symbolic.clone());
+            }
+            let mut solver = builder.build().unwrap();
+            let shared = solver.get_symbolic().unwrap();
+            let mut state = SolverState::new_interior(&lp.l, &lp.u, lp.b.nrows());
+            let status = solver
+                .solve(&mut state, &mut SolverHooks::silent())
+                .unwrap();
+            assert_eq!(status, crate::Status::Optimal);
+            (shared, state.x)
+        };
+
+        let (shared, x) = solve(Some(&symbolic), AugmentedSystemType::SlackReduced);
+        assert!(std::sync::Arc::ptr_eq(&shared.0, &symbolic.0));
+        let (fresh, x_fresh) = solve(None, AugmentedSystemType::SlackReduced);
+        assert!(!std::sync::Arc::ptr_eq(&fresh.0, &symbolic.0));
+        assert!((&x - &x_fresh).norm_max() < 1e-8);
+
+        // The analysis of another system does not match and is analyzed again
+        let (other, x_other) = solve(Some(&symbolic), AugmentedSystemType::SchurComplement);
+        assert!(!std::sync::Arc::ptr_eq(&other.0, &symbolic.0));
+        assert!((&x - &x_other).norm_max() < 1e-6);
+
+        // Nor does the analysis of a program with the dimensions and number
+        // of entries but not the pattern of the analyzed one
+        let swap = [1, 0, 2];
+        let triplets = (0..shifted.A.ncols())
+            .flat_map(|j| {
+                let a = shifted.A.as_ref();
+                a.row_idx_of_col(j)
+                    .zip(a.val_of_col(j))
+                    .map(move |(i, &v)| Triplet::new(to_index(swap[i]), to_index(j), v))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let swapped = LinearProgram::new(
+            shifted.c.clone(),
+            SparseColMat::try_new_from_triplets(3, 5, &triplets).unwrap(),
+            Col::from_fn(3, |i| shifted.b[swap[i]]),
+            shifted.l.clone(),
+            shifted.u.clone(),
+        );
+        assert_eq!(swapped.A.compute_nnz(), shifted.A.compute_nnz());
+        let mut solver = swapped
+            .solver_builder()
+            .with_solver(solver_type)
+            .with_system(AugmentedSystemType::SlackReduced)
+            .with_symbolic(symbolic.clone())
+            .build()
+            .unwrap();
+        assert!(!std::sync::Arc::ptr_eq(
+            &solver.get_symbolic().unwrap().0,
+            &symbolic.0
+        ));
+        let mut state = SolverState::new_interior(&swapped.l, &swapped.u, 3);
+        let status = solver
+            .solve(&mut state, &mut SolverHooks::silent())
+            .unwrap();
+        assert_eq!(status, crate::Status::Optimal);
+        assert!((&x - &state.x).norm_max() < 1e-6);
+    }
+
+    #[test]
+    fn test_symbolic_cache() {
+        let lp = build_simple_lp();
+        let cache = symbolic_cache::SymbolicCache::new();
+        let solve = |lp: &LinearProgram, system_type| {
+            let mut solver = cache
+                .build(lp.solver_builder().with_system(system_type))
+                .unwrap();
+            let mut state = SolverState::new_interior(&lp.l, &lp.u, lp.b.nrows());
+            let status = solver
+                .solve(&mut state, &mut SolverHooks::silent())
+                .unwrap();
+            assert_eq!(status, crate::Status::Optimal);
+            (solver.get_symbolic().unwrap(), state.x)
+        };
+
+        let (first, _) = solve(lp, AugmentedSystemType::SlackReduced);
+        assert_eq!((cache.len(), cache.get_n_misses()), (1, 1));
+
+        // Same sparsity pattern, other right-hand sides
+        for b in [[-1., 5., 2.], [-3., 2., 0.5]] {
+            let mut shifted = lp.clone();
+            shifted.b = Col::from_fn(3, |i| b[i]);
+            let (shared, x) = solve(&shifted, AugmentedSystemType::SlackReduced);
+            assert!(std::sync::Arc::ptr_eq(&shared.0, &first.0));
+
+            let mut state = SolverState::new_interior(&lp.l, &lp.u, 3);
+            shifted
+                .solver_builder()
+                .with_system(AugmentedSystemType::SlackReduced)
+                .build()
+                .unwrap()
+                .solve(&mut state, &mut SolverHooks::silent())
+                .unwrap();
+            assert!((&x - &state.x).norm_max() < 1e-8);
+        }
+        assert_eq!((cache.get_n_hits(), cache.get_n_misses()), (2, 1));
+
+        // Another system, or another pattern of finite bounds, is analyzed again
+        let (other, _) = solve(lp, AugmentedSystemType::SchurComplement);
+        assert!(!std::sync::Arc::ptr_eq(&other.0, &first.0));
+        let mut bounded = lp.clone();
+        bounded.u = Col::full(5, 1e3);
+        solve(&bounded, AugmentedSystemType::SlackReduced);
+        assert_eq!((cache.len(), cache.get_n_misses()), (3, 3));
+
+        // A program of another pattern under the same key, as after a hash
+        // collision, is not given the stored analysis
+        let key = symbolic_cache::CacheKey::new(
+            lp,
+            LPSolverType::MpcSimplicialCholesky,
+            AugmentedSystemType::SlackReduced,
+            None,
+        );
+        let mut permuted = lp.clone();
+        permuted.A = SparseColMat::try_new_from_triplets(
+            3,
+            5,
+            &lp.A
+                .triplet_iter()
+                .map(|t| Triplet::new(to_index((t.row + 1) % 3), to_index(t.col), *t.val))
+                .collect::<Vec<_>>(),
+        )
+        .unwrap();
+        cache.insert(lp, key.clone(), first);
+        assert!(cache.get(lp, &key).is_some());
+        assert!(cache.get(&permuted, &key).is_none());
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_effective_options() {
+        let lp = build_simple_lp();
+        let mut options = SolverOptions::new();
+        options.set_option("max_iterations", 50usize).unwrap();
+        options.set_option("lazy_max_rounds", 3usize).unwrap();
+
+        let mut state = SolverState::new_interior(&lp.l, &lp.u, lp.b.nrows());
+        let result = lp
+            .solver_builder()
+            .with_options(options)
+            .build()
+            .unwrap()
+            .solve_detailed(&mut state, &mut SolverHooks::silent())
+            .unwrap();
+        let effective = result.get_effective_options().unwrap();
+
+        let max_iterations = effective.get("max_iterations").unwrap();
+        assert_eq!(max_iterations.get_value(), "50");
+        assert_eq!(max_iterations.get_source(), crate::OptionSource::User);
+        let centrality = effective.get("centrality_correctors").unwrap();
+        assert_eq!(centrality.get_source(), crate::OptionSource::Default);
+        // Options of components the solve does not use are not reported
+        assert!(effective.get("lazy_max_rounds").is_none());
+        assert!(effective.to_string().contains("max_iterations"));
+    }
+
+    #[test]
+    fn test_scoped_options() {
+        let lp = build_simple_lp();
+        let mut options = SolverOptions::new();
+        options.set_option("qp.mpc.max_iterations", 1usize).unwrap();
+        options.set_option("lp.max_iterations", 3usize).unwrap();
+        options.set_option("lp.mpc.max_iterations", 2usize).unwrap();
+        assert!(options.set_option("lp.mpc.max_iterations", 2.).is_err());
+        assert!(
+            options
+                .set_option("lp.mpc.lazy_max_rounds", 2usize)
+                .is_err()
+        );
+        assert!(options.set_option("nlp.gd.mu_fixed", 2.).is_err());
+        assert!(options.set_option("lp.mpc.no_such_option", 2usize).is_err());
+        assert_eq!(
+            options.get_scoped_option::<usize>("lp.mpc", "max_iterations"),
+            Some(2)
+        );
+        assert_eq!(
+            options.get_scoped_option::<usize>("lp.network_simplex", "max_iterations"),
+            Some(3)
+        );
+        assert_eq!(
+            options.get_scoped_option::<usize>("nlp.gd", "max_iterations"),
+            Some(0)
+        );
+
+        let mut state = SolverState::new_interior(&lp.l, &lp.u, lp.b.nrows());
+        let result = lp
+            .solver_builder()
+            .with_options(options)
+            .build()
+            .unwrap()
+            .solve_detailed(&mut state, &mut SolverHooks::silent())
+            .unwrap();
+        // The iterations are counted from 0
+        assert_eq!(result.get_status(), crate::Status::IterationLimit);
+        assert_eq!(state.get_nit(), 1);
+        let effective = result.get_effective_options().unwrap();
+        assert_eq!(
+            effective.get("lp.mpc.max_iterations").unwrap().get_value(),
+            "2"
+        );
+
+        let listing = SolverOptions::describe();
+        let mu_fixed = listing
+            .lines()
+            .find(|line| line.starts_with("mu_fixed "))
+            .unwrap();
+        assert!(mu_fixed.contains("lp.mpc, qp.mpc"));
+    }
+
+    #[test]
+    fn test_option_info() {
+        let names: Vec<_> = SolverOptions::iter().map(|info| info.get_name()).collect();
+        assert!(names.is_sorted());
+        assert!(names.contains(&"lp_mu_update"));
+        assert!(SolverOptions::get_info("no_such_option").is_none());
+
+        let info = SolverOptions::get_info("centrality_correctors").unwrap();
+        assert_eq!(info.get_type_name(), "crate::ipm::CorrectorCount");
+        assert_eq!(info.get_default(), Some("0"));
+        assert_eq!(info.get_scopes(), ["lp.mpc"]);
+        assert_eq!(info.get_owners(), ["MehrotraPredictorCorrector"]);
+        assert!(
+            info.get_description()
+                .starts_with("Maximum number of centrality")
+        );
+
+        let json = serde_json::to_string(info).unwrap();
+        assert!(json.contains(r#""scopes":["lp.mpc"]"#));
+    }
+
+    #[test]
+    fn test_norms() {
+        // min x_0 + 2 x_1 subject to x_0 + 3 x_1 = 1e6, x >= 0
+        let a = SparseColMat::try_new_from_triplets(
+            1,
+            2,
+            &[Triplet::new(0, 0, 1.), Triplet::new(0, 1, 3.)],
+        )
+        .unwrap();
+        let mut lp = LinearProgram::new(
+            faer::col![1., 2.],
+            a,
+            faer::col![1e6],
+            faer::col![0., 0.],
+            faer::col![E::INFINITY, E::INFINITY],
+        );
+        let norms = lp.get_norms().unwrap();
+        assert_eq!(norms, ProblemNorms::new(3., 1e6, 2., 0.));
+        assert_eq!(norms.get_spread(), 1e6 / 2.);
+
+        // Residuals relative to 1 + ‖b‖∞ stop a solve at the scale of the data
+        let options = SolverOptions::new();
+        let mut hooks = SolverHooks::new(
+            Box::new(NoOpCallback::new()),
+            Box::new(RelativeConvergenceTerminator::new(&options)),
+        );
+        let mut state = SolverState::new_interior(&lp.l, &lp.u, 1);
+        let status = lp
+            .solver_builder()
+            .build()
+            .unwrap()
+            .solve(&mut state, &mut hooks)
+            .unwrap();
+        assert_eq!(status, crate::Status::Optimal);
+        assert_eq!(state.get_norms(), Some(norms));
+        assert!((lp.get_objective_value(state.get_primal()) / (2e6 / 3.) - 1.).abs() < 1e-6);
+
+        // The cached norms follow added rows
+        let row = SparseColMat::try_new_from_triplets(1, 2, &[Triplet::new(0, 0, -5.)]).unwrap();
+        lp.add_rows(row.as_ref(), faer::col![-1e7].as_ref())
+            .unwrap();
+        assert_eq!(lp.get_norms(), Some(ProblemNorms::new(5., 1e7, 2., 0.)));
+    }
+
+    #[test]
+    fn test_add_columns_and_rows() {
+        // min x_0 + 2 x_1 subject to x_0 + x_1 = 1, 0 <= x <= 1
+        let a = SparseColMat::try_new_from_triplets(
+            1,
+            2,
+            &[Triplet::new(0, 0, 1.), Triplet::new(0, 1, 1.)],
+        )
+        .unwrap();
+        let mut lp = LinearProgram::new(
+            faer::col![1., 2.],
+            a,
+            faer::col![1.],
+            faer::col![0., 0.],
+            faer::col![1., 1.],
+        );
+        // Warm starts are dual feasible, so convergence also requires complementarity
+        let options = SolverOptions::new();
+        let mut hooks = SolverHooks::new(
+            Box::new(NoOpCallback::new()),
+            Box::new(ComplementarityTerminator::new(&options)),
+        );
+        let mut solve = |lp: &LinearProgram, state: &mut SolverState| {
+            let status = lp
+                .solver_builder()
+                .with_solver(LPSolverType::MpcSimplicialCholesky)
+                .build()
+                .unwrap()
+                .solve(state, &mut hooks)
+                .unwrap();
+            assert_eq!(status, crate::Status::Optimal);
+            lp.get_objective_value(state.get_primal())
+        };
+        let mut state = SolverState::new_interior(&lp.l, &lp.u, 1);
+        assert!((solve(&lp, &mut state) - 1.).abs() < 1e-6);
+
+        // A cheaper column x_2 with cost 0.5
+        let col = SparseColMat::try_new_from_triplets(1, 1, &[Triplet::new(0, 0, 1.)]).unwrap();
+        lp.add_columns(
+            col.as_ref(),
+            faer::col![0.5].as_ref(),
+            faer::col![0.].as_ref(),
+            faer::col![1.].as_ref(),
+        )
+        .unwrap();
+        assert_eq!(lp.get_dims(), (3, 1));
+        let mut state = lp.extend_state(&state, 1e-2);
+        assert!((solve(&lp, &mut state) - 0.5).abs() < 1e-6);
+
+        // x_0 - x_2 = 0 makes the optimum x = (0.5, 0, 0.5)
+        let row = SparseColMat::try_new_from_triplets(
+            1,
+            3,
+            &[Triplet::new(0, 0, 1.), Triplet::new(0, 2, -1.)],
+        )
+        .unwrap();
+        lp.add_rows(row.as_ref(), faer::col![0.].as_ref()).unwrap();
+        assert_eq!(lp.get_dims(), (3, 2));
+        assert_eq!(lp.get_constraint_matrix().compute_nnz(), 5);
+        // The cached row-major copy follows the added columns and rows
+        let a_csr = lp.get_constraint_matrix_csr();
+        assert_eq!(
+            (a_csr.nrows(), a_csr.ncols(), a_csr.compute_nnz()),
+            (2, 3, 5)
+        );
+        assert_eq!(a_csr.to_dense(), lp.get_constraint_matrix().to_dense());
+        let mut state = lp.extend_state(&state, 1e-2);
+        assert!((solve(&lp, &mut state) - 0.75).abs() < 1e-6);
+
+        // Mismatched dimensions are rejected without changing the program
+        assert!(lp.add_rows(col.as_ref(), faer::col![0.].as_ref()).is_err());
+        assert!(
+            lp.add_columns(
+                col.as_ref(),
+                faer::col![0.].as_ref(),
+                faer::col![0.].as_ref(),
+                faer::col![1.].as_ref(),
+            )
+            .is_err()
+        );
+        assert_eq!(lp.get_dims(), (3, 2));
+    }
+
+    #[rstest]
+    #[case::infeasible(1., faer::col![0., 0.], faer::col![-1.])]
+    #[case::unbounded(-1., faer::col![-1., 0.], faer::col![1.])]
+    fn test_solve_detailed_certificate(#[case] a_1: E, #[case] c: Col<E>, #[case] b: Col<E>) {
+        // x_0 + a_1 x_1 = b with x >= 0
+        let a = SparseColMat::try_new_from_triplets(
+            1,
+            2,
+            &[Triplet::new(0, 0, 1.), Triplet::new(0, 1, a_1)],
+        )
+        .unwrap();
+        let lp = LinearProgram::new(c, a, b, Col::zeros(2), Col::full(2, E::INFINITY));
+        let mut options = SolverOptions::new();
+        options.set_option("max_iterations", 100usize).unwrap();
+        let mut state = SolverState::new_interior(&lp.l, &lp.u, 1);
+        let result = lp
+            .solver_builder()
+            .with_solver(LPSolverType::MpcSimplicialCholesky)
+            .with_options(options)
+            .build()
+            .unwrap()
+            .solve_detailed(&mut state, &mut SolverHooks::silent())
+            .unwrap();
+
+        assert_ne!(result.get_status(), crate::Status::Optimal);
+        assert_eq!(result.get_nit(), state.get_nit());
+        match result.get_certificate() {
+            // y = -1 gives b^T y = 1 > 0 >= (A^T y)^T x for all x >= 0
+            Some(crate::Certificate::PrimalInfeasible(y)) => {
+                assert_eq!(a_1, 1.);
+                assert!((y[0] + 1.).abs() < 1e-12);
+            }
+            // The direction (1, 1) keeps x_0 - x_1 = 1 and decreases -x_0
+            Some(crate::Certificate::Unbounded(d)) => {
+                assert_eq!(a_1, -1.);
+                assert!((d - faer::col![1., 1.]).norm_max() < 1e-6);
+            }
+            None => panic!("no certificate in {result:?}"),
+        }
+    }
+
+    #[rstest]
+    fn test_solve_detailed(#[values(build_simple_lp())] lp: &'static LinearProgram) {
+        let mut state = SolverState::new_interior(&lp.l, &lp.u, lp.b.nrows());
+        let result = LinearProgram::solver_builder(lp)
+            .with_solver(LPSolverType::MpcSimplicialCholesky)
+            .build()
+            .unwrap()
+            .solve_detailed(&mut state, &mut SolverHooks::silent())
+            .unwrap();
+
+        assert_eq!(result.get_status(), crate::Status::Optimal);
+        assert!(result.get_nit() > 0);
+        assert!(result.get_primal_residual() < 1e-6);
+        assert!(result.get_dual_residual() < 1e-6);
+        assert!(result.get_complementarity() < 1e-6);
+        assert_eq!(result.get_duality_gap(), state.get_duality_gap());
+        assert!(result.get_certificate().is_none());
+
+        let termination = result.get_termination().unwrap();
+        assert_eq!(termination, state.get_termination().unwrap());
+        assert_eq!(termination.get_status(), crate::Status::Optimal);
+        assert!(termination.get_reason().contains("within tolerance"));
+        assert!(termination.get_detail() < 1e-6);
+
+        // The time limit fires before convergence
+        let mut state = SolverState::new_interior(&lp.l, &lp.u, lp.b.nrows());
+        let result = LinearProgram::solver_builder(lp)
+            .with_solver(LPSolverType::MpcSimplicialCholesky)
+            .build()
+            .unwrap()
+            .solve_detailed(
+                &mut state,
+                &mut SolverHooks::silent().with_time_limit(std::time::Duration::ZERO),
+            )
+            .unwrap();
+        let termination = result.get_termination().unwrap();
+        assert_eq!(termination.get_status(), crate::Status::TimeLimit);
+        assert!(
+            termination
+                .get_reason()
+                .starts_with("reached the time limit")
+        );
+        assert!(termination.get_detail() >= 0.);
+    }
 }
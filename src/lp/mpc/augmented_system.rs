@@ -1,25 +1,59 @@
 use faer::{
     col::generic::Col,
     prelude::ReborrowMut,
-    sparse::{SparseColMat, SymbolicSparseColMat},
+    sparse::{SparseColMat, SparseColMatRef, SymbolicSparseColMat},
+    traits::IndexCore,
 };
 use problemo::Problem;
 
 use crate::{
-    E, I, SearchDirection, SolverState,
-    ipm::RHS,
+    E, I, SearchDirection, SolverOptions, SolverState,
+    ipm::{AugmentedSystemType, FactorizationReuse, NormalMatrix, RHS, prefer_normal_equations},
     linalg::{
-        solver::LinearSolver,
+        solver::{LinearSolver, MemoryEstimate, SymbolicAnalysis},
         vector_ops::{cwise_inverse, cwise_multiply},
     },
     lp::LinearProgram,
+    to_index,
 };
 
+/// Primal regularization of the diagonal of free columns in the quasi-definite
+/// systems.
+///
+/// Without finite bounds `D_j = 0`, e.g. for the free slack of a free row, and
+/// a fill-reducing ordering may pivot on the column before its rows.
+const FREE_REGULARIZATION: E = 1e-8;
+
+/// Diagonal `D_j` of column `j` in the factorized matrix, regularized by
+/// [`FREE_REGULARIZATION`] if the column is free.
+fn regularized_diagonal(lp: &LinearProgram, sys_diag: &faer::Col<E>, j: usize) -> E {
+    if lp.l[j].is_finite() || lp.u[j].is_finite() {
+        sys_diag[j]
+    } else {
+        sys_diag[j] + FREE_REGULARIZATION
+    }
+}
+
 /// Formulation and factorization of the augmented KKT system used to
 /// compute search directions in a primal-dual interior-point method.
-pub trait AugmentedSystem<'a, Solver: LinearSolver> {
+pub trait AugmentedSystem<'a, Solver: LinearSolver>: Send {
     /// Creates a new instance, performing symbolic analysis of the sparsity pattern.
-    fn new(lp: &'a LinearProgram) -> Self
+    /// The linear solver is configured from `options`.
+    fn new(lp: &'a LinearProgram, options: &SolverOptions) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new_with_symbolic(lp, options, None)
+    }
+
+    /// Creates a new instance reusing `symbolic`, the analysis of the same system of a program
+    /// with the sparsity pattern of `lp`, see [`AugmentedSystem::get_symbolic`]. The pattern is
+    /// analyzed as in `new` if `symbolic` is `None` or rejected by the linear solver.
+    fn new_with_symbolic(
+        lp: &'a LinearProgram,
+        options: &SolverOptions,
+        symbolic: Option<&SymbolicAnalysis>,
+    ) -> Self
     where
         Self: Sized;
 
@@ -28,6 +62,21 @@ pub trait AugmentedSystem<'a, Solver: LinearSolver> {
 
     /// Solves for a search direction reusing the current factorization.
     fn resolve(&mut self, state: &SolverState, rhs: &RHS) -> Result<SearchDirection, Problem>;
+
+    /// The matrix of the most recent factorization.
+    fn get_matrix(&self) -> SparseColMatRef<'_, I, E>;
+
+    /// Memory of the factorization, estimated by the linear solver from the symbolic analysis.
+    fn estimate_memory(&self) -> Option<MemoryEstimate>;
+
+    /// Symbolic analysis of the sparsity pattern, shared by the linear solver if it supports it.
+    fn get_symbolic(&self) -> Option<SymbolicAnalysis>;
+
+    /// Number of factorizations skipped by `solve` because the scaling matrix barely changed,
+    /// see the `refactorization_threshold` option.
+    fn get_n_skipped_factorizations(&self) -> usize {
+        0
+    }
 }
 
 /// Standard augmented system formulation.
@@ -45,10 +94,15 @@ pub struct SlackReducedSystem<'a, Solver: LinearSolver> {
     lp: &'a LinearProgram,
     mat: SparseColMat<I, E>,
     solver: Solver,
+    reuse: FactorizationReuse,
 }
 
 impl<'a, Solver: LinearSolver> AugmentedSystem<'a, Solver> for SlackReducedSystem<'a, Solver> {
-    fn new(lp: &'a LinearProgram) -> Self {
+    fn new_with_symbolic(
+        lp: &'a LinearProgram,
+        options: &SolverOptions,
+        symbolic: Option<&SymbolicAnalysis>,
+    ) -> Self {
         // Get properties
         let (n_var, n_con) = lp.get_dims();
         let a_nnz = lp.A.compute_nnz();
@@ -64,37 +118,37 @@ impl<'a, Solver: LinearSolver> AugmentedSystem<'a, Solver> for SlackReducedSyste
         let a_values = lp.A.val();
 
         // Set each column (0...n_var)
-        col_ptrs.push(0);
+        col_ptrs.push(to_index(0));
         for j in 0..n_var {
-            row_indices.push(j); // Identity part for dx
+            row_indices.push(to_index(j)); // Identity part for dx
             values.push(E::from(1.));
 
-            let start = a_col_ptr[j];
-            let end = a_col_ptr[j + 1];
+            let start = a_col_ptr[j].zx();
+            let end = a_col_ptr[j + 1].zx();
             for k in start..end {
-                row_indices.push(a_row_idx[k] + n_var); // A part for dx
+                row_indices.push(to_index(a_row_idx[k].zx() + n_var)); // A part for dx
                 values.push(-a_values[k]);
             }
 
-            col_ptrs.push(row_indices.len());
+            col_ptrs.push(to_index(row_indices.len()));
         }
 
         // Set pointers for A^T
-        let a_csr = lp.A.to_row_major().unwrap();
+        let a_csr = lp.get_constraint_matrix_csr();
         let a_row_ptr = a_csr.symbolic().row_ptr();
         let a_col_idx = a_csr.symbolic().col_idx();
         let a_values = a_csr.val();
 
         // Set columns for A^T
         for j in 0..n_con {
-            let start = a_row_ptr[j];
-            let end = a_row_ptr[j + 1];
+            let start = a_row_ptr[j].zx();
+            let end = a_row_ptr[j + 1].zx();
             for k in start..end {
                 row_indices.push(a_col_idx[k]); // A^T part for dy
                 values.push(-a_values[k]);
             }
 
-            col_ptrs.push(row_indices.len());
+            col_ptrs.push(to_index(row_indices.len()));
         }
 
         let mat = unsafe {
@@ -108,31 +162,41 @@ impl<'a, Solver: LinearSolver> AugmentedSystem<'a, Solver> for SlackReducedSyste
             SparseColMat::<I, E>::new(sym, values)
         };
 
-        let mut solver = Solver::new();
-        solver.analyze(mat.as_ref()).unwrap();
+        let mut solver = Solver::new_with_options(options);
+        solver.analyze_or_reuse(mat.as_ref(), symbolic).unwrap();
 
-        Self { lp, mat, solver }
+        Self {
+            lp,
+            mat,
+            solver,
+            reuse: FactorizationReuse::new(options),
+        }
     }
 
     fn solve(&mut self, state: &SolverState, rhs: &RHS) -> Result<SearchDirection, Problem> {
-        // Get necessary values
-        let xl_inv = cwise_inverse((&state.x - &self.lp.l).as_ref());
-        let xu_inv = cwise_inverse((&state.x - &self.lp.u).as_ref());
-        let sys_diag = cwise_multiply(xl_inv.as_ref(), state.z_l.as_ref())
-            + cwise_multiply(xu_inv.as_ref(), state.z_u.as_ref());
-
-        // Get matrix pointers
-        let mat = self.mat.rb_mut();
-        let col_ptrs = mat.symbolic().col_ptr();
-        let values = mat.val_mut();
+        let sys_diag = crate::profile!("assemble", {
+            // Get necessary values
+            let xl_inv = cwise_inverse((&state.x - &self.lp.l).as_ref());
+            let xu_inv = cwise_inverse((&state.x - &self.lp.u).as_ref());
+            let sys_diag = cwise_multiply(xl_inv.as_ref(), state.z_l.as_ref())
+                + cwise_multiply(xu_inv.as_ref(), state.z_u.as_ref());
+
+            // Get matrix pointers
+            let mat = self.mat.rb_mut();
+            let col_ptrs = mat.symbolic().col_ptr();
+            let values = mat.val_mut();
+
+            // Update the matrix
+            for j in 0..self.lp.get_n_vars() {
+                values[col_ptrs[j].zx()] = regularized_diagonal(self.lp, &sys_diag, j); // Identity part for dx
+            }
+            sys_diag
+        });
 
-        // Update the matrix
-        for j in 0..self.lp.get_n_vars() {
-            values[col_ptrs[j]] = sys_diag[j] as E; // Identity part for dx
+        if self.reuse.needs_factorization(&sys_diag) {
+            self.solver.factorize(self.mat.as_ref())?;
         }
 
-        self.solver.factorize(self.mat.as_ref())?;
-
         self.resolve(state, rhs)
     }
 
@@ -155,10 +219,9 @@ impl<'a, Solver: LinearSolver> AugmentedSystem<'a, Solver> for SlackReducedSyste
         );
         rhs_primal.copy_from(r_c.as_ref());
 
-        let solution = {
-            let sol = self.solver.solve(rhs.as_mat().as_ref())?;
-            sol.col(0).to_owned()
-        };
+        let solution = self
+            .reuse
+            .solve(&mut self.solver, self.mat.as_ref(), &rhs)?;
         let (dx, dy) = solution.split_at_row(n_var);
         let dz_l = sigma * mu * xl_inv.as_ref()
             - cwise_multiply(
@@ -180,6 +243,394 @@ impl<'a, Solver: LinearSolver> AugmentedSystem<'a, Solver> for SlackReducedSyste
             dz_u,              // Placeholder
         })
     }
+
+    fn get_matrix(&self) -> SparseColMatRef<'_, I, E> {
+        self.mat.as_ref()
+    }
+
+    fn estimate_memory(&self) -> Option<MemoryEstimate> {
+        self.solver.estimate_memory()
+    }
+
+    fn get_symbolic(&self) -> Option<SymbolicAnalysis> {
+        self.solver.get_symbolic()
+    }
+
+    fn get_n_skipped_factorizations(&self) -> usize {
+        self.reuse.get_n_skipped()
+    }
+}
+
+/// Normal equations formulation.
+///
+/// Eliminates `dx` from the slack-reduced system and solves the `n_con x n_con`
+/// positive definite system:
+///
+/// ```text
+/// A D^{-1} A^T dy = -r_p - A D^{-1} r
+/// ```
+///
+/// where `D` and `r` are the diagonal and dual right-hand side of
+/// [`SlackReducedSystem`]. The primal direction is recovered as
+/// `dx = D^{-1} (r + A^T dy)`. Every variable must have at least one finite
+/// bound so that `D` is nonsingular.
+pub struct NormalEquationsSystem<'a, Solver: LinearSolver> {
+    lp: &'a LinearProgram,
+    normal: NormalMatrix,
+    d_inv: faer::Col<E>,
+    solver: Solver,
+    reuse: FactorizationReuse,
+}
+
+impl<'a, Solver: LinearSolver> AugmentedSystem<'a, Solver> for NormalEquationsSystem<'a, Solver> {
+    fn new_with_symbolic(
+        lp: &'a LinearProgram,
+        options: &SolverOptions,
+        symbolic: Option<&SymbolicAnalysis>,
+    ) -> Self {
+        let normal = NormalMatrix::new(lp.A.as_ref());
+
+        let mut solver = Solver::new_with_options(options);
+        solver.analyze_or_reuse(normal.as_ref(), symbolic).unwrap();
+
+        Self {
+            lp,
+            normal,
+            d_inv: Col::zeros(lp.get_n_vars()),
+            solver,
+            reuse: FactorizationReuse::new(options),
+        }
+    }
+
+    fn solve(&mut self, state: &SolverState, rhs: &RHS) -> Result<SearchDirection, Problem> {
+        let sys_diag = crate::profile!("assemble", {
+            let xl_inv = cwise_inverse((&state.x - &self.lp.l).as_ref());
+            let xu_inv = cwise_inverse((&state.x - &self.lp.u).as_ref());
+            let sys_diag = cwise_multiply(xl_inv.as_ref(), state.z_l.as_ref())
+                + cwise_multiply(xu_inv.as_ref(), state.z_u.as_ref());
+
+            self.d_inv = cwise_inverse(sys_diag.as_ref());
+            self.normal.update(self.lp.A.as_ref(), &self.d_inv);
+            sys_diag
+        });
+
+        if self.reuse.needs_factorization(&sys_diag) {
+            self.solver.factorize(self.normal.as_ref())?;
+        }
+
+        self.resolve(state, rhs)
+    }
+
+    fn resolve(&mut self, state: &SolverState, rhs: &RHS) -> Result<SearchDirection, Problem> {
+        let (r_d, r_c, r_l, r_u) = (rhs.r_d(), rhs.r_c(), rhs.r_l(), rhs.r_u());
+
+        let (sigma, mu) = (state.sigma.unwrap(), state.mu.unwrap());
+        let xl_inv = cwise_inverse((&state.x - &self.lp.l).as_ref());
+        let xu_inv = cwise_inverse((&state.x - &self.lp.u).as_ref());
+
+        let rhs_dual = r_d
+            + cwise_multiply(xl_inv.as_ref(), r_l.as_ref())
+            + cwise_multiply(xu_inv.as_ref(), r_u.as_ref())
+            + sigma * mu * (&xl_inv + &xu_inv);
+        let scaled_dual = cwise_multiply(self.d_inv.as_ref(), rhs_dual.as_ref());
+        let rhs_normal = -r_c - &self.lp.A * &scaled_dual;
+
+        let dy = self
+            .reuse
+            .solve(&mut self.solver, self.normal.as_ref(), &rhs_normal)?;
+        let dx = cwise_multiply(
+            self.d_inv.as_ref(),
+            (rhs_dual + self.lp.A.transpose() * &dy).as_ref(),
+        );
+
+        let dz_l = sigma * mu * xl_inv.as_ref()
+            - cwise_multiply(
+                cwise_multiply(xl_inv.as_ref(), state.z_l.as_ref()).as_ref(),
+                dx.as_ref(),
+            )
+            + cwise_multiply(xl_inv.as_ref(), r_l.as_ref());
+        let dz_u = sigma * mu * xu_inv.as_ref()
+            - cwise_multiply(
+                cwise_multiply(xu_inv.as_ref(), state.z_u.as_ref()).as_ref(),
+                dx.as_ref(),
+            )
+            + cwise_multiply(xu_inv.as_ref(), r_u.as_ref());
+
+        Ok(SearchDirection { dx, dy, dz_l, dz_u })
+    }
+
+    fn get_matrix(&self) -> SparseColMatRef<'_, I, E> {
+        self.normal.as_ref()
+    }
+
+    fn estimate_memory(&self) -> Option<MemoryEstimate> {
+        self.solver.estimate_memory()
+    }
+
+    fn get_symbolic(&self) -> Option<SymbolicAnalysis> {
+        self.solver.get_symbolic()
+    }
+
+    fn get_n_skipped_factorizations(&self) -> usize {
+        self.reuse.get_n_skipped()
+    }
+}
+
+/// Augmented system with the bound-only columns eliminated.
+///
+/// A column of `A` with at most one nonzero and a finite bound only couples its
+/// variable to a single row, so its block of [`SlackReducedSystem`] can be
+/// eliminated analytically. With `S` the eliminated and `K` the kept columns,
+/// the `(n_kept + n_con) x (n_kept + n_con)` system
+///
+/// ```text
+/// [  D_K  -A_K^T ] [ dx_K ] = [ r_K                         ]
+/// [ -A_K  -C     ] [ dy   ]   [ r_p + A_S D_S^{-1} r_S        ]
+/// ```
+///
+/// is solved, where `C = diag(A_S D_S^{-1} A_S^T)` is diagonal because every
+/// eliminated column is a singleton, and `r` is the dual right-hand side of
+/// [`SlackReducedSystem`]. The eliminated directions are recovered as
+/// `dx_S = D_S^{-1} (r_S + A_S^T dy)`.
+pub struct SchurComplementSystem<'a, Solver: LinearSolver> {
+    lp: &'a LinearProgram,
+    mat: SparseColMat<I, E>,
+    /// Kept columns of `A`, in the order of the first block of the system.
+    kept: Vec<usize>,
+    /// Eliminated columns of `A` with the row and value of their nonzero, if any.
+    eliminated: Vec<(usize, Option<(usize, E)>)>,
+    d_inv: faer::Col<E>,
+    solver: Solver,
+    reuse: FactorizationReuse,
+}
+
+impl<'a, Solver: LinearSolver> SchurComplementSystem<'a, Solver> {
+    /// Number of columns of `A` eliminated from the system.
+    pub fn get_n_eliminated(&self) -> usize {
+        self.eliminated.len()
+    }
+}
+
+impl<'a, Solver: LinearSolver> AugmentedSystem<'a, Solver> for SchurComplementSystem<'a, Solver> {
+    fn new_with_symbolic(
+        lp: &'a LinearProgram,
+        options: &SolverOptions,
+        symbolic: Option<&SymbolicAnalysis>,
+    ) -> Self {
+        let (n_var, n_con) = lp.get_dims();
+        let a_col_ptr = lp.A.symbolic().col_ptr();
+        let a_row_idx = lp.A.symbolic().row_idx();
+        let a_values = lp.A.val();
+
+        // Split the columns into kept and eliminated ones
+        let mut kept = Vec::new();
+        let mut eliminated = Vec::new();
+        let mut position = vec![usize::MAX; n_var];
+        for j in 0..n_var {
+            let (start, end) = (a_col_ptr[j].zx(), a_col_ptr[j + 1].zx());
+            let bounded = lp.l[j].is_finite() || lp.u[j].is_finite();
+            if bounded && end - start <= 1 {
+                let entry = (start < end).then(|| (a_row_idx[start].zx(), a_values[start]));
+                eliminated.push((j, entry));
+            } else {
+                position[j] = kept.len();
+                kept.push(j);
+            }
+        }
+        let n_kept = kept.len();
+
+        let mut col_ptrs = Vec::with_capacity(n_kept + n_con + 1);
+        let mut row_indices = Vec::new();
+        let mut values = Vec::new();
+
+        // Set columns for dx_K
+        col_ptrs.push(to_index(0));
+        for &j in &kept {
+            row_indices.push(to_index(position[j])); // Diagonal part for dx_K
+            values.push(E::from(1.));
+
+            for k in a_col_ptr[j].zx()..a_col_ptr[j + 1].zx() {
+                row_indices.push(to_index(a_row_idx[k].zx() + n_kept)); // A_K part for dx_K
+                values.push(-a_values[k]);
+            }
+
+            col_ptrs.push(to_index(row_indices.len()));
+        }
+
+        // Set columns for dy, with A_K^T and the diagonal of the Schur complement
+        let a_csr = lp.get_constraint_matrix_csr();
+        let a_row_ptr = a_csr.symbolic().row_ptr();
+        let a_col_idx = a_csr.symbolic().col_idx();
+        let a_values = a_csr.val();
+        for i in 0..n_con {
+            for k in a_row_ptr[i].zx()..a_row_ptr[i + 1].zx() {
+                let kept_position = position[a_col_idx[k].zx()];
+                if kept_position != usize::MAX {
+                    row_indices.push(to_index(kept_position)); // A_K^T part for dy
+                    values.push(-a_values[k]);
+                }
+            }
+            row_indices.push(to_index(n_kept + i)); // Schur complement part for dy
+            values.push(E::from(0.));
+
+            col_ptrs.push(to_index(row_indices.len()));
+        }
+
+        let mat = unsafe {
+            let sym = SymbolicSparseColMat::new_unchecked(
+                n_kept + n_con,
+                n_kept + n_con,
+                col_ptrs,
+                None,
+                row_indices,
+            );
+            SparseColMat::<I, E>::new(sym, values)
+        };
+
+        let mut solver = Solver::new_with_options(options);
+        solver.analyze_or_reuse(mat.as_ref(), symbolic).unwrap();
+
+        Self {
+            lp,
+            mat,
+            kept,
+            eliminated,
+            d_inv: Col::zeros(n_var),
+            solver,
+            reuse: FactorizationReuse::new(options),
+        }
+    }
+
+    fn solve(&mut self, state: &SolverState, rhs: &RHS) -> Result<SearchDirection, Problem> {
+        let sys_diag = crate::profile!("assemble", {
+            let n_kept = self.kept.len();
+            let xl_inv = cwise_inverse((&state.x - &self.lp.l).as_ref());
+            let xu_inv = cwise_inverse((&state.x - &self.lp.u).as_ref());
+            let sys_diag = cwise_multiply(xl_inv.as_ref(), state.z_l.as_ref())
+                + cwise_multiply(xu_inv.as_ref(), state.z_u.as_ref());
+            self.d_inv = cwise_inverse(sys_diag.as_ref());
+
+            // Get matrix pointers
+            let mat = self.mat.rb_mut();
+            let col_ptrs = mat.symbolic().col_ptr();
+            let values = mat.val_mut();
+
+            // Update the diagonal of the kept columns
+            for (k, &j) in self.kept.iter().enumerate() {
+                values[col_ptrs[k].zx()] = regularized_diagonal(self.lp, &sys_diag, j);
+            }
+
+            // Update the Schur complement, stored last in each column of dy
+            for i in 0..self.lp.get_n_cons() {
+                values[col_ptrs[n_kept + i + 1].zx() - 1] = E::from(0.);
+            }
+            for &(j, entry) in &self.eliminated {
+                if let Some((i, a)) = entry {
+                    values[col_ptrs[n_kept + i + 1].zx() - 1] -= a * a * self.d_inv[j];
+                }
+            }
+            sys_diag
+        });
+
+        if self.reuse.needs_factorization(&sys_diag) {
+            self.solver.factorize(self.mat.as_ref())?;
+        }
+
+        self.resolve(state, rhs)
+    }
+
+    fn resolve(&mut self, state: &SolverState, rhs: &RHS) -> Result<SearchDirection, Problem> {
+        let (n_var, n_con) = self.lp.get_dims();
+        let n_kept = self.kept.len();
+
+        let (r_d, r_c, r_l, r_u) = (rhs.r_d(), rhs.r_c(), rhs.r_l(), rhs.r_u());
+
+        let (sigma, mu) = (state.sigma.unwrap(), state.mu.unwrap());
+        let xl_inv = cwise_inverse((&state.x - &self.lp.l).as_ref());
+        let xu_inv = cwise_inverse((&state.x - &self.lp.u).as_ref());
+        let rhs_dual = r_d
+            + cwise_multiply(xl_inv.as_ref(), r_l.as_ref())
+            + cwise_multiply(xu_inv.as_ref(), r_u.as_ref())
+            + sigma * mu * (&xl_inv + &xu_inv);
+
+        // Fold the eliminated columns into the right-hand side
+        let mut rhs = Col::zeros(n_kept + n_con);
+        for (k, &j) in self.kept.iter().enumerate() {
+            rhs[k] = rhs_dual[j];
+        }
+        rhs.subrows_mut(n_kept, n_con).copy_from(r_c);
+        for &(j, entry) in &self.eliminated {
+            if let Some((i, a)) = entry {
+                rhs[n_kept + i] += a * self.d_inv[j] * rhs_dual[j];
+            }
+        }
+
+        let solution = self
+            .reuse
+            .solve(&mut self.solver, self.mat.as_ref(), &rhs)?;
+        let dy = solution.subrows(n_kept, n_con).to_owned();
+
+        // Recover the primal direction of the eliminated columns
+        let mut dx = Col::zeros(n_var);
+        for (k, &j) in self.kept.iter().enumerate() {
+            dx[j] = solution[k];
+        }
+        for &(j, entry) in &self.eliminated {
+            let coupling = entry.map_or(E::from(0.), |(i, a)| a * dy[i]);
+            dx[j] = self.d_inv[j] * (rhs_dual[j] + coupling);
+        }
+
+        let dz_l = sigma * mu * xl_inv.as_ref()
+            - cwise_multiply(
+                cwise_multiply(xl_inv.as_ref(), state.z_l.as_ref()).as_ref(),
+                dx.as_ref(),
+            )
+            + cwise_multiply(xl_inv.as_ref(), r_l.as_ref());
+        let dz_u = sigma * mu * xu_inv.as_ref()
+            - cwise_multiply(
+                cwise_multiply(xu_inv.as_ref(), state.z_u.as_ref()).as_ref(),
+                dx.as_ref(),
+            )
+            + cwise_multiply(xu_inv.as_ref(), r_u.as_ref());
+
+        Ok(SearchDirection { dx, dy, dz_l, dz_u })
+    }
+
+    fn get_matrix(&self) -> SparseColMatRef<'_, I, E> {
+        self.mat.as_ref()
+    }
+
+    fn estimate_memory(&self) -> Option<MemoryEstimate> {
+        self.solver.estimate_memory()
+    }
+
+    fn get_symbolic(&self) -> Option<SymbolicAnalysis> {
+        self.solver.get_symbolic()
+    }
+
+    fn get_n_skipped_factorizations(&self) -> usize {
+        self.reuse.get_n_skipped()
+    }
+}
+
+/// Returns `true` if every variable has at least one finite bound, which keeps the
+/// diagonal `D` of the slack-reduced system nonsingular.
+pub(crate) fn has_bounded_variables(lp: &LinearProgram) -> bool {
+    lp.l.iter()
+        .zip(lp.u.iter())
+        .all(|(l, u)| l.is_finite() || u.is_finite())
+}
+
+/// Resolves [`AugmentedSystemType::Auto`] for the given linear program.
+///
+/// The normal equations are used when every variable is bounded and `A` has no
+/// dense columns; otherwise the slack-reduced system is used.
+pub(crate) fn select_system(lp: &LinearProgram) -> AugmentedSystemType {
+    if has_bounded_variables(lp) && prefer_normal_equations(lp.A.as_ref()) {
+        AugmentedSystemType::NormalEquations
+    } else {
+        AugmentedSystemType::SlackReduced
+    }
 }
 
 // struct FullSystem<'a, Solver: LinearSolver> {
@@ -229,7 +680,7 @@ impl<'a, Solver: LinearSolver> AugmentedSystem<'a, Solver> for SlackReducedSyste
 //         }
 
 //         // Set pointers for A^T
-//         let a_csr = lp.A.to_row_major().unwrap();
+//         let a_csr = lp.get_constraint_matrix_csr();
 //         let a_row_ptr = a_csr.symbolic().row_ptr();
 //         let a_col_idx = a_csr.symbolic().col_idx();
 //         let a_values = a_csr.val();
@@ -270,7 +721,7 @@ impl<'a, Solver: LinearSolver> AugmentedSystem<'a, Solver> for SlackReducedSyste
 //             SparseColMat::<I, E>::new(sym, values)
 //         };
 
-//         let mut solver = Solver::new();
+//         let mut solver = Solver::new_with_options(options);
 //         solver.analyze(mat.as_ref()).unwrap();
 
 //         Self {
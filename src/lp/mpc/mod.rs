@@ -1,17 +1,27 @@
-use std::marker::PhantomData;
+use std::{marker::PhantomData, time::Instant};
 
 use faer::traits::num_traits::pow;
 use macros::{explicit_options, use_option};
 use problemo::Problem;
 
 use crate::{
-    E, I, IterativeSolver, OptimizationProgram, SearchDirection, SolverHooks, SolverOptions,
-    SolverState, Status,
-    ipm::{self, RHS},
-    linalg::{solver::LinearSolver, vector_ops::cwise_multiply_finite},
+    E, IterativeSolver, OptimizationProgram, SearchDirection, SolverHooks, SolverOptions,
+    SolverState, Status, StepLength,
+    ipm::{
+        self, CORRECTOR_MIN_GAIN, CORRECTOR_STEP_INCREASE, KktDump, RHS, SolveCosts,
+        centrality_correction,
+    },
+    linalg::{
+        solver::{LinearSolver, MemoryEstimate, SymbolicAnalysis},
+        vector_ops::{axpy, cwise_multiply_finite},
+    },
     lp::{
         LPSolver, LinearProgram,
-        mpc::{augmented_system::AugmentedSystem, mu_update::MuUpdate},
+        mpc::{
+            augmented_system::AugmentedSystem,
+            line_search::{LineSearch, LineSearches},
+            mu_update::MuUpdate,
+        },
     },
 };
 
@@ -26,11 +36,24 @@ pub mod mu_update;
 /// 2. **Corrector step** — adjusts centering parameter `sigma` based on the
 ///    affine step and adds second-order corrections to the complementarity.
 ///
+/// The corrector reuses the factorization of the affine step, as do the
+/// optional centrality correctors that follow it, see
+/// [`CorrectorCount`](crate::lp::CorrectorCount). Their number is set by the
+/// `centrality_correctors` option.
+///
+/// With the `refactorization_threshold` option, iterations whose scaling
+/// matrix barely changed reuse the previous factorization with iterative
+/// refinement instead of refactorizing.
+///
 /// The solver is generic over the linear system factorization (`Solver`),
 /// augmented system formulation (`System`), barrier parameter strategy (`MU`),
 /// and line search (`LS`).
-#[explicit_options(name = SolverOptions)]
-#[use_option(name = "max_iterations", type_=I, default="0", description="Maximum number of iterations (0 uses solver defaults).")]
+#[explicit_options(name = SolverOptions, scope = "lp.mpc")]
+#[use_option(name = "max_iterations", type_=usize, default="0", description="Maximum number of iterations (0 uses solver defaults).")]
+#[use_option(name = "kkt_dump_directory", type_ = String, default = "", description = "Directory to write the augmented system, right-hand side and step of interior-point iterations to as MatrixMarket files; empty disables the dump.")]
+#[use_option(name = "kkt_dump_iterations", type_ = String, default = "", description = "Comma-separated interior-point iterations to dump; empty dumps every iteration.")]
+#[use_option(name = "centrality_correctors", type_ = crate::ipm::CorrectorCount, default = "0", description = "Maximum number of centrality correctors per interior-point iteration, or auto to choose it from the ratio of factorization to solve time.")]
+#[use_option(name = "max_memory", type_ = E, default = "0", description = "Maximum memory in MiB of the factorization of the Newton system; 0 disables the limit.")]
 pub struct MehrotraPredictorCorrector<
     'a,
     LinSolve: LinearSolver,
@@ -42,8 +65,11 @@ pub struct MehrotraPredictorCorrector<
     system: Sys,
     mu_updater: MU,
 
-    aff_ls: fn(&'a LinearProgram, &SolverOptions, &SolverState, &SearchDirection) -> (E, E),
-    cc_ls: fn(&'a LinearProgram, &SolverOptions, &SolverState, &SearchDirection) -> (E, E),
+    aff_ls: Box<dyn LineSearch<'a> + 'a>,
+    cc_ls: Box<dyn LineSearch<'a> + 'a>,
+
+    costs: SolveCosts,
+    n_correctors: usize,
 
     _solver: PhantomData<LinSolve>,
 }
@@ -52,27 +78,121 @@ impl<'a, LinSolve: LinearSolver, Sys: AugmentedSystem<'a, LinSolve>, MU: MuUpdat
     MehrotraPredictorCorrector<'a, LinSolve, Sys, MU>
 {
     fn initialize(&mut self, _state: &mut SolverState) {
-        // TODO: Initialization code here
+        self.n_correctors = 0;
+    }
+
+    /// Measured times of the factorizations and solves.
+    pub fn get_costs(&self) -> &SolveCosts {
+        &self.costs
+    }
+
+    /// Number of centrality correctors kept during the last solve.
+    pub fn get_n_correctors(&self) -> usize {
+        self.n_correctors
+    }
+
+    /// Number of iterations that reused the factorization of an earlier one,
+    /// see the `refactorization_threshold` option.
+    pub fn get_n_skipped_factorizations(&self) -> usize {
+        self.system.get_n_skipped_factorizations()
+    }
+
+    /// Improves `step`, of length `length`, by centrality correctors until
+    /// `max_correctors` are kept or one fails to lengthen the step. Each
+    /// corrector adds the [`centrality_correction`] of the point reached by a
+    /// slightly longer step to `rhs` and solves with the current factorization.
+    fn correct(
+        &mut self,
+        state: &SolverState,
+        rhs: &mut RHS,
+        mut step: SearchDirection,
+        mut length: StepLength,
+        max_correctors: usize,
+    ) -> Result<(SearchDirection, StepLength), Problem> {
+        let target = state.sigma.unwrap_or(E::from(0.)) * state.mu.unwrap_or(E::from(0.));
+        // Steps are scaled by the safety factor, so they cannot grow beyond it
+        let full_step = state.safety_factor.unwrap_or(E::from(1.));
+        for _ in 0..max_correctors {
+            let alpha = length.primal.min(length.dual);
+            if alpha >= full_step {
+                break;
+            }
+
+            let alpha_primal = E::min(E::from(1.), length.primal + CORRECTOR_STEP_INCREASE);
+            let alpha_dual = E::min(E::from(1.), length.dual + CORRECTOR_STEP_INCREASE);
+            let mut x = state.x.clone();
+            let mut z_l = state.z_l.clone();
+            let mut z_u = state.z_u.clone();
+            axpy(alpha_primal, step.dx.as_ref(), x.as_mut());
+            axpy(alpha_dual, step.dz_l.as_ref(), z_l.as_mut());
+            axpy(alpha_dual, step.dz_u.as_ref(), z_u.as_mut());
+            *rhs.r_l_mut() +=
+                centrality_correction(x.as_ref(), self.lp.l.as_ref(), z_l.as_ref(), target);
+            *rhs.r_u_mut() +=
+                centrality_correction(x.as_ref(), self.lp.u.as_ref(), z_u.as_ref(), target);
+
+            let start = Instant::now();
+            let trial_step = self.system.resolve(state, rhs)?;
+            self.costs.record_solve(start.elapsed());
+            let trial_length = self.cc_ls.compute(state, &trial_step);
+            if trial_length.primal.min(trial_length.dual)
+                < alpha + CORRECTOR_MIN_GAIN * CORRECTOR_STEP_INCREASE
+            {
+                break;
+            }
+            (step, length) = (trial_step, trial_length);
+            self.n_correctors += 1;
+        }
+        Ok((step, length))
     }
 
     fn iterate(&mut self, state: &mut SolverState) -> Result<(), Problem> {
+        // Stop before the factorization allocates more than allowed
+        if let Some(termination) =
+            ipm::exceeds_memory_limit(self.system.estimate_memory(), self.options.max_memory)
+        {
+            state.status = Status::MemoryLimit;
+            state.termination = Some(termination);
+            return Ok(());
+        }
+
         state.sigma = Some(E::from(0.));
         state.mu = Some(self.mu_updater.get(state));
         state.safety_factor = Some(E::from(1.));
 
         let mut rhs = RHS::from(&*state);
+        let dump = KktDump::new(
+            &self.options.kkt_dump_directory,
+            &self.options.kkt_dump_iterations,
+            state.nit,
+        )?;
 
         // Affine Step
+        let skipped = self.system.get_n_skipped_factorizations();
+        let start = Instant::now();
         let aff_step = self.system.solve(state, &rhs)?;
-        let (alpha_aff_primal, alpha_aff_dual) =
-            (self.aff_ls)(self.lp, &self.options.root, state, &aff_step);
+        let factor_and_solve = start.elapsed();
+        let factorized = self.system.get_n_skipped_factorizations() == skipped;
+        if let Some(dump) = &dump {
+            dump.write("affine", self.system.get_matrix(), &rhs, &aff_step)?;
+        }
+        let aff_length = self.aff_ls.compute(state, &aff_step);
+        let (alpha_aff_primal, alpha_aff_dual) = (aff_length.primal, aff_length.dual);
 
         // Center-Corrector Step
         let mut state_aff = state.clone();
-        state_aff.x += alpha_aff_primal * &aff_step.dx;
-        state_aff.y += alpha_aff_dual * &aff_step.dy;
-        state_aff.z_l += alpha_aff_dual * &aff_step.dz_l;
-        state_aff.z_u += alpha_aff_dual * &aff_step.dz_u;
+        axpy(alpha_aff_primal, aff_step.dx.as_ref(), state_aff.x.as_mut());
+        axpy(alpha_aff_dual, aff_step.dy.as_ref(), state_aff.y.as_mut());
+        axpy(
+            alpha_aff_dual,
+            aff_step.dz_l.as_ref(),
+            state_aff.z_l.as_mut(),
+        );
+        axpy(
+            alpha_aff_dual,
+            aff_step.dz_u.as_ref(),
+            state_aff.z_u.as_mut(),
+        );
 
         state.sigma = Some(pow(
             self.mu_updater.get(&state_aff) / state.mu.unwrap_or(E::from(1.)),
@@ -83,17 +203,38 @@ impl<'a, LinSolve: LinearSolver, Sys: AugmentedSystem<'a, LinSolve>, MU: MuUpdat
         *rhs.r_l_mut() -= cwise_multiply_finite(aff_step.dz_l.as_ref(), aff_step.dx.as_ref());
         *rhs.r_u_mut() -= cwise_multiply_finite(aff_step.dz_u.as_ref(), aff_step.dx.as_ref());
 
-        let corr_step = self.system.solve(state, &rhs)?;
-        let (alpha_corr_primal, alpha_corr_dual) =
-            (self.cc_ls)(self.lp, &self.options.root, state, &corr_step);
+        // The matrix only depends on the iterate, so the factorization is reused
+        let start = Instant::now();
+        let corr_step = self.system.resolve(state, &rhs)?;
+        let solve = start.elapsed();
+        if factorized {
+            self.costs
+                .record_factor(factor_and_solve.saturating_sub(solve));
+        }
+        self.costs.record_solve(solve);
+        if let Some(dump) = &dump {
+            dump.write("corrector", self.system.get_matrix(), &rhs, &corr_step)?;
+        }
+        let corr_length = self.cc_ls.compute(state, &corr_step);
+
+        // Centrality Correctors
+        let max_correctors = self
+            .options
+            .centrality_correctors
+            .get_max_correctors(&self.costs);
+        let (corr_step, corr_length) =
+            self.correct(state, &mut rhs, corr_step, corr_length, max_correctors)?;
+        let (alpha_corr_primal, alpha_corr_dual) = (corr_length.primal, corr_length.dual);
 
         // Update the state with the corrector step and step lengths
-        state.x += alpha_corr_primal * &corr_step.dx;
-        state.y += alpha_corr_dual * &corr_step.dy;
-        state.z_l += alpha_corr_dual * &corr_step.dz_l;
-        state.z_u += alpha_corr_dual * &corr_step.dz_u;
+        axpy(alpha_corr_primal, corr_step.dx.as_ref(), state.x.as_mut());
+        axpy(alpha_corr_dual, corr_step.dy.as_ref(), state.y.as_mut());
+        axpy(alpha_corr_dual, corr_step.dz_l.as_ref(), state.z_l.as_mut());
+        axpy(alpha_corr_dual, corr_step.dz_u.as_ref(), state.z_u.as_mut());
         state.alpha_primal = alpha_corr_primal;
         state.alpha_dual = alpha_corr_dual;
+        state.blocking_primal = corr_length.blocking_primal;
+        state.blocking_dual = corr_length.blocking_dual;
 
         self.lp.update_residual(state);
         state.status = Status::InProgress;
@@ -106,19 +247,41 @@ impl<'a, LinSolve: LinearSolver, Sys: AugmentedSystem<'a, LinSolve>, MU: MuUpdat
     for MehrotraPredictorCorrector<'a, LinSolve, Sys, MU>
 {
     fn new(lp: &'a LinearProgram, options: &SolverOptions) -> Self {
+        Self::new_with_symbolic(lp, options, None)
+    }
+
+    fn new_with_symbolic(
+        lp: &'a LinearProgram,
+        options: &SolverOptions,
+        symbolic: Option<&SymbolicAnalysis>,
+    ) -> Self {
+        let line_search = options
+            .get_option::<LineSearches>("lp_line_search")
+            .unwrap_or_default();
         Self {
             lp,
-            system: Sys::new(lp),
+            system: Sys::new_with_symbolic(lp, options, symbolic),
             mu_updater: MU::new(lp, options),
 
-            aff_ls: line_search::compute_max_step_length,
-            cc_ls: line_search::compute_max_step_length,
+            aff_ls: LineSearches::into_variant(&line_search, lp, options),
+            cc_ls: LineSearches::into_variant(&line_search, lp, options),
+
+            costs: SolveCosts::new(),
+            n_correctors: 0,
 
             options: options.into(),
 
             _solver: PhantomData,
         }
     }
+
+    fn estimate_memory(&self) -> Option<MemoryEstimate> {
+        self.system.estimate_memory()
+    }
+
+    fn get_symbolic(&self) -> Option<SymbolicAnalysis> {
+        self.system.get_symbolic()
+    }
 }
 
 impl<'a, LinSolve: LinearSolver, Sys: AugmentedSystem<'a, LinSolve>, MU: MuUpdate<'a>>
@@ -132,6 +295,10 @@ impl<'a, LinSolve: LinearSolver, Sys: AugmentedSystem<'a, LinSolve>, MU: MuUpdat
         }
     }
 
+    fn get_options(&self) -> Option<&SolverOptions> {
+        Some(&self.options.root)
+    }
+
     fn get_program(&self) -> &dyn OptimizationProgram {
         self.lp
     }
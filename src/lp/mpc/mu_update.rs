@@ -1,9 +1,9 @@
 use std::marker::PhantomData;
 
-use macros::{explicit_options, use_option};
+use macros::{build_option_enum, explicit_options, use_option};
 
 use crate::{
-    E, SolverOptions, SolverState, linalg::vector_ops::cwise_multiply_finite, lp::LinearProgram,
+    E, SolverOptions, SolverState, linalg::vector_ops::complementarity, lp::LinearProgram,
 };
 
 /// Strategy for computing the barrier parameter `mu`.
@@ -11,7 +11,7 @@ use crate::{
 /// The barrier parameter controls the trade-off between optimality and
 /// centrality in an interior-point method. Implementations determine
 /// how `mu` evolves across iterations.
-pub trait MuUpdate<'a> {
+pub trait MuUpdate<'a>: Send {
     /// Creates a new instance from the linear program and solver options.
     fn new(lp: &'a LinearProgram, options: &SolverOptions) -> Self
     where
@@ -21,7 +21,7 @@ pub trait MuUpdate<'a> {
 }
 
 /// Returns a fixed barrier parameter value across all iterations.
-#[explicit_options(name = SolverOptions)]
+#[explicit_options(name = SolverOptions, scope = "lp.mpc")]
 #[use_option(name = "mu_fixed", type_ = E, default = "1.", description = "Constant value for the barrier parameter mu")]
 pub struct ConstantMuUpdate<'a> {
     _a: PhantomData<&'a ()>,
@@ -47,7 +47,7 @@ impl<'a> MuUpdate<'a> for ConstantMuUpdate<'a> {
 /// ```
 ///
 /// Infinite bounds are ignored (treated as zero contribution).
-#[explicit_options(name = SolverOptions)]
+#[explicit_options(name = SolverOptions, scope = "lp.mpc")]
 #[use_option(name = "mu_min", type_ = E, default = "1e-7", description = "Minimum value for the barrier parameter mu")]
 #[use_option(name = "mu_max", type_ = E, default = "1e7", description = "Maximum value for the barrier parameter mu")]
 pub struct AdaptiveMuUpdate<'a> {
@@ -69,13 +69,84 @@ impl<'a> MuUpdate<'a> for AdaptiveMuUpdate<'a> {
         // (x-u)^T z_upper
         // sum / n
 
-        let xl = &state.x - &self.lp.l;
-        let xu = &state.x - &self.lp.u;
-
-        let l = cwise_multiply_finite(state.z_l.as_ref(), xl.as_ref()).sum();
-        let u = cwise_multiply_finite(state.z_u.as_ref(), xu.as_ref()).sum();
+        let l = complementarity(state.x.as_ref(), self.lp.l.as_ref(), state.z_l.as_ref());
+        let u = complementarity(state.x.as_ref(), self.lp.u.as_ref(), state.z_u.as_ref());
         let mu = (l + u) / state.x.nrows() as E;
 
         mu.clamp(self.options.mu_min, self.options.mu_max)
     }
 }
+
+build_option_enum!(
+    trait_ = for<'a> MuUpdate<'a>,
+    name = "MuUpdates",
+    variants = (AdaptiveMuUpdate, ConstantMuUpdate),
+    aliases = [["adaptive"], ["constant", "fixed"]],
+    new_arguments = (&'a LinearProgram, &SolverOptions),
+    doc_header = "Barrier parameter updates of the interior-point method for linear programs, selected with the `lp_mu_update` option."
+);
+
+/// The update selected with the `lp_mu_update` option.
+impl<'a> MuUpdate<'a> for Box<dyn MuUpdate<'a> + 'a> {
+    fn new(lp: &'a LinearProgram, options: &SolverOptions) -> Self {
+        let update = options
+            .get_option::<MuUpdates>("lp_mu_update")
+            .unwrap_or_default();
+        MuUpdates::into_variant(&update, lp, options)
+    }
+
+    fn get(&mut self, state: &SolverState) -> E {
+        (**self).get(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use faer::{
+        col,
+        sparse::{SparseColMat, Triplet},
+    };
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("adaptive_mu_update", MuUpdates::AdaptiveMuUpdate)]
+    #[case("Adaptive", MuUpdates::AdaptiveMuUpdate)]
+    #[case("constant_mu_update", MuUpdates::ConstantMuUpdate)]
+    #[case("FIXED", MuUpdates::ConstantMuUpdate)]
+    fn test_parse(#[case] name: &str, #[case] expected: MuUpdates) {
+        assert_eq!(MuUpdates::from_str(name), Ok(expected));
+        assert_eq!(MuUpdates::from_str(expected.name()), Ok(expected));
+    }
+
+    #[test]
+    fn test_selected_update() {
+        assert!(MuUpdates::from_str("adaptive_mu").is_err());
+        assert_eq!(MuUpdates::VARIANTS.len(), 2);
+        assert_eq!(MuUpdates::default(), MuUpdates::AdaptiveMuUpdate);
+
+        let lp = LinearProgram::new(
+            col![1., 1.],
+            SparseColMat::try_new_from_triplets(
+                1,
+                2,
+                &[Triplet::new(0, 0, 1.), Triplet::new(0, 1, 1.)],
+            )
+            .unwrap(),
+            col![1.],
+            col![0., 0.],
+            col![10., 10.],
+        );
+        let mut options = SolverOptions::new();
+        options
+            .set_option("lp_mu_update", MuUpdates::from_str("fixed").unwrap())
+            .unwrap();
+        options.set_option("mu_fixed", 0.25).unwrap();
+        let state = SolverState::new_interior(lp.get_lower_bounds(), lp.get_upper_bounds(), 1);
+        let mut update = <Box<dyn MuUpdate<'_>>>::new(&lp, &options);
+        assert_eq!(update.get(&state), 0.25);
+    }
+}
@@ -0,0 +1,631 @@
+//! Multi-objective linear and quadratic programming.
+//!
+//! Given objectives `f_k(x) = 1/2 x^T Q_k x + c_k^T x` over the feasible set of a
+//! [`LinearProgram`], the Pareto front is approximated by solving a sequence of
+//! scalarized problems:
+//!
+//! - **Weighted sum**: `min sum_k w_k f_k(x)` for weights `w >= 0`. Every
+//!   solution is Pareto optimal, but only the supported points of the front
+//!   (those on its convex hull) can be reached.
+//! - **Epsilon constraint**: `min f_p(x)` subject to `f_k(x) <= eps_k` for all
+//!   `k != p`. This reaches every Pareto point, but the constrained objectives
+//!   must be linear.
+//!
+//! Each problem is warm started from the solution of the previous one, in the
+//! same way as the solves of [`parametric`](crate::lp::parametric). Scalarized
+//! problems with a quadratic term are solved as a [`QuadraticProgram`].
+
+use faer::{
+    Col,
+    sparse::{SparseColMat, Triplet},
+};
+use macros::{explicit_options, use_option};
+use problemo::{Problem, common::IntoCommonProblem};
+
+use crate::{
+    E, I, SolverHooks, SolverOptions, SolverState, Status,
+    callback::NoOpCallback,
+    lp::{LinearProgram, parametric::warm_start},
+    qp::{QPSolverType, QuadraticProgram},
+    terminators::ComplementarityTerminator,
+    to_index,
+};
+
+/// A Pareto optimal point together with the scalarization that produced it.
+#[derive(Debug, Clone)]
+pub struct ParetoPoint {
+    x: Col<E>,
+    values: Col<E>,
+    parameters: Col<E>,
+}
+
+impl ParetoPoint {
+    pub fn get_primal(&self) -> &Col<E> {
+        &self.x
+    }
+
+    /// Value of every objective at the point.
+    pub fn get_objective_values(&self) -> &Col<E> {
+        &self.values
+    }
+
+    /// Weights or objective bounds of the scalarized problem.
+    pub fn get_parameters(&self) -> &Col<E> {
+        &self.parameters
+    }
+
+    /// Whether `other` is at least as good in every objective and better in one.
+    fn is_dominated_by(&self, other: &ParetoPoint, tolerance: E) -> bool {
+        let mut better = false;
+        for k in 0..self.values.nrows() {
+            let slack = tolerance * (E::from(1.) + self.values[k].abs());
+            if other.values[k] > self.values[k] + slack {
+                return false;
+            }
+            better |= other.values[k] < self.values[k] - slack;
+        }
+        better
+    }
+
+    fn is_close(&self, other: &ParetoPoint, tolerance: E) -> bool {
+        (0..self.values.nrows()).all(|k| {
+            (other.values[k] - self.values[k]).abs()
+                <= tolerance * (E::from(1.) + self.values[k].abs())
+        })
+    }
+}
+
+/// Nondominated points found by a sweep, in the order they were solved.
+#[derive(Debug, Clone)]
+pub struct ParetoFront {
+    points: Vec<ParetoPoint>,
+}
+
+impl ParetoFront {
+    /// Keeps the points that are neither dominated nor duplicates of an earlier point.
+    fn new(points: Vec<ParetoPoint>, tolerance: E) -> Self {
+        let points = points
+            .iter()
+            .enumerate()
+            .filter(|(i, p)| {
+                !points.iter().any(|q| p.is_dominated_by(q, tolerance))
+                    && !points[..*i].iter().any(|q| p.is_close(q, tolerance))
+            })
+            .map(|(_, p)| p.clone())
+            .collect();
+        Self { points }
+    }
+
+    pub fn get_points(&self) -> &[ParetoPoint] {
+        &self.points
+    }
+}
+
+/// Evenly spaced weights on the unit simplex, with every weight a multiple of
+/// `1 / n_divisions`.
+pub fn weight_grid(n_objectives: usize, n_divisions: usize) -> Vec<Col<E>> {
+    fn compositions(
+        parts: usize,
+        total: usize,
+        prefix: &mut Vec<usize>,
+        out: &mut Vec<Vec<usize>>,
+    ) {
+        if parts == 1 {
+            prefix.push(total);
+            out.push(prefix.clone());
+            prefix.pop();
+            return;
+        }
+        for k in 0..=total {
+            prefix.push(k);
+            compositions(parts - 1, total - k, prefix, out);
+            prefix.pop();
+        }
+    }
+
+    if n_objectives == 0 || n_divisions == 0 {
+        return Vec::new();
+    }
+    let mut out = Vec::new();
+    compositions(n_objectives, n_divisions, &mut Vec::new(), &mut out);
+    out.iter()
+        .map(|w| Col::from_fn(n_objectives, |k| w[k] as E / n_divisions as E))
+        .collect()
+}
+
+/// Driver for weighted-sum and epsilon-constraint sweeps over several objectives.
+#[explicit_options(name = SolverOptions)]
+#[use_option(name = "parametric_warm_start_shift", type_ = E, default = "1e-2", description = "Minimal distance of a warm start to the bounds and minimal magnitude of its bound multipliers.")]
+#[use_option(name = "pareto_tolerance", type_ = E, default = "1e-6", description = "Relative difference in the objectives below which Pareto points are considered equal.")]
+pub struct MultiObjectiveSolver<'a> {
+    lp: &'a LinearProgram,
+    objectives: Vec<Col<E>>,
+    quadratics: Vec<Option<SparseColMat<I, E>>>,
+
+    n_solves: usize,
+    n_warm_starts: usize,
+}
+
+impl<'a> MultiObjectiveSolver<'a> {
+    /// Creates a driver for the linear objectives `objectives` over the feasible
+    /// set of `lp`. The objective of `lp` itself is ignored.
+    pub fn new(
+        lp: &'a LinearProgram,
+        objectives: Vec<Col<E>>,
+        options: &SolverOptions,
+    ) -> Result<Self, Problem> {
+        if objectives.len() < 2 {
+            return Err("Multi-objective problems require at least two objectives".gloss());
+        }
+        let n_vars = lp.get_n_vars();
+        if let Some(k) = objectives.iter().position(|c| c.nrows() != n_vars) {
+            return Err(format!("Objective {k} does not have {n_vars} coefficients").gloss());
+        }
+
+        Ok(Self {
+            lp,
+            quadratics: vec![None; objectives.len()],
+            objectives,
+            n_solves: 0,
+            n_warm_starts: 0,
+            options: options.into(),
+        })
+    }
+
+    /// Adds the quadratic term `1/2 x^T Q x` to objective `k`.
+    #[allow(non_snake_case)]
+    pub fn with_quadratic(mut self, k: usize, Q: SparseColMat<I, E>) -> Self {
+        self.quadratics[k] = Some(Q);
+        self
+    }
+
+    pub fn get_n_objectives(&self) -> usize {
+        self.objectives.len()
+    }
+
+    /// Number of scalarized problems solved so far.
+    pub fn get_n_solves(&self) -> usize {
+        self.n_solves
+    }
+
+    /// Number of solves that reached optimality from a warm start.
+    pub fn get_n_warm_starts(&self) -> usize {
+        self.n_warm_starts
+    }
+
+    /// Value of every objective at `x`.
+    pub fn evaluate(&self, x: &Col<E>) -> Col<E> {
+        Col::from_fn(self.objectives.len(), |k| {
+            let quadratic = self.quadratics[k]
+                .as_ref()
+                .map_or(E::from(0.), |q| E::from(0.5) * (x.transpose() * (q * x)));
+            quadratic + self.objectives[k].transpose() * x
+        })
+    }
+
+    /// Minimizes the weighted sum of the objectives for every weight vector.
+    /// Fails if some weight vector is negative or zero, or if a problem cannot
+    /// be solved.
+    pub fn solve_weighted_sum(&mut self, weights: &[Col<E>]) -> Result<ParetoFront, Problem> {
+        let n_objectives = self.objectives.len();
+        self.check_quadratics()?;
+
+        let lp = self.lp;
+        let mut points = Vec::with_capacity(weights.len());
+        let mut previous: Option<SolverState> = None;
+        for w in weights {
+            if w.nrows() != n_objectives
+                || (0..n_objectives).any(|k| w[k].is_nan() || w[k] < E::from(0.))
+                || w.sum() <= E::from(0.)
+            {
+                return Err(format!("Invalid weights {:?}", w.as_ref()).gloss());
+            }
+
+            let c = (0..n_objectives).fold(Col::zeros(lp.get_n_vars()), |c, k| {
+                c + w[k] * &self.objectives[k]
+            });
+            let q = self.weighted_quadratic(w);
+            let state = self.solve_scalarized(
+                c,
+                q,
+                lp.get_constraint_matrix().clone(),
+                lp.get_rhs().clone(),
+                lp.get_lower_bounds().clone(),
+                lp.get_upper_bounds().clone(),
+                previous.as_ref(),
+            )?;
+
+            points.push(ParetoPoint {
+                values: self.evaluate(&state.x),
+                x: state.x.clone(),
+                parameters: w.clone(),
+            });
+            previous = Some(state);
+        }
+
+        Ok(ParetoFront::new(points, self.options.pareto_tolerance))
+    }
+
+    /// Minimizes objective `primary` subject to `f_k(x) <= bounds[k]` for every
+    /// other objective. The entry of `primary` in each bound vector is ignored.
+    /// Bounds for which the problem cannot be solved, e.g. because they are
+    /// infeasible, are skipped.
+    #[allow(non_snake_case)]
+    pub fn solve_epsilon_constraint(
+        &mut self,
+        primary: usize,
+        bounds: &[Col<E>],
+    ) -> Result<ParetoFront, Problem> {
+        let n_objectives = self.objectives.len();
+        if primary >= n_objectives {
+            return Err(format!("Objective {primary} does not exist").gloss());
+        }
+        self.check_quadratics()?;
+        if let Some(k) = (0..n_objectives).find(|&k| k != primary && self.quadratics[k].is_some()) {
+            return Err(format!(
+                "Objective {k} is quadratic and cannot be bounded by a constraint"
+            )
+            .gloss());
+        }
+
+        // Rows f_k(x) + s_k = eps_k with slacks s_k >= 0 follow the rows of the program
+        let lp = self.lp;
+        let (n, m) = lp.get_dims();
+        let constrained = (0..n_objectives)
+            .filter(|&k| k != primary)
+            .collect::<Vec<_>>();
+        let n_extra = constrained.len();
+
+        let A = lp.get_constraint_matrix();
+        let mut triplets = Vec::with_capacity(A.compute_nnz() + n_extra * (n + 1));
+        for j in 0..n {
+            for (i, a) in A.row_idx_of_col(j).zip(A.val_of_col(j)) {
+                triplets.push(Triplet::new(to_index(i), to_index(j), *a));
+            }
+        }
+        for (r, &k) in constrained.iter().enumerate() {
+            for j in 0..n {
+                if self.objectives[k][j] != E::from(0.) {
+                    triplets.push(Triplet::new(
+                        to_index(m + r),
+                        to_index(j),
+                        self.objectives[k][j],
+                    ));
+                }
+            }
+            triplets.push(Triplet::new(to_index(m + r), to_index(n + r), E::from(1.)));
+        }
+        let A = SparseColMat::try_new_from_triplets(m + n_extra, n + n_extra, &triplets)
+            .map_err(|e| format!("Failed to assemble epsilon-constraint rows: {e:?}").gloss())?;
+
+        let mut c = Col::zeros(n + n_extra);
+        c.subrows_mut(0, n).copy_from(&self.objectives[primary]);
+        let mut l = Col::zeros(n + n_extra);
+        let mut u = Col::from_fn(n + n_extra, |_| E::INFINITY);
+        l.subrows_mut(0, n).copy_from(lp.get_lower_bounds());
+        u.subrows_mut(0, n).copy_from(lp.get_upper_bounds());
+        let mut weights = Col::zeros(n_objectives);
+        weights[primary] = E::from(1.);
+        let q = self.weighted_quadratic(&weights);
+
+        let mut points = Vec::with_capacity(bounds.len());
+        let mut previous: Option<SolverState> = None;
+        for eps in bounds {
+            if eps.nrows() != n_objectives || constrained.iter().any(|&k| !eps[k].is_finite()) {
+                return Err(format!("Invalid objective bounds {:?}", eps.as_ref()).gloss());
+            }
+            let mut b = Col::zeros(m + n_extra);
+            b.subrows_mut(0, m).copy_from(lp.get_rhs());
+            for (r, &k) in constrained.iter().enumerate() {
+                b[m + r] = eps[k];
+            }
+
+            let Ok(state) = self.solve_scalarized(
+                c.clone(),
+                q.clone(),
+                A.clone(),
+                b,
+                l.clone(),
+                u.clone(),
+                previous.as_ref(),
+            ) else {
+                continue;
+            };
+
+            let x = state.x.subrows(0, n).to_owned();
+            points.push(ParetoPoint {
+                values: self.evaluate(&x),
+                x,
+                parameters: eps.clone(),
+            });
+            previous = Some(state);
+        }
+
+        Ok(ParetoFront::new(points, self.options.pareto_tolerance))
+    }
+
+    #[allow(non_snake_case)]
+    fn check_quadratics(&self) -> Result<(), Problem> {
+        let n_vars = self.lp.get_n_vars();
+        match self.quadratics.iter().position(|Q| {
+            Q.as_ref()
+                .is_some_and(|Q| Q.nrows() != n_vars || Q.ncols() != n_vars)
+        }) {
+            Some(k) => {
+                Err(format!("Quadratic term of objective {k} is not {n_vars} x {n_vars}").gloss())
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Triplets of `sum_k w_k Q_k`, or `None` if no objective is quadratic.
+    #[allow(non_snake_case)]
+    fn weighted_quadratic(&self, w: &Col<E>) -> Option<Vec<Triplet<I, I, E>>> {
+        let mut triplets = None;
+        for (k, Q) in self.quadratics.iter().enumerate() {
+            let Some(Q) = Q else { continue };
+            let triplets = triplets.get_or_insert_with(Vec::new);
+            for j in 0..Q.ncols() {
+                for (i, q) in Q.row_idx_of_col(j).zip(Q.val_of_col(j)) {
+                    triplets.push(Triplet::new(to_index(i), to_index(j), w[k] * *q));
+                }
+            }
+        }
+        triplets
+    }
+
+    /// Solves the scalarized problem, first from a warm start at `previous` and
+    /// then from the default starting point.
+    #[allow(non_snake_case, clippy::too_many_arguments)]
+    fn solve_scalarized(
+        &mut self,
+        c: Col<E>,
+        q: Option<Vec<Triplet<I, I, E>>>,
+        A: SparseColMat<I, E>,
+        b: Col<E>,
+        l: Col<E>,
+        u: Col<E>,
+        previous: Option<&SolverState>,
+    ) -> Result<SolverState, Problem> {
+        let n_cons = b.nrows();
+        let mut starts = Vec::with_capacity(2);
+        if let Some(previous) = previous {
+            starts.push(warm_start(
+                previous,
+                &l,
+                &u,
+                self.options.parametric_warm_start_shift,
+            ));
+        }
+        starts.push(SolverState::new_interior(&l, &u, n_cons));
+        let n_starts = starts.len();
+
+        let qp = match q {
+            Some(q) => {
+                let n = c.nrows();
+                let Q = SparseColMat::try_new_from_triplets(n, n, &q)
+                    .map_err(|e| format!("Failed to assemble quadratic term: {e:?}").gloss())?;
+                Some(QuadraticProgram::new(
+                    Q,
+                    c.clone(),
+                    A.clone(),
+                    b.clone(),
+                    l.clone(),
+                    u.clone(),
+                ))
+            }
+            None => None,
+        };
+        let lp = LinearProgram::new(c, A, b, l, u);
+
+        let mut error = None;
+        for (k, mut state) in starts.into_iter().enumerate() {
+            self.n_solves += 1;
+            let mut hooks = SolverHooks {
+                callback: Box::new(NoOpCallback::new()),
+                terminator: Box::new(ComplementarityTerminator::new(&self.options.root)),
+            };
+            let status = match &qp {
+                Some(qp) => qp
+                    .solver_builder()
+                    .with_solver(QPSolverType::MpcSimplicialCholesky)
+                    .with_options(self.options.root.clone())
+                    .build()
+                    .and_then(|mut solver| solver.solve(&mut state, &mut hooks)),
+                None => lp
+                    .solver_builder()
+                    .with_options(self.options.root.clone())
+                    .build()
+                    .and_then(|mut solver| solver.solve(&mut state, &mut hooks)),
+            };
+            match status {
+                Ok(Status::Optimal) => {
+                    if k + 1 < n_starts {
+                        self.n_warm_starts += 1;
+                    }
+                    return Ok(state);
+                }
+                Ok(status) => {
+                    error = Some(
+                        format!("Scalarized problem terminated with status {status:?}").gloss(),
+                    )
+                }
+                Err(e) => error = Some(e),
+            }
+        }
+        Err(error.unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::col;
+    use rstest::rstest;
+
+    use super::*;
+
+    /// Feasible set `x_0 + x_1 >= 1` with `0 <= x <= 1`.
+    fn build_lp() -> LinearProgram {
+        let a = SparseColMat::try_new_from_triplets(
+            1,
+            3,
+            &[
+                Triplet::new(0, 0, 1.),
+                Triplet::new(0, 1, 1.),
+                Triplet::new(0, 2, -1.),
+            ],
+        )
+        .unwrap();
+        LinearProgram::new(
+            col![0., 0., 0.],
+            a,
+            col![1.],
+            col![0., 0., 0.],
+            col![1., 1., E::INFINITY],
+        )
+    }
+
+    #[rstest]
+    #[case(2, 2, 3)]
+    #[case(3, 2, 6)]
+    #[case(3, 4, 15)]
+    fn test_weight_grid(#[case] n_objectives: usize, #[case] n_divisions: usize, #[case] n: usize) {
+        let weights = weight_grid(n_objectives, n_divisions);
+        assert_eq!(weights.len(), n);
+        for w in weights {
+            assert!((w.sum() - 1.).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_weighted_sum() {
+        // Feasible set x_0 + 2 x_1 >= 2 and 2 x_0 + x_1 >= 2 with 0 <= x <= 3
+        let a = SparseColMat::try_new_from_triplets(
+            2,
+            4,
+            &[
+                Triplet::new(0, 0, 1.),
+                Triplet::new(0, 1, 2.),
+                Triplet::new(0, 2, -1.),
+                Triplet::new(1, 0, 2.),
+                Triplet::new(1, 1, 1.),
+                Triplet::new(1, 3, -1.),
+            ],
+        )
+        .unwrap();
+        let lp = LinearProgram::new(
+            col![0., 0., 0., 0.],
+            a,
+            col![2., 2.],
+            col![0., 0., 0., 0.],
+            col![3., 3., E::INFINITY, E::INFINITY],
+        );
+        let mut solver = MultiObjectiveSolver::new(
+            &lp,
+            vec![col![1., 0., 0., 0.], col![0., 1., 0., 0.]],
+            &SolverOptions::new(),
+        )
+        .unwrap();
+        // Zero weights are left out, as they allow weakly Pareto optimal solutions
+        let weights = [col![0.25, 0.75], col![0.5, 0.5], col![0.75, 0.25]];
+        let front = solver.solve_weighted_sum(&weights).unwrap();
+
+        let vertices = [[2., 0.], [2. / 3., 2. / 3.], [0., 2.]];
+        let points = front.get_points();
+        assert_eq!(points.len(), 3);
+        for (point, vertex) in points.iter().zip(vertices) {
+            let values = point.get_objective_values();
+            assert!((values[0] - vertex[0]).abs() < 1e-5);
+            assert!((values[1] - vertex[1]).abs() < 1e-5);
+        }
+        assert!(solver.get_n_warm_starts() > 0);
+    }
+
+    #[test]
+    fn test_epsilon_constraint() {
+        let lp = build_lp();
+        let mut solver = MultiObjectiveSolver::new(
+            &lp,
+            vec![col![1., 0., 0.], col![0., 1., 0.]],
+            &SolverOptions::new(),
+        )
+        .unwrap();
+        let bounds = [-1., 0.25, 0.5, 0.75]
+            .iter()
+            .map(|&eps| col![0., eps])
+            .collect::<Vec<_>>();
+        let front = solver.solve_epsilon_constraint(0, &bounds).unwrap();
+
+        // The infeasible bound is skipped
+        let points = front.get_points();
+        assert_eq!(points.len(), 3);
+        for point in points {
+            let eps = point.get_parameters()[1];
+            assert!((point.get_objective_values()[0] - (1. - eps)).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_quadratic() {
+        // f_0 = |x|^2 / 2 and f_1 = -x_0 - x_1 subject to x_0 + x_1 <= 2
+        let a = SparseColMat::try_new_from_triplets(
+            1,
+            3,
+            &[
+                Triplet::new(0, 0, 1.),
+                Triplet::new(0, 1, 1.),
+                Triplet::new(0, 2, 1.),
+            ],
+        )
+        .unwrap();
+        let lp = LinearProgram::new(
+            col![0., 0., 0.],
+            a,
+            col![2.],
+            col![0., 0., 0.],
+            col![2., 2., E::INFINITY],
+        );
+        let q = SparseColMat::try_new_from_triplets(
+            3,
+            3,
+            &[Triplet::new(0, 0, 1.), Triplet::new(1, 1, 1.)],
+        )
+        .unwrap();
+        let mut solver = MultiObjectiveSolver::new(
+            &lp,
+            vec![col![0., 0., 0.], col![-1., -1., 0.]],
+            &SolverOptions::new(),
+        )
+        .unwrap()
+        .with_quadratic(0, q);
+
+        let weights = [col![0.25, 0.75], col![0.6, 0.4], col![0.8, 0.2]];
+        let front = solver.solve_weighted_sum(&weights).unwrap();
+        assert_eq!(front.get_points().len(), 3);
+        for point in front.get_points() {
+            let w = point.get_parameters();
+            let x = (w[1] / w[0]).min(1.);
+            assert!((point.get_primal()[0] - x).abs() < 1e-5);
+            assert!((point.get_objective_values()[0] - x * x).abs() < 1e-5);
+        }
+
+        assert!(solver.solve_epsilon_constraint(1, &[col![0., 0.]]).is_err());
+    }
+
+    #[test]
+    fn test_invalid() {
+        let lp = build_lp();
+        let options = SolverOptions::new();
+        assert!(MultiObjectiveSolver::new(&lp, vec![col![1., 0., 0.]], &options).is_err());
+        assert!(
+            MultiObjectiveSolver::new(&lp, vec![col![1., 0., 0.], col![1.]], &options).is_err()
+        );
+
+        let mut solver =
+            MultiObjectiveSolver::new(&lp, vec![col![1., 0., 0.], col![0., 1., 0.]], &options)
+                .unwrap();
+        assert!(solver.solve_weighted_sum(&[col![0., 0.]]).is_err());
+        assert!(solver.solve_weighted_sum(&[col![-1., 2.]]).is_err());
+        assert!(solver.solve_epsilon_constraint(2, &[]).is_err());
+    }
+}
@@ -0,0 +1,220 @@
+//! Detection and solution of network-flow linear programs.
+//!
+//! A linear program is a network LP when, after flipping the sign of some rows
+//! and scaling some columns, every column of `A` contains at most one `+1` and
+//! at most one `-1`. Each row is then a node, each column an arc from the row
+//! holding the `+1` (tail) to the row holding the `-1` (head), and `A x = b`
+//! states that the net outflow of every node equals its supply. Columns with a
+//! single entry connect their node to an implicit ground node whose balance
+//! equation is implied by the others.
+//!
+//! [`NetworkStructure::detect`] recognizes such matrices and
+//! [`simplex::NetworkSimplex`] solves the resulting minimum-cost flow problem.
+
+use std::collections::VecDeque;
+
+use faer::sparse::SparseColMatRef;
+
+use crate::{E, I, lp::LinearProgram};
+
+pub mod simplex;
+
+/// The node-arc incidence structure underlying a constraint matrix.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkStructure {
+    n_rows: usize,
+    /// Sign (`±1`) applied to each row of `A`.
+    row_signs: Vec<E>,
+    /// Magnitude of the nonzeros in each column of `A`.
+    col_scales: Vec<E>,
+    /// Tail node of each arc; the ground node has index `n_rows`.
+    tails: Vec<usize>,
+    /// Head node of each arc; the ground node has index `n_rows`.
+    heads: Vec<usize>,
+}
+
+#[allow(non_snake_case)]
+impl NetworkStructure {
+    /// Detects whether `A` is a node-arc incidence matrix up to row reflections and
+    /// column scaling, returning the corresponding network if so.
+    ///
+    /// Every column must have at most two nonzeros, and the nonzeros of a column
+    /// must share the same magnitude. Row signs are assigned by propagating the
+    /// requirement that the two entries of each column have opposite signs; the
+    /// detection fails if these requirements are contradictory.
+    pub fn detect(A: SparseColMatRef<I, E>) -> Option<Self> {
+        let (n_rows, n_cols) = (A.nrows(), A.ncols());
+
+        // Nonzero entries of each column
+        let mut entries = Vec::with_capacity(n_cols);
+        let mut col_scales = Vec::with_capacity(n_cols);
+        for j in 0..n_cols {
+            let col = A
+                .row_idx_of_col(j)
+                .zip(A.val_of_col(j))
+                .filter(|&(_, &a)| a != E::from(0.))
+                .map(|(i, &a)| (i, a))
+                .collect::<Vec<_>>();
+
+            let scale = match col.as_slice() {
+                [] => E::from(1.),
+                [(_, a)] => a.abs(),
+                [(_, a), (_, b)] if a.abs() == b.abs() => a.abs(),
+                _ => return None,
+            };
+            entries.push(col);
+            col_scales.push(scale);
+        }
+
+        // Each two-entry column relates the signs of its rows: s_i a_i = -s_k a_k
+        let mut adjacency = vec![Vec::new(); n_rows];
+        for col in entries.iter() {
+            if let [(i, a), (k, b)] = col.as_slice() {
+                let same_sign = a.signum() == b.signum();
+                adjacency[*i].push((*k, same_sign));
+                adjacency[*k].push((*i, same_sign));
+            }
+        }
+
+        let mut row_signs = vec![E::from(0.); n_rows];
+        let mut queue = VecDeque::new();
+        for root in 0..n_rows {
+            if row_signs[root] != E::from(0.) {
+                continue;
+            }
+            row_signs[root] = E::from(1.);
+            queue.push_back(root);
+
+            while let Some(i) = queue.pop_front() {
+                for &(k, same_sign) in adjacency[i].iter() {
+                    let sign = if same_sign {
+                        -row_signs[i]
+                    } else {
+                        row_signs[i]
+                    };
+                    if row_signs[k] == E::from(0.) {
+                        row_signs[k] = sign;
+                        queue.push_back(k);
+                    } else if row_signs[k] != sign {
+                        return None;
+                    }
+                }
+            }
+        }
+
+        let ground = n_rows;
+        let (mut tails, mut heads) = (Vec::with_capacity(n_cols), Vec::with_capacity(n_cols));
+        for col in entries.iter() {
+            let (mut tail, mut head) = (ground, ground);
+            for &(i, a) in col.iter() {
+                if row_signs[i] * a > E::from(0.) {
+                    tail = i;
+                } else {
+                    head = i;
+                }
+            }
+            tails.push(tail);
+            heads.push(head);
+        }
+
+        Some(Self {
+            n_rows,
+            row_signs,
+            col_scales,
+            tails,
+            heads,
+        })
+    }
+
+    /// Number of nodes, including the ground node.
+    pub fn get_n_nodes(&self) -> usize {
+        self.n_rows + 1
+    }
+
+    /// Number of arcs, equal to the number of columns of `A`.
+    pub fn get_n_arcs(&self) -> usize {
+        self.tails.len()
+    }
+
+    /// Index of the ground node, which stands in for the implied balance equation.
+    pub fn get_ground(&self) -> usize {
+        self.n_rows
+    }
+
+    pub fn get_row_signs(&self) -> &[E] {
+        &self.row_signs
+    }
+
+    pub fn get_col_scales(&self) -> &[E] {
+        &self.col_scales
+    }
+
+    pub fn get_tails(&self) -> &[usize] {
+        &self.tails
+    }
+
+    pub fn get_heads(&self) -> &[usize] {
+        &self.heads
+    }
+}
+
+/// Returns `true` if `lp` can be solved by [`simplex::NetworkSimplex`]: its constraint
+/// matrix is a network matrix and every variable has at least one finite bound.
+pub fn is_network_lp(lp: &LinearProgram) -> bool {
+    has_finite_bounds(lp) && NetworkStructure::detect(lp.get_constraint_matrix().as_ref()).is_some()
+}
+
+pub(crate) fn has_finite_bounds(lp: &LinearProgram) -> bool {
+    let (l, u) = (lp.get_lower_bounds(), lp.get_upper_bounds());
+    (0..lp.get_n_vars()).all(|j| l[j].is_finite() || u[j].is_finite())
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::sparse::{SparseColMat, Triplet};
+
+    use super::*;
+
+    #[test]
+    fn test_detect_reflected_network() {
+        // Arcs 0 -> 1, 1 -> ground, ground -> 0 with row 1 negated and column 0 scaled by 2
+        let a = SparseColMat::<I, E>::try_new_from_triplets(
+            2,
+            3,
+            &[
+                Triplet::new(0, 0, 2.),
+                Triplet::new(1, 0, 2.),
+                Triplet::new(1, 1, -1.),
+                Triplet::new(0, 2, -1.),
+            ],
+        )
+        .unwrap();
+
+        let network = NetworkStructure::detect(a.as_ref()).unwrap();
+
+        assert_eq!(network.get_row_signs(), &[1., -1.]);
+        assert_eq!(network.get_col_scales(), &[2., 1., 1.]);
+        assert_eq!(network.get_tails(), &[0, 1, 2]);
+        assert_eq!(network.get_heads(), &[1, 2, 0]);
+    }
+
+    #[test]
+    fn test_detect_non_network() {
+        // An odd cycle of same-sign columns cannot be reflected into an incidence matrix
+        let a = SparseColMat::<I, E>::try_new_from_triplets(
+            3,
+            3,
+            &[
+                Triplet::new(0, 0, 1.),
+                Triplet::new(1, 0, 1.),
+                Triplet::new(1, 1, 1.),
+                Triplet::new(2, 1, 1.),
+                Triplet::new(2, 2, 1.),
+                Triplet::new(0, 2, 1.),
+            ],
+        )
+        .unwrap();
+
+        assert!(NetworkStructure::detect(a.as_ref()).is_none());
+    }
+}
@@ -0,0 +1,478 @@
+//! Primal network simplex for linear programs with a network constraint matrix.
+//!
+//! The variables are transformed into arc flows `f_j = d_j (x_j - o_j)` with
+//! `0 <= f_j <= cap_j`, where the origin `o_j` is the lower bound of `x_j` (or
+//! the upper bound, with the arc reversed, if the lower bound is infinite).
+//! Feasibility is established with big-M artificial arcs joining every node to
+//! an artificial root, and cycling is prevented by maintaining a strongly
+//! feasible spanning tree.
+//!
+//! Each solver iteration is one pricing pass over all arcs. Arcs are priced in
+//! blocks, and the most violating arc of each block enters the basis.
+
+use macros::{explicit_options, use_option};
+use problemo::{Problem, common::IntoCommonProblem};
+
+use faer::Col;
+
+use crate::{
+    E, IterativeSolver, OptimizationProgram, SolverOptions, SolverState, Status,
+    lp::{
+        LPSolver, LinearProgram,
+        network::{NetworkStructure, has_finite_bounds},
+    },
+};
+
+/// Default limit on the number of pricing passes.
+const DEFAULT_MAX_ITERATIONS: usize = 100_000;
+
+/// Reduced costs smaller in magnitude than this are treated as zero.
+const OPTIMALITY_TOLERANCE: E = 1e-9;
+
+/// Relative flow on artificial arcs above which the problem is declared infeasible.
+const FEASIBILITY_TOLERANCE: E = 1e-9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArcState {
+    Tree,
+    Lower,
+    Upper,
+}
+
+/// Position of the leaving arc within the pivot cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CycleSide {
+    /// On the tree path from the apex to the first node of the entering arc.
+    First,
+    Entering,
+    /// On the tree path from the second node of the entering arc to the apex.
+    Second,
+}
+
+/// Primal network simplex solver for network linear programs.
+///
+/// The constraint matrix must be a network matrix in the sense of
+/// [`NetworkStructure::detect`], and every variable must have a finite bound.
+#[explicit_options(name = SolverOptions, scope = "lp.network_simplex")]
+#[use_option(name = "max_iterations", type_=usize, description="Maximum number of iterations (0 uses solver defaults).")]
+pub struct NetworkSimplex<'a> {
+    lp: &'a LinearProgram,
+    network: Option<NetworkStructure>,
+
+    // Arcs: one per column of `A`, followed by one artificial arc per node
+    tail: Vec<usize>,
+    head: Vec<usize>,
+    cost: Vec<E>,
+    capacity: Vec<E>,
+    flow: Vec<E>,
+    arc_state: Vec<ArcState>,
+
+    // Map from arc flows back to the variables: x_j = origin_j + f_j / direction_j
+    origin: Col<E>,
+    direction: Vec<E>,
+
+    // Spanning tree rooted at the artificial root
+    parent: Vec<usize>,
+    pred: Vec<usize>,
+    depth: Vec<usize>,
+    potential: Vec<E>,
+    tree_arcs: Vec<Vec<usize>>,
+
+    // Pricing
+    block_size: usize,
+    next_arc: usize,
+    infeasibility_threshold: E,
+}
+
+impl<'a> NetworkSimplex<'a> {
+    fn n_arcs(&self) -> usize {
+        self.tail.len()
+    }
+
+    fn reduced_cost(&self, a: usize) -> E {
+        self.cost[a] - self.potential[self.tail[a]] + self.potential[self.head[a]]
+    }
+
+    /// Builds the arcs and the initial tree of artificial arcs.
+    fn initialize(&mut self) {
+        let Some(network) = self.network.clone() else {
+            return;
+        };
+
+        let lp = self.lp;
+        let (l, u, c) = (
+            lp.get_lower_bounds(),
+            lp.get_upper_bounds(),
+            lp.get_objective(),
+        );
+        let n_real = network.get_n_arcs();
+        let n_nodes = network.get_n_nodes();
+        let root = n_nodes;
+
+        self.tail = network.get_tails().to_vec();
+        self.head = network.get_heads().to_vec();
+        self.cost = Vec::with_capacity(n_real + n_nodes);
+        self.capacity = Vec::with_capacity(n_real + n_nodes);
+        self.direction = Vec::with_capacity(n_real);
+        self.origin = Col::zeros(n_real);
+
+        for j in 0..n_real {
+            let scale = network.get_col_scales()[j];
+            if l[j].is_finite() {
+                self.origin[j] = l[j];
+                self.direction.push(scale);
+                self.capacity.push(scale * (u[j] - l[j]));
+            } else {
+                // Only the upper bound is finite: reverse the arc and measure from u
+                self.origin[j] = u[j];
+                self.direction.push(-scale);
+                self.capacity.push(E::INFINITY);
+                std::mem::swap(&mut self.tail[j], &mut self.head[j]);
+            }
+            self.cost.push(c[j] / self.direction[j]);
+        }
+
+        // Supplies of the transformed problem, with the ground node absorbing the total
+        let residual = lp.get_rhs() - lp.get_constraint_matrix() * &self.origin;
+        let mut supply = (0..n_nodes - 1)
+            .map(|i| network.get_row_signs()[i] * residual[i])
+            .collect::<Vec<_>>();
+        supply.push(-supply.iter().sum::<E>());
+
+        // Big-M cost exceeding the cost of any simple path through the network
+        let max_cost = self.cost.iter().fold(E::from(0.), |m, c| m.max(c.abs()));
+        let big_m = (n_nodes as E + 1.) * (max_cost + 1.);
+
+        self.flow = vec![E::from(0.); n_real];
+        self.arc_state = vec![ArcState::Lower; n_real];
+        self.parent = vec![root; n_nodes + 1];
+        self.pred = vec![0; n_nodes + 1];
+        self.depth = vec![1; n_nodes + 1];
+        self.potential = vec![E::from(0.); n_nodes + 1];
+        self.tree_arcs = vec![Vec::new(); n_nodes + 1];
+        self.depth[root] = 0;
+
+        for (v, &supply) in supply.iter().enumerate() {
+            let a = self.tail.len();
+            // Zero-flow artificial arcs point towards the root to keep the tree strongly feasible
+            if supply >= E::from(0.) {
+                self.tail.push(v);
+                self.head.push(root);
+                self.flow.push(supply);
+                self.potential[v] = big_m;
+            } else {
+                self.tail.push(root);
+                self.head.push(v);
+                self.flow.push(-supply);
+                self.potential[v] = -big_m;
+            }
+            self.cost.push(big_m);
+            self.capacity.push(E::INFINITY);
+            self.arc_state.push(ArcState::Tree);
+            self.pred[v] = a;
+            self.tree_arcs[v].push(a);
+            self.tree_arcs[root].push(a);
+        }
+
+        let max_supply = supply.iter().fold(E::from(0.), |m, s| m.max(s.abs()));
+        self.infeasibility_threshold = FEASIBILITY_TOLERANCE * (1. + max_supply);
+        self.block_size = ((self.n_arcs() as E).sqrt() as usize).max(1);
+        self.next_arc = 0;
+    }
+
+    /// Pivots `entering` into the tree. Returns `false` if the pivot cycle has
+    /// unbounded capacity.
+    fn pivot(&mut self, entering: usize) -> bool {
+        // Orient the cycle along the direction that improves the objective
+        let forward = self.arc_state[entering] == ArcState::Lower;
+        let (first, second) = if forward {
+            (self.tail[entering], self.head[entering])
+        } else {
+            (self.head[entering], self.tail[entering])
+        };
+
+        let mut apex = (first, second);
+        while apex.0 != apex.1 {
+            if self.depth[apex.0] >= self.depth[apex.1] {
+                apex.0 = self.parent[apex.0];
+            } else {
+                apex.1 = self.parent[apex.1];
+            }
+        }
+        let apex = apex.0;
+
+        // Tree arcs of the cycle in the order of its orientation starting at the apex,
+        // together with whether the cycle traverses them from tail to head
+        let mut first_path = Vec::new();
+        let mut v = first;
+        while v != apex {
+            first_path.push((self.pred[v], self.tail[self.pred[v]] == self.parent[v]));
+            v = self.parent[v];
+        }
+        first_path.reverse();
+        let mut second_path = Vec::new();
+        let mut v = second;
+        while v != apex {
+            second_path.push((self.pred[v], self.tail[self.pred[v]] == v));
+            v = self.parent[v];
+        }
+
+        let residual = |(a, forward): (usize, bool)| {
+            if forward {
+                self.capacity[a] - self.flow[a]
+            } else {
+                self.flow[a]
+            }
+        };
+
+        // Choose the last blocking arc when traversing the cycle from the apex
+        let mut delta = E::INFINITY;
+        let mut leaving = (entering, forward, CycleSide::Entering);
+        for &(a, arc_forward) in first_path.iter() {
+            let r = residual((a, arc_forward));
+            if r <= delta {
+                delta = r;
+                leaving = (a, arc_forward, CycleSide::First);
+            }
+        }
+        if self.capacity[entering] <= delta {
+            delta = self.capacity[entering];
+            leaving = (entering, forward, CycleSide::Entering);
+        }
+        for &(a, arc_forward) in second_path.iter() {
+            let r = residual((a, arc_forward));
+            if r <= delta {
+                delta = r;
+                leaving = (a, arc_forward, CycleSide::Second);
+            }
+        }
+
+        if delta == E::INFINITY {
+            return false;
+        }
+
+        // Augment the flow around the cycle
+        for &(a, arc_forward) in first_path
+            .iter()
+            .chain(std::iter::once(&(entering, forward)))
+            .chain(second_path.iter())
+        {
+            self.flow[a] += if arc_forward { delta } else { -delta };
+        }
+
+        let (out_arc, leaving_forward, side) = leaving;
+        if leaving_forward {
+            self.flow[out_arc] = self.capacity[out_arc];
+            self.arc_state[out_arc] = ArcState::Upper;
+        } else {
+            self.flow[out_arc] = E::from(0.);
+            self.arc_state[out_arc] = ArcState::Lower;
+        }
+
+        if side == CycleSide::Entering {
+            return true;
+        }
+
+        // Exchange the arcs and rehang the subtree cut off by the leaving arc
+        self.arc_state[entering] = ArcState::Tree;
+        for v in [self.tail[out_arc], self.head[out_arc]] {
+            self.tree_arcs[v].retain(|&a| a != out_arc);
+        }
+        for v in [self.tail[entering], self.head[entering]] {
+            self.tree_arcs[v].push(entering);
+        }
+
+        let (inner, outer) = if side == CycleSide::First {
+            (first, second)
+        } else {
+            (second, first)
+        };
+        self.attach(inner, outer, entering);
+        let mut stack = vec![inner];
+        while let Some(v) = stack.pop() {
+            for k in 0..self.tree_arcs[v].len() {
+                let a = self.tree_arcs[v][k];
+                if a == self.pred[v] {
+                    continue;
+                }
+                let w = if self.tail[a] == v {
+                    self.head[a]
+                } else {
+                    self.tail[a]
+                };
+                self.attach(w, v, a);
+                stack.push(w);
+            }
+        }
+
+        true
+    }
+
+    /// Makes `v` a child of `parent` through the tree arc `a`.
+    fn attach(&mut self, v: usize, parent: usize, a: usize) {
+        self.parent[v] = parent;
+        self.pred[v] = a;
+        self.depth[v] = self.depth[parent] + 1;
+        // Tree arcs have zero reduced cost: pi_tail - pi_head = cost
+        self.potential[v] = if self.tail[a] == parent {
+            self.potential[parent] - self.cost[a]
+        } else {
+            self.potential[parent] + self.cost[a]
+        };
+    }
+
+    /// Writes the primal-dual point corresponding to the current tree into `state`.
+    fn update_state(&self, state: &mut SolverState) {
+        let Some(network) = &self.network else {
+            return;
+        };
+        let lp = self.lp;
+        let (n, m) = lp.get_dims();
+        let ground = network.get_ground();
+
+        state.x = Col::from_fn(n, |j| self.origin[j] + self.flow[j] / self.direction[j]);
+        state.y = Col::from_fn(m, |i| {
+            network.get_row_signs()[i] * (self.potential[i] - self.potential[ground])
+        });
+
+        // Reduced costs are attributed to the bound at which the variable rests. Only
+        // the part with the sign of an optimal multiplier is kept, so that pricing
+        // violations remain visible in the dual residual until the final pass.
+        let rc = lp.get_objective() - lp.get_constraint_matrix().transpose() * &state.y;
+        state.z_l = Col::zeros(n);
+        state.z_u = Col::zeros(n);
+        for j in 0..n {
+            let at_origin = match self.arc_state[j] {
+                ArcState::Tree => continue,
+                ArcState::Lower => true,
+                ArcState::Upper => false,
+            };
+            if at_origin == (self.direction[j] > E::from(0.)) {
+                state.z_l[j] = rc[j].max(E::from(0.));
+            } else {
+                state.z_u[j] = rc[j].min(E::from(0.));
+            }
+        }
+
+        lp.update_residual(state);
+    }
+
+    fn iterate(&mut self, state: &mut SolverState) -> Result<(), Problem> {
+        if self.network.is_none() {
+            return Err(
+                "Network simplex requires a network matrix and a finite bound on every variable"
+                    .gloss(),
+            );
+        }
+
+        let n_arcs = self.n_arcs();
+        let mut n_pivots = 0;
+        let mut n_scanned = 0;
+        while n_scanned < n_arcs {
+            let mut entering = None;
+            let mut max_violation = OPTIMALITY_TOLERANCE;
+            for _ in 0..self.block_size.min(n_arcs - n_scanned) {
+                let a = self.next_arc;
+                self.next_arc = (self.next_arc + 1) % n_arcs;
+                n_scanned += 1;
+
+                let violation = match self.arc_state[a] {
+                    ArcState::Tree => continue,
+                    ArcState::Lower => -self.reduced_cost(a),
+                    ArcState::Upper => self.reduced_cost(a),
+                };
+                if violation > max_violation {
+                    max_violation = violation;
+                    entering = Some(a);
+                }
+            }
+
+            if let Some(a) = entering {
+                if !self.pivot(a) {
+                    state.status = Status::Unbounded;
+                    self.update_state(state);
+                    return Ok(());
+                }
+                n_pivots += 1;
+            }
+        }
+
+        state.alpha_primal = E::from(1.);
+        state.alpha_dual = E::from(1.);
+        self.update_state(state);
+
+        state.status = if n_pivots > 0 {
+            Status::InProgress
+        } else if self.flow[self.lp.get_n_vars()..]
+            .iter()
+            .any(|&f| f > self.infeasibility_threshold)
+        {
+            Status::Infeasible
+        } else {
+            Status::Optimal
+        };
+
+        Ok(())
+    }
+}
+
+impl<'a> LPSolver<'a> for NetworkSimplex<'a> {
+    fn new(lp: &'a LinearProgram, options: &SolverOptions) -> Self {
+        let network = NetworkStructure::detect(lp.get_constraint_matrix().as_ref())
+            .filter(|_| has_finite_bounds(lp));
+
+        Self {
+            lp,
+            network,
+
+            tail: Vec::new(),
+            head: Vec::new(),
+            cost: Vec::new(),
+            capacity: Vec::new(),
+            flow: Vec::new(),
+            arc_state: Vec::new(),
+
+            origin: Col::zeros(0),
+            direction: Vec::new(),
+
+            parent: Vec::new(),
+            pred: Vec::new(),
+            depth: Vec::new(),
+            potential: Vec::new(),
+            tree_arcs: Vec::new(),
+
+            block_size: 1,
+            next_arc: 0,
+            infeasibility_threshold: FEASIBILITY_TOLERANCE,
+
+            options: options.into(),
+        }
+    }
+}
+
+impl<'a> IterativeSolver for NetworkSimplex<'a> {
+    fn get_max_iterations(&self) -> usize {
+        if self.options.max_iterations > 0 {
+            self.options.max_iterations
+        } else {
+            DEFAULT_MAX_ITERATIONS
+        }
+    }
+
+    fn get_options(&self) -> Option<&SolverOptions> {
+        Some(&self.options.root)
+    }
+
+    fn get_program(&self) -> &dyn OptimizationProgram {
+        self.lp
+    }
+
+    fn initialize(&mut self, _state: &mut SolverState) {
+        self.initialize();
+    }
+
+    fn iterate(&mut self, state: &mut SolverState) -> Result<Status, Problem> {
+        self.iterate(state)?;
+        Ok(state.get_status())
+    }
+}
@@ -0,0 +1,443 @@
+//! Parametric linear programming.
+//!
+//! Solves the family of linear programs obtained by moving the objective or the
+//! right-hand side of a [`LinearProgram`] along a direction,
+//!
+//! ```text
+//! min  (c + t dc)^T x            min  c^T x
+//! s.t. A x = b            or     s.t. A x = b + t db
+//!      l <= x <= u                    l <= x <= u
+//! ```
+//!
+//! for a range of values of `t`. Consecutive problems differ only in `c` or `b`,
+//! so each solve is warm started from the solution at the previous value of `t`,
+//! pushed back into the interior by `parametric_warm_start_shift`. A warm start
+//! that does not reach optimality is retried from the default starting point.
+//!
+//! The active set of a solution is the bound status of every column. Whenever it
+//! differs between consecutive values of `t`, the interval is bisected down to
+//! `parametric_breakpoint_tolerance` and the midpoint is reported as a breakpoint.
+//! Only one breakpoint is located per interval, so the grid should be fine enough
+//! to separate them.
+
+use std::cmp::Ordering;
+
+use faer::Col;
+use macros::{explicit_options, use_option};
+use problemo::{Problem, common::IntoCommonProblem};
+
+use crate::{
+    E, SolverHooks, SolverOptions, SolverState, Status, callback::NoOpCallback,
+    interface::basis::BasisStatus, lp::LinearProgram, terminators::ComplementarityTerminator,
+};
+
+/// Direction along which the linear program is moved.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParametricDirection {
+    /// Objective `c + t dc`.
+    Objective(Col<E>),
+    /// Right-hand side `b + t db`.
+    Rhs(Col<E>),
+}
+
+/// Optimal solution of the linear program at one value of the parameter.
+#[derive(Debug, Clone)]
+pub struct ParametricSolution {
+    t: E,
+    objective: E,
+    state: SolverState,
+    active_set: Vec<BasisStatus>,
+}
+
+impl ParametricSolution {
+    pub fn get_parameter(&self) -> E {
+        self.t
+    }
+
+    pub fn get_objective_value(&self) -> E {
+        self.objective
+    }
+
+    pub fn get_primal(&self) -> &Col<E> {
+        &self.state.x
+    }
+
+    pub fn get_dual(&self) -> &Col<E> {
+        &self.state.y
+    }
+
+    /// Bound status of every column; columns strictly between their bounds are basic.
+    pub fn get_active_set(&self) -> &[BasisStatus] {
+        &self.active_set
+    }
+}
+
+/// Solutions over a range of the parameter, with the breakpoints in between.
+#[derive(Debug, Clone)]
+pub struct ParametricPath {
+    solutions: Vec<ParametricSolution>,
+    breakpoints: Vec<E>,
+}
+
+impl ParametricPath {
+    pub fn get_solutions(&self) -> &[ParametricSolution] {
+        &self.solutions
+    }
+
+    /// Values of the parameter at which the active set changes, in increasing order.
+    pub fn get_breakpoints(&self) -> &[E] {
+        &self.breakpoints
+    }
+}
+
+/// Solver for a linear program moving along a [`ParametricDirection`].
+#[explicit_options(name = SolverOptions)]
+#[use_option(name = "parametric_active_tolerance", type_ = E, default = "1e-6", description = "Relative distance to a bound below which a column counts as active in parametric solves.")]
+#[use_option(name = "parametric_breakpoint_tolerance", type_ = E, default = "1e-6", description = "Width of the parameter interval at which the bisection for a breakpoint stops.")]
+#[use_option(name = "parametric_warm_start_shift", type_ = E, default = "1e-2", description = "Minimal distance of a warm start to the bounds and minimal magnitude of its bound multipliers.")]
+pub struct ParametricSolver<'a> {
+    lp: &'a LinearProgram,
+    direction: ParametricDirection,
+
+    n_solves: usize,
+    n_warm_starts: usize,
+}
+
+impl<'a> ParametricSolver<'a> {
+    pub fn new(
+        lp: &'a LinearProgram,
+        direction: ParametricDirection,
+        options: &SolverOptions,
+    ) -> Result<Self, Problem> {
+        let (n_vars, n_cons) = lp.get_dims();
+        match &direction {
+            ParametricDirection::Objective(dc) if dc.nrows() != n_vars => {
+                return Err(format!(
+                    "Objective direction has length {} but the program has {n_vars} variables",
+                    dc.nrows()
+                )
+                .gloss());
+            }
+            ParametricDirection::Rhs(db) if db.nrows() != n_cons => {
+                return Err(format!(
+                    "Right-hand side direction has length {} but the program has {n_cons} constraints",
+                    db.nrows()
+                )
+                .gloss());
+            }
+            _ => {}
+        }
+
+        Ok(Self {
+            lp,
+            direction,
+            n_solves: 0,
+            n_warm_starts: 0,
+            options: options.into(),
+        })
+    }
+
+    /// Number of linear programs solved so far.
+    pub fn get_n_solves(&self) -> usize {
+        self.n_solves
+    }
+
+    /// Number of solves that reached optimality from a warm start.
+    pub fn get_n_warm_starts(&self) -> usize {
+        self.n_warm_starts
+    }
+
+    /// The linear program at parameter `t`.
+    #[allow(non_snake_case)]
+    pub fn get_program(&self, t: E) -> LinearProgram {
+        let lp = self.lp;
+        let A = lp.get_constraint_matrix().clone();
+        let (l, u) = (lp.get_lower_bounds().clone(), lp.get_upper_bounds().clone());
+        match &self.direction {
+            ParametricDirection::Objective(dc) => {
+                LinearProgram::new(lp.get_objective() + t * dc, A, lp.get_rhs().clone(), l, u)
+            }
+            ParametricDirection::Rhs(db) => {
+                LinearProgram::new(lp.get_objective().clone(), A, lp.get_rhs() + t * db, l, u)
+            }
+        }
+    }
+
+    /// Solves the linear program at `t`, warm started from `previous` if given.
+    /// Fails if the program is not solved to optimality.
+    pub fn solve_at(
+        &mut self,
+        t: E,
+        previous: Option<&ParametricSolution>,
+    ) -> Result<ParametricSolution, Problem> {
+        let lp = self.get_program(t);
+
+        let mut state = None;
+        if let Some(previous) = previous {
+            let mut warm = warm_start(
+                &previous.state,
+                lp.get_lower_bounds(),
+                lp.get_upper_bounds(),
+                self.options.parametric_warm_start_shift,
+            );
+            if self.solve_program(&lp, &mut warm).is_ok() {
+                self.n_warm_starts += 1;
+                state = Some(warm);
+            }
+        }
+        let state = match state {
+            Some(state) => state,
+            None => {
+                let mut cold = SolverState::new_interior(
+                    lp.get_lower_bounds(),
+                    lp.get_upper_bounds(),
+                    lp.get_n_cons(),
+                );
+                self.solve_program(&lp, &mut cold)
+                    .map_err(|e| format!("Parametric problem at t = {t}: {e}").gloss())?;
+                cold
+            }
+        };
+
+        Ok(ParametricSolution {
+            t,
+            objective: lp.get_objective_value(&state.x),
+            active_set: self.active_set(&state.x),
+            state,
+        })
+    }
+
+    /// Solves the linear program at `n_steps + 1` equally spaced values from
+    /// `t_start` to `t_end` and locates the breakpoints between them.
+    pub fn solve_range(
+        &mut self,
+        t_start: E,
+        t_end: E,
+        n_steps: usize,
+    ) -> Result<ParametricPath, Problem> {
+        if n_steps == 0 || t_start.partial_cmp(&t_end) != Some(Ordering::Less) {
+            return Err("Parametric range requires t_start < t_end and at least one step".gloss());
+        }
+
+        let step = (t_end - t_start) / n_steps as E;
+        let mut solutions: Vec<ParametricSolution> = Vec::with_capacity(n_steps + 1);
+        let mut breakpoints = Vec::new();
+        for k in 0..=n_steps {
+            let t = if k == n_steps {
+                t_end
+            } else {
+                t_start + k as E * step
+            };
+            let solution = self.solve_at(t, solutions.last())?;
+            if let Some(previous) = solutions.last()
+                && previous.active_set != solution.active_set
+            {
+                breakpoints.push(self.bisect(previous, &solution)?);
+            }
+            solutions.push(solution);
+        }
+
+        Ok(ParametricPath {
+            solutions,
+            breakpoints,
+        })
+    }
+
+    /// Narrows the interval between two solutions with different active sets.
+    fn bisect(
+        &mut self,
+        left: &ParametricSolution,
+        right: &ParametricSolution,
+    ) -> Result<E, Problem> {
+        let (mut t_left, mut t_right) = (left.t, right.t);
+        let mut warm = left.clone();
+        while t_right - t_left
+            > self.options.parametric_breakpoint_tolerance * E::from(1.).max(t_left.abs())
+        {
+            let t = (t_left + t_right) / E::from(2.);
+            let solution = self.solve_at(t, Some(&warm))?;
+            if solution.active_set == left.active_set {
+                t_left = t;
+                warm = solution;
+            } else {
+                t_right = t;
+            }
+        }
+        Ok((t_left + t_right) / E::from(2.))
+    }
+
+    fn solve_program(
+        &mut self,
+        lp: &LinearProgram,
+        state: &mut SolverState,
+    ) -> Result<(), Problem> {
+        self.n_solves += 1;
+        let mut hooks = SolverHooks {
+            callback: Box::new(NoOpCallback::new()),
+            terminator: Box::new(ComplementarityTerminator::new(&self.options.root)),
+        };
+        let mut solver = lp
+            .solver_builder()
+            .with_options(self.options.root.clone())
+            .build()?;
+        match solver.solve(state, &mut hooks)? {
+            Status::Optimal => Ok(()),
+            status => Err(format!("Parametric solve terminated with status {status:?}").gloss()),
+        }
+    }
+
+    fn active_set(&self, x: &Col<E>) -> Vec<BasisStatus> {
+        let tolerance = self.options.parametric_active_tolerance;
+        let (l, u) = (self.lp.get_lower_bounds(), self.lp.get_upper_bounds());
+        (0..x.nrows())
+            .map(|j| {
+                if x[j] - l[j] <= tolerance * (E::from(1.) + l[j].abs()) {
+                    BasisStatus::AtLower
+                } else if u[j] - x[j] <= tolerance * (E::from(1.) + u[j].abs()) {
+                    BasisStatus::AtUpper
+                } else {
+                    BasisStatus::Basic
+                }
+            })
+            .collect()
+    }
+}
+
+/// Moves a previous solution at least `shift` into the interior of the bounds
+/// `l <= x <= u` and away from zero multipliers on the finite bounds. See
+/// [`SolverState::push_to_interior`].
+pub(crate) fn warm_start(previous: &SolverState, l: &Col<E>, u: &Col<E>, shift: E) -> SolverState {
+    let mut state = SolverState::new(
+        previous.x.clone(),
+        previous.y.clone(),
+        previous.z_l.clone(),
+        previous.z_u.clone(),
+    );
+    state.push_to_interior(l, u, shift);
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::{
+        col,
+        sparse::{SparseColMat, Triplet},
+    };
+
+    use super::*;
+
+    /// `min c^T x` subject to `x_0 + x_1 + s = b` with `x_0 <= 1` and `x_1 <= 1.5`.
+    fn build_lp(c: Col<E>, b: E) -> LinearProgram {
+        let a = SparseColMat::try_new_from_triplets(
+            1,
+            3,
+            &[
+                Triplet::new(0, 0, 1.),
+                Triplet::new(0, 1, 1.),
+                Triplet::new(0, 2, 1.),
+            ],
+        )
+        .unwrap();
+        LinearProgram::new(c, a, col![b], col![0., 0., 0.], col![1., 1.5, E::INFINITY])
+    }
+
+    fn build_options() -> SolverOptions {
+        let mut options = SolverOptions::new();
+        options.set_option("tolerance", 1e-10).unwrap();
+        options
+            .set_option("parametric_breakpoint_tolerance", 1e-4)
+            .unwrap();
+        options
+    }
+
+    #[test]
+    fn test_objective() {
+        // min -(1 - t) x_0 - t x_1 moves from x_0 = 1 to x_1 = 1 at t = 0.5
+        let lp = build_lp(col![-1., 0., 0.], 1.);
+        let mut solver = ParametricSolver::new(
+            &lp,
+            ParametricDirection::Objective(col![1., -1., 0.]),
+            &build_options(),
+        )
+        .unwrap();
+        let path = solver.solve_range(0., 1., 3).unwrap();
+
+        let solutions = path.get_solutions();
+        assert_eq!(solutions.len(), 4);
+        for solution in solutions {
+            let t = solution.get_parameter();
+            assert!((solution.get_objective_value() + (1. - t).max(t)).abs() < 1e-6);
+        }
+        assert_eq!(solutions[0].get_active_set()[0], BasisStatus::AtUpper);
+        assert_eq!(solutions[3].get_active_set()[1], BasisStatus::Basic);
+
+        assert_eq!(path.get_breakpoints().len(), 1);
+        assert!((path.get_breakpoints()[0] - 0.5).abs() < 1e-2);
+        assert!(solver.get_n_warm_starts() > 0);
+    }
+
+    #[test]
+    fn test_rhs() {
+        // min -x_0 - 2 x_1 fills x_1 up to b = 1.5, then x_0 up to b = 2.5
+        let lp = build_lp(col![-1., -2., 0.], 1.);
+        let mut solver =
+            ParametricSolver::new(&lp, ParametricDirection::Rhs(col![1.]), &build_options())
+                .unwrap();
+        let path = solver.solve_range(0., 2., 5).unwrap();
+
+        for solution in path.get_solutions() {
+            let b = 1. + solution.get_parameter();
+            let x_1 = b.min(1.5);
+            let x_0 = (b - x_1).min(1.);
+            assert!((solution.get_objective_value() + x_0 + 2. * x_1).abs() < 1e-6);
+            assert!((solution.get_primal()[0] - x_0).abs() < 1e-6);
+        }
+
+        let breakpoints = path.get_breakpoints();
+        assert_eq!(breakpoints.len(), 2);
+        assert!((breakpoints[0] - 0.5).abs() < 1e-2);
+        assert!((breakpoints[1] - 1.5).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let lp = build_lp(col![-1., 0., 0.], 1.);
+        let options = SolverOptions::new();
+        assert!(
+            ParametricSolver::new(&lp, ParametricDirection::Rhs(col![1., 1.]), &options).is_err()
+        );
+        assert!(
+            ParametricSolver::new(&lp, ParametricDirection::Objective(col![1.]), &options).is_err()
+        );
+
+        let mut solver =
+            ParametricSolver::new(&lp, ParametricDirection::Rhs(col![1.]), &options).unwrap();
+        assert!(solver.solve_range(1., 0., 2).is_err());
+        assert!(solver.solve_range(0., 1., 0).is_err());
+    }
+
+    #[test]
+    fn test_push_to_interior() {
+        let (l, u) = (
+            col![0., 0., -E::INFINITY, 1.],
+            col![E::INFINITY, 0.1, E::INFINITY, 1.],
+        );
+        let mut state = SolverState::new(
+            col![-1., 0., 5., 1.],
+            col![2.],
+            col![0., 0.5, 1., 0.],
+            col![-1., 0., 0., 0.],
+        );
+        state.push_to_interior(&l, &u, 0.05);
+
+        // Close bounds shift by a quarter of their width, fixed variables stay
+        assert_eq!(state.get_primal(), &col![0.05, 0.025, 5., 1.]);
+        assert_eq!(state.get_dual(), &col![2.]);
+        assert_eq!(state.z_l, col![0.05, 0.5, 0., 0.05]);
+        assert_eq!(state.z_u, col![0., -0.05, 0., -0.05]);
+
+        let interior = SolverState::new_interior(&l, &u, 1);
+        assert_eq!(interior.get_primal(), &col![1., 0.05, 0., 1.]);
+        assert_eq!(interior.z_u, col![0., -1., 0., -1.]);
+    }
+}
@@ -0,0 +1,283 @@
+//! Perturbation of linear programs to break degeneracy.
+//!
+//! Degenerate linear programs, whose optimal face or optimal dual set is not a
+//! single point, slow interior-point methods down near the optimum. Moving the
+//! objective and right-hand side by a tiny random amount makes the optimum
+//! unique with high probability:
+//!
+//! - each cost `c_j` is shifted by `perturbation_size * (1 + |c_j|) * r_j` with
+//!   `r_j` drawn from `[0.5, 1]`, towards the finite bound of the column so that
+//!   the column is kept at that bound;
+//! - each right-hand side `b_i` is shifted by `perturbation_size * (1 + |b_i|) * r_i`
+//!   with a random sign.
+//!
+//! [`PerturbedSolver`] solves the perturbed program and then removes the
+//! perturbation by re-solving the original program warm started from the
+//! perturbed solution, which also verifies that solution. The random numbers are
+//! drawn from the `perturbation` stream of [`SeedSequence`], so a run is
+//! reproducible for a fixed `random_seed`.
+
+use faer::{
+    Col,
+    rand::{Rng, rngs::StdRng},
+};
+use macros::{explicit_options, use_option};
+use problemo::Problem;
+
+use crate::{
+    E, SolverHooks, SolverOptions, SolverState, Status,
+    lp::{LinearProgram, parametric::warm_start},
+    utils::random::SeedSequence,
+};
+
+/// Shifts of the objective and right-hand side of a linear program.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Perturbation {
+    objective: Col<E>,
+    rhs: Col<E>,
+}
+
+impl Perturbation {
+    /// Draws a perturbation of `lp` of relative size `size`. Disabled parts
+    /// are zero.
+    pub fn sample(
+        lp: &LinearProgram,
+        size: E,
+        objective: bool,
+        rhs: bool,
+        rng: &mut StdRng,
+    ) -> Self {
+        let (c, b) = (lp.get_objective(), lp.get_rhs());
+        let (l, u) = (lp.get_lower_bounds(), lp.get_upper_bounds());
+
+        let magnitude = |value: E, rng: &mut StdRng| {
+            size * (E::from(1.) + value.abs()) * rng.random_range(0.5..=1.)
+        };
+        let objective = Col::from_fn(c.nrows(), |j| {
+            if !objective || l[j] == u[j] {
+                return E::from(0.);
+            }
+            let shift = magnitude(c[j], rng);
+            // Keep the column at the finite bound its cost already favours
+            match (l[j].is_finite(), u[j].is_finite()) {
+                (true, false) => shift,
+                (false, true) => -shift,
+                _ if c[j] < E::from(0.) => -shift,
+                _ => shift,
+            }
+        });
+        let rhs = Col::from_fn(b.nrows(), |i| {
+            if !rhs {
+                return E::from(0.);
+            }
+            let shift = magnitude(b[i], rng);
+            if rng.random::<bool>() { shift } else { -shift }
+        });
+
+        Self { objective, rhs }
+    }
+
+    pub fn get_objective_shift(&self) -> &Col<E> {
+        &self.objective
+    }
+
+    pub fn get_rhs_shift(&self) -> &Col<E> {
+        &self.rhs
+    }
+
+    /// Returns `lp` with the perturbation applied.
+    pub fn apply(&self, lp: &LinearProgram) -> LinearProgram {
+        LinearProgram::new(
+            lp.get_objective() + &self.objective,
+            lp.get_constraint_matrix().clone(),
+            lp.get_rhs() + &self.rhs,
+            lp.get_lower_bounds().clone(),
+            lp.get_upper_bounds().clone(),
+        )
+    }
+}
+
+/// Solves a linear program through a perturbed copy of it.
+#[explicit_options(name = SolverOptions)]
+#[use_option(name = "perturb_objective", type_ = bool, default = "true", description = "Perturb the objective of linear programs solved through PerturbedSolver.")]
+#[use_option(name = "perturb_rhs", type_ = bool, default = "false", description = "Perturb the right-hand side of linear programs solved through PerturbedSolver.")]
+#[use_option(name = "perturbation_size", type_ = E, default = "1e-6", description = "Relative size of the perturbation of the objective and right-hand side.")]
+#[use_option(name = "parametric_warm_start_shift", type_ = E, default = "1e-2", description = "Minimal distance of a warm start to the bounds and minimal magnitude of its bound multipliers.")]
+pub struct PerturbedSolver<'a> {
+    lp: &'a LinearProgram,
+    perturbation: Perturbation,
+
+    perturbed_status: Option<Status>,
+    perturbed_objective: Option<E>,
+    objective: Option<E>,
+}
+
+impl<'a> PerturbedSolver<'a> {
+    /// Draws the perturbation of `lp` according to `options`.
+    pub fn new(lp: &'a LinearProgram, options: &SolverOptions) -> Self {
+        let mut rng = SeedSequence::new(options).get_rng("perturbation");
+        let options: PerturbedSolverInternalOptions = options.into();
+        let perturbation = Perturbation::sample(
+            lp,
+            options.perturbation_size,
+            options.perturb_objective,
+            options.perturb_rhs,
+            &mut rng,
+        );
+        Self {
+            lp,
+            perturbation,
+            perturbed_status: None,
+            perturbed_objective: None,
+            objective: None,
+            options,
+        }
+    }
+
+    pub fn get_perturbation(&self) -> &Perturbation {
+        &self.perturbation
+    }
+
+    /// Status of the solve of the perturbed program.
+    pub fn get_perturbed_status(&self) -> Option<Status> {
+        self.perturbed_status
+    }
+
+    /// Original objective value at the solution of the perturbed program.
+    pub fn get_perturbed_objective(&self) -> Option<E> {
+        self.perturbed_objective
+    }
+
+    /// Change of the original objective value between the solution of the
+    /// perturbed program and the final solution.
+    pub fn get_objective_change(&self) -> Option<E> {
+        Some(self.objective? - self.perturbed_objective?)
+    }
+
+    /// Solves the perturbed program starting from `state`, then re-solves the
+    /// original program from its solution. `state` holds the final iterate and
+    /// the status of the re-solve is returned. If the perturbed program is not
+    /// solved to optimality, its status is returned without a re-solve.
+    pub fn solve(
+        &mut self,
+        state: &mut SolverState,
+        hooks: &mut SolverHooks,
+    ) -> Result<Status, Problem> {
+        let perturbed = self.perturbation.apply(self.lp);
+        let status = perturbed
+            .solver_builder()
+            .with_options(self.options.root.clone())
+            .build()?
+            .solve(state, &mut hooks.clone())?;
+        self.perturbed_status = Some(status);
+        self.perturbed_objective = Some(self.lp.get_objective_value(&state.x));
+        self.objective = None;
+        if status != Status::Optimal {
+            return Ok(status);
+        }
+
+        let (l, u) = (self.lp.get_lower_bounds(), self.lp.get_upper_bounds());
+        let mut warm = warm_start(state, l, u, self.options.parametric_warm_start_shift);
+        let status = self
+            .lp
+            .solver_builder()
+            .with_options(self.options.root.clone())
+            .build()?
+            .solve(&mut warm, hooks)?;
+        self.objective = Some(self.lp.get_objective_value(&warm.x));
+        *state = warm;
+        Ok(status)
+    }
+
+    /// Solves from the default starting point of [`SolverState::new_interior`].
+    pub fn solve_cold(
+        &mut self,
+        hooks: &mut SolverHooks,
+    ) -> Result<(Status, SolverState), Problem> {
+        let mut state = SolverState::new_interior(
+            self.lp.get_lower_bounds(),
+            self.lp.get_upper_bounds(),
+            self.lp.get_n_cons(),
+        );
+        let status = self.solve(&mut state, hooks)?;
+        Ok((status, state))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::{
+        col,
+        sparse::{SparseColMat, Triplet},
+    };
+    use rstest::rstest;
+
+    use super::*;
+    use crate::{callback::NoOpCallback, terminators::ComplementarityTerminator};
+
+    /// `min -x_0 - x_1 + x_2` subject to `x_0 + x_1 + x_2 = 1`, `0 <= x <= 1`:
+    /// every point of the edge `x_0 + x_1 = 1` is optimal.
+    fn build_lp() -> LinearProgram {
+        let a = SparseColMat::try_new_from_triplets(
+            1,
+            3,
+            &[
+                Triplet::new(0, 0, 1.),
+                Triplet::new(0, 1, 1.),
+                Triplet::new(0, 2, 1.),
+            ],
+        )
+        .unwrap();
+        LinearProgram::new(
+            col![-1., -1., 1.],
+            a,
+            col![1.],
+            col![0., 0., 0.],
+            col![1., 1., 1.],
+        )
+    }
+
+    fn options(seed: u64, rhs: bool) -> SolverOptions {
+        let mut options = SolverOptions::new();
+        options.set_option("random_seed", seed).unwrap();
+        options.set_option("perturb_rhs", rhs).unwrap();
+        options
+    }
+
+    #[rstest]
+    fn test_reproducible(#[values(false, true)] rhs: bool) {
+        let lp = build_lp();
+        let first = PerturbedSolver::new(&lp, &options(3, rhs));
+        let second = PerturbedSolver::new(&lp, &options(3, rhs));
+        let other = PerturbedSolver::new(&lp, &options(4, rhs));
+        assert_eq!(first.get_perturbation(), second.get_perturbation());
+        assert_ne!(first.get_perturbation(), other.get_perturbation());
+
+        let perturbation = first.get_perturbation();
+        let shift = perturbation.get_objective_shift();
+        // Costs move away from zero, by at most 2e-6 relative to the cost
+        assert!(shift[0] < 0. && shift[1] < 0. && shift[2] > 0.);
+        assert!(shift.iter().all(|s| s.abs() <= 2e-6));
+        assert_eq!(perturbation.get_rhs_shift()[0] != 0., rhs);
+    }
+
+    #[rstest]
+    fn test_solve(#[values(false, true)] rhs: bool) {
+        let lp = build_lp();
+        let options = options(0, rhs);
+        let mut hooks = SolverHooks {
+            callback: Box::new(NoOpCallback::new()),
+            terminator: Box::new(ComplementarityTerminator::new(&options)),
+        };
+
+        let mut solver = PerturbedSolver::new(&lp, &options);
+        let (status, state) = solver.solve_cold(&mut hooks).unwrap();
+        assert_eq!(solver.get_perturbed_status(), Some(Status::Optimal));
+        assert_eq!(status, Status::Optimal);
+
+        // The re-solve verifies the solution of the perturbed program
+        assert!((lp.get_objective_value(state.get_primal()) + 1.).abs() < 1e-6);
+        assert!(solver.get_objective_change().unwrap().abs() < 1e-5);
+        assert!(lp.get_constraint_values(state.get_primal()).norm_l2() < 1e-6);
+    }
+}
@@ -0,0 +1,316 @@
+//! Phase-1 feasibility solve of linear programs.
+//!
+//! Interior-point methods start from the bound midpoints of
+//! [`SolverState::new_interior`], whose residual `A x - b` may be enormous,
+//! e.g. for rows with large right-hand sides, or from which the solve may fail
+//! to converge. Phase 1 instead minimizes the total infeasibility of the
+//! constraints with elastic variables `p, q >= 0`:
+//!
+//! ```text
+//! min  1^T p + 1^T q
+//! s.t. A x + p - q = b
+//!      l <= x <= u
+//! ```
+//!
+//! which is feasible for any `b` and needs no big-M penalty on the original
+//! objective. If the minimal infeasibility is zero, `x` satisfies `A x = b`
+//! and the original program is solved warm started from it; otherwise the
+//! program is infeasible.
+//!
+//! [`Phase1Solver`] selects the mode with the `phase1` option:
+//!
+//! - `auto`: phase 1 runs first if the primal residual of the starting point,
+//!   relative to `1 + ‖b‖∞`, exceeds `phase1_residual_threshold`, and after a
+//!   solve from the starting point that does not reach optimality otherwise.
+//! - `always`: phase 1 always runs first.
+//! - `never`: the program is solved from the starting point only.
+
+use std::str::FromStr;
+
+use faer::{
+    Col,
+    sparse::{SparseColMat, Triplet},
+};
+use macros::{explicit_options, use_option};
+use problemo::{Problem, common::IntoCommonProblem};
+
+use crate::{
+    E, OptionTrait, SolverHooks, SolverOptions, SolverState, Status, TerminationInfo,
+    lp::LinearProgram, to_index,
+};
+
+/// When [`Phase1Solver`] runs the phase-1 feasibility solve.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Phase1Mode {
+    /// If the starting point is far from feasible or the solve from it fails.
+    #[default]
+    Auto,
+    /// Before every solve.
+    Always,
+    /// Never.
+    Never,
+}
+
+impl OptionTrait for Phase1Mode {}
+
+impl FromStr for Phase1Mode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(Phase1Mode::Auto),
+            "always" => Ok(Phase1Mode::Always),
+            "never" => Ok(Phase1Mode::Never),
+            _ => Err(format!("Invalid phase 1 mode: {}", s)),
+        }
+    }
+}
+
+/// Solves a linear program, finding a feasible starting point by phase 1 when
+/// needed.
+#[explicit_options(name = SolverOptions)]
+#[use_option(name = "phase1", type_ = crate::lp::phase1::Phase1Mode, default = "auto", description = "When linear programs solved through Phase1Solver first minimize their infeasibility: auto, always or never.")]
+#[use_option(name = "phase1_residual_threshold", type_ = E, default = "1e6", description = "Primal residual of the starting point, relative to 1 + the largest right-hand side, above which phase 1 runs first in auto mode.")]
+#[use_option(name = "phase1_tolerance", type_ = E, default = "1e-6", description = "Minimal infeasibility of phase 1, relative to 1 + the largest right-hand side, above which a linear program is infeasible.")]
+#[use_option(name = "parametric_warm_start_shift", type_ = E, default = "1e-2", description = "Minimal distance of a warm start to the bounds and minimal magnitude of its bound multipliers.")]
+pub struct Phase1Solver<'a> {
+    lp: &'a LinearProgram,
+
+    phase1_status: Option<Status>,
+    infeasibility: Option<E>,
+}
+
+impl<'a> Phase1Solver<'a> {
+    pub fn new(lp: &'a LinearProgram, options: &SolverOptions) -> Self {
+        Self {
+            lp,
+            phase1_status: None,
+            infeasibility: None,
+            options: options.into(),
+        }
+    }
+
+    /// Status of the last phase-1 solve, `None` if phase 1 did not run.
+    pub fn get_phase1_status(&self) -> Option<Status> {
+        self.phase1_status
+    }
+
+    /// Total infeasibility `‖A x - b‖_1` of the last phase-1 solution, `None`
+    /// if phase 1 did not run.
+    pub fn get_infeasibility(&self) -> Option<E> {
+        self.infeasibility
+    }
+
+    /// Solves the program from `state`, running phase 1 as selected by the
+    /// `phase1` option. `state` holds the final iterate. If phase 1 finds the
+    /// program infeasible, `state` holds the phase-1 point and
+    /// [`Status::Infeasible`] is returned.
+    pub fn solve(
+        &mut self,
+        state: &mut SolverState,
+        hooks: &mut SolverHooks,
+    ) -> Result<Status, Problem> {
+        self.phase1_status = None;
+        self.infeasibility = None;
+
+        let lp = self.lp;
+        let scale = E::from(1.) + lp.get_rhs().norm_max();
+        let residual = lp.get_constraint_values(&state.x).norm_max() / scale;
+        let first = match self.options.phase1 {
+            Phase1Mode::Always => true,
+            Phase1Mode::Never => false,
+            Phase1Mode::Auto => {
+                !residual.is_finite() || residual > self.options.phase1_residual_threshold
+            }
+        };
+        if !first {
+            let mut attempt = state.clone();
+            let status = self.solve_from(&mut attempt, &mut hooks.clone())?;
+            if status == Status::Optimal || self.options.phase1 == Phase1Mode::Never {
+                *state = attempt;
+                return Ok(status);
+            }
+        }
+
+        let (status, phase1) = self.find_feasible(hooks)?;
+        self.phase1_status = Some(status);
+        if status != Status::Optimal {
+            *state = phase1;
+            return Ok(status);
+        }
+        let infeasibility = self.infeasibility.unwrap_or(E::INFINITY);
+        if infeasibility > self.options.phase1_tolerance * scale {
+            *state = phase1;
+            state.set_status(Status::Infeasible);
+            state.termination = Some(TerminationInfo::new(
+                Status::Infeasible,
+                "phase 1 found a positive minimal infeasibility",
+                infeasibility,
+            ));
+            return Ok(Status::Infeasible);
+        }
+
+        let (l, u) = (lp.get_lower_bounds(), lp.get_upper_bounds());
+        *state = SolverState::new_interior(l, u, lp.get_n_cons());
+        state.x = phase1.x;
+        state.push_to_interior(l, u, self.options.parametric_warm_start_shift);
+        self.solve_from(state, hooks)
+    }
+
+    /// Solves the original program from `state`.
+    fn solve_from(
+        &self,
+        state: &mut SolverState,
+        hooks: &mut SolverHooks,
+    ) -> Result<Status, Problem> {
+        self.lp
+            .solver_builder()
+            .with_options(self.options.root.clone())
+            .build()?
+            .solve(state, hooks)
+    }
+
+    /// Solves the elastic program and returns its status with its solution
+    /// restricted to `x`, the rows and the bounds of the original program.
+    fn find_feasible(&mut self, hooks: &mut SolverHooks) -> Result<(Status, SolverState), Problem> {
+        let elastic = elastic_program(self.lp)?;
+        let mut state = SolverState::new_interior(
+            elastic.get_lower_bounds(),
+            elastic.get_upper_bounds(),
+            elastic.get_n_cons(),
+        );
+        let status = elastic
+            .solver_builder()
+            .with_options(self.options.root.clone())
+            .build()?
+            .solve(&mut state, &mut hooks.clone())?;
+
+        // p and q are interior, so their sum overestimates the violation of the rows
+        let n = self.lp.get_n_vars();
+        let x = state.x.subrows(0, n).to_owned();
+        self.infeasibility = Some(self.lp.get_constraint_values(&x).norm_l1());
+        let mut phase1 = SolverState::new(
+            x,
+            state.y.clone(),
+            state.z_l.subrows(0, n).to_owned(),
+            state.z_u.subrows(0, n).to_owned(),
+        );
+        phase1.nit = state.nit;
+        phase1.set_status(status);
+        Ok((status, phase1))
+    }
+}
+
+/// The elastic program `min 1^T p + 1^T q` subject to `A x + p - q = b` and the
+/// bounds of `lp`, with the columns ordered `x, p, q`.
+fn elastic_program(lp: &LinearProgram) -> Result<LinearProgram, Problem> {
+    let (n, m) = lp.get_dims();
+    let a = lp.get_constraint_matrix();
+
+    let mut triplets = Vec::with_capacity(a.compute_nnz() + 2 * m);
+    for j in 0..n {
+        for (i, value) in a.row_idx_of_col(j).zip(a.val_of_col(j)) {
+            triplets.push(Triplet::new(to_index(i), to_index(j), *value));
+        }
+    }
+    for i in 0..m {
+        triplets.push(Triplet::new(to_index(i), to_index(n + i), E::from(1.)));
+        triplets.push(Triplet::new(to_index(i), to_index(n + m + i), E::from(-1.)));
+    }
+    let a = SparseColMat::try_new_from_triplets(m, n + 2 * m, &triplets)
+        .map_err(|_| "Invalid elastic constraint matrix".gloss())?;
+
+    let (l, u) = (lp.get_lower_bounds(), lp.get_upper_bounds());
+    Ok(LinearProgram::new(
+        Col::from_fn(n + 2 * m, |j| if j < n { 0. } else { 1. }),
+        a,
+        lp.get_rhs().clone(),
+        Col::from_fn(n + 2 * m, |j| if j < n { l[j] } else { 0. }),
+        Col::from_fn(n + 2 * m, |j| if j < n { u[j] } else { E::INFINITY }),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::col;
+    use rstest::rstest;
+
+    use super::*;
+
+    /// `min x_0 + 2 x_1` subject to `x_0 + x_1 = b` and `x_0 - x_1 = 0`, with
+    /// `0 <= x <= u`.
+    fn build_lp(b: E, u: E) -> LinearProgram {
+        let triplets = [
+            Triplet::new(0, 0, 1.),
+            Triplet::new(0, 1, 1.),
+            Triplet::new(1, 0, 1.),
+            Triplet::new(1, 1, -1.),
+        ];
+        LinearProgram::new(
+            col![1., 2.],
+            SparseColMat::try_new_from_triplets(2, 2, &triplets).unwrap(),
+            col![b, 0.],
+            col![0., 0.],
+            col![u, u],
+        )
+    }
+
+    fn options(mode: &str) -> SolverOptions {
+        let mut options = SolverOptions::new();
+        options
+            .set_option("phase1", Phase1Mode::from_str(mode).unwrap())
+            .unwrap();
+        options
+    }
+
+    #[rstest]
+    #[case("always", true)]
+    #[case("never", false)]
+    #[case("auto", false)]
+    fn test_phase1_modes(#[case] mode: &str, #[case] runs: bool) {
+        let lp = build_lp(2., E::INFINITY);
+        let mut solver = Phase1Solver::new(&lp, &options(mode));
+        let mut state = SolverState::new_interior(lp.get_lower_bounds(), lp.get_upper_bounds(), 2);
+        let status = solver
+            .solve(&mut state, &mut SolverHooks::silent())
+            .unwrap();
+        assert_eq!(status, Status::Optimal);
+        assert!((&state.x - col![1., 1.]).norm_max() < 1e-6);
+        assert_eq!(solver.get_phase1_status().is_some(), runs);
+        if runs {
+            assert!(solver.get_infeasibility().unwrap() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_enormous_residual() {
+        // The midpoint start is 1e9 away from the right-hand side
+        let lp = build_lp(2., 1e12);
+        let mut solver = Phase1Solver::new(&lp, &options("auto"));
+        let mut state = SolverState::new_interior(lp.get_lower_bounds(), lp.get_upper_bounds(), 2);
+        let status = solver
+            .solve(&mut state, &mut SolverHooks::silent())
+            .unwrap();
+        assert_eq!(status, Status::Optimal);
+        assert_eq!(solver.get_phase1_status(), Some(Status::Optimal));
+        assert!((&state.x - col![1., 1.]).norm_max() < 1e-6);
+    }
+
+    #[test]
+    fn test_infeasible() {
+        // x_0 + x_1 = 2 with x_0 = x_1 <= 0.5 misses the right-hand side by 1
+        let lp = build_lp(2., 0.5);
+        let mut solver = Phase1Solver::new(&lp, &options("always"));
+        let mut state = SolverState::new_interior(lp.get_lower_bounds(), lp.get_upper_bounds(), 2);
+        let status = solver
+            .solve(&mut state, &mut SolverHooks::silent())
+            .unwrap();
+        assert_eq!(status, Status::Infeasible);
+        assert_eq!(solver.get_phase1_status(), Some(Status::Optimal));
+        assert!((solver.get_infeasibility().unwrap() - 1.).abs() < 1e-6);
+        assert_eq!(
+            state.get_termination().unwrap().get_status(),
+            Status::Infeasible
+        );
+    }
+}
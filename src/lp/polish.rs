@@ -0,0 +1,315 @@
+//! Polishing of interior-point solutions on the predicted active set.
+//!
+//! An interior-point solution is accurate to the tolerance of the solver only,
+//! with every variable strictly inside its bounds. Near the optimum the active
+//! set is predicted reliably, see [`ActiveSetPrediction`], so that fixing the
+//! nonbasic columns `N` at their predicted bounds leaves the equality
+//! constrained program on the support `S`
+//!
+//! ```text
+//! min  c_S^T x_S
+//! s.t. A_S x_S = b - A_N x_N
+//! ```
+//!
+//! whose optimality conditions `A_S x_S = b - A_N x_N` and `A_S^T y = c_S` are
+//! solved through the regularized system
+//!
+//! ```text
+//! [ delta I   A_S^T    ] [ x_S ]   [ -c_S          ]
+//! [ A_S       -delta I ] [ -y  ] = [ b - A_N x_N   ]
+//! ```
+//!
+//! followed by `polish_refine_iterations` steps of iterative refinement against
+//! the unregularized system, as in OSQP. The bound multipliers of the nonbasic
+//! columns are their reduced costs `c_N - A_N^T y`. The polished point satisfies
+//! complementarity exactly and is accepted only if its KKT error, measured by
+//! [`check_solution`], is smaller than that of the interior-point solution, so a
+//! wrong prediction leaves the solution unchanged.
+
+use faer::{
+    Col, Mat,
+    sparse::{SparseColMat, Triplet},
+};
+use macros::{explicit_options, use_option};
+use problemo::{Problem, common::IntoCommonProblem};
+
+use crate::{
+    E, I, OptimizationProgram, SolverHooks, SolverOptions, SolverState, Status,
+    interface::basis::BasisStatus,
+    linalg::{cholesky::SimplicialSparseCholesky, solver::Solver},
+    lp::{LinearProgram, active_set::ActiveSetPrediction},
+    to_index,
+    verify::{CertificateReport, check_solution},
+};
+
+/// Outcome of [`Polisher::polish`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PolishResult {
+    accepted: bool,
+    n_support: usize,
+    error_before: E,
+    error_after: E,
+}
+
+impl PolishResult {
+    /// Whether the polished point replaced the solution.
+    pub fn is_accepted(&self) -> bool {
+        self.accepted
+    }
+
+    /// Number of columns predicted to lie strictly between their bounds.
+    pub fn get_n_support(&self) -> usize {
+        self.n_support
+    }
+
+    /// KKT error of the solution before polishing.
+    pub fn get_error_before(&self) -> E {
+        self.error_before
+    }
+
+    /// KKT error of the polished point, whether accepted or not.
+    pub fn get_error_after(&self) -> E {
+        self.error_after
+    }
+}
+
+/// Polishes the solutions of a linear program on their predicted active set.
+#[explicit_options(name = SolverOptions)]
+#[use_option(name = "polish_regularization", type_ = E, default = "1e-7", description = "Regularization of the reduced KKT system solved when polishing a solution.")]
+#[use_option(name = "polish_refine_iterations", type_ = usize, default = "3", description = "Iterative refinement steps against the unregularized reduced KKT system when polishing a solution.")]
+pub struct Polisher<'a> {
+    lp: &'a LinearProgram,
+    result: Option<PolishResult>,
+}
+
+impl<'a> Polisher<'a> {
+    pub fn new(lp: &'a LinearProgram, options: &SolverOptions) -> Self {
+        Self {
+            lp,
+            result: None,
+            options: options.into(),
+        }
+    }
+
+    /// Outcome of the last call to [`Polisher::polish`].
+    pub fn get_result(&self) -> Option<&PolishResult> {
+        self.result.as_ref()
+    }
+
+    /// Solves the program starting from `state` and polishes the solution if
+    /// it is optimal. The status of the solve is returned.
+    pub fn solve(
+        &mut self,
+        state: &mut SolverState,
+        hooks: &mut SolverHooks,
+    ) -> Result<Status, Problem> {
+        self.result = None;
+        let status = self
+            .lp
+            .solver_builder()
+            .with_options(self.options.root.clone())
+            .build()?
+            .solve(state, hooks)?;
+        if status == Status::Optimal {
+            self.polish(state)?;
+        }
+        Ok(status)
+    }
+
+    /// Replaces the primal-dual solution in `state` by its polished point if
+    /// that reduces the KKT error, and refreshes the residuals of `state`.
+    pub fn polish(&mut self, state: &mut SolverState) -> Result<PolishResult, Problem> {
+        let lp = self.lp;
+        let prediction = ActiveSetPrediction::from_state(lp, state);
+        let support = prediction.get_support();
+
+        let mut polished = state.clone();
+        let (x_s, y) = self.solve_reduced(&prediction, &support)?;
+        let reduced_cost = lp.get_objective() - lp.get_constraint_matrix().transpose() * &y;
+        let (l, u) = (lp.get_lower_bounds(), lp.get_upper_bounds());
+        for (j, status) in prediction.get_status().iter().enumerate() {
+            let (x, z) = (&mut polished.x[j], reduced_cost[j]);
+            let (z_l, z_u) = match status {
+                BasisStatus::Basic => (E::from(0.), E::from(0.)),
+                // The multiplier of a fixed column takes either sign
+                BasisStatus::AtLower if l[j] == u[j] && z < E::from(0.) => (E::from(0.), z),
+                BasisStatus::AtLower => (z, E::from(0.)),
+                _ => (E::from(0.), z),
+            };
+            match status {
+                BasisStatus::AtLower => *x = l[j],
+                BasisStatus::AtUpper => *x = u[j],
+                _ => {}
+            }
+            polished.z_l[j] = z_l;
+            polished.z_u[j] = z_u;
+        }
+        for (p, &j) in support.iter().enumerate() {
+            polished.x[j] = x_s[p];
+        }
+        polished.y = y;
+
+        let error_before = kkt_error(&check_solution(lp, state, E::from(0.)));
+        let error_after = kkt_error(&check_solution(lp, &polished, E::from(0.)));
+        let accepted = error_after < error_before;
+        if accepted {
+            *state = polished;
+            lp.update_residual(state);
+        }
+
+        let result = PolishResult {
+            accepted,
+            n_support: support.len(),
+            error_before,
+            error_after,
+        };
+        self.result = Some(result);
+        Ok(result)
+    }
+
+    /// Solves the optimality conditions of the program reduced to `support`,
+    /// with the other columns at their predicted bounds, for `x_S` and `y`.
+    fn solve_reduced(
+        &self,
+        prediction: &ActiveSetPrediction,
+        support: &[usize],
+    ) -> Result<(Col<E>, Col<E>), Problem> {
+        let lp = self.lp;
+        let a = lp.get_constraint_matrix();
+        let (k, m) = (support.len(), lp.get_n_cons());
+        let delta = self.options.polish_regularization;
+
+        let mut fixed = Col::<E>::zeros(lp.get_n_vars());
+        for (j, status) in prediction.get_status().iter().enumerate() {
+            match status {
+                BasisStatus::AtLower => fixed[j] = lp.get_lower_bounds()[j],
+                BasisStatus::AtUpper => fixed[j] = lp.get_upper_bounds()[j],
+                _ => {}
+            }
+        }
+        let mut rhs = Mat::<E>::zeros(k + m, 1);
+        for (p, &j) in support.iter().enumerate() {
+            rhs[(p, 0)] = -lp.get_objective()[j];
+        }
+        let r = lp.get_rhs() - a * &fixed;
+        for i in 0..m {
+            rhs[(k + i, 0)] = r[i];
+        }
+
+        let mut triplets = Vec::with_capacity(k + m + 2 * a.compute_nnz());
+        for (p, &j) in support.iter().enumerate() {
+            triplets.push(Triplet::new(to_index(p), to_index(p), delta));
+            for (i, value) in a.row_idx_of_col(j).zip(a.val_of_col(j)) {
+                triplets.push(Triplet::new(to_index(k + i), to_index(p), *value));
+                triplets.push(Triplet::new(to_index(p), to_index(k + i), *value));
+            }
+        }
+        for i in 0..m {
+            triplets.push(Triplet::new(to_index(k + i), to_index(k + i), -delta));
+        }
+        let kkt = SparseColMat::<I, E>::try_new_from_triplets(k + m, k + m, &triplets)
+            .map_err(|_| "Invalid reduced KKT system".gloss())?;
+
+        let mut solver = SimplicialSparseCholesky::new_with_options(&self.options.root);
+        solver.analyze(kkt.as_ref())?;
+        solver.factorize(kkt.as_ref())?;
+
+        // Iterative refinement against the system without regularization
+        let mut sol = solver.solve(rhs.as_ref())?;
+        for _ in 0..self.options.polish_refine_iterations {
+            let mut residual = rhs.clone();
+            for (p, &j) in support.iter().enumerate() {
+                for (i, value) in a.row_idx_of_col(j).zip(a.val_of_col(j)) {
+                    residual[(p, 0)] -= value * sol[(k + i, 0)];
+                    residual[(k + i, 0)] -= value * sol[(p, 0)];
+                }
+            }
+            sol += solver.solve(residual.as_ref())?;
+        }
+
+        let x_s = Col::from_fn(k, |p| sol[(p, 0)]);
+        let y = Col::from_fn(m, |i| -sol[(k + i, 0)]);
+        Ok((x_s, y))
+    }
+}
+
+/// Largest relative violation of the feasibility, sign and complementarity
+/// conditions in `report`.
+fn kkt_error(report: &CertificateReport) -> E {
+    [
+        report.primal_feasibility,
+        report.bound_feasibility,
+        report.dual_feasibility,
+        report.dual_sign,
+        report.complementarity,
+    ]
+    .iter()
+    .map(|check| check.relative)
+    .fold(E::from(0.), E::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::col;
+
+    use super::*;
+    use crate::lp::test::build_budget_lp;
+
+    #[test]
+    fn test_polish() {
+        // Interior point at distance 1e-5 of the optimum
+        let lp = build_budget_lp();
+        let mut state = SolverState::new(
+            col![0.5, 1.5 - 1e-5, 1e-5],
+            col![-1. + 1e-5],
+            col![1e-5, 1e-5, 1.],
+            col![-1e-5, -1., 0.],
+        );
+        let mut polisher = Polisher::new(lp, &SolverOptions::new());
+        let result = polisher.polish(&mut state).unwrap();
+        assert!(result.is_accepted());
+        assert_eq!(result.get_n_support(), 1);
+        assert!(result.get_error_before() > 1e-6);
+        assert!(result.get_error_after() < 1e-12);
+        assert_eq!(polisher.get_result(), Some(&result));
+
+        assert!((&state.x - col![0.5, 1.5, 0.]).norm_max() < 1e-12);
+        assert!((&state.y - col![-1.]).norm_max() < 1e-12);
+        assert!((&state.z_l - col![0., 0., 1.]).norm_max() < 1e-12);
+        assert!((&state.z_u - col![0., -1., 0.]).norm_max() < 1e-12);
+        assert!(state.get_primal_feasibility().norm_max() < 1e-12);
+        assert!(state.get_dual_feasibility().norm_max() < 1e-12);
+    }
+
+    #[test]
+    fn test_polish_rejected() {
+        // The exact optimum cannot be improved, so it is kept unchanged
+        let lp = build_budget_lp();
+        let mut state = SolverState::new(
+            col![0.5, 1.5, 0.],
+            col![-1.],
+            col![0., 0., 1.],
+            col![0., -1., 0.],
+        );
+        let original = state.clone();
+        let mut polisher = Polisher::new(lp, &SolverOptions::new());
+        let result = polisher.polish(&mut state).unwrap();
+        assert!(!result.is_accepted());
+        assert_eq!(result.get_error_before(), 0.);
+        assert_eq!(state.x, original.x);
+        assert_eq!(state.y, original.y);
+    }
+
+    #[test]
+    fn test_solve() {
+        let lp = build_budget_lp();
+        let mut polisher = Polisher::new(lp, &SolverOptions::new());
+        let mut state = SolverState::new_interior(lp.get_lower_bounds(), lp.get_upper_bounds(), 1);
+        let status = polisher
+            .solve(&mut state, &mut SolverHooks::silent())
+            .unwrap();
+        assert_eq!(status, Status::Optimal);
+        assert!(polisher.get_result().is_some());
+        assert!((&state.x - col![0.5, 1.5, 0.]).norm_max() < 1e-9);
+    }
+}
@@ -0,0 +1,590 @@
+//! Aggregation of parallel rows and duplicate columns.
+//!
+//! Rows are compared in the standard form `A x = b`, where a row may carry a
+//! slack: a zero-cost column whose only nonzero is in the row. Two rows are
+//! **parallel** if their entries outside the slack are proportional,
+//! `a_k = r a_i`. Row `i` then restricts `t = a_i^T x` to an interval, through
+//! its right-hand side and the bounds of its slack, and row `k` to another one.
+//! Row `k` and its slack are removed and the intersection of both intervals is
+//! imposed on row `i` by tightening the bounds of its slack. An equality row is
+//! preferred as the kept row, since it is not tightened.
+//!
+//! Two columns are **duplicates** if `A_k = r A_j` and `c_k = r c_j`. They
+//! enter the program only through `x_j + r x_k`, so column `k` is removed and
+//! the bounds of `x_j` become those of the sum.
+//!
+//! Every pass of reductions is recorded in a [`PostsolveStep`], which maps a
+//! solution of the reduced program back to the program before the pass:
+//!
+//! - The slack of a removed row takes the value that satisfies the row. The
+//!   multiplier of a slack bound that was tightened by a removed row is moved
+//!   to that row and its slack, which keeps the stationarity `c = A^T y + z`
+//!   and the complementarity of the original program.
+//! - A merged column is split at the bounds of its parts, and the multipliers
+//!   of the duplicate are those of the merged column scaled by `r`.
+
+use std::collections::HashMap;
+
+use faer::{
+    Col,
+    sparse::{SparseColMat, Triplet},
+};
+use problemo::{Problem, common::IntoCommonProblem};
+
+use crate::{E, SolverState, lp::LinearProgram, to_index};
+
+/// Relative tolerance of the comparison of proportional coefficients.
+const PROPORTIONALITY_TOLERANCE: E = 1e-12;
+
+/// Relative tolerance of the consistency check of parallel rows.
+const FEASIBILITY_TOLERANCE: E = 1e-9;
+
+/// Reductions of one pass, undone by [`PostsolveStep::undo`].
+#[derive(Debug, Clone)]
+pub(crate) struct PostsolveStep {
+    n_rows: usize,
+    n_cols: usize,
+    removed_rows: Vec<bool>,
+    removed_cols: Vec<bool>,
+    reductions: Vec<Reduction>,
+}
+
+#[derive(Debug, Clone)]
+enum Reduction {
+    /// Row `row` with entries `r` times those of row `kept` outside the slacks.
+    ParallelRow {
+        row: usize,
+        kept: usize,
+        ratio: E,
+        rhs: E,
+        entries: Vec<(usize, E)>,
+        slack: Option<(usize, E)>,
+        kept_slack: Option<(usize, E)>,
+        tightened_lower: bool,
+        tightened_upper: bool,
+    },
+    /// Column `col` equal to `r` times column `kept`, merged into `x_kept + r x_col`.
+    DuplicateColumn {
+        col: usize,
+        kept: usize,
+        ratio: E,
+        kept_bounds: (E, E),
+        col_bounds: (E, E),
+    },
+}
+
+impl PostsolveStep {
+    /// Maps a state of the program after the pass to a state of the program
+    /// before it.
+    pub(crate) fn undo(&self, state: &SolverState) -> SolverState {
+        let expand = |values: &Col<E>, n: usize, removed: &[bool]| {
+            let mut expanded = Col::zeros(n);
+            let mut k = 0;
+            for i in 0..n {
+                if !removed[i] {
+                    expanded[i] = values[k];
+                    k += 1;
+                }
+            }
+            expanded
+        };
+        let mut x = expand(&state.x, self.n_cols, &self.removed_cols);
+        let mut y = expand(&state.y, self.n_rows, &self.removed_rows);
+        let mut z_l = expand(&state.z_l, self.n_cols, &self.removed_cols);
+        let mut z_u = expand(&state.z_u, self.n_cols, &self.removed_cols);
+
+        for reduction in self.reductions.iter().rev() {
+            match *reduction {
+                Reduction::ParallelRow {
+                    row,
+                    kept,
+                    ratio,
+                    rhs,
+                    ref entries,
+                    slack,
+                    kept_slack,
+                    tightened_lower,
+                    tightened_upper,
+                } => {
+                    let Some((s, sigma)) = slack else {
+                        continue;
+                    };
+                    let activity = entries.iter().map(|&(j, a)| a * x[j]).sum::<E>();
+                    x[s] = (rhs - activity) / sigma;
+
+                    let Some((s_kept, sigma_kept)) = kept_slack else {
+                        continue;
+                    };
+                    let mut moved = E::from(0.);
+                    if tightened_lower {
+                        moved += std::mem::replace(&mut z_l[s_kept], E::from(0.));
+                    }
+                    if tightened_upper {
+                        moved += std::mem::replace(&mut z_u[s_kept], E::from(0.));
+                    }
+                    y[kept] += moved / sigma_kept;
+                    y[row] = -moved / (sigma_kept * ratio);
+                    let z = moved * sigma / (sigma_kept * ratio);
+                    if z > E::from(0.) {
+                        z_l[s] = z;
+                    } else {
+                        z_u[s] = z;
+                    }
+                }
+                Reduction::DuplicateColumn {
+                    col,
+                    kept,
+                    ratio,
+                    kept_bounds: (l_kept, u_kept),
+                    col_bounds: (l_col, u_col),
+                } => {
+                    let merged = x[kept];
+                    let reference = E::from(0.).max(l_col).min(u_col);
+                    x[kept] = (merged - ratio * reference).max(l_kept).min(u_kept);
+                    x[col] = ((merged - x[kept]) / ratio).max(l_col).min(u_col);
+
+                    if ratio > E::from(0.) {
+                        (z_l[col], z_u[col]) = (ratio * z_l[kept], ratio * z_u[kept]);
+                    } else {
+                        (z_l[col], z_u[col]) = (ratio * z_u[kept], ratio * z_l[kept]);
+                    }
+                }
+            }
+        }
+
+        SolverState::new(x, y, z_l, z_u)
+    }
+}
+
+/// Removes the rows of `lp` that are parallel to another row.
+pub(crate) fn aggregate_parallel_rows(
+    lp: &LinearProgram,
+) -> Result<(LinearProgram, PostsolveStep), Problem> {
+    let (m, n) = (lp.get_n_cons(), lp.get_n_vars());
+    let (b, c) = (&lp.b, &lp.c);
+    let (mut l, mut u) = (lp.l.clone(), lp.u.clone());
+
+    let a_csr = lp.get_constraint_matrix_csr();
+    let is_slack = |j: usize| lp.A.row_idx_of_col_raw(j).len() == 1 && c[j] == E::from(0.);
+
+    // Entries outside the slack and the slack of every row
+    let mut rows = Vec::with_capacity(m);
+    for i in 0..m {
+        let mut slack = None;
+        let mut entries = Vec::new();
+        for (j, &a_ij) in a_csr.col_idx_of_row(i).zip(a_csr.val_of_row(i)) {
+            if a_ij == E::from(0.) {
+                continue;
+            }
+            if slack.is_none() && is_slack(j) {
+                slack = Some((j, a_ij));
+            } else {
+                entries.push((j, a_ij));
+            }
+        }
+        entries.sort_by_key(|&(j, _)| j);
+        rows.push((entries, slack));
+    }
+
+    // Group the rows by pattern, then by proportional values
+    let mut patterns = HashMap::<Vec<usize>, Vec<usize>>::new();
+    for (i, (entries, _)) in rows.iter().enumerate() {
+        if !entries.is_empty() {
+            let pattern = entries.iter().map(|&(j, _)| j).collect();
+            patterns.entry(pattern).or_default().push(i);
+        }
+    }
+    let mut groups = patterns
+        .into_values()
+        .flat_map(|members| group_proportional(&members, |i| &rows[i].0))
+        .filter(|group| group.len() > 1)
+        .collect::<Vec<_>>();
+    groups.sort();
+
+    let mut removed_rows = vec![false; m];
+    let mut removed_cols = vec![false; n];
+    let mut reductions = Vec::new();
+    for group in groups {
+        // Keep an equality row if there is one
+        let kept = *group
+            .iter()
+            .find(|&&i| rows[i].1.is_none())
+            .unwrap_or(&group[0]);
+        let kept_slack = rows[kept].1;
+        for &row in group.iter().filter(|&&i| i != kept) {
+            let (entries, slack) = &rows[row];
+            let ratio = entries[0].1 / rows[kept].0[0].1;
+
+            // Ranges of t = a_kept^T x allowed by both rows
+            let (kept_lower, kept_upper) = row_range(b[kept], kept_slack, &l, &u);
+            let (lower, upper) = row_range(b[row], *slack, &l, &u);
+            let (lower, upper) = if ratio > E::from(0.) {
+                (lower / ratio, upper / ratio)
+            } else {
+                (upper / ratio, lower / ratio)
+            };
+            let (mut merged_lower, mut merged_upper) =
+                (kept_lower.max(lower), kept_upper.min(upper));
+            let scale = E::from(1.) + merged_lower.abs().min(merged_upper.abs());
+            if merged_lower > merged_upper + FEASIBILITY_TOLERANCE * scale {
+                return Err(format!(
+                    "Parallel rows {kept} and {row} are inconsistent: the program is infeasible"
+                )
+                .gloss());
+            }
+            if merged_lower > merged_upper {
+                let middle = (merged_lower + merged_upper) / E::from(2.);
+                (merged_lower, merged_upper) = (middle, middle);
+            }
+
+            // Impose the tighter sides on the slack of the kept row
+            let (mut tightened_lower, mut tightened_upper) = (false, false);
+            if let Some((s, sigma)) = kept_slack {
+                let (t_lower, t_upper) = (merged_lower > kept_lower, merged_upper < kept_upper);
+                let (s_lower, s_upper) = if sigma > E::from(0.) {
+                    (t_upper, t_lower)
+                } else {
+                    (t_lower, t_upper)
+                };
+                let (from_lower, from_upper) = (
+                    (b[kept] - merged_lower) / sigma,
+                    (b[kept] - merged_upper) / sigma,
+                );
+                if s_lower {
+                    l[s] = from_lower.min(from_upper);
+                    tightened_lower = true;
+                }
+                if s_upper {
+                    u[s] = from_lower.max(from_upper);
+                    tightened_upper = true;
+                }
+            }
+
+            removed_rows[row] = true;
+            if let Some((s, _)) = *slack {
+                removed_cols[s] = true;
+            }
+            reductions.push(Reduction::ParallelRow {
+                row,
+                kept,
+                ratio,
+                rhs: b[row],
+                entries: entries.clone(),
+                slack: *slack,
+                kept_slack,
+                tightened_lower,
+                tightened_upper,
+            });
+        }
+    }
+
+    let reduced = LinearProgram::new(lp.c.clone(), lp.A.clone(), b.clone(), l, u);
+    let step = PostsolveStep {
+        n_rows: m,
+        n_cols: n,
+        removed_rows,
+        removed_cols,
+        reductions,
+    };
+    Ok((compact(&reduced, &step), step))
+}
+
+/// Merges the columns of `lp` that duplicate another column.
+pub(crate) fn merge_duplicate_columns(lp: &LinearProgram) -> (LinearProgram, PostsolveStep) {
+    let (m, n) = (lp.get_n_cons(), lp.get_n_vars());
+    let c = &lp.c;
+    let (mut l, mut u) = (lp.l.clone(), lp.u.clone());
+
+    let columns = (0..n)
+        .map(|j| {
+            lp.A.row_idx_of_col(j)
+                .zip(lp.A.val_of_col(j).iter().copied())
+                .filter(|&(_, a)| a != E::from(0.))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut patterns = HashMap::<Vec<usize>, Vec<usize>>::new();
+    for (j, entries) in columns.iter().enumerate() {
+        if !entries.is_empty() {
+            let pattern = entries.iter().map(|&(i, _)| i).collect();
+            patterns.entry(pattern).or_default().push(j);
+        }
+    }
+    let mut groups = patterns
+        .into_values()
+        .flat_map(|members| group_proportional(&members, |j| &columns[j]))
+        .collect::<Vec<_>>();
+    groups.sort();
+
+    let mut removed_cols = vec![false; n];
+    let mut reductions = Vec::new();
+    for group in groups {
+        let kept = group[0];
+        for &col in &group[1..] {
+            let ratio = columns[col][0].1 / columns[kept][0].1;
+            if !is_close(c[col], ratio * c[kept]) {
+                continue;
+            }
+
+            let (kept_bounds, col_bounds) = ((l[kept], u[kept]), (l[col], u[col]));
+            let (low, high) = if ratio > E::from(0.) {
+                (ratio * l[col], ratio * u[col])
+            } else {
+                (ratio * u[col], ratio * l[col])
+            };
+            l[kept] += low;
+            u[kept] += high;
+
+            removed_cols[col] = true;
+            reductions.push(Reduction::DuplicateColumn {
+                col,
+                kept,
+                ratio,
+                kept_bounds,
+                col_bounds,
+            });
+        }
+    }
+
+    let reduced = LinearProgram::new(c.clone(), lp.A.clone(), lp.b.clone(), l, u);
+    let step = PostsolveStep {
+        n_rows: m,
+        n_cols: n,
+        removed_rows: vec![false; m],
+        removed_cols,
+        reductions,
+    };
+    (compact(&reduced, &step), step)
+}
+
+impl PostsolveStep {
+    pub(crate) fn get_n_removed_rows(&self) -> usize {
+        self.removed_rows.iter().filter(|&&removed| removed).count()
+    }
+
+    pub(crate) fn get_n_removed_cols(&self) -> usize {
+        self.removed_cols.iter().filter(|&&removed| removed).count()
+    }
+}
+
+/// Splits `members`, which share a pattern, into groups of proportional
+/// vectors, each ordered by index.
+fn group_proportional<'a>(
+    members: &[usize],
+    entries: impl Fn(usize) -> &'a Vec<(usize, E)>,
+) -> Vec<Vec<usize>> {
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for &k in members {
+        let values = entries(k);
+        let group = groups.iter_mut().find(|group| {
+            let first = entries(group[0]);
+            let ratio = values[0].1 / first[0].1;
+            values
+                .iter()
+                .zip(first)
+                .all(|(&(_, a), &(_, f))| is_close(a, ratio * f))
+        });
+        match group {
+            Some(group) => group.push(k),
+            None => groups.push(vec![k]),
+        }
+    }
+    groups
+}
+
+fn is_close(a: E, b: E) -> bool {
+    (a - b).abs() <= PROPORTIONALITY_TOLERANCE * E::from(1.).max(a.abs()).max(b.abs())
+}
+
+/// Range of the activity `t` of a row `t + sigma s = b` over the bounds of its slack `s`.
+fn row_range(b: E, slack: Option<(usize, E)>, l: &Col<E>, u: &Col<E>) -> (E, E) {
+    match slack {
+        None => (b, b),
+        Some((s, sigma)) => {
+            let (from_lower, from_upper) = (b - sigma * l[s], b - sigma * u[s]);
+            (from_lower.min(from_upper), from_lower.max(from_upper))
+        }
+    }
+}
+
+/// The program without the rows and columns removed by `step`.
+fn compact(lp: &LinearProgram, step: &PostsolveStep) -> LinearProgram {
+    let mut row_map = vec![None; step.n_rows];
+    let mut m = 0;
+    for (i, &removed) in step.removed_rows.iter().enumerate() {
+        if !removed {
+            row_map[i] = Some(m);
+            m += 1;
+        }
+    }
+    let cols = (0..step.n_cols)
+        .filter(|&j| !step.removed_cols[j])
+        .collect::<Vec<_>>();
+
+    let mut triplets = Vec::with_capacity(lp.A.compute_nnz());
+    for (new, &j) in cols.iter().enumerate() {
+        for (i, &a) in lp.A.row_idx_of_col(j).zip(lp.A.val_of_col(j)) {
+            if let Some(i) = row_map[i] {
+                triplets.push(Triplet::new(to_index(i), to_index(new), a));
+            }
+        }
+    }
+    let select = |values: &Col<E>| Col::from_fn(cols.len(), |k| values[cols[k]]);
+
+    LinearProgram::new(
+        select(&lp.c),
+        SparseColMat::try_new_from_triplets(m, cols.len(), &triplets).unwrap(),
+        Col::from_fn(m, {
+            let rows = (0..step.n_rows)
+                .filter(|&i| !step.removed_rows[i])
+                .collect::<Vec<_>>();
+            move |k| lp.b[rows[k]]
+        }),
+        select(&lp.l),
+        select(&lp.u),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::col;
+
+    use super::*;
+    use crate::{
+        OptimizationProgram, SolverHooks, SolverOptions, Status, callback::NoOpCallback,
+        terminators::ComplementarityTerminator,
+    };
+
+    fn solve(lp: &LinearProgram) -> SolverState {
+        let options = SolverOptions::new();
+        let mut state = SolverState::new_interior(&lp.l, &lp.u, lp.get_n_cons());
+        let mut hooks = SolverHooks {
+            callback: Box::new(NoOpCallback::new()),
+            terminator: Box::new(ComplementarityTerminator::new(&options)),
+        };
+        let status = lp
+            .solver_builder()
+            .build()
+            .unwrap()
+            .solve(&mut state, &mut hooks)
+            .unwrap();
+        assert_eq!(status, Status::Optimal);
+        state
+    }
+
+    /// Checks primal feasibility and stationarity of `state` for `lp`.
+    fn assert_kkt(lp: &LinearProgram, state: &mut SolverState) {
+        lp.update_residual(state);
+        let (l, u) = (&lp.l, &lp.u);
+        for j in 0..lp.get_n_vars() {
+            assert!(state.x[j] >= l[j] - 1e-6 && state.x[j] <= u[j] + 1e-6);
+            assert!(state.z_l[j] >= -1e-9 && state.z_u[j] <= 1e-9);
+        }
+        assert!(state.primal_feasibility.norm_max() < 1e-6);
+        assert!(state.dual_feasibility.norm_max() < 1e-6);
+    }
+
+    /// Rows:
+    /// - `x0 + 2 x1 + s0 = 4`
+    /// - `2 x0 + 4 x1 + s1 = 6`, parallel and tighter: `x0 + 2 x1 <= 3`
+    /// - `-x0 - 2 x1 + s2 = -1`, parallel with the opposite sign: `x0 + 2 x1 >= 1`
+    /// - `x0 + x2 = 2`
+    fn build_parallel_lp() -> LinearProgram {
+        let triplets = [
+            Triplet::new(0, 0, 1.),
+            Triplet::new(0, 1, 2.),
+            Triplet::new(0, 3, 1.),
+            Triplet::new(1, 0, 2.),
+            Triplet::new(1, 1, 4.),
+            Triplet::new(1, 4, 1.),
+            Triplet::new(2, 0, -1.),
+            Triplet::new(2, 1, -2.),
+            Triplet::new(2, 5, 1.),
+            Triplet::new(3, 0, 1.),
+            Triplet::new(3, 2, 1.),
+        ];
+        LinearProgram::new(
+            col![-1., -1., 0.5, 0., 0., 0.],
+            SparseColMat::try_new_from_triplets(4, 6, &triplets).unwrap(),
+            col![4., 6., -1., 2.],
+            Col::zeros(6),
+            Col::from_fn(6, |_| E::INFINITY),
+        )
+    }
+
+    #[test]
+    fn test_parallel_rows() {
+        let lp = build_parallel_lp();
+        let (reduced, step) = aggregate_parallel_rows(&lp).unwrap();
+        assert_eq!(reduced.get_dims(), (4, 2));
+        assert_eq!(step.get_n_removed_rows(), 2);
+        assert_eq!(
+            (reduced.get_lower_bounds()[3], reduced.get_upper_bounds()[3]),
+            (1., 3.)
+        );
+
+        let mut state = step.undo(&solve(&reduced));
+        assert_kkt(&lp, &mut state);
+        let expected = solve(&lp);
+        assert!(
+            (lp.get_objective_value(&state.x) - lp.get_objective_value(&expected.x)).abs() < 1e-6
+        );
+        // The bound x0 + 2 x1 <= 3 of the removed row is active
+        assert!(state.y[1] < -1e-3 && state.y[0].abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_inconsistent_rows() {
+        // x0 + x1 = 1 and 2 x0 + 2 x1 = 3
+        let triplets = [
+            Triplet::new(0, 0, 1.),
+            Triplet::new(0, 1, 1.),
+            Triplet::new(1, 0, 2.),
+            Triplet::new(1, 1, 2.),
+        ];
+        let lp = LinearProgram::new(
+            col![1., 1.],
+            SparseColMat::try_new_from_triplets(2, 2, &triplets).unwrap(),
+            col![1., 3.],
+            Col::zeros(2),
+            Col::from_fn(2, |_| E::INFINITY),
+        );
+        assert!(aggregate_parallel_rows(&lp).is_err());
+    }
+
+    #[test]
+    fn test_duplicate_columns() {
+        // x0 + 2 x1 - x2 + x3 = 1 and x0 + 2 x1 + 2 x3 = 2, where x1 duplicates x0
+        let triplets = [
+            Triplet::new(0, 0, 1.),
+            Triplet::new(0, 1, 2.),
+            Triplet::new(0, 2, -1.),
+            Triplet::new(0, 3, 1.),
+            Triplet::new(1, 0, 1.),
+            Triplet::new(1, 1, 2.),
+            Triplet::new(1, 3, 2.),
+        ];
+        let lp = LinearProgram::new(
+            col![-1., -2., 1., 0.],
+            SparseColMat::try_new_from_triplets(2, 4, &triplets).unwrap(),
+            col![1., 2.],
+            Col::zeros(4),
+            col![0.5, 1., E::INFINITY, E::INFINITY],
+        );
+        let (reduced, step) = merge_duplicate_columns(&lp);
+        assert_eq!(reduced.get_dims(), (3, 2));
+        assert_eq!(step.get_n_removed_cols(), 1);
+        assert_eq!(
+            (reduced.get_lower_bounds()[0], reduced.get_upper_bounds()[0]),
+            (0., 2.5)
+        );
+
+        let mut state = step.undo(&solve(&reduced));
+        assert_kkt(&lp, &mut state);
+        let expected = solve(&lp);
+        assert!(
+            (lp.get_objective_value(&state.x) - lp.get_objective_value(&expected.x)).abs() < 1e-6
+        );
+    }
+}
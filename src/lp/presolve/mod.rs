@@ -0,0 +1,171 @@
+//! Presolve reductions for linear programs.
+//!
+//! A [`Presolver`] owns a copy of the linear program and applies reductions to
+//! it in place. The reduced program is retrieved with
+//! [`Presolver::get_program`] and passed to any LP solver. Every reduction is
+//! recorded on a transformation stack, see [`postsolve`], and
+//! [`Presolver::postsolve`] maps a primal-dual solution of the reduced program
+//! back to the original one. [`Presolver::check_postsolve`] verifies the
+//! recovered solution, including its duals, against the original program.
+
+use faer::Col;
+use problemo::Problem;
+
+use crate::{
+    E, SolverOptions, SolverState,
+    lp::LinearProgram,
+    verify::{CertificateReport, check_solution},
+};
+
+mod aggregation;
+pub mod obbt;
+mod postsolve;
+pub mod structure;
+
+pub use obbt::BoundTightening;
+use postsolve::{ImpliedBound, Transformation};
+pub use structure::{KnapsackConstraint, RowStructure, VubConstraint};
+
+pub struct Presolver {
+    original: LinearProgram,
+    lp: LinearProgram,
+    structure: RowStructure,
+    steps: Vec<Transformation>,
+}
+
+impl Presolver {
+    pub fn new(lp: LinearProgram) -> Self {
+        Self {
+            original: lp.clone(),
+            lp,
+            structure: RowStructure::default(),
+            steps: Vec::new(),
+        }
+    }
+
+    /// The program before any reduction.
+    pub fn get_original_program(&self) -> &LinearProgram {
+        &self.original
+    }
+
+    pub fn get_program(&self) -> &LinearProgram {
+        &self.lp
+    }
+
+    pub fn into_program(self) -> LinearProgram {
+        self.lp
+    }
+
+    /// Returns the row structure found by the last call to [`Presolver::detect_structure`].
+    pub fn get_structure(&self) -> &RowStructure {
+        &self.structure
+    }
+
+    /// Detects the GUB, knapsack, and VUB rows of the current program.
+    pub fn detect_structure(&mut self) -> &RowStructure {
+        self.structure = RowStructure::detect(&self.lp);
+        &self.structure
+    }
+
+    /// Replaces infinite upper bounds by those implied by the detected row structure
+    /// and returns the number of bounds that became finite.
+    ///
+    /// Only infinite bounds are replaced, so that slacks and other unbounded
+    /// variables gain a finite bound without otherwise changing the feasible
+    /// region. The implied bounds are redundant, hence the primal solution set
+    /// is unchanged, and postsolve moves their multipliers to the implying rows.
+    pub fn tighten_bounds(&mut self) -> usize {
+        let (implied, rows) = self.structure.implied_upper_bounds_with_rows(&self.lp);
+        let a_csr = self.lp.get_constraint_matrix_csr();
+
+        let bounds = rows
+            .into_iter()
+            .filter(|&(j, _)| self.lp.u[j] == E::INFINITY && implied[j].is_finite())
+            .map(|(col, row)| ImpliedBound {
+                col,
+                row,
+                entries: a_csr
+                    .col_idx_of_row(row)
+                    .zip(a_csr.val_of_row(row).iter().copied())
+                    .filter(|&(_, a)| a != E::from(0.))
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+        for bound in &bounds {
+            self.lp.u[bound.col] = implied[bound.col];
+        }
+        let n_tightened = bounds.len();
+        if n_tightened > 0 {
+            self.steps.push(Transformation::ImpliedBounds(bounds));
+        }
+        n_tightened
+    }
+
+    /// Tightens the bounds of `variables` to the range they attain over the
+    /// current program, see [`obbt`], and returns the number of bounds that
+    /// were tightened.
+    pub fn tighten_bounds_by_optimization(
+        &mut self,
+        variables: &[usize],
+        options: &SolverOptions,
+    ) -> Result<usize, Problem> {
+        let (l, u) = (self.lp.l.clone(), self.lp.u.clone());
+        let n_tightened = BoundTightening::new(options).tighten(&mut self.lp, variables)?;
+        let changed = |before: &Col<E>, after: &Col<E>| {
+            (0..before.nrows())
+                .filter(|&j| before[j] != after[j])
+                .collect::<Vec<_>>()
+        };
+        let (lower, upper) = (changed(&l, &self.lp.l), changed(&u, &self.lp.u));
+        if !lower.is_empty() || !upper.is_empty() {
+            self.steps
+                .push(Transformation::OptimizedBounds { lower, upper });
+        }
+        Ok(n_tightened)
+    }
+
+    /// Removes the rows that are parallel to another row, tightening the kept
+    /// row to the intersection of both, and returns the number of removed rows.
+    /// Fails if two parallel rows are inconsistent.
+    ///
+    /// Row and column indices change, so the row structure is detected again
+    /// if needed.
+    pub fn aggregate_parallel_rows(&mut self) -> Result<usize, Problem> {
+        let (lp, step) = aggregation::aggregate_parallel_rows(&self.lp)?;
+        let n_removed = step.get_n_removed_rows();
+        self.lp = lp;
+        self.steps.push(Transformation::Aggregation(step));
+        Ok(n_removed)
+    }
+
+    /// Merges the columns that are multiples of another column, with the same
+    /// multiple of its cost, and returns the number of removed columns.
+    ///
+    /// Column indices change, so the row structure is detected again if
+    /// needed.
+    pub fn merge_duplicate_columns(&mut self) -> usize {
+        let (lp, step) = aggregation::merge_duplicate_columns(&self.lp);
+        let n_removed = step.get_n_removed_cols();
+        self.lp = lp;
+        self.steps.push(Transformation::Aggregation(step));
+        n_removed
+    }
+
+    /// Maps a primal-dual solution of the reduced program to one of the
+    /// original program, undoing the recorded reductions in reverse order. The
+    /// residuals of the returned state are not evaluated.
+    pub fn postsolve(&self, state: &SolverState) -> SolverState {
+        self.steps
+            .iter()
+            .rev()
+            .fold(state.clone(), |state, step| step.undo(&state))
+    }
+
+    /// Verifies a solution returned by [`Presolver::postsolve`] against the
+    /// KKT conditions of the original program, see [`check_solution`]. A
+    /// failed complementarity or dual sign check points to multipliers that
+    /// postsolve could not recover.
+    pub fn check_postsolve(&self, state: &SolverState, tol: E) -> CertificateReport {
+        check_solution(&self.original, state, tol)
+    }
+}
@@ -0,0 +1,225 @@
+//! Optimization-based bound tightening (OBBT).
+//!
+//! The bounds of a variable `x_j` are tightened to the range it attains over
+//! the feasible set,
+//!
+//! ```text
+//! min / max  x_j
+//! s.t.       A x = b
+//!            l <= x <= u
+//! ```
+//!
+//! Each of the `2k` solves for `k` selected variables is warm started from the
+//! solution of the previous one, pushed back into the interior by
+//! `parametric_warm_start_shift`. A warm start that does not reach optimality is
+//! retried from the default starting point. Tightened bounds are used by the
+//! later solves.
+//!
+//! Interior-point solutions are only optimal to within the tolerance, so the
+//! new bound is the weaker of the primal and dual objective values, relaxed by
+//! `obbt_safety_margin` relative to its magnitude. Solves that do not reach
+//! optimality, such as those of unbounded directions, leave the bound as is.
+
+use std::sync::OnceLock;
+
+use faer::Col;
+use macros::{explicit_options, use_option};
+use problemo::{Problem, common::IntoCommonProblem};
+
+use crate::{
+    E, OptimizationProgram, SolverHooks, SolverOptions, SolverState, Status,
+    callback::NoOpCallback,
+    lp::{LinearProgram, parametric::warm_start},
+    terminators::ComplementarityTerminator,
+};
+
+/// Tightens the bounds of selected variables of a linear program in place.
+#[explicit_options(name = SolverOptions)]
+#[use_option(name = "parametric_warm_start_shift", type_ = E, default = "1e-2", description = "Minimal distance of a warm start to the bounds and minimal magnitude of its bound multipliers.")]
+#[use_option(name = "obbt_safety_margin", type_ = E, default = "1e-6", description = "Relative margin by which bounds found by optimization-based bound tightening are relaxed.")]
+pub struct BoundTightening {
+    n_solves: usize,
+    n_warm_starts: usize,
+}
+
+impl BoundTightening {
+    pub fn new(options: &SolverOptions) -> Self {
+        Self {
+            n_solves: 0,
+            n_warm_starts: 0,
+            options: options.into(),
+        }
+    }
+
+    /// Number of linear programs solved so far.
+    pub fn get_n_solves(&self) -> usize {
+        self.n_solves
+    }
+
+    /// Number of solves that reached optimality from a warm start.
+    pub fn get_n_warm_starts(&self) -> usize {
+        self.n_warm_starts
+    }
+
+    /// Minimizes and maximizes each of `variables` over the feasible set of
+    /// `lp` and tightens its bounds to the results. Returns the number of
+    /// bounds that were tightened.
+    ///
+    /// The objective of `lp` is restored before returning. Fails if a variable
+    /// is out of range or the program is infeasible.
+    pub fn tighten(
+        &mut self,
+        lp: &mut LinearProgram,
+        variables: &[usize],
+    ) -> Result<usize, Problem> {
+        let n = lp.get_n_vars();
+        if let Some(&j) = variables.iter().find(|&&j| j >= n) {
+            return Err(format!("Variable {j} is out of range for {n} variables").gloss());
+        }
+
+        let objective = std::mem::replace(&mut lp.c, Col::zeros(n));
+        let result = self.tighten_all(lp, variables);
+        lp.c = objective;
+        lp.norms = OnceLock::new();
+        result
+    }
+
+    fn tighten_all(
+        &mut self,
+        lp: &mut LinearProgram,
+        variables: &[usize],
+    ) -> Result<usize, Problem> {
+        let margin = self.options.obbt_safety_margin;
+        let mut n_tightened = 0;
+        let mut previous: Option<SolverState> = None;
+        for &j in variables {
+            for sign in [E::from(1.), E::from(-1.)] {
+                lp.c[j] = sign;
+                lp.norms = OnceLock::new();
+                let state = self.solve(lp, previous.as_ref())?;
+                lp.c[j] = E::from(0.);
+                let Some(state) = state else {
+                    continue;
+                };
+
+                // Weakest of the objective values, as a bound on min sign * x_j
+                let value = state
+                    .get_primal_objective()
+                    .unwrap()
+                    .min(state.get_dual_objective().unwrap());
+                let value = sign * (value - margin * value.abs().max(E::from(1.)));
+                if sign > E::from(0.) && value > lp.l[j] {
+                    lp.l[j] = value.min(lp.u[j]);
+                    n_tightened += 1;
+                } else if sign < E::from(0.) && value < lp.u[j] {
+                    lp.u[j] = value.max(lp.l[j]);
+                    n_tightened += 1;
+                }
+                previous = Some(state);
+            }
+        }
+        Ok(n_tightened)
+    }
+
+    /// Solves `lp` from a warm start at `previous`, then from the default
+    /// starting point. Returns `None` if neither solve reaches optimality.
+    fn solve(
+        &mut self,
+        lp: &LinearProgram,
+        previous: Option<&SolverState>,
+    ) -> Result<Option<SolverState>, Problem> {
+        let (l, u) = (lp.get_lower_bounds(), lp.get_upper_bounds());
+        let mut starts = Vec::with_capacity(2);
+        if let Some(previous) = previous {
+            starts.push(warm_start(
+                previous,
+                l,
+                u,
+                self.options.parametric_warm_start_shift,
+            ));
+        }
+        starts.push(SolverState::new_interior(l, u, lp.get_n_cons()));
+        let n_starts = starts.len();
+
+        for (k, mut state) in starts.into_iter().enumerate() {
+            self.n_solves += 1;
+            let mut hooks = SolverHooks {
+                callback: Box::new(NoOpCallback::new()),
+                terminator: Box::new(ComplementarityTerminator::new(&self.options.root)),
+            };
+            let status = lp
+                .solver_builder()
+                .with_options(self.options.root.clone())
+                .build()?
+                .solve(&mut state, &mut hooks)?;
+            match status {
+                Status::Optimal => {
+                    if k + 1 < n_starts {
+                        self.n_warm_starts += 1;
+                    }
+                    lp.update_residual(&mut state);
+                    return Ok(Some(state));
+                }
+                Status::Infeasible => {
+                    return Err("Bound tightening of an infeasible program".gloss());
+                }
+                _ => {}
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::{
+        col,
+        sparse::{SparseColMat, Triplet},
+    };
+
+    use super::*;
+
+    /// `x_0 + x_1 + s = 2` and `x_0 - x_1 - t = 0` with nonnegative variables,
+    /// so that `x_0 <= 2` and `x_1 <= 1`.
+    fn build_lp() -> LinearProgram {
+        let a = SparseColMat::try_new_from_triplets(
+            2,
+            4,
+            &[
+                Triplet::new(0, 0, 1.),
+                Triplet::new(0, 1, 1.),
+                Triplet::new(0, 2, 1.),
+                Triplet::new(1, 0, 1.),
+                Triplet::new(1, 1, -1.),
+                Triplet::new(1, 3, -1.),
+            ],
+        )
+        .unwrap();
+        LinearProgram::new(
+            col![1., -1., 0., 0.],
+            a,
+            col![2., 0.],
+            col![0., 0., 0., 0.],
+            col![E::INFINITY, E::INFINITY, E::INFINITY, E::INFINITY],
+        )
+    }
+
+    #[test]
+    fn test_tighten() {
+        let options = SolverOptions::new();
+        let mut lp = build_lp();
+        let mut obbt = BoundTightening::new(&options);
+        let n_tightened = obbt.tighten(&mut lp, &[0, 1]).unwrap();
+
+        assert_eq!(n_tightened, 2);
+        let (l, u) = (lp.get_lower_bounds(), lp.get_upper_bounds());
+        assert_eq!((l[0], l[1]), (0., 0.));
+        assert!(u[0] >= 2. && u[0] < 2. + 1e-4);
+        assert!(u[1] >= 1. && u[1] < 1. + 1e-4);
+        assert_eq!(lp.get_objective(), &col![1., -1., 0., 0.]);
+
+        assert_eq!(obbt.get_n_solves(), 4);
+        assert_eq!(obbt.get_n_warm_starts(), 3);
+        assert!(obbt.tighten(&mut lp, &[4]).is_err());
+    }
+}
@@ -0,0 +1,167 @@
+//! Transformation stack of the [`Presolver`](super::Presolver).
+//!
+//! Every reduction pushes a [`Transformation`] that maps a primal-dual solution
+//! of the program after it to one of the program before it, so that undoing
+//! the stack in reverse order recovers the duals of every original row and
+//! bound, and not only the primal solution:
+//!
+//! - An **aggregation** pass restores the removed rows and columns, see
+//!   [`aggregation`](super::aggregation).
+//! - An **implied bound** replaces the infinite upper bound of `x_j` by one
+//!   implied by row `i`. The bound is active only if the other variables of the
+//!   row are at the bounds that imply it, so its multiplier `z_u[j]` is moved to
+//!   the row: `y_i += z_u[j] / a_ij`, and every other variable `k` of the row
+//!   receives `-a_ik z_u[j] / a_ij` as a bound multiplier, which keeps the
+//!   stationarity `c = A^T y + z_l + z_u` and the complementarity.
+//! - **Optimization-based** bounds are relaxed by `obbt_safety_margin`, so they
+//!   are inactive at a solution and their multipliers are dropped.
+//!
+//! Bounds are undone in the reverse order in which they were implied, so that a
+//! multiplier moved onto another implied bound, e.g. by a VUB row, is moved
+//! again to the row that implies it.
+
+use crate::{E, SolverState, lp::presolve::aggregation::PostsolveStep};
+
+/// Reduction recorded by the presolver, undone by [`Transformation::undo`].
+#[derive(Debug, Clone)]
+pub(crate) enum Transformation {
+    /// Rows and columns removed by one aggregation pass.
+    Aggregation(PostsolveStep),
+    /// Infinite upper bounds replaced by bounds implied by rows, in the order
+    /// in which they were implied.
+    ImpliedBounds(Vec<ImpliedBound>),
+    /// Variables whose lower and upper bounds optimization-based bound
+    /// tightening changed.
+    OptimizedBounds {
+        lower: Vec<usize>,
+        upper: Vec<usize>,
+    },
+}
+
+/// Upper bound of `col` implied by row `row`.
+#[derive(Debug, Clone)]
+pub(crate) struct ImpliedBound {
+    pub(crate) col: usize,
+    pub(crate) row: usize,
+    /// Nonzero entries of the row, including `col`.
+    pub(crate) entries: Vec<(usize, E)>,
+}
+
+impl Transformation {
+    /// Maps a state of the program after the reduction to a state of the
+    /// program before it.
+    pub(crate) fn undo(&self, state: &SolverState) -> SolverState {
+        match self {
+            Transformation::Aggregation(step) => step.undo(state),
+            Transformation::ImpliedBounds(bounds) => {
+                let mut state = state.clone();
+                for bound in bounds.iter().rev() {
+                    let z = std::mem::replace(&mut state.z_u[bound.col], E::from(0.));
+                    let a = bound
+                        .entries
+                        .iter()
+                        .find(|&&(j, _)| j == bound.col)
+                        .map_or(E::from(0.), |&(_, a)| a);
+                    if z == E::from(0.) || a == E::from(0.) {
+                        continue;
+                    }
+                    state.y[bound.row] += z / a;
+                    for &(k, a_k) in bound.entries.iter().filter(|&&(k, _)| k != bound.col) {
+                        let moved = -a_k * z / a;
+                        if moved > E::from(0.) {
+                            state.z_l[k] += moved;
+                        } else {
+                            state.z_u[k] += moved;
+                        }
+                    }
+                }
+                state
+            }
+            Transformation::OptimizedBounds { lower, upper } => {
+                let mut state = state.clone();
+                for &j in lower {
+                    state.z_l[j] = E::from(0.);
+                }
+                for &j in upper {
+                    state.z_u[j] = E::from(0.);
+                }
+                state
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::{
+        Col, col,
+        sparse::{SparseColMat, Triplet},
+    };
+
+    use crate::{
+        E, SolverHooks, SolverOptions, SolverState, Status,
+        callback::NoOpCallback,
+        lp::{LPSolverType, LinearProgram, presolve::Presolver},
+        terminators::ComplementarityTerminator,
+    };
+
+    fn solve(lp: &LinearProgram) -> SolverState {
+        let options = SolverOptions::new();
+        let mut state = SolverState::new_interior(&lp.l, &lp.u, lp.get_n_cons());
+        let mut hooks = SolverHooks {
+            callback: Box::new(NoOpCallback::new()),
+            terminator: Box::new(ComplementarityTerminator::new(&options)),
+        };
+        let status = lp
+            .solver_builder()
+            .with_solver(LPSolverType::MpcSimplicialCholesky)
+            .build()
+            .unwrap()
+            .solve(&mut state, &mut hooks)
+            .unwrap();
+        assert_eq!(status, Status::Optimal);
+        state
+    }
+
+    /// `min -2 x0 - x1` subject to `x0 + x1 + s0 = 1` and the parallel
+    /// `2 x0 + 2 x1 + s1 = 4`, with the optimum `x = (1, 0)`, `y = (-2, 0)`
+    /// and `z_l = (0, 1, 2, 0)`. The knapsack row implies `x0 <= 1`, which is
+    /// active at the optimum.
+    fn build_lp() -> LinearProgram {
+        let triplets = [
+            Triplet::new(0, 0, 1.),
+            Triplet::new(0, 1, 1.),
+            Triplet::new(0, 2, 1.),
+            Triplet::new(1, 0, 2.),
+            Triplet::new(1, 1, 2.),
+            Triplet::new(1, 3, 1.),
+        ];
+        LinearProgram::new(
+            col![-2., -1., 0., 0.],
+            SparseColMat::try_new_from_triplets(2, 4, &triplets).unwrap(),
+            col![1., 4.],
+            Col::zeros(4),
+            Col::full(4, E::INFINITY),
+        )
+    }
+
+    #[test]
+    fn test_dual_recovery() {
+        let mut presolver = Presolver::new(build_lp());
+        assert_eq!(presolver.aggregate_parallel_rows().unwrap(), 1);
+        presolver.detect_structure();
+        assert!(presolver.tighten_bounds() > 0);
+        assert_eq!(presolver.get_program().get_upper_bounds()[0], 1.);
+
+        let reduced = solve(presolver.get_program());
+        assert!(reduced.z_u[0] < -1e-3);
+
+        let state = presolver.postsolve(&reduced);
+        let report = presolver.check_postsolve(&state, 1e-6);
+        assert!(report.is_optimal(), "{report:?}");
+        assert!((state.x - col![1., 0., 0., 2.]).norm_max() < 1e-6);
+        assert!((state.y - col![-2., 0.]).norm_max() < 1e-6);
+        assert!((state.z_l - col![0., 1., 2., 0.]).norm_max() < 1e-6);
+        assert!(state.z_u.norm_max() < 1e-9);
+    }
+}
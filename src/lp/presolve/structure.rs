@@ -0,0 +1,378 @@
+//! Detection of special row structures in standard-form linear programs.
+//!
+//! Rows are recognized in the standard form `A x = b` produced by the
+//! interfaces, where an inequality row carries a slack column: a column with a
+//! single nonzero, bounds `[0, inf)`, and no objective coefficient.
+//!
+//! - A **knapsack** row reads `sum_j a_j x_j (+ s) = b` with `a_j > 0`, `b > 0`
+//!   and nonnegative variables (up to a sign flip of the whole row).
+//! - A **GUB** (generalized upper bound) row is a knapsack row whose
+//!   structural coefficients are all equal, i.e. `sum_j x_j <= b / a`. GUB rows
+//!   are selected so that no column belongs to more than one of them.
+//! - A **VUB** (variable upper bound) row reads `p x_j - q x_k + s = 0` with
+//!   `p, q > 0`, i.e. `x_j <= (q / p) x_k`.
+
+use faer::{Col, traits::IndexCore};
+
+use crate::{E, lp::LinearProgram};
+
+/// A knapsack row `sum_j a_j x_j (+ s) = b` over nonnegative variables.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KnapsackConstraint {
+    row: usize,
+    /// Structural columns of the row, excluding the slack.
+    cols: Vec<usize>,
+    /// Positive coefficients of `cols`, after normalizing the sign of the row.
+    coefficients: Vec<E>,
+    /// Positive right-hand side, after normalizing the sign of the row.
+    rhs: E,
+    /// Slack column and its positive coefficient, if the row is an inequality.
+    slack: Option<(usize, E)>,
+}
+
+impl KnapsackConstraint {
+    pub fn get_row(&self) -> usize {
+        self.row
+    }
+
+    pub fn get_cols(&self) -> &[usize] {
+        &self.cols
+    }
+
+    pub fn get_coefficients(&self) -> &[E] {
+        &self.coefficients
+    }
+
+    pub fn get_rhs(&self) -> E {
+        self.rhs
+    }
+
+    pub fn get_slack(&self) -> Option<usize> {
+        self.slack.map(|(j, _)| j)
+    }
+
+    /// Returns `true` if all structural coefficients are equal.
+    pub fn is_gub(&self) -> bool {
+        self.coefficients.windows(2).all(|w| w[0] == w[1])
+    }
+}
+
+/// A variable upper bound `x_col <= ratio * x_bound_col`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VubConstraint {
+    row: usize,
+    col: usize,
+    bound_col: usize,
+    ratio: E,
+    slack: usize,
+}
+
+impl VubConstraint {
+    pub fn get_row(&self) -> usize {
+        self.row
+    }
+
+    /// The bounded variable.
+    pub fn get_col(&self) -> usize {
+        self.col
+    }
+
+    /// The variable providing the bound.
+    pub fn get_bound_col(&self) -> usize {
+        self.bound_col
+    }
+
+    pub fn get_ratio(&self) -> E {
+        self.ratio
+    }
+
+    pub fn get_slack(&self) -> usize {
+        self.slack
+    }
+}
+
+/// Special row structures found in a linear program.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RowStructure {
+    gub: Vec<KnapsackConstraint>,
+    knapsack: Vec<KnapsackConstraint>,
+    vub: Vec<VubConstraint>,
+}
+
+impl RowStructure {
+    /// Pairwise column-disjoint GUB rows.
+    pub fn get_gub(&self) -> &[KnapsackConstraint] {
+        &self.gub
+    }
+
+    /// Knapsack rows not reported as GUB rows.
+    pub fn get_knapsack(&self) -> &[KnapsackConstraint] {
+        &self.knapsack
+    }
+
+    pub fn get_vub(&self) -> &[VubConstraint] {
+        &self.vub
+    }
+
+    /// Returns `true` if no special structure was found.
+    pub fn is_empty(&self) -> bool {
+        self.gub.is_empty() && self.knapsack.is_empty() && self.vub.is_empty()
+    }
+
+    /// Detects the GUB, knapsack, and VUB rows of `lp`.
+    pub fn detect(lp: &LinearProgram) -> Self {
+        let a = lp.get_constraint_matrix();
+        let (b, l, u, c) = (
+            lp.get_rhs(),
+            lp.get_lower_bounds(),
+            lp.get_upper_bounds(),
+            lp.get_objective(),
+        );
+
+        let col_ptr = a.symbolic().col_ptr();
+        let is_slack = |j: usize| {
+            col_ptr[j + 1] - col_ptr[j] == 1
+                && l[j] == E::from(0.)
+                && u[j] == E::INFINITY
+                && c[j] == E::from(0.)
+        };
+
+        let a_csr = lp.get_constraint_matrix_csr();
+        let row_ptr = a_csr.symbolic().row_ptr();
+        let col_idx = a_csr.symbolic().col_idx();
+        let values = a_csr.val();
+
+        let mut structure = Self::default();
+        let mut in_gub = vec![false; lp.get_n_vars()];
+
+        for i in 0..lp.get_n_cons() {
+            let mut slack = None;
+            let mut entries = Vec::new();
+            for k in row_ptr[i].zx()..row_ptr[i + 1].zx() {
+                let (j, a_ij) = (col_idx[k].zx(), values[k]);
+                if a_ij == E::from(0.) {
+                    continue;
+                }
+                if slack.is_none() && is_slack(j) {
+                    slack = Some((j, a_ij));
+                } else {
+                    entries.push((j, a_ij));
+                }
+            }
+
+            if let Some(knapsack) = knapsack_row(i, b[i], &entries, slack, l) {
+                if knapsack.is_gub() && knapsack.cols.iter().all(|&j| !in_gub[j]) {
+                    knapsack.cols.iter().for_each(|&j| in_gub[j] = true);
+                    structure.gub.push(knapsack);
+                } else {
+                    structure.knapsack.push(knapsack);
+                }
+            } else if let Some(vub) = vub_row(i, b[i], &entries, slack, l) {
+                structure.vub.push(vub);
+            }
+        }
+
+        structure
+    }
+
+    /// Upper bounds implied by the detected rows, intersected with the bounds of `lp`.
+    ///
+    /// Every variable of a knapsack or GUB row, including its slack, is bounded
+    /// by the row's right-hand side less the minimum activity of the other
+    /// variables. A VUB row bounds its variable by `ratio * u[bound_col]`.
+    pub fn implied_upper_bounds(&self, lp: &LinearProgram) -> Col<E> {
+        self.implied_upper_bounds_with_rows(lp).0
+    }
+
+    /// Implied upper bounds as in [`RowStructure::implied_upper_bounds`], and
+    /// the pairs `(col, row)` of the variables whose bound a row tightened, in
+    /// the order in which the final bounds were set.
+    pub(crate) fn implied_upper_bounds_with_rows(
+        &self,
+        lp: &LinearProgram,
+    ) -> (Col<E>, Vec<(usize, usize)>) {
+        let (l, mut u) = (lp.get_lower_bounds(), lp.get_upper_bounds().clone());
+        // Row and order of the last tightening of every bound
+        let mut sources = vec![None; u.nrows()];
+        let mut n_tightened = 0;
+        let mut tighten = |u: &mut Col<E>, j: usize, bound: E, row: usize| {
+            if bound < u[j] {
+                u[j] = bound;
+                sources[j] = Some((n_tightened, row));
+                n_tightened += 1;
+            }
+        };
+
+        for row in self.gub.iter().chain(self.knapsack.iter()) {
+            let min_activity = row
+                .cols
+                .iter()
+                .zip(row.coefficients.iter())
+                .map(|(&j, &a_j)| a_j * l[j])
+                .sum::<E>();
+            let slack_room = row.rhs - min_activity;
+
+            for (&j, &a_j) in row.cols.iter().zip(row.coefficients.iter()) {
+                tighten(&mut u, j, l[j] + slack_room / a_j, row.row);
+            }
+            if let Some((s, a_s)) = row.slack {
+                tighten(&mut u, s, slack_room / a_s, row.row);
+            }
+        }
+
+        for row in self.vub.iter() {
+            if u[row.bound_col].is_finite() {
+                let bound = row.ratio * u[row.bound_col];
+                tighten(&mut u, row.col, bound, row.row);
+            }
+        }
+
+        let mut sources = sources
+            .into_iter()
+            .enumerate()
+            .filter_map(|(j, source)| source.map(|(order, row)| (order, j, row)))
+            .collect::<Vec<_>>();
+        sources.sort();
+        (u, sources.into_iter().map(|(_, j, row)| (j, row)).collect())
+    }
+}
+
+/// Recognizes `sum_j a_j x_j (+ s) = b` with coefficients and right-hand side of a common sign.
+fn knapsack_row(
+    row: usize,
+    rhs: E,
+    entries: &[(usize, E)],
+    slack: Option<(usize, E)>,
+    l: &Col<E>,
+) -> Option<KnapsackConstraint> {
+    if entries.is_empty() || rhs == E::from(0.) {
+        return None;
+    }
+    let sign = rhs.signum();
+
+    let same_sign = entries
+        .iter()
+        .all(|&(j, a_j)| a_j * sign > E::from(0.) && l[j] >= E::from(0.));
+    // A slack of the opposite sign turns the row into a covering constraint
+    let slack_ok = slack.is_none_or(|(_, a_s)| a_s * sign > E::from(0.));
+    if !same_sign || !slack_ok {
+        return None;
+    }
+
+    Some(KnapsackConstraint {
+        row,
+        cols: entries.iter().map(|&(j, _)| j).collect(),
+        coefficients: entries.iter().map(|&(_, a_j)| a_j * sign).collect(),
+        rhs: rhs * sign,
+        slack: slack.map(|(j, a_s)| (j, a_s * sign)),
+    })
+}
+
+/// Recognizes `p x_j - q x_k + s = 0` over nonnegative variables.
+fn vub_row(
+    row: usize,
+    rhs: E,
+    entries: &[(usize, E)],
+    slack: Option<(usize, E)>,
+    l: &Col<E>,
+) -> Option<VubConstraint> {
+    let (slack, a_s) = slack?;
+    let [(j, a_j), (k, a_k)] = entries else {
+        return None;
+    };
+    if rhs != E::from(0.) || l[*j] < E::from(0.) || l[*k] < E::from(0.) {
+        return None;
+    }
+
+    // Normalize to a positive slack coefficient, so the row reads a_j x_j + a_k x_k <= 0
+    let (a_j, a_k) = (a_j * a_s.signum(), a_k * a_s.signum());
+    let (col, p, bound_col, q) = if a_j > E::from(0.) && a_k < E::from(0.) {
+        (*j, a_j, *k, -a_k)
+    } else if a_k > E::from(0.) && a_j < E::from(0.) {
+        (*k, a_k, *j, -a_j)
+    } else {
+        return None;
+    };
+
+    Some(VubConstraint {
+        row,
+        col,
+        bound_col,
+        ratio: q / p,
+        slack,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::{
+        col,
+        sparse::{SparseColMat, Triplet},
+    };
+
+    use super::*;
+
+    /// Rows:
+    /// - `x0 + x1 + x2 + s0 = 1` (GUB)
+    /// - `2 x0 + 3 x3 + s1 = 6` (knapsack)
+    /// - `x3 - 5 x4 + s2 = 0` (VUB)
+    /// - `-x1 - x4 = -4` (knapsack after flipping the sign)
+    fn build_lp() -> LinearProgram {
+        let triplets = [
+            Triplet::new(0, 0, 1.),
+            Triplet::new(0, 1, 1.),
+            Triplet::new(0, 2, 1.),
+            Triplet::new(0, 5, 1.),
+            Triplet::new(1, 0, 2.),
+            Triplet::new(1, 3, 3.),
+            Triplet::new(1, 6, 1.),
+            Triplet::new(2, 3, 1.),
+            Triplet::new(2, 4, -5.),
+            Triplet::new(2, 7, 1.),
+            Triplet::new(3, 1, -1.),
+            Triplet::new(3, 4, -1.),
+        ];
+        let a = SparseColMat::try_new_from_triplets(4, 8, &triplets).unwrap();
+
+        LinearProgram::new(
+            col![1., 1., 1., 1., 1., 0., 0., 0.],
+            a,
+            col![1., 6., 0., -4.],
+            Col::zeros(8),
+            Col::from_fn(8, |_| E::INFINITY),
+        )
+    }
+
+    #[test]
+    fn test_detect_structure() {
+        let lp = build_lp();
+        let structure = RowStructure::detect(&lp);
+
+        assert_eq!(structure.get_gub().len(), 1);
+        assert_eq!(structure.get_gub()[0].get_row(), 0);
+        assert_eq!(structure.get_gub()[0].get_cols(), &[0, 1, 2]);
+        assert_eq!(structure.get_gub()[0].get_slack(), Some(5));
+
+        // The last row is also a GUB row, but shares x1 with the first one
+        let knapsack_rows = structure
+            .get_knapsack()
+            .iter()
+            .map(|k| k.get_row())
+            .collect::<Vec<_>>();
+        assert_eq!(knapsack_rows, vec![1, 3]);
+        assert_eq!(structure.get_knapsack()[1].get_rhs(), 4.);
+
+        assert_eq!(structure.get_vub().len(), 1);
+        assert_eq!(structure.get_vub()[0].get_col(), 3);
+        assert_eq!(structure.get_vub()[0].get_bound_col(), 4);
+        assert_eq!(structure.get_vub()[0].get_ratio(), 5.);
+    }
+
+    #[test]
+    fn test_implied_upper_bounds() {
+        let lp = build_lp();
+        let u = RowStructure::detect(&lp).implied_upper_bounds(&lp);
+
+        assert_eq!(u, col![1., 1., 1., 2., 4., 1., 6., E::INFINITY]);
+    }
+}
@@ -0,0 +1,221 @@
+//! Cache of the symbolic analyses of the KKT systems across solves.
+//!
+//! Decomposition and sampling loops, e.g. Benders or sample average
+//! approximation, solve thousands of linear programs that share their
+//! constraint matrix pattern and only change `c`, `b` and the bounds. The
+//! symbolic analysis of the KKT system, i.e. the fill-reducing ordering, the
+//! elimination tree and the pattern of the factor, is the same for all of them.
+//! [`LPSolverBuilder::with_symbolic`] reuses one analysis. A [`SymbolicCache`]
+//! keeps the analysis of every pattern and solver configuration it has built,
+//! and [`SymbolicCache::build`] passes the stored one to
+//! [`LPSolverBuilder::with_symbolic`], so that only the first build of each
+//! analyzes the system.
+//!
+//! Only the analyses are cached. Each build still assembles its KKT matrix and
+//! allocates its numeric factor and vectors, since the solvers borrow their
+//! program.
+//!
+//! The key of an analysis consists of the dimensions of the program, a hash of
+//! the pattern of `A` and of which bounds are finite, the solver and system
+//! types, and the fill-reducing ordering. A stored analysis is only reused if
+//! the pattern of `A` and the finite bounds are the ones it was built for, so
+//! that colliding hashes cause a new analysis rather than a wrong one.
+
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{Arc, Mutex},
+};
+
+use problemo::Problem;
+
+use crate::{
+    I,
+    ipm::AugmentedSystemType,
+    linalg::{ordering::FillReducingOrdering, solver::SymbolicAnalysis},
+    lp::{LPSolver, LPSolverBuilder, LPSolverType, LinearProgram},
+};
+
+/// Identifies the programs and solver configurations that share an analysis.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct CacheKey {
+    n_vars: usize,
+    n_cons: usize,
+    nnz: usize,
+    pattern: u64,
+    configuration: String,
+}
+
+impl CacheKey {
+    pub(crate) fn new(
+        lp: &LinearProgram,
+        solver_type: LPSolverType,
+        system_type: AugmentedSystemType,
+        ordering: Option<FillReducingOrdering>,
+    ) -> Self {
+        let a = lp.get_constraint_matrix();
+        let mut hasher = DefaultHasher::new();
+        a.symbolic().col_ptr().hash(&mut hasher);
+        a.symbolic().row_idx().hash(&mut hasher);
+        for (l, u) in lp
+            .get_lower_bounds()
+            .iter()
+            .zip(lp.get_upper_bounds().iter())
+        {
+            (l.is_finite(), u.is_finite()).hash(&mut hasher);
+        }
+        Self {
+            n_vars: lp.get_n_vars(),
+            n_cons: lp.get_n_cons(),
+            nnz: a.compute_nnz(),
+            pattern: hasher.finish(),
+            configuration: format!("{solver_type:?}/{system_type:?}/{ordering:?}"),
+        }
+    }
+}
+
+/// Sparsity pattern of `A` and finite bounds of a program, compared on a
+/// match of the hashes of [`CacheKey`].
+#[derive(Debug, PartialEq, Eq)]
+struct Pattern {
+    col_ptr: Vec<I>,
+    row_idx: Vec<I>,
+    finite_bounds: Vec<(bool, bool)>,
+}
+
+impl Pattern {
+    fn new(lp: &LinearProgram) -> Self {
+        let a = lp.get_constraint_matrix().symbolic();
+        Self {
+            col_ptr: a.col_ptr().to_vec(),
+            row_idx: a.row_idx().to_vec(),
+            finite_bounds: lp
+                .get_lower_bounds()
+                .iter()
+                .zip(lp.get_upper_bounds().iter())
+                .map(|(l, u)| (l.is_finite(), u.is_finite()))
+                .collect(),
+        }
+    }
+
+    fn matches(&self, lp: &LinearProgram) -> bool {
+        let a = lp.get_constraint_matrix().symbolic();
+        self.col_ptr == a.col_ptr()
+            && self.row_idx == a.row_idx()
+            && self
+                .finite_bounds
+                .iter()
+                .zip(
+                    lp.get_lower_bounds()
+                        .iter()
+                        .zip(lp.get_upper_bounds().iter()),
+                )
+                .all(|(&finite, (l, u))| finite == (l.is_finite(), u.is_finite()))
+    }
+}
+
+/// Symbolic analyses shared by the builds of linear program solvers. See the
+/// [module documentation](self).
+///
+/// Clones share the analyses, so that a cache can be handed to the
+/// builders of several threads.
+#[derive(Clone, Debug, Default)]
+pub struct SymbolicCache {
+    inner: Arc<Mutex<CacheInner>>,
+}
+
+#[derive(Debug, Default)]
+struct CacheInner {
+    analyses: HashMap<CacheKey, (Pattern, SymbolicAnalysis)>,
+    n_hits: usize,
+    n_misses: usize,
+}
+
+impl SymbolicCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of distinct patterns and configurations analyzed.
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().analyses.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of builds that reused an analysis.
+    pub fn get_n_hits(&self) -> usize {
+        self.inner.lock().unwrap().n_hits
+    }
+
+    /// Number of builds that analyzed their system.
+    pub fn get_n_misses(&self) -> usize {
+        self.inner.lock().unwrap().n_misses
+    }
+
+    /// Drops all analyses, e.g. once the loop moves on to programs of another
+    /// pattern.
+    pub fn clear(&self) {
+        self.inner.lock().unwrap().analyses.clear();
+    }
+
+    /// Builds the solver of `builder` with the stored analysis of the pattern
+    /// of its program and its solver configuration, and stores the analysis of
+    /// the solver if there is none. Builds with an analysis given to
+    /// [`LPSolverBuilder::with_symbolic`] or of a registered solver are left
+    /// unchanged.
+    pub fn build<'a>(
+        &self,
+        builder: LPSolverBuilder<'a>,
+    ) -> Result<Box<dyn LPSolver<'a> + 'a>, Problem> {
+        let lp = builder.get_lp()?;
+        if builder.symbolic.is_some() || builder.get_registered_name().is_some() {
+            return builder.build();
+        }
+        let key = CacheKey::new(
+            lp,
+            builder.get_solver_type(lp),
+            builder.get_system_type(lp)?,
+            builder
+                .options
+                .get_option::<FillReducingOrdering>("cholesky_ordering"),
+        );
+        if let Some(symbolic) = self.get(lp, &key) {
+            return builder.with_symbolic(symbolic).build();
+        }
+
+        let solver = builder.build()?;
+        if let Some(symbolic) = solver.get_symbolic() {
+            self.insert(lp, key, symbolic);
+        }
+        Ok(solver)
+    }
+
+    /// Analysis stored under `key` for the pattern of `lp`, counting the lookup
+    /// as a hit or a miss.
+    pub(crate) fn get(&self, lp: &LinearProgram, key: &CacheKey) -> Option<SymbolicAnalysis> {
+        let mut inner = self.inner.lock().unwrap();
+        let symbolic = inner
+            .analyses
+            .get(key)
+            .filter(|(pattern, _)| pattern.matches(lp))
+            .map(|(_, symbolic)| symbolic.clone());
+        match symbolic {
+            Some(_) => inner.n_hits += 1,
+            None => inner.n_misses += 1,
+        }
+        symbolic
+    }
+
+    /// Stores `symbolic` under `key` for the pattern of `lp`, replacing the
+    /// analysis of another pattern with the same key.
+    pub(crate) fn insert(&self, lp: &LinearProgram, key: CacheKey, symbolic: SymbolicAnalysis) {
+        self.inner
+            .lock()
+            .unwrap()
+            .analyses
+            .insert(key, (Pattern::new(lp), symbolic));
+    }
+}
@@ -0,0 +1,326 @@
+//! Validation of the data of linear programs for probable modeling errors.
+//!
+//! Interior-point methods scale their iterates and tolerances with the
+//! magnitude of the problem data, so data that mixes units, e.g. a row in
+//! megawatts next to one in watts, or a finite bound of `1e30` used for
+//! infinity, leads to slow progress or a spurious failure rather than an
+//! error. A [`Validator`] flags such data before the solve:
+//!
+//! - [`ValidationIssue::WideRange`]: the nonzero magnitudes of the objective,
+//!   the constraint matrix, the right-hand side or the finite bounds span more
+//!   than `validation_max_orders` orders of magnitude.
+//! - [`ValidationIssue::SentinelBound`]: a lower bound at or below
+//!   `-infinity_threshold` or an upper bound at or above it, which most
+//!   modeling tools write for a missing bound.
+//! - [`ValidationIssue::HugeValue`]: any other entry with a magnitude of at
+//!   least `infinity_threshold`, which cannot stand for a missing bound.
+//!
+//! [`Validator::sanitize`] additionally replaces the sentinel bounds by true
+//! infinities. [`LinearProgram::new`] already does so for the default
+//! threshold [`DEFAULT_INFINITY_THRESHOLD`](crate::DEFAULT_INFINITY_THRESHOLD), so sentinel bounds are only found
+//! with a lower `infinity_threshold`, e.g. for data that writes `1e10` for a
+//! missing bound.
+
+use std::fmt;
+
+use faer::Col;
+use macros::{explicit_options, use_option};
+use serde::Serialize;
+
+use crate::{E, SolverOptions, lp::LinearProgram};
+
+/// Part of the data of a linear program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ProblemData {
+    Objective,
+    ConstraintMatrix,
+    Rhs,
+    Bounds,
+}
+
+/// A probable modeling error found by a [`Validator`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum ValidationIssue {
+    /// The smallest and largest nonzero magnitudes of `data` are `min` and
+    /// `max`, too far apart.
+    WideRange { data: ProblemData, min: E, max: E },
+    /// The lower or upper bound of `variable` is `value`, a sentinel for an
+    /// infinite bound.
+    SentinelBound {
+        variable: usize,
+        value: E,
+        upper: bool,
+    },
+    /// Entry `index` of `data` is `value`, of a magnitude beyond
+    /// `infinity_threshold`.
+    HugeValue {
+        data: ProblemData,
+        index: usize,
+        value: E,
+    },
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationIssue::WideRange { data, min, max } => write!(
+                f,
+                "{data:?} spans {:.1} orders of magnitude, from {min:e} to {max:e}",
+                (max / min).log10()
+            ),
+            ValidationIssue::SentinelBound {
+                variable,
+                value,
+                upper,
+            } => write!(
+                f,
+                "{} bound {value:e} of variable {variable} is treated as infinite",
+                if *upper { "Upper" } else { "Lower" }
+            ),
+            ValidationIssue::HugeValue { data, index, value } => {
+                write!(f, "{data:?} entry {index} has magnitude {value:e}")
+            }
+        }
+    }
+}
+
+/// Issues found in a linear program.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ValidationReport {
+    issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    pub fn get_issues(&self) -> &[ValidationIssue] {
+        &self.issues
+    }
+
+    /// Whether no issue was found.
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Number of sentinel bounds, which [`Validator::sanitize`] replaces.
+    pub fn get_n_sentinel_bounds(&self) -> usize {
+        self.issues
+            .iter()
+            .filter(|issue| matches!(issue, ValidationIssue::SentinelBound { .. }))
+            .count()
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for issue in &self.issues {
+            writeln!(f, "{issue}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks linear programs for probable modeling errors. See the
+/// [module documentation](self).
+#[explicit_options(name = SolverOptions)]
+#[use_option(name = "validation_max_orders", type_ = E, default = "10", description = "Number of orders of magnitude that the nonzero entries of each part of the problem data may span before validation flags them.")]
+#[use_option(name = "infinity_threshold", type_ = E, default = "1e20", description = "Magnitude from which bounds are treated as infinite, and from which validation flags other problem data as huge.")]
+pub struct Validator {}
+
+impl Validator {
+    pub fn new(options: &SolverOptions) -> Self {
+        Self {
+            options: options.into(),
+        }
+    }
+
+    /// Issues found in `lp`.
+    pub fn check(&self, lp: &LinearProgram) -> ValidationReport {
+        let infinity = self.options.infinity_threshold;
+        let mut issues = Vec::new();
+
+        let (l, u) = (lp.get_lower_bounds(), lp.get_upper_bounds());
+        for j in 0..lp.get_n_vars() {
+            for (value, upper) in [(l[j], false), (u[j], true)] {
+                if !value.is_finite() || value.abs() < infinity {
+                    continue;
+                }
+                if (value > E::from(0.)) == upper {
+                    issues.push(ValidationIssue::SentinelBound {
+                        variable: j,
+                        value,
+                        upper,
+                    });
+                } else {
+                    issues.push(ValidationIssue::HugeValue {
+                        data: ProblemData::Bounds,
+                        index: j,
+                        value,
+                    });
+                }
+            }
+        }
+
+        let bounds: Vec<E> = l
+            .iter()
+            .chain(u.iter())
+            .copied()
+            .filter(|value| value.abs() < infinity)
+            .collect();
+        let data: [(ProblemData, &[E]); 4] = [
+            (ProblemData::Objective, col_values(lp.get_objective())),
+            (
+                ProblemData::ConstraintMatrix,
+                lp.get_constraint_matrix().val(),
+            ),
+            (ProblemData::Rhs, col_values(lp.get_rhs())),
+            (ProblemData::Bounds, &bounds),
+        ];
+        for (data, values) in data {
+            if data != ProblemData::Bounds {
+                issues.extend(
+                    values
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, value)| value.abs() >= infinity)
+                        .map(|(index, &value)| ValidationIssue::HugeValue { data, index, value }),
+                );
+            }
+            if let Some(issue) = self.check_range(data, values) {
+                issues.push(issue);
+            }
+        }
+        ValidationReport { issues }
+    }
+
+    /// Checks `lp` and replaces its sentinel bounds by infinities.
+    pub fn sanitize(&self, lp: &LinearProgram) -> (LinearProgram, ValidationReport) {
+        let report = self.check(lp);
+        let (mut l, mut u) = (lp.get_lower_bounds().clone(), lp.get_upper_bounds().clone());
+        for issue in report.get_issues() {
+            if let ValidationIssue::SentinelBound {
+                variable, upper, ..
+            } = *issue
+            {
+                if upper {
+                    u[variable] = E::INFINITY;
+                } else {
+                    l[variable] = -E::INFINITY;
+                }
+            }
+        }
+        let sanitized = LinearProgram::new(
+            lp.get_objective().clone(),
+            lp.get_constraint_matrix().clone(),
+            lp.get_rhs().clone(),
+            l,
+            u,
+        );
+        (sanitized, report)
+    }
+
+    /// Flags the nonzero finite magnitudes of `values` if they span too many
+    /// orders of magnitude.
+    fn check_range(&self, data: ProblemData, values: &[E]) -> Option<ValidationIssue> {
+        let magnitudes = values
+            .iter()
+            .map(|value| value.abs())
+            .filter(|value| *value > E::from(0.) && *value < self.options.infinity_threshold);
+        let (min, max) = magnitudes.fold((E::INFINITY, E::from(0.)), |(min, max), value| {
+            (min.min(value), max.max(value))
+        });
+        (max > E::from(0.) && (max / min).log10() > self.options.validation_max_orders)
+            .then_some(ValidationIssue::WideRange { data, min, max })
+    }
+}
+
+fn col_values(col: &Col<E>) -> &[E] {
+    col.try_as_col_major().unwrap().as_slice()
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::{
+        col,
+        sparse::{SparseColMat, Triplet},
+    };
+
+    use super::*;
+
+    /// `min x_0 + x_1` subject to `a x_0 + x_1 = 1` with bounds `l <= x <= u`.
+    fn build_lp(a: E, l: Col<E>, u: Col<E>) -> LinearProgram {
+        let triplets = [Triplet::new(0, 0, a), Triplet::new(0, 1, 1.)];
+        LinearProgram::new(
+            col![1., 1.],
+            SparseColMat::try_new_from_triplets(1, 2, &triplets).unwrap(),
+            col![1.],
+            l,
+            u,
+        )
+    }
+
+    #[test]
+    fn test_clean() {
+        let lp = build_lp(2., col![0., 0.], col![10., E::INFINITY]);
+        let report = Validator::new(&SolverOptions::new()).check(&lp);
+        assert!(report.is_clean());
+    }
+
+    #[test]
+    fn test_sentinel_bounds() {
+        // Bounds beyond the default threshold are infinite from the start
+        let lp = build_lp(2., col![-1e30, 0.], col![1e20, 1e30]);
+        assert_eq!(lp.get_upper_bounds(), &col![E::INFINITY, E::INFINITY]);
+        let validator = Validator::new(&SolverOptions::new());
+        assert!(validator.check(&lp).is_clean());
+
+        // Data that writes 1e10 for infinity needs a lower threshold
+        let mut options = SolverOptions::new();
+        options.set_option("infinity_threshold", 1e10).unwrap();
+        let validator = Validator::new(&options);
+        let lp = build_lp(2., col![-1e10, 0.], col![1e10, 5e10]);
+        let (sanitized, report) = validator.sanitize(&lp);
+
+        assert_eq!(report.get_n_sentinel_bounds(), 3);
+        assert_eq!(
+            report.get_issues()[0],
+            ValidationIssue::SentinelBound {
+                variable: 0,
+                value: -1e10,
+                upper: false
+            }
+        );
+        assert_eq!(sanitized.get_lower_bounds()[0], -E::INFINITY);
+        assert_eq!(
+            sanitized.get_upper_bounds(),
+            &col![E::INFINITY, E::INFINITY]
+        );
+        assert_eq!(sanitized.get_lower_bounds()[1], 0.);
+        assert!(validator.check(&sanitized).is_clean());
+    }
+
+    #[test]
+    fn test_wide_range() {
+        // A coefficient in watts next to one in gigawatts, and a huge lower bound
+        let lp = build_lp(1e-12, col![1e25, 0.], col![E::INFINITY, 10.]);
+        let report = Validator::new(&SolverOptions::new()).check(&lp);
+        assert_eq!(
+            report.get_issues(),
+            &[
+                ValidationIssue::HugeValue {
+                    data: ProblemData::Bounds,
+                    index: 0,
+                    value: 1e25
+                },
+                ValidationIssue::WideRange {
+                    data: ProblemData::ConstraintMatrix,
+                    min: 1e-12,
+                    max: 1.
+                },
+            ]
+        );
+        assert!(report.to_string().contains("12.0 orders of magnitude"));
+
+        let mut options = SolverOptions::new();
+        options.set_option("validation_max_orders", 15.).unwrap();
+        assert_eq!(Validator::new(&options).check(&lp).get_issues().len(), 1);
+    }
+}
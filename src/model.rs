@@ -0,0 +1,536 @@
+//! Modelling layer for linear and quadratic programs.
+//!
+//! [`ModelBuilder`] collects named variables with bounds and objective
+//! coefficients, named rows `lower <= a^T x <= upper` and quadratic objective
+//! terms, and builds a [`Model`] in the standard form of
+//! [`QuadraticProgram::new_with_row_bounds`]. Models without quadratic terms are
+//! solved as a [`LinearProgram`].
+//!
+//! [`Model::solve`] reports a [`Solution`] in terms of the variables and rows of
+//! the model: values, row activities, row duals and reduced costs, with the
+//! objective sense of the model. A solution of a model warm starts the solve of
+//! a modified model of the same dimensions through [`Model::solve_from`], in the
+//! same way as the solves of [`parametric`](crate::lp::parametric).
+//!
+//! Dual values follow the sign convention of the solvers: at an optimum of a
+//! minimization, `c = A^T y + d` for the row duals `y` and reduced costs `d`.
+//! For a maximization, both are negated, so that a row dual is the rate of
+//! change of the optimal objective with the bounds of its row.
+
+use faer::{
+    Col,
+    sparse::{SparseColMat, Triplet},
+};
+use macros::{explicit_options, use_option};
+use problemo::{Problem, common::IntoCommonProblem};
+
+use crate::{
+    E, OptimizationProgram, SolveResult, SolverHooks, SolverOptions, SolverState, Status,
+    lp::{LinearProgram, parametric::warm_start},
+    qp::{QPSolverType, QuadraticProgram},
+    to_index,
+};
+
+/// Direction of the optimization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Sense {
+    #[default]
+    Minimize,
+    Maximize,
+}
+
+/// Handle of a variable of a [`ModelBuilder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Variable(usize);
+
+impl Variable {
+    /// Position of the variable in the order of creation.
+    pub fn get_index(&self) -> usize {
+        self.0
+    }
+}
+
+/// Handle of a constraint row of a [`ModelBuilder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Constraint(usize);
+
+impl Constraint {
+    /// Position of the row in the order of creation.
+    pub fn get_index(&self) -> usize {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Row {
+    name: String,
+    terms: Vec<(usize, E)>,
+    lower: E,
+    upper: E,
+}
+
+/// Incremental construction of a [`Model`].
+#[derive(Clone)]
+pub struct ModelBuilder {
+    sense: Sense,
+    names: Vec<String>,
+    costs: Vec<E>,
+    lower: Vec<E>,
+    upper: Vec<E>,
+    rows: Vec<Row>,
+    quadratic: Vec<(usize, usize, E)>,
+    options: SolverOptions,
+}
+
+impl ModelBuilder {
+    pub fn new() -> Self {
+        Self {
+            sense: Sense::Minimize,
+            names: Vec::new(),
+            costs: Vec::new(),
+            lower: Vec::new(),
+            upper: Vec::new(),
+            rows: Vec::new(),
+            quadratic: Vec::new(),
+            options: SolverOptions::new(),
+        }
+    }
+
+    pub fn with_sense(mut self, sense: Sense) -> Self {
+        self.sense = sense;
+        self
+    }
+
+    /// Options of the solves of the built model.
+    pub fn with_options(mut self, options: SolverOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Adds the variable `lower <= x <= upper` with a zero objective coefficient.
+    pub fn add_variable(&mut self, name: impl Into<String>, lower: E, upper: E) -> Variable {
+        self.names.push(name.into());
+        self.costs.push(E::from(0.));
+        self.lower.push(lower);
+        self.upper.push(upper);
+        Variable(self.names.len() - 1)
+    }
+
+    /// Sets the linear objective coefficient of `var`.
+    pub fn set_cost(&mut self, var: Variable, cost: E) {
+        self.costs[var.0] = cost;
+    }
+
+    pub fn set_bounds(&mut self, var: Variable, lower: E, upper: E) {
+        self.lower[var.0] = lower;
+        self.upper[var.0] = upper;
+    }
+
+    /// Adds `coef * x_i * x_j` to the objective, which becomes `coef * x_i^2`
+    /// for `i == j`.
+    pub fn add_quadratic_term(&mut self, var_i: Variable, var_j: Variable, coef: E) {
+        self.quadratic.push((var_i.0, var_j.0, coef));
+    }
+
+    /// Adds the row `lower <= sum_k coef_k x_k <= upper` over the `(variable,
+    /// coef)` pairs of `terms`. Equal bounds make an equality and infinite bounds
+    /// one-sided rows; repeated variables are summed.
+    pub fn add_constraint(
+        &mut self,
+        name: impl Into<String>,
+        terms: &[(Variable, E)],
+        lower: E,
+        upper: E,
+    ) -> Constraint {
+        self.rows.push(Row {
+            name: name.into(),
+            terms: terms.iter().map(|&(var, coef)| (var.0, coef)).collect(),
+            lower,
+            upper,
+        });
+        Constraint(self.rows.len() - 1)
+    }
+
+    pub fn set_constraint_bounds(&mut self, con: Constraint, lower: E, upper: E) {
+        self.rows[con.0].lower = lower;
+        self.rows[con.0].upper = upper;
+    }
+
+    pub fn get_n_variables(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn get_n_constraints(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Builds the model in standard form. Fails if a variable or row has a
+    /// lower bound above its upper bound.
+    #[allow(non_snake_case)]
+    pub fn build(&self) -> Result<Model, Problem> {
+        let (n, m) = (self.names.len(), self.rows.len());
+        if let Some(j) = (0..n).find(|&j| self.lower[j] > self.upper[j]) {
+            return Err(format!(
+                "Variable {} has lower bound {} above upper bound {}",
+                self.names[j], self.lower[j], self.upper[j]
+            )
+            .gloss());
+        }
+
+        // Maximization is solved as the minimization of the negated objective
+        let sign = match self.sense {
+            Sense::Minimize => E::from(1.),
+            Sense::Maximize => E::from(-1.),
+        };
+
+        let mut triplets = Vec::new();
+        for (i, row) in self.rows.iter().enumerate() {
+            for &(j, coef) in &row.terms {
+                triplets.push(Triplet::new(to_index(i), to_index(j), coef));
+            }
+        }
+        let A = SparseColMat::try_new_from_triplets(m, n, &triplets)
+            .map_err(|e| format!("Failed to assemble constraint matrix: {e:?}").gloss())?;
+
+        // Symmetric Hessian of 1/2 x^T Q x
+        let mut hessian = Vec::with_capacity(2 * self.quadratic.len());
+        for &(i, j, coef) in &self.quadratic {
+            if i == j {
+                hessian.push(Triplet::new(to_index(i), to_index(i), 2. * sign * coef));
+            } else {
+                hessian.push(Triplet::new(to_index(i), to_index(j), sign * coef));
+                hessian.push(Triplet::new(to_index(j), to_index(i), sign * coef));
+            }
+        }
+        let Q = SparseColMat::try_new_from_triplets(n, n, &hessian)
+            .map_err(|e| format!("Failed to assemble quadratic objective: {e:?}").gloss())?;
+
+        let qp = QuadraticProgram::new_with_row_bounds(
+            Q,
+            Col::from_fn(n, |j| sign * self.costs[j]),
+            A,
+            Col::from_fn(m, |i| self.rows[i].lower),
+            Col::from_fn(m, |i| self.rows[i].upper),
+            Col::from_fn(n, |j| self.lower[j]),
+            Col::from_fn(n, |j| self.upper[j]),
+        )?;
+        let slack_rows = qp.get_slack_rows().to_vec();
+        let program = if self.quadratic.is_empty() {
+            Program::Linear(LinearProgram::new(
+                qp.get_linear_objective().clone(),
+                qp.get_constraint_matrix().clone(),
+                qp.get_rhs().clone(),
+                qp.get_lower_bounds().clone(),
+                qp.get_upper_bounds().clone(),
+            ))
+        } else {
+            Program::Quadratic(qp)
+        };
+
+        Ok(Model {
+            options: (&self.options).into(),
+            program,
+            sign,
+            variable_names: self.names.clone(),
+            constraint_names: self.rows.iter().map(|row| row.name.clone()).collect(),
+            slack_rows,
+        })
+    }
+}
+
+impl Default for ModelBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+enum Program {
+    Linear(LinearProgram),
+    Quadratic(QuadraticProgram),
+}
+
+/// Program built by a [`ModelBuilder`], with the names of its variables and rows.
+#[explicit_options(name = SolverOptions)]
+#[use_option(name = "parametric_warm_start_shift", type_ = E, default = "1e-2", description = "Minimal distance of a warm start to the bounds and minimal magnitude of its bound multipliers.")]
+pub struct Model {
+    program: Program,
+    sign: E,
+    variable_names: Vec<String>,
+    constraint_names: Vec<String>,
+    slack_rows: Vec<usize>,
+}
+
+impl Model {
+    /// The program in standard form, if the model has no quadratic terms.
+    pub fn get_linear_program(&self) -> Option<&LinearProgram> {
+        match &self.program {
+            Program::Linear(lp) => Some(lp),
+            Program::Quadratic(_) => None,
+        }
+    }
+
+    /// The program in standard form, if the model has quadratic terms.
+    pub fn get_quadratic_program(&self) -> Option<&QuadraticProgram> {
+        match &self.program {
+            Program::Linear(_) => None,
+            Program::Quadratic(qp) => Some(qp),
+        }
+    }
+
+    pub fn get_variable_names(&self) -> &[String] {
+        &self.variable_names
+    }
+
+    pub fn get_constraint_names(&self) -> &[String] {
+        &self.constraint_names
+    }
+
+    /// Solves the model from the default starting point.
+    pub fn solve(&self, hooks: &mut SolverHooks) -> Result<Solution, Problem> {
+        let (l, u, n_cons) = self.get_bounds();
+        self.solve_state(SolverState::new_interior(l, u, n_cons), hooks)
+    }
+
+    /// Solves the model from `start`, a solution of a model with the same
+    /// variables and rows, moved into the interior by
+    /// `parametric_warm_start_shift`.
+    pub fn solve_from(
+        &self,
+        start: &Solution,
+        hooks: &mut SolverHooks,
+    ) -> Result<Solution, Problem> {
+        let (l, u, n_cons) = self.get_bounds();
+        if start.state.get_primal().nrows() != l.nrows() || start.state.get_dual().nrows() != n_cons
+        {
+            return Err("Warm start has different dimensions than the model".gloss());
+        }
+        let state = warm_start(&start.state, l, u, self.options.parametric_warm_start_shift);
+        self.solve_state(state, hooks)
+    }
+
+    fn get_bounds(&self) -> (&Col<E>, &Col<E>, usize) {
+        match &self.program {
+            Program::Linear(lp) => (
+                lp.get_lower_bounds(),
+                lp.get_upper_bounds(),
+                lp.get_n_cons(),
+            ),
+            Program::Quadratic(qp) => (
+                qp.get_lower_bounds(),
+                qp.get_upper_bounds(),
+                qp.get_n_cons(),
+            ),
+        }
+    }
+
+    #[allow(non_snake_case)]
+    fn solve_state(
+        &self,
+        mut state: SolverState,
+        hooks: &mut SolverHooks,
+    ) -> Result<Solution, Problem> {
+        let (A, result) = match &self.program {
+            Program::Linear(lp) => {
+                let mut solver = lp
+                    .solver_builder()
+                    .with_options(self.options.root.clone())
+                    .build()?;
+                let result = solver.solve_detailed(&mut state, hooks)?;
+                lp.update_residual(&mut state);
+                (lp.get_constraint_matrix(), result)
+            }
+            Program::Quadratic(qp) => {
+                let mut solver = qp
+                    .solver_builder()
+                    .with_solver(QPSolverType::MpcSimplicialCholesky)
+                    .with_options(self.options.root.clone())
+                    .build()?;
+                let result = solver.solve_detailed(&mut state, hooks)?;
+                qp.update_residual(&mut state);
+                (qp.get_constraint_matrix(), result)
+            }
+        };
+
+        // Rows with a slack `a_i^T x - w_i = 0` have the activity `w_i`
+        let n = self.variable_names.len();
+        let x = state.get_primal();
+        let mut activities = A.as_ref() * x;
+        for (s, &i) in self.slack_rows.iter().enumerate() {
+            activities[i] += x[n + s];
+        }
+
+        let sign = self.sign;
+        Ok(Solution {
+            result,
+            objective: sign * state.get_primal_objective().unwrap_or(E::NAN),
+            values: x.subrows(0, n).to_owned(),
+            activities,
+            row_duals: sign * state.get_dual(),
+            reduced_costs: sign * state.get_reduced_cost().subrows(0, n),
+            state,
+        })
+    }
+}
+
+/// Solution of a [`Model`], in terms of its variables and rows.
+#[derive(Debug, Clone)]
+pub struct Solution {
+    result: SolveResult,
+    objective: E,
+    values: Col<E>,
+    activities: Col<E>,
+    row_duals: Col<E>,
+    reduced_costs: Col<E>,
+    state: SolverState,
+}
+
+impl Solution {
+    pub fn get_status(&self) -> Status {
+        self.result.get_status()
+    }
+
+    pub fn is_optimal(&self) -> bool {
+        self.result.get_status() == Status::Optimal
+    }
+
+    /// Summary of the solve, with its residuals, time and certificate.
+    pub fn get_result(&self) -> &SolveResult {
+        &self.result
+    }
+
+    /// Number of iterations of the solve.
+    pub fn get_nit(&self) -> usize {
+        self.state.get_nit() + 1
+    }
+
+    /// Objective value in the sense of the model.
+    pub fn get_objective_value(&self) -> E {
+        self.objective
+    }
+
+    pub fn get_value(&self, var: Variable) -> E {
+        self.values[var.0]
+    }
+
+    /// Values of all variables, in the order of creation.
+    pub fn get_values(&self) -> &Col<E> {
+        &self.values
+    }
+
+    /// Value `a^T x` of a constraint row.
+    pub fn get_activity(&self, con: Constraint) -> E {
+        self.activities[con.0]
+    }
+
+    pub fn get_dual(&self, con: Constraint) -> E {
+        self.row_duals[con.0]
+    }
+
+    pub fn get_reduced_cost(&self, var: Variable) -> E {
+        self.reduced_costs[var.0]
+    }
+
+    /// Final state of the solver on the program in standard form.
+    pub fn get_state(&self) -> &SolverState {
+        &self.state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `max 3 x + 2 y` subject to `x + y <= 4`, `x + 3 y <= 9` and `0 <= x <= 3`.
+    fn build_production() -> (ModelBuilder, Variable, Variable, Constraint) {
+        let mut builder = ModelBuilder::new().with_sense(Sense::Maximize);
+        let x = builder.add_variable("x", 0., 3.);
+        let y = builder.add_variable("y", 0., E::INFINITY);
+        builder.set_cost(x, 3.);
+        builder.set_cost(y, 2.);
+        let capacity = builder.add_constraint("capacity", &[(x, 1.), (y, 1.)], -E::INFINITY, 4.);
+        builder.add_constraint("labor", &[(x, 1.), (y, 3.)], -E::INFINITY, 9.);
+        (builder, x, y, capacity)
+    }
+
+    #[test]
+    fn test_linear_model() {
+        let (mut builder, x, y, capacity) = build_production();
+        let model = builder.build().unwrap();
+        assert!(model.get_linear_program().is_some());
+        assert_eq!(model.get_constraint_names(), ["capacity", "labor"]);
+
+        let solution = model.solve(&mut SolverHooks::silent()).unwrap();
+        assert!(solution.is_optimal());
+        assert!((solution.get_value(x) - 3.).abs() < 1e-4);
+        assert!((solution.get_value(y) - 1.).abs() < 1e-4);
+        assert!((solution.get_objective_value() - 11.).abs() < 1e-4);
+        assert!((solution.get_activity(capacity) - 4.).abs() < 1e-4);
+        // One more unit of capacity is worth the profit of y
+        assert!((solution.get_dual(capacity) - 2.).abs() < 1e-4);
+
+        builder.set_constraint_bounds(capacity, -E::INFINITY, 4.5);
+        let model = builder.build().unwrap();
+        let warm = model
+            .solve_from(&solution, &mut SolverHooks::silent())
+            .unwrap();
+        assert!(warm.is_optimal());
+        assert!((warm.get_objective_value() - 12.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_quadratic_model() {
+        // min (x - 1)^2 + (y - 2)^2 subject to x + y = 1
+        let mut builder = ModelBuilder::new();
+        let x = builder.add_variable("x", -E::INFINITY, E::INFINITY);
+        let y = builder.add_variable("y", 0., E::INFINITY);
+        builder.add_quadratic_term(x, x, 1.);
+        builder.add_quadratic_term(y, y, 1.);
+        builder.set_cost(x, -2.);
+        builder.set_cost(y, -4.);
+        let sum = builder.add_constraint("sum", &[(x, 1.), (y, 1.)], 1., 1.);
+        let model = builder.build().unwrap();
+        assert!(model.get_quadratic_program().is_some());
+
+        let solution = model.solve(&mut SolverHooks::silent()).unwrap();
+        assert!(solution.is_optimal());
+        assert!(solution.get_value(x).abs() < 1e-4);
+        assert!((solution.get_value(y) - 1.).abs() < 1e-4);
+        assert!((solution.get_activity(sum) - 1.).abs() < 1e-4);
+        assert!((solution.get_objective_value() + 3.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_coupled_quadratic_model() {
+        // min x^2 + x y + y^2 - 3 x with the inactive row x - y <= 10
+        let mut builder = ModelBuilder::new();
+        let x = builder.add_variable("x", -E::INFINITY, E::INFINITY);
+        let y = builder.add_variable("y", -E::INFINITY, E::INFINITY);
+        builder.add_quadratic_term(x, x, 1.);
+        builder.add_quadratic_term(x, y, 1.);
+        builder.add_quadratic_term(y, y, 1.);
+        builder.set_cost(x, -3.);
+        let row = builder.add_constraint("row", &[(x, 1.), (y, -1.)], -E::INFINITY, 10.);
+
+        let solution = builder
+            .build()
+            .unwrap()
+            .solve(&mut SolverHooks::silent())
+            .unwrap();
+        assert!(solution.is_optimal());
+        assert!((solution.get_value(x) - 2.).abs() < 1e-4);
+        assert!((solution.get_value(y) + 1.).abs() < 1e-4);
+        assert!(solution.get_dual(row).abs() < 1e-4);
+        assert!((solution.get_objective_value() + 3.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_invalid_bounds() {
+        let mut builder = ModelBuilder::new();
+        let x = builder.add_variable("x", 1., 0.);
+        assert!(builder.build().is_err());
+
+        builder.set_bounds(x, 0., 1.);
+        builder.add_constraint("row", &[(x, 1.)], 2., 1.);
+        assert!(builder.build().is_err());
+    }
+}
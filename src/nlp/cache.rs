@@ -0,0 +1,242 @@
+//! Caching of the evaluations of a [`NonlinearProgram`].
+//!
+//! Solvers evaluate the functions of a program at the same iterate several
+//! times per iteration, e.g. for the residual, the line search and the update
+//! of the barrier parameter. [`cached`] wraps a program so that every function
+//! keeps its last value and returns it again while it is evaluated at the same
+//! point. Two points are the same if they are within `nlp_cache_tolerance`
+//! relative to the larger of their max norm and one; the Hessian of the
+//! Lagrangian also compares the multipliers.
+//!
+//! The cached program has the [`EvaluationCache`] as its context, which counts
+//! the hits and the evaluations of the wrapped program.
+
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use faer::{Col, sparse::SparseColMat};
+use macros::{explicit_options, use_option};
+
+use crate::{E, I, SolverOptions, nlp::NonlinearProgram};
+
+/// Last point and multipliers of a function, with its value.
+type Slot<T> = Mutex<Option<(Col<E>, Option<Col<E>>, T)>>;
+
+/// Cached values of the functions of a wrapped program.
+#[explicit_options(name = SolverOptions)]
+#[use_option(name = "nlp_cache_tolerance", type_ = E, default = "0", description = "Relative distance below which two points share the cached evaluations of a nonlinear program (0 compares exactly).")]
+pub struct EvaluationCache {
+    program: NonlinearProgram,
+    f: Slot<E>,
+    g: Slot<Col<E>>,
+    df: Slot<Col<E>>,
+    dg: Slot<SparseColMat<I, E>>,
+    h: Slot<SparseColMat<I, E>>,
+    n_hits: AtomicUsize,
+    n_evaluations: AtomicUsize,
+}
+
+impl EvaluationCache {
+    fn new(program: NonlinearProgram, options: &SolverOptions) -> Self {
+        Self {
+            program,
+            f: Mutex::new(None),
+            g: Mutex::new(None),
+            df: Mutex::new(None),
+            dg: Mutex::new(None),
+            h: Mutex::new(None),
+            n_hits: AtomicUsize::new(0),
+            n_evaluations: AtomicUsize::new(0),
+            options: options.into(),
+        }
+    }
+
+    /// Wrapped program, e.g. to retrieve its own context.
+    pub fn get_program(&self) -> &NonlinearProgram {
+        &self.program
+    }
+
+    /// Number of evaluations answered from the cache.
+    pub fn get_n_hits(&self) -> usize {
+        self.n_hits.load(Ordering::Relaxed)
+    }
+
+    /// Number of evaluations of the functions of the wrapped program.
+    pub fn get_n_evaluations(&self) -> usize {
+        self.n_evaluations.load(Ordering::Relaxed)
+    }
+
+    /// Discards all cached values.
+    pub fn clear(&self) {
+        *self.f.lock().unwrap() = None;
+        *self.g.lock().unwrap() = None;
+        *self.df.lock().unwrap() = None;
+        *self.dg.lock().unwrap() = None;
+        *self.h.lock().unwrap() = None;
+    }
+
+    fn is_same(&self, a: &Col<E>, b: &Col<E>) -> bool {
+        let tolerance = self.options.nlp_cache_tolerance;
+        if a.nrows() != b.nrows() {
+            return false;
+        }
+        if tolerance == E::from(0.) {
+            return a == b;
+        }
+        let scale = a.norm_max().max(b.norm_max()).max(E::from(1.));
+        (a - b).norm_max() <= tolerance * scale
+    }
+
+    /// Returns the value in `slot` if it was computed at `x` and `y`, else
+    /// computes and stores it. The lock is not held while computing.
+    fn lookup<T: Clone>(
+        &self,
+        slot: &Slot<T>,
+        x: &Col<E>,
+        y: Option<&Col<E>>,
+        compute: impl FnOnce() -> T,
+    ) -> T {
+        if let Some((x_cached, y_cached, value)) = slot.lock().unwrap().as_ref()
+            && self.is_same(x_cached, x)
+            && match (y_cached, y) {
+                (Some(y_cached), Some(y)) => self.is_same(y_cached, y),
+                (None, None) => true,
+                _ => false,
+            }
+        {
+            self.n_hits.fetch_add(1, Ordering::Relaxed);
+            return value.clone();
+        }
+
+        self.n_evaluations.fetch_add(1, Ordering::Relaxed);
+        let value = compute();
+        *slot.lock().unwrap() = Some((x.clone(), y.cloned(), value.clone()));
+        value
+    }
+}
+
+/// Wraps `nlp` into a program that caches the values of its functions, with the
+/// `nlp_cache_tolerance` of `options`. The [`EvaluationCache`] is the context
+/// of the returned program.
+pub fn cached(nlp: NonlinearProgram, options: &SolverOptions) -> NonlinearProgram {
+    let (n_var, n_cons) = (nlp.n_var, nlp.n_cons);
+    let (l, u) = (nlp.l.clone(), nlp.u.clone());
+    let has_hessian = nlp.h.is_some();
+    let cache = Arc::new(EvaluationCache::new(nlp, options));
+
+    let f = {
+        let cache = cache.clone();
+        Box::new(move |x: &Col<E>| cache.lookup(&cache.f, x, None, || cache.program.f(x)))
+    };
+    let g = {
+        let cache = cache.clone();
+        Box::new(move |x: &Col<E>| cache.lookup(&cache.g, x, None, || cache.program.g(x)))
+    };
+    let df = {
+        let cache = cache.clone();
+        Box::new(move |x: &Col<E>| cache.lookup(&cache.df, x, None, || cache.program.df(x)))
+    };
+    let dg = {
+        let cache = cache.clone();
+        Box::new(move |x: &Col<E>| cache.lookup(&cache.dg, x, None, || cache.program.dg(x)))
+    };
+    let h = has_hessian.then(|| {
+        let cache = cache.clone();
+        Box::new(move |x: &Col<E>, y: &Col<E>| {
+            cache.lookup(&cache.h, x, Some(y), || cache.program.h(x, y).unwrap())
+        }) as _
+    });
+
+    NonlinearProgram {
+        n_var,
+        n_cons,
+        f,
+        g,
+        df,
+        dg,
+        h,
+        l,
+        u,
+        context: Some(cache),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::{
+        col,
+        sparse::{SparseColMat, Triplet},
+    };
+    use rstest::rstest;
+
+    use super::*;
+
+    /// `min (x_0 - 1)^2 + (x_1 - 2)^2` subject to `x_0 + x_1 = 3`, counting the
+    /// evaluations of the objective in its context.
+    fn build_nlp() -> NonlinearProgram {
+        NonlinearProgram::new_with_context(
+            2,
+            1,
+            AtomicUsize::new(0),
+            |n_evaluations, x| {
+                n_evaluations.fetch_add(1, Ordering::Relaxed);
+                (x[0] - 1.).powi(2) + (x[1] - 2.).powi(2)
+            },
+            |_, x| col![x[0] + x[1] - 3.],
+            |_, x| col![2. * (x[0] - 1.), 2. * (x[1] - 2.)],
+            |_, _| {
+                let triplets = [Triplet::new(0, 0, 1.), Triplet::new(0, 1, 1.)];
+                SparseColMat::try_new_from_triplets(1, 2, &triplets).unwrap()
+            },
+            Some(|_, _, y| {
+                let triplets = [Triplet::new(0, 0, 2. + y[0]), Triplet::new(1, 1, 2.)];
+                SparseColMat::try_new_from_triplets(2, 2, &triplets).unwrap()
+            }),
+            None,
+            None,
+        )
+    }
+
+    #[rstest]
+    #[case(0., 2)]
+    #[case(1e-8, 1)]
+    fn test_cached(#[case] tolerance: E, #[case] n_objective_evaluations: usize) {
+        let mut options = SolverOptions::new();
+        options
+            .set_option("nlp_cache_tolerance", tolerance)
+            .unwrap();
+        let nlp = cached(build_nlp(), &options);
+        let cache = nlp.get_context::<EvaluationCache>().unwrap();
+
+        let x = col![2., 4.];
+        assert_eq!(nlp.f(&x), 5.);
+        assert_eq!(nlp.f(&x), 5.);
+        assert_eq!(nlp.df(&x), col![2., 4.]);
+        assert_eq!(nlp.df(&x), col![2., 4.]);
+        assert_eq!((cache.get_n_evaluations(), cache.get_n_hits()), (2, 2));
+
+        // A nearby point is the same only with a positive tolerance
+        nlp.f(&col![2. + 1e-12, 4.]);
+        let n_evaluations = cache.get_program().get_context::<AtomicUsize>().unwrap();
+        assert_eq!(
+            n_evaluations.load(Ordering::Relaxed),
+            n_objective_evaluations
+        );
+
+        // The Hessian also depends on the multipliers
+        nlp.h(&x, &col![1.]).unwrap();
+        nlp.h(&x, &col![1.]).unwrap();
+        let h = nlp.h(&x, &col![2.]).unwrap();
+        assert_eq!(h.val_of_col(0), &[4.]);
+        assert_eq!(cache.get_n_hits(), 5 - n_objective_evaluations);
+
+        cache.clear();
+        nlp.f(&x);
+        assert_eq!(
+            n_evaluations.load(Ordering::Relaxed),
+            n_objective_evaluations + 1
+        );
+    }
+}
@@ -0,0 +1,141 @@
+//! Shared state of the functions of a [`NonlinearProgram`].
+//!
+//! The functions of a program built with
+//! [`NonlinearProgram::new_with_context`] receive a shared reference to a
+//! context, which may be used from several threads at once. State that the
+//! functions update therefore lives behind atomics, locks or a [`Memo`], which
+//! keeps an intermediate result at the last point it was computed at, so that
+//! `f`, `g`, `df` and `dg` evaluated at the same `x` compute it only once.
+//!
+//! [`NonlinearProgram`]: crate::nlp::NonlinearProgram
+//! [`NonlinearProgram::new_with_context`]: crate::nlp::NonlinearProgram::new_with_context
+
+use std::sync::{Arc, Mutex};
+
+use faer::Col;
+
+use crate::E;
+
+/// Value computed at one point, reused while the functions are evaluated at
+/// the same point.
+///
+/// Points are compared exactly. The value is computed without holding the
+/// lock, so concurrent evaluations at different points do not wait for each
+/// other; the memo then holds the value of the last point computed.
+#[derive(Debug)]
+pub struct Memo<T> {
+    entry: Mutex<Option<(Col<E>, Arc<T>)>>,
+}
+
+impl<T> Memo<T> {
+    pub fn new() -> Self {
+        Self {
+            entry: Mutex::new(None),
+        }
+    }
+
+    /// Returns the value at `x`, computed by `compute` unless the memo holds
+    /// it already.
+    pub fn get_or_compute(&self, x: &Col<E>, compute: impl FnOnce(&Col<E>) -> T) -> Arc<T> {
+        if let Some((point, value)) = self.entry.lock().unwrap().as_ref()
+            && point == x
+        {
+            return value.clone();
+        }
+
+        let value = Arc::new(compute(x));
+        *self.entry.lock().unwrap() = Some((x.clone(), value.clone()));
+        value
+    }
+
+    /// Discards the memoized value.
+    pub fn clear(&self) {
+        *self.entry.lock().unwrap() = None;
+    }
+}
+
+impl<T> Default for Memo<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use faer::{
+        col,
+        sparse::{SparseColMat, Triplet},
+    };
+    use rayon::prelude::*;
+
+    use super::*;
+    use crate::{I, nlp::NonlinearProgram};
+
+    /// Counts the evaluations of the residual `r(x) = x - (1, 2)` shared by
+    /// the objective `|r|^2` and its gradient `2 r`.
+    #[derive(Default)]
+    struct Context {
+        n_residuals: AtomicUsize,
+        residual: Memo<Col<E>>,
+    }
+
+    impl Context {
+        fn residual(&self, x: &Col<E>) -> Arc<Col<E>> {
+            self.residual.get_or_compute(x, |x| {
+                self.n_residuals.fetch_add(1, Ordering::Relaxed);
+                x - col![1., 2.]
+            })
+        }
+    }
+
+    fn build_nlp() -> NonlinearProgram {
+        NonlinearProgram::new_with_context(
+            2,
+            1,
+            Context::default(),
+            |context, x| context.residual(x).squared_norm_l2(),
+            |_, x| col![x[0] + x[1] - 3.],
+            |context, x| E::from(2.) * context.residual(x).as_ref(),
+            |_, _| {
+                let triplets = [Triplet::new(0, 0, 1.), Triplet::new(0, 1, 1.)];
+                SparseColMat::<I, E>::try_new_from_triplets(1, 2, &triplets).unwrap()
+            },
+            None,
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_memoized_context() {
+        let nlp = build_nlp();
+        let context = nlp.get_context::<Context>().unwrap();
+        assert!(nlp.get_context::<usize>().is_none());
+
+        let x = col![2., 4.];
+        assert_eq!(nlp.f(&x), 5.);
+        assert_eq!(nlp.df(&x), col![2., 4.]);
+        assert_eq!(context.n_residuals.load(Ordering::Relaxed), 1);
+
+        assert_eq!(nlp.f(&col![1., 2.]), 0.);
+        assert_eq!(context.n_residuals.load(Ordering::Relaxed), 2);
+        context.residual.clear();
+        assert_eq!(nlp.f(&col![1., 2.]), 0.);
+        assert_eq!(context.n_residuals.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn test_parallel_evaluation() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<NonlinearProgram>();
+
+        let nlp = build_nlp();
+        let values = (0..64)
+            .into_par_iter()
+            .map(|k| nlp.f(&col![1. + k as E, 2.]))
+            .collect::<Vec<_>>();
+        assert!(values.iter().enumerate().all(|(k, &f)| f == (k * k) as E));
+    }
+}
@@ -0,0 +1,210 @@
+//! Finite-difference checks of the derivatives of a [`NonlinearProgram`].
+//!
+//! [`NonlinearProgram::check_derivatives`] compares the gradient `df`, the
+//! Jacobian `dg` and the Hessian of the Lagrangian `h` with central differences
+//! of `f`, `g` and of the gradient of the Lagrangian `df + dg^T y`. The step of
+//! variable `j` is `eps^(1/3) max(1, |x_j|)`, and an entry mismatches if
+//!
+//! ```text
+//! |analytic - finite difference| > tol max(1, |analytic|, |finite difference|)
+//! ```
+//!
+//! The Hessian is expected as the full symmetric matrix
+//! `∇²f + sum_i y_i ∇²g_i`.
+
+use faer::{Col, Mat};
+
+use crate::{E, nlp::NonlinearProgram};
+
+/// Which derivative an entry belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Derivative {
+    Gradient,
+    Jacobian,
+    Hessian,
+}
+
+/// Entry of a derivative that differs from its finite difference.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DerivativeMismatch {
+    pub derivative: Derivative,
+    /// Row of the entry, zero for the gradient.
+    pub row: usize,
+    /// Variable the entry is differentiated by.
+    pub col: usize,
+    pub analytic: E,
+    pub finite_difference: E,
+    /// Difference relative to the larger of one and the two values.
+    pub error: E,
+}
+
+/// Result of [`NonlinearProgram::check_derivatives`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DerivativeCheck {
+    mismatches: Vec<DerivativeMismatch>,
+    max_error: E,
+}
+
+impl DerivativeCheck {
+    /// Whether every checked entry matches its finite difference.
+    pub fn is_valid(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    /// Mismatching entries, the largest error first.
+    pub fn get_mismatches(&self) -> &[DerivativeMismatch] {
+        &self.mismatches
+    }
+
+    /// Largest relative error over all checked entries.
+    pub fn get_max_error(&self) -> E {
+        self.max_error
+    }
+}
+
+impl NonlinearProgram {
+    /// Compares the derivatives of the program at `x` with finite differences,
+    /// with unit multipliers in the Hessian of the Lagrangian. See the
+    /// [module documentation](crate::nlp::derivatives).
+    pub fn check_derivatives(&self, x: &Col<E>, tol: E) -> DerivativeCheck {
+        self.check_derivatives_with_multipliers(x, &Col::ones(self.n_cons), tol)
+    }
+
+    /// Compares the derivatives of the program at `x` with finite differences,
+    /// with the multipliers `y` in the Hessian of the Lagrangian.
+    pub fn check_derivatives_with_multipliers(
+        &self,
+        x: &Col<E>,
+        y: &Col<E>,
+        tol: E,
+    ) -> DerivativeCheck {
+        let mut check = DerivativeCheck {
+            mismatches: Vec::new(),
+            max_error: E::from(0.),
+        };
+        let mut compare = |derivative, analytic: &Mat<E>, finite_difference: &Mat<E>| {
+            for j in 0..analytic.ncols() {
+                for i in 0..analytic.nrows() {
+                    let (a, d) = (analytic[(i, j)], finite_difference[(i, j)]);
+                    let error = (a - d).abs() / E::from(1.).max(a.abs()).max(d.abs());
+                    check.max_error = check.max_error.max(error);
+                    if error > tol || error.is_nan() {
+                        check.mismatches.push(DerivativeMismatch {
+                            derivative,
+                            row: i,
+                            col: j,
+                            analytic: a,
+                            finite_difference: d,
+                            error,
+                        });
+                    }
+                }
+            }
+        };
+
+        let df = self.df(x);
+        let gradient = central_difference(x, 1, |x| Col::from_fn(1, |_| self.f(x)));
+        compare(
+            Derivative::Gradient,
+            &Mat::from_fn(1, self.n_var, |_, j| df[j]),
+            &gradient,
+        );
+
+        let jacobian = central_difference(x, self.n_cons, |x| self.g(x));
+        compare(Derivative::Jacobian, &self.dg(x).to_dense(), &jacobian);
+
+        if let Some(h) = self.h(x, y) {
+            let lagrangian = |x: &Col<E>| self.df(x) + self.dg(x).transpose() * y;
+            let hessian = central_difference(x, self.n_var, lagrangian);
+            compare(Derivative::Hessian, &h.to_dense(), &hessian);
+        }
+
+        check.mismatches.sort_by(|a, b| b.error.total_cmp(&a.error));
+        check
+    }
+}
+
+/// Central-difference Jacobian of the function `f` with `m` values at `x`.
+fn central_difference(x: &Col<E>, m: usize, f: impl Fn(&Col<E>) -> Col<E>) -> Mat<E> {
+    let mut jacobian = Mat::zeros(m, x.nrows());
+    let mut point = x.clone();
+    for j in 0..x.nrows() {
+        let step = E::EPSILON.cbrt() * E::from(1.).max(x[j].abs());
+        point[j] = x[j] + step;
+        let forward = f(&point);
+        point[j] = x[j] - step;
+        let backward = f(&point);
+        point[j] = x[j];
+        for i in 0..m {
+            jacobian[(i, j)] = (forward[i] - backward[i]) / (E::from(2.) * step);
+        }
+    }
+    jacobian
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::{
+        col,
+        sparse::{SparseColMat, Triplet},
+    };
+
+    use super::*;
+    use crate::I;
+
+    /// `min x_0^2 x_1 + exp(x_1)` subject to `x_0^2 + x_1^2 = 1`, with the
+    /// derivative of `x_0^2 x_1` by `x_1` in the gradient given by `wrong`.
+    fn build_nlp(wrong: fn(&Col<E>) -> E) -> NonlinearProgram {
+        NonlinearProgram::new_with_context(
+            2,
+            1,
+            wrong,
+            |_, x| x[0] * x[0] * x[1] + x[1].exp(),
+            |_, x| col![x[0] * x[0] + x[1] * x[1] - 1.],
+            |wrong, x| col![2. * x[0] * x[1], wrong(x) + x[1].exp()],
+            |_, x| {
+                let triplets = [Triplet::new(0, 0, 2. * x[0]), Triplet::new(0, 1, 2. * x[1])];
+                SparseColMat::<I, E>::try_new_from_triplets(1, 2, &triplets).unwrap()
+            },
+            Some(|_, x, y| {
+                let triplets = [
+                    Triplet::new(0, 0, 2. * x[1] + 2. * y[0]),
+                    Triplet::new(1, 0, 2. * x[0]),
+                    Triplet::new(0, 1, 2. * x[0]),
+                    Triplet::new(1, 1, x[1].exp() + 2. * y[0]),
+                ];
+                SparseColMat::try_new_from_triplets(2, 2, &triplets).unwrap()
+            }),
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_check_derivatives() {
+        let x = col![0.25, -1.5];
+        let check = build_nlp(|x| x[0] * x[0]).check_derivatives(&x, 1e-6);
+        assert!(check.is_valid(), "{:?}", check.get_mismatches());
+        assert!(check.get_max_error() < 1e-6);
+
+        // x_0 instead of x_0^2 in the gradient, which the Hessian then disagrees with
+        let check = build_nlp(|x| x[0]).check_derivatives(&x, 1e-6);
+        assert!(!check.is_valid());
+        let find = |derivative, row, col| {
+            check
+                .get_mismatches()
+                .iter()
+                .find(|m| m.derivative == derivative && (m.row, m.col) == (row, col))
+                .unwrap()
+        };
+        let gradient = find(Derivative::Gradient, 0, 1);
+        assert!((gradient.analytic - (0.25 + (-1.5 as E).exp())).abs() < 1e-12);
+        assert!((gradient.finite_difference - (0.0625 + (-1.5 as E).exp())).abs() < 1e-6);
+        let hessian = find(Derivative::Hessian, 1, 0);
+        assert!((hessian.analytic - 0.5).abs() < 1e-12);
+        assert!((hessian.finite_difference - 1.).abs() < 1e-6);
+        // Sorted by error, the Hessian entry first
+        assert_eq!(check.get_mismatches()[0], *hessian);
+        assert_eq!(check.get_max_error(), hessian.error);
+    }
+}
@@ -1,15 +1,75 @@
 pub mod stepsize;
 
-use faer::{unzip, zip};
+use std::str::FromStr;
+
+use faer::{Col, unzip, zip};
 use macros::{explicit_options, use_option};
 use problemo::Problem;
 
 use crate::{
-    E, I, IterativeSolver, OptimizationProgram, SolverHooks, SolverOptions, SolverState, Status,
-    ipm,
-    nlp::{NLPSolver, NonlinearProgram, gd::stepsize::StepSize},
+    E, IterativeSolver, OptimizationProgram, OptionTrait, SolverHooks, SolverOptions, SolverState,
+    Status, ipm,
+    nlp::{NLPSolver, NonlinearProgram, gd::stepsize::StepSize, projection::Projection},
 };
 
+/// Update of the primal variables of [`GradientDescent`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum GDUpdate {
+    /// Gradient step followed by the Euclidean projection onto the bounds or
+    /// the projection set.
+    #[default]
+    Euclidean,
+    /// Exponentiated gradient step `x_j <- r x_j exp(-s_j) / sum_k x_k exp(-s_k)`
+    /// on the simplex `x >= 0`, `1^T x = r`, with `r` the `gd_simplex_radius`:
+    /// mirror descent with the entropy as distance generating function, whose
+    /// convergence depends only logarithmically on the dimension.
+    ExponentiatedGradient,
+}
+
+impl OptionTrait for GDUpdate {}
+
+impl FromStr for GDUpdate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "euclidean" | "projected" => Ok(GDUpdate::Euclidean),
+            "exponentiated_gradient" | "mirror" | "entropic" => Ok(GDUpdate::ExponentiatedGradient),
+            _ => Err(format!("Invalid gradient descent update: {}", s)),
+        }
+    }
+}
+
+/// Momentum of the Euclidean update of [`GradientDescent`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum GDMomentum {
+    /// Plain gradient steps.
+    #[default]
+    None,
+    /// Heavy ball (Polyak) momentum: the step from `x_k` adds
+    /// `gd_momentum_coefficient (x_k - x_{k-1})`.
+    HeavyBall,
+    /// Nesterov acceleration: the gradient is evaluated at the extrapolation
+    /// `x_k + beta_k (x_k - x_{k-1})`, with `beta_k = (t_k - 1) / t_{k+1}` and
+    /// `t_{k+1} = (1 + sqrt(1 + 4 t_k^2)) / 2` as in FISTA.
+    Nesterov,
+}
+
+impl OptionTrait for GDMomentum {}
+
+impl FromStr for GDMomentum {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(GDMomentum::None),
+            "heavy_ball" | "polyak" => Ok(GDMomentum::HeavyBall),
+            "nesterov" | "fista" => Ok(GDMomentum::Nesterov),
+            _ => Err(format!("Invalid gradient descent momentum: {}", s)),
+        }
+    }
+}
+
 /// Projected gradient descent solver for nonlinear programs.
 ///
 /// Solves problems of the form:
@@ -22,37 +82,139 @@ use crate::{
 /// Uses a primal-dual gradient step: the primal variables `x` are updated along
 /// the negative gradient of the Lagrangian, while the dual variables `y` are
 /// updated via a gradient ascent step on the constraint violation. After each
-/// step, `x` is projected onto the box `[l, u]`.
+/// step, `x` is projected onto the box `[l, u]`, or onto the set of the
+/// [`Projection`] given with [`GradientDescent::with_projection`], e.g. a
+/// simplex. The projection replaces the box, which it should include if the
+/// program has bounds.
+///
+/// For simplex-constrained programs, the `gd_update` option selects the
+/// exponentiated gradient (mirror descent) update of [`GDUpdate`] instead,
+/// which keeps the iterates in the simplex without a projection.
+///
+/// The `gd_momentum` option accelerates the Euclidean update by heavy ball
+/// momentum or Nesterov acceleration, see [`GDMomentum`]. With
+/// `gd_adaptive_restart`, the momentum restarts from zero whenever the step
+/// moves against the gradient (O'Donoghue and Candès, 2015), which avoids the
+/// oscillations of accelerated methods on strongly convex problems.
 ///
 /// The step size strategy is configurable via the [`StepSize`] trait (e.g.
-/// constant, linear decay, quadratic decay).
-#[explicit_options(name = SolverOptions)]
+/// constant, linear decay, quadratic decay). The normalized and AdaGrad
+/// strategies adapt the step to the magnitude of the gradient, globally or per
+/// variable, so that badly scaled problems need no tuned learning rate; the
+/// [`NLPSolverBuilder`](crate::nlp::NLPSolverBuilder) selects the strategy with
+/// the `step_size` option, see [`StepSizeType`](stepsize::StepSizeType).
+#[explicit_options(name = SolverOptions, scope = "nlp.gd")]
 #[use_option(name="learning_rate", type_=E, default="0.1", description="Learning rate for gradient descent.")]
-#[use_option(name="max_iterations", type_=I, description="Maximum number of iterations for gradient descent.")]
+#[use_option(name="max_iterations", type_=usize, description="Maximum number of iterations for gradient descent.")]
+#[use_option(name = "gd_update", type_ = crate::nlp::gd::GDUpdate, default = "euclidean", description = "Update of the primal variables of gradient descent: euclidean or exponentiated_gradient.")]
+#[use_option(name = "gd_simplex_radius", type_ = E, default = "1", description = "Sum of the variables of the simplex of the exponentiated gradient update.")]
+#[use_option(name = "gd_momentum", type_ = crate::nlp::gd::GDMomentum, default = "none", description = "Momentum of the euclidean gradient descent update: none, heavy_ball or nesterov.")]
+#[use_option(name = "gd_momentum_coefficient", type_ = E, default = "0.9", description = "Weight of the previous step in the heavy ball update of gradient descent.")]
+#[use_option(name = "gd_adaptive_restart", type_ = bool, default = "true", description = "Whether the momentum of gradient descent restarts when the step moves against the gradient.")]
 pub struct GradientDescent<'a, SS: StepSize> {
     nlp: &'a NonlinearProgram,
     step: SS,
+    projection: Option<Box<dyn Projection>>,
+
+    /// Previous iterate `x_{k-1}`, `None` after a restart of the momentum.
+    previous: Option<Col<E>>,
+    /// Nesterov sequence `t_k`.
+    t: E,
 }
 
 impl<'a, SS: StepSize> GradientDescent<'a, SS> {
+    /// Projects the iterates onto the set of `projection` instead of the bounds.
+    pub fn with_projection(mut self, projection: Box<dyn Projection>) -> Self {
+        self.projection = Some(projection);
+        self
+    }
+
     /// Performs a single projected primal-dual gradient descent iteration.
     ///
     /// Updates `state.x` and `state.y` by taking a gradient step on the
     /// Lagrangian, projects `x` onto the bound constraints, and computes
     /// primal/dual infeasibility measures.
     fn iterate(&mut self, state: &mut SolverState) -> Result<Status, Problem> {
-        state.df = Some(self.nlp.df(&state.x));
-        state.g = Some(self.nlp.g(&state.x));
-        state.dg = Some(self.nlp.dg(&state.x));
+        let momentum = if self.options.gd_update == GDUpdate::Euclidean {
+            self.options.gd_momentum
+        } else {
+            GDMomentum::None
+        };
+        let current = state.x.clone();
+        let velocity = self
+            .previous
+            .as_ref()
+            .map(|previous| &current - previous)
+            .filter(|_| momentum != GDMomentum::None);
+
+        // Nesterov evaluates the gradient at the extrapolated point
+        let t_next = (E::from(1.) + (E::from(1.) + E::from(4.) * self.t * self.t).sqrt()) / 2.;
+        if momentum == GDMomentum::Nesterov
+            && let Some(velocity) = &velocity
+        {
+            state.x += ((self.t - E::from(1.)) / t_next) * velocity;
+        }
+
+        crate::profile!("evaluate", {
+            state.df = Some(self.nlp.df(&state.x));
+            state.g = Some(self.nlp.g(&state.x));
+            state.dg = Some(self.nlp.dg(&state.x));
+        });
 
         state.dL =
             Some(state.df.as_ref().unwrap() + state.dg.as_ref().unwrap().transpose() * &state.y); // Gradient of the Lagrangian w.r.t. x
 
-        let step_size = self.step.compute(state);
-        state.x -= step_size * state.dL.as_ref().unwrap(); // Simple gradient step on the objective
+        let step_size = crate::profile!("line_search", self.step.compute(state));
+        let step = self.step.scale(state, step_size);
         state.y += step_size * state.g.as_ref().unwrap(); // Simple gradient step on the constraints
 
-        // Ensure feasibility of the primal variables
+        // Gradient step on the objective, keeping the primal variables feasible
+        if self.options.gd_update == GDUpdate::ExponentiatedGradient {
+            self.exponentiated_step(state, &step);
+        } else {
+            state.x -= &step;
+            if momentum == GDMomentum::HeavyBall
+                && let Some(velocity) = &velocity
+            {
+                state.x += self.options.gd_momentum_coefficient * velocity;
+            }
+            match &self.projection {
+                Some(projection) => projection.project(&mut state.x),
+                None => self.project_bounds(state),
+            }
+        }
+
+        // Restart if the iterate moves against the gradient at the evaluated point
+        let restart = self.options.gd_adaptive_restart
+            && step.transpose() * (&state.x - &current) > E::from(0.);
+        if restart {
+            self.previous = None;
+            self.t = E::from(1.);
+        } else {
+            self.previous = Some(current);
+            self.t = t_next;
+        }
+
+        // Update the state
+        self.nlp.update_residual(state);
+
+        state.alpha_primal = step_size;
+        state.alpha_dual = step_size;
+
+        Ok(Status::InProgress) // Placeholder for actual status based on convergence criteria
+    }
+
+    /// Multiplies `x` by `exp(-step)` and rescales it onto the simplex.
+    fn exponentiated_step(&self, state: &mut SolverState, step: &Col<E>) {
+        // Shifting the exponents by their minimum avoids overflow and cancels
+        // out in the normalization
+        let shift = step.iter().copied().fold(E::INFINITY, E::min);
+        zip!(&mut state.x, step).for_each(|unzip!(x_i, s_i)| *x_i *= (shift - *s_i).exp());
+        let sum = state.x.sum();
+        state.x *= self.options.gd_simplex_radius / sum;
+    }
+
+    fn project_bounds(&self, state: &mut SolverState) {
         if let Some(l) = self.nlp.l() {
             zip!(&mut state.x, l).for_each(|unzip!(x_i, l_i)| {
                 if *x_i < *l_i {
@@ -67,14 +229,6 @@ impl<'a, SS: StepSize> GradientDescent<'a, SS> {
                 }
             });
         }
-
-        // Update the state
-        self.nlp.update_residual(state);
-
-        state.alpha_primal = step_size;
-        state.alpha_dual = step_size;
-
-        Ok(Status::InProgress) // Placeholder for actual status based on convergence criteria
     }
 }
 
@@ -84,12 +238,37 @@ impl<'a, SS: StepSize> NLPSolver<'a> for GradientDescent<'a, SS> {
         Self {
             nlp,
             step: SS::new(options),
+            projection: None,
+            previous: None,
+            t: E::from(1.),
             options: options.into(),
         }
     }
 }
 
 impl<'a, SS: StepSize> IterativeSolver for GradientDescent<'a, SS> {
+    fn initialize(&mut self, state: &mut SolverState) {
+        self.previous = None;
+        self.t = E::from(1.);
+
+        // The exponentiated gradient update keeps zero entries at zero, so it
+        // starts from the center of the simplex unless x is in its interior
+        if self.options.gd_update == GDUpdate::ExponentiatedGradient {
+            let n = state.x.nrows();
+            let radius = self.options.gd_simplex_radius;
+            if state
+                .x
+                .iter()
+                .any(|x_i| !x_i.is_finite() || *x_i <= E::from(0.))
+            {
+                state.x = Col::full(n, radius / n as E);
+            } else {
+                let sum = state.x.sum();
+                state.x *= radius / sum;
+            }
+        }
+    }
+
     fn get_max_iterations(&self) -> usize {
         if self.options.max_iterations as usize > 0 {
             self.options.max_iterations as usize
@@ -98,6 +277,10 @@ impl<'a, SS: StepSize> IterativeSolver for GradientDescent<'a, SS> {
         }
     }
 
+    fn get_options(&self) -> Option<&SolverOptions> {
+        Some(&self.options.root)
+    }
+
     fn get_program(&self) -> &dyn OptimizationProgram {
         self.nlp
     }
@@ -109,11 +292,20 @@ impl<'a, SS: StepSize> IterativeSolver for GradientDescent<'a, SS> {
 
 #[cfg(test)]
 mod tests {
-    use faer::sparse::{SparseColMat, Triplet};
+    use faer::{
+        Col, col,
+        sparse::{SparseColMat, Triplet},
+    };
+    use rstest::rstest;
 
     use crate::{
-        callback::ConvergenceOutput, nlp::gd::stepsize::ConstantStepSize,
-        terminators::SlowProgressTerminator,
+        I,
+        callback::{ConvergenceOutput, NoOpCallback},
+        nlp::{
+            gd::stepsize::{ConstantStepSize, StepSizeType},
+            projection::SimplexProjection,
+        },
+        terminators::{NullTerminator, ProjectedGradientTerminator, SlowProgressTerminator},
     };
 
     use super::*;
@@ -149,7 +341,7 @@ mod tests {
 
         let options = SolverOptions::new();
         let mut properties = SolverHooks {
-            callback: Box::new(ConvergenceOutput::new()),
+            callback: Box::new(ConvergenceOutput::new(&options)),
             terminator: Box::new(SlowProgressTerminator::new(&options)),
         };
 
@@ -159,4 +351,222 @@ mod tests {
         assert!((state.x[0] - 1.0).abs() < 1e-3);
         assert!((state.x[1] - 2.0).abs() < 1e-3);
     }
+
+    /// Solves `min scale (1e3 (x_0 - 1)^2 + (x_1 - 2)^2)` for at most
+    /// `iterations` iterations. The curvatures differ by 1e3, so that no
+    /// constant step converges in both variables quickly.
+    fn solve_badly_scaled(
+        scale: E,
+        step_size: StepSizeType,
+        iterations: usize,
+        terminator: Box<dyn crate::terminators::Terminator>,
+    ) -> (Status, Col<E>) {
+        let nlp = NonlinearProgram::new_boxed(
+            2,
+            0,
+            Box::new(move |x| scale * (1e3 * (x[0] - 1.0).powi(2) + (x[1] - 2.0).powi(2))),
+            Box::new(|_x| Col::zeros(0)),
+            Box::new(move |x| {
+                vec![scale * 2e3 * (x[0] - 1.0), scale * 2.0 * (x[1] - 2.0)]
+                    .into_iter()
+                    .collect()
+            }),
+            Box::new(|_x| SparseColMat::<I, E>::try_new_from_triplets(0, 2, &[]).unwrap()),
+            None,
+            None,
+            None,
+        );
+
+        let mut options = SolverOptions::new();
+        options.set_option("step_size", step_size).unwrap();
+        options.set_option("learning_rate", 0.5).unwrap();
+        options.set_option("max_iterations", iterations).unwrap();
+        let mut hooks = SolverHooks {
+            callback: Box::new(NoOpCallback::new()),
+            terminator,
+        };
+
+        let mut state =
+            SolverState::new(Col::zeros(2), Col::zeros(0), Col::zeros(2), Col::zeros(2));
+        let status = nlp
+            .solver_builder()
+            .with_options(options)
+            .build()
+            .unwrap()
+            .solve(&mut state, &mut hooks)
+            .unwrap();
+        (status, state.x)
+    }
+
+    #[test]
+    fn test_adagrad_badly_scaled() {
+        let options = SolverOptions::new();
+        let terminator = Box::new(SlowProgressTerminator::new(&options));
+        let (status, x) = solve_badly_scaled(1., StepSizeType::AdaGrad, 5000, terminator);
+        assert_eq!(status, Status::Optimal);
+        assert!((x[0] - 1.0).abs() < 1e-3);
+        assert!((x[1] - 2.0).abs() < 1e-3);
+    }
+
+    #[rstest]
+    #[case(StepSizeType::Normalized)]
+    #[case(StepSizeType::AdaGrad)]
+    fn test_scale_invariance(#[case] step_size: StepSizeType) {
+        // Scaling the objective leaves the iterates unchanged
+        let options = SolverOptions::new();
+        let null = || Box::new(NullTerminator::new(&options));
+        let (_, x) = solve_badly_scaled(1., step_size, 5000, null());
+        let (_, scaled) = solve_badly_scaled(1e3, step_size, 5000, null());
+        assert!((&x - &scaled).norm_max() < 1e-6);
+        assert!((x[0] - 1.0).abs() < 1e-2);
+        assert!((x[1] - 2.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_simplex_projection() {
+        // The closest point of the probability simplex to (0.8, 0.6, -0.2)
+        let nlp = NonlinearProgram::new(
+            3,
+            0,
+            |x| (x - col![0.8, 0.6, -0.2]).squared_norm_l2(),
+            |_x| Col::zeros(0),
+            |x| 2. * (x - col![0.8, 0.6, -0.2]),
+            |_x| SparseColMat::<I, E>::try_new_from_triplets(0, 3, &[]).unwrap(),
+            None,
+            None,
+            None,
+        );
+
+        let options = SolverOptions::new();
+        let mut hooks = SolverHooks {
+            callback: Box::new(NoOpCallback::new()),
+            terminator: Box::new(SlowProgressTerminator::new(&options)),
+        };
+        let mut state = SolverState::new(
+            Col::full(3, 1. / 3.),
+            Col::zeros(0),
+            Col::zeros(3),
+            Col::zeros(3),
+        );
+        let status = nlp
+            .solver_builder()
+            .with_projection(Box::new(SimplexProjection::default()))
+            .build()
+            .unwrap()
+            .solve(&mut state, &mut hooks)
+            .unwrap();
+        assert_eq!(status, Status::Optimal);
+        assert!((&state.x - col![0.6, 0.4, 0.]).norm_max() < 1e-6);
+    }
+
+    #[test]
+    fn test_exponentiated_gradient() {
+        // (0.9, 0.6, 0.5) lies in the simplex of radius 2, from whose center the
+        // iterates start
+        let nlp = NonlinearProgram::new(
+            3,
+            0,
+            |x| (x - col![0.9, 0.6, 0.5]).squared_norm_l2(),
+            |_x| Col::zeros(0),
+            |x| 2. * (x - col![0.9, 0.6, 0.5]),
+            |_x| SparseColMat::<I, E>::try_new_from_triplets(0, 3, &[]).unwrap(),
+            None,
+            None,
+            None,
+        );
+
+        let mut options = SolverOptions::new();
+        options
+            .set_option("gd_update", GDUpdate::ExponentiatedGradient)
+            .unwrap();
+        options.set_option("gd_simplex_radius", 2.).unwrap();
+        let mut hooks = SolverHooks {
+            callback: Box::new(NoOpCallback::new()),
+            terminator: Box::new(SlowProgressTerminator::new(&options)),
+        };
+        let mut state =
+            SolverState::new(Col::zeros(3), Col::zeros(0), Col::zeros(3), Col::zeros(3));
+        let mut solver = GradientDescent::<ConstantStepSize>::new(&nlp, &options);
+        let status = solver.solve(&mut state, &mut hooks).unwrap();
+        assert_eq!(status, Status::Optimal);
+        assert!((state.x.sum() - 2.).abs() < 1e-12);
+        assert!((&state.x - col![0.9, 0.6, 0.5]).norm_max() < 1e-6);
+    }
+
+    /// Iterations to solve `min 1/2 (x_0^2 + 100 x_1^2)` from (1, 1) with the
+    /// step 1/L = 0.01.
+    fn solve_ill_conditioned(momentum: GDMomentum, restart: bool) -> usize {
+        let nlp = NonlinearProgram::new(
+            2,
+            0,
+            |x| 0.5 * (x[0] * x[0] + 100. * x[1] * x[1]),
+            |_x| Col::zeros(0),
+            |x| col![x[0], 100. * x[1]],
+            |_x| SparseColMat::<I, E>::try_new_from_triplets(0, 2, &[]).unwrap(),
+            None,
+            None,
+            None,
+        );
+
+        let mut options = SolverOptions::new();
+        options.set_option("learning_rate", 0.01).unwrap();
+        options.set_option("gd_momentum", momentum).unwrap();
+        options.set_option("gd_adaptive_restart", restart).unwrap();
+        options.set_option("max_iterations", 10000usize).unwrap();
+        options
+            .set_option("slow_progress_tolerance", 1e-10)
+            .unwrap();
+        let mut hooks = SolverHooks {
+            callback: Box::new(NoOpCallback::new()),
+            terminator: Box::new(SlowProgressTerminator::new(&options)),
+        };
+        let mut state = SolverState::new(col![1., 1.], Col::zeros(0), Col::zeros(2), Col::zeros(2));
+        let mut solver = GradientDescent::<ConstantStepSize>::new(&nlp, &options);
+        let status = solver.solve(&mut state, &mut hooks).unwrap();
+        assert_eq!(status, Status::Optimal);
+        assert!(state.x.norm_max() < 1e-6);
+        state.get_nit()
+    }
+
+    #[rstest]
+    #[case(GDMomentum::HeavyBall, false)]
+    #[case(GDMomentum::HeavyBall, true)]
+    #[case(GDMomentum::Nesterov, true)]
+    fn test_momentum(#[case] momentum: GDMomentum, #[case] restart: bool) {
+        let plain = solve_ill_conditioned(GDMomentum::None, false);
+        let accelerated = solve_ill_conditioned(momentum, restart);
+        assert!(3 * accelerated < plain);
+    }
+
+    #[test]
+    fn test_projected_gradient_termination() {
+        // The minimizer (1, 0.5) has x_0 at its upper bound, where the gradient,
+        // and thus the dual residual, does not vanish
+        let nlp = NonlinearProgram::new(
+            2,
+            0,
+            |x| (x[0] - 2.).powi(2) + (x[1] - 0.5).powi(2),
+            |_x| Col::zeros(0),
+            |x| col![2. * (x[0] - 2.), 2. * (x[1] - 0.5)],
+            |_x| SparseColMat::<I, E>::try_new_from_triplets(0, 2, &[]).unwrap(),
+            None,
+            Some(col![0., 0.]),
+            Some(col![1., 1.]),
+        );
+
+        let options = SolverOptions::new();
+        let terminator =
+            ProjectedGradientTerminator::new(&options).with_bounds(col![0., 0.], col![1., 1.]);
+        let mut hooks = SolverHooks {
+            callback: Box::new(NoOpCallback::new()),
+            terminator: Box::new(terminator),
+        };
+        let mut state =
+            SolverState::new(Col::zeros(2), Col::zeros(0), Col::zeros(2), Col::zeros(2));
+        let mut solver = GradientDescent::<ConstantStepSize>::new(&nlp, &options);
+        let status = solver.solve(&mut state, &mut hooks).unwrap();
+        assert_eq!(status, Status::Optimal);
+        assert!((&state.x - col![1., 0.5]).norm_max() < 1e-6);
+        assert!(state.get_dual_feasibility().norm_max() > 1.);
+    }
 }
@@ -1,8 +1,10 @@
+use std::str::FromStr;
+
 use macros::{explicit_options, use_option};
 
 use faer::{Col, unzip, zip};
 
-use crate::{E, SolverOptions, SolverState};
+use crate::{E, OptionTrait, SolverOptions, SolverState};
 
 /// Strategy for computing the step size at each gradient descent iteration.
 pub trait StepSize {
@@ -13,10 +15,54 @@ pub trait StepSize {
 
     /// Computes the step size for the current iteration.
     fn compute(&mut self, state: &SolverState) -> E;
+
+    /// Step subtracted from `x`, given the step size `step` of [`StepSize::compute`]. Defaults to
+    /// `step` times the gradient of the Lagrangian; per-variable strategies scale each entry.
+    fn scale(&mut self, state: &SolverState, step: E) -> Col<E> {
+        step * state.dL.as_ref().unwrap()
+    }
+}
+
+/// Step size strategy of [`GradientDescent`](super::GradientDescent) built by the
+/// [`NLPSolverBuilder`](crate::nlp::NLPSolverBuilder), selected by the `step_size` option.
+///
+/// - `Constant`: [`ConstantStepSize`].
+/// - `LinearDecay`: [`LinearDecayStepSize`].
+/// - `QuadraticDecay`: [`QuadraticDecayStepSize`].
+/// - `BarzilaiBorwein`: [`BarzilaiBorweinStepSize`].
+/// - `Normalized`: [`NormalizedStepSize`], independent of the scale of the objective.
+/// - `AdaGrad`: [`AdaGradStepSize`], independent of the scale of each variable.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum StepSizeType {
+    #[default]
+    Constant,
+    LinearDecay,
+    QuadraticDecay,
+    BarzilaiBorwein,
+    Normalized,
+    AdaGrad,
+}
+
+impl OptionTrait for StepSizeType {}
+
+impl FromStr for StepSizeType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "constant" => Ok(StepSizeType::Constant),
+            "linear_decay" => Ok(StepSizeType::LinearDecay),
+            "quadratic_decay" => Ok(StepSizeType::QuadraticDecay),
+            "barzilai_borwein" | "bb" => Ok(StepSizeType::BarzilaiBorwein),
+            "normalized" => Ok(StepSizeType::Normalized),
+            "adagrad" => Ok(StepSizeType::AdaGrad),
+            _ => Err(format!("Invalid step size: {}", s)),
+        }
+    }
 }
 
 /// Constant step size: `α_k = learning_rate` for all `k`.
-#[explicit_options(name = SolverOptions)]
+#[explicit_options(name = SolverOptions, scope = "nlp.gd")]
 #[use_option(name = "learning_rate", type_ = E, description = "Constant learning rate for gradient descent.")]
 pub struct ConstantStepSize {}
 
@@ -33,7 +79,7 @@ impl StepSize for ConstantStepSize {
 }
 
 /// Linear decay step size: `α_k = learning_rate / (1 + k)`.
-#[explicit_options(name = SolverOptions)]
+#[explicit_options(name = SolverOptions, scope = "nlp.gd")]
 #[use_option(name = "learning_rate", type_ = E, description = "Initial learning rate for linear decay step size.")]
 pub struct LinearDecayStepSize {}
 
@@ -50,7 +96,7 @@ impl StepSize for LinearDecayStepSize {
 }
 
 /// Quadratic decay step size: `α_k = learning_rate / (1 + k²)`.
-#[explicit_options(name = SolverOptions)]
+#[explicit_options(name = SolverOptions, scope = "nlp.gd")]
 #[use_option(name = "learning_rate", type_ = E, description = "Initial learning rate for quadratic decay step size.")]
 pub struct QuadraticDecayStepSize {}
 
@@ -66,7 +112,7 @@ impl StepSize for QuadraticDecayStepSize {
     }
 }
 
-#[explicit_options(name = SolverOptions)]
+#[explicit_options(name = SolverOptions, scope = "nlp.gd")]
 pub struct BarzilaiBorweinStepSize {
     prev_x: Option<Col<E>>,
     prev_grad: Option<Col<E>>,
@@ -107,3 +153,66 @@ impl StepSize for BarzilaiBorweinStepSize {
         step
     }
 }
+
+/// Normalized step size: `α_k = learning_rate / ((1 + k) ‖∇L‖₂)`.
+///
+/// The step moves `x` by `learning_rate / (1 + k)` whatever the magnitude of the gradient,
+/// so that scaling the objective does not change the iterates. Gradients below
+/// `step_size_epsilon` are not normalized.
+#[explicit_options(name = SolverOptions, scope = "nlp.gd")]
+#[use_option(name = "learning_rate", type_ = E, description = "Length of the first normalized gradient step.")]
+#[use_option(name = "step_size_epsilon", type_ = E, default = "1e-8", description = "Safeguard against division by zero of the normalized and AdaGrad step sizes.")]
+pub struct NormalizedStepSize {}
+
+impl StepSize for NormalizedStepSize {
+    fn new(options: &SolverOptions) -> Self {
+        Self {
+            options: options.into(),
+        }
+    }
+
+    fn compute(&mut self, state: &SolverState) -> E {
+        let norm = state.dL.as_ref().map_or(E::from(0.), |dl| dl.norm_l2());
+        self.options.learning_rate
+            / ((1. + state.nit as E) * norm.max(self.options.step_size_epsilon))
+    }
+}
+
+/// AdaGrad step size: `x_j` moves by `learning_rate ∇L_j / (sqrt(G_j) + epsilon)`, where `G_j`
+/// is the sum of the squares of all gradients `∇L_j` so far (Duchi et al., 2011).
+///
+/// Each variable gets its own step, inversely proportional to the magnitude of its gradients, so
+/// that rescaling a variable or the objective does not require another learning rate. The dual
+/// step on the constraints is `learning_rate`.
+#[explicit_options(name = SolverOptions, scope = "nlp.gd")]
+#[use_option(name = "learning_rate", type_ = E, description = "Learning rate of the AdaGrad step size.")]
+#[use_option(name = "step_size_epsilon", type_ = E, default = "1e-8", description = "Safeguard against division by zero of the normalized and AdaGrad step sizes.")]
+pub struct AdaGradStepSize {
+    /// Sums of the squared gradients of each variable.
+    squared: Option<Col<E>>,
+}
+
+impl StepSize for AdaGradStepSize {
+    fn new(options: &SolverOptions) -> Self {
+        Self {
+            squared: None,
+
+            options: options.into(),
+        }
+    }
+
+    fn compute(&mut self, _state: &SolverState) -> E {
+        self.options.learning_rate
+    }
+
+    fn scale(&mut self, state: &SolverState, step: E) -> Col<E> {
+        let gradient = state.dL.as_ref().unwrap();
+        let squared = self
+            .squared
+            .get_or_insert_with(|| Col::zeros(gradient.nrows()));
+        zip!(squared.as_mut(), gradient).for_each(|unzip!(s_i, g_i)| *s_i += g_i * g_i);
+
+        let epsilon = self.options.step_size_epsilon;
+        zip!(gradient, &*squared).map(|unzip!(g_i, s_i)| step * g_i / (s_i.sqrt() + epsilon))
+    }
+}
@@ -1,7 +1,12 @@
+pub mod cache;
+pub mod context;
+pub mod derivatives;
 pub mod gd;
 pub mod ipm;
+pub mod multistart;
+pub mod projection;
 
-use std::str::FromStr;
+use std::{any::Any, str::FromStr, sync::Arc};
 
 use faer::{Col, sparse::SparseColMat};
 use macros::use_option;
@@ -9,7 +14,7 @@ use problemo::{Problem, common::IntoCommonProblem};
 
 use crate::{
     E, I, IterativeSolver, OptimizationProgram, OptionTrait, SolverOptions, SolverState,
-    linalg::vector_ops::cwise_multiply_finite,
+    linalg::vector_ops::cwise_multiply_finite, registry,
 };
 
 /// A nonlinear program of the form:
@@ -24,37 +29,57 @@ use crate::{
 /// `df` is the gradient of `f`, `dg` is the Jacobian of `g`, and `h` is the
 /// (optional) Hessian of the Lagrangian. The vectors `l` and `u` are optional
 /// lower and upper bounds on the decision variables.
+///
+/// The functions are `Send + Sync`, so a program can be evaluated from several
+/// threads. Data shared by the functions, such as counters or intermediate
+/// results memoized with [`context::Memo`], is passed as a context to
+/// [`NonlinearProgram::new_with_context`].
 #[allow(unused)]
 #[use_option(name = "nlp_solver_type", type_ = crate::nlp::NLPSolverType, default = "gradient_descent", description = "Type of NLP solver to use.")]
 pub struct NonlinearProgram {
     /// Number of decision variables.
-    n_var: I,
+    n_var: usize,
     /// Number of equality constraints.
-    n_cons: I,
+    n_cons: usize,
 
     /// Objective function `f(x) -> scalar`.
-    f: Box<dyn Fn(&Col<E>) -> E>,
+    f: ScalarFn,
     /// Equality constraint function `g(x) -> Col`.
-    g: Box<dyn Fn(&Col<E>) -> Col<E>>,
+    g: VectorFn,
     /// Gradient of the objective `∇f(x)`.
-    df: Box<dyn Fn(&Col<E>) -> Col<E>>,
+    df: VectorFn,
     /// Jacobian of the constraints `∇g(x)` (sparse).
-    dg: Box<dyn Fn(&Col<E>) -> SparseColMat<I, E>>,
+    dg: MatrixFn,
     /// Hessian of the Lagrangian `∇²L(x, y)` (optional, sparse).
-    h: Option<Box<dyn Fn(&Col<E>, &Col<E>) -> SparseColMat<I, E>>>,
+    h: Option<HessianFn>,
 
     /// Lower bounds on the decision variables (optional).
     l: Option<Col<E>>,
     /// Upper bounds on the decision variables (optional).
     u: Option<Col<E>>,
+
+    /// Context shared by the functions (optional).
+    context: Option<Arc<dyn Any + Send + Sync>>,
 }
 
+/// Scalar function of the decision variables.
+pub type ScalarFn = Box<dyn Fn(&Col<E>) -> E + Send + Sync>;
+/// Vector function of the decision variables.
+pub type VectorFn = Box<dyn Fn(&Col<E>) -> Col<E> + Send + Sync>;
+/// Sparse matrix function of the decision variables.
+pub type MatrixFn = Box<dyn Fn(&Col<E>) -> SparseColMat<I, E> + Send + Sync>;
+/// Sparse matrix function of the decision variables and the multipliers.
+pub type HessianFn = Box<dyn Fn(&Col<E>, &Col<E>) -> SparseColMat<I, E> + Send + Sync>;
+/// Hessian of a program with a shared context, see
+/// [`NonlinearProgram::new_with_context`].
+pub type ContextHessianFn<C> = fn(&C, &Col<E>, &Col<E>) -> SparseColMat<I, E>;
+
 #[allow(unused)]
 impl NonlinearProgram {
     /// Creates a new nonlinear program from its component functions and bounds.
     pub fn new(
-        n_var: I,
-        n_cons: I,
+        n_var: usize,
+        n_cons: usize,
         f: fn(&Col<E>) -> E,
         g: fn(&Col<E>) -> Col<E>,
         df: fn(&Col<E>) -> Col<E>,
@@ -65,7 +90,7 @@ impl NonlinearProgram {
     ) -> Self {
         let h = {
             if let Some(h_fn) = h {
-                Some(Box::new(h_fn) as Box<dyn Fn(&Col<E>, &Col<E>) -> SparseColMat<I, E>>)
+                Some(Box::new(h_fn) as HessianFn)
             } else {
                 None
             }
@@ -81,20 +106,74 @@ impl NonlinearProgram {
             h,
             l,
             u,
+            context: None,
         }
     }
 
     pub fn new_boxed(
-        n_var: I,
-        n_cons: I,
-        f: Box<dyn Fn(&Col<E>) -> E>,
-        g: Box<dyn Fn(&Col<E>) -> Col<E>>,
-        df: Box<dyn Fn(&Col<E>) -> Col<E>>,
-        dg: Box<dyn Fn(&Col<E>) -> SparseColMat<I, E>>,
-        h: Option<Box<dyn Fn(&Col<E>, &Col<E>) -> SparseColMat<I, E>>>,
+        n_var: usize,
+        n_cons: usize,
+        f: ScalarFn,
+        g: VectorFn,
+        df: VectorFn,
+        dg: MatrixFn,
+        h: Option<HessianFn>,
+        l: Option<Col<E>>,
+        u: Option<Col<E>>,
+    ) -> Self {
+        Self {
+            n_var,
+            n_cons,
+            f,
+            g,
+            df,
+            dg,
+            h,
+            l,
+            u,
+            context: None,
+        }
+    }
+
+    /// Creates a nonlinear program whose functions receive the shared
+    /// `context` as their first argument.
+    ///
+    /// The context is shared between threads, so state that the functions
+    /// update, such as evaluation counters or a [`context::Memo`] of the
+    /// intermediate results at the current point, needs interior mutability.
+    /// It is retrieved with [`NonlinearProgram::get_context`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_with_context<C: Send + Sync + 'static>(
+        n_var: usize,
+        n_cons: usize,
+        context: C,
+        f: fn(&C, &Col<E>) -> E,
+        g: fn(&C, &Col<E>) -> Col<E>,
+        df: fn(&C, &Col<E>) -> Col<E>,
+        dg: fn(&C, &Col<E>) -> SparseColMat<I, E>,
+        h: Option<ContextHessianFn<C>>,
         l: Option<Col<E>>,
         u: Option<Col<E>>,
     ) -> Self {
+        let context = Arc::new(context);
+        let bind = |function: fn(&C, &Col<E>) -> Col<E>| -> VectorFn {
+            let context = context.clone();
+            Box::new(move |x| function(&context, x))
+        };
+        let (g, df) = (bind(g), bind(df));
+        let f = {
+            let context = context.clone();
+            Box::new(move |x: &Col<E>| f(&context, x))
+        };
+        let dg = {
+            let context = context.clone();
+            Box::new(move |x: &Col<E>| dg(&context, x))
+        };
+        let h = h.map(|h| {
+            let context = context.clone();
+            Box::new(move |x: &Col<E>, y: &Col<E>| h(&context, x, y)) as HessianFn
+        });
+
         Self {
             n_var,
             n_cons,
@@ -105,9 +184,16 @@ impl NonlinearProgram {
             h,
             l,
             u,
+            context: Some(context),
         }
     }
 
+    /// Context passed to [`NonlinearProgram::new_with_context`], if it has
+    /// type `C`.
+    pub fn get_context<C: Send + Sync + 'static>(&self) -> Option<&C> {
+        self.context.as_ref()?.downcast_ref()
+    }
+
     pub fn f(&self, x: &Col<E>) -> E {
         (self.f)(x)
     }
@@ -146,6 +232,7 @@ impl NonlinearProgram {
 
 impl OptimizationProgram for NonlinearProgram {
     fn update_residual(&self, state: &mut SolverState) {
+        crate::profile!("residual");
         let (x, y, z_l, z_u) = (&state.x, &state.y, &state.z_l, &state.z_u);
         let zero = Col::<E>::zeros(self.n_var);
         let inf = E::INFINITY * Col::<E>::ones(self.n_var);
@@ -158,6 +245,38 @@ impl OptimizationProgram for NonlinearProgram {
     }
 }
 
+/// Instantiates gradient descent with the step size strategy `step_size`,
+/// projecting onto the set of `projection` if given.
+fn build_gradient_descent<'a>(
+    nlp: &'a NonlinearProgram,
+    step_size: gd::stepsize::StepSizeType,
+    projection: Option<Box<dyn projection::Projection>>,
+    options: &SolverOptions,
+) -> Box<dyn NLPSolver<'a> + 'a> {
+    use gd::stepsize::*;
+
+    fn build<'a, SS: StepSize + 'a>(
+        nlp: &'a NonlinearProgram,
+        projection: Option<Box<dyn projection::Projection>>,
+        options: &SolverOptions,
+    ) -> Box<dyn NLPSolver<'a> + 'a> {
+        let solver = gd::GradientDescent::<SS>::new(nlp, options);
+        match projection {
+            Some(projection) => Box::new(solver.with_projection(projection)),
+            None => Box::new(solver),
+        }
+    }
+
+    match step_size {
+        StepSizeType::Constant => build::<ConstantStepSize>(nlp, projection, options),
+        StepSizeType::LinearDecay => build::<LinearDecayStepSize>(nlp, projection, options),
+        StepSizeType::QuadraticDecay => build::<QuadraticDecayStepSize>(nlp, projection, options),
+        StepSizeType::BarzilaiBorwein => build::<BarzilaiBorweinStepSize>(nlp, projection, options),
+        StepSizeType::Normalized => build::<NormalizedStepSize>(nlp, projection, options),
+        StepSizeType::AdaGrad => build::<AdaGradStepSize>(nlp, projection, options),
+    }
+}
+
 pub trait NLPSolver<'a>: IterativeSolver {
     fn new(nlp: &'a NonlinearProgram, options: &SolverOptions) -> Self
     where
@@ -186,9 +305,13 @@ impl FromStr for NLPSolverType {
     }
 }
 
+#[use_option(name = "step_size", type_ = crate::nlp::gd::stepsize::StepSizeType, default = "constant", description = "Step size strategy of gradient descent: constant, linear_decay, quadratic_decay, barzilai_borwein, normalized or adagrad.")]
+#[use_option(name = "nlp_solver", type_ = String, default = "", description = "Name of a registered nonlinear program solver to build when no solver type is given; empty selects nlp_solver_type.")]
 pub struct NLPSolverBuilder<'a> {
     nlp: Option<&'a NonlinearProgram>,
     solver_type: Option<NLPSolverType>,
+    solver_name: Option<String>,
+    projection: Option<Box<dyn projection::Projection>>,
     options: SolverOptions,
     // Add any additional configuration options here.
 }
@@ -198,6 +321,8 @@ impl<'a> NLPSolverBuilder<'a> {
         Self {
             nlp: None,
             solver_type: None,
+            solver_name: None,
+            projection: None,
             options: SolverOptions::new(),
         }
     }
@@ -217,6 +342,19 @@ impl<'a> NLPSolverBuilder<'a> {
         self
     }
 
+    /// Selects a solver registered with [`crate::registry::register_nlp_solver`].
+    pub fn with_solver_name(mut self, name: &str) -> Self {
+        self.solver_name = Some(name.to_string());
+        self
+    }
+
+    /// Projects the iterates of first-order solvers onto the set of
+    /// `projection` instead of the bounds.
+    pub fn with_projection(mut self, projection: Box<dyn projection::Projection>) -> Self {
+        self.projection = Some(projection);
+        self
+    }
+
     // Add any additional builder methods here.
 
     pub fn build(self) -> Result<Box<dyn NLPSolver<'a> + 'a>, Problem> {
@@ -224,6 +362,12 @@ impl<'a> NLPSolverBuilder<'a> {
         let nlp = self
             .nlp
             .ok_or_else(|| "Nonlinear program must be provided".gloss())?;
+        if self.solver_type.is_none()
+            && let Some(name) =
+                registry::selected_name(self.solver_name.clone(), &self.options, "nlp_solver")
+        {
+            return registry::build_nlp_solver(&name, nlp, &self.options);
+        }
 
         // Get the solver type from the builder or fallback to options
         let solver_type = self
@@ -236,10 +380,64 @@ impl<'a> NLPSolverBuilder<'a> {
             // NLPSolverType::InteriorPointMethod => {
             //     Ok(Box::new(ipm::InteriorPointMethod::<>::new(nlp, &self.options)))
             // }
-            NLPSolverType::GradientDescent => Ok(Box::new(gd::GradientDescent::<
-                gd::stepsize::ConstantStepSize,
-            >::new(nlp, &self.options))),
+            NLPSolverType::GradientDescent => Ok(build_gradient_descent(
+                nlp,
+                self.options
+                    .get_option::<gd::stepsize::StepSizeType>("step_size")
+                    .unwrap_or_default(),
+                self.projection,
+                &self.options,
+            )),
             _ => Err("Invalid solver type.".gloss()),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use faer::col;
+
+    use super::*;
+    use crate::{lp::test::build_ranged_lp, qp::QuadraticProgram};
+
+    #[test]
+    fn test_ranged_and_free_rows() {
+        // Optimum of the program with a ranged and a free row, with stray
+        // multipliers on the infinite bounds of x_1 and of the free slack
+        let lp = build_ranged_lp();
+        let state = SolverState::new(
+            col![0., 3., 2., 3.],
+            col![-2., 0.],
+            col![1., 0., 0., 5.],
+            col![0., -5., -2., 0.],
+        );
+
+        let residuals = |program: &dyn OptimizationProgram| {
+            let mut state = state.clone();
+            program.update_residual(&mut state);
+            state
+        };
+        let lp_state = residuals(lp);
+        for state in [
+            residuals(&QuadraticProgram::from(lp)),
+            residuals(&NonlinearProgram::from(lp)),
+        ] {
+            assert!(
+                (state.get_dual_feasibility() - lp_state.get_dual_feasibility()).norm_max() < 1e-12
+            );
+            assert!(
+                (state.get_primal_feasibility() - lp_state.get_primal_feasibility()).norm_max()
+                    < 1e-12
+            );
+            assert_eq!(state.get_cs_lower(), lp_state.get_cs_lower());
+            assert_eq!(state.get_cs_upper(), lp_state.get_cs_upper());
+        }
+
+        // The stray multipliers enter the dual residual but not the
+        // complementarity of the infinite bounds
+        assert_eq!(lp_state.get_dual_feasibility(), &col![0., -5., 0., 5.]);
+        assert_eq!(lp_state.get_primal_feasibility().norm_max(), 0.);
+        assert_eq!(lp_state.get_cs_lower(), &Col::<E>::zeros(4));
+        assert_eq!(lp_state.get_cs_upper(), &Col::<E>::zeros(4));
+    }
+}
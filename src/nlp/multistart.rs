@@ -0,0 +1,275 @@
+//! Multi-start driver for nonconvex nonlinear programs.
+//!
+//! A local solver such as [`GradientDescent`](crate::nlp::gd::GradientDescent)
+//! converges to the stationary point whose basin contains its starting point,
+//! which for a nonconvex program need not be the best one. [`MultiStartSolver`]
+//! solves the program from the starting points given with
+//! [`MultiStartSolver::with_start`] followed by `multistart_n_starts` points
+//! sampled uniformly within the bounds, and keeps the best KKT point: the
+//! optimal solve with the lowest objective, or the solve with the smallest KKT
+//! error if none reaches optimality.
+//!
+//! Variables without a finite bound are sampled within
+//! `multistart_sample_radius` of their finite bound, or of zero if they have
+//! none. The samples are reproducible for a fixed `random_seed`, and the starts
+//! are solved in parallel if `multistart_parallel` is set.
+
+use faer::{Col, rand::Rng};
+use macros::{explicit_options, use_option};
+use problemo::{Problem, common::IntoCommonProblem};
+use rayon::prelude::*;
+
+use crate::{
+    E, SolverHooks, SolverOptions, SolverState, Status, nlp::NonlinearProgram,
+    utils::random::SeedSequence,
+};
+
+/// Outcome of the solve from one starting point.
+#[derive(Debug, Clone)]
+pub struct StartResult {
+    start: Col<E>,
+    status: Status,
+    objective: E,
+    kkt_error: E,
+    nit: usize,
+}
+
+impl StartResult {
+    pub fn get_start(&self) -> &Col<E> {
+        &self.start
+    }
+
+    pub fn get_status(&self) -> Status {
+        self.status
+    }
+
+    /// Objective value of the final iterate.
+    pub fn get_objective(&self) -> E {
+        self.objective
+    }
+
+    /// Largest primal, dual or complementarity residual of the final iterate.
+    pub fn get_kkt_error(&self) -> E {
+        self.kkt_error
+    }
+
+    pub fn get_nit(&self) -> usize {
+        self.nit
+    }
+
+    /// Whether this result is a better KKT point than `other`.
+    fn is_better(&self, other: &StartResult) -> bool {
+        match (
+            self.status == Status::Optimal,
+            other.status == Status::Optimal,
+        ) {
+            (true, false) => true,
+            (false, true) => false,
+            (true, true) => self.objective < other.objective,
+            (false, false) => self.kkt_error < other.kkt_error,
+        }
+    }
+}
+
+/// Solves a nonlinear program from several starting points. See the
+/// [module documentation](self).
+#[explicit_options(name = SolverOptions)]
+#[use_option(name = "multistart_n_starts", type_ = usize, default = "8", description = "Number of starting points that MultiStartSolver samples within the bounds in addition to the given ones.")]
+#[use_option(name = "multistart_parallel", type_ = bool, default = "false", description = "Whether MultiStartSolver solves from its starting points in parallel.")]
+#[use_option(name = "multistart_sample_radius", type_ = E, default = "10", description = "Width of the interval from which MultiStartSolver samples variables without a finite bound, around their finite bound or zero.")]
+pub struct MultiStartSolver<'a> {
+    nlp: &'a NonlinearProgram,
+
+    starts: Vec<Col<E>>,
+    results: Vec<StartResult>,
+}
+
+impl<'a> MultiStartSolver<'a> {
+    pub fn new(nlp: &'a NonlinearProgram, options: &SolverOptions) -> Self {
+        Self {
+            nlp,
+            starts: Vec::new(),
+            results: Vec::new(),
+            options: options.into(),
+        }
+    }
+
+    /// Adds a starting point, solved before the sampled ones.
+    pub fn with_start(mut self, x: Col<E>) -> Self {
+        self.starts.push(x);
+        self
+    }
+
+    /// Outcomes of the last solve, in the order of the starting points: the
+    /// given ones first, then the sampled ones.
+    pub fn get_results(&self) -> &[StartResult] {
+        &self.results
+    }
+
+    /// Index in [`MultiStartSolver::get_results`] of the best KKT point of the
+    /// last solve.
+    pub fn get_best(&self) -> Option<usize> {
+        (0..self.results.len()).reduce(|best, k| {
+            if self.results[k].is_better(&self.results[best]) {
+                k
+            } else {
+                best
+            }
+        })
+    }
+
+    /// Starting points sampled uniformly within the bounds.
+    fn sample_starts(&self) -> Vec<Col<E>> {
+        let n = self.nlp.n_var;
+        let radius = self.options.multistart_sample_radius;
+        let infinite = Col::full(n, E::INFINITY);
+        let l = self.nlp.l().cloned().unwrap_or_else(|| -&infinite);
+        let u = self.nlp.u().cloned().unwrap_or(infinite);
+        let (lower, upper) = (
+            Col::<E>::from_fn(n, |j| match (l[j].is_finite(), u[j].is_finite()) {
+                (true, _) => l[j],
+                (false, true) => u[j] - radius,
+                (false, false) => -radius / E::from(2.),
+            }),
+            Col::<E>::from_fn(n, |j| match (l[j].is_finite(), u[j].is_finite()) {
+                (_, true) => u[j],
+                (true, false) => l[j] + radius,
+                (false, false) => radius / E::from(2.),
+            }),
+        );
+
+        let mut rng = SeedSequence::new(&self.options.root).get_rng("multistart");
+        (0..self.options.multistart_n_starts)
+            .map(|_| Col::from_fn(n, |j| lower[j] + (upper[j] - lower[j]) * rng.random::<E>()))
+            .collect()
+    }
+
+    /// Solves the program from every starting point with a solver built by
+    /// [`NonlinearProgram::solver_builder`] from the options, each with a copy
+    /// of `hooks`. `state` holds the best KKT point, whose status is returned.
+    pub fn solve(
+        &mut self,
+        state: &mut SolverState,
+        hooks: &mut SolverHooks,
+    ) -> Result<Status, Problem> {
+        self.results.clear();
+
+        let starts: Vec<_> = self
+            .starts
+            .iter()
+            .cloned()
+            .chain(self.sample_starts())
+            .map(|x| (x, hooks.clone()))
+            .collect();
+        let solutions: Vec<_> = if self.options.multistart_parallel {
+            starts
+                .into_par_iter()
+                .map(|(x, mut hooks)| self.solve_from(x, &mut hooks))
+                .collect::<Result<_, _>>()?
+        } else {
+            starts
+                .into_iter()
+                .map(|(x, mut hooks)| self.solve_from(x, &mut hooks))
+                .collect::<Result<_, _>>()?
+        };
+
+        let (results, mut states): (Vec<_>, Vec<_>) = solutions.into_iter().unzip();
+        self.results = results;
+        let Some(best) = self.get_best() else {
+            return Err("No starting point to solve from".gloss());
+        };
+        *state = states.swap_remove(best);
+        Ok(state.get_status())
+    }
+
+    /// Solves the program from `x` with zero multipliers.
+    fn solve_from(
+        &self,
+        x: Col<E>,
+        hooks: &mut SolverHooks,
+    ) -> Result<(StartResult, SolverState), Problem> {
+        let (n, m) = (self.nlp.n_var, self.nlp.n_cons);
+        let mut state = SolverState::new(x.clone(), Col::zeros(m), Col::zeros(n), Col::zeros(n));
+        let status = self
+            .nlp
+            .solver_builder()
+            .with_options(self.options.root.clone())
+            .build()?
+            .solve(&mut state, hooks)?;
+        state.set_status(status);
+
+        let kkt_error = [
+            state.get_primal_feasibility().norm_max(),
+            state.get_dual_feasibility().norm_max(),
+            state.get_cs_lower().norm_max(),
+            state.get_cs_upper().norm_max(),
+        ]
+        .into_iter()
+        .fold(E::from(0.), E::max);
+        let result = StartResult {
+            start: x,
+            status,
+            objective: self.nlp.f(&state.x),
+            kkt_error,
+            nit: state.get_nit(),
+        };
+        Ok((result, state))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::{col, sparse::SparseColMat};
+    use rstest::rstest;
+
+    use super::*;
+    use crate::{I, callback::NoOpCallback, terminators::SlowProgressTerminator};
+
+    /// The double well `(x^2 - 1)^2 + 0.3 x` on `[-2, 2]`, whose global
+    /// minimum lies near -1 and whose other local minimum lies near 1.
+    fn build_nlp() -> NonlinearProgram {
+        NonlinearProgram::new(
+            1,
+            0,
+            |x| (x[0] * x[0] - 1.).powi(2) + 0.3 * x[0],
+            |_x| Col::zeros(0),
+            |x| col![4. * x[0] * (x[0] * x[0] - 1.) + 0.3],
+            |_x| SparseColMat::<I, E>::try_new_from_triplets(0, 1, &[]).unwrap(),
+            None,
+            Some(col![-2.]),
+            Some(col![2.]),
+        )
+    }
+
+    #[rstest]
+    fn test_multistart(#[values(false, true)] parallel: bool) {
+        let nlp = build_nlp();
+        let mut options = SolverOptions::new();
+        options.set_option("multistart_parallel", parallel).unwrap();
+        let mut hooks = SolverHooks::new(
+            Box::new(NoOpCallback::new()),
+            Box::new(SlowProgressTerminator::new(&options)),
+        );
+
+        // The given start converges to the local minimum near 1
+        let mut solver = MultiStartSolver::new(&nlp, &options).with_start(col![1.5]);
+        let mut state = SolverState::new(col![0.], Col::zeros(0), col![0.], col![0.]);
+        let status = solver.solve(&mut state, &mut hooks).unwrap();
+        assert_eq!(status, Status::Optimal);
+
+        let results = solver.get_results();
+        assert_eq!(results.len(), 9);
+        assert_eq!(results[0].get_start(), &col![1.5]);
+        assert!(results[0].get_objective() > 0.);
+        assert!(state.x[0] < -0.9 && state.x[0] > -1.2);
+
+        let best = &results[solver.get_best().unwrap()];
+        assert!(best.get_objective() < results[0].get_objective());
+        assert_eq!(best.get_objective(), nlp.f(&state.x));
+        assert!(
+            results
+                .iter()
+                .all(|r| r.get_start()[0] >= -2. && r.get_start()[0] <= 2.)
+        );
+    }
+}
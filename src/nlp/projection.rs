@@ -0,0 +1,159 @@
+//! Euclidean projections onto simple constraint sets.
+//!
+//! First-order methods such as [`GradientDescent`](crate::nlp::gd::GradientDescent)
+//! handle constraints whose projection `P(x) = argmin_{y in C} ‖y - x‖₂` has a
+//! closed form by projecting every iterate onto the set `C`:
+//!
+//! - [`BoxProjection`]: `l <= x <= u`.
+//! - [`SimplexProjection`]: `x >= 0`, `1^T x = radius`, e.g. probability
+//!   vectors (Duchi et al., 2008).
+//! - [`BallProjection`]: `‖x - center‖₂ <= radius`.
+//! - [`HalfspaceProjection`]: `a^T x <= b`.
+
+use dyn_clone::DynClone;
+use faer::Col;
+
+use crate::E;
+
+/// Euclidean projection onto a closed convex set.
+pub trait Projection: DynClone + Send + Sync {
+    /// Replaces `x` by its projection onto the set.
+    fn project(&self, x: &mut Col<E>);
+}
+
+dyn_clone::clone_trait_object!(Projection);
+
+/// Projection onto the box `l <= x <= u`, whose bounds may be infinite.
+#[derive(Clone, Debug)]
+pub struct BoxProjection {
+    l: Col<E>,
+    u: Col<E>,
+}
+
+impl BoxProjection {
+    pub fn new(l: Col<E>, u: Col<E>) -> Self {
+        Self { l, u }
+    }
+}
+
+impl Projection for BoxProjection {
+    fn project(&self, x: &mut Col<E>) {
+        for j in 0..x.nrows() {
+            x[j] = x[j].max(self.l[j]).min(self.u[j]);
+        }
+    }
+}
+
+/// Projection onto the simplex `x >= 0`, `1^T x = radius`.
+#[derive(Clone, Debug)]
+pub struct SimplexProjection {
+    radius: E,
+}
+
+impl SimplexProjection {
+    pub fn new(radius: E) -> Self {
+        Self { radius }
+    }
+}
+
+impl Default for SimplexProjection {
+    /// The probability simplex.
+    fn default() -> Self {
+        Self::new(E::from(1.))
+    }
+}
+
+impl Projection for SimplexProjection {
+    fn project(&self, x: &mut Col<E>) {
+        // The projection is max(x - theta, 0) for the largest theta that keeps the
+        // sum at the radius, found from the entries sorted in decreasing order
+        let mut sorted: Vec<E> = x.iter().copied().collect();
+        sorted.sort_by(|a, b| b.total_cmp(a));
+        let mut sum = E::from(0.);
+        let mut theta = E::from(0.);
+        for (k, &value) in sorted.iter().enumerate() {
+            sum += value;
+            let candidate = (sum - self.radius) / (k + 1) as E;
+            if value > candidate {
+                theta = candidate;
+            }
+        }
+        for j in 0..x.nrows() {
+            x[j] = (x[j] - theta).max(E::from(0.));
+        }
+    }
+}
+
+/// Projection onto the ball `‖x - center‖₂ <= radius`.
+#[derive(Clone, Debug)]
+pub struct BallProjection {
+    center: Col<E>,
+    radius: E,
+}
+
+impl BallProjection {
+    pub fn new(center: Col<E>, radius: E) -> Self {
+        Self { center, radius }
+    }
+}
+
+impl Projection for BallProjection {
+    fn project(&self, x: &mut Col<E>) {
+        let distance = (&*x - &self.center).norm_l2();
+        if distance > self.radius {
+            *x = &self.center + (self.radius / distance) * (&*x - &self.center);
+        }
+    }
+}
+
+/// Projection onto the halfspace `a^T x <= b`.
+#[derive(Clone, Debug)]
+pub struct HalfspaceProjection {
+    a: Col<E>,
+    b: E,
+}
+
+impl HalfspaceProjection {
+    pub fn new(a: Col<E>, b: E) -> Self {
+        Self { a, b }
+    }
+}
+
+impl Projection for HalfspaceProjection {
+    fn project(&self, x: &mut Col<E>) {
+        let violation = self.a.transpose() * &*x - self.b;
+        if violation > E::from(0.) {
+            *x -= (violation / self.a.squared_norm_l2()) * &self.a;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::col;
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(Box::new(BoxProjection::new(col![0., 0., 0.], col![1., E::INFINITY, 1.])), col![-1., 5., 0.5], col![0., 5., 0.5])]
+    #[case(Box::new(SimplexProjection::default()), col![0.8, 0.6, -0.2], col![0.6, 0.4, 0.])]
+    #[case(Box::new(SimplexProjection::new(3.)), col![0., 0., 0.], col![1., 1., 1.])]
+    #[case(Box::new(BallProjection::new(col![1., 0., 0.], 2.)), col![1., 0., 4.], col![1., 0., 2.])]
+    #[case(Box::new(BallProjection::new(col![1., 0., 0.], 2.)), col![1., 1., 1.], col![1., 1., 1.])]
+    #[case(Box::new(HalfspaceProjection::new(col![1., 1., 0.], 1.)), col![2., 1., 3.], col![1., 0., 3.])]
+    #[case(Box::new(HalfspaceProjection::new(col![1., 1., 0.], 1.)), col![0., 0., 3.], col![0., 0., 3.])]
+    fn test_projection(
+        #[case] projection: Box<dyn Projection>,
+        #[case] x: Col<E>,
+        #[case] expected: Col<E>,
+    ) {
+        let mut projected = x.clone();
+        projection.project(&mut projected);
+        assert!((&projected - &expected).norm_max() < 1e-12);
+
+        // Projections are idempotent
+        projection.project(&mut projected);
+        assert!((&projected - &expected).norm_max() < 1e-12);
+    }
+}
@@ -0,0 +1,498 @@
+//! Dual active-set solver for small and medium quadratic programs.
+//!
+//! The method of Goldfarb and Idnani (1983) writes the rows and the finite
+//! bounds of
+//!
+//! ```text
+//! min  1/2 x^T Q x + c^T x
+//! s.t. A x = b
+//!      l <= x <= u
+//! ```
+//!
+//! as constraints `n_i^T x >= b_i`, with `e_j` for a lower and `-e_j` for an
+//! upper bound, and keeps a working set `W` of constraints held as equalities.
+//! The iterate `x` always minimizes the objective subject to `W` with
+//! nonnegative multipliers of the bounds in `W`, i.e. it is dual feasible. Each
+//! step adds the most violated constraint `p` by moving along `z = H n_p` in the
+//! null space of `W` while the multipliers change by `-r = -N^* n_p`. If a bound
+//! in `W` reaches a zero multiplier first, it is dropped and the step
+//! continues, so that the objective increases monotonically until no
+//! constraint is violated. The solution is exact up to rounding, with the
+//! active set identified by `W`.
+//!
+//! The method requires a positive definite Hessian. A Hessian that is only
+//! semidefinite is regularized to `Q + delta I`, with `delta` given by
+//! `active_set_regularization` relative to the largest entry of `Q`, see
+//! [`QPSolver::get_regularization`]. The KKT systems of the working set are
+//! dense and factorized in every step, which suits programs with up to a few
+//! hundred variables, e.g. the re-solves of model-predictive control.
+//!
+//! A working set passed to [`ActiveSetQPSolver::with_working_set`], e.g. from
+//! a previous solve or from [`crossover`](crate::qp::crossover), warm-starts the
+//! method: its linearly independent constraints are held as equalities and
+//! bounds with negative multipliers are dropped before the first step.
+
+use faer::{Col, Mat, Side, linalg::solvers::Solve};
+use macros::{explicit_options, use_option};
+use problemo::Problem;
+
+use crate::{
+    E, IterativeSolver, OptimizationProgram, SolverOptions, SolverState, Status,
+    ipm::DEFAULT_MAX_ITERATIONS,
+    max_abs,
+    qp::{QPSolver, QuadraticProgram},
+};
+
+/// Relative norm below which a constraint counts as linearly dependent on the
+/// working set.
+const DEPENDENCE_TOLERANCE: E = 1e-10;
+
+/// Constraint of a quadratic program held as an equality in a working set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ActiveConstraint {
+    /// Row `i` of `A x = b`.
+    Row(usize),
+    /// Lower bound of variable `j`.
+    Lower(usize),
+    /// Upper bound of variable `j`.
+    Upper(usize),
+}
+
+impl ActiveConstraint {
+    fn is_bound(&self) -> bool {
+        !matches!(self, ActiveConstraint::Row(_))
+    }
+}
+
+/// Solves a quadratic program by the dual active-set method of Goldfarb and
+/// Idnani. See the [module documentation](self).
+///
+/// A single iteration of the solve runs the method to completion. The number
+/// of changes of the working set is bounded by `max_iterations`, or
+/// `10 (n + m)`, at least the default iteration limit, if it is zero.
+#[explicit_options(name = SolverOptions, scope = "qp.active_set")]
+#[use_option(name = "max_iterations", type_=usize, default="0", description="Maximum number of iterations (0 uses solver defaults).")]
+#[use_option(name = "active_set_tolerance", type_ = E, default = "1e-9", description = "Largest violation of a constraint, relative to its right-hand side, accepted by the active-set method for quadratic programs.")]
+#[use_option(name = "active_set_regularization", type_ = E, default = "1e-9", description = "Regularization of Hessians that are not positive definite, relative to their largest entry, in the active-set method for quadratic programs.")]
+pub struct ActiveSetQPSolver<'a> {
+    qp: &'a QuadraticProgram,
+    /// Dense Hessian, regularized if it is not positive definite.
+    hessian: Mat<E>,
+    /// Largest entry of the Hessian, which scales the dependence test.
+    hessian_scale: E,
+    regularization: E,
+    /// Dense constraint matrix.
+    a: Mat<E>,
+    initial: Vec<ActiveConstraint>,
+    working_set: Vec<ActiveConstraint>,
+    n_changes: usize,
+}
+
+impl<'a> ActiveSetQPSolver<'a> {
+    /// Starts the next solve from `working_set` instead of the rows alone.
+    pub fn with_working_set(mut self, working_set: Vec<ActiveConstraint>) -> Self {
+        self.initial = working_set;
+        self
+    }
+
+    /// Working set at the end of the last solve, which holds the rows and the
+    /// active bounds of the solution.
+    pub fn get_working_set(&self) -> &[ActiveConstraint] {
+        &self.working_set
+    }
+
+    /// Number of constraints added to or dropped from the working set by the
+    /// last solve, including the constraints of the initial working set that
+    /// were dropped.
+    pub fn get_n_changes(&self) -> usize {
+        self.n_changes
+    }
+
+    /// Normal `n` and right-hand side `b` of `constraint` as `n^T x >= b`, or
+    /// `n^T x = b` for rows, with the sign `sign` of a row.
+    fn constraint(&self, constraint: ActiveConstraint, sign: E) -> (Col<E>, E) {
+        let n = self.qp.get_n_vars();
+        match constraint {
+            ActiveConstraint::Row(i) => (
+                Col::from_fn(n, |j| sign * self.a[(i, j)]),
+                sign * self.qp.b[i],
+            ),
+            ActiveConstraint::Lower(j) => (Col::from_fn(n, |k| E::from(k == j)), self.qp.l[j]),
+            ActiveConstraint::Upper(j) => (Col::from_fn(n, |k| -E::from(k == j)), -self.qp.u[j]),
+        }
+    }
+
+    /// Solves `[H N; N^T 0] [v; w] = [f; g]` for the normals `N` of `working`.
+    fn solve_kkt(
+        &self,
+        working: &[(ActiveConstraint, E)],
+        f: &Col<E>,
+        g: &Col<E>,
+    ) -> (Col<E>, Col<E>) {
+        let (n, q) = (self.qp.get_n_vars(), working.len());
+        let mut kkt = Mat::<E>::zeros(n + q, n + q);
+        kkt.submatrix_mut(0, 0, n, n).copy_from(&self.hessian);
+        for (k, &(constraint, sign)) in working.iter().enumerate() {
+            let (normal, _) = self.constraint(constraint, sign);
+            for j in 0..n {
+                kkt[(j, n + k)] = normal[j];
+                kkt[(n + k, j)] = normal[j];
+            }
+        }
+        let rhs = Mat::from_fn(n + q, 1, |k, _| if k < n { f[k] } else { g[k - n] });
+        let solution = kkt.full_piv_lu().solve(&rhs);
+        (
+            Col::from_fn(n, |k| solution[(k, 0)]),
+            Col::from_fn(q, |k| solution[(n + k, 0)]),
+        )
+    }
+
+    /// Minimizer of the objective subject to `working` as equalities and its
+    /// multipliers, with `H x + c = N u`.
+    fn solve_working_set(&self, working: &[(ActiveConstraint, E)]) -> (Col<E>, Vec<E>) {
+        let g = Col::from_fn(working.len(), |k| {
+            self.constraint(working[k].0, working[k].1).1
+        });
+        let (x, w) = self.solve_kkt(working, &(-&self.qp.c), &g);
+        (x, w.iter().map(|w| -w).collect())
+    }
+
+    /// Constraints of `candidates` whose normals are linearly independent of
+    /// the ones before them, in order.
+    fn independent(&self, candidates: &[(ActiveConstraint, E)]) -> Vec<(ActiveConstraint, E)> {
+        let mut span: Vec<Col<E>> = Vec::new();
+        let mut working = Vec::new();
+        for &(constraint, sign) in candidates {
+            let (normal, _) = self.constraint(constraint, sign);
+            let norm = normal.norm_l2();
+            let mut r = normal;
+            // Two passes of Gram-Schmidt keep the projection accurate
+            for _ in 0..2 {
+                for v in &span {
+                    let coefficient = v.transpose() * &r;
+                    r -= coefficient * v;
+                }
+            }
+            let residual = r.norm_l2();
+            if norm > E::from(0.) && residual > DEPENDENCE_TOLERANCE * norm {
+                span.push(r / residual);
+                working.push((constraint, sign));
+            }
+        }
+        working
+    }
+
+    /// Most violated constraint outside `working` at `x` with its sign, scaled
+    /// by its right-hand side.
+    fn most_violated(
+        &self,
+        x: &Col<E>,
+        working: &[(ActiveConstraint, E)],
+    ) -> Option<(ActiveConstraint, E)> {
+        let tolerance = self.options.active_set_tolerance;
+        let ax = &self.a * x;
+        let (l, u, b) = (&self.qp.l, &self.qp.u, &self.qp.b);
+        let held = |constraint| working.iter().any(|(c, _)| *c == constraint);
+
+        let rows = (0..b.nrows()).map(|i| {
+            let residual = ax[i] - b[i];
+            (
+                ActiveConstraint::Row(i),
+                -residual.signum(),
+                residual.abs(),
+                b[i],
+            )
+        });
+        let lower = (0..x.nrows())
+            .filter(|&j| l[j].is_finite())
+            .map(|j| (ActiveConstraint::Lower(j), E::from(1.), l[j] - x[j], l[j]));
+        let upper = (0..x.nrows())
+            .filter(|&j| u[j].is_finite())
+            .map(|j| (ActiveConstraint::Upper(j), E::from(1.), x[j] - u[j], u[j]));
+
+        rows.chain(lower)
+            .chain(upper)
+            .map(|(constraint, sign, violation, rhs)| {
+                (constraint, sign, violation / (E::from(1.) + rhs.abs()))
+            })
+            .filter(|&(constraint, _, violation)| violation > tolerance && !held(constraint))
+            .max_by(|a, b| a.2.total_cmp(&b.2))
+            .map(|(constraint, sign, _)| (constraint, sign))
+    }
+
+    /// Runs the dual active-set method from the initial working set and writes
+    /// the primal-dual solution to `state`.
+    fn iterate(&mut self, state: &mut SolverState) -> Result<(), Problem> {
+        let (n, m) = self.qp.get_dims();
+        let max_changes = match self.options.max_iterations {
+            0 => (10 * (n + m)).max(DEFAULT_MAX_ITERATIONS),
+            max_changes => max_changes,
+        };
+
+        // Rows first, so that hints dependent on them are left out
+        let candidates: Vec<_> = (0..m)
+            .map(|i| (ActiveConstraint::Row(i), E::from(1.)))
+            .chain(
+                self.initial
+                    .iter()
+                    .filter(|constraint| match constraint {
+                        ActiveConstraint::Row(_) => false,
+                        ActiveConstraint::Lower(j) => self.qp.l[*j].is_finite(),
+                        ActiveConstraint::Upper(j) => self.qp.u[*j].is_finite(),
+                    })
+                    .map(|&constraint| (constraint, E::from(1.))),
+            )
+            .collect();
+        let mut working = self.independent(&candidates);
+        // Hints that depend on the working set count as dropped
+        let n_bounds = working.iter().filter(|(c, _)| c.is_bound()).count();
+        self.n_changes = candidates.len() - m - n_bounds;
+
+        // Drop the bounds with negative multipliers until the start is dual feasible
+        let (mut x, mut multipliers) = loop {
+            let (x, multipliers) = self.solve_working_set(&working);
+            let negative = (0..working.len())
+                .filter(|&k| working[k].0.is_bound() && multipliers[k] < E::from(0.))
+                .min_by(|&a, &b| multipliers[a].total_cmp(&multipliers[b]));
+            match negative {
+                Some(k) => {
+                    working.remove(k);
+                    self.n_changes += 1;
+                }
+                None => break (x, multipliers),
+            }
+        };
+
+        let mut status = Status::Optimal;
+        'outer: while let Some((p, sign)) = self.most_violated(&x, &working) {
+            let (normal, rhs) = self.constraint(p, sign);
+            let mut multiplier = E::from(0.);
+            loop {
+                if self.n_changes >= max_changes {
+                    status = Status::IterationLimit;
+                    break 'outer;
+                }
+
+                let (z, r) = self.solve_kkt(&working, &normal, &Col::zeros(working.len()));
+                // Largest step that keeps the multipliers of the bounds nonnegative
+                let blocking = (0..working.len())
+                    .filter(|&k| working[k].0.is_bound() && r[k] > E::from(0.))
+                    .map(|k| (k, multipliers[k] / r[k]))
+                    .min_by(|a, b| a.1.total_cmp(&b.1));
+                // Step that satisfies p, unless p depends on the working set
+                let curvature = z.transpose() * &normal;
+                let full = if curvature
+                    <= DEPENDENCE_TOLERANCE * normal.squared_norm_l2() / self.hessian_scale
+                {
+                    None
+                } else {
+                    Some((rhs - normal.transpose() * &x) / curvature)
+                };
+
+                let t = match (blocking, full) {
+                    (None, None) => {
+                        status = Status::Infeasible;
+                        break 'outer;
+                    }
+                    (Some((_, t1)), Some(t2)) => t1.min(t2),
+                    (Some((_, t1)), None) => t1,
+                    (None, Some(t2)) => t2,
+                };
+                if full.is_some() {
+                    x += t * &z;
+                }
+                for (k, multiplier) in multipliers.iter_mut().enumerate() {
+                    *multiplier -= t * r[k];
+                }
+                multiplier += t;
+                self.n_changes += 1;
+
+                match (blocking, full) {
+                    (Some((k, t1)), full) if full.is_none_or(|t2| t1 < t2) => {
+                        working.remove(k);
+                        multipliers.remove(k);
+                    }
+                    _ => {
+                        working.push((p, sign));
+                        multipliers.push(multiplier);
+                        // Solving on the new working set removes the drift of
+                        // the updates
+                        (x, multipliers) = self.solve_working_set(&working);
+                        break;
+                    }
+                }
+            }
+        }
+
+        state.x = x;
+        state.y = Col::zeros(m);
+        state.z_l = Col::zeros(n);
+        state.z_u = Col::zeros(n);
+        for (&(constraint, sign), &multiplier) in working.iter().zip(&multipliers) {
+            match constraint {
+                ActiveConstraint::Row(i) => state.y[i] = sign * multiplier,
+                ActiveConstraint::Lower(j) => state.z_l[j] = multiplier,
+                ActiveConstraint::Upper(j) => state.z_u[j] = -multiplier,
+            }
+        }
+        state.alpha_primal = E::from(1.);
+        state.alpha_dual = E::from(1.);
+        self.qp.update_residual(state);
+
+        self.working_set = working
+            .into_iter()
+            .map(|(constraint, _)| constraint)
+            .collect();
+        state.status = if state.x.iter().all(|v| v.is_finite()) {
+            status
+        } else {
+            Status::Diverged
+        };
+        Ok(())
+    }
+}
+
+impl<'a> QPSolver<'a> for ActiveSetQPSolver<'a> {
+    fn new(qp: &'a QuadraticProgram, options: &SolverOptions) -> Self {
+        let options: ActiveSetQPSolverInternalOptions = options.into();
+        let mut hessian = qp.Q.to_dense();
+        let scale = max_abs(qp.Q.val()).max(E::from(1.));
+        let mut regularization = E::from(0.);
+        if hessian.llt(Side::Lower).is_err() {
+            regularization = options.active_set_regularization * scale;
+            for j in 0..hessian.nrows() {
+                hessian[(j, j)] += regularization;
+            }
+        }
+        Self {
+            qp,
+            hessian,
+            hessian_scale: scale,
+            regularization,
+            a: qp.A.to_dense(),
+            initial: Vec::new(),
+            working_set: Vec::new(),
+            n_changes: 0,
+            options,
+        }
+    }
+
+    fn get_regularization(&self) -> Option<E> {
+        Some(self.regularization)
+    }
+}
+
+impl<'a> IterativeSolver for ActiveSetQPSolver<'a> {
+    fn get_program(&self) -> &dyn OptimizationProgram {
+        self.qp
+    }
+
+    fn get_max_iterations(&self) -> usize {
+        1
+    }
+
+    fn get_options(&self) -> Option<&SolverOptions> {
+        Some(&self.options.root)
+    }
+
+    fn iterate(&mut self, state: &mut SolverState) -> Result<Status, Problem> {
+        self.iterate(state)?;
+        Ok(state.get_status())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::{
+        col,
+        sparse::{SparseColMat, Triplet},
+    };
+    use rstest::rstest;
+
+    use super::*;
+    use crate::{SolverHooks, qp::QPSolverType};
+
+    /// `min 1/2 q |x|^2 + c^T x` subject to `x_0 + x_1 + x_2 = b` and
+    /// `0 <= x <= 1`.
+    fn build_qp(q: E, c: Col<E>, b: E) -> QuadraticProgram {
+        let diagonal: Vec<_> = (0..3).map(|j| Triplet::new(j, j, q)).collect();
+        let row: Vec<_> = (0..3).map(|j| Triplet::new(0, j, 1.)).collect();
+        QuadraticProgram::new(
+            SparseColMat::try_new_from_triplets(3, 3, &diagonal).unwrap(),
+            c,
+            SparseColMat::try_new_from_triplets(1, 3, &row).unwrap(),
+            col![b],
+            Col::zeros(3),
+            Col::full(3, 1.),
+        )
+    }
+
+    fn solve(qp: &QuadraticProgram) -> (Status, SolverState) {
+        let mut state = SolverState::new_interior(qp.get_lower_bounds(), qp.get_upper_bounds(), 1);
+        let status = qp
+            .solver_builder()
+            .with_solver(QPSolverType::ActiveSet)
+            .build()
+            .unwrap()
+            .solve(&mut state, &mut SolverHooks::silent())
+            .unwrap();
+        (status, state)
+    }
+
+    #[rstest]
+    // No bound active
+    #[case(col![0., 0., 0.], 1., col![1. / 3., 1. / 3., 1. / 3.])]
+    // x_2 at its lower bound with x_i = y - c_i for the others
+    #[case(col![-2., -1.5, 1.], 1., col![0.75, 0.25, 0.])]
+    // x_0 at its upper bound
+    #[case(col![-3., 0., 0.], 2., col![1., 0.5, 0.5])]
+    fn test_active_set(#[case] c: Col<E>, #[case] b: E, #[case] expected: Col<E>) {
+        let qp = build_qp(1., c, b);
+        let (status, state) = solve(&qp);
+        assert_eq!(status, Status::Optimal);
+        assert!((state.get_primal() - &expected).norm_max() < 1e-12);
+        assert!(state.get_dual_feasibility().norm_max() < 1e-12);
+        let report = crate::verify::check_solution(&qp, &state, 1e-9);
+        assert!(report.is_optimal(), "{report:?}");
+    }
+
+    #[test]
+    fn test_warm_start() {
+        let options = SolverOptions::new();
+        let qp = build_qp(1., col![-2., -1.5, 1.], 1.);
+        let mut state = SolverState::new_interior(qp.get_lower_bounds(), qp.get_upper_bounds(), 1);
+        let mut solver = ActiveSetQPSolver::new(&qp, &options);
+        IterativeSolver::iterate(&mut solver, &mut state).unwrap();
+        let mut working_set = solver.get_working_set().to_vec();
+        working_set.sort();
+        assert_eq!(
+            working_set,
+            [ActiveConstraint::Row(0), ActiveConstraint::Lower(2)]
+        );
+        assert_eq!(solver.get_n_changes(), 1);
+
+        // A nearby program keeps the active set
+        let perturbed = build_qp(1., col![-2.1, -1.5, 1.], 1.);
+        let mut solver = ActiveSetQPSolver::new(&perturbed, &options).with_working_set(working_set);
+        let status = IterativeSolver::iterate(&mut solver, &mut state).unwrap();
+        assert_eq!(status, Status::Optimal);
+        assert_eq!(solver.get_n_changes(), 0);
+        assert!((state.get_primal() - col![0.8, 0.2, 0.]).norm_max() < 1e-12);
+    }
+
+    #[test]
+    fn test_semidefinite() {
+        // Linear program min -x_0 over the simplex
+        let qp = build_qp(0., col![-1., 0., 0.], 1.);
+        let (status, state) = solve(&qp);
+        assert_eq!(status, Status::Optimal);
+        assert!((state.get_primal() - col![1., 0., 0.]).norm_max() < 1e-6);
+    }
+
+    #[test]
+    fn test_infeasible() {
+        let qp = build_qp(1., col![0., 0., 0.], 5.);
+        let (status, _) = solve(&qp);
+        assert_eq!(status, Status::Infeasible);
+    }
+}
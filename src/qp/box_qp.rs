@@ -0,0 +1,308 @@
+//! Projected Newton solver for bound-constrained quadratic programs.
+//!
+//! A quadratic program without rows,
+//!
+//! ```text
+//! min  1/2 x^T Q x + c^T x
+//! s.t. l <= x <= u
+//! ```
+//!
+//! leaves the constraint block of the KKT system of the interior-point solvers
+//! empty. [`BoxQPSolver`] instead keeps `x` within the bounds and, in every
+//! iteration, fixes the variables at a bound whose gradient `g = Q x + c` pushes
+//! them outwards, takes a Newton step `(Q_FF + delta I) d_F = -g_F` in the
+//! remaining variables `F`, and projects `x + alpha d` onto the bounds, halving
+//! `alpha` until the objective decreases sufficiently (Bertsekas, 1982).
+//!
+//! The bound multipliers are the gradient of the fixed variables, so that the
+//! iterate is optimal once the projected gradient `x - P(x - g)` vanishes.
+
+use faer::{
+    Col,
+    sparse::{SparseColMat, Triplet},
+};
+use macros::{explicit_options, use_option};
+use problemo::{Problem, common::IntoCommonProblem};
+
+use crate::{
+    E, IterativeSolver, OptimizationProgram, SolverOptions, SolverState, Status,
+    ipm::DEFAULT_MAX_ITERATIONS,
+    linalg::solver::LinearSolver,
+    qp::{QPSolver, QuadraticProgram},
+    to_index,
+};
+
+/// Regularization of the Hessian of the free variables.
+const REGULARIZATION: E = 1e-10;
+
+/// Fraction of the decrease predicted by the gradient that a step must achieve.
+const SUFFICIENT_DECREASE: E = 1e-4;
+
+/// Halvings of the step length before the line search gives up.
+const MAX_BACKTRACKS: usize = 50;
+
+/// Whether `qp` has no rows, so that [`BoxQPSolver`] applies.
+pub(crate) fn has_no_rows(qp: &QuadraticProgram) -> bool {
+    qp.get_n_cons() == 0
+}
+
+/// Solves a quadratic program without rows by projected Newton steps. See the
+/// [module documentation](self).
+#[explicit_options(name = SolverOptions, scope = "qp.box_qp")]
+#[use_option(name = "max_iterations", type_=usize, default="0", description="Maximum number of iterations (0 uses solver defaults).")]
+#[use_option(name = "tolerance", type_ = E, default = "1e-7", description = "Tolerance for convergence-based termination")]
+pub struct BoxQPSolver<'a, LinSolve: LinearSolver> {
+    qp: &'a QuadraticProgram,
+    _solver: std::marker::PhantomData<LinSolve>,
+}
+
+impl<'a, LinSolve: LinearSolver> BoxQPSolver<'a, LinSolve> {
+    fn objective(&self, x: &Col<E>) -> E {
+        E::from(0.5) * (x.transpose() * (&self.qp.Q * x)) + self.qp.c.transpose() * x
+    }
+
+    fn project(&self, x: &Col<E>) -> Col<E> {
+        Col::from_fn(x.nrows(), |j| x[j].max(self.qp.l[j]).min(self.qp.u[j]))
+    }
+
+    /// Sets the multipliers of `state` to the gradient of the variables held at
+    /// a bound by it, updates the residuals and returns whether the projected
+    /// gradient is within the tolerance.
+    fn update_multipliers(&self, state: &mut SolverState, gradient: &Col<E>) -> bool {
+        let (l, u) = (&self.qp.l, &self.qp.u);
+        let x = &state.x;
+        state.z_l = Col::from_fn(x.nrows(), |j| {
+            if x[j] <= l[j] && gradient[j] > E::from(0.) {
+                gradient[j]
+            } else {
+                E::from(0.)
+            }
+        });
+        state.z_u = Col::from_fn(x.nrows(), |j| {
+            if x[j] >= u[j] && gradient[j] < E::from(0.) {
+                gradient[j]
+            } else {
+                E::from(0.)
+            }
+        });
+        let projected = (x - self.project(&(x - gradient))).norm_max();
+        self.qp.update_residual(state);
+
+        projected <= self.options.tolerance * (E::from(1.) + self.qp.c.norm_max())
+    }
+
+    /// Newton direction in the variables that are not held at a bound, zero in
+    /// the others.
+    fn newton_direction(&self, x: &Col<E>, gradient: &Col<E>) -> Result<Col<E>, Problem> {
+        let (l, u) = (&self.qp.l, &self.qp.u);
+        let n = x.nrows();
+        let mut position = vec![None; n];
+        let mut free = Vec::new();
+        for j in 0..n {
+            let held = (x[j] <= l[j] && gradient[j] > E::from(0.))
+                || (x[j] >= u[j] && gradient[j] < E::from(0.));
+            if !held {
+                position[j] = Some(free.len());
+                free.push(j);
+            }
+        }
+
+        let mut direction = Col::zeros(n);
+        if free.is_empty() {
+            return Ok(direction);
+        }
+
+        // Explicit diagonal entries, also where Q has none
+        let q = &self.qp.Q;
+        let mut triplets = Vec::with_capacity(q.compute_nnz() + free.len());
+        for (k, &j) in free.iter().enumerate() {
+            for (i, &value) in q.row_idx_of_col(j).zip(q.val_of_col(j)) {
+                if let Some(p) = position[i] {
+                    triplets.push(Triplet::new(to_index(p), to_index(k), value));
+                }
+            }
+            triplets.push(Triplet::new(to_index(k), to_index(k), REGULARIZATION));
+        }
+        let hessian = SparseColMat::try_new_from_triplets(free.len(), free.len(), &triplets)
+            .map_err(|_| "Invalid reduced Hessian".gloss())?;
+
+        let mut solver = LinSolve::new_with_options(&self.options.root);
+        solver.analyze(hessian.as_ref())?;
+        solver.factorize(hessian.as_ref())?;
+        let rhs = Col::from_fn(free.len(), |k| -gradient[free[k]]);
+        let step = solver.solve(rhs.as_mat())?;
+        for (k, &j) in free.iter().enumerate() {
+            direction[j] = step[(k, 0)];
+        }
+        Ok(direction)
+    }
+
+    fn iterate(&mut self, state: &mut SolverState) -> Result<(), Problem> {
+        state.x = self.project(&state.x);
+        let gradient = &self.qp.Q * &state.x + &self.qp.c;
+        if self.update_multipliers(state, &gradient) {
+            state.status = Status::Optimal;
+            return Ok(());
+        }
+
+        let direction = self.newton_direction(&state.x, &gradient)?;
+        let objective = self.objective(&state.x);
+        let mut alpha = E::from(1.);
+        let mut accepted = None;
+        for _ in 0..MAX_BACKTRACKS {
+            let trial = self.project(&(&state.x + alpha * &direction));
+            let decrease = gradient.transpose() * (&trial - &state.x);
+            if self.objective(&trial) <= objective + SUFFICIENT_DECREASE * decrease {
+                accepted = Some(trial);
+                break;
+            }
+            alpha *= E::from(0.5);
+        }
+
+        let Some(x) = accepted else {
+            // No step decreases the objective, which only happens at the
+            // accuracy limit of the Newton direction
+            state.status = Status::Unknown;
+            return Ok(());
+        };
+        if !x.iter().all(|v| v.is_finite()) {
+            state.status = Status::Diverged;
+            return Ok(());
+        }
+
+        state.x = x;
+        state.alpha_primal = alpha;
+        state.alpha_dual = E::from(1.);
+        let gradient = &self.qp.Q * &state.x + &self.qp.c;
+        state.status = if self.update_multipliers(state, &gradient) {
+            Status::Optimal
+        } else {
+            Status::InProgress
+        };
+        Ok(())
+    }
+}
+
+impl<'a, LinSolve: LinearSolver> QPSolver<'a> for BoxQPSolver<'a, LinSolve> {
+    fn new(qp: &'a QuadraticProgram, options: &SolverOptions) -> Self {
+        Self {
+            qp,
+            _solver: std::marker::PhantomData,
+            options: options.into(),
+        }
+    }
+}
+
+impl<'a, LinSolve: LinearSolver> IterativeSolver for BoxQPSolver<'a, LinSolve> {
+    fn get_program(&self) -> &dyn OptimizationProgram {
+        self.qp
+    }
+
+    fn get_max_iterations(&self) -> usize {
+        if self.options.max_iterations > 0 {
+            self.options.max_iterations
+        } else {
+            DEFAULT_MAX_ITERATIONS
+        }
+    }
+
+    fn get_options(&self) -> Option<&SolverOptions> {
+        Some(&self.options.root)
+    }
+
+    fn iterate(&mut self, state: &mut SolverState) -> Result<Status, Problem> {
+        self.iterate(state)?;
+        Ok(state.get_status())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::col;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::{
+        SolverHooks, callback::NoOpCallback, qp::QPSolverType,
+        terminators::ComplementarityTerminator,
+    };
+
+    /// `min 1/2 x^T Q x + c^T x` subject to `0 <= x <= 1`, with
+    /// `Q = [2, 1; 1, 2]`.
+    fn build_qp(c: Col<E>) -> QuadraticProgram {
+        let q = SparseColMat::try_new_from_triplets(
+            2,
+            2,
+            &[
+                Triplet::new(0, 0, 2.),
+                Triplet::new(1, 0, 1.),
+                Triplet::new(0, 1, 1.),
+                Triplet::new(1, 1, 2.),
+            ],
+        )
+        .unwrap();
+        QuadraticProgram::new(
+            q,
+            c,
+            SparseColMat::try_new_from_triplets(0, 2, &[]).unwrap(),
+            Col::zeros(0),
+            Col::zeros(2),
+            Col::full(2, 1.),
+        )
+    }
+
+    #[rstest]
+    // Unconstrained minimizer (1/3, 1/3) inside the box
+    #[case(col![-1., -1.], col![1. / 3., 1. / 3.])]
+    // x_0 at its upper bound, then 2 x_1 + 1 = 2
+    #[case(col![-4., -2.], col![1., 0.5])]
+    // Both at a bound
+    #[case(col![1., -5.], col![0., 1.])]
+    fn test_box_qp(
+        #[case] c: Col<E>,
+        #[case] expected: Col<E>,
+        #[values(QPSolverType::MpcSimplicialCholesky, QPSolverType::MpcSimplicialLu)]
+        solver_type: QPSolverType,
+    ) {
+        let qp = build_qp(c);
+        assert!(has_no_rows(&qp));
+
+        let mut state = SolverState::new_interior(&qp.l, &qp.u, 0);
+        let status = qp
+            .solver_builder()
+            .with_solver(solver_type)
+            .build()
+            .unwrap()
+            .solve(&mut state, &mut SolverHooks::silent())
+            .unwrap();
+        assert_eq!(status, Status::Optimal);
+        assert!((state.get_primal() - &expected).norm_max() < 1e-9);
+        assert!(state.get_dual_feasibility().norm_max() < 1e-9);
+        let report = crate::verify::check_solution(&qp, &state, 1e-9);
+        assert!(report.is_optimal(), "{report:?}");
+    }
+
+    #[test]
+    fn test_disabled_fast_path() {
+        let qp = build_qp(col![-4., -2.]);
+        let mut options = SolverOptions::new();
+        options.set_option("qp_box_fast_path", false).unwrap();
+
+        let mut hooks = SolverHooks {
+            callback: Box::new(NoOpCallback::new()),
+            terminator: Box::new(ComplementarityTerminator::new(&options)),
+        };
+        let mut state = SolverState::new_interior(&qp.l, &qp.u, 0);
+        let status = qp
+            .solver_builder()
+            .with_solver(QPSolverType::MpcSimplicialCholesky)
+            .with_options(options)
+            .build()
+            .unwrap()
+            .solve(&mut state, &mut hooks)
+            .unwrap();
+        assert_eq!(status, Status::Optimal);
+        assert!(state.get_nit() > 0);
+        assert!((state.get_primal() - col![1., 0.5]).norm_max() < 1e-6);
+    }
+}
@@ -0,0 +1,194 @@
+//! Crossover from interior-point solutions of quadratic programs to their
+//! active set.
+//!
+//! An interior-point solution keeps every variable strictly inside its bounds,
+//! so that it identifies the active set only up to the tolerance of the
+//! solver. As in [`ActiveSetPrediction`](crate::lp::active_set::ActiveSetPrediction),
+//! a variable with gap `g_j` to its nearest finite bound and multiplier `z_j`
+//! on that bound is predicted to be at the bound if `g_j <= |z_j|`.
+//!
+//! [`crossover`] starts the dual active-set method of [`ActiveSetQPSolver`]
+//! from the predicted bounds. A correct prediction is confirmed without a
+//! change of the working set, a wrong one is corrected by the steps of the
+//! method. The result is exact up to rounding, and its working set
+//! warm-starts the re-solves of nearby programs, e.g. in parametric or
+//! model-predictive control loops.
+
+use problemo::Problem;
+
+use crate::{
+    E, IterativeSolver, SolverOptions, SolverState, Status,
+    qp::{
+        QPSolver, QuadraticProgram,
+        active_set::{ActiveConstraint, ActiveSetQPSolver},
+    },
+};
+
+/// Outcome of [`crossover`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrossoverResult {
+    status: Status,
+    n_predicted: usize,
+    n_changes: usize,
+    working_set: Vec<ActiveConstraint>,
+}
+
+impl CrossoverResult {
+    /// Status of the active-set method. The solution is replaced only if it is
+    /// [`Status::Optimal`].
+    pub fn get_status(&self) -> Status {
+        self.status
+    }
+
+    /// Number of bounds predicted to be active.
+    pub fn get_n_predicted(&self) -> usize {
+        self.n_predicted
+    }
+
+    /// Number of changes of the working set needed to correct the prediction.
+    pub fn get_n_changes(&self) -> usize {
+        self.n_changes
+    }
+
+    /// Rows and active bounds of the solution, to warm-start
+    /// [`ActiveSetQPSolver::with_working_set`].
+    pub fn get_working_set(&self) -> &[ActiveConstraint] {
+        &self.working_set
+    }
+}
+
+/// Bounds of `qp` predicted to be active at the primal-dual iterate `state`.
+pub fn predict_working_set(qp: &QuadraticProgram, state: &SolverState) -> Vec<ActiveConstraint> {
+    let (l, u) = (qp.get_lower_bounds(), qp.get_upper_bounds());
+    (0..qp.get_n_vars())
+        .filter_map(|j| {
+            if l[j] == u[j] {
+                return Some(ActiveConstraint::Lower(j));
+            }
+            let (gap_l, gap_u) = (state.x[j] - l[j], u[j] - state.x[j]);
+            let (gap, multiplier, bound) = if gap_l <= gap_u {
+                (gap_l, state.z_l[j], ActiveConstraint::Lower(j))
+            } else {
+                (gap_u, state.z_u[j], ActiveConstraint::Upper(j))
+            };
+            (gap.is_finite() && gap.max(E::from(0.)) <= multiplier.abs()).then_some(bound)
+        })
+        .collect()
+}
+
+/// Replaces the primal-dual iterate `state` of `qp` by the solution of the
+/// active-set method started from its predicted active set. See the
+/// [module documentation](self).
+pub fn crossover(
+    qp: &QuadraticProgram,
+    state: &mut SolverState,
+    options: &SolverOptions,
+) -> Result<CrossoverResult, Problem> {
+    let prediction = predict_working_set(qp, state);
+    let n_predicted = prediction.len();
+    let mut solver = ActiveSetQPSolver::new(qp, options).with_working_set(prediction);
+
+    let mut solution = state.clone();
+    let status = solver.iterate(&mut solution)?;
+    if status == Status::Optimal {
+        *state = solution;
+    }
+    Ok(CrossoverResult {
+        status,
+        n_predicted,
+        n_changes: solver.get_n_changes(),
+        working_set: solver.get_working_set().to_vec(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::{
+        Col, col,
+        sparse::{SparseColMat, Triplet},
+    };
+
+    use super::*;
+    use crate::{SolverHooks, qp::QPSolverType};
+
+    /// `min 1/2 |x|^2 + c^T x` subject to `x_0 + x_1 + x_2 = 1` and
+    /// `0 <= x <= 1`, with the optimum `x = (0.75, 0.25, 0)`, `y = -1.25` and
+    /// `z_l = (0, 0, 2.25)`.
+    fn build_qp() -> QuadraticProgram {
+        QuadraticProgram::new(
+            SparseColMat::try_new_from_triplets(
+                3,
+                3,
+                &[
+                    Triplet::new(0, 0, 1.),
+                    Triplet::new(1, 1, 1.),
+                    Triplet::new(2, 2, 1.),
+                ],
+            )
+            .unwrap(),
+            col![-2., -1.5, 1.],
+            SparseColMat::try_new_from_triplets(
+                1,
+                3,
+                &[
+                    Triplet::new(0, 0, 1.),
+                    Triplet::new(0, 1, 1.),
+                    Triplet::new(0, 2, 1.),
+                ],
+            )
+            .unwrap(),
+            col![1.],
+            Col::zeros(3),
+            Col::full(3, 1.),
+        )
+    }
+
+    fn assert_exact(state: &SolverState) {
+        assert!((&state.x - col![0.75, 0.25, 0.]).norm_max() < 1e-12);
+        assert!((&state.y - col![-1.25]).norm_max() < 1e-12);
+        assert!((&state.z_l - col![0., 0., 2.25]).norm_max() < 1e-12);
+        assert!(state.get_dual_feasibility().norm_max() < 1e-12);
+    }
+
+    #[test]
+    fn test_crossover() {
+        let qp = build_qp();
+        let mut state = SolverState::new_interior(qp.get_lower_bounds(), qp.get_upper_bounds(), 1);
+        let status = qp
+            .solver_builder()
+            .with_solver(QPSolverType::MpcSimplicialCholesky)
+            .build()
+            .unwrap()
+            .solve(&mut state, &mut SolverHooks::silent())
+            .unwrap();
+        assert_eq!(status, Status::Optimal);
+        assert!(state.x[2] > 0.);
+
+        let result = crossover(&qp, &mut state, &SolverOptions::new()).unwrap();
+        assert_eq!(result.get_status(), Status::Optimal);
+        assert_eq!(result.get_n_predicted(), 1);
+        assert_eq!(result.get_n_changes(), 0);
+        assert_eq!(
+            result.get_working_set(),
+            [ActiveConstraint::Row(0), ActiveConstraint::Lower(2)]
+        );
+        assert_exact(&state);
+    }
+
+    #[test]
+    fn test_wrong_prediction() {
+        // x_0 looks active and x_2 inactive
+        let qp = build_qp();
+        let mut state = SolverState::new(
+            col![1e-6, 0.5, 0.5],
+            col![-1.],
+            col![1., 1e-6, 1e-6],
+            Col::zeros(3),
+        );
+        let result = crossover(&qp, &mut state, &SolverOptions::new()).unwrap();
+        assert_eq!(result.get_status(), Status::Optimal);
+        assert_eq!(result.get_n_predicted(), 1);
+        assert_eq!(result.get_n_changes(), 2);
+        assert_exact(&state);
+    }
+}
@@ -0,0 +1,333 @@
+//! Direct solver for quadratic programs without finite bounds.
+//!
+//! If no variable has a finite bound, the optimality conditions of a convex
+//! quadratic program are the linear system
+//!
+//! ```text
+//! [ Q  -A^T ] [ x ]   [ -c ]
+//! [ -A   0  ] [ y ] = [ -b ]
+//! ```
+//!
+//! which is factorized once instead of running the interior-point loop. The
+//! factorized matrix is regularized by `+delta` on the first and `-delta` on the
+//! second diagonal block, so that singular Hessians and redundant rows do not
+//! break the factorization, and the solution is refined against the
+//! unregularized system.
+//!
+//! Without bounds, a program is unbounded unless its Hessian is positive
+//! semidefinite on the null space of `A`, in which case the factorized matrix
+//! has exactly one negative pivot per row. A program whose factorization has
+//! another inertia, or whose refined solution leaves a dual residual, is
+//! [`Status::Unbounded`]; one that leaves a primal residual, i.e. whose rows
+//! are inconsistent, is [`Status::Infeasible`].
+
+use faer::{
+    Col,
+    sparse::{SparseColMat, Triplet},
+};
+use problemo::Problem;
+
+use crate::{
+    E, I, IterativeSolver, OptimizationProgram, SolverOptions, SolverState, Status,
+    linalg::solver::{LinearSolver, MemoryEstimate},
+    qp::{QPSolver, QuadraticProgram},
+    to_index,
+};
+
+/// Regularization of the diagonal blocks of the KKT matrix.
+const REGULARIZATION: E = 1e-10;
+
+/// Steps of iterative refinement against the unregularized matrix.
+const REFINEMENT_STEPS: usize = 3;
+
+/// Residual of the unregularized KKT system, relative to `1 + |(c, b)|`, above
+/// which the refined solution does not solve it.
+const RESIDUAL_TOLERANCE: E = 1e-8;
+
+/// Whether every bound of `qp` is infinite, so that [`EqualityQPSolver`]
+/// applies.
+pub(crate) fn has_no_finite_bounds(qp: &QuadraticProgram) -> bool {
+    qp.l.iter().all(|l| l.is_infinite()) && qp.u.iter().all(|u| u.is_infinite())
+}
+
+/// Solves a quadratic program without finite bounds with a single
+/// factorization of its KKT system. See the [module documentation](self).
+pub struct EqualityQPSolver<'a, LinSolve: LinearSolver> {
+    qp: &'a QuadraticProgram,
+    kkt: SparseColMat<I, E>,
+    regularized: SparseColMat<I, E>,
+    solver: LinSolve,
+}
+
+impl<'a, LinSolve: LinearSolver> EqualityQPSolver<'a, LinSolve> {
+    fn iterate(&mut self, state: &mut SolverState) -> Result<(), Problem> {
+        let (n, m) = self.qp.get_dims();
+        self.solver.factorize(self.regularized.as_ref())?;
+        let convex = self.solver.count_negative_pivots() == Some(m);
+
+        let mut rhs = Col::zeros(n + m);
+        rhs.subrows_mut(0, n).copy_from(-&self.qp.c);
+        rhs.subrows_mut(n, m).copy_from(-&self.qp.b);
+        let mut solution = Col::<E>::zeros(n + m);
+        for _ in 0..=REFINEMENT_STEPS {
+            let residual = &rhs - &self.kkt * &solution;
+            let correction = self.solver.solve(residual.as_mat())?;
+            solution += correction.col(0);
+        }
+
+        state.x = solution.subrows(0, n).to_owned();
+        state.y = solution.subrows(n, m).to_owned();
+        state.z_l = Col::zeros(n);
+        state.z_u = Col::zeros(n);
+        state.alpha_primal = E::from(1.);
+        state.alpha_dual = E::from(1.);
+        self.qp.update_residual(state);
+
+        let tolerance = RESIDUAL_TOLERANCE * (E::from(1.) + rhs.norm_max());
+        state.status = if solution.iter().any(|v| !v.is_finite()) {
+            Status::Diverged
+        } else if state.get_primal_feasibility().norm_max() > tolerance {
+            Status::Infeasible
+        } else if !convex || state.get_dual_feasibility().norm_max() > tolerance {
+            Status::Unbounded
+        } else {
+            Status::Optimal
+        };
+        Ok(())
+    }
+}
+
+impl<'a, LinSolve: LinearSolver> QPSolver<'a> for EqualityQPSolver<'a, LinSolve> {
+    fn new(qp: &'a QuadraticProgram, options: &SolverOptions) -> Self {
+        let (n, m) = qp.get_dims();
+        let mut triplets = Vec::with_capacity(qp.Q.compute_nnz() + 2 * qp.A.compute_nnz());
+        for j in 0..n {
+            for (i, &value) in qp.Q.row_idx_of_col(j).zip(qp.Q.val_of_col(j)) {
+                triplets.push(Triplet::new(to_index(i), to_index(j), value));
+            }
+            for (i, &value) in qp.A.row_idx_of_col(j).zip(qp.A.val_of_col(j)) {
+                triplets.push(Triplet::new(to_index(n + i), to_index(j), -value));
+                triplets.push(Triplet::new(to_index(j), to_index(n + i), -value));
+            }
+        }
+        let kkt = SparseColMat::try_new_from_triplets(n + m, n + m, &triplets).unwrap();
+
+        // Explicit diagonal entries, also where Q and the zero block have none
+        for k in 0..n + m {
+            let delta = if k < n {
+                REGULARIZATION
+            } else {
+                -REGULARIZATION
+            };
+            triplets.push(Triplet::new(to_index(k), to_index(k), delta));
+        }
+        let regularized = SparseColMat::try_new_from_triplets(n + m, n + m, &triplets).unwrap();
+
+        let mut solver = LinSolve::new_with_options(options);
+        solver.analyze(regularized.as_ref()).unwrap();
+        Self {
+            qp,
+            kkt,
+            regularized,
+            solver,
+        }
+    }
+
+    fn estimate_memory(&self) -> Option<MemoryEstimate> {
+        self.solver.estimate_memory()
+    }
+}
+
+impl<'a, LinSolve: LinearSolver> IterativeSolver for EqualityQPSolver<'a, LinSolve> {
+    fn get_program(&self) -> &dyn OptimizationProgram {
+        self.qp
+    }
+
+    fn get_max_iterations(&self) -> usize {
+        1
+    }
+
+    fn iterate(&mut self, state: &mut SolverState) -> Result<Status, Problem> {
+        self.iterate(state)?;
+        Ok(state.get_status())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::col;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::{SolverHooks, qp::QPSolverType};
+
+    /// `min 1/2 x^T diag(q) x + c^T x` subject to `x_0 + x_1 + x_2 = 1`.
+    fn build_qp(q: [E; 3], c: Col<E>) -> QuadraticProgram {
+        let triplets = (0..3)
+            .filter(|&j| q[j] != 0.)
+            .map(|j| Triplet::new(to_index(j), to_index(j), q[j]))
+            .collect::<Vec<_>>();
+        QuadraticProgram::new(
+            SparseColMat::try_new_from_triplets(3, 3, &triplets).unwrap(),
+            c,
+            SparseColMat::try_new_from_triplets(
+                1,
+                3,
+                &[
+                    Triplet::new(0, 0, 1.),
+                    Triplet::new(0, 1, 1.),
+                    Triplet::new(0, 2, 1.),
+                ],
+            )
+            .unwrap(),
+            col![1.],
+            Col::full(3, -E::INFINITY),
+            Col::full(3, E::INFINITY),
+        )
+    }
+
+    #[rstest]
+    // x_i = y / q_i with y = 4/7
+    #[case([1., 2., 4.], col![0., 0., 0.], col![4. / 7., 2. / 7., 1. / 7.])]
+    // A singular Hessian: x_2 = 1 - x_0 - x_1 leaves 1/2 (x_0^2 + x_1^2) - x_0 - x_1
+    #[case([1., 1., 0.], col![0., 0., 1.], col![1., 1., -1.])]
+    fn test_equality_qp(
+        #[case] q: [E; 3],
+        #[case] c: Col<E>,
+        #[case] expected: Col<E>,
+        #[values(
+            QPSolverType::MpcSimplicialCholesky,
+            QPSolverType::MpcSupernodalCholesky
+        )]
+        solver_type: QPSolverType,
+    ) {
+        let qp = build_qp(q, c);
+        assert!(has_no_finite_bounds(&qp));
+
+        let mut state =
+            SolverState::new(Col::zeros(3), Col::zeros(1), Col::zeros(3), Col::zeros(3));
+        let status = qp
+            .solver_builder()
+            .with_solver(solver_type)
+            .build()
+            .unwrap()
+            .solve(&mut state, &mut SolverHooks::silent())
+            .unwrap();
+        assert_eq!(status, Status::Optimal);
+        assert_eq!(state.get_nit(), 0);
+        assert!((state.get_primal() - expected).norm_max() < 1e-12);
+        assert!(state.get_dual_feasibility().norm_max() < 1e-12);
+        assert!(state.get_primal_feasibility().norm_max() < 1e-12);
+    }
+
+    fn solve(qp: &QuadraticProgram, solver_type: QPSolverType) -> (Status, SolverState) {
+        let (n, m) = qp.get_dims();
+        let mut state =
+            SolverState::new(Col::zeros(n), Col::zeros(m), Col::zeros(n), Col::zeros(n));
+        let status = qp
+            .solver_builder()
+            .with_solver(solver_type)
+            .build()
+            .unwrap()
+            .solve(&mut state, &mut SolverHooks::silent())
+            .unwrap();
+        (status, state)
+    }
+
+    #[rstest]
+    fn test_unbounded(
+        #[values(
+            QPSolverType::MpcSimplicialCholesky,
+            QPSolverType::MpcSupernodalCholesky
+        )]
+        solver_type: QPSolverType,
+    ) {
+        // min x_0 + 1/2 x_1^2 subject to x_1 = 1 decreases along x_0
+        let qp = QuadraticProgram::new(
+            SparseColMat::try_new_from_triplets(2, 2, &[Triplet::new(1, 1, 1.)]).unwrap(),
+            col![1., 0.],
+            SparseColMat::try_new_from_triplets(1, 2, &[Triplet::new(0, 1, 1.)]).unwrap(),
+            col![1.],
+            Col::full(2, -E::INFINITY),
+            Col::full(2, E::INFINITY),
+        );
+        let (status, state) = solve(&qp, solver_type);
+        assert_eq!(status, Status::Unbounded);
+        assert_eq!(state.get_nit(), 0);
+
+        // The KKT system of a nonconvex program has a finite solution, a
+        // saddle point, but an extra negative pivot along d = (0, 1, -1)
+        let (status, state) = solve(&build_qp([1., -4., 1.], col![0., 0., 0.]), solver_type);
+        assert!(state.get_dual_feasibility().norm_max() < 1e-12);
+        assert_eq!(status, Status::Unbounded);
+    }
+
+    #[rstest]
+    fn test_inconsistent_rows(
+        #[values(
+            QPSolverType::MpcSimplicialCholesky,
+            QPSolverType::MpcSupernodalCholesky
+        )]
+        solver_type: QPSolverType,
+    ) {
+        // x_0 + x_1 = 1 and x_0 + x_1 = 2
+        let qp = QuadraticProgram::new(
+            SparseColMat::try_new_from_triplets(
+                2,
+                2,
+                &[Triplet::new(0, 0, 1.), Triplet::new(1, 1, 1.)],
+            )
+            .unwrap(),
+            col![0., 0.],
+            SparseColMat::try_new_from_triplets(
+                2,
+                2,
+                &[
+                    Triplet::new(0, 0, 1.),
+                    Triplet::new(0, 1, 1.),
+                    Triplet::new(1, 0, 1.),
+                    Triplet::new(1, 1, 1.),
+                ],
+            )
+            .unwrap(),
+            col![1., 2.],
+            Col::full(2, -E::INFINITY),
+            Col::full(2, E::INFINITY),
+        );
+        assert_eq!(solve(&qp, solver_type).0, Status::Infeasible);
+    }
+
+    #[test]
+    fn test_lu_without_inertia() {
+        // The LU factorization does not report its inertia, so the program is
+        // solved by the interior-point method
+        let (status, state) = solve(
+            &build_qp([1., 2., 4.], col![0., 0., 0.]),
+            QPSolverType::MpcSimplicialLu,
+        );
+        assert_eq!(status, Status::Optimal);
+        assert!(state.get_nit() > 0);
+        assert!((state.get_primal() - col![4. / 7., 2. / 7., 1. / 7.]).norm_max() < 1e-6);
+    }
+
+    #[test]
+    fn test_disabled_fast_path() {
+        let qp = build_qp([1., 2., 4.], col![0., 0., 0.]);
+        let mut options = SolverOptions::new();
+        options.set_option("qp_equality_fast_path", false).unwrap();
+
+        let mut state =
+            SolverState::new(Col::zeros(3), Col::zeros(1), Col::zeros(3), Col::zeros(3));
+        let status = qp
+            .solver_builder()
+            .with_solver(QPSolverType::MpcSimplicialCholesky)
+            .with_options(options)
+            .build()
+            .unwrap()
+            .solve(&mut state, &mut SolverHooks::silent())
+            .unwrap();
+        assert_eq!(status, Status::Optimal);
+        assert!(state.get_nit() > 0);
+        assert!((state.get_primal() - col![4. / 7., 2. / 7., 1. / 7.]).norm_max() < 1e-6);
+    }
+}
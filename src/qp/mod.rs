@@ -1,25 +1,49 @@
-use faer::{Col, sparse::SparseColMat};
+use std::sync::OnceLock;
+
+use faer::{
+    Col,
+    sparse::{SparseColMat, SparseRowMat, Triplet},
+};
 use problemo::Problem;
 use problemo::common::IntoCommonProblem;
 
-use crate::{OptimizationProgram, SolverState};
+use macros::use_option;
+
+use crate::linalg::solver::{LinearSolver, MemoryEstimate};
 use crate::linalg::vector_ops::cwise_multiply_finite;
+use crate::lp::{
+    CERTIFICATE_TOLERANCE, bound_objective, farkas_certificate, unbounded_certificate,
+};
 use crate::nlp::NonlinearProgram;
+use crate::registry;
+use crate::{Certificate, OptimizationProgram, ProblemNorms, SolverState, max_abs};
 use crate::{
     E, I, IterativeSolver, SolverOptions,
     linalg::cholesky::{SimplicialSparseCholesky, SupernodalSparseCholesky},
     linalg::lu::SimplicialSparseLu,
+    to_index,
 };
 
+pub mod active_set;
+pub mod box_qp;
+pub mod crossover;
+pub mod equality;
 pub mod mpc;
+pub mod nnls;
 
-/// A linear program in standard form:
+pub use crate::ipm::AugmentedSystemType;
+
+/// A quadratic program in standard form:
 ///
 /// ```text
-/// min  c^T x
+/// min  1/2 x^T Q x + c^T x
 /// s.t. A x = b
 ///      l <= x <= u
 /// ```
+///
+/// Programs with row bounds `l_c <= A x <= u_c` are created with
+/// [`QuadraticProgram::new_with_row_bounds`], which stores them in this form
+/// with a slack variable for every row that is not an equality.
 #[allow(non_snake_case)]
 pub struct QuadraticProgram {
     Q: SparseColMat<I, E>,
@@ -33,6 +57,16 @@ pub struct QuadraticProgram {
     l: Col<E>,
     /// Upper bounds on the variables.
     u: Col<E>,
+    /// Row of each slack variable, in the order of the slack columns that
+    /// follow the structural variables.
+    slack_rows: Vec<usize>,
+    /// Number of factor variables `t = F^T x` of a factor model, which are the
+    /// last columns, with their defining equalities as the last rows.
+    n_factors: usize,
+    /// Row-major copy of `A`, built on first use.
+    A_csr: OnceLock<SparseRowMat<I, E>>,
+    /// Norms of `Q`, `A`, `b` and `c`, computed on first use.
+    norms: OnceLock<ProblemNorms>,
 }
 
 #[allow(non_snake_case)]
@@ -46,7 +80,172 @@ impl QuadraticProgram {
         l: Col<E>,
         u: Col<E>,
     ) -> Self {
-        Self { Q, c, A, b, l, u }
+        Self {
+            Q,
+            c,
+            A,
+            b,
+            l,
+            u,
+            slack_rows: Vec::new(),
+            n_factors: 0,
+            A_csr: OnceLock::new(),
+            norms: OnceLock::new(),
+        }
+    }
+
+    /// Creates a quadratic program with the row bounds `row_lower <= A x <= row_upper`
+    /// instead of equality constraints.
+    ///
+    /// Rows with equal bounds become equality constraints. Every other row `i`
+    /// gets a slack variable `w_i` with `a_i^T x - w_i = 0` and
+    /// `row_lower_i <= w_i <= row_upper_i`, appended after the variables `x`,
+    /// so that the solvers treat the row bounds as variable bounds.
+    pub fn new_with_row_bounds(
+        Q: SparseColMat<I, E>,
+        c: Col<E>,
+        A: SparseColMat<I, E>,
+        row_lower: Col<E>,
+        row_upper: Col<E>,
+        l: Col<E>,
+        u: Col<E>,
+    ) -> Result<Self, Problem> {
+        let (m, n) = (A.nrows(), A.ncols());
+        if Q.nrows() != n || Q.ncols() != n || c.nrows() != n || l.nrows() != n || u.nrows() != n {
+            return Err(format!("Objective and bounds must have {n} variables").gloss());
+        }
+        if row_lower.nrows() != m || row_upper.nrows() != m {
+            return Err(format!("Row bounds must have {m} rows").gloss());
+        }
+        if let Some(i) = (0..m).find(|&i| row_lower[i] > row_upper[i]) {
+            return Err(format!(
+                "Row {i} has lower bound {} above upper bound {}",
+                row_lower[i], row_upper[i]
+            )
+            .gloss());
+        }
+
+        let slack_rows: Vec<usize> = (0..m).filter(|&i| row_lower[i] != row_upper[i]).collect();
+        let k = slack_rows.len();
+
+        let mut triplets = Vec::with_capacity(A.compute_nnz() + k);
+        let mut hessian = Vec::with_capacity(Q.compute_nnz());
+        for (matrix, triplets) in [(&A, &mut triplets), (&Q, &mut hessian)] {
+            for j in 0..n {
+                let rows = matrix.symbolic().row_idx_of_col_raw(j);
+                for (&i, &value) in rows.iter().zip(matrix.val_of_col(j)) {
+                    triplets.push(Triplet::new(i, to_index(j), value));
+                }
+            }
+        }
+        for (s, &i) in slack_rows.iter().enumerate() {
+            triplets.push(Triplet::new(to_index(i), to_index(n + s), E::from(-1.)));
+        }
+
+        let b = Col::from_fn(m, |i| {
+            if row_lower[i] == row_upper[i] {
+                row_lower[i]
+            } else {
+                E::from(0.)
+            }
+        });
+
+        Ok(Self {
+            Q: SparseColMat::try_new_from_triplets(n + k, n + k, &hessian).unwrap(),
+            c: Col::from_fn(n + k, |j| if j < n { c[j] } else { E::from(0.) }),
+            A: SparseColMat::try_new_from_triplets(m, n + k, &triplets).unwrap(),
+            b,
+            l: Col::from_fn(n + k, |j| {
+                if j < n {
+                    l[j]
+                } else {
+                    row_lower[slack_rows[j - n]]
+                }
+            }),
+            u: Col::from_fn(n + k, |j| {
+                if j < n {
+                    u[j]
+                } else {
+                    row_upper[slack_rows[j - n]]
+                }
+            }),
+            slack_rows,
+            n_factors: 0,
+            A_csr: OnceLock::new(),
+            norms: OnceLock::new(),
+        })
+    }
+
+    /// Creates a quadratic program with the factor-model Hessian
+    /// `Q = F F^T + diag(d)` without forming `Q`.
+    ///
+    /// `F` has a column for each of the `k` factors. The program is stored with
+    /// the factor variables `t = F^T x` appended after the variables `x` and
+    /// the rows `F^T x - t = 0` appended after the rows of `A`, so that
+    ///
+    /// ```text
+    /// 1/2 x^T Q x = 1/2 x^T diag(d) x + 1/2 t^T t
+    /// ```
+    ///
+    /// has a diagonal Hessian. The interior-point solvers then eliminate the
+    /// diagonal into the slack-reduced system or the normal equations, whose
+    /// Schur complement has a `k x k` block `F^T D^{-1} F + I` instead of the
+    /// dense `n x n` matrix `Q`.
+    pub fn new_factor_model(
+        F: SparseColMat<I, E>,
+        d: Col<E>,
+        c: Col<E>,
+        A: SparseColMat<I, E>,
+        b: Col<E>,
+        l: Col<E>,
+        u: Col<E>,
+    ) -> Result<Self, Problem> {
+        let (m, n, k) = (A.nrows(), A.ncols(), F.ncols());
+        if F.nrows() != n || d.nrows() != n || c.nrows() != n || l.nrows() != n || u.nrows() != n {
+            return Err(format!("Factors, objective and bounds must have {n} variables").gloss());
+        }
+        if b.nrows() != m {
+            return Err(format!("Right-hand side must have {m} rows").gloss());
+        }
+        if let Some(j) = (0..n).find(|&j| d[j] < E::from(0.) || d[j].is_nan()) {
+            return Err(format!("Diagonal entry {j} of the factor model is negative").gloss());
+        }
+
+        let mut triplets = Vec::with_capacity(A.compute_nnz() + F.compute_nnz() + k);
+        for j in 0..n {
+            for (i, &value) in A.row_idx_of_col(j).zip(A.val_of_col(j)) {
+                triplets.push(Triplet::new(to_index(i), to_index(j), value));
+            }
+        }
+        for f in 0..k {
+            for (j, &value) in F.row_idx_of_col(f).zip(F.val_of_col(f)) {
+                triplets.push(Triplet::new(to_index(m + f), to_index(j), value));
+            }
+            triplets.push(Triplet::new(to_index(m + f), to_index(n + f), E::from(-1.)));
+        }
+        let hessian = (0..n + k)
+            .filter(|&j| j >= n || d[j] != E::from(0.))
+            .map(|j| {
+                Triplet::new(
+                    to_index(j),
+                    to_index(j),
+                    if j < n { d[j] } else { E::from(1.) },
+                )
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Self {
+            Q: SparseColMat::try_new_from_triplets(n + k, n + k, &hessian).unwrap(),
+            c: Col::from_fn(n + k, |j| if j < n { c[j] } else { E::from(0.) }),
+            A: SparseColMat::try_new_from_triplets(m + k, n + k, &triplets).unwrap(),
+            b: Col::from_fn(m + k, |i| if i < m { b[i] } else { E::from(0.) }),
+            l: Col::from_fn(n + k, |j| if j < n { l[j] } else { -E::INFINITY }),
+            u: Col::from_fn(n + k, |j| if j < n { u[j] } else { E::INFINITY }),
+            slack_rows: Vec::new(),
+            n_factors: k,
+            A_csr: OnceLock::new(),
+            norms: OnceLock::new(),
+        })
     }
 
     /// Returns the number of variables (columns of `A`).
@@ -64,6 +263,10 @@ impl QuadraticProgram {
         (self.get_n_vars(), self.get_n_cons())
     }
 
+    pub fn get_quadratic_objective(&self) -> &SparseColMat<I, E> {
+        &self.Q
+    }
+
     pub fn get_linear_objective(&self) -> &Col<E> {
         &self.c
     }
@@ -72,6 +275,12 @@ impl QuadraticProgram {
         &self.A
     }
 
+    /// Row-major copy of the constraint matrix, i.e. the column-major storage
+    /// of `A^T`, built on first use.
+    pub fn get_constraint_matrix_csr(&self) -> &SparseRowMat<I, E> {
+        self.A_csr.get_or_init(|| self.A.to_row_major().unwrap())
+    }
+
     pub fn get_rhs(&self) -> &Col<E> {
         &self.b
     }
@@ -84,20 +293,107 @@ impl QuadraticProgram {
         &self.u
     }
 
+    /// Number of variables of the model, without the slack variables of the
+    /// row bounds and the factor variables of a factor model.
+    pub fn get_n_structural(&self) -> usize {
+        self.get_n_vars() - self.slack_rows.len() - self.n_factors
+    }
+
+    /// Number of factors of a program created with
+    /// [`QuadraticProgram::new_factor_model`], zero otherwise.
+    pub fn get_n_factors(&self) -> usize {
+        self.n_factors
+    }
+
+    /// Number of rows of the model, without the rows that define the factor
+    /// variables of a factor model.
+    pub fn get_n_rows(&self) -> usize {
+        self.get_n_cons() - self.n_factors
+    }
+
+    /// Rows that have a slack variable, in the order of the slack columns.
+    pub fn get_slack_rows(&self) -> &[usize] {
+        &self.slack_rows
+    }
+
+    /// Lower bounds `l_c` on the rows `A x` of the model.
+    pub fn get_row_lower_bounds(&self) -> Col<E> {
+        let mut bounds = self.b.subrows(0, self.get_n_rows()).to_owned();
+        for (s, &i) in self.slack_rows.iter().enumerate() {
+            bounds[i] = self.l[self.get_n_structural() + s];
+        }
+        bounds
+    }
+
+    /// Upper bounds `u_c` on the rows `A x` of the model.
+    pub fn get_row_upper_bounds(&self) -> Col<E> {
+        let mut bounds = self.b.subrows(0, self.get_n_rows()).to_owned();
+        for (s, &i) in self.slack_rows.iter().enumerate() {
+            bounds[i] = self.u[self.get_n_structural() + s];
+        }
+        bounds
+    }
+
+    /// Structural variables of the iterate `x`.
+    pub fn get_structural_values(&self, x: &Col<E>) -> Col<E> {
+        x.subrows(0, self.get_n_structural()).to_owned()
+    }
+
+    /// Row activities `A x` of the model at the iterate `x`.
+    pub fn get_row_values(&self, x: &Col<E>) -> Col<E> {
+        let mut values = (self.A.as_ref() * x)
+            .subrows(0, self.get_n_rows())
+            .to_owned();
+        for (s, &i) in self.slack_rows.iter().enumerate() {
+            values[i] += x[self.get_n_structural() + s];
+        }
+        values
+    }
+
     pub fn solver_builder<'a>(&'a self) -> QPSolverBuilder<'a> {
         QPSolverBuilder::new().with_lp(self)
     }
 }
 
 impl OptimizationProgram for QuadraticProgram {
+    fn get_norms(&self) -> Option<ProblemNorms> {
+        Some(*self.norms.get_or_init(|| {
+            ProblemNorms::new(
+                max_abs(self.A.val()),
+                self.b.norm_max(),
+                self.c.norm_max(),
+                max_abs(self.Q.val()),
+            )
+        }))
+    }
+
     fn update_residual(&self, state: &mut SolverState) {
-        state.dual_feasibility = -&self.Q * &state.x - &self.c
-            + self.A.transpose() * &state.y
-            + &state.z_l
-            + &state.z_u;
+        crate::profile!("residual");
+        let qx = &self.Q * &state.x;
+        state.dual_feasibility =
+            -&qx - &self.c + self.A.transpose() * &state.y + &state.z_l + &state.z_u;
         state.primal_feasibility = self.A.as_ref() * &state.x - &self.b;
         state.cs_lower = -cwise_multiply_finite(state.z_l.as_ref(), (&state.x - &self.l).as_ref());
         state.cs_upper = -cwise_multiply_finite(state.z_u.as_ref(), (&state.x - &self.u).as_ref());
+
+        // Wolfe dual objective
+        let quadratic = E::from(0.5) * (state.x.transpose() * &qx);
+        let primal_obj = quadratic + self.c.transpose() * &state.x;
+        let dual_obj = self.b.transpose() * &state.y
+            + bound_objective(&self.l, &self.u, &state.z_l, &state.z_u)
+            - quadratic;
+        state.set_objective_values(primal_obj, dual_obj);
+    }
+
+    /// Directions of unboundedness must also lie in the null space of `Q`.
+    fn get_certificate(&self, state: &SolverState) -> Option<Certificate> {
+        farkas_certificate(&self.A, &self.b, &self.l, &self.u, &state.y).or_else(|| {
+            let certificate = unbounded_certificate(&self.A, &self.c, &self.l, &self.u, &state.x)?;
+            let Certificate::Unbounded(d) = &certificate else {
+                return None;
+            };
+            ((&self.Q * d).norm_max() <= CERTIFICATE_TOLERANCE).then_some(certificate)
+        })
     }
 }
 
@@ -128,11 +424,27 @@ impl From<QuadraticProgram> for NonlinearProgram {
 }
 
 /// Trait for solvers that operate on a [`QuadraticProgram`].
-pub trait QPSolver<'a>: IterativeSolver {
+///
+/// Solvers are `Send`, and [`QuadraticProgram`] is `Send + Sync`, so that built
+/// solvers can be moved to worker threads.
+pub trait QPSolver<'a>: IterativeSolver + Send {
     /// Creates a new solver instance for the given linear program and options.
     fn new(lp: &'a QuadraticProgram, options: &SolverOptions) -> Self
     where
         Self: Sized;
+
+    /// Memory of the factorizations of the solver, estimated before the first
+    /// iteration. `None` if the solver does not factorize or cannot predict it.
+    fn estimate_memory(&self) -> Option<MemoryEstimate> {
+        None
+    }
+
+    /// Largest primal regularization `delta` of `Q + delta I` in the Newton
+    /// systems so far. A positive value means that the iterates solve a
+    /// perturbed problem. `None` if the solver does not regularize.
+    fn get_regularization(&self) -> Option<E> {
+        None
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -144,11 +456,32 @@ pub enum QPSolverType {
     MpcMKL,
     #[cfg(feature = "panua")]
     MpcPanua,
+    /// Dual active-set method with dense factorizations, for small and medium
+    /// programs (see [`active_set`]).
+    ActiveSet,
 }
 
+/// Builds a solver of the type given by [`QPSolverBuilder::with_solver`], or
+/// the registered solver named with [`QPSolverBuilder::with_solver_name`] or
+/// `qp_solver`.
+///
+/// Programs without finite bounds are dispatched to
+/// [`equality::EqualityQPSolver`] with the linear solver of that type if it is
+/// a Cholesky solver, whose factorizations report their inertia, unless
+/// `qp_equality_fast_path` is disabled. Programs without rows are dispatched
+/// to [`box_qp::BoxQPSolver`], unless `qp_box_fast_path` is disabled. The
+/// [`QPSolverType::ActiveSet`] solver handles every program itself.
+#[use_option(name = "augmented_system", type_ = crate::ipm::AugmentedSystemType, default = "auto", description = "Formulation of the Newton system in interior-point methods.")]
+#[use_option(name = "qp_equality_fast_path", type_ = bool, default = "true", description = "Solve quadratic programs without finite bounds with a single factorization of their KKT system.")]
+#[use_option(name = "qp_box_fast_path", type_ = bool, default = "true", description = "Solve quadratic programs without rows by projected Newton steps instead of interior-point iterations.")]
+#[use_option(name = "qp_mu_update", type_ = crate::qp::mpc::mu_update::MuUpdates, default = "adaptive", description = "Barrier parameter update of the interior-point method for quadratic programs (adaptive or constant).")]
+#[use_option(name = "qp_line_search", type_ = crate::qp::mpc::line_search::LineSearches, default = "max_step", description = "Step length rule of the interior-point method for quadratic programs (max_step).")]
+#[use_option(name = "qp_solver", type_ = String, default = "", description = "Name of a registered quadratic program solver to build when no solver type is given; empty requires a solver type.")]
 pub struct QPSolverBuilder<'a> {
     lp: Option<&'a QuadraticProgram>,
     solver_type: Option<QPSolverType>,
+    solver_name: Option<String>,
+    system_type: Option<AugmentedSystemType>,
     options: SolverOptions,
 }
 
@@ -157,6 +490,8 @@ impl<'a> QPSolverBuilder<'a> {
         Self {
             lp: None,
             solver_type: None,
+            solver_name: None,
+            system_type: None,
             options: SolverOptions::new(),
         }
     }
@@ -171,6 +506,17 @@ impl<'a> QPSolverBuilder<'a> {
         self
     }
 
+    /// Selects a solver registered with [`crate::registry::register_qp_solver`].
+    pub fn with_solver_name(mut self, name: &str) -> Self {
+        self.solver_name = Some(name.to_string());
+        self
+    }
+
+    pub fn with_system(mut self, system_type: AugmentedSystemType) -> Self {
+        self.system_type = Some(system_type);
+        self
+    }
+
     pub fn with_options(mut self, options: SolverOptions) -> Self {
         self.options = options;
         self
@@ -180,47 +526,184 @@ impl<'a> QPSolverBuilder<'a> {
         let lp = self
             .lp
             .ok_or_else(|| "Linear program must be provided".gloss())?;
+        if self.solver_type.is_none()
+            && let Some(name) =
+                registry::selected_name(self.solver_name.clone(), &self.options, "qp_solver")
+        {
+            return registry::build_qp_solver(&name, lp, &self.options);
+        }
         let solver_type = self
             .solver_type
             .ok_or_else(|| "Solver type must be specified".gloss())?;
+        if let QPSolverType::ActiveSet = solver_type {
+            return Ok(Box::new(active_set::ActiveSetQPSolver::new(
+                lp,
+                &self.options,
+            )));
+        }
 
-        match solver_type {
-            QPSolverType::MpcSimplicialCholesky => {
-                Ok(Box::new(mpc::MehrotraPredictorCorrector::<
-                    'a,
-                    SimplicialSparseCholesky,
-                    mpc::augmented_system::StandardSystem<'a, SimplicialSparseCholesky>,
-                    mpc::mu_update::AdaptiveMuUpdate<'a>,
-                >::new(lp, &self.options)))
+        if equality::has_no_finite_bounds(lp)
+            && self
+                .options
+                .get_option::<bool>("qp_equality_fast_path")
+                .unwrap_or(true)
+            && let Some(solver) = build_equality(lp, solver_type, &self.options)
+        {
+            return Ok(solver);
+        }
+        if box_qp::has_no_rows(lp)
+            && self
+                .options
+                .get_option::<bool>("qp_box_fast_path")
+                .unwrap_or(true)
+        {
+            return Ok(build_box(lp, solver_type, &self.options));
+        }
+
+        // Get the system type from the builder or fallback to options
+        let system_type = match self
+            .system_type
+            .or(self
+                .options
+                .get_option::<AugmentedSystemType>("augmented_system"))
+            .unwrap_or_default()
+        {
+            AugmentedSystemType::Auto => mpc::augmented_system::select_system(lp),
+            AugmentedSystemType::SchurComplement => {
+                return Err(
+                    "Schur complement elimination is only available for linear programs".gloss(),
+                );
             }
-            QPSolverType::MpcSupernodalCholesky => {
-                Ok(Box::new(mpc::MehrotraPredictorCorrector::<
-                    'a,
-                    SupernodalSparseCholesky,
-                    mpc::augmented_system::StandardSystem<'a, SupernodalSparseCholesky>,
-                    mpc::mu_update::AdaptiveMuUpdate<'a>,
-                >::new(lp, &self.options)))
+            AugmentedSystemType::SlackReduced | AugmentedSystemType::NormalEquations
+                if !mpc::augmented_system::has_diagonal_hessian(lp) =>
+            {
+                return Err("Reduced augmented systems require a diagonal Hessian".gloss());
             }
-            QPSolverType::MpcSimplicialLu => Ok(Box::new(mpc::MehrotraPredictorCorrector::<
-                'a,
-                SimplicialSparseLu,
-                mpc::augmented_system::StandardSystem<'a, SimplicialSparseLu>,
-                mpc::mu_update::AdaptiveMuUpdate<'a>,
-            >::new(lp, &self.options))),
+            AugmentedSystemType::NormalEquations
+                if !mpc::augmented_system::has_bounded_variables(lp) =>
+            {
+                return Err(
+                    "Normal equations require every variable to have a finite bound or positive curvature"
+                        .gloss(),
+                );
+            }
+            system_type => system_type,
+        };
+
+        match solver_type {
+            QPSolverType::ActiveSet => {
+                unreachable!("The active-set solver is built without a linear solver")
+            }
+            QPSolverType::MpcSimplicialCholesky => Ok(build_mpc::<SimplicialSparseCholesky>(
+                lp,
+                system_type,
+                &self.options,
+            )),
+            QPSolverType::MpcSupernodalCholesky => Ok(build_mpc::<SupernodalSparseCholesky>(
+                lp,
+                system_type,
+                &self.options,
+            )),
+            QPSolverType::MpcSimplicialLu => Ok(build_mpc::<SimplicialSparseLu>(
+                lp,
+                system_type,
+                &self.options,
+            )),
             #[cfg(feature = "mkl")]
-            QPSolverType::MpcMKL => Ok(Box::new(mpc::MehrotraPredictorCorrector::<
-                'a,
-                crate::linalg::pardiso::MKLPardiso,
-                mpc::augmented_system::StandardSystem<'a, crate::linalg::pardiso::MKLPardiso>,
-                mpc::mu_update::AdaptiveMuUpdate<'a>,
-            >::new(lp, &self.options))),
+            QPSolverType::MpcMKL => Ok(build_mpc::<crate::linalg::pardiso::MKLPardiso>(
+                lp,
+                system_type,
+                &self.options,
+            )),
             #[cfg(feature = "panua")]
-            QPSolverType::MpcPanua => Ok(Box::new(mpc::MehrotraPredictorCorrector::<
+            QPSolverType::MpcPanua => Ok(build_mpc::<crate::linalg::pardiso::PanuaPardiso>(
+                lp,
+                system_type,
+                &self.options,
+            )),
+        }
+    }
+}
+
+/// Instantiates the direct solver of programs without finite bounds with the
+/// linear solver of `solver_type`, or `None` if that solver does not report the
+/// inertia of its factorizations, which rejects nonconvex programs.
+fn build_equality<'a>(
+    qp: &'a QuadraticProgram,
+    solver_type: QPSolverType,
+    options: &SolverOptions,
+) -> Option<Box<dyn QPSolver<'a> + 'a>> {
+    match solver_type {
+        QPSolverType::MpcSimplicialCholesky => Some(Box::new(equality::EqualityQPSolver::<
+            SimplicialSparseCholesky,
+        >::new(qp, options))),
+        QPSolverType::MpcSupernodalCholesky => Some(Box::new(equality::EqualityQPSolver::<
+            SupernodalSparseCholesky,
+        >::new(qp, options))),
+        _ => None,
+    }
+}
+
+/// Instantiates the projected Newton solver of programs without rows with the
+/// linear solver of `solver_type`.
+fn build_box<'a>(
+    qp: &'a QuadraticProgram,
+    solver_type: QPSolverType,
+    options: &SolverOptions,
+) -> Box<dyn QPSolver<'a> + 'a> {
+    match solver_type {
+        QPSolverType::ActiveSet => {
+            unreachable!("The active-set solver is built without a linear solver")
+        }
+        QPSolverType::MpcSimplicialCholesky => Box::new(box_qp::BoxQPSolver::<
+            SimplicialSparseCholesky,
+        >::new(qp, options)),
+        QPSolverType::MpcSupernodalCholesky => Box::new(box_qp::BoxQPSolver::<
+            SupernodalSparseCholesky,
+        >::new(qp, options)),
+        QPSolverType::MpcSimplicialLu => {
+            Box::new(box_qp::BoxQPSolver::<SimplicialSparseLu>::new(qp, options))
+        }
+        #[cfg(feature = "mkl")]
+        QPSolverType::MpcMKL => {
+            Box::new(box_qp::BoxQPSolver::<crate::linalg::pardiso::MKLPardiso>::new(qp, options))
+        }
+        #[cfg(feature = "panua")]
+        QPSolverType::MpcPanua => {
+            Box::new(box_qp::BoxQPSolver::<crate::linalg::pardiso::PanuaPardiso>::new(qp, options))
+        }
+    }
+}
+
+/// Instantiates the MPC solver with the augmented system given by `system_type`.
+fn build_mpc<'a, LinSolve: LinearSolver + 'a>(
+    qp: &'a QuadraticProgram,
+    system_type: AugmentedSystemType,
+    options: &SolverOptions,
+) -> Box<dyn QPSolver<'a> + 'a> {
+    match system_type {
+        AugmentedSystemType::Auto | AugmentedSystemType::Standard => {
+            Box::new(mpc::MehrotraPredictorCorrector::<
                 'a,
-                crate::linalg::pardiso::PanuaSolver,
-                mpc::augmented_system::StandardSystem<'a, crate::linalg::pardiso::PanuaSolver>,
-                mpc::mu_update::AdaptiveMuUpdate<'a>,
-            >::new(lp, &self.options))),
+                LinSolve,
+                mpc::augmented_system::StandardSystem<'a, LinSolve>,
+                Box<dyn mpc::mu_update::MuUpdate<'a> + 'a>,
+            >::new(qp, options))
+        }
+        AugmentedSystemType::SlackReduced => Box::new(mpc::MehrotraPredictorCorrector::<
+            'a,
+            LinSolve,
+            mpc::augmented_system::SlackReducedSystem<'a, LinSolve>,
+            Box<dyn mpc::mu_update::MuUpdate<'a> + 'a>,
+        >::new(qp, options)),
+        AugmentedSystemType::NormalEquations => Box::new(mpc::MehrotraPredictorCorrector::<
+            'a,
+            LinSolve,
+            mpc::augmented_system::NormalEquationsSystem<'a, LinSolve>,
+            Box<dyn mpc::mu_update::MuUpdate<'a> + 'a>,
+        >::new(qp, options)),
+        AugmentedSystemType::SchurComplement => {
+            unreachable!("Schur complement elimination is rejected for quadratic programs")
         }
     }
 }
@@ -232,15 +715,16 @@ mod tests {
     use std::sync::OnceLock;
 
     use faer::{
-        Col, ColRef,
+        Col, ColRef, col,
         sparse::{SparseColMat, Triplet},
     };
     use rstest::{fixture, rstest};
     use rstest_reuse::{apply, template};
 
     use crate::{
-        E, SolverHooks, SolverOptions, SolverState, callback::ConvergenceOutput,
-        terminators::ConvergenceTerminator,
+        E, SolverHooks, SolverOptions, SolverState,
+        callback::{ConvergenceOutput, NoOpCallback},
+        terminators::{ComplementarityTerminator, ConvergenceTerminator},
     };
 
     #[template]
@@ -316,7 +800,7 @@ mod tests {
         let options = SolverOptions::new();
 
         let mut properties = SolverHooks {
-            callback: Box::new(ConvergenceOutput::new()),
+            callback: Box::new(ConvergenceOutput::new(&options)),
             terminator: Box::new(ConvergenceTerminator::new(&options)),
         };
 
@@ -329,4 +813,411 @@ mod tests {
 
         assert_eq!(status.unwrap(), crate::Status::Optimal);
     }
+
+    #[rstest]
+    fn test_system_types(
+        #[values(build_simple_qp())] qp: &'static QuadraticProgram,
+        #[values(
+            AugmentedSystemType::Auto,
+            AugmentedSystemType::Standard,
+            AugmentedSystemType::SlackReduced,
+            AugmentedSystemType::NormalEquations
+        )]
+        system_type: AugmentedSystemType,
+    ) {
+        let mut state = SolverState::new(
+            Col::ones(qp.get_n_vars()),
+            Col::ones(qp.get_n_cons()),
+            Col::ones(qp.get_n_vars()),
+            -Col::<E>::ones(qp.get_n_vars()),
+        );
+
+        let options = SolverOptions::new();
+
+        let mut properties = SolverHooks {
+            callback: Box::new(ConvergenceOutput::new(&options)),
+            terminator: Box::new(ConvergenceTerminator::new(&options)),
+        };
+
+        let mut solver = QuadraticProgram::solver_builder(qp)
+            .with_solver(QPSolverType::MpcSimplicialCholesky)
+            .with_system(system_type)
+            .with_options(options.clone())
+            .build()
+            .unwrap();
+        let status = solver.solve(&mut state, &mut properties);
+
+        assert_eq!(status.unwrap(), crate::Status::Optimal);
+    }
+
+    #[rstest]
+    fn test_objective_values(#[values(build_simple_qp())] qp: &'static QuadraticProgram) {
+        // Multipliers only on finite bounds, so that the dual objective is finite
+        let mut state = SolverState::new_interior(&qp.l, &qp.u, qp.get_n_cons());
+        assert_eq!(state.get_primal_objective(), None);
+
+        let options = SolverOptions::new();
+        let mut properties = SolverHooks {
+            callback: Box::new(NoOpCallback::new()),
+            terminator: Box::new(ComplementarityTerminator::new(&options)),
+        };
+        let mut solver = QuadraticProgram::solver_builder(qp)
+            .with_solver(QPSolverType::MpcSimplicialCholesky)
+            .with_options(options.clone())
+            .build()
+            .unwrap();
+        let status = solver.solve(&mut state, &mut properties);
+        assert_eq!(status.unwrap(), crate::Status::Optimal);
+
+        let primal = state.get_primal_objective().unwrap();
+        let dual = state.get_dual_objective().unwrap();
+        assert!(
+            (primal - crate::verify::VerifiableProgram::objective(qp, state.get_primal())).abs()
+                < 1e-12
+        );
+        assert!((primal - dual).abs() < 1e-5, "primal {primal}, dual {dual}");
+        assert_eq!(state.get_duality_gap(), Some(primal - dual));
+    }
+
+    #[rstest]
+    fn test_infeasible_start(
+        #[values(build_simple_qp())] qp: &'static QuadraticProgram,
+        #[values(false, true)] infeasible_start: bool,
+    ) {
+        // Far from x_0 + x_1 = 1 and x_1 + x_2 = 1
+        let mut state = SolverState::new_interior(&qp.l, &qp.u, qp.get_n_cons());
+        state.x = Col::from_fn(3, |_| 1e4);
+
+        let mut options = SolverOptions::new();
+        options
+            .set_option("qp_infeasible_start", infeasible_start)
+            .unwrap();
+        let mut properties = SolverHooks {
+            callback: Box::new(NoOpCallback::new()),
+            terminator: Box::new(ComplementarityTerminator::new(&options)),
+        };
+        let mut solver = QuadraticProgram::solver_builder(qp)
+            .with_solver(QPSolverType::MpcSimplicialCholesky)
+            .with_options(options.clone())
+            .build()
+            .unwrap();
+
+        // A x - b is linear along the step. The corrector direction removes all
+        // of it by default and only the fraction 1 - sigma with the option
+        qp.update_residual(&mut state);
+        let mut n_damped = 0;
+        for _ in 0..4 {
+            let residual = state.get_primal_feasibility().norm_l2();
+            solver.iterate(&mut state).unwrap();
+            let (alpha, sigma) = (state.alpha_primal, state.sigma.unwrap());
+            let full = (1. - alpha) * residual;
+            let damped = (1. - alpha * (1. - sigma)) * residual;
+            let expected = if infeasible_start { damped } else { full };
+            let actual = state.get_primal_feasibility().norm_l2();
+            assert!(
+                (actual - expected).abs() < 1e-8 * residual,
+                "residual {actual}, expected {expected}"
+            );
+            n_damped += usize::from(damped - full > 1e-4 * residual);
+        }
+        assert!(n_damped > 0);
+
+        let status = solver.solve(&mut state, &mut properties);
+        assert_eq!(status.unwrap(), crate::Status::Optimal);
+
+        // x = (1/3, 2/3, 1/3)
+        let primal = state.get_primal_objective().unwrap();
+        assert!((primal - 2. / 3.).abs() < 1e-6, "primal {primal}");
+    }
+
+    #[rstest]
+    fn test_hessian_regularization(#[values(false, true)] auto: bool) {
+        // min x_0^2 + x_1 subject to x_0 + x_1 = 1, -10 <= x_0 <= 10 and x_1
+        // free, whose Hessian block is singular in x_1
+        let qp = QuadraticProgram::new(
+            SparseColMat::try_new_from_triplets(2, 2, &[Triplet::new(0, 0, 2.)]).unwrap(),
+            col![0., 1.],
+            SparseColMat::try_new_from_triplets(
+                1,
+                2,
+                &[Triplet::new(0, 0, 1.), Triplet::new(0, 1, 1.)],
+            )
+            .unwrap(),
+            col![1.],
+            col![-10., E::NEG_INFINITY],
+            col![10., E::INFINITY],
+        );
+
+        // Eliminating x_1 before the row meets a zero pivot
+        let mut options = SolverOptions::new();
+        options
+            .set_option(
+                "cholesky_ordering",
+                crate::linalg::ordering::FillReducingOrdering::Natural,
+            )
+            .unwrap();
+        options
+            .set_option("augmented_system", AugmentedSystemType::Standard)
+            .unwrap();
+        options.set_option("qp_auto_regularization", auto).unwrap();
+        let mut properties = SolverHooks {
+            callback: Box::new(NoOpCallback::new()),
+            terminator: Box::new(ConvergenceTerminator::new(&options)),
+        };
+        let mut solver = qp
+            .solver_builder()
+            .with_solver(QPSolverType::MpcSimplicialCholesky)
+            .with_options(options.clone())
+            .build()
+            .unwrap();
+        let mut state = SolverState::new_interior(&qp.l, &qp.u, 1);
+        let status = solver.solve(&mut state, &mut properties);
+        if !auto {
+            assert!(status.is_err());
+            assert_eq!(solver.get_regularization(), Some(0.));
+            return;
+        }
+        assert_eq!(status.unwrap(), crate::Status::Optimal);
+        let delta = solver.get_regularization().unwrap();
+        assert!(delta > 0. && delta <= 1e-2, "delta {delta}");
+
+        // The perturbed Newton steps still converge to x = (1/2, 1/2)
+        assert!((state.get_primal() - col![0.5, 0.5]).norm_max() < 1e-6);
+    }
+
+    /// `min (x_0 - 2)^2 + (x_1 - 2)^2` subject to `1 <= x_0 + x_1 <= 2`,
+    /// `x_0 - x_1 = 0` and `0 <= x <= 10`.
+    #[allow(non_snake_case)]
+    fn build_row_bounded_qp(row_lower: Col<E>) -> Result<QuadraticProgram, Problem> {
+        let Q = SparseColMat::try_new_from_triplets(
+            2,
+            2,
+            &[Triplet::new(0, 0, 2.0), Triplet::new(1, 1, 2.0)],
+        )
+        .unwrap();
+        let A = SparseColMat::try_new_from_triplets(
+            2,
+            2,
+            &[
+                Triplet::new(0, 0, 1.0),
+                Triplet::new(0, 1, 1.0),
+                Triplet::new(1, 0, 1.0),
+                Triplet::new(1, 1, -1.0),
+            ],
+        )
+        .unwrap();
+        QuadraticProgram::new_with_row_bounds(
+            Q,
+            col![-4.0, -4.0],
+            A,
+            row_lower,
+            col![2.0, 0.0],
+            col![0.0, 0.0],
+            col![10.0, 10.0],
+        )
+    }
+
+    #[apply(solver_types)]
+    fn test_row_bounds(solver_type: QPSolverType) {
+        assert!(build_row_bounded_qp(col![3.0, 0.0]).is_err());
+        let qp = build_row_bounded_qp(col![1.0, 0.0]).unwrap();
+        assert_eq!(qp.get_dims(), (3, 2));
+        assert_eq!(qp.get_n_structural(), 2);
+        assert_eq!(qp.get_slack_rows(), &[0]);
+        assert_eq!(qp.get_row_lower_bounds(), col![1.0, 0.0]);
+        assert_eq!(qp.get_row_upper_bounds(), col![2.0, 0.0]);
+
+        let mut state = SolverState::new_interior(&qp.l, &qp.u, qp.get_n_cons());
+        let mut hooks = SolverHooks::silent();
+        let status = qp
+            .solver_builder()
+            .with_solver(solver_type)
+            .build()
+            .unwrap()
+            .solve(&mut state, &mut hooks)
+            .unwrap();
+        assert_eq!(status, crate::Status::Optimal);
+
+        // The upper bound of the first row is binding
+        let x = qp.get_structural_values(state.get_primal());
+        assert!((&x - col![1.0, 1.0]).norm_l2() < 1e-6);
+        let rows = qp.get_row_values(state.get_primal());
+        assert!((rows - col![2.0, 0.0]).norm_l2() < 1e-6);
+    }
+
+    #[apply(solver_types)]
+    fn test_free_rows(solver_type: QPSolverType) {
+        // min (x_0 - 2)^2 + (x_1 - 1)^2 subject to 1 <= x_0 + x_1 <= 2 and the
+        // free row x_0 - x_1, whose slack is free and whose multiplier vanishes
+        let q = SparseColMat::try_new_from_triplets(
+            2,
+            2,
+            &[Triplet::new(0, 0, 2.0), Triplet::new(1, 1, 2.0)],
+        )
+        .unwrap();
+        let a = SparseColMat::try_new_from_triplets(
+            2,
+            2,
+            &[
+                Triplet::new(0, 0, 1.0),
+                Triplet::new(0, 1, 1.0),
+                Triplet::new(1, 0, 1.0),
+                Triplet::new(1, 1, -1.0),
+            ],
+        )
+        .unwrap();
+        let qp = QuadraticProgram::new_with_row_bounds(
+            q,
+            col![-4.0, -2.0],
+            a,
+            col![1.0, -E::INFINITY],
+            col![2.0, E::INFINITY],
+            col![0.0, 0.0],
+            col![10.0, 10.0],
+        )
+        .unwrap();
+        assert_eq!(qp.get_slack_rows(), &[0, 1]);
+
+        let mut state = SolverState::new_interior(&qp.l, &qp.u, qp.get_n_cons());
+        let status = qp
+            .solver_builder()
+            .with_solver(solver_type)
+            .build()
+            .unwrap()
+            .solve(&mut state, &mut SolverHooks::silent())
+            .unwrap();
+        assert_eq!(status, crate::Status::Optimal);
+
+        let x = qp.get_structural_values(state.get_primal());
+        assert!((&x - col![1.5, 0.5]).norm_max() < 1e-6);
+        let rows = qp.get_row_values(state.get_primal());
+        assert!((rows - col![2.0, 1.0]).norm_max() < 1e-6);
+        assert!((&state.y - col![-1.0, 0.0]).norm_max() < 1e-6);
+        assert!((state.z_u[2] + 1.0).abs() < 1e-6);
+
+        // The free slack has no multipliers and no complementarity terms
+        assert_eq!((state.z_l[3], state.z_u[3]), (0.0, 0.0));
+        assert_eq!((state.cs_lower[3], state.cs_upper[3]), (0.0, 0.0));
+        assert!(state.get_dual_feasibility().norm_max() < 1e-6);
+        assert!(state.get_cs_lower().norm_max() < 1e-6 && state.get_cs_upper().norm_max() < 1e-6);
+
+        // Neither the free slack nor its multipliers block a step
+        let step = crate::SearchDirection {
+            dx: col![0.0, 0.0, 0.0, -1e6],
+            dy: col![0.0, 1e6],
+            dz_l: col![0.0, 0.0, 0.0, -1e6],
+            dz_u: col![0.0, 0.0, 0.0, 1e6],
+        };
+        let length =
+            mpc::line_search::compute_max_step_length(&qp, &SolverOptions::new(), &state, &step);
+        assert_eq!(
+            (length.get_blocking_primal(), length.get_blocking_dual()),
+            (None, None)
+        );
+    }
+
+    /// `min 1/2 x^T (f f^T + diag(d)) x - mu^T x` over the simplex, with the
+    /// factor model and with the explicit Hessian.
+    #[allow(non_snake_case)]
+    fn build_portfolio(factor_model: bool) -> QuadraticProgram {
+        let f = [0.5, 1.0, -0.25];
+        let d = col![0.1, 0.2, 0.3];
+        let mu = col![0.1, 0.2, 0.15];
+        let A = SparseColMat::try_new_from_triplets(
+            1,
+            3,
+            &[
+                Triplet::new(0, 0, 1.0),
+                Triplet::new(0, 1, 1.0),
+                Triplet::new(0, 2, 1.0),
+            ],
+        )
+        .unwrap();
+        let (l, u) = (Col::zeros(3), Col::full(3, E::INFINITY));
+        if factor_model {
+            let triplets = (0..3)
+                .map(|j| Triplet::new(to_index(j), 0, f[j]))
+                .collect::<Vec<_>>();
+            let F = SparseColMat::try_new_from_triplets(3, 1, &triplets).unwrap();
+            QuadraticProgram::new_factor_model(F, d, -mu, A, col![1.0], l, u).unwrap()
+        } else {
+            let mut triplets = Vec::new();
+            for j in 0..3 {
+                for i in 0..3 {
+                    let value = f[i] * f[j] + if i == j { d[i] } else { 0.0 };
+                    triplets.push(Triplet::new(to_index(i), to_index(j), value));
+                }
+            }
+            let Q = SparseColMat::try_new_from_triplets(3, 3, &triplets).unwrap();
+            QuadraticProgram::new(Q, -mu, A, col![1.0], l, u)
+        }
+    }
+
+    #[apply(solver_types)]
+    fn test_factor_model(solver_type: QPSolverType) {
+        let solve = |qp: &QuadraticProgram| {
+            let mut state = SolverState::new_interior(&qp.l, &qp.u, qp.get_n_cons());
+            let status = qp
+                .solver_builder()
+                .with_solver(solver_type)
+                .build()
+                .unwrap()
+                .solve(&mut state, &mut SolverHooks::silent())
+                .unwrap();
+            assert_eq!(status, crate::Status::Optimal);
+            qp.get_structural_values(state.get_primal())
+        };
+
+        let factor_model = build_portfolio(true);
+        assert_eq!(factor_model.get_dims(), (4, 2));
+        assert_eq!(factor_model.get_n_factors(), 1);
+        assert_eq!(factor_model.get_n_structural(), 3);
+        assert_eq!(factor_model.get_n_rows(), 1);
+        assert!(mpc::augmented_system::has_diagonal_hessian(&factor_model));
+
+        let x = solve(&factor_model);
+        assert!((&x - solve(&build_portfolio(false))).norm_max() < 1e-6);
+        let mut padded = Col::zeros(4);
+        padded.subrows_mut(0, 3).copy_from(&x);
+        assert!((factor_model.get_row_values(&padded) - col![1.0]).norm_max() < 1e-6);
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_factor_model_dimensions() {
+        let F = SparseColMat::try_new_from_triplets(2, 1, &[Triplet::new(0, 0, 1.0)]).unwrap();
+        let A = SparseColMat::try_new_from_triplets(1, 3, &[Triplet::new(0, 0, 1.0)]).unwrap();
+        let qp = QuadraticProgram::new_factor_model(
+            F,
+            Col::zeros(3),
+            Col::zeros(3),
+            A,
+            col![1.0],
+            Col::zeros(3),
+            Col::full(3, E::INFINITY),
+        );
+        assert!(qp.is_err());
+    }
+
+    #[rstest]
+    fn test_solve_on_worker_thread(#[values(build_simple_qp())] qp: &'static QuadraticProgram) {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<QuadraticProgram>();
+
+        let mut solver = QuadraticProgram::solver_builder(qp)
+            .with_solver(QPSolverType::MpcSimplicialCholesky)
+            .build()
+            .unwrap();
+        let mut hooks = SolverHooks::silent();
+        let status = std::thread::scope(|scope| {
+            scope
+                .spawn(move || {
+                    let mut state = SolverState::new_interior(&qp.l, &qp.u, qp.get_n_cons());
+                    solver.solve(&mut state, &mut hooks).unwrap()
+                })
+                .join()
+                .unwrap()
+        });
+        assert_eq!(status, crate::Status::Optimal);
+    }
 }
@@ -3,25 +3,29 @@ use std::marker::PhantomData;
 use faer::{
     col::generic::Col,
     prelude::ReborrowMut,
-    sparse::{SparseColMat, SymbolicSparseColMat},
+    sparse::{SparseColMat, SparseColMatRef, SymbolicSparseColMat},
+    traits::IndexCore,
 };
-use problemo::Problem;
+use macros::{explicit_options, use_option};
+use problemo::{Problem, common::IntoCommonProblem};
 
 use crate::{
-    E, I, SearchDirection, SolverState,
-    ipm::RHS,
+    E, I, SearchDirection, SolverOptions, SolverState,
+    ipm::{AugmentedSystemType, NormalMatrix, RHS, prefer_normal_equations},
     linalg::{
-        solver::LinearSolver,
+        solver::{LinearSolver, MemoryEstimate},
         vector_ops::{cwise_inverse, cwise_multiply},
     },
     qp::QuadraticProgram,
+    to_index,
 };
 
 /// Formulation and factorization of the augmented KKT system used to
 /// compute search directions in a primal-dual interior-point method.
-pub trait AugmentedSystem<'a, Solver: LinearSolver> {
+pub trait AugmentedSystem<'a, Solver: LinearSolver>: Send {
     /// Creates a new instance, performing symbolic analysis of the sparsity pattern.
-    fn new(qp: &'a QuadraticProgram) -> Self
+    /// The linear solver is configured from `options`.
+    fn new(qp: &'a QuadraticProgram, options: &SolverOptions) -> Self
     where
         Self: Sized;
 
@@ -30,6 +34,101 @@ pub trait AugmentedSystem<'a, Solver: LinearSolver> {
 
     /// Solves for a search direction reusing the current factorization.
     fn resolve(&mut self, state: &SolverState, rhs: &RHS) -> Result<SearchDirection, Problem>;
+
+    /// The matrix of the most recent factorization.
+    fn get_matrix(&self) -> SparseColMatRef<'_, I, E>;
+
+    /// Memory of the factorization, estimated by the linear solver from the symbolic analysis.
+    fn estimate_memory(&self) -> Option<MemoryEstimate>;
+
+    /// Largest primal regularization `delta` of `Q + delta I` applied so far, see
+    /// [`HessianRegularization`].
+    fn get_regularization(&self) -> E {
+        E::from(0.)
+    }
+}
+
+/// Smallest `delta` tried when a factorization needs regularization.
+const AUTO_REGULARIZATION_MIN: E = 1e-8;
+
+/// Factor by which `delta` grows between attempts, and by which the next
+/// factorization starts below the last automatic `delta`.
+const AUTO_REGULARIZATION_FACTOR: E = 10.;
+
+/// Primal regularization `Q + delta I` of the Hessian block of the Newton
+/// system, for quadratic programs whose `Q` is only positive semidefinite.
+///
+/// Every factorization adds the static `qp_regularization`. With
+/// `qp_auto_regularization`, a factorization that fails on a zero pivot, or
+/// whose linear solver reports more negative pivots than the system has rows,
+/// is repeated with `delta` raised by [`AUTO_REGULARIZATION_FACTOR`] from
+/// [`AUTO_REGULARIZATION_MIN`] up to `qp_max_regularization`. The next
+/// factorization starts from the last automatic `delta` divided by the same
+/// factor, so that the regularization decays once it is no longer needed.
+///
+/// The search direction then solves a perturbed problem, so the largest
+/// `delta` is reported through [`AugmentedSystem::get_regularization`].
+#[explicit_options(name = SolverOptions, scope = "qp.mpc")]
+#[use_option(name = "qp_regularization", type_ = E, default = "0", description = "Static primal regularization delta added to the Hessian of quadratic program Newton systems.")]
+#[use_option(name = "qp_auto_regularization", type_ = bool, default = "true", description = "Raise the primal regularization of quadratic program Newton systems whose factorization fails or has negative pivots in the Hessian block.")]
+#[use_option(name = "qp_max_regularization", type_ = E, default = "1e-2", description = "Largest automatic primal regularization of quadratic program Newton systems.")]
+pub struct HessianRegularization {
+    /// Last automatic `delta`, zero while none was needed.
+    last: E,
+    /// Largest `delta` of any factorization.
+    largest: E,
+}
+
+impl HessianRegularization {
+    pub fn new(options: &SolverOptions) -> Self {
+        Self {
+            last: E::from(0.),
+            largest: E::from(0.),
+            options: options.into(),
+        }
+    }
+
+    /// Largest `delta` of any factorization so far.
+    pub fn get_largest(&self) -> E {
+        self.largest
+    }
+
+    /// Calls `factorize` with increasing `delta` until it succeeds with at most
+    /// `max_negative` negative pivots, or until `delta` reaches the maximum.
+    pub fn factorize<Solver: LinearSolver>(
+        &mut self,
+        solver: &mut Solver,
+        max_negative: usize,
+        mut factorize: impl FnMut(&mut Solver, E) -> Result<(), Problem>,
+    ) -> Result<(), Problem> {
+        let fixed = self.options.qp_regularization;
+        let max = self.options.qp_max_regularization;
+        let start = self.last / AUTO_REGULARIZATION_FACTOR;
+        let mut delta = if start >= AUTO_REGULARIZATION_MIN {
+            fixed + start
+        } else {
+            fixed
+        };
+        loop {
+            let result = factorize(solver, delta);
+            let inertia_ok = solver
+                .count_negative_pivots()
+                .is_none_or(|negative| negative <= max_negative);
+            let automatic = delta - fixed;
+            if (result.is_ok() && inertia_ok)
+                || !self.options.qp_auto_regularization
+                || automatic >= max
+            {
+                self.last = automatic;
+                self.largest = self.largest.max(delta);
+                return result;
+            }
+            delta = fixed
+                + (automatic * AUTO_REGULARIZATION_FACTOR)
+                    .max(AUTO_REGULARIZATION_MIN)
+                    .min(max);
+        }
+    }
 }
 
 /// Standard augmented system formulation.
@@ -47,13 +146,14 @@ pub struct StandardSystem<'a, Solver: LinearSolver> {
     qp: &'a QuadraticProgram,
     mat: SparseColMat<I, E>,
     solver: Solver,
-    diag_idx: Vec<I>, // Indices of the diagonal entries corresponding to dx in the matrix
+    diag_idx: Vec<usize>, // Indices of the diagonal entries corresponding to dx in the matrix
+    regularization: HessianRegularization,
 
     _a: PhantomData<&'a ()>,
 }
 
 impl<'a, Solver: LinearSolver> AugmentedSystem<'a, Solver> for StandardSystem<'a, Solver> {
-    fn new(qp: &'a QuadraticProgram) -> Self {
+    fn new(qp: &'a QuadraticProgram, options: &SolverOptions) -> Self {
         // Get properties
         let (n_var, n_con) = qp.get_dims();
         let a_nnz = qp.A.compute_nnz();
@@ -65,10 +165,6 @@ impl<'a, Solver: LinearSolver> AugmentedSystem<'a, Solver> for StandardSystem<'a
         let mut values = Vec::with_capacity(n_values);
 
         // Set pointers and values for the first n_var columns (dx)
-        let q_col_ptr = qp.Q.symbolic().col_ptr();
-        let q_row_idx = qp.Q.symbolic().row_idx();
-        let q_values = qp.Q.val();
-
         let a_col_ptr = qp.A.symbolic().col_ptr();
         let a_row_idx = qp.A.symbolic().row_idx();
         let a_values = qp.A.val();
@@ -76,69 +172,61 @@ impl<'a, Solver: LinearSolver> AugmentedSystem<'a, Solver> for StandardSystem<'a
         // Set each column (0...n_var)
         // TODO: ensure diagonals exist for dx and are set to -1, then only store the off-diagonal values of Q
         let mut diag_idx = Vec::with_capacity(n_var);
-        col_ptrs.push(0);
+        col_ptrs.push(to_index(0));
         for j in 0..n_var {
             let mut has_diag = false;
-            if j < q_col_ptr.len() {
-                let start = q_col_ptr[j];
-                let end = q_col_ptr[j + 1];
-                for k in start..end {
-                    if k == j {
-                        // Add the diagonal contribution from the complementarity terms
-                        row_indices.push(q_row_idx[k]); // Hessian part for dx
-                        values.push(q_values[k] + 1.); // Identity part for dx
-                        diag_idx.push(row_indices.len() - 1); // Store index of diagonal for later updates
-                        has_diag = true;
-                    } else if k != end - 1 && j > q_row_idx[k] && j < q_row_idx[k + 1] {
-                        // If the diagonal was skipped make sure to add it
-                        row_indices.push(j); // Diagonal part for dx
-                        values.push(1.);
-                        diag_idx.push(row_indices.len() - 1); // Store index of diagonal for later updates
-                        has_diag = true;
-
-                        row_indices.push(q_row_idx[k]); // Hessian part for dx
-                        values.push(q_values[k]);
-                    } else {
-                        // Just add it normally
-                        row_indices.push(q_row_idx[k]); // Hessian part for dx
-                        values.push(q_values[k]);
-                    }
+            for (i, &value) in qp.Q.row_idx_of_col(j).zip(qp.Q.val_of_col(j)) {
+                if i > j && !has_diag {
+                    // The Hessian skips the diagonal, so insert it before the lower part
+                    row_indices.push(to_index(j)); // Diagonal part for dx
+                    values.push(1.);
+                    diag_idx.push(row_indices.len() - 1); // Store index of diagonal for later updates
+                    has_diag = true;
+                }
+                row_indices.push(to_index(i)); // Hessian part for dx
+                if i == j {
+                    // Add the diagonal contribution from the complementarity terms
+                    values.push(value + 1.);
+                    diag_idx.push(row_indices.len() - 1); // Store index of diagonal for later updates
+                    has_diag = true;
+                } else {
+                    values.push(value);
                 }
             }
 
             // Add diagonal if it was not present in the Hessian (i.e. last element was before the diagonal)
             if !has_diag {
-                row_indices.push(j); // Diagonal part for dx
+                row_indices.push(to_index(j)); // Diagonal part for dx
                 values.push(1.);
                 diag_idx.push(row_indices.len() - 1); // Store index of diagonal for later updates
             }
 
-            let start = a_col_ptr[j];
-            let end = a_col_ptr[j + 1];
+            let start = a_col_ptr[j].zx();
+            let end = a_col_ptr[j + 1].zx();
             for k in start..end {
-                row_indices.push(a_row_idx[k] + n_var); // A part for dx
+                row_indices.push(to_index(a_row_idx[k].zx() + n_var)); // A part for dx
                 values.push(-a_values[k]);
             }
 
-            col_ptrs.push(row_indices.len());
+            col_ptrs.push(to_index(row_indices.len()));
         }
 
         // Set pointers for A^T
-        let a_csr = qp.A.to_row_major().unwrap();
+        let a_csr = qp.get_constraint_matrix_csr();
         let a_row_ptr = a_csr.symbolic().row_ptr();
         let a_col_idx = a_csr.symbolic().col_idx();
         let a_values = a_csr.val();
 
         // Set columns for A^T
         for j in 0..n_con {
-            let start = a_row_ptr[j];
-            let end = a_row_ptr[j + 1];
+            let start = a_row_ptr[j].zx();
+            let end = a_row_ptr[j + 1].zx();
             for k in start..end {
                 row_indices.push(a_col_idx[k]); // A^T part for dy
                 values.push(-a_values[k]);
             }
 
-            col_ptrs.push(row_indices.len());
+            col_ptrs.push(to_index(row_indices.len()));
         }
 
         let mat = unsafe {
@@ -152,7 +240,7 @@ impl<'a, Solver: LinearSolver> AugmentedSystem<'a, Solver> for StandardSystem<'a
             SparseColMat::<I, E>::new(sym, values)
         };
 
-        let mut solver = Solver::new();
+        let mut solver = Solver::new_with_options(options);
         solver.analyze(mat.as_ref()).unwrap();
 
         Self {
@@ -160,6 +248,7 @@ impl<'a, Solver: LinearSolver> AugmentedSystem<'a, Solver> for StandardSystem<'a
             mat,
             solver,
             diag_idx,
+            regularization: HessianRegularization::new(options),
 
             _a: PhantomData,
         }
@@ -172,18 +261,22 @@ impl<'a, Solver: LinearSolver> AugmentedSystem<'a, Solver> for StandardSystem<'a
         let sys_diag = cwise_multiply(xl_inv.as_ref(), state.z_l.as_ref())
             + cwise_multiply(xu_inv.as_ref(), state.z_u.as_ref());
 
-        // Get matrix pointers
-        let mat = self.mat.rb_mut();
-        let _col_ptrs = mat.symbolic().col_ptr();
-        let values = mat.val_mut();
-
-        // Update the matrix values based on the current iterate
-        for j in 0..self.qp.get_n_vars() {
-            let val = self.qp.Q.get(j, j).unwrap_or(&0.0);
-            values[self.diag_idx[j]] = val + sys_diag[j] as E; // Identity part for dx
-        }
-
-        self.solver.factorize(self.mat.as_ref())?;
+        let (qp, mat, diag_idx) = (self.qp, &mut self.mat, &self.diag_idx);
+        self.regularization
+            .factorize(&mut self.solver, qp.get_n_cons(), |solver, delta| {
+                crate::profile!("assemble", {
+                    // Get matrix pointers
+                    let mat = mat.rb_mut();
+                    let values = mat.val_mut();
+
+                    // Update the matrix values based on the current iterate
+                    for j in 0..qp.get_n_vars() {
+                        let val = qp.Q.get(j, j).unwrap_or(&0.0);
+                        values[diag_idx[j]] = val + sys_diag[j] as E + delta; // Identity part for dx
+                    }
+                });
+                solver.factorize(mat.as_ref())
+            })?;
 
         self.resolve(state, rhs)
     }
@@ -232,4 +325,334 @@ impl<'a, Solver: LinearSolver> AugmentedSystem<'a, Solver> for StandardSystem<'a
             dz_u,              // Placeholder
         })
     }
+
+    fn get_matrix(&self) -> SparseColMatRef<'_, I, E> {
+        self.mat.as_ref()
+    }
+
+    fn estimate_memory(&self) -> Option<MemoryEstimate> {
+        self.solver.estimate_memory()
+    }
+
+    fn get_regularization(&self) -> E {
+        self.regularization.get_largest()
+    }
+}
+
+/// Slack-reduced augmented system for quadratic programs with a diagonal Hessian.
+///
+/// Assembles the same `(n_var + n_con) x (n_var + n_con)` system as
+/// [`StandardSystem`], but with `diag(Q)` folded into the diagonal block:
+///
+/// ```text
+/// [ diag(Q)+D  -A^T ] [ dx ] = [ r ]
+/// [   -A        0   ] [ dy ]   [ r_p ]
+/// ```
+///
+/// which shares its sparsity pattern with the linear program system.
+pub struct SlackReducedSystem<'a, Solver: LinearSolver> {
+    qp: &'a QuadraticProgram,
+    mat: SparseColMat<I, E>,
+    q_diag: faer::Col<E>,
+    solver: Solver,
+    regularization: HessianRegularization,
+}
+
+impl<'a, Solver: LinearSolver> AugmentedSystem<'a, Solver> for SlackReducedSystem<'a, Solver> {
+    fn new(qp: &'a QuadraticProgram, options: &SolverOptions) -> Self {
+        // Get properties
+        let (n_var, n_con) = qp.get_dims();
+        let a_nnz = qp.A.compute_nnz();
+        let n_values = n_var + 2 * a_nnz;
+
+        let mut col_ptrs = Vec::with_capacity(n_var + n_con + 1);
+        let mut row_indices = Vec::with_capacity(n_values);
+        let mut values = Vec::with_capacity(n_values);
+
+        let a_col_ptr = qp.A.symbolic().col_ptr();
+        let a_row_idx = qp.A.symbolic().row_idx();
+        let a_values = qp.A.val();
+
+        // Set each column (0...n_var)
+        col_ptrs.push(to_index(0));
+        for j in 0..n_var {
+            row_indices.push(to_index(j)); // Diagonal part for dx
+            values.push(E::from(1.));
+
+            let start = a_col_ptr[j].zx();
+            let end = a_col_ptr[j + 1].zx();
+            for k in start..end {
+                row_indices.push(to_index(a_row_idx[k].zx() + n_var)); // A part for dx
+                values.push(-a_values[k]);
+            }
+
+            col_ptrs.push(to_index(row_indices.len()));
+        }
+
+        // Set pointers for A^T
+        let a_csr = qp.get_constraint_matrix_csr();
+        let a_row_ptr = a_csr.symbolic().row_ptr();
+        let a_col_idx = a_csr.symbolic().col_idx();
+        let a_values = a_csr.val();
+
+        // Set columns for A^T
+        for j in 0..n_con {
+            let start = a_row_ptr[j].zx();
+            let end = a_row_ptr[j + 1].zx();
+            for k in start..end {
+                row_indices.push(a_col_idx[k]); // A^T part for dy
+                values.push(-a_values[k]);
+            }
+
+            col_ptrs.push(to_index(row_indices.len()));
+        }
+
+        let mat = unsafe {
+            let sym = SymbolicSparseColMat::new_unchecked(
+                n_var + n_con,
+                n_var + n_con,
+                col_ptrs,
+                None,
+                row_indices,
+            );
+            SparseColMat::<I, E>::new(sym, values)
+        };
+
+        let mut solver = Solver::new_with_options(options);
+        solver.analyze(mat.as_ref()).unwrap();
+
+        Self {
+            qp,
+            mat,
+            q_diag: hessian_diagonal(qp),
+            solver,
+            regularization: HessianRegularization::new(options),
+        }
+    }
+
+    fn solve(&mut self, state: &SolverState, rhs: &RHS) -> Result<SearchDirection, Problem> {
+        let sys_diag = &self.q_diag + barrier_diagonal(self.qp, state);
+
+        let (n_var, n_con) = self.qp.get_dims();
+        let mat = &mut self.mat;
+        self.regularization
+            .factorize(&mut self.solver, n_con, |solver, delta| {
+                crate::profile!("assemble", {
+                    // Get matrix pointers
+                    let mat = mat.rb_mut();
+                    let col_ptrs = mat.symbolic().col_ptr();
+                    let values = mat.val_mut();
+
+                    // Update the matrix
+                    for j in 0..n_var {
+                        values[col_ptrs[j].zx()] = sys_diag[j] + delta; // Diagonal part for dx
+                    }
+                });
+                solver.factorize(mat.as_ref())
+            })?;
+
+        self.resolve(state, rhs)
+    }
+
+    fn resolve(&mut self, state: &SolverState, rhs: &RHS) -> Result<SearchDirection, Problem> {
+        let (n_var, n_con) = self.qp.get_dims();
+
+        let mut rhs_full = Col::zeros(n_var + n_con);
+        let (mut rhs_dual, mut rhs_primal) = rhs_full.split_at_row_mut(n_var);
+        rhs_dual.copy_from(dual_rhs(self.qp, state, rhs));
+        rhs_primal.copy_from(rhs.r_c());
+
+        let solution = {
+            let sol = self.solver.solve(rhs_full.as_mat().as_ref())?;
+            sol.col(0).to_owned()
+        };
+        let (dx, dy) = solution.split_at_row(n_var);
+
+        Ok(recover_direction(
+            self.qp,
+            state,
+            rhs,
+            dx.to_owned(),
+            dy.to_owned(),
+        ))
+    }
+
+    fn get_matrix(&self) -> SparseColMatRef<'_, I, E> {
+        self.mat.as_ref()
+    }
+
+    fn estimate_memory(&self) -> Option<MemoryEstimate> {
+        self.solver.estimate_memory()
+    }
+
+    fn get_regularization(&self) -> E {
+        self.regularization.get_largest()
+    }
+}
+
+/// Normal equations for quadratic programs with a diagonal Hessian.
+///
+/// Solves `A (diag(Q)+D)^{-1} A^T dy = -r_p - A (diag(Q)+D)^{-1} r` and recovers
+/// `dx = (diag(Q)+D)^{-1} (r + A^T dy)`. Every variable must have a finite bound
+/// or positive curvature so that `diag(Q)+D` is nonsingular.
+pub struct NormalEquationsSystem<'a, Solver: LinearSolver> {
+    qp: &'a QuadraticProgram,
+    normal: NormalMatrix,
+    q_diag: faer::Col<E>,
+    d_inv: faer::Col<E>,
+    solver: Solver,
+    regularization: HessianRegularization,
+}
+
+impl<'a, Solver: LinearSolver> AugmentedSystem<'a, Solver> for NormalEquationsSystem<'a, Solver> {
+    fn new(qp: &'a QuadraticProgram, options: &SolverOptions) -> Self {
+        let normal = NormalMatrix::new(qp.A.as_ref());
+
+        let mut solver = Solver::new_with_options(options);
+        solver.analyze(normal.as_ref()).unwrap();
+
+        Self {
+            qp,
+            normal,
+            q_diag: hessian_diagonal(qp),
+            d_inv: Col::zeros(qp.get_n_vars()),
+            solver,
+            regularization: HessianRegularization::new(options),
+        }
+    }
+
+    fn solve(&mut self, state: &SolverState, rhs: &RHS) -> Result<SearchDirection, Problem> {
+        let sys_diag = &self.q_diag + barrier_diagonal(self.qp, state);
+
+        let (qp, normal, d_inv) = (self.qp, &mut self.normal, &mut self.d_inv);
+        self.regularization
+            .factorize(&mut self.solver, 0, |solver, delta| {
+                crate::profile!("assemble", {
+                    *d_inv =
+                        cwise_inverse((&sys_diag + Col::full(sys_diag.nrows(), delta)).as_ref());
+                    if !d_inv.iter().all(|d| d.is_finite() && *d > E::from(0.)) {
+                        return Err("Singular Hessian block of the normal equations".gloss());
+                    }
+                    normal.update(qp.A.as_ref(), d_inv);
+                });
+                solver.factorize(normal.as_ref())
+            })?;
+
+        self.resolve(state, rhs)
+    }
+
+    fn resolve(&mut self, state: &SolverState, rhs: &RHS) -> Result<SearchDirection, Problem> {
+        let rhs_dual = dual_rhs(self.qp, state, rhs);
+        let scaled_dual = cwise_multiply(self.d_inv.as_ref(), rhs_dual.as_ref());
+        let rhs_normal = -rhs.r_c() - &self.qp.A * &scaled_dual;
+
+        let dy = {
+            let sol = self.solver.solve(rhs_normal.as_mat().as_ref())?;
+            sol.col(0).to_owned()
+        };
+        let dx = cwise_multiply(
+            self.d_inv.as_ref(),
+            (rhs_dual + self.qp.A.transpose() * &dy).as_ref(),
+        );
+
+        Ok(recover_direction(self.qp, state, rhs, dx, dy))
+    }
+
+    fn get_matrix(&self) -> SparseColMatRef<'_, I, E> {
+        self.normal.as_ref()
+    }
+
+    fn estimate_memory(&self) -> Option<MemoryEstimate> {
+        self.solver.estimate_memory()
+    }
+
+    fn get_regularization(&self) -> E {
+        self.regularization.get_largest()
+    }
+}
+
+/// Returns the diagonal of `Q`.
+fn hessian_diagonal(qp: &QuadraticProgram) -> faer::Col<E> {
+    faer::Col::from_fn(qp.get_n_vars(), |j| *qp.Q.get(j, j).unwrap_or(&0.0))
+}
+
+/// Returns `D = Z_l (X-L)^{-1} + Z_u (X-U)^{-1}`.
+fn barrier_diagonal(qp: &QuadraticProgram, state: &SolverState) -> faer::Col<E> {
+    let xl_inv = cwise_inverse((&state.x - &qp.l).as_ref());
+    let xu_inv = cwise_inverse((&state.x - &qp.u).as_ref());
+    cwise_multiply(xl_inv.as_ref(), state.z_l.as_ref())
+        + cwise_multiply(xu_inv.as_ref(), state.z_u.as_ref())
+}
+
+/// Returns the right-hand side of the `dx` block after eliminating `dz_l` and `dz_u`.
+fn dual_rhs(qp: &QuadraticProgram, state: &SolverState, rhs: &RHS) -> faer::Col<E> {
+    let (sigma, mu) = (state.sigma.unwrap(), state.mu.unwrap());
+    let xl_inv = cwise_inverse((&state.x - &qp.l).as_ref());
+    let xu_inv = cwise_inverse((&state.x - &qp.u).as_ref());
+
+    rhs.r_d()
+        + cwise_multiply(xl_inv.as_ref(), rhs.r_l().as_ref())
+        + cwise_multiply(xu_inv.as_ref(), rhs.r_u().as_ref())
+        + sigma * mu * (&xl_inv + &xu_inv)
+}
+
+/// Recovers `dz_l` and `dz_u` from `dx`.
+fn recover_direction(
+    qp: &QuadraticProgram,
+    state: &SolverState,
+    rhs: &RHS,
+    dx: faer::Col<E>,
+    dy: faer::Col<E>,
+) -> SearchDirection {
+    let (sigma, mu) = (state.sigma.unwrap(), state.mu.unwrap());
+    let xl_inv = cwise_inverse((&state.x - &qp.l).as_ref());
+    let xu_inv = cwise_inverse((&state.x - &qp.u).as_ref());
+
+    let dz_l = sigma * mu * xl_inv.as_ref()
+        - cwise_multiply(
+            cwise_multiply(xl_inv.as_ref(), state.z_l.as_ref()).as_ref(),
+            dx.as_ref(),
+        )
+        + cwise_multiply(xl_inv.as_ref(), rhs.r_l().as_ref());
+    let dz_u = sigma * mu * xu_inv.as_ref()
+        - cwise_multiply(
+            cwise_multiply(xu_inv.as_ref(), state.z_u.as_ref()).as_ref(),
+            dx.as_ref(),
+        )
+        + cwise_multiply(xu_inv.as_ref(), rhs.r_u().as_ref());
+
+    SearchDirection { dx, dy, dz_l, dz_u }
+}
+
+/// Returns `true` if `Q` has no nonzero off-diagonal entries.
+pub(crate) fn has_diagonal_hessian(qp: &QuadraticProgram) -> bool {
+    let col_ptr = qp.Q.symbolic().col_ptr();
+    let row_idx = qp.Q.symbolic().row_idx();
+    let values = qp.Q.val();
+
+    (0..qp.Q.ncols()).all(|j| {
+        (col_ptr[j].zx()..col_ptr[j + 1].zx()).all(|k| row_idx[k].zx() == j || values[k] == 0.)
+    })
+}
+
+/// Returns `true` if every variable has a finite bound or positive curvature, which
+/// keeps `diag(Q)+D` nonsingular.
+pub(crate) fn has_bounded_variables(qp: &QuadraticProgram) -> bool {
+    let q_diag = hessian_diagonal(qp);
+    (0..qp.get_n_vars()).all(|j| qp.l[j].is_finite() || qp.u[j].is_finite() || q_diag[j] > 0.)
+}
+
+/// Resolves [`AugmentedSystemType::Auto`] for the given quadratic program.
+///
+/// Problems with off-diagonal Hessian entries use the standard system. Otherwise
+/// the normal equations are used when they are expected to be sparse, falling
+/// back to the slack-reduced system.
+pub(crate) fn select_system(qp: &QuadraticProgram) -> AugmentedSystemType {
+    if !has_diagonal_hessian(qp) {
+        AugmentedSystemType::Standard
+    } else if has_bounded_variables(qp) && prefer_normal_equations(qp.A.as_ref()) {
+        AugmentedSystemType::NormalEquations
+    } else {
+        AugmentedSystemType::SlackReduced
+    }
 }
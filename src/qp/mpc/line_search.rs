@@ -1,62 +1,48 @@
-use faer::{unzip, zip};
-
-use crate::{
-    E, SearchDirection, SolverOptions, SolverState, qp::{QuadraticProgram}
-};
+use macros::build_option_enum;
+
+use crate::{SearchDirection, SolverOptions, SolverState, StepLength, ipm, qp::QuadraticProgram};
+
+/// Rule for the step lengths along the search directions of the
+/// interior-point method.
+pub trait LineSearch<'a>: Send {
+    /// Creates a new instance from the quadratic program and solver options.
+    fn new(qp: &'a QuadraticProgram, options: &SolverOptions) -> Self
+    where
+        Self: Sized;
+    /// Returns the primal and dual step lengths along `step` from `state`.
+    fn compute(&self, state: &SolverState, step: &SearchDirection) -> StepLength;
+}
 
 pub fn compute_max_step_length<'a>(
     qp: &'a QuadraticProgram,
     _options: &SolverOptions,
     state: &SolverState,
     step: &SearchDirection,
-) -> (E, E) {
-    let xl = &state.x - &qp.l;
-    let xu = &state.x - &qp.u;
-
-    let mut alpha_primal = E::from(1.);
-    zip!(&xl, &xu, &qp.l, &qp.u, &step.dx).for_each(|unzip!(xl, xu, l, u, dx)| {
-        let dx_neg = if *dx < E::from(0.) { *dx } else { -*xl };
-        let dx_pos = if *dx > E::from(0.) { *dx } else { -*xu };
-
-        let alpha_lb = if l.is_finite() {
-            -xl / dx_neg
-        } else {
-            E::INFINITY
-        };
-        let alpha_ub = if u.is_finite() {
-            -xu / dx_pos
-        } else {
-            E::INFINITY
-        };
-
-        alpha_primal = E::min(alpha_primal, E::min(alpha_lb, alpha_ub));
-    });
-
-    let mut alpha_dual = E::from(1.);
-    zip!(&state.z_u, &step.dz_u).for_each(|unzip!(z_ub, dz_ub)| {
-        let dz_ub_pos = if *dz_ub > E::from(0.) { *dz_ub } else { -*z_ub };
-        let alpha_ub = if *z_ub < E::from(0.) {
-            -z_ub / dz_ub_pos
-        } else {
-            E::INFINITY
-        };
-
-        alpha_dual = E::min(alpha_dual, alpha_ub);
-    });
-
-    zip!(&state.z_l, &step.dz_l).for_each(|unzip!(z_lb, dz_lb)| {
-        let dz_lb_neg = if *dz_lb < E::from(0.) { *dz_lb } else { -*z_lb };
-        let alpha_lb = if *z_lb > E::from(0.) {
-            -z_lb / dz_lb_neg
-        } else {
-            E::INFINITY
-        };
+) -> StepLength {
+    ipm::max_step_length(&qp.l, &qp.u, state, step)
+}
 
-        alpha_dual = E::min(alpha_dual, alpha_lb);
-    });
+/// Takes the longest steps that keep the iterate within the bounds, see
+/// [`compute_max_step_length`].
+pub struct MaxStepLength<'a> {
+    qp: &'a QuadraticProgram,
+}
 
-    let alpha_primal = E::min(E::from(1.), state.safety_factor.unwrap() * alpha_primal);
-    let alpha_dual = E::min(E::from(1.), state.safety_factor.unwrap() * alpha_dual);
+impl<'a> LineSearch<'a> for MaxStepLength<'a> {
+    fn new(qp: &'a QuadraticProgram, _options: &SolverOptions) -> Self {
+        Self { qp }
+    }
 
-    (alpha_primal, alpha_dual)
+    fn compute(&self, state: &SolverState, step: &SearchDirection) -> StepLength {
+        ipm::max_step_length(&self.qp.l, &self.qp.u, state, step)
+    }
 }
+
+build_option_enum!(
+    trait_ = for<'a> LineSearch<'a>,
+    name = "LineSearches",
+    variants = (MaxStepLength,),
+    aliases = [["max_step"]],
+    new_arguments = (&'a QuadraticProgram, &SolverOptions),
+    doc_header = "Line searches of the interior-point method for quadratic programs, selected with the `qp_line_search` option."
+);
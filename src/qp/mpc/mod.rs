@@ -5,13 +5,19 @@ use macros::{explicit_options, use_option};
 use problemo::Problem;
 
 use crate::{
-    E, I, OptimizationProgram, SearchDirection, IterativeSolver, SolverHooks, SolverOptions, SolverState,
-    Status,
-    ipm::{self, RHS},
-    linalg::{solver::LinearSolver, vector_ops::cwise_multiply_finite},
+    E, IterativeSolver, OptimizationProgram, SolverHooks, SolverOptions, SolverState, Status,
+    ipm::{self, KktDump, RHS},
+    linalg::{
+        solver::{LinearSolver, MemoryEstimate},
+        vector_ops::{axpy, cwise_multiply_finite},
+    },
     qp::{
         QPSolver, QuadraticProgram,
-        mpc::{augmented_system::AugmentedSystem, mu_update::MuUpdate},
+        mpc::{
+            augmented_system::AugmentedSystem,
+            line_search::{LineSearch, LineSearches},
+            mu_update::MuUpdate,
+        },
     },
 };
 
@@ -26,11 +32,25 @@ pub mod mu_update;
 /// 2. **Corrector step** — adjusts centering parameter `sigma` based on the
 ///    affine step and adds second-order corrections to the complementarity.
 ///
+/// With the `qp_infeasible_start` option, the corrector aims at reducing the
+/// primal and dual residuals by the factor `sigma` rather than eliminating
+/// them, as in the classical infeasible interior-point method. The residuals
+/// then decrease at the rate of the complementarity, so that iterates started
+/// far from `A x = b` do not reach the boundary before they become feasible.
+///
+/// Positive semidefinite Hessians are regularized to `Q + delta I` when the
+/// factorization of the augmented system fails, see
+/// [`HessianRegularization`](augmented_system::HessianRegularization).
+///
 /// The solver is generic over the linear system factorization (`Solver`),
 /// augmented system formulation (`System`), barrier parameter strategy (`MU`),
 /// and line search (`LS`).
-#[explicit_options(name = SolverOptions)]
-#[use_option(name = "max_iterations", type_=I, description="Maximum number of iterations (0 uses solver defaults).")]
+#[explicit_options(name = SolverOptions, scope = "qp.mpc")]
+#[use_option(name = "max_iterations", type_=usize, description="Maximum number of iterations (0 uses solver defaults).")]
+#[use_option(name = "kkt_dump_directory", type_ = String, default = "", description = "Directory to write the augmented system, right-hand side and step of interior-point iterations to as MatrixMarket files; empty disables the dump.")]
+#[use_option(name = "kkt_dump_iterations", type_ = String, default = "", description = "Comma-separated interior-point iterations to dump; empty dumps every iteration.")]
+#[use_option(name = "qp_infeasible_start", type_ = bool, default = "false", description = "Drive the residuals of QP interior-point iterations to zero at the rate of the complementarity instead of in a single Newton step.")]
+#[use_option(name = "max_memory", type_ = E, default = "0", description = "Maximum memory in MiB of the factorization of the Newton system; 0 disables the limit.")]
 pub struct MehrotraPredictorCorrector<
     'a,
     LinSolve: LinearSolver,
@@ -42,8 +62,8 @@ pub struct MehrotraPredictorCorrector<
     system: Sys,
     mu_updater: MU,
 
-    aff_ls: fn(&'a QuadraticProgram, &SolverOptions, &SolverState, &SearchDirection) -> (E, E),
-    cc_ls: fn(&'a QuadraticProgram, &SolverOptions, &SolverState, &SearchDirection) -> (E, E),
+    aff_ls: Box<dyn LineSearch<'a> + 'a>,
+    cc_ls: Box<dyn LineSearch<'a> + 'a>,
 
     _solver: PhantomData<LinSolve>,
 }
@@ -56,7 +76,14 @@ impl<'a, LinSolve: LinearSolver, Sys: AugmentedSystem<'a, LinSolve>, MU: MuUpdat
     }
 
     fn iterate(&mut self, state: &mut SolverState) -> Result<(), Problem> {
-        // Iteration step code here
+        // Stop before the factorization allocates more than allowed
+        if let Some(termination) =
+            ipm::exceeds_memory_limit(self.system.estimate_memory(), self.options.max_memory)
+        {
+            state.status = Status::MemoryLimit;
+            state.termination = Some(termination);
+            return Ok(());
+        }
 
         state.sigma = Some(E::from(0.));
         state.mu = Some(self.mu_updater.get(state));
@@ -64,18 +91,34 @@ impl<'a, LinSolve: LinearSolver, Sys: AugmentedSystem<'a, LinSolve>, MU: MuUpdat
 
         // Compute RHS from residual
         let mut rhs = RHS::from(&*state);
+        let dump = KktDump::new(
+            &self.options.kkt_dump_directory,
+            &self.options.kkt_dump_iterations,
+            state.nit,
+        )?;
 
         // Affine Step
         let aff_step = self.system.solve(state, &rhs)?;
-        let (alpha_aff_primal, alpha_aff_dual) =
-            (self.aff_ls)(self.qp, &self.options.root, state, &aff_step);
+        if let Some(dump) = &dump {
+            dump.write("affine", self.system.get_matrix(), &rhs, &aff_step)?;
+        }
+        let aff_length = self.aff_ls.compute(state, &aff_step);
+        let (alpha_aff_primal, alpha_aff_dual) = (aff_length.primal, aff_length.dual);
 
         // Center-Corrector Step
         let mut state_aff = state.clone();
-        state_aff.x += alpha_aff_primal * &aff_step.dx;
-        state_aff.y += alpha_aff_dual * &aff_step.dy;
-        state_aff.z_l += alpha_aff_dual * &aff_step.dz_l;
-        state_aff.z_u += alpha_aff_dual * &aff_step.dz_u;
+        axpy(alpha_aff_primal, aff_step.dx.as_ref(), state_aff.x.as_mut());
+        axpy(alpha_aff_dual, aff_step.dy.as_ref(), state_aff.y.as_mut());
+        axpy(
+            alpha_aff_dual,
+            aff_step.dz_l.as_ref(),
+            state_aff.z_l.as_mut(),
+        );
+        axpy(
+            alpha_aff_dual,
+            aff_step.dz_u.as_ref(),
+            state_aff.z_u.as_mut(),
+        );
 
         state.sigma = Some(pow(
             self.mu_updater.get(&state_aff) / state.mu.unwrap_or(E::from(1.)),
@@ -87,18 +130,29 @@ impl<'a, LinSolve: LinearSolver, Sys: AugmentedSystem<'a, LinSolve>, MU: MuUpdat
             cwise_multiply_finite(aff_step.get_dz_l().as_ref(), aff_step.get_dx().as_ref());
         *rhs.r_u_mut() -=
             cwise_multiply_finite(aff_step.get_dz_u().as_ref(), aff_step.get_dx().as_ref());
+        if self.options.qp_infeasible_start {
+            // A full step leaves the residuals sigma r instead of zero
+            let reduction = E::from(1.) - state.sigma.unwrap_or(E::from(0.));
+            rhs.set_r_c(reduction * rhs.r_c());
+            rhs.set_r_d(reduction * rhs.r_d());
+        }
 
         let corr_step = self.system.solve(state, &rhs)?;
-        let (alpha_corr_primal, alpha_corr_dual) =
-            (self.cc_ls)(self.qp, &self.options.root, state, &corr_step);
+        if let Some(dump) = &dump {
+            dump.write("corrector", self.system.get_matrix(), &rhs, &corr_step)?;
+        }
+        let corr_length = self.cc_ls.compute(state, &corr_step);
+        let (alpha_corr_primal, alpha_corr_dual) = (corr_length.primal, corr_length.dual);
 
         // Update the state with the corrector step and step lengths
-        state.x += alpha_corr_primal * &corr_step.dx;
-        state.y += alpha_corr_dual * &corr_step.dy;
-        state.z_l += alpha_corr_dual * &corr_step.dz_l;
-        state.z_u += alpha_corr_dual * &corr_step.dz_u;
+        axpy(alpha_corr_primal, corr_step.dx.as_ref(), state.x.as_mut());
+        axpy(alpha_corr_dual, corr_step.dy.as_ref(), state.y.as_mut());
+        axpy(alpha_corr_dual, corr_step.dz_l.as_ref(), state.z_l.as_mut());
+        axpy(alpha_corr_dual, corr_step.dz_u.as_ref(), state.z_u.as_mut());
         state.alpha_primal = alpha_corr_primal;
         state.alpha_dual = alpha_corr_dual;
+        state.blocking_primal = corr_length.blocking_primal;
+        state.blocking_dual = corr_length.blocking_dual;
 
         self.qp.update_residual(state);
         state.status = Status::InProgress;
@@ -111,23 +165,34 @@ impl<'a, LinSolve: LinearSolver, Sys: AugmentedSystem<'a, LinSolve>, MU: MuUpdat
     for MehrotraPredictorCorrector<'a, LinSolve, Sys, MU>
 {
     fn new(qp: &'a QuadraticProgram, options: &SolverOptions) -> Self {
+        let line_search = options
+            .get_option::<LineSearches>("qp_line_search")
+            .unwrap_or_default();
         Self {
             qp,
-            system: Sys::new(qp),
+            system: Sys::new(qp, options),
             mu_updater: MU::new(qp, options),
 
-            aff_ls: line_search::compute_max_step_length,
-            cc_ls: line_search::compute_max_step_length,
+            aff_ls: LineSearches::into_variant(&line_search, qp, options),
+            cc_ls: LineSearches::into_variant(&line_search, qp, options),
 
             options: options.into(),
 
             _solver: PhantomData,
         }
     }
+
+    fn estimate_memory(&self) -> Option<MemoryEstimate> {
+        self.system.estimate_memory()
+    }
+
+    fn get_regularization(&self) -> Option<E> {
+        Some(self.system.get_regularization())
+    }
 }
 
-impl<'a, LinSolve: LinearSolver, Sys: AugmentedSystem<'a, LinSolve>, MU: MuUpdate<'a>> IterativeSolver
-    for MehrotraPredictorCorrector<'a, LinSolve, Sys, MU>
+impl<'a, LinSolve: LinearSolver, Sys: AugmentedSystem<'a, LinSolve>, MU: MuUpdate<'a>>
+    IterativeSolver for MehrotraPredictorCorrector<'a, LinSolve, Sys, MU>
 {
     fn get_max_iterations(&self) -> usize {
         if self.options.max_iterations as usize > 0 {
@@ -137,6 +202,10 @@ impl<'a, LinSolve: LinearSolver, Sys: AugmentedSystem<'a, LinSolve>, MU: MuUpdat
         }
     }
 
+    fn get_options(&self) -> Option<&SolverOptions> {
+        Some(&self.options.root)
+    }
+
     fn get_program(&self) -> &dyn OptimizationProgram {
         self.qp
     }
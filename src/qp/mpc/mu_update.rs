@@ -1,9 +1,9 @@
 use std::marker::PhantomData;
 
-use macros::{explicit_options, use_option};
+use macros::{build_option_enum, explicit_options, use_option};
 
 use crate::{
-    E, SolverOptions, SolverState, linalg::vector_ops::cwise_multiply_finite, qp::QuadraticProgram,
+    E, SolverOptions, SolverState, linalg::vector_ops::complementarity, qp::QuadraticProgram,
 };
 
 /// Strategy for computing the barrier parameter `mu`.
@@ -11,7 +11,7 @@ use crate::{
 /// The barrier parameter controls the trade-off between optimality and
 /// centrality in an interior-point method. Implementations determine
 /// how `mu` evolves across iterations.
-pub trait MuUpdate<'a> {
+pub trait MuUpdate<'a>: Send {
     /// Creates a new instance from the linear program and solver options.
     fn new(qp: &'a QuadraticProgram, options: &SolverOptions) -> Self
     where
@@ -21,7 +21,7 @@ pub trait MuUpdate<'a> {
 }
 
 /// Returns a fixed barrier parameter value across all iterations.
-#[explicit_options(name = SolverOptions)]
+#[explicit_options(name = SolverOptions, scope = "qp.mpc")]
 #[use_option(name = "mu_fixed", type_ = E, default = "1.", description = "Constant value for the barrier parameter mu")]
 pub struct ConstantMuUpdate<'a> {
     _a: PhantomData<&'a ()>,
@@ -47,7 +47,7 @@ impl<'a> MuUpdate<'a> for ConstantMuUpdate<'a> {
 /// ```
 ///
 /// Infinite bounds are ignored (treated as zero contribution).
-#[explicit_options(name = SolverOptions)]
+#[explicit_options(name = SolverOptions, scope = "qp.mpc")]
 #[use_option(name = "mu_min", type_ = E, description = "Minimum value for the barrier parameter mu")]
 #[use_option(name = "mu_max", type_ = E, description = "Maximum value for the barrier parameter mu")]
 pub struct AdaptiveMuUpdate<'a> {
@@ -69,13 +69,33 @@ impl<'a> MuUpdate<'a> for AdaptiveMuUpdate<'a> {
         // (x-u)^T z_upper
         // sum / n
 
-        let xl = &state.x - &self.qp.l;
-        let xu = &state.x - &self.qp.u;
-
-        let l = cwise_multiply_finite(state.z_l.as_ref(), xl.as_ref()).sum();
-        let u = cwise_multiply_finite(state.z_u.as_ref(), xu.as_ref()).sum();
+        let l = complementarity(state.x.as_ref(), self.qp.l.as_ref(), state.z_l.as_ref());
+        let u = complementarity(state.x.as_ref(), self.qp.u.as_ref(), state.z_u.as_ref());
         let mu = (l + u) / state.x.nrows() as E;
 
         mu.clamp(self.options.mu_min, self.options.mu_max)
     }
 }
+
+build_option_enum!(
+    trait_ = for<'a> MuUpdate<'a>,
+    name = "MuUpdates",
+    variants = (AdaptiveMuUpdate, ConstantMuUpdate),
+    aliases = [["adaptive"], ["constant", "fixed"]],
+    new_arguments = (&'a QuadraticProgram, &SolverOptions),
+    doc_header = "Barrier parameter updates of the interior-point method for quadratic programs, selected with the `qp_mu_update` option."
+);
+
+/// The update selected with the `qp_mu_update` option.
+impl<'a> MuUpdate<'a> for Box<dyn MuUpdate<'a> + 'a> {
+    fn new(qp: &'a QuadraticProgram, options: &SolverOptions) -> Self {
+        let update = options
+            .get_option::<MuUpdates>("qp_mu_update")
+            .unwrap_or_default();
+        MuUpdates::into_variant(&update, qp, options)
+    }
+
+    fn get(&mut self, state: &SolverState) -> E {
+        (**self).get(state)
+    }
+}
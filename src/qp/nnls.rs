@@ -0,0 +1,139 @@
+//! Nonnegative least squares.
+//!
+//! [`nnls`] solves
+//!
+//! ```text
+//! min  1/2 |A x - b|^2
+//! s.t. x >= 0
+//! ```
+//!
+//! as the quadratic program
+//!
+//! ```text
+//! min  1/2 r^T r
+//! s.t. A x - r = b
+//!      x >= 0
+//! ```
+//!
+//! in the residual `r`, which keeps the sparsity of `A` instead of forming
+//! `A^T A`.
+
+use faer::{
+    Col,
+    sparse::{SparseColMat, Triplet},
+};
+use problemo::{Problem, common::IntoCommonProblem};
+
+use crate::{
+    E, I, SolverHooks, SolverOptions, SolverState, Status,
+    callback::NoOpCallback,
+    qp::{QPSolverType, QuadraticProgram},
+    terminators::ComplementarityTerminator,
+    to_index,
+};
+
+/// Solves `min 1/2 |A x - b|^2` subject to `x >= 0` with the default options.
+/// See [`nnls_with_options`].
+pub fn nnls(a: &SparseColMat<I, E>, b: &Col<E>) -> Result<Col<E>, Problem> {
+    nnls_with_options(a, b, &SolverOptions::new())
+}
+
+/// Solves `min 1/2 |A x - b|^2` subject to `x >= 0` with the interior-point
+/// solver for quadratic programs and `options`. Fails if the dimensions of `a`
+/// and `b` do not match or if the solver does not converge.
+pub fn nnls_with_options(
+    a: &SparseColMat<I, E>,
+    b: &Col<E>,
+    options: &SolverOptions,
+) -> Result<Col<E>, Problem> {
+    let (m, n) = (a.nrows(), a.ncols());
+    if b.nrows() != m {
+        return Err(format!(
+            "Right-hand side of size {} does not match a matrix with {m} rows",
+            b.nrows()
+        )
+        .gloss());
+    }
+
+    let qp = to_quadratic_program(a, b);
+    let mut state = SolverState::new_interior(qp.get_lower_bounds(), qp.get_upper_bounds(), m);
+    let mut hooks = SolverHooks {
+        callback: Box::new(NoOpCallback::new()),
+        terminator: Box::new(ComplementarityTerminator::new(options)),
+    };
+    let mut solver = qp
+        .solver_builder()
+        .with_solver(QPSolverType::MpcSimplicialCholesky)
+        .with_options(options.clone())
+        .build()?;
+    match solver.solve(&mut state, &mut hooks)? {
+        Status::Optimal => Ok(state.x.subrows(0, n).to_owned()),
+        status => {
+            Err(format!("Nonnegative least squares terminated with status {status:?}").gloss())
+        }
+    }
+}
+
+/// Quadratic program in the variables `(x, r)` of the [module documentation](self).
+fn to_quadratic_program(a: &SparseColMat<I, E>, b: &Col<E>) -> QuadraticProgram {
+    let (m, n) = (a.nrows(), a.ncols());
+    let mut triplets = Vec::with_capacity(a.compute_nnz() + m);
+    for j in 0..n {
+        for (i, &value) in a.row_idx_of_col(j).zip(a.val_of_col(j)) {
+            triplets.push(Triplet::new(to_index(i), to_index(j), value));
+        }
+    }
+    for i in 0..m {
+        triplets.push(Triplet::new(to_index(i), to_index(n + i), E::from(-1.)));
+    }
+    let hessian = (n..n + m)
+        .map(|j| Triplet::new(to_index(j), to_index(j), E::from(1.)))
+        .collect::<Vec<_>>();
+
+    QuadraticProgram::new(
+        SparseColMat::try_new_from_triplets(n + m, n + m, &hessian).unwrap(),
+        Col::zeros(n + m),
+        SparseColMat::try_new_from_triplets(m, n + m, &triplets).unwrap(),
+        b.clone(),
+        Col::from_fn(n + m, |j| if j < n { E::from(0.) } else { -E::INFINITY }),
+        Col::full(n + m, E::INFINITY),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::col;
+    use rstest::rstest;
+
+    use super::*;
+
+    /// Rows `x_0`, `x_1` and `x_0 + x_1`.
+    fn build_matrix() -> SparseColMat<I, E> {
+        SparseColMat::try_new_from_triplets(
+            3,
+            2,
+            &[
+                Triplet::new(0, 0, 1.),
+                Triplet::new(2, 0, 1.),
+                Triplet::new(1, 1, 1.),
+                Triplet::new(2, 1, 1.),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[rstest]
+    // Consistent system with a nonnegative solution
+    #[case(col![1., 2., 3.], col![1., 2.])]
+    // The unconstrained solution (1, -1) has a negative entry
+    #[case(col![1., -1., 0.], col![0.5, 0.])]
+    fn test_nnls(#[case] b: Col<E>, #[case] expected: Col<E>) {
+        let x = nnls(&build_matrix(), &b).unwrap();
+        assert!((x - expected).norm_max() < 1e-6);
+    }
+
+    #[test]
+    fn test_nnls_dimension_mismatch() {
+        assert!(nnls(&build_matrix(), &col![1., 2.]).is_err());
+    }
+}
@@ -0,0 +1,294 @@
+//! Registry of solvers implemented outside this crate.
+//!
+//! Crates that implement [`LPSolver`], [`QPSolver`] or [`NLPSolver`] register a
+//! factory under a name, once at startup:
+//!
+//! ```ignore
+//! copters::registry::register_lp_solver("my_simplex", |lp, options| {
+//!     Ok(Box::new(MySimplex::new(lp, options)))
+//! })?;
+//! ```
+//!
+//! The solver is then selected by name, either with `with_solver_name` of
+//! [`LPSolverBuilder`](crate::lp::LPSolverBuilder),
+//! [`QPSolverBuilder`](crate::qp::QPSolverBuilder) and
+//! [`NLPSolverBuilder`](crate::nlp::NLPSolverBuilder), or with the `lp_solver`,
+//! `qp_solver` and `nlp_solver` options. A solver type given with
+//! `with_solver` takes precedence over a name.
+//!
+//! The registries are global to the process and names are unique within each
+//! of them.
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, RwLock},
+};
+
+use problemo::{Problem, common::IntoCommonProblem};
+
+use crate::{
+    SolverOptions,
+    lp::{LPSolver, LinearProgram},
+    nlp::{NLPSolver, NonlinearProgram},
+    qp::{QPSolver, QuadraticProgram},
+};
+
+/// Creates a linear program solver from the program and the options of the builder.
+pub type LPSolverFactory = Arc<
+    dyn for<'a> Fn(&'a LinearProgram, &SolverOptions) -> Result<Box<dyn LPSolver<'a> + 'a>, Problem>
+        + Send
+        + Sync,
+>;
+
+/// Creates a quadratic program solver from the program and the options of the builder.
+pub type QPSolverFactory = Arc<
+    dyn for<'a> Fn(
+            &'a QuadraticProgram,
+            &SolverOptions,
+        ) -> Result<Box<dyn QPSolver<'a> + 'a>, Problem>
+        + Send
+        + Sync,
+>;
+
+/// Creates a nonlinear program solver from the program and the options of the builder.
+pub type NLPSolverFactory = Arc<
+    dyn for<'a> Fn(
+            &'a NonlinearProgram,
+            &SolverOptions,
+        ) -> Result<Box<dyn NLPSolver<'a> + 'a>, Problem>
+        + Send
+        + Sync,
+>;
+
+/// Factories of one kind of program, by name.
+struct Registry<F> {
+    kind: &'static str,
+    factories: RwLock<BTreeMap<String, F>>,
+}
+
+impl<F: Clone> Registry<F> {
+    const fn new(kind: &'static str) -> Self {
+        Self {
+            kind,
+            factories: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    fn register(&self, name: &str, factory: F) -> Result<(), Problem> {
+        let mut factories = self.factories.write().unwrap();
+        if factories.contains_key(name) {
+            return Err(format!("{} solver {name} is already registered", self.kind).gloss());
+        }
+        factories.insert(name.to_string(), factory);
+        Ok(())
+    }
+
+    fn get(&self, name: &str) -> Result<F, Problem> {
+        let factories = self.factories.read().unwrap();
+        factories.get(name).cloned().ok_or_else(|| {
+            let names = factories.keys().cloned().collect::<Vec<_>>();
+            format!(
+                "No {} solver is registered as {name} (registered: {})",
+                self.kind,
+                names.join(", ")
+            )
+            .gloss()
+        })
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.factories.read().unwrap().keys().cloned().collect()
+    }
+}
+
+static LP_SOLVERS: Registry<LPSolverFactory> = Registry::new("LP");
+static QP_SOLVERS: Registry<QPSolverFactory> = Registry::new("QP");
+static NLP_SOLVERS: Registry<NLPSolverFactory> = Registry::new("NLP");
+
+/// Registers a linear program solver under `name`. Fails if the name is taken.
+pub fn register_lp_solver<F>(name: &str, factory: F) -> Result<(), Problem>
+where
+    F: for<'a> Fn(&'a LinearProgram, &SolverOptions) -> Result<Box<dyn LPSolver<'a> + 'a>, Problem>
+        + Send
+        + Sync
+        + 'static,
+{
+    LP_SOLVERS.register(name, Arc::new(factory))
+}
+
+/// Registers a quadratic program solver under `name`. Fails if the name is taken.
+pub fn register_qp_solver<F>(name: &str, factory: F) -> Result<(), Problem>
+where
+    F: for<'a> Fn(
+            &'a QuadraticProgram,
+            &SolverOptions,
+        ) -> Result<Box<dyn QPSolver<'a> + 'a>, Problem>
+        + Send
+        + Sync
+        + 'static,
+{
+    QP_SOLVERS.register(name, Arc::new(factory))
+}
+
+/// Registers a nonlinear program solver under `name`. Fails if the name is taken.
+pub fn register_nlp_solver<F>(name: &str, factory: F) -> Result<(), Problem>
+where
+    F: for<'a> Fn(
+            &'a NonlinearProgram,
+            &SolverOptions,
+        ) -> Result<Box<dyn NLPSolver<'a> + 'a>, Problem>
+        + Send
+        + Sync
+        + 'static,
+{
+    NLP_SOLVERS.register(name, Arc::new(factory))
+}
+
+/// Names of the registered linear program solvers, in alphabetical order.
+pub fn get_lp_solver_names() -> Vec<String> {
+    LP_SOLVERS.names()
+}
+
+/// Names of the registered quadratic program solvers, in alphabetical order.
+pub fn get_qp_solver_names() -> Vec<String> {
+    QP_SOLVERS.names()
+}
+
+/// Names of the registered nonlinear program solvers, in alphabetical order.
+pub fn get_nlp_solver_names() -> Vec<String> {
+    NLP_SOLVERS.names()
+}
+
+pub(crate) fn build_lp_solver<'a>(
+    name: &str,
+    lp: &'a LinearProgram,
+    options: &SolverOptions,
+) -> Result<Box<dyn LPSolver<'a> + 'a>, Problem> {
+    LP_SOLVERS.get(name)?(lp, options)
+}
+
+pub(crate) fn build_qp_solver<'a>(
+    name: &str,
+    qp: &'a QuadraticProgram,
+    options: &SolverOptions,
+) -> Result<Box<dyn QPSolver<'a> + 'a>, Problem> {
+    QP_SOLVERS.get(name)?(qp, options)
+}
+
+pub(crate) fn build_nlp_solver<'a>(
+    name: &str,
+    nlp: &'a NonlinearProgram,
+    options: &SolverOptions,
+) -> Result<Box<dyn NLPSolver<'a> + 'a>, Problem> {
+    NLP_SOLVERS.get(name)?(nlp, options)
+}
+
+/// Name of the solver selected with the builder or, if empty, with the option
+/// `option` of `options`.
+pub(crate) fn selected_name(
+    name: Option<String>,
+    options: &SolverOptions,
+    option: &str,
+) -> Option<String> {
+    name.or_else(|| options.get_option::<String>(option))
+        .filter(|name| !name.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::{
+        Col, col,
+        sparse::{SparseColMat, Triplet},
+    };
+
+    use super::*;
+    use crate::{
+        E, IterativeSolver, OptimizationProgram, SolverHooks, SolverState, Status, lp::LPSolverType,
+    };
+
+    /// Returns the midpoint of the bounds as the solution, in one iteration.
+    struct MidpointSolver<'a> {
+        lp: &'a LinearProgram,
+    }
+
+    impl<'a> IterativeSolver for MidpointSolver<'a> {
+        fn get_program(&self) -> &dyn OptimizationProgram {
+            self.lp
+        }
+
+        fn get_max_iterations(&self) -> usize {
+            1
+        }
+
+        fn iterate(&mut self, state: &mut SolverState) -> Result<Status, Problem> {
+            let (l, u) = (self.lp.get_lower_bounds(), self.lp.get_upper_bounds());
+            state.x = Col::from_fn(l.nrows(), |j| (l[j] + u[j]) / 2.);
+            state.set_status(Status::Optimal);
+            Ok(Status::Optimal)
+        }
+    }
+
+    impl<'a> LPSolver<'a> for MidpointSolver<'a> {
+        fn new(lp: &'a LinearProgram, _options: &SolverOptions) -> Self {
+            Self { lp }
+        }
+    }
+
+    fn build_lp() -> LinearProgram {
+        let a = SparseColMat::try_new_from_triplets(
+            1,
+            2,
+            &[Triplet::new(0, 0, 1.), Triplet::new(0, 1, 1.)],
+        )
+        .unwrap();
+        LinearProgram::new(col![1., 2.], a, col![1.], col![0., 0.], col![1., 1.])
+    }
+
+    fn solve(builder: crate::lp::LPSolverBuilder) -> Result<Col<E>, Problem> {
+        let mut state = SolverState::new(
+            Col::zeros(2),
+            Col::zeros(1),
+            Col::ones(2),
+            -Col::<E>::ones(2),
+        );
+        builder
+            .build()?
+            .solve(&mut state, &mut SolverHooks::silent())?;
+        Ok(state.get_primal().clone())
+    }
+
+    #[test]
+    fn test_registered_lp_solver() {
+        register_lp_solver("test_midpoint", |lp, options| {
+            Ok(Box::new(MidpointSolver::new(lp, options)))
+        })
+        .unwrap();
+        assert!(
+            register_lp_solver("test_midpoint", |lp, options| {
+                Ok(Box::new(MidpointSolver::new(lp, options)))
+            })
+            .is_err()
+        );
+        assert!(get_lp_solver_names().contains(&"test_midpoint".to_string()));
+
+        let lp = build_lp();
+        let x = solve(lp.solver_builder().with_solver_name("test_midpoint")).unwrap();
+        assert_eq!(x, col![0.5, 0.5]);
+
+        let mut options = SolverOptions::new();
+        options
+            .set_option("lp_solver", "test_midpoint".to_string())
+            .unwrap();
+        let x = solve(lp.solver_builder().with_options(options.clone())).unwrap();
+        assert_eq!(x, col![0.5, 0.5]);
+
+        // A solver type takes precedence over the name
+        let builder = lp
+            .solver_builder()
+            .with_options(options)
+            .with_solver(LPSolverType::MpcSimplicialCholesky);
+        assert_ne!(solve(builder).unwrap(), col![0.5, 0.5]);
+
+        assert!(solve(lp.solver_builder().with_solver_name("test_unknown")).is_err());
+    }
+}
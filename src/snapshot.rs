@@ -0,0 +1,303 @@
+//! Per-iteration snapshots of a solve, for replay and regression testing.
+//!
+//! A [`SnapshotRecorder`] added to the hooks of a solve with
+//! [`SolverHooks::with_callback`](crate::SolverHooks::with_callback) records a
+//! compact [`Snapshot`] of every iterate: hashes of the primal and dual
+//! variables, the largest residuals, the barrier parameter and the step
+//! lengths. [`Recording::compare`] finds the first iteration at which two
+//! recorded runs differ, either bitwise or beyond a relative tolerance, so that
+//! a refactoring of the solver internals can be checked to reproduce the
+//! iterates of the previous implementation. Recordings are serializable to
+//! keep reference runs, e.g. of the netlib suite, between versions.
+
+use std::sync::{Arc, Mutex};
+
+use faer::ColRef;
+use serde::{Deserialize, Serialize};
+
+use crate::{E, StateView, callback::Callback};
+
+/// Compact summary of one iterate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    nit: usize,
+    /// Hash of the bits of the primal variables.
+    x_hash: u64,
+    /// Hash of the bits of the dual variables.
+    y_hash: u64,
+    primal_residual: E,
+    dual_residual: E,
+    mu: Option<E>,
+    alpha_primal: E,
+    alpha_dual: E,
+}
+
+impl Snapshot {
+    pub fn new<S: StateView + ?Sized>(state: &S) -> Self {
+        Self {
+            nit: state.get_nit(),
+            x_hash: hash_col(state.get_primal()),
+            y_hash: hash_col(state.get_dual()),
+            primal_residual: state.get_primal_feasibility().norm_max(),
+            dual_residual: state.get_dual_feasibility().norm_max(),
+            mu: state.get_mu(),
+            alpha_primal: state.get_alpha_primal(),
+            alpha_dual: state.get_alpha_dual(),
+        }
+    }
+
+    pub fn get_nit(&self) -> usize {
+        self.nit
+    }
+
+    pub fn get_x_hash(&self) -> u64 {
+        self.x_hash
+    }
+
+    pub fn get_y_hash(&self) -> u64 {
+        self.y_hash
+    }
+
+    pub fn get_primal_residual(&self) -> E {
+        self.primal_residual
+    }
+
+    pub fn get_dual_residual(&self) -> E {
+        self.dual_residual
+    }
+
+    pub fn get_mu(&self) -> Option<E> {
+        self.mu
+    }
+
+    /// First field in which `self` and `other` differ, comparing the values
+    /// within the relative `tolerance`, or their bits and the hashes if it is
+    /// zero.
+    fn difference(&self, other: &Snapshot, tolerance: E) -> Option<&'static str> {
+        let close = |a: E, b: E| {
+            if tolerance == E::from(0.) {
+                a.to_bits() == b.to_bits()
+            } else {
+                (a - b).abs() <= tolerance * (E::from(1.) + a.abs().max(b.abs()))
+            }
+        };
+        let values = [
+            (
+                "primal_residual",
+                self.primal_residual,
+                other.primal_residual,
+            ),
+            ("dual_residual", self.dual_residual, other.dual_residual),
+            ("alpha_primal", self.alpha_primal, other.alpha_primal),
+            ("alpha_dual", self.alpha_dual, other.alpha_dual),
+        ];
+        if tolerance == E::from(0.) && self.x_hash != other.x_hash {
+            Some("x")
+        } else if tolerance == E::from(0.) && self.y_hash != other.y_hash {
+            Some("y")
+        } else if let Some((field, _, _)) = values.iter().find(|(_, a, b)| !close(*a, *b)) {
+            Some(field)
+        } else {
+            match (self.mu, other.mu) {
+                (Some(a), Some(b)) if close(a, b) => None,
+                (None, None) => None,
+                _ => Some("mu"),
+            }
+        }
+    }
+}
+
+/// FNV-1a hash of the bits of `col`, which is stable across platforms.
+fn hash_col(col: ColRef<'_, E>) -> u64 {
+    col.iter()
+        .flat_map(|value| value.to_bits().to_le_bytes())
+        .fold(0xcbf2_9ce4_8422_2325_u64, |h, b| {
+            (h ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+/// First difference between two recordings found by [`Recording::compare`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mismatch {
+    /// The runs took different numbers of iterations, but agree on the
+    /// iterations of both.
+    Length { left: usize, right: usize },
+    /// The snapshots at the `index`-th recorded iteration differ in `field`.
+    Iteration { index: usize, field: &'static str },
+}
+
+/// Snapshots of the iterations of one solve.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Recording {
+    snapshots: Vec<Snapshot>,
+}
+
+impl Recording {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get_snapshots(&self) -> &[Snapshot] {
+        &self.snapshots
+    }
+
+    pub fn push(&mut self, snapshot: Snapshot) {
+        self.snapshots.push(snapshot);
+    }
+
+    /// First difference between `self` and `other`, `None` if the runs are
+    /// equivalent. With a zero `tolerance`, the runs must be bitwise equal;
+    /// otherwise, the residuals, step lengths and barrier parameters must agree
+    /// within the relative `tolerance`, and the iterates are not compared.
+    pub fn compare(&self, other: &Recording, tolerance: E) -> Option<Mismatch> {
+        let iteration = self
+            .snapshots
+            .iter()
+            .zip(&other.snapshots)
+            .enumerate()
+            .find_map(|(index, (left, right))| {
+                let field = left.difference(right, tolerance)?;
+                Some(Mismatch::Iteration { index, field })
+            });
+        let (left, right) = (self.snapshots.len(), other.snapshots.len());
+        iteration.or((left != right).then_some(Mismatch::Length { left, right }))
+    }
+}
+
+/// Records a [`Snapshot`] of every iteration.
+///
+/// Clones share the recording, so that the recorder kept by the caller sees
+/// the iterations of the copy moved into the hooks. Every solve restarts the
+/// recording.
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotRecorder {
+    recording: Arc<Mutex<Recording>>,
+}
+
+impl SnapshotRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Snapshots recorded since the start of the last solve.
+    pub fn get_recording(&self) -> Recording {
+        self.recording.lock().unwrap().clone()
+    }
+}
+
+impl<S: StateView + ?Sized> Callback<S> for SnapshotRecorder {
+    fn init(&mut self, _state: &S) {
+        *self.recording.lock().unwrap() = Recording::new();
+    }
+
+    fn call(&mut self, state: &S) {
+        self.recording.lock().unwrap().push(Snapshot::new(state));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::{
+        col,
+        sparse::{SparseColMat, Triplet},
+    };
+    use rstest::rstest;
+
+    use super::*;
+    use crate::{
+        SolverHooks, SolverOptions, SolverState, Status, callback::NoOpCallback,
+        ipm::AugmentedSystemType, lp::LinearProgram, terminators::ComplementarityTerminator,
+    };
+
+    /// Records the solve of `min x_0 + 2 x_1` subject to `x_0 + x_1 = 2`,
+    /// `x_0 - x_1 = 0` and `0 <= x <= 10` with the augmented system `system`.
+    fn record(system: AugmentedSystemType) -> Recording {
+        let triplets = [
+            Triplet::new(0, 0, 1.),
+            Triplet::new(0, 1, 1.),
+            Triplet::new(1, 0, 1.),
+            Triplet::new(1, 1, -1.),
+        ];
+        let lp = LinearProgram::new(
+            col![1., 2.],
+            SparseColMat::try_new_from_triplets(2, 2, &triplets).unwrap(),
+            col![2., 0.],
+            col![0., 0.],
+            col![10., 10.],
+        );
+        let mut options = SolverOptions::new();
+        options.set_option("augmented_system", system).unwrap();
+
+        let recorder = SnapshotRecorder::new();
+        let mut hooks = SolverHooks::new(
+            Box::new(NoOpCallback::new()),
+            Box::new(ComplementarityTerminator::new(&options)),
+        )
+        .with_callback(Box::new(recorder.clone()));
+        let mut state = SolverState::new_interior(lp.get_lower_bounds(), lp.get_upper_bounds(), 2);
+        let status = lp
+            .solver_builder()
+            .with_options(options)
+            .build()
+            .unwrap()
+            .solve(&mut state, &mut hooks)
+            .unwrap();
+        assert_eq!(status, Status::Optimal);
+        recorder.get_recording()
+    }
+
+    #[rstest]
+    #[case(AugmentedSystemType::Auto)]
+    #[case(AugmentedSystemType::NormalEquations)]
+    fn test_replay(#[case] system: AugmentedSystemType) {
+        let recording = record(system);
+        assert!(!recording.get_snapshots().is_empty());
+        assert!(recording.get_snapshots()[0].get_mu().is_some());
+        assert_eq!(recording.compare(&record(system), 0.), None);
+    }
+
+    #[test]
+    fn test_compare() {
+        let recording = record(AugmentedSystemType::NormalEquations);
+        let mut perturbed = recording.clone();
+        perturbed.snapshots[1].dual_residual *= 1. + 1e-12;
+        assert_eq!(
+            recording.compare(&perturbed, 0.),
+            Some(Mismatch::Iteration {
+                index: 1,
+                field: "dual_residual"
+            })
+        );
+        assert_eq!(recording.compare(&perturbed, 1e-9), None);
+
+        perturbed.snapshots[2].x_hash ^= 1;
+        assert_eq!(
+            recording.compare(&perturbed, 0.),
+            Some(Mismatch::Iteration {
+                index: 1,
+                field: "dual_residual"
+            })
+        );
+        assert_eq!(recording.compare(&perturbed, 1e-9), None);
+
+        let n = recording.get_snapshots().len();
+        let mut truncated = recording.clone();
+        truncated.snapshots.pop();
+        assert_eq!(
+            recording.compare(&truncated, 0.),
+            Some(Mismatch::Length {
+                left: n,
+                right: n - 1
+            })
+        );
+
+        let json = serde_json::to_string(&recording).unwrap();
+        let restored: Recording = serde_json::from_str(&json).unwrap();
+        // The hashes survive exactly, the values up to the parsing of the floats
+        assert_eq!(recording.compare(&restored, 1e-15), None);
+        assert_eq!(
+            restored.get_snapshots()[2].get_x_hash(),
+            recording.get_snapshots()[2].get_x_hash()
+        );
+    }
+}
@@ -0,0 +1,531 @@
+//! Reformulations of individual linear chance constraints with Gaussian data.
+//!
+//! A chance constraint requires a linear inequality with random data to hold
+//! with probability at least `1 - risk`,
+//!
+//! ```text
+//! P(a^T x <= b) >= 1 - risk,    a ~ N(mu, Sigma),  b ~ N(b_bar, sigma_b^2),
+//! ```
+//!
+//! with `a` and `b` independent. Since `a^T x - b` is Gaussian, the constraint is
+//! equivalent to the second-order cone constraint
+//!
+//! ```text
+//! mu^T x + kappa sqrt(x^T Sigma x + sigma_b^2) <= b_bar,    kappa = Phi^{-1}(1 - risk),
+//! ```
+//!
+//! which is convex for `risk <= 0.5`. Bounding the standard deviation by the
+//! sum of the individual standard deviations yields a conservative linear
+//! (quantile) approximation, which is exact when only `b` is random.
+
+use std::sync::Arc;
+
+use faer::{
+    Col, Mat,
+    sparse::{SparseColMat, Triplet},
+    traits::IndexCore,
+};
+use problemo::{Problem, common::IntoCommonProblem};
+
+use crate::{E, I, lp::LinearProgram, nlp::NonlinearProgram, to_index};
+
+/// The chance constraint `P(a^T x <= b) >= 1 - risk` with Gaussian `a` and `b`.
+#[derive(Clone, Debug)]
+pub struct GaussianChanceConstraint {
+    mean: Col<E>,
+    covariance: Option<Mat<E>>,
+    rhs_mean: E,
+    rhs_variance: E,
+    risk: E,
+}
+
+impl GaussianChanceConstraint {
+    /// Creates a constraint with deterministic coefficients `mean` and right-hand
+    /// side `rhs_mean`. Fails unless `0 < risk <= 0.5`.
+    pub fn new(mean: Col<E>, rhs_mean: E, risk: E) -> Result<Self, Problem> {
+        if !(risk > E::from(0.) && risk <= E::from(0.5)) {
+            return Err(format!("Risk level {risk} must lie in (0, 0.5]").gloss());
+        }
+        Ok(Self {
+            mean,
+            covariance: None,
+            rhs_mean,
+            rhs_variance: E::from(0.),
+            risk,
+        })
+    }
+
+    /// Sets the covariance of the coefficients, which must be symmetric positive semidefinite.
+    pub fn with_covariance(mut self, covariance: Mat<E>) -> Self {
+        self.covariance = Some(covariance);
+        self
+    }
+
+    pub fn with_rhs_variance(mut self, variance: E) -> Self {
+        self.rhs_variance = variance;
+        self
+    }
+
+    pub fn get_n_vars(&self) -> usize {
+        self.mean.nrows()
+    }
+
+    pub fn get_mean(&self) -> &Col<E> {
+        &self.mean
+    }
+
+    pub fn get_rhs_mean(&self) -> E {
+        self.rhs_mean
+    }
+
+    pub fn get_risk(&self) -> E {
+        self.risk
+    }
+
+    /// The standard normal quantile `kappa = Phi^{-1}(1 - risk)`.
+    pub fn get_safety_factor(&self) -> E {
+        normal_quantile(E::from(1.) - self.risk)
+    }
+
+    /// Standard deviation of `a^T x - b`.
+    pub fn get_std_dev(&self, x: &Col<E>) -> E {
+        let variance = match &self.covariance {
+            Some(sigma) => (x.transpose() * (sigma * x)) + self.rhs_variance,
+            None => self.rhs_variance,
+        };
+        variance.max(E::from(0.)).sqrt()
+    }
+
+    /// Left-hand side less the right-hand side of the cone constraint, which is
+    /// nonpositive exactly when the chance constraint holds.
+    pub fn evaluate(&self, x: &Col<E>) -> E {
+        self.mean.transpose() * x + self.get_safety_factor() * self.get_std_dev(x) - self.rhs_mean
+    }
+
+    /// Gradient of [`GaussianChanceConstraint::evaluate`]. At a point with zero
+    /// standard deviation the cone term contributes nothing.
+    pub fn gradient(&self, x: &Col<E>) -> Col<E> {
+        let std_dev = self.get_std_dev(x);
+        match &self.covariance {
+            Some(sigma) if std_dev > E::from(0.) => {
+                &self.mean + (self.get_safety_factor() / std_dev) * (sigma * x)
+            }
+            _ => self.mean.clone(),
+        }
+    }
+
+    /// Hessian of [`GaussianChanceConstraint::evaluate`].
+    pub fn hessian(&self, x: &Col<E>) -> Mat<E> {
+        let n = self.get_n_vars();
+        let std_dev = self.get_std_dev(x);
+        match &self.covariance {
+            Some(sigma) if std_dev > E::from(0.) => {
+                let sigma_x = sigma * x;
+                let kappa = self.get_safety_factor();
+                Mat::from_fn(n, n, |i, j| {
+                    kappa
+                        * (sigma[(i, j)] / std_dev
+                            - sigma_x[i] * sigma_x[j] / (std_dev * std_dev * std_dev))
+                })
+            }
+            _ => Mat::zeros(n, n),
+        }
+    }
+
+    /// The linear row `coefficients^T x <= rhs` implying the chance constraint
+    /// over the box `l <= x <= u`.
+    ///
+    /// Uses `sqrt(x^T Sigma x) <= sum_i sigma_i |x_i|`, so the sign of every
+    /// variable with random coefficient must be fixed by its bounds.
+    pub fn quantile_approximation(&self, l: &Col<E>, u: &Col<E>) -> Result<(Col<E>, E), Problem> {
+        let kappa = self.get_safety_factor();
+        let mut coefficients = self.mean.clone();
+
+        if let Some(sigma) = &self.covariance {
+            for j in 0..self.get_n_vars() {
+                let sigma_j = sigma[(j, j)].max(E::from(0.)).sqrt();
+                if sigma_j == E::from(0.) {
+                    continue;
+                }
+                if l[j] >= E::from(0.) {
+                    coefficients[j] += kappa * sigma_j;
+                } else if u[j] <= E::from(0.) {
+                    coefficients[j] -= kappa * sigma_j;
+                } else {
+                    return Err(
+                        format!("Variable {j} has a random coefficient but no fixed sign").gloss(),
+                    );
+                }
+            }
+        }
+
+        Ok((
+            coefficients,
+            self.rhs_mean - kappa * self.rhs_variance.sqrt(),
+        ))
+    }
+}
+
+/// Appends the quantile approximations of `constraints` to `lp` as inequality
+/// rows, each with a nonnegative slack column after the original variables.
+#[allow(non_snake_case)]
+pub fn with_linear_chance_constraints(
+    lp: &LinearProgram,
+    constraints: &[GaussianChanceConstraint],
+) -> Result<LinearProgram, Problem> {
+    let (n, m) = lp.get_dims();
+    let k = constraints.len();
+    check_dimensions(n, constraints)?;
+
+    let mut triplets = csc_triplets(lp.get_constraint_matrix());
+    let mut b = Col::zeros(m + k);
+    b.subrows_mut(0, m).copy_from(lp.get_rhs());
+
+    for (i, constraint) in constraints.iter().enumerate() {
+        let (coefficients, rhs) =
+            constraint.quantile_approximation(lp.get_lower_bounds(), lp.get_upper_bounds())?;
+        for (j, &a_j) in coefficients.iter().enumerate() {
+            if a_j != E::from(0.) {
+                triplets.push(Triplet::new(to_index(m + i), to_index(j), a_j));
+            }
+        }
+        triplets.push(Triplet::new(to_index(m + i), to_index(n + i), E::from(1.)));
+        b[m + i] = rhs;
+    }
+
+    let (c, l, u) = extend_with_slacks(lp, k);
+    let A = SparseColMat::try_new_from_triplets(m + k, n + k, &triplets)
+        .map_err(|e| format!("Failed to assemble constraint matrix: {e:?}").gloss())?;
+    Ok(LinearProgram::new(c, A, b, l, u))
+}
+
+/// Adds `constraints` to `lp` in their exact second-order cone form, giving a
+/// nonlinear program whose variables are those of `lp` followed by one
+/// nonnegative slack per chance constraint.
+#[allow(non_snake_case)]
+pub fn with_cone_chance_constraints(
+    lp: &LinearProgram,
+    constraints: &[GaussianChanceConstraint],
+) -> Result<NonlinearProgram, Problem> {
+    let (n, m) = lp.get_dims();
+    let k = constraints.len();
+    check_dimensions(n, constraints)?;
+
+    let (c, l, u) = extend_with_slacks(lp, k);
+    let A = lp.get_constraint_matrix().clone();
+    let b = lp.get_rhs().clone();
+    let constraints = Arc::new(constraints.to_vec());
+
+    let f = {
+        let c = c.clone();
+        Box::new(move |x: &Col<E>| c.transpose() * x)
+    };
+    let df = Box::new(move |_: &Col<E>| c.clone());
+    let g = {
+        let (A, constraints) = (A.clone(), constraints.clone());
+        Box::new(move |x: &Col<E>| {
+            let x_lp = x.subrows(0, n).to_owned();
+            let mut g = Col::zeros(m + k);
+            g.subrows_mut(0, m).copy_from(&A * &x_lp - &b);
+            for (i, constraint) in constraints.iter().enumerate() {
+                g[m + i] = constraint.evaluate(&x_lp) + x[n + i];
+            }
+            g
+        })
+    };
+    let dg = {
+        let constraints = constraints.clone();
+        let A_triplets = csc_triplets(&A);
+        Box::new(move |x: &Col<E>| {
+            let x_lp = x.subrows(0, n).to_owned();
+            let mut triplets = A_triplets.clone();
+            for (i, constraint) in constraints.iter().enumerate() {
+                let gradient = constraint.gradient(&x_lp);
+                for (j, &d_j) in gradient.iter().enumerate() {
+                    if d_j != E::from(0.) {
+                        triplets.push(Triplet::new(to_index(m + i), to_index(j), d_j));
+                    }
+                }
+                triplets.push(Triplet::new(to_index(m + i), to_index(n + i), E::from(1.)));
+            }
+            SparseColMat::try_new_from_triplets(m + k, n + k, &triplets).unwrap()
+        })
+    };
+    // Only the cone rows are curved, weighted by their multipliers
+    let h = Box::new(move |x: &Col<E>, y: &Col<E>| {
+        let x_lp = x.subrows(0, n).to_owned();
+        let mut triplets = Vec::new();
+        for (i, constraint) in constraints.iter().enumerate() {
+            let hessian = constraint.hessian(&x_lp);
+            for j in 0..n {
+                for r in 0..n {
+                    if hessian[(r, j)] != E::from(0.) {
+                        triplets.push(Triplet::new(
+                            to_index(r),
+                            to_index(j),
+                            y[m + i] * hessian[(r, j)],
+                        ));
+                    }
+                }
+            }
+        }
+        SparseColMat::try_new_from_triplets(n + k, n + k, &triplets).unwrap()
+    });
+
+    Ok(NonlinearProgram::new_boxed(
+        n + k,
+        m + k,
+        f,
+        g,
+        df,
+        dg,
+        Some(h),
+        Some(l),
+        Some(u),
+    ))
+}
+
+fn check_dimensions(n: usize, constraints: &[GaussianChanceConstraint]) -> Result<(), Problem> {
+    match constraints.iter().position(|c| c.get_n_vars() != n) {
+        Some(i) => Err(format!("Chance constraint {i} does not have {n} coefficients").gloss()),
+        None => Ok(()),
+    }
+}
+
+/// Objective and bounds of `lp` extended by `k` slack columns in `[0, inf)`.
+fn extend_with_slacks(lp: &LinearProgram, k: usize) -> (Col<E>, Col<E>, Col<E>) {
+    let n = lp.get_n_vars();
+    let mut c = Col::zeros(n + k);
+    let mut l = Col::zeros(n + k);
+    let mut u = Col::from_fn(n + k, |_| E::INFINITY);
+    c.subrows_mut(0, n).copy_from(lp.get_objective());
+    l.subrows_mut(0, n).copy_from(lp.get_lower_bounds());
+    u.subrows_mut(0, n).copy_from(lp.get_upper_bounds());
+    (c, l, u)
+}
+
+fn csc_triplets(a: &SparseColMat<I, E>) -> Vec<Triplet<I, I, E>> {
+    let col_ptr = a.symbolic().col_ptr();
+    let row_idx = a.symbolic().row_idx();
+    let values = a.val();
+    (0..a.ncols())
+        .flat_map(|j| {
+            (col_ptr[j].zx()..col_ptr[j + 1].zx())
+                .map(move |k| Triplet::new(row_idx[k], to_index(j), values[k]))
+        })
+        .collect()
+}
+
+/// Inverse of the standard normal distribution function (Wichura, AS 241).
+pub fn normal_quantile(p: E) -> E {
+    const A: [E; 8] = [
+        3.387_132_872_796_366_6,
+        1.331_416_678_917_843_8e2,
+        1.971_590_950_306_551_4e3,
+        1.373_169_376_550_946e4,
+        4.592_195_393_154_987e4,
+        6.726_577_092_700_87e4,
+        3.343_057_558_358_813e4,
+        2.509_080_928_730_122_7e3,
+    ];
+    const B: [E; 8] = [
+        1.,
+        4.231_333_070_160_091e1,
+        6.871_870_074_920_579e2,
+        5.394_196_021_424_751e3,
+        2.121_379_430_158_659_7e4,
+        3.930_789_580_009_271e4,
+        2.872_908_573_572_194_3e4,
+        5.226_495_278_852_854e3,
+    ];
+    const C: [E; 8] = [
+        1.423_437_110_749_683_6,
+        4.630_337_846_156_545,
+        5.769_497_221_460_691,
+        3.647_848_324_763_204_5,
+        1.270_458_252_452_368_4,
+        2.417_807_251_774_506e-1,
+        2.272_384_498_926_918_4e-2,
+        7.745_450_142_783_414e-4,
+    ];
+    const D: [E; 8] = [
+        1.,
+        2.053_191_626_637_759,
+        1.676_384_830_183_803_8,
+        6.897_673_349_851e-1,
+        1.481_039_764_274_800_8e-1,
+        1.519_866_656_361_645_7e-2,
+        5.475_938_084_995_345e-4,
+        1.050_750_071_644_416_8e-9,
+    ];
+    const F: [E; 8] = [
+        6.657_904_643_501_103,
+        5.463_784_911_164_114,
+        1.784_826_539_917_291_3,
+        2.965_605_718_285_049e-1,
+        2.653_218_952_657_612_4e-2,
+        1.242_660_947_388_078_4e-3,
+        2.711_555_568_743_487_6e-5,
+        2.010_334_399_292_288_1e-7,
+    ];
+    const G: [E; 8] = [
+        1.,
+        5.998_322_065_558_879e-1,
+        1.369_298_809_227_358e-1,
+        1.487_536_129_085_061_5e-2,
+        7.868_691_311_456_133e-4,
+        1.846_318_317_510_054_8e-5,
+        1.421_511_758_316_446e-7,
+        2.044_263_103_389_939_7e-15,
+    ];
+    let ratio = |num: &[E; 8], den: &[E; 8], r: E| {
+        let eval = |coeffs: &[E; 8]| coeffs.iter().rev().fold(E::from(0.), |acc, &a| acc * r + a);
+        eval(num) / eval(den)
+    };
+
+    if p <= E::from(0.) {
+        return E::NEG_INFINITY;
+    }
+    if p >= E::from(1.) {
+        return E::INFINITY;
+    }
+
+    let q = p - 0.5;
+    if q.abs() <= 0.425 {
+        let r = 0.180625 - q * q;
+        return q * ratio(&A, &B, r);
+    }
+
+    let r = (-p.min(1. - p).ln()).sqrt();
+    let z = if r <= 5. {
+        ratio(&C, &D, r - 1.6)
+    } else {
+        ratio(&F, &G, r - 5.)
+    };
+    if q < 0. { -z } else { z }
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::{col, mat};
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(0.5, 0.)]
+    #[case(0.95, 1.6448536269514722)]
+    #[case(0.975, 1.959963984540054)]
+    #[case(0.01, -2.3263478740408408)]
+    #[case(1e-10, -6.361340902404056)]
+    #[case(1. - 1e-12, 7.0344869100478356)]
+    #[case(1e-300, -37.0470962993612)]
+    fn test_normal_quantile(#[case] p: E, #[case] expected: E) {
+        assert!((normal_quantile(p) - expected).abs() < 1e-9 * expected.abs().max(1.));
+    }
+
+    #[test]
+    fn test_rhs_uncertainty_is_exact() {
+        let constraint = GaussianChanceConstraint::new(col![1., 2.], 10., 0.05)
+            .unwrap()
+            .with_rhs_variance(4.);
+        let (coefficients, rhs) = constraint
+            .quantile_approximation(&col![-1., -1.], &col![1., 1.])
+            .unwrap();
+
+        assert_eq!(coefficients, col![1., 2.]);
+        assert!((rhs - (10. - 2. * 1.6448536269514722)).abs() < 1e-9);
+        // The row and the cone constraint agree everywhere
+        let x = col![0.3, -0.8];
+        let row = coefficients.transpose() * &x - rhs;
+        assert!((row - constraint.evaluate(&x)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_quantile_approximation_is_conservative() {
+        let constraint = GaussianChanceConstraint::new(col![1., 1.], 10., 0.05)
+            .unwrap()
+            .with_covariance(mat![[0.25, 0.1], [0.1, 1.]]);
+        let (l, u) = (col![0., -5.], col![5., 0.]);
+        let (coefficients, rhs) = constraint.quantile_approximation(&l, &u).unwrap();
+
+        for x in [col![0., 0.], col![2., -1.], col![5., -5.], col![1., -3.]] {
+            let row = coefficients.transpose() * &x - rhs;
+            assert!(constraint.evaluate(&x) <= row + 1e-12);
+        }
+        // A variable of either sign cannot be bounded linearly
+        assert!(
+            constraint
+                .quantile_approximation(&col![-1., -1.], &u)
+                .is_err()
+        );
+        assert!(GaussianChanceConstraint::new(col![1., 1.], 10., 0.7).is_err());
+    }
+
+    /// `max x_0 + x_1` over `x_0 + x_1 + s = 10` and `0 <= x <= 10`, where the
+    /// coefficients of the chance constraint are uncertain.
+    fn build_problem() -> (LinearProgram, GaussianChanceConstraint) {
+        let a = SparseColMat::try_new_from_triplets(
+            1,
+            3,
+            &[
+                Triplet::new(0, 0, 1.),
+                Triplet::new(0, 1, 1.),
+                Triplet::new(0, 2, 1.),
+            ],
+        )
+        .unwrap();
+        let lp = LinearProgram::new(
+            col![-1., -1., 0.],
+            a,
+            col![10.],
+            col![0., 0., 0.],
+            col![10., 10., E::INFINITY],
+        );
+        let constraint = GaussianChanceConstraint::new(col![1., 2., 0.], 12., 0.05)
+            .unwrap()
+            .with_covariance(mat![[0.25, 0., 0.], [0., 0.25, 0.], [0., 0., 0.]]);
+        (lp, constraint)
+    }
+
+    #[test]
+    fn test_linear_reformulation() {
+        let (lp, constraint) = build_problem();
+        let reformulated =
+            with_linear_chance_constraints(&lp, std::slice::from_ref(&constraint)).unwrap();
+
+        assert_eq!(reformulated.get_dims(), (4, 2));
+        let kappa = constraint.get_safety_factor();
+        let row = reformulated.get_constraint_matrix().to_dense();
+        assert!((row[(1, 0)] - (1. + 0.5 * kappa)).abs() < 1e-12);
+        assert!((row[(1, 1)] - (2. + 0.5 * kappa)).abs() < 1e-12);
+        assert_eq!((row[(1, 2)], row[(1, 3)]), (0., 1.));
+        assert_eq!(reformulated.get_rhs(), &col![10., 12.]);
+    }
+
+    #[test]
+    fn test_cone_reformulation() {
+        let (lp, constraint) = build_problem();
+        let nlp = with_cone_chance_constraints(&lp, std::slice::from_ref(&constraint)).unwrap();
+
+        let x = col![2., 3., 5., 0.5];
+        let x_lp = col![2., 3., 5.];
+        assert!((nlp.g(&x)[1] - (constraint.evaluate(&x_lp) + 0.5)).abs() < 1e-12);
+        assert!((nlp.f(&x) + 5.).abs() < 1e-12);
+
+        let y = col![0.7, -1.3];
+        let dg = nlp.dg(&x).to_dense();
+        let h = nlp.h(&x, &y).unwrap().to_dense();
+        let grad_lagrangian = |x: &Col<E>| nlp.df(x) + nlp.dg(x).transpose() * &y;
+        for j in 0..4 {
+            let step = 1e-6;
+            let (mut x_plus, mut x_minus) = (x.clone(), x.clone());
+            x_plus[j] += step;
+            x_minus[j] -= step;
+            let dg_j = (nlp.g(&x_plus) - nlp.g(&x_minus)) / (2. * step);
+            let h_j = (grad_lagrangian(&x_plus) - grad_lagrangian(&x_minus)) / (2. * step);
+            assert!((dg_j - dg.col(j)).norm_l2() < 1e-6);
+            assert!((h_j - h.col(j)).norm_l2() < 1e-6);
+        }
+    }
+}
@@ -0,0 +1,471 @@
+//! Conditional value-at-risk (CVaR) of scenario losses.
+//!
+//! For losses `L_s(x) = a_s^T x + d_s` realized with probabilities `p_s`, the
+//! Rockafellar–Uryasev formula
+//!
+//! ```text
+//! CVaR_alpha(x) = min_eta  eta + 1 / (1 - alpha) sum_s p_s max(0, L_s(x) - eta)
+//! ```
+//!
+//! turns the risk measure into linear constraints on an auxiliary variable `eta`
+//! (the value-at-risk at the optimum) and one shortfall variable per scenario:
+//!
+//! ```text
+//! CVaR_alpha(x) = min  eta + 1 / (1 - alpha) sum_s p_s u_s
+//!                 s.t. a_s^T x - eta - u_s <= -d_s,    u_s >= 0.
+//! ```
+//!
+//! The reformulated programs append `eta`, the shortfalls `u`, and one slack per
+//! scenario row to the variables of the original program; see
+//! [`CVaRReformulation`] for their positions.
+
+use std::ops::Range;
+
+use faer::{
+    Col,
+    sparse::{SparseColMat, Triplet},
+    traits::IndexCore,
+};
+use problemo::{Problem, common::IntoCommonProblem};
+
+use crate::{
+    E, I,
+    lp::LinearProgram,
+    stochastic::scenario_tree::{ScenarioTree, push_block},
+    to_index,
+};
+
+/// CVaR at level `alpha` of linear scenario losses.
+#[derive(Clone, Debug)]
+pub struct ConditionalValueAtRisk {
+    /// Scenario losses as rows, over the variables of the program.
+    losses: SparseColMat<I, E>,
+    offsets: Col<E>,
+    probabilities: Col<E>,
+    alpha: E,
+}
+
+impl ConditionalValueAtRisk {
+    /// Creates the CVaR of the losses `losses * x`, whose rows are realized with
+    /// the given probabilities. Fails unless `0 <= alpha < 1` and the
+    /// probabilities form a distribution.
+    pub fn new(
+        losses: SparseColMat<I, E>,
+        probabilities: Col<E>,
+        alpha: E,
+    ) -> Result<Self, Problem> {
+        if !(E::from(0.)..E::from(1.)).contains(&alpha) {
+            return Err(format!("CVaR level {alpha} must lie in [0, 1)").gloss());
+        }
+        if probabilities.nrows() != losses.nrows() {
+            return Err(format!(
+                "Expected {} scenario probabilities, found {}",
+                losses.nrows(),
+                probabilities.nrows()
+            )
+            .gloss());
+        }
+        let total = probabilities.iter().sum::<E>();
+        if probabilities.iter().any(|&p| p < E::from(0.)) || (total - E::from(1.)).abs() > 1e-9 {
+            return Err("Scenario probabilities must be nonnegative and sum to one".gloss());
+        }
+
+        Ok(Self {
+            offsets: Col::zeros(losses.nrows()),
+            losses,
+            probabilities,
+            alpha,
+        })
+    }
+
+    /// CVaR of the total cost along each scenario of `tree`, over the variables
+    /// of [`ScenarioTree::deterministic_equivalent`].
+    pub fn from_scenario_tree(tree: &ScenarioTree, alpha: E) -> Result<Self, Problem> {
+        let scenarios = tree.get_scenarios();
+        let n_vars = tree.get_node_columns(tree.get_n_nodes() - 1).end;
+
+        let mut triplets = Vec::new();
+        for (s, &leaf) in scenarios.iter().enumerate() {
+            for node in tree.get_path(leaf) {
+                let columns = tree.get_node_columns(node);
+                for (j, &c_j) in columns.zip(tree.get_objective(node).iter()) {
+                    if c_j != E::from(0.) {
+                        triplets.push(Triplet::new(to_index(s), to_index(j), c_j));
+                    }
+                }
+            }
+        }
+
+        let losses = SparseColMat::try_new_from_triplets(scenarios.len(), n_vars, &triplets)
+            .map_err(|e| format!("Failed to assemble scenario losses: {e:?}").gloss())?;
+        let probabilities = Col::from_fn(scenarios.len(), |s| tree.get_probability(scenarios[s]));
+        Self::new(losses, probabilities, alpha)
+    }
+
+    /// Sets the constant part `d` of the scenario losses.
+    pub fn with_offsets(mut self, offsets: Col<E>) -> Self {
+        self.offsets = offsets;
+        self
+    }
+
+    pub fn get_alpha(&self) -> E {
+        self.alpha
+    }
+
+    pub fn get_n_scenarios(&self) -> usize {
+        self.losses.nrows()
+    }
+
+    pub fn get_n_vars(&self) -> usize {
+        self.losses.ncols()
+    }
+
+    pub fn get_probabilities(&self) -> &Col<E> {
+        &self.probabilities
+    }
+
+    pub fn get_scenario_losses(&self, x: &Col<E>) -> Col<E> {
+        &self.losses * x + &self.offsets
+    }
+
+    /// Smallest and largest scenario loss over the box `l <= x <= u`.
+    pub fn get_loss_range(&self, l: &Col<E>, u: &Col<E>) -> (E, E) {
+        let col_ptr = self.losses.symbolic().col_ptr();
+        let row_idx = self.losses.symbolic().row_idx();
+        let values = self.losses.val();
+
+        let mut lowest = self.offsets.clone();
+        let mut highest = self.offsets.clone();
+        for j in 0..self.get_n_vars() {
+            for k in col_ptr[j].zx()..col_ptr[j + 1].zx() {
+                let (s, a) = (row_idx[k].zx(), values[k]);
+                let (low, high) = if a >= E::from(0.) {
+                    (a * l[j], a * u[j])
+                } else {
+                    (a * u[j], a * l[j])
+                };
+                lowest[s] += low;
+                highest[s] += high;
+            }
+        }
+        (
+            lowest.iter().cloned().fold(E::INFINITY, E::min),
+            highest.iter().cloned().fold(E::NEG_INFINITY, E::max),
+        )
+    }
+
+    /// Smallest loss `eta` with `P(L <= eta) >= alpha`.
+    pub fn get_value_at_risk(&self, x: &Col<E>) -> E {
+        let losses = self.get_scenario_losses(x);
+        let mut order = (0..losses.nrows()).collect::<Vec<_>>();
+        order.sort_by(|&s, &t| losses[s].total_cmp(&losses[t]));
+
+        let mut cumulative = E::from(0.);
+        for &s in order.iter() {
+            cumulative += self.probabilities[s];
+            if cumulative >= self.alpha && self.probabilities[s] > E::from(0.) {
+                return losses[s];
+            }
+        }
+        losses[*order.last().unwrap()]
+    }
+
+    pub fn evaluate(&self, x: &Col<E>) -> E {
+        let eta = self.get_value_at_risk(x);
+        let shortfall = self
+            .get_scenario_losses(x)
+            .iter()
+            .zip(self.probabilities.iter())
+            .map(|(&loss, &p)| p * (loss - eta).max(E::from(0.)))
+            .sum::<E>();
+        eta + shortfall / (E::from(1.) - self.alpha)
+    }
+
+    /// Minimizes `c^T x + weight * CVaR_alpha(x)` subject to the constraints of `lp`.
+    pub fn add_to_objective(
+        &self,
+        lp: &LinearProgram,
+        weight: E,
+    ) -> Result<CVaRReformulation, Problem> {
+        self.reformulate(lp, Some(weight), None)
+    }
+
+    /// Adds the constraint `CVaR_alpha(x) <= bound` to `lp`.
+    pub fn add_constraint(
+        &self,
+        lp: &LinearProgram,
+        bound: E,
+    ) -> Result<CVaRReformulation, Problem> {
+        self.reformulate(lp, None, Some(bound))
+    }
+
+    #[allow(non_snake_case)]
+    fn reformulate(
+        &self,
+        lp: &LinearProgram,
+        weight: Option<E>,
+        bound: Option<E>,
+    ) -> Result<CVaRReformulation, Problem> {
+        let (n, m) = lp.get_dims();
+        let n_scenarios = self.get_n_scenarios();
+        if self.get_n_vars() != n {
+            return Err(format!(
+                "Scenario losses are defined over {} variables, the program has {n}",
+                self.get_n_vars()
+            )
+            .gloss());
+        }
+
+        let reformulation = CVaRReformulation {
+            n_vars: n,
+            n_scenarios,
+            lp: lp.clone(),
+        };
+        let (eta, shortfall, slack) = (
+            reformulation.get_eta_column(),
+            reformulation.get_shortfall_columns(),
+            reformulation.get_slack_columns(),
+        );
+        let n_bound = usize::from(bound.is_some());
+        let (n_vars, n_cons) = (slack.end + n_bound, m + n_scenarios + n_bound);
+        let scale = E::from(1.) / (E::from(1.) - self.alpha);
+
+        let mut c = Col::zeros(n_vars);
+        let mut b = Col::zeros(n_cons);
+        let mut l = Col::zeros(n_vars);
+        let mut u = Col::from_fn(n_vars, |_| E::INFINITY);
+        c.subrows_mut(0, n).copy_from(lp.get_objective());
+        b.subrows_mut(0, m).copy_from(lp.get_rhs());
+        l.subrows_mut(0, n).copy_from(lp.get_lower_bounds());
+        u.subrows_mut(0, n).copy_from(lp.get_upper_bounds());
+        // Keeping eta off the free variables helps the factorization of the
+        // interior-point systems; its optimum lies within the range of the losses
+        let (loss_min, loss_max) =
+            self.get_loss_range(lp.get_lower_bounds(), lp.get_upper_bounds());
+        l[eta] = loss_min - E::from(1.);
+        u[eta] = loss_max + E::from(1.);
+
+        let mut triplets = Vec::new();
+        push_block(&mut triplets, lp.get_constraint_matrix(), 0, 0);
+        push_block(&mut triplets, &self.losses, m, 0);
+
+        // a_s^T x - eta - u_s + w_s = -d_s
+        for s in 0..n_scenarios {
+            triplets.push(Triplet::new(to_index(m + s), to_index(eta), E::from(-1.)));
+            triplets.push(Triplet::new(
+                to_index(m + s),
+                to_index(shortfall.start + s),
+                E::from(-1.),
+            ));
+            triplets.push(Triplet::new(
+                to_index(m + s),
+                to_index(slack.start + s),
+                E::from(1.),
+            ));
+            b[m + s] = -self.offsets[s];
+        }
+
+        if let Some(weight) = weight {
+            c[eta] = weight;
+            for s in 0..n_scenarios {
+                c[shortfall.start + s] = weight * scale * self.probabilities[s];
+            }
+        }
+
+        // eta + 1 / (1 - alpha) sum_s p_s u_s + t = bound
+        if let Some(bound) = bound {
+            let row = m + n_scenarios;
+            triplets.push(Triplet::new(to_index(row), to_index(eta), E::from(1.)));
+            for s in 0..n_scenarios {
+                triplets.push(Triplet::new(
+                    to_index(row),
+                    to_index(shortfall.start + s),
+                    scale * self.probabilities[s],
+                ));
+            }
+            triplets.push(Triplet::new(
+                to_index(row),
+                to_index(slack.end),
+                E::from(1.),
+            ));
+            b[row] = bound;
+        }
+
+        let A = SparseColMat::try_new_from_triplets(n_cons, n_vars, &triplets)
+            .map_err(|e| format!("Failed to assemble CVaR reformulation: {e:?}").gloss())?;
+        Ok(CVaRReformulation {
+            lp: LinearProgram::new(c, A, b, l, u),
+            ..reformulation
+        })
+    }
+}
+
+/// A linear program extended by the CVaR reformulation.
+///
+/// Its variables are those of the original program, then `eta`, the scenario
+/// shortfalls, the slacks of the scenario rows and, for a CVaR constraint, the
+/// slack of the bound.
+#[derive(Clone, Debug)]
+pub struct CVaRReformulation {
+    lp: LinearProgram,
+    n_vars: usize,
+    n_scenarios: usize,
+}
+
+impl CVaRReformulation {
+    pub fn get_program(&self) -> &LinearProgram {
+        &self.lp
+    }
+
+    pub fn into_program(self) -> LinearProgram {
+        self.lp
+    }
+
+    pub fn get_eta_column(&self) -> usize {
+        self.n_vars
+    }
+
+    pub fn get_shortfall_columns(&self) -> Range<usize> {
+        self.n_vars + 1..self.n_vars + 1 + self.n_scenarios
+    }
+
+    pub fn get_slack_columns(&self) -> Range<usize> {
+        let start = self.get_shortfall_columns().end;
+        start..start + self.n_scenarios
+    }
+
+    /// Variables of the original program within a solution of the reformulation.
+    pub fn get_original(&self, x: &Col<E>) -> Col<E> {
+        x.subrows(0, self.n_vars).to_owned()
+    }
+
+    /// CVaR at a solution of the reformulation, read from `eta` and the shortfalls.
+    pub fn get_cvar(&self, cvar: &ConditionalValueAtRisk, x: &Col<E>) -> E {
+        let shortfall = self.get_shortfall_columns();
+        let tail = cvar
+            .get_probabilities()
+            .iter()
+            .zip(shortfall)
+            .map(|(&p, j)| p * x[j])
+            .sum::<E>();
+        x[self.get_eta_column()] + tail / (E::from(1.) - cvar.get_alpha())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::col;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::{
+        lp::test::solve_silent,
+        stochastic::{NodeOverrides, StageData},
+    };
+
+    fn build_losses(values: &[E]) -> SparseColMat<I, E> {
+        let triplets = values
+            .iter()
+            .enumerate()
+            .map(|(s, &v)| Triplet::new(to_index(s), to_index(0), v))
+            .collect::<Vec<_>>();
+        SparseColMat::try_new_from_triplets(values.len(), 1, &triplets).unwrap()
+    }
+
+    #[rstest]
+    #[case(0., 2.5)]
+    #[case(0.5, 3.5)]
+    #[case(0.6, 3.625)]
+    #[case(0.75, 4.)]
+    fn test_evaluate(#[case] alpha: E, #[case] expected: E) {
+        let cvar = ConditionalValueAtRisk::new(
+            build_losses(&[3., 1., 4., 2.]),
+            col![0.25, 0.25, 0.25, 0.25],
+            alpha,
+        )
+        .unwrap();
+        assert!((cvar.evaluate(&col![1.]) - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let losses = build_losses(&[1., 2.]);
+        assert!(ConditionalValueAtRisk::new(losses.clone(), col![0.5, 0.5], 1.).is_err());
+        assert!(ConditionalValueAtRisk::new(losses.clone(), col![0.5, 0.6], 0.5).is_err());
+        assert!(ConditionalValueAtRisk::new(losses, col![1.], 0.5).is_err());
+    }
+
+    /// Splits a unit budget `x_0 + x_1 = 1` between a risky asset returning 0.2
+    /// or -0.1 with equal probability and a riskless asset returning nothing,
+    /// while maximizing the expected return.
+    fn build_portfolio() -> (LinearProgram, ConditionalValueAtRisk) {
+        let a = SparseColMat::try_new_from_triplets(
+            1,
+            2,
+            &[Triplet::new(0, 0, 1.), Triplet::new(0, 1, 1.)],
+        )
+        .unwrap();
+        let lp = LinearProgram::new(col![-0.05, 0.], a, col![1.], col![0., 0.], col![1., 1.]);
+
+        // Losses are the negated returns
+        let losses = SparseColMat::try_new_from_triplets(
+            2,
+            2,
+            &[Triplet::new(0, 0, -0.2), Triplet::new(1, 0, 0.1)],
+        )
+        .unwrap();
+        let cvar = ConditionalValueAtRisk::new(losses, col![0.5, 0.5], 0.5).unwrap();
+        (lp, cvar)
+    }
+
+    #[rstest]
+    #[case(0.25, 1.)]
+    #[case(1., 0.)]
+    fn test_objective(#[case] weight: E, #[case] risky: E) {
+        let (lp, cvar) = build_portfolio();
+        let reformulation = cvar.add_to_objective(&lp, weight).unwrap();
+        let x = solve_silent(reformulation.get_program());
+
+        // The worst case loses 0.1 per unit of the risky asset
+        let x_lp = reformulation.get_original(&x);
+        assert!((x_lp[0] - risky).abs() < 1e-5);
+        assert!((reformulation.get_cvar(&cvar, &x) - cvar.evaluate(&x_lp)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_constraint() {
+        let (lp, cvar) = build_portfolio();
+        let reformulation = cvar.add_constraint(&lp, 0.05).unwrap();
+        let x = solve_silent(reformulation.get_program());
+
+        let x_lp = reformulation.get_original(&x);
+        assert!((x_lp[0] - 0.5).abs() < 1e-5);
+        assert!(cvar.evaluate(&x_lp) <= 0.05 + 1e-6);
+    }
+
+    #[test]
+    fn test_scenario_tree() {
+        let empty = SparseColMat::try_new_from_triplets(0, 1, &[]).unwrap();
+        let mut tree = ScenarioTree::new(StageData::new(
+            col![1.],
+            empty,
+            Col::zeros(0),
+            col![0.],
+            col![10.],
+        ));
+        let w = SparseColMat::try_new_from_triplets(1, 1, &[Triplet::new(0, 0, 1.)]).unwrap();
+        tree.add_stage(StageData::new(col![-3.], w, col![0.], col![0.], col![10.]));
+        for (probability, price) in [(0.25, -1.), (0.75, -4.)] {
+            let overrides = NodeOverrides::new().with_objective(col![price]);
+            tree.add_node(0, probability, overrides).unwrap();
+        }
+
+        let cvar = ConditionalValueAtRisk::from_scenario_tree(&tree, 0.5).unwrap();
+        assert_eq!((cvar.get_n_scenarios(), cvar.get_n_vars()), (2, 3));
+
+        // Costs along the scenarios are 2 - 1 and 2 - 4
+        let x = col![2., 1., 1.];
+        assert_eq!(cvar.get_scenario_losses(&x), col![1., -2.]);
+        assert!((cvar.evaluate(&x) - (0.25 * 1. + 0.25 * -2.) / 0.5).abs() < 1e-12);
+    }
+}
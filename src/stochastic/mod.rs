@@ -1 +1,10 @@
-pub mod sgd;
\ No newline at end of file
+pub mod chance;
+pub mod cvar;
+pub mod scenario_tree;
+pub mod sddp;
+pub mod sgd;
+
+pub use chance::GaussianChanceConstraint;
+pub use cvar::{CVaRReformulation, ConditionalValueAtRisk};
+pub use scenario_tree::{NodeOverrides, ScenarioTree, StageData};
+pub use sddp::StochasticDualDynamicProgramming;
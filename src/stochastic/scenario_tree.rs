@@ -0,0 +1,537 @@
+//! Scenario trees for multistage stochastic linear programs.
+//!
+//! Every node `n` of the tree holds a stage problem in standard form, linked to
+//! the decisions of its parent `a(n)`:
+//!
+//! ```text
+//! min  c_n^T x_n
+//! s.t. T_n x_a(n) + W_n x_n = h_n
+//!      l_n <= x_n <= u_n
+//! ```
+//!
+//! The data is given once per stage by a [`StageData`] template, and individual
+//! nodes replace parts of it through [`NodeOverrides`], which is how scenario
+//! realizations of costs, right-hand sides or coefficients are described.
+
+use std::ops::Range;
+
+use faer::{
+    Col,
+    sparse::{SparseColMat, Triplet},
+    traits::IndexCore,
+};
+use problemo::{Problem, common::IntoCommonProblem};
+
+use crate::{E, I, lp::LinearProgram, to_index};
+
+/// Template data shared by the nodes of a stage.
+#[allow(non_snake_case)]
+#[derive(Clone, Debug)]
+pub struct StageData {
+    c: Col<E>,
+    /// Recourse matrix acting on the decisions of the node.
+    W: SparseColMat<I, E>,
+    /// Technology matrix acting on the decisions of the parent, absent in the first stage.
+    T: Option<SparseColMat<I, E>>,
+    h: Col<E>,
+    l: Col<E>,
+    u: Col<E>,
+}
+
+#[allow(non_snake_case)]
+impl StageData {
+    pub fn new(c: Col<E>, W: SparseColMat<I, E>, h: Col<E>, l: Col<E>, u: Col<E>) -> Self {
+        Self {
+            c,
+            W,
+            T: None,
+            h,
+            l,
+            u,
+        }
+    }
+
+    pub fn with_technology_matrix(mut self, T: SparseColMat<I, E>) -> Self {
+        self.T = Some(T);
+        self
+    }
+
+    /// Number of decisions of each node in the stage.
+    pub fn get_n_vars(&self) -> usize {
+        self.c.nrows()
+    }
+
+    /// Number of constraints of each node in the stage.
+    pub fn get_n_cons(&self) -> usize {
+        self.h.nrows()
+    }
+}
+
+/// Node data replacing the corresponding entries of its [`StageData`].
+#[allow(non_snake_case)]
+#[derive(Clone, Debug, Default)]
+pub struct NodeOverrides {
+    c: Option<Col<E>>,
+    W: Option<SparseColMat<I, E>>,
+    T: Option<SparseColMat<I, E>>,
+    h: Option<Col<E>>,
+    l: Option<Col<E>>,
+    u: Option<Col<E>>,
+}
+
+#[allow(non_snake_case)]
+impl NodeOverrides {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_objective(mut self, c: Col<E>) -> Self {
+        self.c = Some(c);
+        self
+    }
+
+    pub fn with_recourse_matrix(mut self, W: SparseColMat<I, E>) -> Self {
+        self.W = Some(W);
+        self
+    }
+
+    pub fn with_technology_matrix(mut self, T: SparseColMat<I, E>) -> Self {
+        self.T = Some(T);
+        self
+    }
+
+    pub fn with_rhs(mut self, h: Col<E>) -> Self {
+        self.h = Some(h);
+        self
+    }
+
+    pub fn with_lower_bounds(mut self, l: Col<E>) -> Self {
+        self.l = Some(l);
+        self
+    }
+
+    pub fn with_upper_bounds(mut self, u: Col<E>) -> Self {
+        self.u = Some(u);
+        self
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    stage: usize,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    /// Probability of the node conditional on its parent.
+    probability: E,
+    overrides: NodeOverrides,
+}
+
+/// A scenario tree whose root is node `0` in stage `0`.
+#[derive(Clone, Debug)]
+pub struct ScenarioTree {
+    stages: Vec<StageData>,
+    nodes: Vec<Node>,
+    /// Nodes of each stage, in the order they were added.
+    stage_nodes: Vec<Vec<usize>>,
+}
+
+#[allow(non_snake_case)]
+impl ScenarioTree {
+    /// Creates a tree consisting of the root node with the first-stage data.
+    pub fn new(root: StageData) -> Self {
+        Self {
+            stages: vec![root],
+            nodes: vec![Node {
+                stage: 0,
+                parent: None,
+                children: Vec::new(),
+                probability: E::from(1.),
+                overrides: NodeOverrides::default(),
+            }],
+            stage_nodes: vec![vec![0]],
+        }
+    }
+
+    /// Appends a stage and returns its index.
+    pub fn add_stage(&mut self, data: StageData) -> usize {
+        self.stages.push(data);
+        self.stage_nodes.push(Vec::new());
+        self.stages.len() - 1
+    }
+
+    /// Adds a child of `parent` in the next stage, reached with the given
+    /// conditional probability, and returns its index.
+    pub fn add_node(
+        &mut self,
+        parent: usize,
+        probability: E,
+        overrides: NodeOverrides,
+    ) -> Result<usize, Problem> {
+        let stage = self
+            .nodes
+            .get(parent)
+            .ok_or_else(|| format!("Parent node {parent} does not exist").gloss())?
+            .stage
+            + 1;
+        if stage >= self.stages.len() {
+            return Err(format!("Stage {stage} has not been added to the tree").gloss());
+        }
+        if !(E::from(0.)..=E::from(1.)).contains(&probability) {
+            return Err(format!("Invalid conditional probability {probability}").gloss());
+        }
+
+        let node = self.nodes.len();
+        self.nodes.push(Node {
+            stage,
+            parent: Some(parent),
+            children: Vec::new(),
+            probability,
+            overrides,
+        });
+        self.nodes[parent].children.push(node);
+        self.stage_nodes[stage].push(node);
+        Ok(node)
+    }
+
+    pub fn get_n_stages(&self) -> usize {
+        self.stages.len()
+    }
+
+    pub fn get_n_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn get_root(&self) -> usize {
+        0
+    }
+
+    pub fn get_stage_data(&self, stage: usize) -> &StageData {
+        &self.stages[stage]
+    }
+
+    pub fn get_stage(&self, node: usize) -> usize {
+        self.nodes[node].stage
+    }
+
+    pub fn get_parent(&self, node: usize) -> Option<usize> {
+        self.nodes[node].parent
+    }
+
+    pub fn get_children(&self, node: usize) -> &[usize] {
+        &self.nodes[node].children
+    }
+
+    /// Returns `true` if the node has no children.
+    pub fn is_leaf(&self, node: usize) -> bool {
+        self.nodes[node].children.is_empty()
+    }
+
+    /// Probability of reaching the node from its parent.
+    pub fn get_conditional_probability(&self, node: usize) -> E {
+        self.nodes[node].probability
+    }
+
+    /// Unconditional probability of the node, the product of the conditional
+    /// probabilities along its path from the root.
+    pub fn get_probability(&self, node: usize) -> E {
+        self.get_path(node)
+            .iter()
+            .map(|&n| self.nodes[n].probability)
+            .product()
+    }
+
+    /// Nodes from the root to `node`, inclusive.
+    pub fn get_path(&self, node: usize) -> Vec<usize> {
+        let mut path = vec![node];
+        while let Some(parent) = self.nodes[*path.last().unwrap()].parent {
+            path.push(parent);
+        }
+        path.reverse();
+        path
+    }
+
+    /// Nodes of a stage, in the order they were added.
+    pub fn get_stage_nodes(&self, stage: usize) -> &[usize] {
+        &self.stage_nodes[stage]
+    }
+
+    /// Iterates over the stages, yielding the stage index and its nodes.
+    pub fn iter_stages(&self) -> impl DoubleEndedIterator<Item = (usize, &[usize])> {
+        self.stage_nodes
+            .iter()
+            .enumerate()
+            .map(|(t, nodes)| (t, nodes.as_slice()))
+    }
+
+    /// The leaves of the tree, each identifying a scenario through its path.
+    pub fn get_scenarios(&self) -> Vec<usize> {
+        (0..self.nodes.len()).filter(|&n| self.is_leaf(n)).collect()
+    }
+
+    pub fn get_objective(&self, node: usize) -> &Col<E> {
+        let n = &self.nodes[node];
+        n.overrides.c.as_ref().unwrap_or(&self.stages[n.stage].c)
+    }
+
+    pub fn get_recourse_matrix(&self, node: usize) -> &SparseColMat<I, E> {
+        let n = &self.nodes[node];
+        n.overrides.W.as_ref().unwrap_or(&self.stages[n.stage].W)
+    }
+
+    /// Matrix coupling the node to its parent, `None` for the root.
+    pub fn get_technology_matrix(&self, node: usize) -> Option<&SparseColMat<I, E>> {
+        let n = &self.nodes[node];
+        n.parent?;
+        n.overrides.T.as_ref().or(self.stages[n.stage].T.as_ref())
+    }
+
+    pub fn get_rhs(&self, node: usize) -> &Col<E> {
+        let n = &self.nodes[node];
+        n.overrides.h.as_ref().unwrap_or(&self.stages[n.stage].h)
+    }
+
+    pub fn get_lower_bounds(&self, node: usize) -> &Col<E> {
+        let n = &self.nodes[node];
+        n.overrides.l.as_ref().unwrap_or(&self.stages[n.stage].l)
+    }
+
+    pub fn get_upper_bounds(&self, node: usize) -> &Col<E> {
+        let n = &self.nodes[node];
+        n.overrides.u.as_ref().unwrap_or(&self.stages[n.stage].u)
+    }
+
+    /// Checks that all leaves lie in the last stage, that the conditional
+    /// probabilities of the children of every node sum to one, and that the
+    /// dimensions of the node data agree.
+    pub fn validate(&self, tolerance: E) -> Result<(), Problem> {
+        let last_stage = self.stages.len() - 1;
+        for (node, n) in self.nodes.iter().enumerate() {
+            if n.children.is_empty() && n.stage != last_stage {
+                return Err(format!("Node {node} is a leaf before the last stage").gloss());
+            }
+            if !n.children.is_empty() {
+                let total = n
+                    .children
+                    .iter()
+                    .map(|&c| self.nodes[c].probability)
+                    .sum::<E>();
+                if (total - E::from(1.)).abs() > tolerance {
+                    return Err(
+                        format!("Children of node {node} have total probability {total}").gloss(),
+                    );
+                }
+            }
+
+            let (n_vars, n_cons) = (self.get_objective(node).nrows(), self.get_rhs(node).nrows());
+            let W = self.get_recourse_matrix(node);
+            let bounds_ok = self.get_lower_bounds(node).nrows() == n_vars
+                && self.get_upper_bounds(node).nrows() == n_vars;
+            if W.nrows() != n_cons || W.ncols() != n_vars || !bounds_ok {
+                return Err(format!("Data of node {node} has inconsistent dimensions").gloss());
+            }
+
+            if let Some(parent) = n.parent {
+                let n_parent = self.get_objective(parent).nrows();
+                match self.get_technology_matrix(node) {
+                    Some(T) if T.nrows() != n_cons || T.ncols() != n_parent => {
+                        return Err(format!(
+                            "Technology matrix of node {node} has inconsistent dimensions"
+                        )
+                        .gloss());
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Columns of the node's decisions in the deterministic equivalent.
+    pub fn get_node_columns(&self, node: usize) -> Range<usize> {
+        let start = (0..node).map(|n| self.get_objective(n).nrows()).sum();
+        start..start + self.get_objective(node).nrows()
+    }
+
+    /// Rows of the node's constraints in the deterministic equivalent.
+    pub fn get_node_rows(&self, node: usize) -> Range<usize> {
+        let start = (0..node).map(|n| self.get_rhs(n).nrows()).sum();
+        start..start + self.get_rhs(node).nrows()
+    }
+
+    /// Builds the deterministic equivalent, which stacks the decisions and
+    /// constraints of all nodes and weighs each objective by the node probability.
+    ///
+    /// Nodes appear in the order of their indices; [`ScenarioTree::get_node_columns`]
+    /// and [`ScenarioTree::get_node_rows`] locate them in the result.
+    pub fn deterministic_equivalent(&self) -> Result<LinearProgram, Problem> {
+        self.validate(E::from(1e-9))?;
+
+        let (mut col_offsets, mut row_offsets) = (vec![0], vec![0]);
+        for node in 0..self.nodes.len() {
+            col_offsets.push(col_offsets[node] + self.get_objective(node).nrows());
+            row_offsets.push(row_offsets[node] + self.get_rhs(node).nrows());
+        }
+        let (n_vars, n_cons) = (col_offsets[self.nodes.len()], row_offsets[self.nodes.len()]);
+
+        let mut c = Col::zeros(n_vars);
+        let mut b = Col::zeros(n_cons);
+        let mut l = Col::zeros(n_vars);
+        let mut u = Col::zeros(n_vars);
+        let mut triplets = Vec::new();
+
+        for node in 0..self.nodes.len() {
+            let (col, row) = (col_offsets[node], row_offsets[node]);
+            let probability = self.get_probability(node);
+
+            let n_vars = self.get_objective(node).nrows();
+            let n_cons = self.get_rhs(node).nrows();
+            c.subrows_mut(col, n_vars)
+                .copy_from(probability * self.get_objective(node));
+            b.subrows_mut(row, n_cons).copy_from(self.get_rhs(node));
+            l.subrows_mut(col, n_vars)
+                .copy_from(self.get_lower_bounds(node));
+            u.subrows_mut(col, n_vars)
+                .copy_from(self.get_upper_bounds(node));
+
+            push_block(&mut triplets, self.get_recourse_matrix(node), row, col);
+            if let (Some(T), Some(parent)) =
+                (self.get_technology_matrix(node), self.get_parent(node))
+            {
+                push_block(&mut triplets, T, row, col_offsets[parent]);
+            }
+        }
+
+        let A = SparseColMat::try_new_from_triplets(n_cons, n_vars, &triplets)
+            .map_err(|e| format!("Failed to assemble deterministic equivalent: {e:?}").gloss())?;
+        Ok(LinearProgram::new(c, A, b, l, u))
+    }
+}
+
+/// Appends the entries of `block` shifted to start at `(row, col)`.
+pub(crate) fn push_block(
+    triplets: &mut Vec<Triplet<I, I, E>>,
+    block: &SparseColMat<I, E>,
+    row: usize,
+    col: usize,
+) {
+    let col_ptr = block.symbolic().col_ptr();
+    let row_idx = block.symbolic().row_idx();
+    let values = block.val();
+    for j in 0..block.ncols() {
+        for k in col_ptr[j].zx()..col_ptr[j + 1].zx() {
+            triplets.push(Triplet::new(
+                to_index(row + row_idx[k].zx()),
+                to_index(col + j),
+                values[k],
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::col;
+
+    use super::*;
+    use crate::{
+        SolverHooks, SolverOptions, SolverState, Status, callback::ConvergenceOutput,
+        terminators::ConvergenceTerminator,
+    };
+
+    /// A newsvendor buys `x <= 10` units at cost 1 and sells `y <= d` of them at
+    /// price 3, with demand `d` equal to 1 or 3 with equal probability.
+    fn build_newsvendor() -> ScenarioTree {
+        let empty = SparseColMat::try_new_from_triplets(0, 1, &[]).unwrap();
+        let mut tree = ScenarioTree::new(StageData::new(
+            col![1.],
+            empty,
+            Col::zeros(0),
+            col![0.],
+            col![10.],
+        ));
+
+        // Sales and unsold units: -x + y + s = 0
+        let w = SparseColMat::try_new_from_triplets(
+            1,
+            2,
+            &[Triplet::new(0, 0, 1.), Triplet::new(0, 1, 1.)],
+        )
+        .unwrap();
+        let t = SparseColMat::try_new_from_triplets(1, 1, &[Triplet::new(0, 0, -1.)]).unwrap();
+        tree.add_stage(
+            StageData::new(
+                col![-3., 0.],
+                w,
+                col![0.],
+                col![0., 0.],
+                col![E::INFINITY, E::INFINITY],
+            )
+            .with_technology_matrix(t),
+        );
+
+        for demand in [1., 3.] {
+            let overrides = NodeOverrides::new().with_upper_bounds(col![demand, E::INFINITY]);
+            tree.add_node(0, 0.5, overrides).unwrap();
+        }
+        tree
+    }
+
+    #[test]
+    fn test_tree_structure() {
+        let mut tree = build_newsvendor();
+        let empty = SparseColMat::try_new_from_triplets(0, 1, &[]).unwrap();
+        tree.add_stage(StageData::new(
+            col![0.],
+            empty,
+            Col::zeros(0),
+            col![0.],
+            col![1.],
+        ));
+        let leaf = tree.add_node(2, 1., NodeOverrides::new()).unwrap();
+
+        assert_eq!(tree.get_n_stages(), 3);
+        assert_eq!(tree.get_path(leaf), vec![0, 2, 3]);
+        assert_eq!(tree.get_probability(leaf), 0.5);
+        assert_eq!(tree.get_upper_bounds(leaf)[0], 1.);
+        assert!(tree.get_technology_matrix(leaf).is_none());
+        assert_eq!(tree.get_upper_bounds(2)[0], 3.);
+        assert_eq!(
+            tree.iter_stages().map(|(_, n)| n.len()).collect::<Vec<_>>(),
+            vec![1, 2, 1]
+        );
+
+        // Node 1 ends before the last stage
+        assert!(tree.validate(1e-9).is_err());
+        tree.add_node(1, 1., NodeOverrides::new()).unwrap();
+        assert!(tree.validate(1e-9).is_ok());
+        assert_eq!(tree.get_scenarios(), vec![3, 4]);
+
+        assert!(tree.add_node(leaf, 1., NodeOverrides::new()).is_err());
+    }
+
+    #[test]
+    fn test_deterministic_equivalent() {
+        let tree = build_newsvendor();
+        let lp = tree.deterministic_equivalent().unwrap();
+
+        assert_eq!(lp.get_dims(), (5, 2));
+        assert_eq!(tree.get_node_columns(2), 3..5);
+        assert_eq!(tree.get_node_rows(2), 1..2);
+        assert_eq!(lp.get_objective(), &col![1., -1.5, 0., -1.5, 0.]);
+
+        let mut state =
+            SolverState::new(Col::zeros(5), Col::zeros(2), Col::zeros(5), Col::zeros(5));
+        let options = SolverOptions::new();
+        let mut properties = SolverHooks {
+            callback: Box::new(ConvergenceOutput::new(&options)),
+            terminator: Box::new(ConvergenceTerminator::new(&options)),
+        };
+        let mut solver = lp.solver_builder().with_options(options).build().unwrap();
+        let status = solver.solve(&mut state, &mut properties).unwrap();
+
+        // Buying for the high demand is optimal
+        assert_eq!(status, Status::Optimal);
+        assert!((lp.get_objective_value(&state.x) + 3.).abs() < 1e-6);
+        assert!((state.x[0] - 3.).abs() < 1e-6);
+    }
+}
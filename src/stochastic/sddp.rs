@@ -0,0 +1,634 @@
+//! Stochastic dual dynamic programming (SDDP) for multistage linear programs.
+//!
+//! The expected cost-to-go of every non-leaf node `n` of a [`ScenarioTree`] is
+//! approximated from below by a piecewise-linear function of its decisions,
+//!
+//! ```text
+//! Q_n(x_n) >= max_k  alpha_k + beta_k^T x_n,
+//! ```
+//!
+//! so that the node problem becomes the linear program
+//!
+//! ```text
+//! min  c_n^T x_n + theta
+//! s.t. W_n x_n = h_n - T_n x_a(n)
+//!      theta - beta_k^T x_n >= alpha_k    for every cut k
+//!      l_n <= x_n <= u_n
+//! ```
+//!
+//! Each iteration consists of
+//! 1. a **forward pass**, which samples `sddp_forward_passes` scenarios with
+//!    the tree probabilities and evaluates the current policy along them. The
+//!    optimal value of the root problem is a lower bound, and the mean cost of
+//!    the scenarios estimates the expected cost of the policy, an upper bound,
+//!    with a confidence interval at level `sddp_confidence_level`;
+//! 2. a **backward pass** over the sampled scenarios, where the children of
+//!    each visited node are solved at the decisions of the forward pass and their
+//!    duals are averaged into a new cut for the node;
+//! 3. an optional **cut selection**, which drops cuts that are not the highest at
+//!    any of the points where cuts of the node have been generated.
+//!
+//! As the upper bound is only estimated, the algorithm stops once the lower
+//! bound lies within the confidence interval of the upper bound, up to the
+//! relative tolerance `sddp_gap_tolerance`, and has changed by less than that
+//! tolerance since the previous iteration. A single scenario per forward pass
+//! gives no interval, so that only the second test applies. Stage problems are
+//! solved with the solver chosen by [`LinearProgram::solver_builder`].
+
+use faer::{
+    Col,
+    rand::{Rng, SeedableRng, rngs::StdRng},
+    sparse::{SparseColMat, Triplet},
+};
+use macros::{explicit_options, use_option};
+use problemo::{Problem, common::IntoCommonProblem};
+
+use crate::{
+    E, SolverHooks, SolverOptions, SolverState, Status,
+    callback::NoOpCallback,
+    lp::LinearProgram,
+    stochastic::{
+        chance::normal_quantile,
+        scenario_tree::{ScenarioTree, push_block},
+    },
+    terminators::ComplementarityTerminator,
+    to_index,
+    utils::random::SeedSequence,
+};
+
+/// A cut `theta >= intercept + gradient^T x` on the expected cost-to-go of a node.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cut {
+    intercept: E,
+    gradient: Col<E>,
+}
+
+impl Cut {
+    pub fn new(intercept: E, gradient: Col<E>) -> Self {
+        Self {
+            intercept,
+            gradient,
+        }
+    }
+
+    pub fn get_intercept(&self) -> E {
+        self.intercept
+    }
+
+    pub fn get_gradient(&self) -> &Col<E> {
+        &self.gradient
+    }
+
+    /// Value of the cut at `x`.
+    pub fn evaluate(&self, x: &Col<E>) -> E {
+        self.intercept + self.gradient.transpose() * x
+    }
+}
+
+/// Optimal solution of a node problem.
+struct NodeSolution {
+    /// Objective value, including the cost-to-go approximation.
+    value: E,
+    x: Col<E>,
+    /// Duals of the rows `W_n x_n = h_n - T_n x_a(n)`.
+    y: Col<E>,
+}
+
+/// SDDP solver operating on a [`ScenarioTree`].
+#[explicit_options(name = SolverOptions)]
+#[use_option(name = "sddp_max_iterations", type_ = usize, default = "100", description = "Maximum number of SDDP iterations.")]
+#[use_option(name = "sddp_forward_passes", type_ = usize, default = "10", description = "Number of scenarios sampled in the forward pass of each SDDP iteration, which estimate the upper bound and give the trial points of the backward pass.")]
+#[use_option(name = "sddp_confidence_level", type_ = E, default = "0.95", description = "Level of the confidence interval of the SDDP upper bound.")]
+#[use_option(name = "sddp_gap_tolerance", type_ = E, default = "1e-6", description = "Relative gap between the SDDP bounds at which the algorithm stops.")]
+#[use_option(name = "sddp_cut_selection", type_ = bool, default = "true", description = "Drop SDDP cuts dominated at every trial point of their node.")]
+#[use_option(name = "sddp_cost_to_go_bound", type_ = E, default = "-inf", description = "Lower bound on the cost-to-go of every node, needed when it cannot be derived from the variable bounds.")]
+#[use_option(name = "sddp_seed", type_ = u64, default = "0", description = "Offset of the seed for sampling the scenarios of the forward pass from the stream derived from random_seed.")]
+pub struct StochasticDualDynamicProgramming<'a> {
+    tree: &'a ScenarioTree,
+    rng: StdRng,
+
+    /// Lower bound on the cost-to-go variable of each node.
+    theta_bounds: Vec<E>,
+    cuts: Vec<Vec<Cut>>,
+    /// Decisions at which the cuts of each node were generated.
+    trial_points: Vec<Vec<Col<E>>>,
+    /// Decisions of the current policy at the nodes of the sampled scenarios.
+    solutions: Vec<Col<E>>,
+
+    nit: usize,
+    lower_bound: E,
+    previous_lower_bound: E,
+    upper_bound: E,
+    upper_bound_half_width: E,
+}
+
+impl<'a> StochasticDualDynamicProgramming<'a> {
+    /// Creates a solver for `tree`. Fails if the tree is invalid or if the
+    /// cost-to-go of some node has no finite lower bound.
+    pub fn new(tree: &'a ScenarioTree, options: &SolverOptions) -> Result<Self, Problem> {
+        tree.validate(E::from(1e-9))?;
+
+        let seeds = SeedSequence::new(options);
+        let options: StochasticDualDynamicProgrammingInternalOptions = options.into();
+        let theta_bounds = cost_to_go_bounds(tree, options.sddp_cost_to_go_bound)?;
+        let n_nodes = tree.get_n_nodes();
+
+        Ok(Self {
+            tree,
+            rng: StdRng::seed_from_u64(seeds.get_stream_seed("sddp") ^ options.sddp_seed),
+            theta_bounds,
+            cuts: vec![Vec::new(); n_nodes],
+            trial_points: vec![Vec::new(); n_nodes],
+            solutions: (0..n_nodes)
+                .map(|n| Col::zeros(tree.get_objective(n).nrows()))
+                .collect(),
+            nit: 0,
+            lower_bound: E::NEG_INFINITY,
+            previous_lower_bound: E::NEG_INFINITY,
+            upper_bound: E::INFINITY,
+            upper_bound_half_width: E::INFINITY,
+            options,
+        })
+    }
+
+    pub fn get_nit(&self) -> usize {
+        self.nit
+    }
+
+    /// Optimal value of the root problem with the current cuts.
+    pub fn get_lower_bound(&self) -> E {
+        self.lower_bound
+    }
+
+    /// Estimate of the expected cost of the current policy, the mean cost of the
+    /// scenarios of the last forward pass.
+    pub fn get_upper_bound(&self) -> E {
+        self.upper_bound
+    }
+
+    /// Confidence interval of the expected cost of the current policy, infinite
+    /// if the forward pass sampled a single scenario.
+    pub fn get_upper_bound_interval(&self) -> (E, E) {
+        (
+            self.upper_bound - self.upper_bound_half_width,
+            self.upper_bound + self.upper_bound_half_width,
+        )
+    }
+
+    /// Gap between the lower bound and the lower end of the confidence interval
+    /// of the upper bound, relative to the magnitude of the upper bound. It is
+    /// nonpositive once the lower bound lies within the interval.
+    pub fn get_relative_gap(&self) -> E {
+        (self.get_upper_bound_interval().0 - self.lower_bound)
+            / self.upper_bound.abs().max(E::from(1.))
+    }
+
+    fn is_converged(&self) -> bool {
+        let tolerance = self.options.sddp_gap_tolerance;
+        let change = (self.lower_bound - self.previous_lower_bound).abs();
+        self.get_relative_gap() <= tolerance
+            && change <= tolerance * self.lower_bound.abs().max(E::from(1.))
+    }
+
+    pub fn get_cuts(&self, node: usize) -> &[Cut] {
+        &self.cuts[node]
+    }
+
+    /// Decisions of the current policy at `node`.
+    pub fn get_solution(&self, node: usize) -> &Col<E> {
+        &self.solutions[node]
+    }
+
+    /// Runs SDDP iterations until the gap is closed or the iteration limit is reached.
+    pub fn solve(&mut self) -> Result<Status, Problem> {
+        loop {
+            let scenarios = self.forward_pass()?;
+            if self.is_converged() {
+                return Ok(Status::Optimal);
+            }
+            if self.nit >= self.options.sddp_max_iterations {
+                return Ok(Status::IterationLimit);
+            }
+
+            self.backward_pass(&scenarios)?;
+            if self.options.sddp_cut_selection {
+                self.select_cuts();
+            }
+            self.nit += 1;
+        }
+    }
+
+    /// Evaluates the current policy along sampled scenarios, given as the nodes
+    /// from the root to a leaf, and updates both bounds.
+    fn forward_pass(&mut self) -> Result<Vec<Vec<usize>>, Problem> {
+        let n_scenarios = self.options.sddp_forward_passes.max(1);
+        let mut scenarios = Vec::with_capacity(n_scenarios);
+        let mut costs = Vec::with_capacity(n_scenarios);
+        // The decisions of a node only depend on its ancestors, so nodes shared
+        // by several scenarios are solved once
+        let mut solved = vec![false; self.tree.get_n_nodes()];
+        self.previous_lower_bound = self.lower_bound;
+
+        for _ in 0..n_scenarios {
+            let mut scenario = Vec::with_capacity(self.tree.get_n_stages());
+            let mut cost = E::from(0.);
+            let mut node = self.tree.get_root();
+            loop {
+                if !solved[node] {
+                    let x_parent = self.tree.get_parent(node).map(|p| &self.solutions[p]);
+                    let solution = self.solve_node(node, x_parent)?;
+                    if node == self.tree.get_root() {
+                        self.lower_bound = solution.value;
+                    }
+                    self.solutions[node] = solution.x;
+                    solved[node] = true;
+                }
+                cost += self.tree.get_objective(node).transpose() * &self.solutions[node];
+                scenario.push(node);
+                if self.tree.is_leaf(node) {
+                    break;
+                }
+                node = self.sample_child(node);
+            }
+            scenarios.push(scenario);
+            costs.push(cost);
+        }
+
+        let n = costs.len() as E;
+        self.upper_bound = costs.iter().sum::<E>() / n;
+        self.upper_bound_half_width = if costs.len() > 1 {
+            let variance = costs
+                .iter()
+                .map(|cost| (cost - self.upper_bound).powi(2))
+                .sum::<E>()
+                / (n - E::from(1.));
+            let level = self.options.sddp_confidence_level;
+            normal_quantile((E::from(1.) + level) / E::from(2.)) * (variance / n).sqrt()
+        } else {
+            E::INFINITY
+        };
+        Ok(scenarios)
+    }
+
+    /// Adds a cut to every non-leaf node of the sampled scenarios, deepest first.
+    fn backward_pass(&mut self, scenarios: &[Vec<usize>]) -> Result<(), Problem> {
+        let n_stages = self.tree.get_n_stages();
+        let mut visited = vec![Vec::new(); n_stages];
+        for &node in scenarios.iter().flatten() {
+            if !self.tree.is_leaf(node) {
+                visited[self.tree.get_stage(node)].push(node);
+            }
+        }
+
+        for nodes in visited.iter_mut().rev() {
+            nodes.sort_unstable();
+            nodes.dedup();
+            for &node in nodes.iter() {
+                let cut = self.generate_cut(node)?;
+                self.cuts[node].push(cut);
+                self.trial_points[node].push(self.solutions[node].clone());
+            }
+        }
+        Ok(())
+    }
+
+    fn sample_child(&mut self, node: usize) -> usize {
+        let children = self.tree.get_children(node);
+        let mut u = self.rng.random::<E>();
+        for &child in children {
+            u -= self.tree.get_conditional_probability(child);
+            if u < E::from(0.) {
+                return child;
+            }
+        }
+        *children.last().unwrap()
+    }
+
+    /// Averages the optimal values and duals of the children of `node` into a cut
+    /// supporting the expected cost-to-go at the decisions of the current policy.
+    #[allow(non_snake_case)]
+    fn generate_cut(&self, node: usize) -> Result<Cut, Problem> {
+        let x_hat = &self.solutions[node];
+        let mut value = E::from(0.);
+        let mut gradient = Col::<E>::zeros(x_hat.nrows());
+
+        for &child in self.tree.get_children(node) {
+            let probability = self.tree.get_conditional_probability(child);
+            let solution = self.solve_node(child, Some(x_hat))?;
+
+            // The parent's decisions enter the right-hand side as h - T x
+            value += probability * solution.value;
+            if let Some(T) = self.tree.get_technology_matrix(child) {
+                gradient -= probability * (T.transpose() * &solution.y);
+            }
+        }
+
+        let intercept = value - gradient.transpose() * x_hat;
+        Ok(Cut::new(intercept, gradient))
+    }
+
+    /// Keeps the cuts that attain the maximum at one of the node's trial points.
+    fn select_cuts(&mut self) {
+        let tolerance = self
+            .options
+            .root
+            .get_option::<E>("tolerance")
+            .unwrap_or(1e-7);
+        for (cuts, points) in self.cuts.iter_mut().zip(self.trial_points.iter()) {
+            if cuts.len() < 2 {
+                continue;
+            }
+
+            let mut keep = vec![false; cuts.len()];
+            for x in points {
+                let values = cuts.iter().map(|cut| cut.evaluate(x)).collect::<Vec<_>>();
+                let best = values.iter().cloned().fold(E::NEG_INFINITY, E::max);
+                values
+                    .iter()
+                    .zip(keep.iter_mut())
+                    .filter(|(v, _)| best - **v <= tolerance * best.abs().max(E::from(1.)))
+                    .for_each(|(_, k)| *k = true);
+            }
+
+            let mut keep = keep.into_iter();
+            cuts.retain(|_| keep.next().unwrap());
+        }
+    }
+
+    /// Assembles the problem of `node` with its cuts, for the given parent decisions.
+    #[allow(non_snake_case)]
+    fn build_node_lp(&self, node: usize, x_parent: Option<&Col<E>>) -> LinearProgram {
+        let tree = self.tree;
+        let cuts = &self.cuts[node];
+        let (n_x, m) = (tree.get_objective(node).nrows(), tree.get_rhs(node).nrows());
+        // The cost-to-go variable follows the decisions, then one slack per cut
+        let n_theta = usize::from(!tree.is_leaf(node));
+        let (n_vars, n_cons) = (n_x + n_theta + cuts.len(), m + cuts.len());
+
+        let mut c = Col::zeros(n_vars);
+        let mut b = Col::zeros(n_cons);
+        let mut l = Col::zeros(n_vars);
+        let mut u = Col::from_fn(n_vars, |_| E::INFINITY);
+        c.subrows_mut(0, n_x).copy_from(tree.get_objective(node));
+        l.subrows_mut(0, n_x).copy_from(tree.get_lower_bounds(node));
+        u.subrows_mut(0, n_x).copy_from(tree.get_upper_bounds(node));
+
+        b.subrows_mut(0, m).copy_from(tree.get_rhs(node));
+        if let (Some(T), Some(x_parent)) = (tree.get_technology_matrix(node), x_parent) {
+            b.subrows_mut(0, m)
+                .copy_from(tree.get_rhs(node) - T * x_parent);
+        }
+
+        let mut triplets = Vec::new();
+        push_block(&mut triplets, tree.get_recourse_matrix(node), 0, 0);
+
+        if n_theta > 0 {
+            c[n_x] = E::from(1.);
+            l[n_x] = self.theta_bounds[node];
+        }
+        for (k, cut) in cuts.iter().enumerate() {
+            let row = m + k;
+            b[row] = cut.intercept;
+            triplets.push(Triplet::new(to_index(row), to_index(n_x), E::from(1.)));
+            triplets.push(Triplet::new(
+                to_index(row),
+                to_index(n_x + 1 + k),
+                E::from(-1.),
+            ));
+            for (j, &beta) in cut.gradient.iter().enumerate() {
+                if beta != E::from(0.) {
+                    triplets.push(Triplet::new(to_index(row), to_index(j), -beta));
+                }
+            }
+        }
+
+        let A = SparseColMat::try_new_from_triplets(n_cons, n_vars, &triplets).unwrap();
+        LinearProgram::new(c, A, b, l, u)
+    }
+
+    fn solve_node(&self, node: usize, x_parent: Option<&Col<E>>) -> Result<NodeSolution, Problem> {
+        let lp = self.build_node_lp(node, x_parent);
+        let mut state = SolverState::new_interior(
+            lp.get_lower_bounds(),
+            lp.get_upper_bounds(),
+            lp.get_n_cons(),
+        );
+        let mut hooks = SolverHooks {
+            callback: Box::new(NoOpCallback::new()),
+            terminator: Box::new(ComplementarityTerminator::new(&self.options.root)),
+        };
+
+        let mut solver = lp
+            .solver_builder()
+            .with_options(self.options.root.clone())
+            .build()?;
+        let status = solver.solve(&mut state, &mut hooks)?;
+        if status != Status::Optimal {
+            return Err(
+                format!("Problem of node {node} terminated with status {status:?}").gloss(),
+            );
+        }
+
+        let n_x = self.tree.get_objective(node).nrows();
+        let m = self.tree.get_rhs(node).nrows();
+        Ok(NodeSolution {
+            value: lp.get_objective_value(&state.x),
+            x: state.x.subrows(0, n_x).to_owned(),
+            y: state.y.subrows(0, m).to_owned(),
+        })
+    }
+}
+
+/// Derives a lower bound on the expected cost-to-go of every node from the
+/// variable bounds of its descendants, falling back to `default` where the
+/// bounds do not limit the objective.
+fn cost_to_go_bounds(tree: &ScenarioTree, default: E) -> Result<Vec<E>, Problem> {
+    let mut bounds = vec![E::from(0.); tree.get_n_nodes()];
+    for (_, nodes) in tree.iter_stages().rev() {
+        for &node in nodes {
+            if tree.is_leaf(node) {
+                continue;
+            }
+
+            let bound = tree
+                .get_children(node)
+                .iter()
+                .map(|&child| {
+                    let (c, l, u) = (
+                        tree.get_objective(child),
+                        tree.get_lower_bounds(child),
+                        tree.get_upper_bounds(child),
+                    );
+                    let stage_bound = c
+                        .iter()
+                        .zip(l.iter().zip(u.iter()))
+                        .map(|(&c_j, (&l_j, &u_j))| match c_j {
+                            c_j if c_j > E::from(0.) => c_j * l_j,
+                            c_j if c_j < E::from(0.) => c_j * u_j,
+                            _ => E::from(0.),
+                        })
+                        .sum::<E>();
+                    tree.get_conditional_probability(child) * (stage_bound + bounds[child])
+                })
+                .sum::<E>()
+                .max(default);
+
+            if !bound.is_finite() {
+                return Err(format!(
+                    "Cost-to-go of node {node} is unbounded below; set sddp_cost_to_go_bound"
+                )
+                .gloss());
+            }
+            bounds[node] = bound;
+        }
+    }
+    Ok(bounds)
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::col;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::I;
+    use crate::stochastic::{NodeOverrides, StageData};
+
+    fn build_matrix(
+        n_rows: usize,
+        n_cols: usize,
+        triplets: &[Triplet<I, I, E>],
+    ) -> SparseColMat<I, E> {
+        SparseColMat::try_new_from_triplets(n_rows, n_cols, triplets).unwrap()
+    }
+
+    /// Inventory over three stages: `x0 <= 10` units are bought at cost 1, then
+    /// each later stage sells `y_t <= d_t` units at price 3, and the second stage
+    /// may restock `b_1` units at cost 2. The demand is 1 or 3 with equal
+    /// probability, independently in both stages.
+    fn build_inventory() -> ScenarioTree {
+        let mut tree = ScenarioTree::new(StageData::new(
+            col![1.],
+            build_matrix(0, 1, &[]),
+            Col::zeros(0),
+            col![0.],
+            col![10.],
+        ));
+
+        // y_1 + s_1 - b_1 = x_0
+        tree.add_stage(
+            StageData::new(
+                col![-3., 0., 2.],
+                build_matrix(
+                    1,
+                    3,
+                    &[
+                        Triplet::new(0, 0, 1.),
+                        Triplet::new(0, 1, 1.),
+                        Triplet::new(0, 2, -1.),
+                    ],
+                ),
+                col![0.],
+                col![0., 0., 0.],
+                col![E::INFINITY, 10., 10.],
+            )
+            .with_technology_matrix(build_matrix(1, 1, &[Triplet::new(0, 0, -1.)])),
+        );
+        // y_2 + s_2 = s_1
+        tree.add_stage(
+            StageData::new(
+                col![-3., 0.],
+                build_matrix(1, 2, &[Triplet::new(0, 0, 1.), Triplet::new(0, 1, 1.)]),
+                col![0.],
+                col![0., 0.],
+                col![E::INFINITY, 10.],
+            )
+            .with_technology_matrix(build_matrix(1, 3, &[Triplet::new(0, 1, -1.)])),
+        );
+
+        for d_1 in [1., 3.] {
+            let overrides = NodeOverrides::new().with_upper_bounds(col![d_1, 10., 10.]);
+            let node = tree.add_node(0, 0.5, overrides).unwrap();
+            for d_2 in [1., 3.] {
+                let overrides = NodeOverrides::new().with_upper_bounds(col![d_2, 10.]);
+                tree.add_node(node, 0.5, overrides).unwrap();
+            }
+        }
+        tree
+    }
+
+    fn solve_deterministic_equivalent(tree: &ScenarioTree) -> E {
+        let lp = tree.deterministic_equivalent().unwrap();
+        let (n_vars, n_cons) = lp.get_dims();
+        let mut state = SolverState::new(
+            Col::from_fn(n_vars, |j| lp.get_upper_bounds()[j].min(E::from(10.)) / 2.),
+            Col::zeros(n_cons),
+            Col::ones(n_vars),
+            -Col::<E>::ones(n_vars),
+        );
+        let options = SolverOptions::new();
+        let mut hooks = SolverHooks {
+            callback: Box::new(NoOpCallback::new()),
+            terminator: Box::new(ComplementarityTerminator::new(&options)),
+        };
+        let mut solver = lp.solver_builder().with_options(options).build().unwrap();
+        assert_eq!(
+            solver.solve(&mut state, &mut hooks).unwrap(),
+            Status::Optimal
+        );
+        lp.get_objective_value(&state.x)
+    }
+
+    #[rstest]
+    fn test_sddp(
+        #[values(true, false)] cut_selection: bool,
+        #[values(1, 4, 16)] forward_passes: usize,
+    ) {
+        let tree = build_inventory();
+        let objective = solve_deterministic_equivalent(&tree);
+
+        let mut options = SolverOptions::new();
+        options
+            .set_option("sddp_cut_selection", cut_selection)
+            .unwrap();
+        options
+            .set_option("sddp_forward_passes", forward_passes)
+            .unwrap();
+        options.set_option("sddp_gap_tolerance", 1e-5).unwrap();
+
+        let mut sddp = StochasticDualDynamicProgramming::new(&tree, &options).unwrap();
+        let status = sddp.solve().unwrap();
+
+        assert_eq!(status, Status::Optimal);
+        assert!(sddp.get_nit() > 0);
+        assert!((sddp.get_lower_bound() - objective).abs() < 1e-4);
+
+        // The sampled costs of the optimal policy vary with the demand
+        let (low, high) = sddp.get_upper_bound_interval();
+        assert!(low <= sddp.get_lower_bound() + 1e-4);
+        if forward_passes > 1 {
+            assert!(high.is_finite() && high > low);
+            assert!(low - 1e-4 <= objective && objective <= high + 1e-4);
+        } else {
+            assert!(high.is_infinite());
+        }
+    }
+
+    #[test]
+    fn test_unbounded_cost_to_go() {
+        let mut tree = build_inventory();
+        tree.add_stage(StageData::new(
+            col![-1.],
+            build_matrix(0, 1, &[]),
+            Col::zeros(0),
+            col![0.],
+            col![E::INFINITY],
+        ));
+        for leaf in tree.get_stage_nodes(2).to_vec() {
+            tree.add_node(leaf, 1., NodeOverrides::new()).unwrap();
+        }
+
+        let options = SolverOptions::new();
+        assert!(StochasticDualDynamicProgramming::new(&tree, &options).is_err());
+    }
+}
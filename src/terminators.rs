@@ -2,32 +2,61 @@
 //!
 //! This module provides several implementations of the [`Terminator`] trait, including:
 //! - [`InterruptTerminator`]: Responds to Ctrl-C (SIGINT) or programmatic interrupts.
+//! - [`CancellationTerminator`]: Stops when its [`CancellationToken`] is cancelled.
 //! - [`TimeOutTerminator`]: Terminates after a specified time limit.
+//! - [`DivergenceTerminator`]: Stops on growing residuals or non-finite iterates.
+//! - [`RelativeConvergenceTerminator`]: Stops on residuals that are small
+//!   relative to the norms of the program data.
+//! - [`ProjectedGradientTerminator`]: Stops first-order solvers of
+//!   bound-constrained programs on a small projected gradient.
 //! - [`MultiTerminator`]: Combines multiple terminators.
 //!
 //! # Note
-//! [`InterruptTerminator`] installs a global signal handler and **can only be constructed once** per process. Attempting to create multiple instances will result in a panic.
+//! [`InterruptTerminator`] shares a single process-wide Ctrl-C handler, installed
+//! on first construction. Any number of instances can exist at the same time.
+//!
+//! Terminators observe the iterate through [`StateView`], so other crates can
+//! implement their own for any state that provides it.
 
-use std::sync::{Arc, atomic::AtomicBool};
+use std::{
+    collections::VecDeque,
+    sync::{
+        Arc, OnceLock,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::Duration,
+};
 
 use dyn_clone::DynClone;
-use enum_dispatch::enum_dispatch;
+use faer::{Col, ColRef};
 use macros::{explicit_options, use_option};
 
-use crate::{E, SolverOptions, SolverState, Status};
+use crate::{E, SolverOptions, SolverState, StateView, Status, TerminationInfo};
 
 /// Criterion for deciding when the solver should stop.
 ///
 /// Checked once per iteration. Returns `Some(Status)` to stop, or `None` to continue.
-#[enum_dispatch]
-pub trait Terminator: DynClone {
+/// Terminators are `Send` so that solves can run on worker threads.
+pub trait Terminator<S: StateView + ?Sized = SolverState>: DynClone + Send {
     /// Called once before the first iteration to reset any internal state (e.g. timers).
     fn init(&mut self, options: &SolverOptions);
 
     /// Returns `Some(status)` if the solver should stop, `None` otherwise.
-    fn terminate(&mut self, state: &SolverState) -> Option<Status>;
+    fn terminate(&mut self, state: &S) -> Option<Status>;
+
+    /// Describes why the last call to [`terminate`](Self::terminate) returned
+    /// `status`. By default, with the largest residual as the detail.
+    fn describe(&self, state: &S, status: Status) -> TerminationInfo {
+        TerminationInfo::new(
+            status,
+            format!("terminator stopped with status {status:?}"),
+            TerminationInfo::residual_of(state),
+        )
+    }
 }
 
+dyn_clone::clone_trait_object!(<S> Terminator<S> where S: StateView + ?Sized);
+
 /// A terminator that never triggers. The solver runs until the iteration limit.
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct NullTerminator {}
@@ -38,56 +67,176 @@ impl NullTerminator {
     }
 }
 
-impl Terminator for NullTerminator {
+impl<S: StateView + ?Sized> Terminator<S> for NullTerminator {
     fn init(&mut self, _options: &SolverOptions) {}
 
-    fn terminate(&mut self, _state: &SolverState) -> Option<Status> {
+    fn terminate(&mut self, _state: &S) -> Option<Status> {
         None
     }
 }
 
+/// Number of Ctrl-C events and broadcast interrupts since the process started.
+static INTERRUPT_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// Whether the process-wide Ctrl-C handler is installed. Installation fails if
+/// the application registered its own handler, in which case it can forward
+/// signals with [`InterruptTerminator::interrupt_all`].
+static SIGNAL_HANDLER: OnceLock<bool> = OnceLock::new();
+
+fn install_signal_handler() -> bool {
+    *SIGNAL_HANDLER.get_or_init(|| {
+        ctrlc::set_handler(|| {
+            INTERRUPT_COUNT.fetch_add(1, Ordering::SeqCst);
+        })
+        .is_ok()
+    })
+}
+
 /// Terminator that responds to Ctrl-C (SIGINT) or programmatic interrupts.
 ///
-/// # Note
-/// Only one instance of `InterruptTerminator` can be constructed per process, as it installs a global signal handler.
-/// Creating more than one will panic.
+/// An instance stops its solver on Ctrl-C events and calls to
+/// [`interrupt_all`](Self::interrupt_all) after its construction or last
+/// [`init`](Terminator::init), and on calls to [`interrupt`](Self::interrupt)
+/// on itself, its clones, or its [`InterruptHandle`]s.
 #[derive(Clone)]
 pub struct InterruptTerminator {
     interrupted: Arc<AtomicBool>,
+    /// Value of [`INTERRUPT_COUNT`] at construction or the last `init`.
+    seen: u64,
 }
 
 impl InterruptTerminator {
     pub fn new(_options: &SolverOptions) -> Self {
+        install_signal_handler();
         Self {
             interrupted: Arc::new(AtomicBool::new(false)),
+            seen: INTERRUPT_COUNT.load(Ordering::SeqCst),
         }
     }
 
+    /// Interrupts this terminator and its clones.
     pub fn interrupt(&mut self) {
-        self.interrupted
-            .store(true, std::sync::atomic::Ordering::SeqCst);
+        self.interrupted.store(true, Ordering::SeqCst);
+    }
+
+    /// Handle to interrupt this terminator from another thread.
+    pub fn get_handle(&self) -> InterruptHandle {
+        InterruptHandle {
+            interrupted: self.interrupted.clone(),
+        }
+    }
+
+    /// Interrupts every existing instance, as a Ctrl-C event does.
+    pub fn interrupt_all() {
+        INTERRUPT_COUNT.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Whether the process-wide Ctrl-C handler could be installed.
+    pub fn has_signal_handler() -> bool {
+        install_signal_handler()
+    }
+
+    pub fn is_interrupted(&self) -> bool {
+        self.interrupted.load(Ordering::SeqCst)
+            || INTERRUPT_COUNT.load(Ordering::SeqCst) > self.seen
     }
 }
 
-impl Terminator for InterruptTerminator {
+impl<S: StateView + ?Sized> Terminator<S> for InterruptTerminator {
     fn init(&mut self, _options: &SolverOptions) {
-        let interrupted = Arc::new(AtomicBool::new(false));
-        ctrlc::set_handler({
-            let interrupted_clone = interrupted.clone();
-            move || {
-                interrupted_clone.store(true, std::sync::atomic::Ordering::SeqCst);
-            }
-        })
-        .expect("Error setting Ctrl-C handler");
+        self.interrupted.store(false, Ordering::SeqCst);
+        self.seen = INTERRUPT_COUNT.load(Ordering::SeqCst);
     }
 
-    fn terminate(&mut self, _state: &SolverState) -> Option<Status> {
-        if self.interrupted.load(std::sync::atomic::Ordering::SeqCst) {
+    fn terminate(&mut self, _state: &S) -> Option<Status> {
+        if self.is_interrupted() {
             Some(Status::Interrupted)
         } else {
             None
         }
     }
+
+    /// The detail is the index of the iteration that was interrupted.
+    fn describe(&self, state: &S, status: Status) -> TerminationInfo {
+        TerminationInfo::new(status, "interrupted", state.get_nit() as E)
+    }
+}
+
+/// Thread-safe handle that interrupts an [`InterruptTerminator`].
+#[derive(Clone, Debug)]
+pub struct InterruptHandle {
+    interrupted: Arc<AtomicBool>,
+}
+
+impl InterruptHandle {
+    pub fn interrupt(&self) {
+        self.interrupted.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Thread-safe flag for cooperative cancellation of solves.
+///
+/// Clones share the flag, so a token can be cancelled from any thread while a
+/// solve observes it through a [`CancellationTerminator`]. Unlike an
+/// [`InterruptTerminator`], the token does not react to Ctrl-C and is not reset
+/// when a solve starts: a token cancelled beforehand stops the solve at its
+/// first iteration.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Clears the cancellation so that the token can be reused.
+    pub fn reset(&self) {
+        self.cancelled.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Terminator that stops with [`Status::Interrupted`] once its
+/// [`CancellationToken`] is cancelled.
+#[derive(Clone, Debug)]
+pub struct CancellationTerminator {
+    token: CancellationToken,
+}
+
+impl CancellationTerminator {
+    pub fn new(token: CancellationToken) -> Self {
+        Self { token }
+    }
+
+    pub fn get_token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+impl<S: StateView + ?Sized> Terminator<S> for CancellationTerminator {
+    fn init(&mut self, _options: &SolverOptions) {}
+
+    fn terminate(&mut self, _state: &S) -> Option<Status> {
+        if self.token.is_cancelled() {
+            Some(Status::Interrupted)
+        } else {
+            None
+        }
+    }
+
+    /// The detail is the index of the iteration that was cancelled.
+    fn describe(&self, state: &S, status: Status) -> TerminationInfo {
+        TerminationInfo::new(status, "cancelled", state.get_nit() as E)
+    }
 }
 
 /// Terminator that triggers after a specified number of seconds.
@@ -96,125 +245,564 @@ impl Terminator for InterruptTerminator {
 #[derive(Clone)]
 pub struct TimeOutTerminator {
     start_time: std::time::Instant,
+    /// Limit overriding `max_time`.
+    limit: Option<Duration>,
 }
 
 impl TimeOutTerminator {
     pub fn new(options: &SolverOptions) -> Self {
         Self {
             start_time: std::time::Instant::now(),
+            limit: None,
             options: options.into(),
         }
     }
+
+    /// Replaces the `max_time` option with `limit`, which need not be a whole
+    /// number of seconds.
+    pub fn with_limit(mut self, limit: Duration) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn get_limit(&self) -> Duration {
+        self.limit
+            .unwrap_or(Duration::from_secs(self.options.max_time))
+    }
 }
 
-impl Terminator for TimeOutTerminator {
+impl<S: StateView + ?Sized> Terminator<S> for TimeOutTerminator {
     fn init(&mut self, options: &SolverOptions) {
         self.start_time = std::time::Instant::now();
         self.options = options.into();
     }
 
-    fn terminate(&mut self, _state: &SolverState) -> Option<Status> {
-        if self.start_time.elapsed().as_secs() >= self.options.max_time {
+    fn terminate(&mut self, _state: &S) -> Option<Status> {
+        if self.start_time.elapsed() >= self.get_limit() {
             Some(Status::TimeLimit)
         } else {
             None
         }
     }
+
+    /// The detail is the elapsed time in seconds.
+    fn describe(&self, _state: &S, status: Status) -> TerminationInfo {
+        TerminationInfo::new(
+            status,
+            format!("reached the time limit of {:?}", self.get_limit()),
+            self.start_time.elapsed().as_secs_f64() as E,
+        )
+    }
+}
+
+/// Absolute tolerances of individual constraints and variables that override
+/// the global `tolerance`, e.g. for constraints in different units.
+///
+/// The tolerance of row `i` bounds the primal residual of constraint `i`, and
+/// the tolerance of variable `j` bounds the dual residual of variable `j`.
+/// Entries beyond the length of a vector, e.g. slack variables appended by the
+/// program, keep the global tolerance.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ToleranceOverrides {
+    rows: Option<Col<E>>,
+    variables: Option<Col<E>>,
+}
+
+impl ToleranceOverrides {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_rows(mut self, tolerances: Col<E>) -> Self {
+        self.rows = Some(tolerances);
+        self
+    }
+
+    pub fn with_variables(mut self, tolerances: Col<E>) -> Self {
+        self.variables = Some(tolerances);
+        self
+    }
+
+    pub fn get_rows(&self) -> Option<&Col<E>> {
+        self.rows.as_ref()
+    }
+
+    pub fn get_variables(&self) -> Option<&Col<E>> {
+        self.variables.as_ref()
+    }
+
+    /// Entries of `residual` whose magnitude exceeds their tolerance in
+    /// `tolerances`, or `tolerance` beyond its length.
+    pub(crate) fn violations(
+        residual: ColRef<'_, E>,
+        tolerances: &Col<E>,
+        tolerance: E,
+    ) -> Vec<usize> {
+        (0..residual.nrows())
+            .filter(|&i| {
+                let bound = if i < tolerances.nrows() {
+                    tolerances[i]
+                } else {
+                    tolerance
+                };
+                residual[i].abs() > bound
+            })
+            .collect()
+    }
 }
 
 /// Terminates when both primal and dual infeasibility fall below `tolerance`.
+///
+/// With [`ConvergenceTerminator::with_overrides`], the residuals of the rows
+/// or variables that have an override are checked entry by entry instead.
 #[explicit_options(name = SolverOptions)]
 #[use_option(name = "tolerance", type_ = E, default = "1e-7", description = "Tolerance for convergence-based termination")]
 #[derive(Clone)]
-pub struct ConvergenceTerminator {}
+pub struct ConvergenceTerminator {
+    overrides: ToleranceOverrides,
+}
 
 impl ConvergenceTerminator {
     pub fn new(options: &SolverOptions) -> Self {
         Self {
+            overrides: ToleranceOverrides::new(),
             options: options.into(),
         }
     }
+
+    pub fn with_overrides(mut self, overrides: ToleranceOverrides) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
+    pub fn get_overrides(&self) -> &ToleranceOverrides {
+        &self.overrides
+    }
 }
 
-impl Terminator for ConvergenceTerminator {
+impl<S: StateView + ?Sized> Terminator<S> for ConvergenceTerminator {
     fn init(&mut self, options: &SolverOptions) {
         self.options = options.into();
     }
 
-    fn terminate(&mut self, state: &SolverState) -> Option<Status> {
-        if state.get_primal_feasibility().norm_l2() <= self.options.tolerance * state.x.nrows() as E
-            && state.get_dual_feasibility().norm_l2()
-                <= self.options.tolerance * state.y.nrows() as E
+    fn terminate(&mut self, state: &S) -> Option<Status> {
+        let tolerance = self.options.tolerance;
+        let primal = match self.overrides.get_rows() {
+            Some(rows) => {
+                ToleranceOverrides::violations(state.get_primal_feasibility(), rows, tolerance)
+                    .is_empty()
+            }
+            None => {
+                state.get_primal_feasibility().norm_l2()
+                    <= tolerance * state.get_primal().nrows() as E
+            }
+        };
+        let dual = match self.overrides.get_variables() {
+            Some(variables) => {
+                ToleranceOverrides::violations(state.get_dual_feasibility(), variables, tolerance)
+                    .is_empty()
+            }
+            None => {
+                state.get_dual_feasibility().norm_l2() <= tolerance * state.get_dual().nrows() as E
+            }
+        };
+        (primal && dual).then_some(Status::Optimal)
+    }
+
+    /// The detail is the largest primal or dual residual.
+    fn describe(&self, state: &S, status: Status) -> TerminationInfo {
+        TerminationInfo::new(
+            status,
+            format!(
+                "primal and dual residuals within tolerance {}",
+                self.options.tolerance
+            ),
+            TerminationInfo::residual_of(state),
+        )
+    }
+}
+
+/// Terminates when the largest primal and dual residuals fall below
+/// `tolerance` relative to the data of the program, i.e.
+/// `‖r_p‖∞ <= tolerance (1 + ‖b‖∞)` and `‖r_d‖∞ <= tolerance (1 + ‖c‖∞)`.
+///
+/// Unlike [`ConvergenceTerminator`], the test is invariant to scaling the
+/// right-hand side or the objective. The norms are those of
+/// [`StateView::get_norms`]; without them the test is absolute.
+#[explicit_options(name = SolverOptions)]
+#[use_option(name = "tolerance", type_ = E, default = "1e-7", description = "Tolerance for convergence-based termination")]
+#[derive(Clone)]
+pub struct RelativeConvergenceTerminator {}
+
+impl RelativeConvergenceTerminator {
+    pub fn new(options: &SolverOptions) -> Self {
+        Self {
+            options: options.into(),
+        }
+    }
+
+    /// Primal and dual residual scales of `state`.
+    fn scales<S: StateView + ?Sized>(state: &S) -> (E, E) {
+        state
+            .get_norms()
+            .map_or((E::from(1.), E::from(1.)), |norms| {
+                (norms.get_primal_scale(), norms.get_dual_scale())
+            })
+    }
+}
+
+impl<S: StateView + ?Sized> Terminator<S> for RelativeConvergenceTerminator {
+    fn init(&mut self, options: &SolverOptions) {
+        self.options = options.into();
+    }
+
+    fn terminate(&mut self, state: &S) -> Option<Status> {
+        let tolerance = self.options.tolerance;
+        let (primal_scale, dual_scale) = Self::scales(state);
+        let primal = state.get_primal_feasibility().norm_max() <= tolerance * primal_scale;
+        let dual = state.get_dual_feasibility().norm_max() <= tolerance * dual_scale;
+        (primal && dual).then_some(Status::Optimal)
+    }
+
+    /// The detail is the larger of the relative primal and dual residuals.
+    fn describe(&self, state: &S, status: Status) -> TerminationInfo {
+        let (primal_scale, dual_scale) = Self::scales(state);
+        TerminationInfo::new(
+            status,
+            format!(
+                "relative primal and dual residuals within tolerance {}",
+                self.options.tolerance
+            ),
+            (state.get_primal_feasibility().norm_max() / primal_scale)
+                .max(state.get_dual_feasibility().norm_max() / dual_scale),
+        )
+    }
+}
+
+/// Terminates when the primal and dual infeasibility and the complementarity all
+/// fall below `tolerance` (scaled by the number of variables). Use it when the
+/// duals of the solution are needed, e.g. for cuts or sensitivities.
+#[explicit_options(name = SolverOptions)]
+#[use_option(name = "tolerance", type_ = E, default = "1e-7", description = "Tolerance for convergence-based termination")]
+#[derive(Clone)]
+pub struct ComplementarityTerminator {}
+
+impl ComplementarityTerminator {
+    pub fn new(options: &SolverOptions) -> Self {
+        Self {
+            options: options.into(),
+        }
+    }
+}
+
+impl<S: StateView + ?Sized> Terminator<S> for ComplementarityTerminator {
+    fn init(&mut self, options: &SolverOptions) {
+        self.options = options.into();
+    }
+
+    fn terminate(&mut self, state: &S) -> Option<Status> {
+        let tolerance = self.options.tolerance * state.get_primal().nrows().max(1) as E;
+        let complementarity = state.get_cs_lower().norm_l2() + state.get_cs_upper().norm_l2();
+        if state.get_primal_feasibility().norm_l2() <= tolerance
+            && state.get_dual_feasibility().norm_l2() <= tolerance
+            && complementarity <= tolerance
         {
             Some(Status::Optimal)
         } else {
             None
         }
     }
+
+    /// The detail is the complementarity.
+    fn describe(&self, state: &S, status: Status) -> TerminationInfo {
+        TerminationInfo::new(
+            status,
+            format!(
+                "residuals and complementarity within tolerance {}",
+                self.options.tolerance
+            ),
+            state.get_cs_lower().norm_l2() + state.get_cs_upper().norm_l2(),
+        )
+    }
 }
 
 #[explicit_options(name = SolverOptions)]
 #[use_option(name = "slow_progress_tolerance", type_ = E, default = "1e-8", description = "Tolerance for detecting slow progress in primal and dual infeasibility.")]
 #[derive(Clone)]
 pub struct SlowProgressTerminator {
-    prev_state: Option<SolverState>,
+    /// Primal and dual infeasibility of the previous iteration.
+    prev_feasibility: Option<(Col<E>, Col<E>)>,
+    /// Larger change of the primal and dual infeasibility in the last iteration.
+    change: E,
 }
 
 impl SlowProgressTerminator {
     pub fn new(options: &SolverOptions) -> Self {
         Self {
-            prev_state: None,
+            prev_feasibility: None,
+            change: E::INFINITY,
             options: options.into(),
         }
     }
 }
 
-impl Terminator for SlowProgressTerminator {
+impl<S: StateView + ?Sized> Terminator<S> for SlowProgressTerminator {
     fn init(&mut self, options: &SolverOptions) {
         self.options = options.into();
     }
 
-    fn terminate(&mut self, state: &SolverState) -> Option<Status> {
-        if let Some(prev) = &self.prev_state {
-            let primal_diff =
-                (state.get_primal_feasibility() - prev.get_primal_feasibility()).norm_l2();
-            let dual_diff = (state.get_dual_feasibility() - prev.get_dual_feasibility()).norm_l2();
+    fn terminate(&mut self, state: &S) -> Option<Status> {
+        let (primal, dual) = (state.get_primal_feasibility(), state.get_dual_feasibility());
+        if let Some((prev_primal, prev_dual)) = &self.prev_feasibility {
+            let primal_diff = (primal - prev_primal).norm_l2();
+            let dual_diff = (dual - prev_dual).norm_l2();
+            self.change = primal_diff.max(dual_diff);
             if primal_diff <= self.options.slow_progress_tolerance
                 && dual_diff <= self.options.slow_progress_tolerance
             {
                 return Some(Status::Optimal);
             }
         }
-        self.prev_state = Some(state.clone());
+        self.prev_feasibility = Some((primal.to_owned(), dual.to_owned()));
         None
     }
+
+    /// The detail is the change of the infeasibility in the last iteration.
+    fn describe(&self, _state: &S, status: Status) -> TerminationInfo {
+        TerminationInfo::new(
+            status,
+            format!(
+                "residuals changed by less than {}",
+                self.options.slow_progress_tolerance
+            ),
+            self.change,
+        )
+    }
 }
 
-#[enum_dispatch(Terminator)]
+/// Terminates when the projected gradient `‖x - P(x - ∇L)‖∞`, with `P` the
+/// projection onto the bounds given with
+/// [`ProjectedGradientTerminator::with_bounds`], falls below
+/// `projected_gradient_tolerance`.
+///
+/// The projected gradient vanishes exactly at the stationary points of a
+/// bound-constrained program, also where the gradient itself does not, unlike
+/// the dual residual of solvers that do not maintain bound multipliers, such
+/// as [`GradientDescent`](crate::nlp::gd::GradientDescent). It uses the
+/// gradient of [`StateView::get_gradient`] and never fires for states without
+/// one.
+#[explicit_options(name = SolverOptions)]
+#[use_option(name = "projected_gradient_tolerance", type_ = E, default = "1e-6", description = "Largest projected gradient entry at which ProjectedGradientTerminator stops.")]
 #[derive(Clone)]
-pub enum Terminators {
-    NullTerminator(NullTerminator),
-    InterruptTerminator(InterruptTerminator),
-    TimeOutTerminator(TimeOutTerminator),
-    ConvergenceTerminator(ConvergenceTerminator),
-    SlowProgressTerminator(SlowProgressTerminator),
+pub struct ProjectedGradientTerminator {
+    bounds: Option<(Col<E>, Col<E>)>,
+    /// Norm of the projected gradient in the last iteration.
+    norm: E,
+}
+
+impl ProjectedGradientTerminator {
+    pub fn new(options: &SolverOptions) -> Self {
+        Self {
+            bounds: None,
+            norm: E::INFINITY,
+            options: options.into(),
+        }
+    }
+
+    /// Projects onto the box `l <= x <= u`; without bounds, the projected
+    /// gradient is the gradient.
+    pub fn with_bounds(mut self, l: Col<E>, u: Col<E>) -> Self {
+        self.bounds = Some((l, u));
+        self
+    }
+
+    /// Norm of the projected gradient `x - P(x - g)`.
+    pub fn projected_gradient_norm(&self, x: ColRef<'_, E>, gradient: ColRef<'_, E>) -> E {
+        (0..x.nrows())
+            .map(|j| {
+                let step = x[j] - gradient[j];
+                let projected = match &self.bounds {
+                    Some((l, u)) => step.max(l[j]).min(u[j]),
+                    None => step,
+                };
+                (x[j] - projected).abs()
+            })
+            .fold(E::from(0.), E::max)
+    }
 }
 
+impl<S: StateView + ?Sized> Terminator<S> for ProjectedGradientTerminator {
+    fn init(&mut self, options: &SolverOptions) {
+        self.options = options.into();
+    }
+
+    fn terminate(&mut self, state: &S) -> Option<Status> {
+        let gradient = state.get_gradient()?;
+        self.norm = self.projected_gradient_norm(state.get_primal(), gradient);
+        (self.norm <= self.options.projected_gradient_tolerance).then_some(Status::Optimal)
+    }
+
+    /// The detail is the norm of the projected gradient.
+    fn describe(&self, _state: &S, status: Status) -> TerminationInfo {
+        TerminationInfo::new(
+            status,
+            format!(
+                "projected gradient within tolerance {}",
+                self.options.projected_gradient_tolerance
+            ),
+            self.norm,
+        )
+    }
+}
+
+/// Stops with [`Status::Diverged`] when the iterate or its residuals contain NaN
+/// or infinite values, or when the residual norm grows by more than
+/// `divergence_factor` over `divergence_window` iterations. Residual norms
+/// below `tolerance` count as `tolerance`, so that noise around a feasible
+/// point is not taken for growth.
+#[explicit_options(name = SolverOptions)]
+#[use_option(name = "divergence_factor", type_ = E, default = "1e6", description = "Growth of the residual norm over the divergence window that counts as divergence")]
+#[use_option(name = "divergence_window", type_ = usize, default = "5", description = "Number of iterations over which residual growth is measured")]
+#[use_option(name = "tolerance", type_ = E, default = "1e-7", description = "Tolerance for convergence-based termination")]
+#[derive(Clone)]
+pub struct DivergenceTerminator {
+    /// Residual norms of the last `divergence_window` iterations, oldest first.
+    history: VecDeque<E>,
+    /// Growth of the residual norm over the window, NaN for non-finite iterates.
+    growth: E,
+}
+
+impl DivergenceTerminator {
+    pub fn new(options: &SolverOptions) -> Self {
+        Self {
+            history: VecDeque::new(),
+            growth: E::from(0.),
+            options: options.into(),
+        }
+    }
+}
+
+impl<S: StateView + ?Sized> Terminator<S> for DivergenceTerminator {
+    fn init(&mut self, options: &SolverOptions) {
+        self.options = options.into();
+        self.history.clear();
+    }
+
+    fn terminate(&mut self, state: &S) -> Option<Status> {
+        let finite = [
+            state.get_primal(),
+            state.get_dual(),
+            state.get_lower_multipliers(),
+            state.get_upper_multipliers(),
+            state.get_primal_feasibility(),
+            state.get_dual_feasibility(),
+        ]
+        .iter()
+        .all(|v| v.iter().all(|x| x.is_finite()));
+        if !finite {
+            self.growth = E::NAN;
+            return Some(Status::Diverged);
+        }
+
+        let residual =
+            state.get_primal_feasibility().norm_l2() + state.get_dual_feasibility().norm_l2();
+        let window = self.options.divergence_window.max(1);
+        if self.history.len() == window {
+            let previous = self.history.pop_front().unwrap();
+            self.growth = residual / previous.max(self.options.tolerance);
+            if self.growth > self.options.divergence_factor {
+                return Some(Status::Diverged);
+            }
+        }
+        self.history.push_back(residual);
+        None
+    }
+
+    /// The detail is the growth of the residual norm over the window, or NaN
+    /// if the iterate is not finite.
+    fn describe(&self, _state: &S, status: Status) -> TerminationInfo {
+        let reason = if self.growth.is_nan() {
+            "non-finite iterate or residual".to_string()
+        } else {
+            format!(
+                "residual grew by more than {} over {} iterations",
+                self.options.divergence_factor,
+                self.options.divergence_window.max(1)
+            )
+        };
+        TerminationInfo::new(status, reason, self.growth)
+    }
+}
+
+/// Implements `From` for each variant and dispatches [`Terminator`] to it.
+macro_rules! dispatch_terminators {
+    ($($variant:ident),* $(,)?) => {
+        #[derive(Clone)]
+        pub enum Terminators {
+            $($variant($variant),)*
+        }
+
+        $(
+            impl From<$variant> for Terminators {
+                fn from(terminator: $variant) -> Self {
+                    Terminators::$variant(terminator)
+                }
+            }
+        )*
+
+        impl<S: StateView + ?Sized> Terminator<S> for Terminators {
+            fn init(&mut self, options: &SolverOptions) {
+                match self {
+                    $(Terminators::$variant(t) => Terminator::<S>::init(t, options),)*
+                }
+            }
+
+            fn terminate(&mut self, state: &S) -> Option<Status> {
+                match self {
+                    $(Terminators::$variant(t) => t.terminate(state),)*
+                }
+            }
+
+            fn describe(&self, state: &S, status: Status) -> TerminationInfo {
+                match self {
+                    $(Terminators::$variant(t) => t.describe(state, status),)*
+                }
+            }
+        }
+    };
+}
+
+dispatch_terminators!(
+    NullTerminator,
+    InterruptTerminator,
+    CancellationTerminator,
+    TimeOutTerminator,
+    ConvergenceTerminator,
+    RelativeConvergenceTerminator,
+    ComplementarityTerminator,
+    SlowProgressTerminator,
+    ProjectedGradientTerminator,
+    DivergenceTerminator,
+);
+
 /// Combines multiple terminators; stops on the first one that fires.
 #[derive(Clone)]
 pub struct MultiTerminator {
     terminators: Vec<Terminators>,
+    /// Index of the terminator that fired last.
+    fired: Option<usize>,
 }
 
 impl MultiTerminator {
     pub fn new(terminators: Vec<Terminators>) -> Self {
-        Self { terminators }
+        Self {
+            terminators,
+            fired: None,
+        }
     }
 
     pub fn new_empty() -> Self {
-        Self {
-            terminators: Vec::new(),
-        }
+        Self::new(Vec::new())
     }
 
     pub fn add_terminator(&mut self, terminator: Terminators) {
@@ -222,21 +810,74 @@ impl MultiTerminator {
     }
 }
 
-impl Terminator for MultiTerminator {
+impl<S: StateView + ?Sized> Terminator<S> for MultiTerminator {
     fn init(&mut self, options: &SolverOptions) {
+        self.fired = None;
         for terminator in &mut self.terminators {
-            terminator.init(options);
+            Terminator::<S>::init(terminator, options);
         }
     }
 
-    fn terminate(&mut self, state: &SolverState) -> Option<Status> {
-        for terminator in &mut self.terminators {
+    fn terminate(&mut self, state: &S) -> Option<Status> {
+        for (k, terminator) in self.terminators.iter_mut().enumerate() {
             if let Some(status) = terminator.terminate(state) {
+                self.fired = Some(k);
                 return Some(status);
             }
         }
         None
     }
+
+    /// Description of the terminator that fired.
+    fn describe(&self, state: &S, status: Status) -> TerminationInfo {
+        match self.fired {
+            Some(k) => self.terminators[k].describe(state, status),
+            None => TerminationInfo::new(status, "no terminator fired", E::NAN),
+        }
+    }
+}
+
+/// Stops on the first of two boxed terminators that fires.
+#[derive(Clone)]
+pub(crate) struct ChainedTerminator {
+    first: Box<dyn Terminator>,
+    second: Box<dyn Terminator>,
+    /// Whether the second terminator fired last.
+    second_fired: bool,
+}
+
+impl ChainedTerminator {
+    pub(crate) fn new(first: Box<dyn Terminator>, second: Box<dyn Terminator>) -> Self {
+        Self {
+            first,
+            second,
+            second_fired: false,
+        }
+    }
+}
+
+impl Terminator for ChainedTerminator {
+    fn init(&mut self, options: &SolverOptions) {
+        self.first.init(options);
+        self.second.init(options);
+    }
+
+    fn terminate(&mut self, state: &SolverState) -> Option<Status> {
+        self.second_fired = false;
+        self.first.terminate(state).or_else(|| {
+            let status = self.second.terminate(state);
+            self.second_fired = status.is_some();
+            status
+        })
+    }
+
+    fn describe(&self, state: &SolverState, status: Status) -> TerminationInfo {
+        if self.second_fired {
+            self.second.describe(state, status)
+        } else {
+            self.first.describe(state, status)
+        }
+    }
 }
 
 #[allow(unused)]
@@ -272,6 +913,7 @@ impl Builder {
 #[cfg(test)]
 mod tests {
     use faer::col::generic::Col;
+    use rstest::rstest;
 
     use super::*;
 
@@ -317,4 +959,302 @@ mod tests {
             }
         }
     }
+
+    /// A state owned by another crate, exposing only what the hooks need.
+    struct ExternalState {
+        x: faer::Col<E>,
+        residual: faer::Col<E>,
+        nit: usize,
+    }
+
+    impl StateView for ExternalState {
+        fn get_status(&self) -> Status {
+            Status::InProgress
+        }
+
+        fn get_nit(&self) -> usize {
+            self.nit
+        }
+
+        fn get_primal(&self) -> faer::ColRef<'_, E> {
+            self.x.as_ref()
+        }
+
+        fn get_dual(&self) -> faer::ColRef<'_, E> {
+            self.x.as_ref()
+        }
+
+        fn get_lower_multipliers(&self) -> faer::ColRef<'_, E> {
+            self.x.as_ref()
+        }
+
+        fn get_upper_multipliers(&self) -> faer::ColRef<'_, E> {
+            self.x.as_ref()
+        }
+
+        fn get_primal_feasibility(&self) -> faer::ColRef<'_, E> {
+            self.residual.as_ref()
+        }
+
+        fn get_dual_feasibility(&self) -> faer::ColRef<'_, E> {
+            self.residual.as_ref()
+        }
+
+        fn get_cs_lower(&self) -> faer::ColRef<'_, E> {
+            self.residual.as_ref()
+        }
+
+        fn get_cs_upper(&self) -> faer::ColRef<'_, E> {
+            self.residual.as_ref()
+        }
+
+        fn get_alpha_primal(&self) -> E {
+            1.
+        }
+
+        fn get_alpha_dual(&self) -> E {
+            1.
+        }
+    }
+
+    /// Stops after a fixed number of iterations, using only the view.
+    #[derive(Clone)]
+    struct IterationTerminator(usize);
+
+    impl<S: StateView + ?Sized> Terminator<S> for IterationTerminator {
+        fn init(&mut self, _options: &SolverOptions) {}
+
+        fn terminate(&mut self, state: &S) -> Option<Status> {
+            (state.get_nit() >= self.0).then_some(Status::IterationLimit)
+        }
+    }
+
+    #[test]
+    fn test_external_state() {
+        let options = SolverOptions::new();
+        let mut state = ExternalState {
+            x: faer::col![1., 2.],
+            residual: faer::col![1., 0.],
+            nit: 0,
+        };
+
+        let mut terminator: Box<dyn Terminator<ExternalState>> =
+            Box::new(ComplementarityTerminator::new(&options));
+        assert_eq!(terminator.terminate(&state), None);
+        state.residual = faer::col![1e-9, 0.];
+        assert_eq!(terminator.terminate(&state), Some(Status::Optimal));
+
+        let mut multi = MultiTerminator::new(vec![NullTerminator::new(&options).into()]);
+        Terminator::<ExternalState>::init(&mut multi, &options);
+        assert_eq!(multi.terminate(&state), None);
+
+        // Third-party terminators also drive the solvers of this crate
+        let mut custom = IterationTerminator(3);
+        state.nit = 3;
+        assert_eq!(custom.terminate(&state), Some(Status::IterationLimit));
+        let solver_state =
+            SolverState::new(Col::zeros(1), Col::zeros(0), Col::zeros(1), Col::zeros(1));
+        let mut boxed: Box<dyn Terminator> = Box::new(custom);
+        assert_eq!(boxed.terminate(&solver_state), None);
+    }
+
+    fn residual_state(residual: E) -> SolverState {
+        let mut state = SolverState::new(
+            faer::col![1., 1.],
+            Col::zeros(1),
+            Col::zeros(2),
+            Col::zeros(2),
+        );
+        state.primal_feasibility = faer::col![residual];
+        state
+    }
+
+    #[rstest]
+    #[case(vec![1., 2., 4., 8., 16., 32.], None)]
+    #[case(vec![1., 10., 100., 1000.], Some(3))]
+    #[case(vec![0., 0., 0., 1e-4], Some(3))]
+    #[case(vec![0., 0., 0., 1e-8], None)]
+    #[case(vec![1., 1., E::NAN], Some(2))]
+    fn test_divergence(#[case] residuals: Vec<E>, #[case] diverged_at: Option<usize>) {
+        let mut options = SolverOptions::new();
+        options.set_option("divergence_factor", 100.).unwrap();
+        options.set_option("divergence_window", 3usize).unwrap();
+        let mut terminator = DivergenceTerminator::new(&options);
+        Terminator::<SolverState>::init(&mut terminator, &options);
+
+        let stopped = residuals
+            .iter()
+            .position(|&r| terminator.terminate(&residual_state(r)).is_some());
+        assert_eq!(stopped, diverged_at);
+    }
+
+    #[test]
+    fn test_divergence_nonfinite_iterate() {
+        let options = SolverOptions::new();
+        let mut terminator = DivergenceTerminator::new(&options);
+        let mut state = residual_state(1.);
+        assert_eq!(terminator.terminate(&state), None);
+        state.x[1] = E::INFINITY;
+        assert_eq!(terminator.terminate(&state), Some(Status::Diverged));
+    }
+
+    #[test]
+    fn test_describe() {
+        let mut options = SolverOptions::new();
+        options.set_option("divergence_window", 1usize).unwrap();
+        let mut terminator = MultiTerminator::new(vec![
+            ConvergenceTerminator::new(&options).into(),
+            DivergenceTerminator::new(&options).into(),
+        ]);
+        Terminator::<SolverState>::init(&mut terminator, &options);
+
+        assert_eq!(terminator.terminate(&residual_state(1.)), None);
+        let state = residual_state(1e7);
+        let status = terminator.terminate(&state).unwrap();
+        let info = terminator.describe(&state, status);
+        assert_eq!(info.get_status(), Status::Diverged);
+        assert!(info.get_reason().starts_with("residual grew"));
+        assert_eq!(info.get_detail(), 1e7);
+
+        let state = residual_state(1e-9);
+        let status = terminator.terminate(&state).unwrap();
+        let info = terminator.describe(&state, status);
+        assert_eq!(info.get_status(), Status::Optimal);
+        assert_eq!(info.get_detail(), 1e-9);
+    }
+
+    #[test]
+    fn test_tolerance_overrides() {
+        let options = SolverOptions::new();
+        let mut state = SolverState::new(
+            faer::col![1., 1.],
+            Col::zeros(2),
+            Col::zeros(2),
+            Col::zeros(2),
+        );
+        state.primal_feasibility = faer::col![1e-3, 1e-9];
+        let mut terminator = ConvergenceTerminator::new(&options);
+        assert_eq!(terminator.terminate(&state), None);
+
+        // A loose first row, while the second row keeps the global tolerance
+        let overrides = ToleranceOverrides::new().with_rows(faer::col![1e-2]);
+        let mut terminator = ConvergenceTerminator::new(&options).with_overrides(overrides);
+        assert_eq!(terminator.terminate(&state), Some(Status::Optimal));
+        state.primal_feasibility[1] = 1e-6;
+        assert_eq!(terminator.terminate(&state), None);
+
+        // A tight variable
+        state.primal_feasibility[1] = 0.;
+        state.dual_feasibility = faer::col![1e-9, 0.];
+        let overrides = terminator
+            .get_overrides()
+            .clone()
+            .with_variables(faer::col![1e-10, 1e-10]);
+        let mut terminator = terminator.with_overrides(overrides);
+        assert_eq!(terminator.terminate(&state), None);
+    }
+
+    #[test]
+    fn test_relative_convergence() {
+        let options = SolverOptions::new();
+        let mut terminator = RelativeConvergenceTerminator::new(&options);
+        let mut state = residual_state(1e-3);
+        assert_eq!(terminator.terminate(&state), None);
+
+        // The same residual is small relative to a right-hand side of 1e5
+        state.norms = Some(crate::ProblemNorms::new(1., 1e5, 1., 0.));
+        let status = terminator.terminate(&state).unwrap();
+        assert_eq!(status, Status::Optimal);
+        let info = terminator.describe(&state, status);
+        assert!((info.get_detail() - 1e-3 / (1. + 1e5)).abs() < 1e-15);
+
+        state.dual_feasibility = faer::col![1e-3, 0.];
+        assert_eq!(terminator.terminate(&state), None);
+    }
+
+    #[test]
+    fn test_projected_gradient() {
+        let options = SolverOptions::new();
+        let mut terminator = ProjectedGradientTerminator::new(&options)
+            .with_bounds(faer::col![0., 0.], faer::col![1., E::INFINITY]);
+        let mut state = residual_state(0.);
+        assert_eq!(terminator.terminate(&state), None);
+
+        // x_0 at its upper bound with a gradient pushing it outwards
+        state.dL = Some(faer::col![-5., 1e-8]);
+        let status = terminator.terminate(&state).unwrap();
+        assert_eq!(status, Status::Optimal);
+        assert!((terminator.describe(&state, status).get_detail() - 1e-8).abs() < 1e-15);
+
+        state.dL = Some(faer::col![5., 1e-8]);
+        assert_eq!(terminator.terminate(&state), None);
+        let unbounded = ProjectedGradientTerminator::new(&options);
+        assert_eq!(
+            unbounded.projected_gradient_norm(state.x.as_ref(), faer::col![-5., 0.].as_ref()),
+            5.
+        );
+    }
+
+    #[test]
+    fn test_time_limit() {
+        let options = SolverOptions::new();
+        let state = residual_state(1.);
+
+        let mut terminator = TimeOutTerminator::new(&options);
+        assert_eq!(terminator.get_limit(), Duration::from_secs(3600));
+        assert_eq!(terminator.terminate(&state), None);
+
+        let mut terminator = terminator.with_limit(Duration::ZERO);
+        assert_eq!(terminator.terminate(&state), Some(Status::TimeLimit));
+
+        // The override survives re-initialization from the options
+        Terminator::<SolverState>::init(&mut terminator, &options);
+        assert_eq!(terminator.get_limit(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_interrupt_instances() {
+        let options = SolverOptions::new();
+        let state = residual_state(1.);
+        let mut first = InterruptTerminator::new(&options);
+        let mut second = InterruptTerminator::new(&options);
+        assert_eq!(first.terminate(&state), None);
+
+        // Interrupts from another thread only reach the handle's terminator
+        let handle = first.get_handle();
+        std::thread::spawn(move || handle.interrupt())
+            .join()
+            .unwrap();
+        assert_eq!(first.terminate(&state), Some(Status::Interrupted));
+        assert_eq!(second.terminate(&state), None);
+
+        // Broadcasts reach existing instances but not later ones
+        InterruptTerminator::interrupt_all();
+        assert_eq!(second.terminate(&state), Some(Status::Interrupted));
+        let mut third = InterruptTerminator::new(&options);
+        assert_eq!(third.terminate(&state), None);
+
+        Terminator::<SolverState>::init(&mut first, &options);
+        assert_eq!(first.terminate(&state), None);
+    }
+
+    #[test]
+    fn test_cancellation_token() {
+        let options = SolverOptions::new();
+        let state = residual_state(1.);
+        let token = CancellationToken::new();
+        let mut terminator = CancellationTerminator::new(token.clone());
+        assert_eq!(terminator.terminate(&state), None);
+
+        let remote = token.clone();
+        std::thread::spawn(move || remote.cancel()).join().unwrap();
+        assert!(terminator.get_token().is_cancelled());
+        assert_eq!(terminator.terminate(&state), Some(Status::Interrupted));
+
+        // Cancellation survives the start of a new solve until the token is reset
+        Terminator::<SolverState>::init(&mut terminator, &options);
+        assert_eq!(terminator.terminate(&state), Some(Status::Interrupted));
+        token.reset();
+        assert_eq!(terminator.terminate(&state), None);
+    }
 }
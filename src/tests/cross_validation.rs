@@ -0,0 +1,146 @@
+//! Cross-validation of the MPS and SIF pipelines on netlib instances.
+//!
+//! Each case is loaded twice: from the EMPS file of netlib.org, expanded to
+//! MPS and read with [`ReadSIF`], and from the SIF collection through
+//! [`TryFromSIF`]. Both programs are brought to a canonical order of rows and
+//! columns before they are compared entry by entry and solved.
+//!
+//! [`TryFromSIF`] does not see `RANGES`, so the cases have no ranged rows.
+//!
+//! [`ReadSIF`]: crate::interface::sif::ReadSIF
+
+use faer::{
+    Col,
+    sparse::{SparseColMat, Triplet},
+};
+use rstest::rstest;
+
+use crate::{
+    E, SolverHooks, SolverOptions, SolverState, Status,
+    callback::NoOpCallback,
+    data_loaders,
+    interface::sif::{SifTransformation, TryFromSIF},
+    lp::{LPSolverType, LinearProgram},
+    terminators::ComplementarityTerminator,
+    to_index,
+};
+
+/// Orders the rows and the variables of `lp` by name and its slack columns by
+/// the new order of their rows.
+fn normalize(lp: &LinearProgram, transformation: &SifTransformation) -> LinearProgram {
+    let sorted = |names: &[String]| {
+        let mut order = (0..names.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&k| &names[k]);
+        let mut position = vec![0; names.len()];
+        for (new, old) in order.into_iter().enumerate() {
+            position[old] = new;
+        }
+        position
+    };
+    let row_position = sorted(transformation.get_row_names());
+    let mut col_position = sorted(transformation.get_col_names());
+
+    let n = col_position.len();
+    let mut slacks = (0..transformation.get_slacks().len()).collect::<Vec<_>>();
+    slacks.sort_by_key(|&k| row_position[transformation.get_slacks()[k].get_row()]);
+    col_position.resize(n + slacks.len(), 0);
+    for (new, old) in slacks.into_iter().enumerate() {
+        col_position[n + old] = n + new;
+    }
+
+    let a = lp.get_constraint_matrix();
+    let mut triplets = Vec::with_capacity(a.compute_nnz());
+    for (j, &col) in col_position.iter().enumerate() {
+        for (i, &val) in a.row_idx_of_col(j).zip(a.val_of_col(j)) {
+            triplets.push(Triplet::new(to_index(row_position[i]), to_index(col), val));
+        }
+    }
+    let permute = |values: &Col<E>, position: &[usize]| {
+        let mut permuted = Col::zeros(values.nrows());
+        for (old, &new) in position.iter().enumerate() {
+            permuted[new] = values[old];
+        }
+        permuted
+    };
+
+    LinearProgram::new(
+        permute(lp.get_objective(), &col_position),
+        SparseColMat::try_new_from_triplets(a.nrows(), a.ncols(), &triplets).unwrap(),
+        permute(lp.get_rhs(), &row_position),
+        permute(lp.get_lower_bounds(), &col_position),
+        permute(lp.get_upper_bounds(), &col_position),
+    )
+}
+
+fn solve(lp: &LinearProgram) -> E {
+    let mut state = SolverState::new_interior(
+        lp.get_lower_bounds(),
+        lp.get_upper_bounds(),
+        lp.get_n_cons(),
+    );
+    let options = SolverOptions::new();
+    let mut hooks = SolverHooks {
+        callback: Box::new(NoOpCallback::new()),
+        terminator: Box::new(ComplementarityTerminator::new(&options)),
+    };
+    let status = lp
+        .solver_builder()
+        .with_solver(LPSolverType::MpcSimplicialCholesky)
+        .build()
+        .unwrap()
+        .solve(&mut state, &mut hooks)
+        .unwrap();
+    assert_eq!(status, Status::Optimal);
+    lp.get_objective_value(state.get_primal())
+}
+
+/// Loads `case_name` through both pipelines and asserts that they produce the
+/// same program and optimal objective.
+fn cross_validate(case_name: &str) {
+    let (mps, mps_transformation) =
+        data_loaders::mps::netlib::read_case_with_transformation::<LinearProgram>(case_name)
+            .unwrap();
+    let (sif, sif_transformation) = LinearProgram::try_from_sif_with_transformation(
+        &data_loaders::sif::netlib::get_case(case_name).unwrap(),
+    )
+    .unwrap();
+
+    let sorted_names = |transformation: &SifTransformation| {
+        let mut cols = transformation.get_col_names().to_vec();
+        let mut rows = transformation.get_row_names().to_vec();
+        cols.sort();
+        rows.sort();
+        (cols, rows)
+    };
+    assert_eq!(
+        sorted_names(&mps_transformation),
+        sorted_names(&sif_transformation)
+    );
+    assert_eq!(
+        mps_transformation.get_objective_offset(),
+        sif_transformation.get_objective_offset()
+    );
+
+    let mps = normalize(&mps, &mps_transformation);
+    let sif = normalize(&sif, &sif_transformation);
+    assert_eq!(mps.get_dims(), sif.get_dims());
+    assert_eq!(
+        mps.get_constraint_matrix().compute_nnz(),
+        sif.get_constraint_matrix().compute_nnz()
+    );
+    let diff = mps.diff(&sif, 1e-12);
+    assert!(diff.is_empty(), "{case_name}:\n{diff}");
+
+    let (mps_objective, sif_objective) = (solve(&mps), solve(&sif));
+    assert!(
+        (mps_objective - sif_objective).abs() <= 1e-6 * sif_objective.abs().max(1.),
+        "{case_name}: {mps_objective} != {sif_objective}"
+    );
+}
+
+#[rstest]
+fn mps_and_sif_agree(
+    #[values("afiro", "scsd1", "scsd6", "scsd8", "sctap1", "sctap2", "sctap3")] case_name: &str,
+) {
+    cross_validate(case_name);
+}
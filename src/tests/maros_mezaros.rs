@@ -8,7 +8,6 @@ use crate::{
     E, SolverHooks, SolverOptions, SolverState,
     callback::ConvergenceOutput,
     data_loaders,
-    interface::sif::TryFromSIF,
     qp::{QPSolverType, QuadraticProgram},
     terminators::ConvergenceTerminator,
 };
@@ -177,10 +176,7 @@ pub fn maros_mezaros_cases(
 
 #[apply(maros_mezaros_cases)]
 fn qp(_download_cases: &(), case_name: &str, solver_type: QPSolverType) {
-    let qp = QuadraticProgram::try_from_sif(
-        &data_loaders::sif::maros_mezaros::get_case(case_name).unwrap(),
-    )
-    .unwrap();
+    let qp = data_loaders::sif::maros_mezaros::read_case::<QuadraticProgram>(case_name).unwrap();
 
     let mut state = SolverState::new(
         Col::ones(qp.get_n_vars()),
@@ -210,7 +206,7 @@ fn qp(_download_cases: &(), case_name: &str, solver_type: QPSolverType) {
     let options = SolverOptions::new();
 
     let mut properties = SolverHooks {
-        callback: Box::new(ConvergenceOutput::new()),
+        callback: Box::new(ConvergenceOutput::new(&options)),
         terminator: Box::new(ConvergenceTerminator::new(&options)),
     };
 
@@ -222,3 +218,32 @@ fn qp(_download_cases: &(), case_name: &str, solver_type: QPSolverType) {
 
     assert_eq!(status.unwrap(), crate::Status::Optimal);
 }
+
+/// Variables and constraints (as in the CUTEst classification), constraint
+/// nonzeros, slack columns and Hessian nonzeros of a converted model.
+type ModelStatistics = (usize, usize, usize, usize, usize);
+
+#[rstest]
+#[case("QPTEST", (2, 2, 4, 2, 3))]
+#[case("EXDATA", (3000, 3001, 7500, 3000, 1125750))]
+fn golden_statistics(
+    _download_cases: &(),
+    #[case] case_name: &str,
+    #[case] statistics: ModelStatistics,
+) {
+    let (qp, transformation) = data_loaders::sif::maros_mezaros::read_case_with_transformation::<
+        QuadraticProgram,
+    >(case_name)
+    .unwrap();
+    let n_slack = transformation.get_slacks().len();
+    assert_eq!(
+        (
+            transformation.get_col_names().len(),
+            transformation.get_row_names().len(),
+            qp.get_constraint_matrix().compute_nnz() - n_slack,
+            n_slack,
+            qp.get_quadratic_objective().compute_nnz(),
+        ),
+        statistics
+    );
+}
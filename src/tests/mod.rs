@@ -1,2 +1,5 @@
+mod cross_validation;
 mod maros_mezaros;
 mod netlib;
+#[cfg(feature = "tracing")]
+mod profiling;
@@ -19,7 +19,7 @@ use crate::{
     E, SolverHooks, SolverOptions, SolverState,
     callback::ConvergenceOutput,
     data_loaders,
-    interface::sif::TryFromSIF,
+    interface::sif::{ObjectiveSense, SifTransformation},
     lp::{LPSolverType, LinearProgram},
     qp::{QPSolverType, QuadraticProgram},
     terminators::ConvergenceTerminator,
@@ -144,8 +144,7 @@ fn lp(
     )]
     solver_type: LPSolverType,
 ) {
-    let lp = LinearProgram::try_from_sif(&data_loaders::sif::netlib::get_case(case_name).unwrap())
-        .unwrap();
+    let lp = data_loaders::sif::netlib::read_case::<LinearProgram>(case_name).unwrap();
 
     let mut state = SolverState::new(
         Col::ones(lp.get_n_vars()),
@@ -175,7 +174,7 @@ fn lp(
     let options = SolverOptions::new();
 
     let mut properties = SolverHooks {
-        callback: Box::new(ConvergenceOutput::new()),
+        callback: Box::new(ConvergenceOutput::new(&options)),
         terminator: Box::new(ConvergenceTerminator::new(&options)),
     };
 
@@ -198,9 +197,7 @@ fn qp(
     )]
     solver_type: QPSolverType,
 ) {
-    let qp =
-        QuadraticProgram::try_from_sif(&data_loaders::sif::netlib::get_case(case_name).unwrap())
-            .unwrap();
+    let qp = data_loaders::sif::netlib::read_case::<QuadraticProgram>(case_name).unwrap();
 
     let mut state = SolverState::new(
         Col::ones(qp.get_n_vars()),
@@ -230,7 +227,7 @@ fn qp(
     let options = SolverOptions::new();
 
     let mut properties = SolverHooks {
-        callback: Box::new(ConvergenceOutput::new()),
+        callback: Box::new(ConvergenceOutput::new(&options)),
         terminator: Box::new(ConvergenceTerminator::new(&options)),
     };
 
@@ -242,3 +239,137 @@ fn qp(
 
     assert_eq!(status.unwrap(), crate::Status::Optimal);
 }
+
+/// Reference dual values of afiro at its optimum `-464.7531`.
+const AFIRO_ROW_DUALS: [(&str, E); 18] = [
+    ("R09", -0.628571),
+    ("R19", -0.942857),
+    ("X05", -0.344771),
+    ("X18", -1.437592),
+    ("X19", -1.446757),
+    ("X20", -1.461877),
+    ("X21", -0.228571),
+    ("X27", -0.874343),
+    ("X41", -1.388396),
+    ("X42", -1.412049),
+    ("X43", -1.348558),
+    ("X44", -0.342857),
+    ("X45", -0.900945),
+    ("X46", -0.628571),
+    ("X48", -0.942857),
+    ("R10", 0.),
+    ("X17", 0.),
+    ("X50", 0.),
+];
+
+/// Reduced costs of the nonbasic afiro columns at the optimum.
+const AFIRO_REDUCED_COSTS: [(&str, E); 6] = [
+    ("X07", 1.437592),
+    ("X10", 2.129834),
+    ("X25", 0.041912),
+    ("X32", 1.973970),
+    ("X39", 10.),
+    ("X01", 0.),
+];
+
+#[rstest]
+fn afiro_duals(_download_cases: &()) {
+    let (lp, transformation) =
+        data_loaders::sif::netlib::read_case_with_transformation::<LinearProgram>("afiro").unwrap();
+
+    // Start from the middle of the bounds, as the lower bounds are finite
+    let (l, u) = (lp.get_lower_bounds(), lp.get_upper_bounds());
+    let mut state = SolverState::new(
+        Col::from_fn(lp.get_n_vars(), |j| {
+            if u[j].is_finite() {
+                (l[j] + u[j]) / 2.
+            } else {
+                l[j] + 1.
+            }
+        }),
+        Col::ones(lp.get_n_cons()),
+        Col::ones(lp.get_n_vars()),
+        Col::from_fn(lp.get_n_vars(), |j| if u[j].is_finite() { -1. } else { 0. }),
+    );
+    let options = SolverOptions::new();
+    let mut hooks = SolverHooks {
+        callback: Box::new(crate::callback::NoOpCallback::new()),
+        terminator: Box::new(crate::terminators::ComplementarityTerminator::new(&options)),
+    };
+    let status = lp
+        .solver_builder()
+        .with_solver(LPSolverType::MpcSimplicialCholesky)
+        .build()
+        .unwrap()
+        .solve(&mut state, &mut hooks)
+        .unwrap();
+    assert_eq!(status, crate::Status::Optimal);
+    assert!((lp.get_objective_value(state.get_primal()) + 464.7531).abs() < 1e-4);
+
+    let solution = transformation.recover(lp.get_constraint_matrix(), &state);
+    let (rows, cols) = (
+        transformation.get_row_names(),
+        transformation.get_col_names(),
+    );
+    assert_eq!(solution.get_row_duals().nrows(), 27);
+    assert_eq!(solution.get_reduced_costs().nrows(), 32);
+    for (name, dual) in AFIRO_ROW_DUALS {
+        let i = rows.iter().position(|row| row == name).unwrap();
+        assert!((solution.get_row_duals()[i] - dual).abs() < 1e-5, "{name}");
+    }
+    for (name, cost) in AFIRO_REDUCED_COSTS {
+        let j = cols.iter().position(|col| col == name).unwrap();
+        assert!(
+            (solution.get_reduced_costs()[j] - cost).abs() < 1e-5,
+            "{name}"
+        );
+    }
+    // Equality rows keep their right-hand side
+    let i = rows.iter().position(|row| row == "R23").unwrap();
+    assert!((solution.get_row_activities()[i] - 44.).abs() < 1e-6);
+}
+
+/// Reference statistics of a converted model: variables and constraints (as in
+/// the CUTEst classification, which counts the objective row of 25fv47),
+/// constraint nonzeros, slack columns, ranged rows and objective constant.
+type ModelStatistics = (usize, usize, usize, usize, usize, E);
+
+fn model_statistics(lp: &LinearProgram, transformation: &SifTransformation) -> ModelStatistics {
+    let n = transformation.get_col_names().len();
+    let n_slack = transformation.get_slacks().len();
+    // Fixed variables, such as those of nesm, are not columns of the program
+    let fixed = transformation.get_fixed_columns();
+    (
+        n + fixed.len(),
+        transformation.get_row_names().len(),
+        lp.get_constraint_matrix().compute_nnz() - n_slack
+            + fixed.iter().map(|f| f.get_entries().len()).sum::<usize>(),
+        n_slack,
+        (n..n + n_slack)
+            .filter(|&j| lp.get_upper_bounds()[j].is_finite())
+            .count(),
+        transformation.get_objective_offset(),
+    )
+}
+
+#[rstest]
+#[case("25fv47", (1571, 821, 10400, 305, 0, 0.))]
+#[case("afiro", (32, 27, 83, 19, 0, 0.))]
+#[case("blend", (83, 74, 491, 31, 0, 0.))]
+#[case("boeing1", (384, 351, 3485, 342, 89, 0.))]
+#[case("boeing2", (143, 166, 1196, 162, 19, 0.))]
+#[case("dfl001", (12230, 6071, 35632, 0, 0, 0.))]
+#[case("nesm", (2923, 662, 13288, 182, 88, 0.))]
+#[case("seba", (1028, 515, 4352, 8, 7, 0.))]
+#[case("sierra", (2036, 1227, 7302, 699, 0, 0.))]
+fn golden_statistics(
+    _download_cases: &(),
+    #[case] case_name: &str,
+    #[case] statistics: ModelStatistics,
+) {
+    let (lp, transformation) =
+        data_loaders::sif::netlib::read_case_with_transformation::<LinearProgram>(case_name)
+            .unwrap();
+    assert_eq!(model_statistics(&lp, &transformation), statistics);
+    assert_eq!(transformation.get_sense(), ObjectiveSense::Minimize);
+}
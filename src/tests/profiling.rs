@@ -0,0 +1,87 @@
+//! Spans of the hot paths of the solvers, recorded with the `tracing` feature.
+
+use std::sync::{Arc, Mutex};
+
+use faer::{
+    col,
+    sparse::{SparseColMat, Triplet},
+};
+use tracing::{
+    Event, Metadata, Subscriber,
+    span::{Attributes, Id, Record},
+};
+
+use crate::{
+    SolverHooks, SolverState, Status,
+    lp::{LPSolverType, LinearProgram},
+};
+
+/// Records the names of the spans that are created.
+#[derive(Clone, Default)]
+struct SpanRecorder {
+    names: Arc<Mutex<Vec<&'static str>>>,
+}
+
+impl Subscriber for SpanRecorder {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut names = self.names.lock().unwrap();
+        names.push(span.metadata().name());
+        Id::from_u64(names.len() as u64)
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, _event: &Event<'_>) {}
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+#[test]
+fn test_solver_spans() {
+    let a = SparseColMat::try_new_from_triplets(
+        1,
+        2,
+        &[Triplet::new(0, 0, 1.), Triplet::new(0, 1, 1.)],
+    )
+    .unwrap();
+    let lp = LinearProgram::new(
+        col![1., 2.],
+        a,
+        col![1.],
+        col![0., 0.],
+        col![f64::INFINITY, f64::INFINITY],
+    );
+
+    let recorder = SpanRecorder::default();
+    let status = tracing::subscriber::with_default(recorder.clone(), || {
+        let mut state = SolverState::new_interior(lp.get_lower_bounds(), lp.get_upper_bounds(), 1);
+        lp.solver_builder()
+            .with_solver(LPSolverType::MpcSimplicialCholesky)
+            .build()
+            .unwrap()
+            .solve(&mut state, &mut SolverHooks::silent())
+            .unwrap()
+    });
+    assert_eq!(status, Status::Optimal);
+
+    let names = recorder.names.lock().unwrap();
+    for name in [
+        "iteration",
+        "assemble",
+        "analyze",
+        "factorize",
+        "triangular_solve",
+        "line_search",
+        "residual",
+    ] {
+        assert!(names.contains(&name), "no span {name} in {names:?}");
+    }
+}
@@ -1 +1,2 @@
 pub mod io;
+pub mod random;
@@ -0,0 +1,81 @@
+//! Reproducible random number generation.
+//!
+//! Every randomized component draws from a generator seeded by
+//! [`SeedSequence`], which derives an independent seed for each named stream
+//! from the crate-wide `random_seed` option. Two runs with the same options
+//! therefore make the same random choices, and adding a randomized component
+//! does not change the streams of the others.
+
+use faer::rand::{SeedableRng, rngs::StdRng};
+use macros::{explicit_options, use_option};
+
+use crate::SolverOptions;
+
+/// Source of the seeds of the named random streams of a run.
+#[explicit_options(name = SolverOptions)]
+#[use_option(name = "random_seed", type_ = u64, default = "0", description = "Seed from which all randomized components derive their random number generators.")]
+#[derive(Clone)]
+pub struct SeedSequence {}
+
+impl SeedSequence {
+    pub fn new(options: &SolverOptions) -> Self {
+        Self {
+            options: options.into(),
+        }
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.options.random_seed
+    }
+
+    /// Seed of the stream `stream`, e.g. the name of the component drawing from it.
+    pub fn get_stream_seed(&self, stream: &str) -> u64 {
+        // FNV-1a is stable across platforms and compiler versions, unlike `DefaultHasher`
+        let hash = stream.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |h, b| {
+            (h ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+        });
+        splitmix64(self.options.random_seed ^ splitmix64(hash))
+    }
+
+    /// Generator of the stream `stream`.
+    pub fn get_rng(&self, stream: &str) -> StdRng {
+        StdRng::seed_from_u64(self.get_stream_seed(stream))
+    }
+}
+
+/// Finalizer of the SplitMix64 generator, which spreads nearby seeds apart.
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::rand::Rng;
+    use rstest::rstest;
+
+    use super::*;
+
+    fn draws(seed: u64, stream: &str) -> Vec<u64> {
+        let mut options = SolverOptions::new();
+        options.set_option("random_seed", seed).unwrap();
+        let mut rng = SeedSequence::new(&options).get_rng(stream);
+        (0..4).map(|_| rng.random()).collect()
+    }
+
+    #[rstest]
+    #[case(0, "sddp")]
+    #[case(42, "perturbation")]
+    fn test_reproducible(#[case] seed: u64, #[case] stream: &str) {
+        assert_eq!(draws(seed, stream), draws(seed, stream));
+    }
+
+    #[test]
+    fn test_independent_streams() {
+        assert_ne!(draws(0, "sddp"), draws(0, "perturbation"));
+        assert_ne!(draws(0, "sddp"), draws(1, "sddp"));
+        assert_eq!(SeedSequence::new(&SolverOptions::new()).get_seed(), 0);
+    }
+}
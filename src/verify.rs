@@ -0,0 +1,325 @@
+//! Independent verification of primal-dual solutions.
+//!
+//! [`check_solution`] recomputes the KKT conditions of a program directly from
+//! the problem data, without relying on the residuals maintained by the solver,
+//! and summarizes the result in a serializable [`CertificateReport`].
+//! [`check_solution_with_overrides`] checks the residuals of individual rows and
+//! variables against their own [`ToleranceOverrides`] instead.
+//!
+//! The sign convention matches the solvers in this crate: the Lagrangian
+//! stationarity condition is `∇f(x) = A^T y + z_l + z_u` with `z_l >= 0` and
+//! `z_u <= 0`.
+
+use faer::{Col, sparse::SparseColMat};
+use serde::Serialize;
+
+use crate::{
+    E, I, SolverState, lp::LinearProgram, qp::QuadraticProgram, terminators::ToleranceOverrides,
+};
+
+/// Programs with linear constraints `A x = b, l <= x <= u` whose solutions can be verified.
+pub trait VerifiableProgram {
+    fn get_constraint_matrix(&self) -> &SparseColMat<I, E>;
+
+    fn get_rhs(&self) -> &Col<E>;
+
+    fn get_lower_bounds(&self) -> &Col<E>;
+
+    fn get_upper_bounds(&self) -> &Col<E>;
+
+    /// Objective value at `x`.
+    fn objective(&self, x: &Col<E>) -> E;
+
+    /// Gradient of the objective at `x`.
+    fn gradient(&self, x: &Col<E>) -> Col<E>;
+
+    /// Value of the Lagrangian dual function, excluding bound terms, at the given point.
+    ///
+    /// The bound terms `l^T z_l + u^T z_u` are added by [`check_solution`] over the
+    /// finite bounds only.
+    fn dual_objective(&self, x: &Col<E>, y: &Col<E>) -> E;
+}
+
+impl VerifiableProgram for LinearProgram {
+    fn get_constraint_matrix(&self) -> &SparseColMat<I, E> {
+        self.get_constraint_matrix()
+    }
+
+    fn get_rhs(&self) -> &Col<E> {
+        self.get_rhs()
+    }
+
+    fn get_lower_bounds(&self) -> &Col<E> {
+        self.get_lower_bounds()
+    }
+
+    fn get_upper_bounds(&self) -> &Col<E> {
+        self.get_upper_bounds()
+    }
+
+    fn objective(&self, x: &Col<E>) -> E {
+        self.get_objective_value(x)
+    }
+
+    fn gradient(&self, _x: &Col<E>) -> Col<E> {
+        self.get_objective().clone()
+    }
+
+    fn dual_objective(&self, _x: &Col<E>, y: &Col<E>) -> E {
+        self.get_rhs().transpose() * y
+    }
+}
+
+impl VerifiableProgram for QuadraticProgram {
+    fn get_constraint_matrix(&self) -> &SparseColMat<I, E> {
+        self.get_constraint_matrix()
+    }
+
+    fn get_rhs(&self) -> &Col<E> {
+        self.get_rhs()
+    }
+
+    fn get_lower_bounds(&self) -> &Col<E> {
+        self.get_lower_bounds()
+    }
+
+    fn get_upper_bounds(&self) -> &Col<E> {
+        self.get_upper_bounds()
+    }
+
+    fn objective(&self, x: &Col<E>) -> E {
+        0.5 * x.transpose() * (self.get_quadratic_objective() * x)
+            + self.get_linear_objective().transpose() * x
+    }
+
+    fn gradient(&self, x: &Col<E>) -> Col<E> {
+        self.get_quadratic_objective() * x + self.get_linear_objective()
+    }
+
+    fn dual_objective(&self, x: &Col<E>, y: &Col<E>) -> E {
+        // Wolfe dual: b^T y - 1/2 x^T Q x
+        self.get_rhs().transpose() * y - 0.5 * x.transpose() * (self.get_quadratic_objective() * x)
+    }
+}
+
+/// Outcome of a single KKT condition check.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct CertificateCheck {
+    /// Largest absolute violation.
+    pub absolute: E,
+    /// Violation scaled by the magnitude of the relevant problem data.
+    pub relative: E,
+    /// Whether the relative violation is within the tolerance.
+    pub passed: bool,
+}
+
+impl CertificateCheck {
+    pub(crate) fn new(absolute: E, scale: E, tol: E) -> Self {
+        let relative = absolute / (1. + scale);
+        Self {
+            absolute,
+            relative,
+            passed: relative <= tol,
+        }
+    }
+}
+
+/// Machine-readable summary of the optimality conditions at a candidate solution.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CertificateReport {
+    pub tolerance: E,
+    pub primal_objective: E,
+    pub dual_objective: E,
+    /// `A x = b`, scaled by `‖b‖∞`.
+    pub primal_feasibility: CertificateCheck,
+    /// `l <= x <= u`, scaled by the largest finite bound.
+    pub bound_feasibility: CertificateCheck,
+    /// `∇f(x) - A^T y - z_l - z_u = 0`, scaled by `‖∇f(x)‖∞`.
+    pub dual_feasibility: CertificateCheck,
+    /// `z_l >= 0`, `z_u <= 0`, and zero multipliers on infinite bounds.
+    pub dual_sign: CertificateCheck,
+    /// `z_l (x - l) = 0` and `z_u (x - u) = 0`, scaled by the primal objective.
+    pub complementarity: CertificateCheck,
+    /// Difference between the primal and dual objectives, scaled by their magnitudes.
+    pub duality_gap: CertificateCheck,
+    /// Rows whose primal residual exceeds their tolerance override.
+    pub violated_rows: Vec<usize>,
+    /// Variables whose dual residual exceeds their tolerance override.
+    pub violated_variables: Vec<usize>,
+}
+
+impl CertificateReport {
+    /// Returns `true` if every check passed.
+    pub fn is_optimal(&self) -> bool {
+        [
+            self.primal_feasibility,
+            self.bound_feasibility,
+            self.dual_feasibility,
+            self.dual_sign,
+            self.complementarity,
+            self.duality_gap,
+        ]
+        .iter()
+        .all(|check| check.passed)
+    }
+}
+
+/// Verifies the primal-dual point in `solution` against the KKT conditions of `program`.
+pub fn check_solution<P: VerifiableProgram + ?Sized>(
+    program: &P,
+    solution: &SolverState,
+    tol: E,
+) -> CertificateReport {
+    check_solution_with_overrides(program, solution, tol, &ToleranceOverrides::new())
+}
+
+/// Verifies `solution` like [`check_solution`], except that the primal and
+/// dual feasibility pass if every row and variable with an override is within
+/// its absolute tolerance, and every other one within `tol`. The offending
+/// rows and variables are listed in the report.
+pub fn check_solution_with_overrides<P: VerifiableProgram + ?Sized>(
+    program: &P,
+    solution: &SolverState,
+    tol: E,
+    overrides: &ToleranceOverrides,
+) -> CertificateReport {
+    let (x, y, z_l, z_u) = (&solution.x, &solution.y, &solution.z_l, &solution.z_u);
+    let (l, u) = (program.get_lower_bounds(), program.get_upper_bounds());
+    let b = program.get_rhs();
+
+    let primal_objective = program.objective(x);
+    let gradient = program.gradient(x);
+
+    let primal_residual = program.get_constraint_matrix() * x - b;
+    let dual_residual = &gradient - program.get_constraint_matrix().transpose() * y - z_l - z_u;
+
+    let mut bound_violation = E::from(0.);
+    let mut bound_scale = E::from(0.);
+    let mut sign_violation = E::from(0.);
+    let mut complementarity = E::from(0.);
+    let mut bound_dual_objective = E::from(0.);
+
+    for j in 0..x.nrows() {
+        if l[j].is_finite() {
+            bound_violation = bound_violation.max(l[j] - x[j]);
+            bound_scale = bound_scale.max(l[j].abs());
+            sign_violation = sign_violation.max(-z_l[j]);
+            complementarity = complementarity.max((z_l[j] * (x[j] - l[j])).abs());
+            bound_dual_objective += l[j] * z_l[j];
+        } else {
+            sign_violation = sign_violation.max(z_l[j].abs());
+        }
+
+        if u[j].is_finite() {
+            bound_violation = bound_violation.max(x[j] - u[j]);
+            bound_scale = bound_scale.max(u[j].abs());
+            sign_violation = sign_violation.max(z_u[j]);
+            complementarity = complementarity.max((z_u[j] * (x[j] - u[j])).abs());
+            bound_dual_objective += u[j] * z_u[j];
+        } else {
+            sign_violation = sign_violation.max(z_u[j].abs());
+        }
+    }
+
+    let dual_objective = program.dual_objective(x, y) + bound_dual_objective;
+
+    let mut primal_feasibility =
+        CertificateCheck::new(primal_residual.norm_max(), b.norm_max(), tol);
+    let mut dual_feasibility =
+        CertificateCheck::new(dual_residual.norm_max(), gradient.norm_max(), tol);
+    let mut violated_rows = Vec::new();
+    let mut violated_variables = Vec::new();
+    if let Some(rows) = overrides.get_rows() {
+        violated_rows = ToleranceOverrides::violations(primal_residual.as_ref(), rows, tol);
+        primal_feasibility.passed = violated_rows.is_empty();
+    }
+    if let Some(variables) = overrides.get_variables() {
+        violated_variables = ToleranceOverrides::violations(dual_residual.as_ref(), variables, tol);
+        dual_feasibility.passed = violated_variables.is_empty();
+    }
+
+    CertificateReport {
+        tolerance: tol,
+        primal_objective,
+        dual_objective,
+        primal_feasibility,
+        bound_feasibility: CertificateCheck::new(bound_violation, bound_scale, tol),
+        dual_feasibility,
+        dual_sign: CertificateCheck::new(sign_violation, E::from(0.), tol),
+        complementarity: CertificateCheck::new(complementarity, primal_objective.abs(), tol),
+        duality_gap: CertificateCheck::new(
+            (primal_objective - dual_objective).abs(),
+            primal_objective.abs() + dual_objective.abs(),
+            tol,
+        ),
+        violated_rows,
+        violated_variables,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use faer::{col, sparse::Triplet};
+
+    use super::*;
+
+    /// min x0 + 2 x1  s.t.  x0 + x1 = 1,  x >= 0, with optimum x = (1, 0), y = 1, z_l = (0, 1).
+    fn build_lp() -> LinearProgram {
+        let a = SparseColMat::try_new_from_triplets(
+            1,
+            2,
+            &[Triplet::new(0, 0, 1.), Triplet::new(0, 1, 1.)],
+        )
+        .unwrap();
+        LinearProgram::new(
+            col![1., 2.],
+            a,
+            col![1.],
+            col![0., 0.],
+            col![E::INFINITY, E::INFINITY],
+        )
+    }
+
+    #[test]
+    fn test_check_optimal_solution() {
+        let lp = build_lp();
+        let state = SolverState::new(col![1., 0.], col![1.], col![0., 1.], col![0., 0.]);
+
+        let report = check_solution(&lp, &state, 1e-9);
+
+        assert!(report.is_optimal());
+        assert_eq!(report.primal_objective, 1.);
+        assert_eq!(report.dual_objective, 1.);
+    }
+
+    #[test]
+    fn test_check_suboptimal_solution() {
+        let lp = build_lp();
+        let state = SolverState::new(col![0., 1.], col![1.], col![0., 1.], col![0., 0.]);
+
+        let report = check_solution(&lp, &state, 1e-9);
+
+        assert!(!report.is_optimal());
+        assert!(report.primal_feasibility.passed);
+        assert!(!report.complementarity.passed);
+        assert!(!report.duality_gap.passed);
+    }
+
+    #[test]
+    fn test_check_with_overrides() {
+        let lp = build_lp();
+        let state = SolverState::new(col![1. + 1e-4, 0.], col![1.], col![0., 1.], col![0., 0.]);
+        assert!(!check_solution(&lp, &state, 1e-9).primal_feasibility.passed);
+
+        let overrides = ToleranceOverrides::new().with_rows(col![1e-3]);
+        let report = check_solution_with_overrides(&lp, &state, 1e-9, &overrides);
+        assert!(report.primal_feasibility.passed);
+        assert!(report.violated_rows.is_empty());
+
+        let overrides = overrides.with_variables(col![0., 0.]);
+        let state = SolverState::new(col![1., 0.], col![1.], col![1e-6, 1.], col![0., 0.]);
+        let report = check_solution_with_overrides(&lp, &state, 1e-3, &overrides);
+        assert!(!report.dual_feasibility.passed);
+        assert_eq!(report.violated_variables, vec![0]);
+    }
+}